@@ -2,27 +2,45 @@
 //!
 //! # Modules
 //!
+//! - [`address_index`](address_index) - Indexes the transactions of the blocks by the scripts they pay to or spend from.
 //! - [`block`](block) - Defines the structure and operations related to blocks.
+//! - [`block_filter`](block_filter) - Computes the compact filters of the blocks defined by BIP158.
 //! - [`block_header`](block_header) - Provides utilities for working with block headers.
+//! - [`block_store`](block_store) - Keeps the blocks in flat files, with the most recent ones in memory.
+//! - [`block_undo`](block_undo) - Defines the changes a block makes to the UTXO set, to revert them.
 //! - [`blockchain`](blockchain) - Implements the main blockchain functionality.
 //! - [`coinbase`](coinbase) - Defines the structure of a coinbase transaction.
+//! - [`filter_index`](filter_index) - Keeps the compact filters of the blocks and the headers of the filters.
 //! - [`merkle_tree`](merkle_tree) - Implements a Merkle tree for verifying block transactions.
 //! - [`outpoint`](outpoint) - Defines the structure of a transaction outpoint.
+//! - [`prune_target`](prune_target) - Defines how many blocks a pruned node keeps.
+//! - [`script_type`](script_type) - Classifies the standard public key scripts.
 //! - [`transaction`](transaction) - Defines the structure of a transaction.
 //! - [`tx_in`](tx_in) - Defines the structure of a transaction input.
 //! - [`tx_in_coinbase`](tx_in_coinbase) - Defines the structure of a coinbase transaction input.
+//! - [`tx_index`](tx_index) - Indexes the transactions of the blocks by their id.
 //! - [`tx_out`](tx_out) - Defines the structure of a transaction output.
+//! - [`undo_store`](undo_store) - Keeps the undo data of the blocks in a file.
 //! - [`utxo`](utxo) - Implements the unspent transaction output (UTXO) model.
 
+pub mod address_index;
 pub mod block;
+pub mod block_filter;
 pub mod block_header;
+pub mod block_store;
+pub mod block_undo;
 pub mod blockchain;
 pub mod coinbase;
+pub mod filter_index;
 pub mod mempool;
 pub mod outpoint;
+pub mod prune_target;
 pub mod script;
+pub mod script_type;
 pub mod transaction;
 pub mod tx_in;
 pub mod tx_in_coinbase;
+pub mod tx_index;
 pub mod tx_out;
+pub mod undo_store;
 pub mod utxo;
@@ -0,0 +1,186 @@
+use std::collections::HashMap;
+
+use bitcoin_hashes::{hash160, sha256, Hash};
+
+use super::{
+    block::Block,
+    script_type::{get_pubkey_hash, get_pushes, get_script_hash},
+    tx_in::TxIn,
+};
+
+/// Returns the key a public key script is indexed with: the hash it is locked to, or its sha256 if it isn't locked to
+/// a hash. The P2PKH and P2WPKH scripts of a key share the same one.
+pub fn get_index_key(pk_script: &[u8]) -> Vec<u8> {
+    match get_pubkey_hash(pk_script).or_else(|| get_script_hash(pk_script)) {
+        Some(hash) => hash.to_vec(),
+        None => sha256::Hash::hash(pk_script).to_byte_array().to_vec(),
+    }
+}
+
+/// Returns the keys of the scripts an input may spend an output of, from the public key or the script it reveals in
+/// the last push of its signature script or the last item of its witness.
+fn get_spent_keys(txin: &TxIn) -> Vec<Vec<u8>> {
+    let revealed = get_pushes(txin.get_signature_script())
+        .and_then(|pushes| pushes.last().map(|push| push.to_vec()))
+        .into_iter()
+        .chain(txin.get_witness().last().cloned());
+
+    revealed
+        .flat_map(|item| {
+            [
+                hash160::Hash::hash(&item).to_byte_array().to_vec(),
+                sha256::Hash::hash(&item).to_byte_array().to_vec(),
+            ]
+        })
+        .collect()
+}
+
+/// Indexes the transactions of the blocks by the scripts they pay to or spend from, so the transactions of an address
+/// are found without scanning the chain.
+///
+/// Each key, see `get_index_key`, maps to the hash of the block and the position in its transaction list of each
+/// transaction with an output locked to it or an input that may spend one. The inputs are indexed by what they reveal,
+/// so a key can list transactions that don't belong to the address, and the caller checks each one it gets.
+#[derive(Debug, Default)]
+pub struct AddressIndex {
+    entries: HashMap<Vec<u8>, Vec<(Vec<u8>, usize)>>,
+    block_keys: HashMap<Vec<u8>, Vec<Vec<u8>>>,
+}
+
+impl AddressIndex {
+    pub fn new() -> AddressIndex {
+        AddressIndex::default()
+    }
+
+    /// Indexes the transactions of a block, replacing the entries of the block if it was indexed before.
+    pub fn connect_block(&mut self, block: &Block) {
+        let block_hash = block.get_header().get_header();
+        self.disconnect_block(&block_hash);
+
+        let mut keys = vec![];
+        for (position, transaction) in block.get_txn_list().iter().enumerate() {
+            let mut tx_keys: Vec<Vec<u8>> = transaction
+                .get_tx_out_list()
+                .iter()
+                .map(|tx_out| get_index_key(&tx_out.get_pk_script()))
+                .chain(transaction.get_tx_in_list().iter().flat_map(get_spent_keys))
+                .collect();
+            tx_keys.sort();
+            tx_keys.dedup();
+
+            for key in tx_keys {
+                self.entries
+                    .entry(key.clone())
+                    .or_default()
+                    .push((block_hash.clone(), position));
+                keys.push(key);
+            }
+        }
+        keys.sort();
+        keys.dedup();
+        self.block_keys.insert(block_hash, keys);
+    }
+
+    /// Removes the entries of a block from the index.
+    pub fn disconnect_block(&mut self, block_hash: &[u8]) {
+        let keys = match self.block_keys.remove(block_hash) {
+            Some(keys) => keys,
+            None => return,
+        };
+        for key in keys {
+            if let Some(entries) = self.entries.get_mut(&key) {
+                entries.retain(|(hash, _)| hash != block_hash);
+                if entries.is_empty() {
+                    self.entries.remove(&key);
+                }
+            }
+        }
+    }
+
+    /// Returns the transactions that may belong to an address.
+    ///
+    /// # Arguments
+    ///
+    /// * `pk_script` - The public key script of the address.
+    /// * `public_key` - The public key of the address, empty if it is locked to a script.
+    ///
+    /// # Returns
+    ///
+    /// The hash of the block and the position in its transaction list of each transaction, without repetitions.
+    pub fn get_entries(&self, pk_script: &[u8], public_key: &[u8]) -> Vec<(Vec<u8>, usize)> {
+        let mut keys = vec![get_index_key(pk_script)];
+        if !public_key.is_empty() {
+            keys.push(hash160::Hash::hash(public_key).to_byte_array().to_vec());
+        }
+
+        let mut entries: Vec<(Vec<u8>, usize)> = keys
+            .iter()
+            .filter_map(|key| self.entries.get(key))
+            .flatten()
+            .cloned()
+            .collect();
+        entries.sort();
+        entries.dedup();
+        entries
+    }
+}
+
+#[cfg(test)]
+mod address_index_test {
+    use super::*;
+    use crate::{
+        block_mod::{block_header::BlockHeader, transaction::Transaction, tx_out::TxOut},
+        messages::message_error::MessageError,
+    };
+
+    /// Creates a block with an empty coinbase and the given transactions.
+    fn test_block(transactions: &[Transaction], time: u32) -> Result<Block, MessageError> {
+        let header = BlockHeader::new(1, vec![0; 32], vec![0; 32], time, 0, 0);
+        let mut bytes = header.as_bytes();
+        bytes.push(transactions.len() as u8 + 1);
+        bytes.extend([1, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        for transaction in transactions {
+            bytes.extend(transaction.as_bytes());
+        }
+        Block::from_bytes(&mut &bytes[..])
+    }
+
+    #[test]
+    fn test_outputs_and_spends_are_indexed() -> Result<(), MessageError> {
+        let public_key = vec![2; 33];
+        let pubkey_hash = hash160::Hash::hash(&public_key).to_byte_array();
+        let p2wpkh = [&[0, 20], &pubkey_hash[..]].concat();
+        let received = Transaction::new(
+            2,
+            vec![TxIn::new(vec![1; 32], 0, vec![], 0)],
+            vec![TxOut::new(1000, p2wpkh.clone())],
+            0,
+        );
+        let mut spent_input = TxIn::new(received.get_id(), 0, vec![], 0);
+        spent_input.set_witness(vec![vec![3; 71], public_key.clone()]);
+        let spent = Transaction::new(
+            2,
+            vec![TxIn::new(vec![1; 32], 1, vec![], 0), spent_input],
+            vec![TxOut::new(900, vec![0x51])],
+            0,
+        );
+        let block = test_block(&[received, spent], 1)?;
+        let block_hash = block.get_header().get_header();
+
+        let mut index = AddressIndex::new();
+        index.connect_block(&block);
+        assert_eq!(
+            index.get_entries(&p2wpkh, &public_key),
+            vec![(block_hash.clone(), 0), (block_hash.clone(), 1)]
+        );
+        assert_eq!(
+            index.get_entries(&[0x51], &[]),
+            vec![(block_hash.clone(), 1)]
+        );
+
+        index.disconnect_block(&block_hash);
+        assert!(index.get_entries(&p2wpkh, &public_key).is_empty());
+        assert!(index.entries.is_empty());
+        Ok(())
+    }
+}
@@ -78,6 +78,11 @@ impl Block {
         txn_ids
     }
 
+    /// Returns the coinbase transaction of the block.
+    pub fn get_coinbase(&self) -> &Coinbase {
+        &self.coinbase
+    }
+
     /// Returns a vector with the transactions of the block.
     pub fn get_txn_list(&self) -> &Vec<Transaction> {
         &self.txn_list
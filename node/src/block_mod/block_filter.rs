@@ -0,0 +1,327 @@
+use bitcoin_hashes::{sha256d, siphash24, Hash};
+
+use crate::messages::{compact_size::CompactSizeUInt, message_error::MessageError};
+
+use super::block::Block;
+
+const FILTER_P: u8 = 19;
+const FILTER_M: u64 = 784931;
+const OP_RETURN: u8 = 0x6a;
+
+/// Writes bits to a byte vector, the most significant bit of each byte first.
+struct BitWriter {
+    bytes: Vec<u8>,
+    used_bits: u8,
+}
+
+impl BitWriter {
+    fn write_bit(&mut self, bit: bool) {
+        if self.used_bits == 0 {
+            self.bytes.push(0);
+        }
+        if bit {
+            if let Some(byte) = self.bytes.last_mut() {
+                *byte |= 0x80 >> self.used_bits;
+            }
+        }
+        self.used_bits = (self.used_bits + 1) % 8;
+    }
+
+    fn write_bits(&mut self, value: u64, count: u8) {
+        for position in (0..count).rev() {
+            self.write_bit((value >> position) & 1 == 1);
+        }
+    }
+}
+
+/// Reads the bits written by a `BitWriter`.
+struct BitReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn read_bit(&mut self) -> Option<bool> {
+        let byte = self.bytes.get(self.position / 8)?;
+        let bit = byte & (0x80 >> (self.position % 8)) != 0;
+        self.position += 1;
+        Some(bit)
+    }
+
+    fn read_bits(&mut self, count: u8) -> Option<u64> {
+        let mut value = 0;
+        for _ in 0..count {
+            value = (value << 1) | self.read_bit()? as u64;
+        }
+        Some(value)
+    }
+}
+
+/// Maps each element to a number in `[0, count * FILTER_M)` with the SipHash of the element, keyed by the first 16 bytes
+/// of the block hash, and returns the numbers sorted.
+///
+/// # Arguments
+///
+/// * `block_hash` - The hash of the block of the filter.
+/// * `elements` - The elements to map.
+/// * `count` - The number of elements of the filter.
+fn hash_elements(block_hash: &[u8], elements: &[Vec<u8>], count: u64) -> Vec<u64> {
+    let mut k0 = [0u8; 8];
+    let mut k1 = [0u8; 8];
+    k0.copy_from_slice(&block_hash[..8]);
+    k1.copy_from_slice(&block_hash[8..16]);
+    let (k0, k1) = (u64::from_le_bytes(k0), u64::from_le_bytes(k1));
+    let range = count as u128 * FILTER_M as u128;
+
+    let mut values: Vec<u64> = elements
+        .iter()
+        .map(|element| {
+            let hash = siphash24::Hash::hash_to_u64_with_keys(k0, k1, element);
+            ((hash as u128 * range) >> 64) as u64
+        })
+        .collect();
+    values.sort();
+    values
+}
+
+/// The basic compact filter of a block, as defined by BIP158.
+///
+/// The filter is a Golomb-Rice coded set of the scripts of the block: the public key scripts of its outputs, except the
+/// empty ones and the `OP_RETURN` ones, and the public key scripts of the outputs its inputs spend. A light client
+/// checks if the filter matches any of its scripts to decide if it has to download the block.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockFilter {
+    filter: Vec<u8>,
+}
+
+impl BlockFilter {
+    /// Computes the filter of a block.
+    ///
+    /// # Arguments
+    ///
+    /// * `block` - The block.
+    /// * `spent_pk_scripts` - The public key scripts of the outputs spent by the inputs of the block.
+    ///
+    /// # Returns
+    ///
+    /// The filter, or a `MessageError` if the coinbase of the block can't be read as a transaction.
+    pub fn new(block: &Block, spent_pk_scripts: &[Vec<u8>]) -> Result<BlockFilter, MessageError> {
+        let coinbase = block.get_coinbase().as_transaction()?;
+        let mut elements: Vec<Vec<u8>> = [&coinbase]
+            .into_iter()
+            .chain(block.get_txn_list())
+            .flat_map(|transaction| transaction.get_tx_out_list())
+            .map(|tx_out| tx_out.get_pk_script())
+            .filter(|pk_script| pk_script.first().is_some_and(|op| *op != OP_RETURN))
+            .chain(
+                spent_pk_scripts
+                    .iter()
+                    .filter(|pk_script| !pk_script.is_empty())
+                    .cloned(),
+            )
+            .collect();
+        elements.sort();
+        elements.dedup();
+
+        let block_hash = block.get_header().get_header();
+        let mut filter = CompactSizeUInt::from_number(elements.len() as u64).as_bytes();
+        let mut writer = BitWriter {
+            bytes: vec![],
+            used_bits: 0,
+        };
+        let mut last_value = 0;
+        for value in hash_elements(&block_hash, &elements, elements.len() as u64) {
+            let delta = value - last_value;
+            for _ in 0..(delta >> FILTER_P) {
+                writer.write_bit(true);
+            }
+            writer.write_bit(false);
+            writer.write_bits(delta, FILTER_P);
+            last_value = value;
+        }
+        filter.extend(writer.bytes);
+
+        Ok(BlockFilter { filter })
+    }
+
+    /// Creates a filter from its serialization, as it is sent in a `cfilter` message.
+    pub fn from_bytes(filter: Vec<u8>) -> BlockFilter {
+        BlockFilter { filter }
+    }
+
+    /// Returns the serialization of the filter: the number of elements as a compact size and the coded set.
+    pub fn as_bytes(&self) -> &Vec<u8> {
+        &self.filter
+    }
+
+    /// Returns the double sha256 of the filter.
+    pub fn get_hash(&self) -> Vec<u8> {
+        sha256d::Hash::hash(&self.filter).to_byte_array().to_vec()
+    }
+
+    /// Returns the header of the filter, which commits to the headers of the filters of all the previous blocks.
+    ///
+    /// # Arguments
+    ///
+    /// * `previous_header` - The header of the filter of the previous block, all zeros for the first one.
+    ///
+    /// # Returns
+    ///
+    /// The double sha256 of the hash of the filter followed by the previous header.
+    pub fn get_header(&self, previous_header: &[u8]) -> Vec<u8> {
+        let mut bytes = self.get_hash();
+        bytes.extend(previous_header);
+        sha256d::Hash::hash(&bytes).to_byte_array().to_vec()
+    }
+
+    /// Checks if any of the scripts is in the filter. As the set is probabilistic, a script that isn't in the block
+    /// matches with a probability of 1/784931.
+    ///
+    /// # Arguments
+    ///
+    /// * `block_hash` - The hash of the block of the filter.
+    /// * `pk_scripts` - The public key scripts to look for.
+    ///
+    /// # Returns
+    ///
+    /// `true` if some script matches, `false` if none does or the filter is malformed, as when its values overflow.
+    pub fn matches_any(&self, block_hash: &[u8], pk_scripts: &[Vec<u8>]) -> bool {
+        let mut stream = &self.filter[..];
+        let count = match CompactSizeUInt::from_bytes(&mut stream) {
+            Ok(count) => count.value(),
+            Err(_) => return false,
+        };
+        if count == 0 || pk_scripts.is_empty() {
+            return false;
+        }
+
+        let mut reader = BitReader {
+            bytes: stream,
+            position: 0,
+        };
+        let targets = hash_elements(block_hash, pk_scripts, count);
+
+        let mut value: u64 = 0;
+        let mut next_target = 0;
+        for _ in 0..count {
+            let mut quotient: u64 = 0;
+            loop {
+                match reader.read_bit() {
+                    Some(true) => quotient += 1,
+                    Some(false) => break,
+                    None => return false,
+                }
+            }
+            let remainder = match reader.read_bits(FILTER_P) {
+                Some(remainder) => remainder,
+                None => return false,
+            };
+            value = match quotient
+                .checked_mul(1 << FILTER_P)
+                .and_then(|delta| delta.checked_add(remainder))
+                .and_then(|delta| value.checked_add(delta))
+            {
+                Some(value) => value,
+                None => return false,
+            };
+
+            while next_target < targets.len() && targets[next_target] < value {
+                next_target += 1;
+            }
+            match targets.get(next_target) {
+                Some(target) if *target == value => return true,
+                Some(_) => {}
+                None => return false,
+            }
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod block_filter_test {
+    use super::*;
+    use crate::{
+        messages::read_from_bytes::{decode_hex, encode_hex},
+        network::bitcoin_network::BitcoinNetwork,
+    };
+
+    const GENESIS_COINBASE: &str = "01000000010000000000000000000000000000000000000000000000000000000000000000ffffffff4d04ffff001d0104455468652054696d65732030332f4a616e2f32303039204368616e63656c6c6f72206f6e206272696e6b206f66207365636f6e64206261696c6f757420666f722062616e6b73ffffffff0100f2052a01000000434104678afdb0fe5548271967f1a67130b7105cd6a828e03909a67962e0ea1f61deb649f6bc3f4cef38c4f35504e51ec112de5c384df7ba0b8d578a4c702b6bf11d5fac00000000";
+
+    #[test]
+    fn test_testnet_genesis_filter() -> Result<(), Box<dyn std::error::Error>> {
+        let mut bytes = BitcoinNetwork::Testnet.get_genesis_header().as_bytes();
+        bytes.push(1);
+        bytes.extend(decode_hex(GENESIS_COINBASE)?);
+        let block = Block::from_bytes(&mut &bytes[..]).map_err(|err| format!("{:?}", err))?;
+        let block_hash = block.get_header().get_header();
+        assert!(block.proof_of_inclusion());
+
+        let filter = BlockFilter::new(&block, &[]).map_err(|err| format!("{:?}", err))?;
+        assert_eq!(encode_hex(filter.as_bytes())?, "019dfca8");
+
+        let mut header = filter.get_header(&[0; 32]);
+        header.reverse();
+        assert_eq!(
+            encode_hex(&header)?,
+            "21584579b7eb08997773e5aeff3a7f932700042d0ed2a6129012b7d7ae81b750"
+        );
+
+        let pk_script = block
+            .get_coinbase()
+            .as_transaction()
+            .map_err(|err| format!("{:?}", err))?
+            .get_tx_out_list()[0]
+            .get_pk_script();
+        assert!(filter.matches_any(&block_hash, &[vec![0x51], pk_script]));
+        assert!(!filter.matches_any(&block_hash, &[vec![0x51]]));
+        Ok(())
+    }
+
+    const SPENDING_BLOCK_HEADER: &str = "0100000043497fd7f826957108f4a30fd9cec3aeba79972084e90ead01ea3309000000005d08f5742389902fbac2e5eddf84360988f21f19c796a650c106a5de9f9cb0a23ce6494dffff001d07000000";
+    const SPENDING_BLOCK_COINBASE: &str = "01000000010000000000000000000000000000000000000000000000000000000000000000ffffffff0403010203ffffffff0300f2052a010000001976a914111111111111111111111111111111111111111188ac0000000000000000066a04deadbeef00000000000000000000000000";
+    const SPENDING_BLOCK_TX: &str = "0100000002aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa0000000000ffffffffbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb0100000000ffffffff03e8030000000000001600142222222222222222222222222222222222222222d00700000000000000b80b0000000000001976a914111111111111111111111111111111111111111188ac00000000";
+
+    /// A block after the testnet genesis one whose coinbase pays to P2PKH, `OP_RETURN` and empty scripts, and whose
+    /// other transaction spends a P2SH and an empty script and pays to P2WPKH, empty and the same P2PKH scripts. The
+    /// expected filter and header were computed with an independent implementation of BIP158 that reproduces the
+    /// genesis vector.
+    #[test]
+    fn test_filter_with_spent_empty_and_op_return_scripts() -> Result<(), Box<dyn std::error::Error>>
+    {
+        let mut bytes = decode_hex(SPENDING_BLOCK_HEADER)?;
+        bytes.push(2);
+        bytes.extend(decode_hex(SPENDING_BLOCK_COINBASE)?);
+        bytes.extend(decode_hex(SPENDING_BLOCK_TX)?);
+        let block = Block::from_bytes(&mut &bytes[..]).map_err(|err| format!("{:?}", err))?;
+        let block_hash = block.get_header().get_header();
+        assert!(block.proof_of_inclusion());
+        let p2sh = decode_hex("a914333333333333333333333333333333333333333387")?;
+        let op_return = decode_hex("6a04deadbeef")?;
+
+        let filter = BlockFilter::new(&block, &[p2sh.clone(), vec![]])
+            .map_err(|err| format!("{:?}", err))?;
+        assert_eq!(encode_hex(filter.as_bytes())?, "034d21d9b08bcb5770");
+
+        let genesis_filter = BlockFilter::from_bytes(vec![0x01, 0x9d, 0xfc, 0xa8]);
+        let mut header = filter.get_header(&genesis_filter.get_header(&[0; 32]));
+        header.reverse();
+        assert_eq!(
+            encode_hex(&header)?,
+            "c8c545774cb083b069f8f3e694173092ac32a43dc5dcd8e876c7623d06120073"
+        );
+
+        assert!(filter.matches_any(&block_hash, &[p2sh]));
+        assert!(!filter.matches_any(&block_hash, &[op_return, vec![]]));
+
+        let without_spent = BlockFilter::new(&block, &[]).map_err(|err| format!("{:?}", err))?;
+        assert_eq!(encode_hex(without_spent.as_bytes())?, "02336be7960f");
+        Ok(())
+    }
+
+    #[test]
+    fn test_malformed_filter_does_not_match() {
+        let filter = BlockFilter::from_bytes(vec![0x02, 0xff, 0xff, 0xff]);
+        assert!(!filter.matches_any(&[0; 32], &[vec![0x51]]));
+    }
+}
@@ -109,6 +109,16 @@ impl BlockHeader {
             .to_vec()
     }
 
+    /// Returns the version of the block.
+    pub fn get_version(&self) -> i32 {
+        self.block_version
+    }
+
+    /// Returns the nonce that makes the hash of the header meet the target.
+    pub fn get_nonce(&self) -> u32 {
+        self.nonce
+    }
+
     /// Returns the timestamp of the block in Unix time format.
     pub fn get_time(&self) -> u32 {
         self.time
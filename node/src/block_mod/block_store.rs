@@ -0,0 +1,325 @@
+use std::{
+    cell::RefCell,
+    collections::{HashMap, VecDeque},
+    fs::{self, File, OpenOptions},
+    io::{self, Read, Seek, SeekFrom, Write},
+    path::Path,
+};
+
+use bitcoin_hashes::{sha256d, Hash};
+
+use crate::messages::message_error::MessageError;
+
+use super::block::Block;
+
+const MAX_BLOCK_FILE_SIZE: u64 = 128 * 1024 * 1024;
+const BLOCK_CACHE_SIZE: usize = 64;
+
+/// Where a block is in the block files, with the checksum of its serialization, so it can be sent to a peer in a
+/// `block` message without reading it first.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockLocation {
+    path: String,
+    offset: u64,
+    size: u32,
+    checksum: Vec<u8>,
+}
+
+impl BlockLocation {
+    pub fn get_size(&self) -> u32 {
+        self.size
+    }
+
+    pub fn get_checksum(&self) -> &Vec<u8> {
+        &self.checksum
+    }
+
+    /// Copies the serialized block from its file to a writer, without keeping it in memory.
+    pub fn copy_to(&self, writer: &mut dyn Write) -> Result<(), MessageError> {
+        let mut file = File::open(&self.path)?;
+        file.seek(SeekFrom::Start(self.offset))?;
+        let copied = io::copy(&mut file.take(self.size as u64), writer)?;
+        if copied != self.size as u64 {
+            return Err(MessageError::ReadFromBytes);
+        }
+        Ok(())
+    }
+}
+
+/// The position of a block in the block files.
+#[derive(Debug, Clone, Copy)]
+struct IndexEntry {
+    file: u32,
+    offset: u64,
+    size: u32,
+    checksum: [u8; 4],
+}
+
+/// The blocks used most recently, discarding the least recently used one when there are more than the capacity.
+#[derive(Debug)]
+struct BlockCache {
+    blocks: HashMap<Vec<u8>, Block>,
+    recent: VecDeque<Vec<u8>>,
+    capacity: Option<usize>,
+}
+
+impl BlockCache {
+    fn get(&mut self, block_hash: &[u8]) -> Option<Block> {
+        let block = self.blocks.get(block_hash)?.clone();
+        self.touch(block_hash);
+        Some(block)
+    }
+
+    fn insert(&mut self, block_hash: Vec<u8>, block: Block) {
+        self.touch(&block_hash);
+        self.blocks.insert(block_hash, block);
+        if let Some(capacity) = self.capacity {
+            while self.blocks.len() > capacity {
+                match self.recent.pop_front() {
+                    Some(oldest) => self.blocks.remove(&oldest),
+                    None => break,
+                };
+            }
+        }
+    }
+
+    fn remove(&mut self, block_hash: &[u8]) -> Option<Block> {
+        self.recent.retain(|hash| hash != block_hash);
+        self.blocks.remove(block_hash)
+    }
+
+    /// Moves a block to the end of the recently used ones. Without a capacity nothing is discarded, so the order isn't
+    /// kept.
+    fn touch(&mut self, block_hash: &[u8]) {
+        if self.capacity.is_some() {
+            self.recent.retain(|hash| hash != block_hash);
+            self.recent.push_back(block_hash.to_vec());
+        }
+    }
+}
+
+/// Keeps the blocks of the blockchain.
+///
+/// Without files, every block is kept in memory. With them, each block is appended to the current block file, a new
+/// one is started when it reaches `MAX_BLOCK_FILE_SIZE`, and only the last `BLOCK_CACHE_SIZE` blocks used are kept in
+/// memory. The others are read from their files when they are requested, with the index of their positions. The files
+/// are named after the given path followed by their number, and the ones of a previous run are removed when they are
+/// opened, as the blocks are downloaded again.
+#[derive(Debug)]
+pub struct BlockStore {
+    path: Option<String>,
+    index: HashMap<Vec<u8>, IndexEntry>,
+    file_blocks: HashMap<u32, usize>,
+    current_file: u32,
+    current_size: u64,
+    cache: RefCell<BlockCache>,
+}
+
+impl BlockStore {
+    /// Creates a store that keeps every block in memory.
+    pub fn new() -> BlockStore {
+        BlockStore {
+            path: None,
+            index: HashMap::new(),
+            file_blocks: HashMap::new(),
+            current_file: 0,
+            current_size: 0,
+            cache: RefCell::new(BlockCache {
+                blocks: HashMap::new(),
+                recent: VecDeque::new(),
+                capacity: None,
+            }),
+        }
+    }
+
+    /// Creates a store that keeps the blocks in files, removing the files of a previous run.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path the names of the files start with.
+    ///
+    /// # Returns
+    ///
+    /// The store, or a `MessageError` if the old files can't be removed.
+    pub fn open(path: &str) -> Result<BlockStore, MessageError> {
+        let mut file = 0;
+        while Path::new(&get_file_path(path, file)).exists() {
+            fs::remove_file(get_file_path(path, file))?;
+            file += 1;
+        }
+
+        let mut block_store = BlockStore::new();
+        block_store.path = Some(path.to_string());
+        block_store.cache.get_mut().capacity = Some(BLOCK_CACHE_SIZE);
+        Ok(block_store)
+    }
+
+    /// Adds a block, appending it to the current file if the store has files and it wasn't added before.
+    ///
+    /// # Returns
+    ///
+    /// The size of the serialized block, or a `MessageError` if it can't be written.
+    pub fn insert(&mut self, block: Block) -> Result<usize, MessageError> {
+        let block_hash = block.get_header().get_header();
+        let bytes = block.as_bytes();
+
+        if let (Some(path), false) = (&self.path, self.index.contains_key(&block_hash)) {
+            let size = bytes.len() as u64;
+            if self.current_size > 0 && self.current_size + size > MAX_BLOCK_FILE_SIZE {
+                self.current_file += 1;
+                self.current_size = 0;
+            }
+            OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(get_file_path(path, self.current_file))?
+                .write_all(&bytes)?;
+
+            let mut checksum = [0u8; 4];
+            checksum.copy_from_slice(&sha256d::Hash::hash(&bytes).to_byte_array()[..4]);
+            let entry = IndexEntry {
+                file: self.current_file,
+                offset: self.current_size,
+                size: bytes.len() as u32,
+                checksum,
+            };
+            self.index.insert(block_hash.clone(), entry);
+            *self.file_blocks.entry(self.current_file).or_default() += 1;
+            self.current_size += size;
+        }
+
+        self.cache.get_mut().insert(block_hash, block);
+        Ok(bytes.len())
+    }
+
+    /// Returns a block, from memory or read from its file.
+    pub fn get(&self, block_hash: &[u8]) -> Option<Block> {
+        if let Some(block) = self.cache.borrow_mut().get(block_hash) {
+            return Some(block);
+        }
+        let location = self.get_location(block_hash)?;
+        let mut bytes = vec![];
+        location.copy_to(&mut bytes).ok()?;
+        let block = Block::from_bytes(&mut &bytes[..]).ok()?;
+        self.cache
+            .borrow_mut()
+            .insert(block_hash.to_vec(), block.clone());
+        Some(block)
+    }
+
+    /// Returns where a block is in the files, or `None` if it isn't stored or the store has no files.
+    pub fn get_location(&self, block_hash: &[u8]) -> Option<BlockLocation> {
+        let entry = self.index.get(block_hash)?;
+        Some(BlockLocation {
+            path: get_file_path(self.path.as_ref()?, entry.file),
+            offset: entry.offset,
+            size: entry.size,
+            checksum: entry.checksum.to_vec(),
+        })
+    }
+
+    pub fn contains(&self, block_hash: &[u8]) -> bool {
+        match self.path {
+            Some(_) => self.index.contains_key(block_hash),
+            None => self.cache.borrow().blocks.contains_key(block_hash),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match self.path {
+            Some(_) => self.index.len(),
+            None => self.cache.borrow().blocks.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the hashes of the stored blocks.
+    pub fn get_hashes(&self) -> Vec<Vec<u8>> {
+        match self.path {
+            Some(_) => self.index.keys().cloned().collect(),
+            None => self.cache.borrow().blocks.keys().cloned().collect(),
+        }
+    }
+
+    /// Removes a block, and the file it is in if no other block of the file is kept and it isn't the current one.
+    ///
+    /// # Returns
+    ///
+    /// The size of the serialized block, `None` if it wasn't stored, or a `MessageError` if its file can't be removed.
+    pub fn remove(&mut self, block_hash: &[u8]) -> Result<Option<usize>, MessageError> {
+        let block = self.cache.get_mut().remove(block_hash);
+        let (path, entry) = match (&self.path, self.index.remove(block_hash)) {
+            (Some(path), Some(entry)) => (path, entry),
+            _ => return Ok(block.map(|block| block.as_bytes().len())),
+        };
+
+        let remaining = self.file_blocks.entry(entry.file).or_default();
+        *remaining = remaining.saturating_sub(1);
+        if *remaining == 0 && entry.file != self.current_file {
+            self.file_blocks.remove(&entry.file);
+            fs::remove_file(get_file_path(path, entry.file))?;
+        }
+        Ok(Some(entry.size as usize))
+    }
+}
+
+impl Default for BlockStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Returns the path of a block file, the given path followed by the number of the file.
+fn get_file_path(path: &str, file: u32) -> String {
+    format!("{}.{:05}", path, file)
+}
+
+#[cfg(test)]
+mod block_store_test {
+    use super::*;
+    use crate::block_mod::block_header::BlockHeader;
+
+    /// Creates an empty block with an empty coinbase.
+    fn test_block(time: u32) -> Result<Block, MessageError> {
+        let header = BlockHeader::new(1, vec![0; 32], vec![0; 32], time, 0, 0);
+        let mut bytes = header.as_bytes();
+        bytes.push(1);
+        bytes.extend([1, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        Block::from_bytes(&mut &bytes[..])
+    }
+
+    #[test]
+    fn test_blocks_are_read_back_from_the_files() -> Result<(), MessageError> {
+        let path = std::env::temp_dir().join(format!("block_store_test_{}", std::process::id()));
+        let path = path.to_string_lossy().to_string();
+        let mut block_store = BlockStore::open(&path)?;
+
+        let mut hashes = vec![];
+        for time in 0..BLOCK_CACHE_SIZE as u32 + 2 {
+            let block = test_block(time)?;
+            hashes.push(block.get_header().get_header());
+            block_store.insert(block)?;
+        }
+        assert_eq!(block_store.len(), BLOCK_CACHE_SIZE + 2);
+        assert_eq!(block_store.cache.borrow().blocks.len(), BLOCK_CACHE_SIZE);
+
+        let first = block_store.get(&hashes[0]).map(|block| block.as_bytes());
+        assert_eq!(first, Some(test_block(0)?.as_bytes()));
+        let location = block_store
+            .get_location(&hashes[1])
+            .ok_or(MessageError::ReadFromBytes)?;
+        let mut streamed = vec![];
+        location.copy_to(&mut streamed)?;
+        assert_eq!(streamed, test_block(1)?.as_bytes());
+        assert_eq!(location.get_size() as usize, streamed.len());
+
+        assert_eq!(block_store.remove(&hashes[0])?, Some(streamed.len()));
+        assert!(!block_store.contains(&hashes[0]));
+        assert!(block_store.get(&hashes[0]).is_none());
+        fs::remove_file(get_file_path(&path, 0))?;
+        Ok(())
+    }
+}
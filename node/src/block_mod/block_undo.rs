@@ -0,0 +1,78 @@
+use std::io::Read;
+
+use crate::messages::{
+    compact_size::CompactSizeUInt, message_error::MessageError,
+    read_from_bytes::read_vec_from_bytes,
+};
+
+use super::{outpoint::Outpoint, tx_out::TxOut};
+
+/// The changes a block made to the UTXO set, to revert them without the block once it is pruned.
+///
+/// It keeps the ids of the transactions of the block, whose outputs the block created, and the outputs its inputs
+/// spent, in the order they were spent.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BlockUndo {
+    tx_ids: Vec<Vec<u8>>,
+    spent_outputs: Vec<(Outpoint, TxOut)>,
+}
+
+impl BlockUndo {
+    pub fn new() -> BlockUndo {
+        BlockUndo::default()
+    }
+
+    /// Parses the undo data of a block, as written by `as_bytes`.
+    pub fn from_bytes(stream: &mut dyn Read) -> Result<BlockUndo, MessageError> {
+        let mut undo = BlockUndo::new();
+        for _ in 0..CompactSizeUInt::from_bytes(stream)?.value() {
+            undo.add_tx_id(read_vec_from_bytes(stream, 32)?);
+        }
+        for _ in 0..CompactSizeUInt::from_bytes(stream)?.value() {
+            let outpoint = Outpoint::from_bytes(stream)?;
+            undo.add_spent_output(outpoint, TxOut::from_bytes(stream)?);
+        }
+        Ok(undo)
+    }
+
+    /// Serializes the undo data: the number of transactions as a compact size followed by their ids, and the number of
+    /// spent outputs followed by each outpoint and output.
+    pub fn as_bytes(&self) -> Vec<u8> {
+        let mut bytes = CompactSizeUInt::from_number(self.tx_ids.len() as u64).as_bytes();
+        for tx_id in self.tx_ids.iter() {
+            bytes.extend(tx_id);
+        }
+        bytes.extend(CompactSizeUInt::from_number(self.spent_outputs.len() as u64).as_bytes());
+        for (outpoint, tx_out) in self.spent_outputs.iter() {
+            bytes.extend(outpoint.as_bytes());
+            bytes.extend(tx_out.as_bytes());
+        }
+        bytes
+    }
+
+    /// Records a transaction whose outputs were added to the UTXO set.
+    pub fn add_tx_id(&mut self, tx_id: Vec<u8>) {
+        self.tx_ids.push(tx_id);
+    }
+
+    /// Records an output that was removed from the UTXO set.
+    pub fn add_spent_output(&mut self, outpoint: Outpoint, tx_out: TxOut) {
+        self.spent_outputs.push((outpoint, tx_out));
+    }
+
+    pub fn get_tx_ids(&self) -> &Vec<Vec<u8>> {
+        &self.tx_ids
+    }
+
+    pub fn get_spent_outputs(&self) -> &Vec<(Outpoint, TxOut)> {
+        &self.spent_outputs
+    }
+
+    /// Returns the public key scripts of the outputs the block spent.
+    pub fn get_spent_pk_scripts(&self) -> Vec<Vec<u8>> {
+        self.spent_outputs
+            .iter()
+            .map(|(_, tx_out)| tx_out.get_pk_script())
+            .collect()
+    }
+}
@@ -1,47 +1,385 @@
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 
-use crate::network::network_constants::{
-    GENESIS_MERKLE_ROOT_HASH, GENESIS_NBITS, GENESIS_NONCE, GENESIS_PREVIOUS_BLOCK_HEADER_HASH,
-    GENESIS_TIME, GENESIS_VERSION,
+use crate::{messages::message_error::MessageError, network::bitcoin_network::BitcoinNetwork};
+
+use super::{
+    address_index::AddressIndex,
+    block::Block,
+    block_header::BlockHeader,
+    block_store::{BlockLocation, BlockStore},
+    filter_index::FilterIndex,
+    prune_target::PruneTarget,
+    tx_index::TxIndex,
+    undo_store::UndoStore,
+    utxo::UnspentTx,
 };
 
-use super::{block::Block, block_header::BlockHeader};
+const UNDO_FILE_EXTENSION: &str = "undo";
 
 /// Represents a blockchain and maintains information about blocks and the last block header.
+///
+/// The headers of the blocks are kept in memory, and their bodies in a `BlockStore`, which keeps only the most recent
+/// ones in memory once the block files are enabled. With a prune target, only the blocks at the greatest heights are kept,
+/// and only the header of the pruned ones is kept instead, their undo data being discarded with them.
+///
+/// Each block whose previous block has a height gets the next one, and the active chain follows the first block added
+/// at the greatest height back to the base of the chain, the genesis block unless the chain starts at a later block.
+/// Blocks added before their previous one wait for it to get their height. The blocks are connected to the UTXO set, the
+/// address index and the compact filters in the order of the active chain, and disconnected from them with their undo
+/// data when a reorganization leaves them out of it.
 pub struct BlockChain {
-    blocks: HashMap<Vec<u8>, Block>,
-    last_block_header: BlockHeader,
+    blocks: BlockStore,
+    headers: HashMap<Vec<u8>, BlockHeader>,
+    heights: HashMap<Vec<u8>, usize>,
+    active_chain: Vec<Vec<u8>>,
+    chain_start: usize,
+    orphans: HashMap<Vec<u8>, Vec<Vec<u8>>>,
+    address_index: Option<AddressIndex>,
+    tx_index: Option<TxIndex>,
+    filter_index: Option<FilterIndex>,
+    undo_data: UndoStore,
+    prune_target: Option<PruneTarget>,
+    stored_blocks: BTreeSet<(usize, Vec<u8>)>,
+    stored_size: usize,
 }
 
 impl BlockChain {
-    /// Creates a new `BlockChain` object with default values for the genesis block.
-    pub fn new() -> BlockChain {
+    /// Creates a new `BlockChain` object starting from the genesis block of the given network.
+    ///
+    /// # Arguments
+    ///
+    /// * `network` - The `BitcoinNetwork` whose genesis block is used as the initial last block.
+    pub fn new(network: BitcoinNetwork) -> BlockChain {
+        let genesis_hash = network.get_genesis_header().get_header();
         BlockChain {
-            blocks: HashMap::new(),
-            last_block_header: BlockHeader::new(
-                GENESIS_VERSION,
-                GENESIS_PREVIOUS_BLOCK_HEADER_HASH.to_vec(),
-                GENESIS_MERKLE_ROOT_HASH.to_vec(),
-                GENESIS_TIME,
-                GENESIS_NBITS,
-                GENESIS_NONCE,
-            ),
+            blocks: BlockStore::new(),
+            headers: HashMap::new(),
+            heights: HashMap::from([(genesis_hash.clone(), 0)]),
+            active_chain: vec![genesis_hash],
+            chain_start: 0,
+            orphans: HashMap::new(),
+            address_index: None,
+            tx_index: None,
+            filter_index: None,
+            undo_data: UndoStore::new(),
+            prune_target: None,
+            stored_blocks: BTreeSet::new(),
+            stored_size: 0,
         }
     }
 
-    /// Adds a new block to the blockchain.
+    /// Starts the chain at a block after the genesis one, whose body isn't downloaded, such as the last one before the
+    /// date limit. It must be called before any block is added.
+    ///
+    /// # Arguments
+    ///
+    /// * `block_hash` - The hash of the block.
+    /// * `height` - The height of the block.
+    pub fn set_chain_base(&mut self, block_hash: Vec<u8>, height: usize) {
+        self.heights = HashMap::from([(block_hash.clone(), height)]);
+        self.active_chain = vec![block_hash];
+        self.chain_start = height;
+    }
+
+    /// Starts keeping the blocks in files, the ones already added included, with only the most recent ones in memory.
+    /// See `BlockStore`. Their undo data is kept in files too, named after the same path. See `UndoStore`.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path the names of the block files start with.
+    ///
+    /// # Returns
+    ///
+    /// An empty result, or a `MessageError` if the files can't be written.
+    pub fn enable_block_files(&mut self, path: &str) -> Result<(), MessageError> {
+        let mut blocks = BlockStore::open(path)?;
+        for block_hash in self.blocks.get_hashes() {
+            if let Some(block) = self.blocks.get(&block_hash) {
+                blocks.insert(block)?;
+            }
+        }
+        let mut undo_data = UndoStore::open(&format!("{}.{}", path, UNDO_FILE_EXTENSION))?;
+        for block_hash in self.undo_data.get_hashes() {
+            if let Some(undo) = self.undo_data.remove(&block_hash)? {
+                undo_data.insert(block_hash, undo)?;
+            }
+        }
+        self.blocks = blocks;
+        self.undo_data = undo_data;
+        Ok(())
+    }
+
+    /// Starts indexing the transactions of the blocks of the active chain by the scripts they pay to or spend from, the
+    /// blocks already added included. See `AddressIndex`.
+    pub fn enable_address_index(&mut self) {
+        let mut address_index = AddressIndex::new();
+        for block_hash in self.active_chain.iter() {
+            if let Some(block) = self.blocks.get(block_hash) {
+                address_index.connect_block(&block);
+            }
+        }
+        self.address_index = Some(address_index);
+    }
+
+    /// Starts indexing the transactions of the blocks by their id, the blocks already added included. See `TxIndex`.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the file the index is kept in.
+    ///
+    /// # Returns
+    ///
+    /// An empty result, or a `MessageError` if the file can't be read or written.
+    pub fn enable_tx_index(&mut self, path: &str) -> Result<(), MessageError> {
+        let mut tx_index = TxIndex::load(path)?;
+        for block_hash in self.blocks.get_hashes() {
+            if let Some(block) = self.blocks.get(&block_hash) {
+                tx_index.connect_block(&block)?;
+            }
+        }
+        self.tx_index = Some(tx_index);
+        Ok(())
+    }
+
+    /// Starts computing the compact filters of the blocks connected from now on. See `FilterIndex`. The filters need the
+    /// UTXO set to be complete, so the chain must start at the genesis block.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the file the headers of the filters are kept in.
+    ///
+    /// # Returns
+    ///
+    /// An empty result, or a `MessageError` if the file can't be read.
+    pub fn enable_block_filters(&mut self, path: &str) -> Result<(), MessageError> {
+        self.filter_index = Some(FilterIndex::load(path)?);
+        Ok(())
+    }
+
+    /// Starts pruning the blocks, the ones already added included, to keep only the most recent ones.
+    ///
+    /// # Arguments
+    ///
+    /// * `prune_target` - How many blocks are kept.
+    pub fn enable_pruning(&mut self, prune_target: PruneTarget) {
+        for block_hash in self.blocks.get_hashes() {
+            if let Some(block) = self.blocks.get(&block_hash) {
+                if let Some(height) = self.get_height(&block_hash) {
+                    self.stored_blocks.insert((height, block_hash));
+                }
+                self.stored_size += block.as_bytes().len();
+            }
+        }
+        self.prune_target = Some(prune_target);
+        self.prune();
+    }
+
+    /// Adds a new block to the blockchain, updating the UTXO set if the active chain changes. A block that can't be
+    /// stored is left out, without indexing it nor changing the chain.
     ///
     /// # Arguments
     ///
     /// * `block` - The `Block` object to be added to the blockchain.
-    pub fn add(&mut self, block: Block) {
-        let header = block.get_header();
+    /// * `utxo` - The UTXO set, which follows the active chain.
+    pub fn add(&mut self, block: Block, utxo: &mut UnspentTx) {
+        let header = block.get_header().clone();
+        let block_hash = header.get_header();
+        let is_new = !self.blocks.contains(&block_hash);
+
+        let size = match self.blocks.insert(block.clone()) {
+            Ok(size) => size,
+            Err(err) => {
+                println!("Falla al guardar el bloque: {:?}", err);
+                return;
+            }
+        };
+        if let Some(tx_index) = self.tx_index.as_mut() {
+            if let Err(err) = tx_index.connect_block(&block) {
+                println!("Falla al guardar el índice de transacciones: {:?}", err);
+            }
+        }
+        if self.prune_target.is_some() && is_new {
+            if let Some(height) = self.get_height(&block_hash) {
+                self.stored_blocks.insert((height, block_hash.clone()));
+            }
+            self.stored_size += size;
+        }
+        self.headers.insert(block_hash.clone(), header);
+        if is_new && self.active_chain.first() == Some(&block_hash) {
+            self.connect_block(&block_hash, utxo);
+        }
+        self.set_height(block_hash, utxo);
+        self.prune();
+    }
+
+    /// Gives a height to a block whose previous block has one, and to the blocks that were waiting for it, and makes
+    /// the first block at the greatest height the tip of the active chain. A block whose previous one has no height
+    /// waits for it.
+    fn set_height(&mut self, block_hash: Vec<u8>, utxo: &mut UnspentTx) {
+        let previous = match self.headers.get(&block_hash) {
+            Some(header) => header.get_previuos_block_header().clone(),
+            None => return,
+        };
+        if self.heights.contains_key(&block_hash) {
+            return;
+        }
+        let previous_height = match self.heights.get(&previous) {
+            Some(height) => *height,
+            None => {
+                self.orphans.entry(previous).or_default().push(block_hash);
+                return;
+            }
+        };
+
+        let mut best_tip = None;
+        let mut best_height = self.get_tip_height();
+        let mut pending = vec![(block_hash, previous_height + 1)];
+        while let Some((hash, height)) = pending.pop() {
+            if height > best_height {
+                best_tip = Some(hash.clone());
+                best_height = height;
+            }
+            for child in self.orphans.remove(&hash).unwrap_or_default() {
+                pending.push((child, height + 1));
+            }
+            if self.prune_target.is_some() && self.blocks.contains(&hash) {
+                self.stored_blocks.insert((height, hash.clone()));
+            }
+            self.heights.insert(hash, height);
+        }
+        if let Some(best_tip) = best_tip {
+            self.set_tip(best_tip, utxo);
+        }
+    }
+
+    /// Makes a block the tip of the active chain: the blocks of the chain after the fork point are disconnected, and the
+    /// ones that lead to the new tip are connected.
+    ///
+    /// The chain only moves to another branch if every block of it can be connected and every block it leaves out can
+    /// be disconnected, so a branch with a block whose body isn't stored, such as a pruned one, doesn't replace the
+    /// active chain. A chain that is only extended stops at the first block that can't be connected.
+    fn set_tip(&mut self, tip: Vec<u8>, utxo: &mut UnspentTx) {
+        let mut branch = vec![];
+        let mut current = tip;
+        while !self.is_in_active_chain(&current) {
+            let previous = match self.headers.get(&current) {
+                Some(header) => header.get_previuos_block_header().clone(),
+                None => return,
+            };
+            branch.push(current);
+            current = previous;
+        }
+
+        let fork_height = self
+            .heights
+            .get(&current)
+            .copied()
+            .unwrap_or(self.chain_start);
+        let fork_index = fork_height + 1 - self.chain_start;
+        if fork_index < self.active_chain.len() {
+            let is_connectable = branch
+                .iter()
+                .all(|block_hash| self.blocks.contains(block_hash));
+            let is_disconnectable = self.active_chain[fork_index..]
+                .iter()
+                .all(|block_hash| self.undo_data.contains(block_hash));
+            if !is_connectable || !is_disconnectable {
+                return;
+            }
+        }
 
-        if self.last_block_header.get_time() < header.get_time() {
-            self.last_block_header = header.clone();
+        while self.active_chain.len() > fork_index {
+            let block_hash = match self.active_chain.last() {
+                Some(block_hash) => block_hash.clone(),
+                None => return,
+            };
+            if !self.disconnect_block(&block_hash, utxo) {
+                return;
+            }
+            self.active_chain.pop();
         }
+        for block_hash in branch.into_iter().rev() {
+            if !self.connect_block(&block_hash, utxo) {
+                return;
+            }
+            self.active_chain.push(block_hash);
+        }
+    }
+
+    /// Connects a block that joins the active chain: updates the UTXO set with it, keeping its undo data, indexes its
+    /// addresses and computes its compact filter with the scripts its inputs spend.
+    ///
+    /// # Returns
+    ///
+    /// Whether the block was connected, which it isn't if its body isn't stored.
+    fn connect_block(&mut self, block_hash: &[u8], utxo: &mut UnspentTx) -> bool {
+        let block = match self.blocks.get(block_hash) {
+            Some(block) => block,
+            None => return false,
+        };
+        let undo = utxo.update(&block);
+        if let Some(address_index) = self.address_index.as_mut() {
+            address_index.connect_block(&block);
+        }
+        if let Some(filter_index) = self.filter_index.as_mut() {
+            if let Err(err) = filter_index.connect_block(&block, &undo.get_spent_pk_scripts()) {
+                println!("Falla al calcular el filtro del bloque: {:?}", err);
+            }
+        }
+        if let Err(err) = self.undo_data.insert(block_hash.to_vec(), undo) {
+            println!(
+                "Falla al guardar los datos para deshacer el bloque: {:?}",
+                err
+            );
+        }
+        true
+    }
 
-        self.blocks.insert(header.get_header(), block);
+    /// Disconnects a block that leaves the active chain, reverting its changes to the UTXO set and the address index.
+    ///
+    /// # Returns
+    ///
+    /// Whether the block was disconnected, which it isn't if its undo data isn't stored or can't be read.
+    fn disconnect_block(&mut self, block_hash: &[u8], utxo: &mut UnspentTx) -> bool {
+        let undo = match self.undo_data.remove(block_hash) {
+            Ok(Some(undo)) => undo,
+            Ok(None) => return false,
+            Err(err) => {
+                println!("Falla al leer los datos para deshacer el bloque: {:?}", err);
+                return false;
+            }
+        };
+        utxo.disconnect_block(&undo);
+        if let Some(address_index) = self.address_index.as_mut() {
+            address_index.disconnect_block(block_hash);
+        }
+        true
+    }
+
+    /// Removes the blocks at the lowest heights, with their undo data, while the kept ones exceed the prune target. The
+    /// blocks that still wait for their previous one have no height, so they are kept until they get one.
+    fn prune(&mut self) {
+        let prune_target = match self.prune_target {
+            Some(prune_target) => prune_target,
+            None => return,
+        };
+        while prune_target.is_exceeded(self.blocks.len(), self.stored_size) {
+            let (_, block_hash) = match self.stored_blocks.pop_first() {
+                Some(oldest) => oldest,
+                None => return,
+            };
+            match self.blocks.remove(&block_hash) {
+                Ok(Some(size)) => self.stored_size -= size,
+                Ok(None) => {}
+                Err(err) => println!("Falla al borrar el archivo de bloques: {:?}", err),
+            }
+            if let Err(err) = self.undo_data.discard(&block_hash) {
+                println!(
+                    "Falla al borrar el archivo de datos para deshacer bloques: {:?}",
+                    err
+                );
+            }
+        }
     }
 
     /// Returns the number of blocks in the blockchain.
@@ -53,26 +391,42 @@ impl BlockChain {
         self.blocks.len()
     }
 
-    /// Returns a reference to the internal HashMap containing the blocks in the blockchain.
-    ///
-    /// # Returns
-    ///
-    /// A reference to the HashMap containing the blocks in the blockchain.
-    pub fn get_blocks(&self) -> &HashMap<Vec<u8>, Block> {
-        //tratar de sacar
-        &self.blocks
+    /// Returns the hashes of the blocks in the blockchain, without the pruned ones.
+    pub fn get_block_hashes(&self) -> Vec<Vec<u8>> {
+        self.blocks.get_hashes()
     }
 
     /// Returns the header of the last block in the blockchain.
     ///
     /// # Returns
     ///
-    /// The header of the last block as a byte vector.
+    /// The hash of the tip of the active chain, the base of the chain if no block was added after it.
     pub fn get_last_block_header(&self) -> Vec<u8> {
-        self.last_block_header.get_header()
+        self.active_chain.last().cloned().unwrap_or_default()
     }
 
-    /// Returns a reference to the block associated with the given block header.
+    /// Returns the height of the tip of the active chain.
+    pub fn get_tip_height(&self) -> usize {
+        self.chain_start + self.active_chain.len() - 1
+    }
+
+    /// Returns the height of a block, or `None` if it is unknown or still waits for its previous block.
+    pub fn get_height(&self, block_hash: &[u8]) -> Option<usize> {
+        self.heights.get(block_hash).copied()
+    }
+
+    /// Checks if a block is part of the active chain, comparing it with the block of the chain at its height.
+    pub fn is_in_active_chain(&self, block_hash: &[u8]) -> bool {
+        match self.heights.get(block_hash) {
+            Some(height) if *height >= self.chain_start => self
+                .active_chain
+                .get(height - self.chain_start)
+                .is_some_and(|active| active == block_hash),
+            _ => false,
+        }
+    }
+
+    /// Returns the block associated with the given block header, read from its file if it isn't in memory.
     ///
     /// # Arguments
     ///
@@ -80,14 +434,412 @@ impl BlockChain {
     ///
     /// # Returns
     ///
-    /// An `Option` containing a reference to the block if found, or `None` if the block is not present in the blockchain.
-    pub fn get_block(&self, block_header: &Vec<u8>) -> Option<&Block> {
+    /// An `Option` containing the block if found, or `None` if the block is not present in the blockchain.
+    pub fn get_block(&self, block_header: &[u8]) -> Option<Block> {
         self.blocks.get(block_header)
     }
+
+    /// Checks if a block is in the blockchain, without reading it.
+    pub fn contains_block(&self, block_hash: &[u8]) -> bool {
+        self.blocks.contains(block_hash)
+    }
+
+    /// Returns where a block is in the block files, to send it without reading it, or `None` if it isn't stored or the
+    /// block files aren't enabled.
+    pub fn get_block_location(&self, block_hash: &[u8]) -> Option<BlockLocation> {
+        self.blocks.get_location(block_hash)
+    }
+
+    /// Returns the header of a block, kept or pruned.
+    pub fn get_block_header(&self, block_hash: &[u8]) -> Option<&BlockHeader> {
+        self.headers.get(block_hash)
+    }
+
+    /// Checks if a block was added and then pruned, so only its header is kept.
+    pub fn is_pruned(&self, block_hash: &[u8]) -> bool {
+        self.headers.contains_key(block_hash) && !self.blocks.contains(block_hash)
+    }
+
+    /// Returns the prune target, or `None` if the blocks aren't pruned.
+    pub fn get_prune_target(&self) -> Option<PruneTarget> {
+        self.prune_target
+    }
+
+    /// Returns the address index, or `None` if it isn't enabled.
+    pub fn get_address_index(&self) -> Option<&AddressIndex> {
+        self.address_index.as_ref()
+    }
+
+    /// Returns the transaction index, or `None` if it isn't enabled.
+    pub fn get_tx_index(&self) -> Option<&TxIndex> {
+        self.tx_index.as_ref()
+    }
+
+    /// Returns the hash of the block of the active chain that includes a transaction and the position of the transaction
+    /// in its ids, or `None` if the transaction index isn't enabled or no block of the active chain includes it.
+    pub fn get_indexed_transaction(&self, tx_id: &[u8]) -> Option<(Vec<u8>, u32)> {
+        self.tx_index
+            .as_ref()?
+            .get(tx_id)
+            .iter()
+            .find(|(block_hash, _)| self.is_in_active_chain(block_hash))
+            .cloned()
+    }
+
+    /// Returns the index of the compact filters, or `None` if they aren't enabled.
+    pub fn get_filter_index(&self) -> Option<&FilterIndex> {
+        self.filter_index.as_ref()
+    }
+
+    /// Returns the header of the compact filter of a block, see `FilterIndex::get_filter_header`. The walk back stops
+    /// at the genesis block.
+    ///
+    /// # Returns
+    ///
+    /// The header, `None` if the filters aren't enabled or the filters of the chain of the block don't reach the
+    /// genesis block, or a `MessageError` if the headers can't be saved.
+    pub fn get_filter_header(
+        &mut self,
+        block_hash: &[u8],
+    ) -> Result<Option<Vec<u8>>, MessageError> {
+        let headers = &self.headers;
+        let heights = &self.heights;
+        let get_previous = |hash: &[u8]| match heights.get(hash) {
+            Some(0) => None,
+            _ => headers
+                .get(hash)
+                .map(|header| header.get_previuos_block_header().clone()),
+        };
+        match self.filter_index.as_mut() {
+            Some(filter_index) => filter_index.get_filter_header(block_hash, get_previous),
+            None => Ok(None),
+        }
+    }
+
+    /// Finds the last block that a known block shares with the chain that ends in the last block.
+    ///
+    /// # Arguments
+    ///
+    /// * `block_hash` - The hash of the block.
+    ///
+    /// # Returns
+    ///
+    /// `None` if the block is unknown. Otherwise, the hash of the fork point, which is the block itself while it is still
+    /// part of the chain, and the hashes of the blocks between the given block and the fork point, which a reorganization
+    /// left out of the chain.
+    pub fn find_fork_point(&self, block_hash: &[u8]) -> Option<(Vec<u8>, Vec<Vec<u8>>)> {
+        let mut disconnected_blocks = vec![];
+        let mut current = block_hash.to_vec();
+        while !self.is_in_active_chain(&current) {
+            let previous = self
+                .get_block_header(&current)?
+                .get_previuos_block_header()
+                .clone();
+            disconnected_blocks.push(current);
+            current = previous;
+        }
+        Some((current, disconnected_blocks))
+    }
 }
 
 impl Default for BlockChain {
     fn default() -> Self {
-        Self::new()
+        Self::new(BitcoinNetwork::default())
+    }
+}
+
+#[cfg(test)]
+mod blockchain_test {
+    use super::*;
+    use crate::{
+        block_mod::{outpoint::Outpoint, transaction::Transaction, tx_in::TxIn, tx_out::TxOut},
+        messages::message_error::MessageError,
+        network::network_constants::NETWORK_LIMITED_MIN_BLOCKS,
+    };
+
+    /// Creates an empty block on top of the given one, with an empty coinbase.
+    fn test_block(previous: &[u8], time: u32) -> Result<Block, MessageError> {
+        let header = BlockHeader::new(1, previous.to_vec(), vec![0; 32], time, 0, 0);
+        let mut bytes = header.as_bytes();
+        bytes.push(1);
+        bytes.extend([1, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        Block::from_bytes(&mut &bytes[..])
+    }
+
+    /// Creates a block on top of the given one whose coinbase pays to the given script and, if there is an id, with a
+    /// transaction that spends the first output of that transaction.
+    fn paying_block(
+        previous: &[u8],
+        time: u32,
+        pk_script: u8,
+        spent_tx_id: Option<Vec<u8>>,
+    ) -> Result<Block, MessageError> {
+        let header = BlockHeader::new(1, previous.to_vec(), vec![0; 32], time, 0, 0);
+        let mut bytes = header.as_bytes();
+        bytes.extend([1 + spent_tx_id.is_some() as u8, 1, 0, 0, 0, 1]);
+        bytes.extend([0; 32]);
+        bytes.extend([0xff, 0xff, 0xff, 0xff, 2, 1, 1, 0xff, 0xff, 0xff, 0xff, 1]);
+        bytes.extend([0; 8]);
+        bytes.extend([1, pk_script, 0, 0, 0, 0]);
+        if let Some(spent_tx_id) = spent_tx_id {
+            let spending = Transaction::new(
+                1,
+                vec![TxIn::new(spent_tx_id, 0, vec![], 0)],
+                vec![TxOut::new(0, vec![0x60])],
+                0,
+            );
+            bytes.extend(spending.as_bytes());
+        }
+        Block::from_bytes(&mut &bytes[..])
+    }
+
+    #[test]
+    fn test_block_that_cant_be_stored_is_left_out() -> Result<(), MessageError> {
+        let path = std::env::temp_dir().join(format!(
+            "blockchain_unstored_test_{}.dat",
+            std::process::id()
+        ));
+        let path = path.to_string_lossy().to_string();
+        let mut blockchain = BlockChain::default();
+        let mut utxo = UnspentTx::new();
+        blockchain.enable_block_files(&format!("{}/missing/blocks", path))?;
+        blockchain.enable_tx_index(&path)?;
+        let genesis = BitcoinNetwork::default().get_genesis_header();
+        let block = test_block(&genesis.get_header(), genesis.get_time() + 1)?;
+        let coinbase_id = block.get_txn_ids()[0].clone();
+
+        blockchain.add(block, &mut utxo);
+        std::fs::remove_file(&path)?;
+
+        assert_eq!(blockchain.get_last_block_header(), genesis.get_header());
+        assert_eq!(
+            blockchain
+                .get_tx_index()
+                .map(|index| index.get(&coinbase_id).len()),
+            Some(0)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_utxo_and_filters_follow_the_active_chain() -> Result<(), MessageError> {
+        let path = std::env::temp_dir().join(format!(
+            "blockchain_filters_test_{}.dat",
+            std::process::id()
+        ));
+        let path = path.to_string_lossy().to_string();
+        let mut blockchain = BlockChain::default();
+        let mut utxo = UnspentTx::new();
+        blockchain.enable_block_files(&path)?;
+        blockchain.enable_block_filters(&path)?;
+        let genesis = BitcoinNetwork::default().get_genesis_header();
+        let time = genesis.get_time();
+        let stale = paying_block(&genesis.get_header(), time + 1, 0x51, None)?;
+        let stale_coinbase = Outpoint::new(stale.get_coinbase().get_id(), 0);
+        let first = paying_block(&genesis.get_header(), time + 2, 0x52, None)?;
+        let first_hash = first.get_header().get_header();
+        let first_coinbase = Outpoint::new(first.get_coinbase().get_id(), 0);
+        let second = paying_block(
+            &first_hash,
+            time + 3,
+            0x53,
+            Some(first.get_coinbase().get_id()),
+        )?;
+        let second_hash = second.get_header().get_header();
+
+        blockchain.add(stale, &mut utxo);
+        assert!(utxo.contains_key(&stale_coinbase));
+        blockchain.add(second, &mut utxo);
+        assert!(!utxo.contains_key(&first_coinbase));
+        blockchain.add(first, &mut utxo);
+        std::fs::remove_file(&path)?;
+        std::fs::remove_file(format!("{}.00000", path))?;
+        std::fs::remove_file(format!("{}.{}.00000", path, UNDO_FILE_EXTENSION))?;
+
+        assert_eq!(blockchain.get_last_block_header(), second_hash);
+        assert!(!utxo.contains_key(&stale_coinbase));
+        assert!(!utxo.contains_key(&first_coinbase));
+        assert_eq!(utxo.tx_count(), 2);
+        let filter_index = blockchain
+            .get_filter_index()
+            .ok_or(MessageError::InvalidInputCompactFilters)?;
+        let second_filter = filter_index
+            .get_filter(&second_hash)
+            .ok_or(MessageError::InvalidInputCompactFilters)?;
+        assert!(second_filter.matches_any(&second_hash, &[vec![0x52]]));
+        Ok(())
+    }
+
+    #[test]
+    fn test_branch_with_a_pruned_block_doesnt_replace_the_active_chain() -> Result<(), MessageError>
+    {
+        let mut blockchain = BlockChain::default();
+        let mut utxo = UnspentTx::new();
+        let genesis = BitcoinNetwork::default().get_genesis_header();
+        let time = genesis.get_time();
+        let tip_height = NETWORK_LIMITED_MIN_BLOCKS + 2;
+        blockchain.enable_pruning(PruneTarget::Blocks(NETWORK_LIMITED_MIN_BLOCKS));
+
+        let stale = test_block(&genesis.get_header(), time)?;
+        let mut branch = vec![stale.get_header().get_header()];
+        blockchain.add(stale, &mut utxo);
+        let mut hashes = vec![genesis.get_header()];
+        for height in 1..tip_height {
+            let block = test_block(&hashes[height - 1], time + height as u32)?;
+            hashes.push(block.get_header().get_header());
+            blockchain.add(block, &mut utxo);
+        }
+        let tip = paying_block(
+            &hashes[tip_height - 1],
+            time + tip_height as u32,
+            0x51,
+            None,
+        )?;
+        let tip_coinbase = Outpoint::new(tip.get_coinbase().get_id(), 0);
+        hashes.push(tip.get_header().get_header());
+        blockchain.add(tip, &mut utxo);
+        assert!(blockchain.is_pruned(&branch[0]));
+
+        for height in 2..=tip_height {
+            let block = test_block(&branch[height - 2], time + (tip_height + height) as u32)?;
+            branch.push(block.get_header().get_header());
+            blockchain.add(block, &mut utxo);
+        }
+        let branch_tip = paying_block(&branch[tip_height - 1], time, 0x52, None)?;
+        let branch_coinbase = Outpoint::new(branch_tip.get_coinbase().get_id(), 0);
+        blockchain.add(branch_tip, &mut utxo);
+
+        assert_eq!(blockchain.get_last_block_header(), hashes[tip_height]);
+        assert_eq!(blockchain.get_tip_height(), tip_height);
+        assert!(utxo.contains_key(&tip_coinbase));
+        assert!(!utxo.contains_key(&branch_coinbase));
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_fork_point() -> Result<(), MessageError> {
+        let mut blockchain = BlockChain::default();
+        let mut utxo = UnspentTx::new();
+        let genesis = BitcoinNetwork::default().get_genesis_header();
+        let time = genesis.get_time();
+        let first = test_block(&genesis.get_header(), time + 1)?;
+        let first_hash = first.get_header().get_header();
+        let orphan = test_block(&first_hash, time + 2)?;
+        let orphan_hash = orphan.get_header().get_header();
+        let second = test_block(&first_hash, time + 3)?;
+        let third = test_block(&second.get_header().get_header(), time + 4)?;
+        let third_hash = third.get_header().get_header();
+        for block in [first, orphan, second, third] {
+            blockchain.add(block, &mut utxo);
+        }
+
+        assert_eq!(
+            blockchain.find_fork_point(&first_hash),
+            Some((first_hash.clone(), vec![]))
+        );
+        assert_eq!(
+            blockchain.find_fork_point(&third_hash),
+            Some((third_hash, vec![]))
+        );
+        assert_eq!(
+            blockchain.find_fork_point(&orphan_hash),
+            Some((first_hash, vec![orphan_hash]))
+        );
+        assert_eq!(blockchain.find_fork_point(&[0; 32]), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_indexed_transaction_is_looked_up_in_the_active_chain() -> Result<(), MessageError> {
+        let path = std::env::temp_dir().join(format!(
+            "blockchain_tx_index_test_{}.dat",
+            std::process::id()
+        ));
+        let path = path.to_string_lossy().to_string();
+        let mut blockchain = BlockChain::default();
+        let mut utxo = UnspentTx::new();
+        blockchain.enable_tx_index(&path)?;
+        let genesis = BitcoinNetwork::default().get_genesis_header();
+        let time = genesis.get_time();
+        let stale = test_block(&genesis.get_header(), time + 1)?;
+        let coinbase_id = stale.get_txn_ids()[0].clone();
+        let first = test_block(&genesis.get_header(), time + 2)?;
+        let first_hash = first.get_header().get_header();
+        let second = test_block(&first_hash, time + 3)?;
+
+        blockchain.add(stale, &mut utxo);
+        blockchain.add(first, &mut utxo);
+        blockchain.add(second, &mut utxo);
+        std::fs::remove_file(&path)?;
+
+        assert_eq!(
+            blockchain
+                .get_tx_index()
+                .map(|index| index.get(&coinbase_id).len()),
+            Some(3)
+        );
+        assert_eq!(
+            blockchain.get_indexed_transaction(&coinbase_id),
+            Some((first_hash, 0))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_pruning_follows_the_heights_not_the_times() -> Result<(), MessageError> {
+        let mut blockchain = BlockChain::default();
+        let mut utxo = UnspentTx::new();
+        let genesis = BitcoinNetwork::default().get_genesis_header();
+        let time = genesis.get_time() + NETWORK_LIMITED_MIN_BLOCKS as u32 + 2;
+        blockchain.enable_pruning(PruneTarget::Blocks(NETWORK_LIMITED_MIN_BLOCKS));
+
+        let mut hashes = vec![genesis.get_header()];
+        for height in 1..=NETWORK_LIMITED_MIN_BLOCKS + 2 {
+            let block = test_block(&hashes[height - 1], time - height as u32)?;
+            hashes.push(block.get_header().get_header());
+            blockchain.add(block, &mut utxo);
+        }
+
+        assert!(blockchain.is_pruned(&hashes[1]));
+        assert!(blockchain.is_pruned(&hashes[2]));
+        assert!(blockchain.get_block(&hashes[3]).is_some());
+        assert!(blockchain
+            .get_block(&hashes[NETWORK_LIMITED_MIN_BLOCKS + 2])
+            .is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn test_pruning_keeps_the_most_recent_blocks() -> Result<(), MessageError> {
+        let mut blockchain = BlockChain::default();
+        let mut utxo = UnspentTx::new();
+        let genesis = BitcoinNetwork::default().get_genesis_header();
+        let time = genesis.get_time();
+        blockchain.enable_pruning(PruneTarget::Blocks(NETWORK_LIMITED_MIN_BLOCKS));
+
+        let mut hashes = vec![genesis.get_header()];
+        for height in 1..=NETWORK_LIMITED_MIN_BLOCKS + 2 {
+            let block = test_block(&hashes[height - 1], time + height as u32)?;
+            hashes.push(block.get_header().get_header());
+            blockchain.add(block, &mut utxo);
+        }
+
+        assert_eq!(blockchain.get_cant_act_blocks(), NETWORK_LIMITED_MIN_BLOCKS);
+        assert!(blockchain.is_pruned(&hashes[2]));
+        assert!(blockchain.get_block(&hashes[2]).is_none());
+        assert!(blockchain.get_block(&hashes[3]).is_some());
+        assert!(!blockchain.undo_data.contains(&hashes[2]));
+        assert!(blockchain.undo_data.contains(&hashes[3]));
+        assert_eq!(
+            blockchain
+                .get_block_header(&hashes[2])
+                .map(BlockHeader::get_header),
+            Some(hashes[2].clone())
+        );
+        assert_eq!(
+            blockchain.find_fork_point(&hashes[1]),
+            Some((hashes[1].clone(), vec![]))
+        );
+        Ok(())
     }
 }
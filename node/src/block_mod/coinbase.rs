@@ -1,3 +1,4 @@
+use crate::block_mod::transaction::Transaction;
 use crate::block_mod::tx_in_coinbase::TxInCoinbase;
 use crate::block_mod::tx_out::TxOut;
 use crate::messages::compact_size::CompactSizeUInt;
@@ -82,6 +83,14 @@ impl Coinbase {
         buff
     }
 
+    /// Parses the coinbase as a regular transaction, whose only input spends the null outpoint.
+    ///
+    /// # Returns
+    /// The `Transaction`, or a `MessageError` if its bytes can't be parsed as one.
+    pub fn as_transaction(&self) -> Result<Transaction, MessageError> {
+        Transaction::from_bytes(&mut &self.as_bytes()[..])
+    }
+
     /// Computes the ID of the Coinbase transaction by hashing its serialized bytes.
     ///
     /// # Returns
@@ -0,0 +1,226 @@
+use std::{
+    collections::HashMap,
+    fs::{File, OpenOptions},
+    io::{Read, Write},
+};
+
+use crate::messages::{message_error::MessageError, read_from_bytes::read_vec_from_bytes};
+
+use super::{block::Block, block_filter::BlockFilter};
+
+const FILTER_HEADER_RECORD_SIZE: usize = 64;
+
+/// Keeps the compact filters of the blocks and the headers of the filters, which chain each filter to the previous ones.
+///
+/// The filters are kept in memory, while the headers are also kept in a file: it is loaded when the node starts and
+/// each header that changes is appended to it, as the hash of the block followed by the header. A record cut short when
+/// the node stopped is removed when the file is loaded. The headers are computed when they are requested, walking back
+/// to a block with a known header or to the genesis block, whose previous header is all zeros. A block whose chain of
+/// filters doesn't reach one of them has no header, as it would commit to a different chain than other nodes.
+#[derive(Debug)]
+pub struct FilterIndex {
+    filters: HashMap<Vec<u8>, BlockFilter>,
+    filter_headers: HashMap<Vec<u8>, Vec<u8>>,
+    file: File,
+}
+
+impl FilterIndex {
+    /// Loads the headers of the filters from their file, creating it if it doesn't exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the file.
+    ///
+    /// # Returns
+    ///
+    /// The index, or a `MessageError` if the file can't be opened or read.
+    pub fn load(path: &str) -> Result<FilterIndex, MessageError> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(path)?;
+        let mut bytes = vec![];
+        file.read_to_end(&mut bytes)?;
+
+        let complete_len = bytes.len() - bytes.len() % FILTER_HEADER_RECORD_SIZE;
+        if complete_len < bytes.len() {
+            file.set_len(complete_len as u64)?;
+        }
+
+        let mut filter_headers = HashMap::new();
+        let mut stream = &bytes[..complete_len];
+        while !stream.is_empty() {
+            let block_hash = read_vec_from_bytes(&mut stream, 32)?;
+            let filter_header = read_vec_from_bytes(&mut stream, 32)?;
+            filter_headers.insert(block_hash, filter_header);
+        }
+
+        Ok(FilterIndex {
+            filters: HashMap::new(),
+            filter_headers,
+            file,
+        })
+    }
+
+    /// Computes the filter of a block, if it wasn't computed before.
+    ///
+    /// # Arguments
+    ///
+    /// * `block` - The block.
+    /// * `spent_pk_scripts` - The public key scripts of the outputs spent by the inputs of the block.
+    ///
+    /// # Returns
+    ///
+    /// An empty result, or a `MessageError` if the filter can't be computed.
+    pub fn connect_block(
+        &mut self,
+        block: &Block,
+        spent_pk_scripts: &[Vec<u8>],
+    ) -> Result<(), MessageError> {
+        let block_hash = block.get_header().get_header();
+        if self.filters.contains_key(&block_hash) {
+            return Ok(());
+        }
+        let filter = BlockFilter::new(block, spent_pk_scripts)?;
+        self.filters.insert(block_hash, filter);
+        Ok(())
+    }
+
+    /// Returns the filter of a block, or `None` if it wasn't computed.
+    pub fn get_filter(&self, block_hash: &[u8]) -> Option<&BlockFilter> {
+        self.filters.get(block_hash)
+    }
+
+    /// Returns the header of the filter of a block, appending to the file the headers computed on the way.
+    ///
+    /// # Arguments
+    ///
+    /// * `block_hash` - The hash of the block.
+    /// * `get_previous` - Returns the hash of the block before a known block, to walk back to the previous ones, or
+    ///   `None` for the genesis block.
+    ///
+    /// # Returns
+    ///
+    /// The header, `None` if the walk reaches a block without a filter nor a known header, or a `MessageError` if the
+    /// file can't be written.
+    pub fn get_filter_header(
+        &mut self,
+        block_hash: &[u8],
+        get_previous: impl Fn(&[u8]) -> Option<Vec<u8>>,
+    ) -> Result<Option<Vec<u8>>, MessageError> {
+        let mut pending = vec![];
+        let mut current = block_hash.to_vec();
+        let mut filter_header = loop {
+            if let Some(filter_header) = self.filter_headers.get(&current) {
+                break filter_header.clone();
+            }
+            if !self.filters.contains_key(&current) {
+                return Ok(None);
+            }
+            let previous = get_previous(&current);
+            pending.push(current);
+            match previous {
+                Some(previous) => current = previous,
+                None => break vec![0; 32],
+            }
+        };
+
+        let mut records = vec![];
+        for hash in pending.into_iter().rev() {
+            if let Some(filter) = self.filters.get(&hash) {
+                filter_header = filter.get_header(&filter_header);
+            }
+            records.extend(&hash);
+            records.extend(&filter_header);
+            self.filter_headers.insert(hash, filter_header.clone());
+        }
+        if !records.is_empty() {
+            self.file.write_all(&records)?;
+        }
+        Ok(Some(filter_header))
+    }
+}
+
+#[cfg(test)]
+mod filter_index_test {
+    use super::*;
+    use crate::block_mod::block_header::BlockHeader;
+    use std::fs;
+
+    /// Creates an empty block on top of the given one, with a coinbase that pays to `OP_TRUE`.
+    fn test_block(previous: &[u8], time: u32) -> Result<Block, MessageError> {
+        let header = BlockHeader::new(1, previous.to_vec(), vec![0; 32], time, 0, 0);
+        let mut bytes = header.as_bytes();
+        bytes.extend([1, 1, 0, 0, 0, 1]);
+        bytes.extend([0; 32]);
+        bytes.extend([0xff, 0xff, 0xff, 0xff, 2, 1, 1, 0xff, 0xff, 0xff, 0xff, 1]);
+        bytes.extend([0; 8]);
+        bytes.extend([1, 0x51, 0, 0, 0, 0]);
+        Block::from_bytes(&mut &bytes[..])
+    }
+
+    #[test]
+    fn test_filter_headers_are_chained_and_reloaded() -> Result<(), MessageError> {
+        let path =
+            std::env::temp_dir().join(format!("filter_index_test_{}.dat", std::process::id()));
+        let path = path.to_string_lossy().to_string();
+        let first = test_block(&[0; 32], 1)?;
+        let first_hash = first.get_header().get_header();
+        let second = test_block(&first_hash, 2)?;
+        let second_hash = second.get_header().get_header();
+        let detached = test_block(&[1; 32], 3)?;
+        let detached_hash = detached.get_header().get_header();
+        let blocks = HashMap::from([
+            (second_hash.clone(), second.clone()),
+            (detached_hash.clone(), detached.clone()),
+        ]);
+        let get_previous = |hash: &[u8]| {
+            blocks
+                .get(hash)
+                .map(|block| block.get_previuos_block_header().clone())
+        };
+
+        let mut filter_index = FilterIndex::load(&path)?;
+        filter_index.connect_block(&second, &[])?;
+        filter_index.connect_block(&first, &[])?;
+        filter_index.connect_block(&detached, &[])?;
+        let first_filter = BlockFilter::new(&first, &[])?;
+        let second_filter = BlockFilter::new(&second, &[])?;
+        assert!(first_filter.matches_any(&first_hash, &[vec![0x51]]));
+
+        let first_header = first_filter.get_header(&[0; 32]);
+        let second_header = second_filter.get_header(&first_header);
+        assert_eq!(
+            filter_index.get_filter_header(&second_hash, get_previous)?,
+            Some(second_header.clone())
+        );
+        assert_eq!(
+            filter_index.get_filter_header(&first_hash, get_previous)?,
+            Some(first_header)
+        );
+        assert_eq!(
+            filter_index.get_filter_header(&detached_hash, get_previous)?,
+            None
+        );
+        assert_eq!(
+            filter_index.get_filter_header(&[1; 32], get_previous)?,
+            None
+        );
+
+        OpenOptions::new()
+            .append(true)
+            .open(&path)?
+            .write_all(&[7; 10])?;
+        let mut reloaded = FilterIndex::load(&path)?;
+        let file_len = fs::metadata(&path)?.len();
+        fs::remove_file(&path)?;
+        assert_eq!(file_len, 2 * FILTER_HEADER_RECORD_SIZE as u64);
+        assert!(reloaded.get_filter(&second_hash).is_none());
+        assert_eq!(
+            reloaded.get_filter_header(&second_hash, |_: &[u8]| None)?,
+            Some(second_header)
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,69 @@
+use std::str::FromStr;
+
+use crate::network::network_constants::NETWORK_LIMITED_MIN_BLOCKS;
+
+const MEGABYTES_SUFFIX: &str = "MB";
+const BYTES_PER_MEGABYTE: usize = 1_000_000;
+
+/// How many of the most recent blocks a pruned node keeps, by number or by their size. The headers of the pruned blocks,
+/// the UTXO set and the undo data of the blocks are always kept.
+///
+/// It is parsed from a number of blocks, like `288`, or of megabytes followed by `MB`, like `550MB`. At least the last
+/// `NETWORK_LIMITED_MIN_BLOCKS` blocks are kept in any case, as BIP159 requires to serve them.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PruneTarget {
+    Blocks(usize),
+    Megabytes(usize),
+}
+
+impl PruneTarget {
+    /// Checks if the kept blocks exceed the target, so the oldest one has to be pruned.
+    ///
+    /// # Arguments
+    ///
+    /// * `block_count` - The number of kept blocks.
+    /// * `size` - The size in bytes of the kept blocks.
+    pub fn is_exceeded(&self, block_count: usize, size: usize) -> bool {
+        if block_count <= NETWORK_LIMITED_MIN_BLOCKS {
+            return false;
+        }
+        match self {
+            PruneTarget::Blocks(blocks) => block_count > *blocks,
+            PruneTarget::Megabytes(megabytes) => size > megabytes * BYTES_PER_MEGABYTE,
+        }
+    }
+}
+
+impl FromStr for PruneTarget {
+    type Err = ();
+
+    fn from_str(target: &str) -> Result<PruneTarget, ()> {
+        match target.strip_suffix(MEGABYTES_SUFFIX) {
+            Some(megabytes) => Ok(PruneTarget::Megabytes(
+                megabytes.trim().parse().map_err(|_| ())?,
+            )),
+            None => match target.trim().parse().map_err(|_| ())? {
+                blocks if blocks >= NETWORK_LIMITED_MIN_BLOCKS => Ok(PruneTarget::Blocks(blocks)),
+                _ => Err(()),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod prune_target_test {
+    use super::*;
+
+    #[test]
+    fn test_prune_target_from_str() {
+        assert_eq!("550MB".parse(), Ok(PruneTarget::Megabytes(550)));
+        assert_eq!("300".parse(), Ok(PruneTarget::Blocks(300)));
+        assert_eq!("100".parse::<PruneTarget>(), Err(()));
+        assert_eq!("MB".parse::<PruneTarget>(), Err(()));
+
+        assert!(PruneTarget::Blocks(300).is_exceeded(301, 0));
+        assert!(!PruneTarget::Blocks(300).is_exceeded(300, 0));
+        assert!(PruneTarget::Megabytes(1).is_exceeded(289, 1_000_001));
+        assert!(!PruneTarget::Megabytes(1).is_exceeded(288, 1_000_001));
+    }
+}
@@ -0,0 +1,170 @@
+const OP_0: u8 = 0x00;
+const OP_PUSHDATA1: u8 = 0x4c;
+const OP_PUSHDATA2: u8 = 0x4d;
+const OP_1: u8 = 0x51;
+const OP_DUP: u8 = 0x76;
+const OP_HASH160: u8 = 0xa9;
+const OP_EQUAL: u8 = 0x87;
+const OP_EQUALVERIFY: u8 = 0x88;
+const OP_CHECKSIG: u8 = 0xac;
+
+/// Represents the standard kinds of public key scripts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScriptType {
+    P2pkh,
+    P2sh,
+    P2wpkh,
+    P2wsh,
+    P2tr,
+    Unknown,
+}
+
+impl ScriptType {
+    /// Classifies a public key script.
+    ///
+    /// # Arguments
+    ///
+    /// * `pk_script` - The public key script of an output.
+    ///
+    /// # Returns
+    ///
+    /// The `ScriptType` of the script, or `ScriptType::Unknown` if it isn't a standard one.
+    pub fn from_pk_script(pk_script: &[u8]) -> ScriptType {
+        match pk_script {
+            [OP_DUP, OP_HASH160, 20, hash @ .., OP_EQUALVERIFY, OP_CHECKSIG] if hash.len() == 20 => {
+                ScriptType::P2pkh
+            }
+            [OP_HASH160, 20, hash @ .., OP_EQUAL] if hash.len() == 20 => ScriptType::P2sh,
+            [OP_0, 20, program @ ..] if program.len() == 20 => ScriptType::P2wpkh,
+            [OP_0, 32, program @ ..] if program.len() == 32 => ScriptType::P2wsh,
+            [OP_1, 32, program @ ..] if program.len() == 32 => ScriptType::P2tr,
+            _ => ScriptType::Unknown,
+        }
+    }
+}
+
+/// Returns the hash160 of the public key a P2PKH or P2WPKH script is locked to.
+pub fn get_pubkey_hash(pk_script: &[u8]) -> Option<&[u8]> {
+    match ScriptType::from_pk_script(pk_script) {
+        ScriptType::P2pkh => Some(&pk_script[3..23]),
+        ScriptType::P2wpkh => Some(&pk_script[2..22]),
+        _ => None,
+    }
+}
+
+/// Builds the P2PKH and P2WPKH scripts that pay to the same public key hash.
+///
+/// # Arguments
+///
+/// * `pk_script` - A P2PKH or P2WPKH public key script.
+///
+/// # Returns
+///
+/// Both scripts locked to the key of `pk_script`. Any other script is returned alone.
+pub fn get_pubkey_hash_scripts(pk_script: &[u8]) -> Vec<Vec<u8>> {
+    match get_pubkey_hash(pk_script) {
+        Some(h160) => vec![
+            [&[OP_DUP, OP_HASH160, 20], h160, &[OP_EQUALVERIFY, OP_CHECKSIG]].concat(),
+            [&[OP_0, 20], h160].concat(),
+        ],
+        None => vec![pk_script.to_vec()],
+    }
+}
+
+/// Returns the hash a P2SH or P2WSH script is locked to: the hash160 of the redeem script or the sha256 of the witness script.
+pub fn get_script_hash(pk_script: &[u8]) -> Option<&[u8]> {
+    match ScriptType::from_pk_script(pk_script) {
+        ScriptType::P2sh => Some(&pk_script[2..22]),
+        ScriptType::P2wsh => Some(&pk_script[2..34]),
+        _ => None,
+    }
+}
+
+/// Splits a script made only of push operations, such as a signature script, into the data it pushes.
+///
+/// # Arguments
+///
+/// * `script` - The script to split.
+///
+/// # Returns
+///
+/// The pushed data in order, where `OP_0` pushes an empty item, or `None` if the script has another opcode or a push is
+/// truncated.
+pub fn get_pushes(script: &[u8]) -> Option<Vec<&[u8]>> {
+    let mut pushes = vec![];
+    let mut rest = script;
+
+    while let Some((&opcode, after_opcode)) = rest.split_first() {
+        let (len, data) = match opcode {
+            OP_0 => (0, after_opcode),
+            len @ 1..=75 => (len as usize, after_opcode),
+            OP_PUSHDATA1 => {
+                let (&len, data) = after_opcode.split_first()?;
+                (len as usize, data)
+            }
+            OP_PUSHDATA2 => {
+                let len = after_opcode.get(..2)?;
+                (
+                    u16::from_le_bytes([len[0], len[1]]) as usize,
+                    &after_opcode[2..],
+                )
+            }
+            _ => return None,
+        };
+        pushes.push(data.get(..len)?);
+        rest = &data[len..];
+    }
+    Some(pushes)
+}
+
+#[cfg(test)]
+mod script_type_test {
+    use super::*;
+    use crate::messages::read_from_bytes::decode_hex;
+    use std::num::ParseIntError;
+
+    #[test]
+    fn test_script_types() -> Result<(), ParseIntError> {
+        let p2pkh = decode_hex("76a914d52ad7ca9b3d096a38e752c2018e6fbc40cdf26f88ac")?;
+        let p2sh = decode_hex("a914b472a266d0bd89c13706a4132ccfb16f7c3b9fcb87")?;
+        let p2wpkh = decode_hex("0014751e76e8199196d454941c45d1b3a323f1433bd6")?;
+        let p2wsh =
+            decode_hex("00201863143c14c5166804bd19203356da136c985678cd4d27a1b8c6329604903262")?;
+        let p2tr =
+            decode_hex("512079be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798")?;
+
+        assert_eq!(ScriptType::from_pk_script(&p2pkh), ScriptType::P2pkh);
+        assert_eq!(ScriptType::from_pk_script(&p2sh), ScriptType::P2sh);
+        assert_eq!(ScriptType::from_pk_script(&p2wpkh), ScriptType::P2wpkh);
+        assert_eq!(ScriptType::from_pk_script(&p2wsh), ScriptType::P2wsh);
+        assert_eq!(ScriptType::from_pk_script(&p2tr), ScriptType::P2tr);
+        assert_eq!(ScriptType::from_pk_script(&[0x6a]), ScriptType::Unknown);
+        Ok(())
+    }
+
+    #[test]
+    fn test_pubkey_hash_scripts() -> Result<(), ParseIntError> {
+        let p2pkh = decode_hex("76a914751e76e8199196d454941c45d1b3a323f1433bd688ac")?;
+        let p2wpkh = decode_hex("0014751e76e8199196d454941c45d1b3a323f1433bd6")?;
+
+        assert_eq!(
+            get_pubkey_hash_scripts(&p2pkh),
+            vec![p2pkh.clone(), p2wpkh.clone()]
+        );
+        assert_eq!(get_pubkey_hash_scripts(&p2wpkh), vec![p2pkh, p2wpkh]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_pushes() {
+        let script = [&[OP_0, 2, 1, 2, OP_PUSHDATA1, 76][..], &[3; 76]].concat();
+
+        assert_eq!(
+            get_pushes(&script),
+            Some(vec![&[][..], &[1, 2][..], &[3; 76][..]])
+        );
+        assert_eq!(get_pushes(&[OP_PUSHDATA2, 1, 0, 4]), Some(vec![&[4][..]]));
+        assert_eq!(get_pushes(&[3, 1, 2]), None);
+        assert_eq!(get_pushes(&[OP_DUP]), None);
+    }
+}
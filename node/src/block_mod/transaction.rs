@@ -2,11 +2,27 @@ use crate::block_mod::tx_in::TxIn;
 use crate::block_mod::tx_out::TxOut;
 use crate::messages::compact_size::CompactSizeUInt;
 use crate::messages::message_error::MessageError;
-use crate::messages::read_from_bytes::{read_i32_from_bytes, read_u32_from_bytes};
+use crate::messages::read_from_bytes::{
+    read_i32_from_bytes, read_u32_from_bytes, read_u8_from_bytes,
+};
+use bitcoin_hashes::sha256d;
 use bitcoin_hashes::Hash;
-use bitcoin_hashes::{sha256, sha256d};
 use std::io::Read;
 
+const SEGWIT_MARKER: u8 = 0x00;
+const SEGWIT_FLAG: u8 = 0x01;
+const OP_PUSHDATA1: u8 = 0x4c;
+const OP_PUSHDATA2: u8 = 0x4d;
+const OP_PUSHDATA4: u8 = 0x4e;
+const OP_CODESEPARATOR: u8 = 0xab;
+
+pub const SIGHASH_ALL: u32 = 0x01;
+pub const SIGHASH_NONE: u32 = 0x02;
+pub const SIGHASH_SINGLE: u32 = 0x03;
+pub const SIGHASH_ANYONECANPAY: u32 = 0x80;
+const SIGHASH_BASE_MASK: u32 = 0x1f;
+const WITNESS_SCALE_FACTOR: usize = 4;
+
 /// Represents a Transaction in the Bitcoin protocol.
 #[derive(Debug, Clone, PartialEq)]
 pub struct Transaction {
@@ -36,6 +52,9 @@ impl Transaction {
     }
     /// Creates a new `Transaction` instance from the provided byte stream.
     ///
+    /// Both the legacy and the SegWit (BIP144) serializations are accepted. In the latter an input count of zero is the
+    /// marker, followed by a flag byte, and the witness of every input comes after the outputs.
+    ///
     /// # Arguments
     /// * `stream` - A mutable reference to the byte stream.
     ///
@@ -43,7 +62,16 @@ impl Transaction {
     /// A `Result` containing the parsed `Transaction` instance or a `MessageError` if parsing fails.
     pub fn from_bytes(stream: &mut dyn Read) -> Result<Transaction, MessageError> {
         let version = read_i32_from_bytes(stream, true)?;
-        let tx_in_count = CompactSizeUInt::from_bytes(stream)?;
+        let mut tx_in_count = CompactSizeUInt::from_bytes(stream)?;
+        let mut segwit = false;
+
+        if tx_in_count.value() == 0 {
+            if read_u8_from_bytes(stream)? != SEGWIT_FLAG {
+                return Err(MessageError::ReadFromBytes);
+            }
+            segwit = true;
+            tx_in_count = CompactSizeUInt::from_bytes(stream)?;
+        }
         let mut tx_in_list: Vec<TxIn> = Vec::new();
 
         for _i in 0..tx_in_count.value() {
@@ -55,6 +83,12 @@ impl Transaction {
         for _i in 0..tx_out_count.value() {
             tx_out_list.push(TxOut::from_bytes(stream)?);
         }
+
+        if segwit {
+            for txin in tx_in_list.iter_mut() {
+                txin.witness_from_bytes(stream)?;
+            }
+        }
         let lock_time = read_u32_from_bytes(stream, true)?;
 
         Ok(Transaction {
@@ -69,9 +103,42 @@ impl Transaction {
 
     /// Converts the `Transaction` instance to a byte representation.
     ///
+    /// If any input has a witness the SegWit serialization is used, otherwise the legacy one.
+    ///
     /// # Returns
     /// A vector of bytes representing the `Transaction` instance.
     pub fn as_bytes(&self) -> Vec<u8> {
+        if !self.has_witness() {
+            return self.as_bytes_without_witness();
+        }
+        let mut buff = Vec::new();
+
+        buff.extend(self.version.to_le_bytes());
+        buff.extend([SEGWIT_MARKER, SEGWIT_FLAG]);
+        buff.extend(self.tx_in_count.as_bytes());
+
+        for txin in self.tx_in_list.iter() {
+            buff.extend(&txin.as_bytes());
+        }
+        buff.extend(self.tx_out_count.as_bytes());
+
+        for txout in self.tx_out_list.iter() {
+            buff.extend(&txout.as_bytes());
+        }
+
+        for txin in self.tx_in_list.iter() {
+            buff.extend(txin.witness_as_bytes());
+        }
+        buff.extend(self.lock_time.to_le_bytes());
+
+        buff
+    }
+
+    /// Converts the `Transaction` instance to its legacy byte representation, leaving out the witnesses.
+    ///
+    /// # Returns
+    /// A vector of bytes representing the `Transaction` instance as it is hashed to get its ID.
+    pub fn as_bytes_without_witness(&self) -> Vec<u8> {
         let mut buff = Vec::new();
 
         buff.extend(self.version.to_le_bytes());
@@ -93,14 +160,43 @@ impl Transaction {
 
     ///Calculates the transaction ID by hashing the serialized bytes of the `BlockHeader`.
     ///
+    /// The witnesses aren't hashed, so the ID of a SegWit transaction doesn't change when it is signed.
+    ///
     /// # Returns
     /// A vector of bytes representing the transaction.
     pub fn get_id(&self) -> Vec<u8> {
-        sha256d::Hash::hash(&self.as_bytes())
+        sha256d::Hash::hash(&self.as_bytes_without_witness())
             .to_byte_array()
             .to_vec()
     }
 
+    /// Returns the virtual size of the transaction as defined by BIP141, the size fee rates are measured in.
+    ///
+    /// The weight counts every byte of the legacy serialization four times and every byte that is only part of the SegWit
+    /// serialization (marker, flag and witnesses) once. The virtual size is the weight divided by four, rounded up.
+    pub fn get_vsize(&self) -> usize {
+        let base_size = self.as_bytes_without_witness().len();
+        let weight = base_size * (WITNESS_SCALE_FACTOR - 1) + self.as_bytes().len();
+        weight.div_ceil(WITNESS_SCALE_FACTOR)
+    }
+
+    /// Checks if any of the inputs has a witness.
+    pub fn has_witness(&self) -> bool {
+        self.tx_in_list
+            .iter()
+            .any(|txin| !txin.get_witness().is_empty())
+    }
+
+    /// Returns the version of the transaction.
+    pub fn get_version(&self) -> i32 {
+        self.version
+    }
+
+    /// Returns the lock time of the transaction.
+    pub fn get_lock_time(&self) -> u32 {
+        self.lock_time
+    }
+
     /// Returns a reference to the list of transaction inputs.
     pub fn get_tx_in_list(&self) -> &Vec<TxIn> {
         &self.tx_in_list
@@ -118,43 +214,169 @@ impl Transaction {
     ///
     /// * `index` - The index of the transaction input for which to compute the signature hash.
     /// * `pk_script` - The public key script associated with the transaction input.
+    /// * `hash_type` - The signature hash type, `SIGHASH_ALL`, `SIGHASH_NONE` or `SIGHASH_SINGLE`, optionally combined with `SIGHASH_ANYONECANPAY`.
     ///
     /// # Returns
     ///
-    /// A vector of bytes representing the computed signature hash.
-    pub fn sig_hash(&self, index: usize, pk_script: &[u8]) -> Vec<u8> {
+    /// A vector of bytes representing the computed signature hash, the double sha256 of the preimage that is signed.
+    ///
+    /// # Description
+    ///
+    /// The `OP_CODESEPARATOR`s of the script are removed and only the signed input keeps it, the rest get an empty script.
+    /// With `SIGHASH_NONE` no output is signed, and with `SIGHASH_SINGLE` only the one with the same index as the input
+    /// (the previous ones are replaced by empty outputs with value -1). In both cases the sequences of the other inputs are
+    /// set to 0 so they can be updated. With `SIGHASH_ANYONECANPAY` only the signed input is included.
+    ///
+    /// As in the reference implementation, if the index is out of range, or the hash type is `SIGHASH_SINGLE` and there is
+    /// no output with the index of the input, the hash is the number one instead of an error.
+    pub fn sig_hash(&self, index: usize, pk_script: &[u8], hash_type: u32) -> Vec<u8> {
+        let base_type = hash_type & SIGHASH_BASE_MASK;
+        let anyone_can_pay = hash_type & SIGHASH_ANYONECANPAY != 0;
+
+        if index >= self.tx_in_list.len()
+            || (base_type == SIGHASH_SINGLE && index >= self.tx_out_list.len())
+        {
+            let mut one = vec![0; 32];
+            one[0] = 1;
+            return one;
+        }
+
         let mut buffer = self.version.to_le_bytes().to_vec();
-        buffer.extend(self.tx_in_count.as_bytes());
+        let script_code = remove_code_separators(pk_script);
 
-        for (i, txin) in self.tx_in_list.iter().enumerate() {
-            if i == index {
-                let aux_txin = TxIn::new(
-                    txin.get_prev_output().get_tx_id().clone(),
-                    txin.get_prev_output().get_index(),
-                    pk_script.to_vec(),
-                    txin.get_sequence(),
-                );
-                buffer.extend(&aux_txin.as_bytes());
+        let signed_inputs: Vec<usize> = if anyone_can_pay {
+            vec![index]
+        } else {
+            (0..self.tx_in_list.len()).collect()
+        };
+        buffer.extend(CompactSizeUInt::from_number(signed_inputs.len() as u64).as_bytes());
+
+        for i in signed_inputs {
+            let txin = &self.tx_in_list[i];
+            let (script, sequence) = if i == index {
+                (script_code.clone(), txin.get_sequence())
+            } else if base_type == SIGHASH_NONE || base_type == SIGHASH_SINGLE {
+                (vec![], 0)
             } else {
-                let aux_txin = TxIn::new(
-                    txin.get_prev_output().get_tx_id().clone(),
-                    txin.get_prev_output().get_index(),
-                    vec![],
-                    txin.get_sequence(),
-                );
-                buffer.extend(&aux_txin.as_bytes());
+                (vec![], txin.get_sequence())
+            };
+            let aux_txin = TxIn::new(
+                txin.get_prev_output().get_tx_id().clone(),
+                txin.get_prev_output().get_index(),
+                script,
+                sequence,
+            );
+            buffer.extend(&aux_txin.as_bytes());
+        }
+
+        match base_type {
+            SIGHASH_NONE => buffer.extend(CompactSizeUInt::from_number(0).as_bytes()),
+            SIGHASH_SINGLE => {
+                buffer.extend(CompactSizeUInt::from_number(index as u64 + 1).as_bytes());
+                for _i in 0..index {
+                    buffer.extend(TxOut::new(-1, vec![]).as_bytes());
+                }
+                buffer.extend(self.tx_out_list[index].as_bytes());
+            }
+            _ => {
+                buffer.extend(self.tx_out_count.as_bytes());
+                for txout in self.tx_out_list.iter() {
+                    buffer.extend(txout.as_bytes());
+                }
             }
         }
-        buffer.extend(self.tx_out_count.as_bytes());
 
-        for txout in self.tx_out_list.iter() {
-            buffer.extend(txout.as_bytes());
+        buffer.extend(self.lock_time.to_le_bytes());
+        buffer.extend(hash_type.to_le_bytes());
+
+        sha256d::Hash::hash(&buffer).as_byte_array().to_vec()
+    }
+
+    /// Computes the signature hash of a witness v0 input as defined in BIP143.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index of the transaction input for which to compute the signature hash.
+    /// * `script_code` - The script code of the input, for P2WPKH the P2PKH script of the key hash.
+    /// * `amount` - The value of the output spent by the input.
+    /// * `hash_type` - The signature hash type, as in `sig_hash`.
+    ///
+    /// # Returns
+    ///
+    /// A vector of bytes representing the computed signature hash, the double sha256 of the preimage that is signed.
+    ///
+    /// # Description
+    ///
+    /// Unlike the legacy algorithm, the preimage commits to the amount being spent and reuses the hashes of all the
+    /// previous outputs (hashPrevouts), sequences (hashSequence) and outputs (hashOutputs), so hashing doesn't grow
+    /// quadratically with the number of inputs. The hash types replace those hashes with zeros instead of changing the
+    /// serialization: `SIGHASH_ANYONECANPAY` clears hashPrevouts and hashSequence, `SIGHASH_NONE` and `SIGHASH_SINGLE`
+    /// clear hashSequence and hashOutputs, and `SIGHASH_SINGLE` hashes only the output with the index of the input if
+    /// there is one.
+    pub fn witness_v0_sig_hash(
+        &self,
+        index: usize,
+        script_code: &[u8],
+        amount: i64,
+        hash_type: u32,
+    ) -> Vec<u8> {
+        let base_type = hash_type & SIGHASH_BASE_MASK;
+        let anyone_can_pay = hash_type & SIGHASH_ANYONECANPAY != 0;
+        let zero = [0_u8; 32];
+
+        let mut hash_prevouts = zero;
+        let mut hash_sequence = zero;
+        if !anyone_can_pay {
+            let mut prevouts = vec![];
+            let mut sequences = vec![];
+            for txin in self.tx_in_list.iter() {
+                prevouts.extend(txin.get_prev_output().as_bytes());
+                sequences.extend(txin.get_sequence().to_le_bytes());
+            }
+            hash_prevouts = sha256d::Hash::hash(&prevouts).to_byte_array();
+            if base_type != SIGHASH_SINGLE && base_type != SIGHASH_NONE {
+                hash_sequence = sha256d::Hash::hash(&sequences).to_byte_array();
+            }
         }
 
+        let mut hash_outputs = zero;
+        if base_type != SIGHASH_SINGLE && base_type != SIGHASH_NONE {
+            let mut outputs = vec![];
+            for txout in self.tx_out_list.iter() {
+                outputs.extend(txout.as_bytes());
+            }
+            hash_outputs = sha256d::Hash::hash(&outputs).to_byte_array();
+        } else if base_type == SIGHASH_SINGLE {
+            if let Some(txout) = self.tx_out_list.get(index) {
+                hash_outputs = sha256d::Hash::hash(&txout.as_bytes()).to_byte_array();
+            }
+        }
+
+        let mut buffer = self.version.to_le_bytes().to_vec();
+        buffer.extend(hash_prevouts);
+        buffer.extend(hash_sequence);
+        if let Some(txin) = self.tx_in_list.get(index) {
+            buffer.extend(txin.get_prev_output().as_bytes());
+            buffer.extend(CompactSizeUInt::from_number(script_code.len() as u64).as_bytes());
+            buffer.extend(script_code);
+            buffer.extend(amount.to_le_bytes());
+            buffer.extend(txin.get_sequence().to_le_bytes());
+        }
+        buffer.extend(hash_outputs);
         buffer.extend(self.lock_time.to_le_bytes());
-        buffer.extend((1_u32).to_le_bytes());
+        buffer.extend(hash_type.to_le_bytes());
+
+        sha256d::Hash::hash(&buffer).as_byte_array().to_vec()
+    }
 
-        sha256::Hash::hash(&buffer).as_byte_array().to_vec()
+    /// Sets the witness for the transaction input at the given index.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index of the transaction input for which to set the witness.
+    /// * `witness` - The witness stack, for P2WPKH the signature and the public key.
+    pub fn set_witness(&mut self, index: usize, witness: Vec<Vec<u8>>) {
+        self.tx_in_list[index].set_witness(witness);
     }
 
     /// Sets the signature for the transaction input at the given index with the provided signature script.
@@ -168,6 +390,43 @@ impl Transaction {
     }
 }
 
+/// Removes the `OP_CODESEPARATOR` opcodes of a script, keeping the bytes pushed as data.
+///
+/// If a push is truncated the rest of the script is kept as it is.
+fn remove_code_separators(script: &[u8]) -> Vec<u8> {
+    let mut result = vec![];
+    let mut i = 0;
+
+    while i < script.len() {
+        let opcode = script[i];
+        let (header_len, data_len) = match opcode {
+            0x01..=0x4b => (1, opcode as usize),
+            OP_PUSHDATA1 if i + 1 < script.len() => (2, script[i + 1] as usize),
+            OP_PUSHDATA2 if i + 2 < script.len() => (
+                3,
+                u16::from_le_bytes([script[i + 1], script[i + 2]]) as usize,
+            ),
+            OP_PUSHDATA4 if i + 4 < script.len() => {
+                let len = [script[i + 1], script[i + 2], script[i + 3], script[i + 4]];
+                (5, u32::from_le_bytes(len) as usize)
+            }
+            OP_PUSHDATA1 | OP_PUSHDATA2 | OP_PUSHDATA4 => (script.len() - i, 0),
+            _ => (1, 0),
+        };
+        let end = i + header_len + data_len;
+
+        if end > script.len() {
+            result.extend(&script[i..]);
+            break;
+        }
+        if opcode != OP_CODESEPARATOR {
+            result.extend(&script[i..end]);
+        }
+        i = end;
+    }
+    result
+}
+
 impl std::fmt::Display for Transaction {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         writeln!(f, "      Version: {}", self.version)?;
@@ -189,3 +448,201 @@ impl std::fmt::Display for Transaction {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod transaction_test {
+    use super::*;
+    use crate::messages::read_from_bytes::{decode_hex, encode_hex};
+
+    #[test]
+    fn test_witness_v0_sig_hash() -> Result<(), MessageError> {
+        // Native P2WPKH example from BIP143
+        let bytes = decode_hex("0100000002fff7f7881a8099afa6940d42d1e7f6362bec38171ea3edf433541db4e4ad969f0000000000eeffffffef51e1b804cc89d182d279655c3aa89e815b1b309fe287d9b2b55d57b90ec68a0100000000ffffffff02202cb206000000001976a9148280b37df378db99f66f85c95a783a76ac7a6d5988ac9093510d000000001976a9143bde42dbee7e4dbe6a21b2d50ce2f0167faa815988ac11000000").map_err(|_| MessageError::ReadFromBytes)?;
+        let transaction = Transaction::from_bytes(&mut bytes.as_slice())?;
+        let script_code = decode_hex("76a9141d0f172a0ecb48aee1be1f2687d2963ae33f71a188ac")
+            .map_err(|_| MessageError::ReadFromBytes)?;
+
+        let sig_hash = transaction.witness_v0_sig_hash(1, &script_code, 600000000, SIGHASH_ALL);
+
+        assert_eq!(
+            encode_hex(&sig_hash).map_err(|_| MessageError::ReadFromBytes)?,
+            "c37af31116d1b27caf68aae9e3ac82f1477929014d5b917657d0eb49478cb670"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_witness_v0_sig_hash_types() -> Result<(), MessageError> {
+        // P2SH-P2WSH 6-of-6 multisig example from BIP143, signed with every hash type
+        let bytes = decode_hex("010000000136641869ca081e70f394c6948e8af409e18b619df2ed74aa106c1ca29787b96e0100000000ffffffff0200e9a435000000001976a914389ffce9cd9ae88dcc0631e88a821ffdbe9bfe2688acc0832f05000000001976a9147480a33f950689af511e6e84c138dbbd3c3ee41588ac00000000").map_err(|_| MessageError::ReadFromBytes)?;
+        let transaction = Transaction::from_bytes(&mut bytes.as_slice())?;
+        let witness_script = decode_hex("56210307b8ae49ac90a048e9b53357a2354b3334e9c8bee813ecb98e99a7e07e8c3ba32103b28f0c28bfab54554ae8c658ac5c3e0ce6e79ad336331f78c428dd43eea8449b21034b8113d703413d57761b8b9781957b8c0ac1dfe69f492580ca4195f50376ba4a21033400f6afecb833092a9a21cfdf1ed1376e58c5d1f47de74683123987e967a8f42103a6d48b1131e94ba04d9737d61acdaa1322008af9602b3b14862c07a1789aac162102d8b661b0b3302ee2f162b09e07a55ad5dfbe673a9f01d9f0c19617681024306b56ae").map_err(|_| MessageError::ReadFromBytes)?;
+
+        let expected = [
+            (
+                SIGHASH_ALL,
+                "185c0be5263dce5b4bb50a047973c1b6272bfbd0103a89444597dc40b248ee7c",
+            ),
+            (
+                SIGHASH_NONE,
+                "e9733bc60ea13c95c6527066bb975a2ff29a925e80aa14c213f686cbae5d2f36",
+            ),
+            (
+                SIGHASH_SINGLE,
+                "1e1f1c303dc025bd664acb72e583e933fae4cff9148bf78c157d1e8f78530aea",
+            ),
+            (
+                SIGHASH_ALL | SIGHASH_ANYONECANPAY,
+                "2a67f03e63a6a422125878b40b82da593be8d4efaafe88ee528af6e5a9955c6e",
+            ),
+            (
+                SIGHASH_NONE | SIGHASH_ANYONECANPAY,
+                "781ba15f3779d5542ce8ecb5c18716733a5ee42a6f51488ec96154934e2c890a",
+            ),
+            (
+                SIGHASH_SINGLE | SIGHASH_ANYONECANPAY,
+                "511e8e52ed574121fc1b654970395502128263f62662e076dc6baf05c2e6a99b",
+            ),
+        ];
+        for (hash_type, sig_hash) in expected {
+            let result = transaction.witness_v0_sig_hash(0, &witness_script, 987654321, hash_type);
+            assert_eq!(
+                encode_hex(&result).map_err(|_| MessageError::ReadFromBytes)?,
+                sig_hash
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_legacy_sig_hash_vector() -> Result<(), MessageError> {
+        // First vector of Bitcoin Core's sighash.json, with an unusual hash type and an empty script
+        let bytes = decode_hex("907c2bc503ade11cc3b04eb2918b6f547b0630ab569273824748c87ea14b0696526c66ba740200000004ab65ababfd1f9bdd4ef073c7afc4ae00da8a66f429c917a0081ad1e1dabce28d373eab81d8628de802000000096aab5253ab52000052ad042b5f25efb33beec9f3364e8a9139e8439d9d7e26529c3c30b6c3fd89f8684cfd68ea0200000009ab53526500636a52ab599ac2fe02a526ed040000000008535300516352515164370e010000000003006300ab2ec229").map_err(|_| MessageError::ReadFromBytes)?;
+        let transaction = Transaction::from_bytes(&mut bytes.as_slice())?;
+
+        let mut sig_hash = transaction.sig_hash(2, &[], 1864164639);
+        sig_hash.reverse();
+
+        assert_eq!(
+            encode_hex(&sig_hash).map_err(|_| MessageError::ReadFromBytes)?,
+            "31af167a6cf3f9d5f6875caa4d31704ceb0eba078d132b78dab52c3b8997317e"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_legacy_sig_hash_types() -> Result<(), MessageError> {
+        // The transaction of the first vector of sighash.json, signing its second input with a script with
+        // OP_CODESEPARATORs. The hashes aren't sighash.json entries but the ones this serializer gives, kept as regression
+        // values so a change to any hash type is noticed.
+        let bytes = decode_hex("907c2bc503ade11cc3b04eb2918b6f547b0630ab569273824748c87ea14b0696526c66ba740200000004ab65ababfd1f9bdd4ef073c7afc4ae00da8a66f429c917a0081ad1e1dabce28d373eab81d8628de802000000096aab5253ab52000052ad042b5f25efb33beec9f3364e8a9139e8439d9d7e26529c3c30b6c3fd89f8684cfd68ea0200000009ab53526500636a52ab599ac2fe02a526ed040000000008535300516352515164370e010000000003006300ab2ec229").map_err(|_| MessageError::ReadFromBytes)?;
+        let transaction = Transaction::from_bytes(&mut bytes.as_slice())?;
+        let pk_script = decode_hex("ab76a9141d0f172a0ecb48aee1be1f2687d2963ae33f71a1ab88ac")
+            .map_err(|_| MessageError::ReadFromBytes)?;
+
+        let vectors = [
+            (
+                SIGHASH_ALL,
+                "d4fded2b7d5e2f5907efc0e8e63928025b26b0a98a632952bc18de9a3db5d5e8",
+            ),
+            (
+                SIGHASH_NONE,
+                "0007d771ca710a276b881661dc9cd1db9304096bc196dd423122720892eef573",
+            ),
+            (
+                SIGHASH_SINGLE,
+                "bac6b97f74e5b73434ff00fcc758f531808d984535876b3fc0853a9da3db41e7",
+            ),
+            (
+                SIGHASH_ALL | SIGHASH_ANYONECANPAY,
+                "f0bf06995f65b1f4c2ccb5b2d388a1756bf01653bb8f89aec38da2eb3cb4f880",
+            ),
+            (
+                SIGHASH_NONE | SIGHASH_ANYONECANPAY,
+                "311d38f4a4e9ba036e0a1bec91cf70ab79364f41e6b6cc701995153bb7e65146",
+            ),
+            (
+                SIGHASH_SINGLE | SIGHASH_ANYONECANPAY,
+                "e4a5f6aa760aa42ed11d94a520b3acbc3b786a38dc797f84318abf499febddf8",
+            ),
+        ];
+        for (hash_type, expected) in vectors {
+            let mut sig_hash = transaction.sig_hash(1, &pk_script, hash_type);
+            sig_hash.reverse();
+            assert_eq!(
+                encode_hex(&sig_hash).map_err(|_| MessageError::ReadFromBytes)?,
+                expected
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_sig_hash_single_without_output() -> Result<(), MessageError> {
+        let bytes = decode_hex("0100000002fff7f7881a8099afa6940d42d1e7f6362bec38171ea3edf433541db4e4ad969f0000000000eeffffffef51e1b804cc89d182d279655c3aa89e815b1b309fe287d9b2b55d57b90ec68a0100000000ffffffff01202cb206000000001976a9148280b37df378db99f66f85c95a783a76ac7a6d5988ac11000000").map_err(|_| MessageError::ReadFromBytes)?;
+        let transaction = Transaction::from_bytes(&mut bytes.as_slice())?;
+        let mut one = vec![0; 32];
+        one[0] = 1;
+
+        assert_eq!(transaction.sig_hash(1, &[], SIGHASH_SINGLE), one);
+        assert_eq!(transaction.sig_hash(2, &[], SIGHASH_ALL), one);
+        assert_ne!(transaction.sig_hash(0, &[], SIGHASH_SINGLE), one);
+        Ok(())
+    }
+
+    #[test]
+    fn test_sig_hash_types_commitments() -> Result<(), MessageError> {
+        let bytes = decode_hex("0100000002fff7f7881a8099afa6940d42d1e7f6362bec38171ea3edf433541db4e4ad969f0000000000eeffffffef51e1b804cc89d182d279655c3aa89e815b1b309fe287d9b2b55d57b90ec68a0100000000ffffffff02202cb206000000001976a9148280b37df378db99f66f85c95a783a76ac7a6d5988ac9093510d000000001976a9143bde42dbee7e4dbe6a21b2d50ce2f0167faa815988ac11000000").map_err(|_| MessageError::ReadFromBytes)?;
+        let transaction = Transaction::from_bytes(&mut bytes.as_slice())?;
+        let pk_script = decode_hex("76a9141d0f172a0ecb48aee1be1f2687d2963ae33f71a188ac")
+            .map_err(|_| MessageError::ReadFromBytes)?;
+
+        let mut other_outputs = transaction.clone();
+        other_outputs.tx_out_list[1] = TxOut::new(1, pk_script.clone());
+        let mut other_input = transaction.clone();
+        other_input.tx_in_list.remove(0);
+        other_input.tx_in_count = CompactSizeUInt::from_number(1);
+
+        for hash_type in [SIGHASH_NONE, SIGHASH_SINGLE] {
+            assert_eq!(
+                transaction.sig_hash(0, &pk_script, hash_type),
+                other_outputs.sig_hash(0, &pk_script, hash_type)
+            );
+        }
+        assert_ne!(
+            transaction.sig_hash(0, &pk_script, SIGHASH_ALL),
+            other_outputs.sig_hash(0, &pk_script, SIGHASH_ALL)
+        );
+        assert_eq!(
+            transaction.sig_hash(1, &pk_script, SIGHASH_ALL | SIGHASH_ANYONECANPAY),
+            other_input.sig_hash(0, &pk_script, SIGHASH_ALL | SIGHASH_ANYONECANPAY)
+        );
+        assert_ne!(
+            transaction.sig_hash(1, &pk_script, SIGHASH_ALL),
+            other_input.sig_hash(0, &pk_script, SIGHASH_ALL)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_segwit_serialization() -> Result<(), MessageError> {
+        let bytes = decode_hex("0100000002fff7f7881a8099afa6940d42d1e7f6362bec38171ea3edf433541db4e4ad969f0000000000eeffffffef51e1b804cc89d182d279655c3aa89e815b1b309fe287d9b2b55d57b90ec68a0100000000ffffffff02202cb206000000001976a9148280b37df378db99f66f85c95a783a76ac7a6d5988ac9093510d000000001976a9143bde42dbee7e4dbe6a21b2d50ce2f0167faa815988ac11000000").map_err(|_| MessageError::ReadFromBytes)?;
+        let mut transaction = Transaction::from_bytes(&mut bytes.as_slice())?;
+        let id = transaction.get_id();
+        assert!(!transaction.has_witness());
+        assert_eq!(transaction.get_vsize(), bytes.len());
+
+        transaction.set_witness(1, vec![vec![0x30; 71], vec![0x02; 33]]);
+        let segwit_bytes = transaction.as_bytes();
+        // Marker, flag, an empty witness and a 107 bytes one weigh 110, a quarter of it rounded up.
+        assert_eq!(transaction.get_vsize(), bytes.len() + 28);
+
+        assert_eq!(segwit_bytes[4..6], [0x00, 0x01]);
+        assert_eq!(transaction.get_id(), id);
+        assert_eq!(
+            Transaction::from_bytes(&mut segwit_bytes.as_slice())?,
+            transaction
+        );
+        Ok(())
+    }
+}
@@ -13,6 +13,7 @@ pub struct TxIn {
     script_bytes: CompactSizeUInt,
     signature_script: Vec<u8>,
     sequence: u32,
+    witness: Vec<Vec<u8>>,
 }
 
 impl TxIn {
@@ -37,11 +38,14 @@ impl TxIn {
             script_bytes,
             signature_script,
             sequence,
+            witness: vec![],
         })
     }
 
     /// Converts the `TxIn` (transaction input) into a byte representation.
     ///
+    /// The witness isn't part of the input serialization, `Transaction` serializes it after the outputs.
+    ///
     /// # Returns
     ///
     /// A `Vec<u8>` containing the byte representation of the `TxIn`.
@@ -74,6 +78,7 @@ impl TxIn {
             script_bytes: CompactSizeUInt::from_number(signature_script.len() as u64),
             signature_script,
             sequence,
+            witness: vec![],
         }
     }
 
@@ -89,4 +94,46 @@ impl TxIn {
     pub fn get_signature_script(&self) -> &Vec<u8> {
         &self.signature_script
     }
+
+    /// Returns the witness stack of the input, empty for inputs that don't spend SegWit outputs.
+    pub fn get_witness(&self) -> &Vec<Vec<u8>> {
+        &self.witness
+    }
+
+    pub fn set_witness(&mut self, witness: Vec<Vec<u8>>) {
+        self.witness = witness;
+    }
+
+    /// Parses the witness stack of the input from a byte stream.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream` - A mutable reference to a byte stream implementing the `Read` trait.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(())` if parsing is successful.
+    /// - `Err(MessageError)` if an error occurs during parsing.
+    pub fn witness_from_bytes(&mut self, stream: &mut dyn Read) -> Result<(), MessageError> {
+        let item_count = CompactSizeUInt::from_bytes(stream)?;
+        let mut witness = vec![];
+
+        for _i in 0..item_count.value() {
+            let item_size = CompactSizeUInt::from_bytes(stream)?;
+            witness.push(read_vec_from_bytes(stream, item_size.value() as usize)?);
+        }
+        self.witness = witness;
+        Ok(())
+    }
+
+    /// Converts the witness stack of the input into its byte representation.
+    pub fn witness_as_bytes(&self) -> Vec<u8> {
+        let mut buff = CompactSizeUInt::from_number(self.witness.len() as u64).as_bytes();
+
+        for item in self.witness.iter() {
+            buff.extend(CompactSizeUInt::from_number(item.len() as u64).as_bytes());
+            buff.extend(item);
+        }
+        buff
+    }
 }
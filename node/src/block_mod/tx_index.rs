@@ -0,0 +1,150 @@
+use std::{
+    collections::HashMap,
+    fs::{File, OpenOptions},
+    io::{Read, Write},
+};
+
+use crate::messages::{
+    message_error::MessageError,
+    read_from_bytes::{read_u32_from_bytes, read_vec_from_bytes},
+};
+
+use super::block::Block;
+
+const TX_INDEX_RECORD_SIZE: usize = 68;
+
+/// Indexes the transactions of the blocks by their id, so a confirmed transaction is found without knowing its block.
+///
+/// Each id maps to the hash of each block that includes the transaction and its position in the ids of the block, the
+/// coinbase being the first one. A transaction can be in several blocks when a reorganization mines it again, so the
+/// caller picks the one of the active chain. The index is kept in a file: it is loaded when the node starts and each new entry is
+/// appended to it, as the id, the hash of the block and the position as a little endian u32. A record cut short when
+/// the node stopped is removed when the file is loaded, so the next ones are read from the right place.
+#[derive(Debug)]
+pub struct TxIndex {
+    entries: HashMap<Vec<u8>, Vec<(Vec<u8>, u32)>>,
+    file: File,
+}
+
+impl TxIndex {
+    /// Loads the index from its file, creating it if it doesn't exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the file.
+    ///
+    /// # Returns
+    ///
+    /// The index, or a `MessageError` if the file can't be opened or read.
+    pub fn load(path: &str) -> Result<TxIndex, MessageError> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(path)?;
+        let mut bytes = vec![];
+        file.read_to_end(&mut bytes)?;
+
+        let complete_len = bytes.len() - bytes.len() % TX_INDEX_RECORD_SIZE;
+        if complete_len < bytes.len() {
+            file.set_len(complete_len as u64)?;
+        }
+
+        let mut entries = HashMap::new();
+        let mut stream = &bytes[..complete_len];
+        while !stream.is_empty() {
+            let tx_id = read_vec_from_bytes(&mut stream, 32)?;
+            let block_hash = read_vec_from_bytes(&mut stream, 32)?;
+            let position = read_u32_from_bytes(&mut stream, true)?;
+            let locations: &mut Vec<(Vec<u8>, u32)> = entries.entry(tx_id).or_default();
+            if !locations.contains(&(block_hash.clone(), position)) {
+                locations.push((block_hash, position));
+            }
+        }
+
+        Ok(TxIndex { entries, file })
+    }
+
+    /// Indexes the transactions of a block, appending to the file the entries that are new.
+    pub fn connect_block(&mut self, block: &Block) -> Result<(), MessageError> {
+        let block_hash = block.get_header().get_header();
+        let mut records = vec![];
+
+        for (position, tx_id) in block.get_txn_ids().into_iter().enumerate() {
+            let entry = (block_hash.clone(), position as u32);
+            let locations = self.entries.entry(tx_id.clone()).or_default();
+            if locations.contains(&entry) {
+                continue;
+            }
+            records.extend(&tx_id);
+            records.extend(&block_hash);
+            records.extend((position as u32).to_le_bytes());
+            locations.push(entry);
+        }
+
+        if !records.is_empty() {
+            self.file.write_all(&records)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the hash of each indexed block that includes a transaction and the position of the transaction in its
+    /// ids, none if no indexed block does.
+    pub fn get(&self, tx_id: &[u8]) -> &[(Vec<u8>, u32)] {
+        self.entries.get(tx_id).map_or(&[], Vec::as_slice)
+    }
+}
+
+#[cfg(test)]
+mod tx_index_test {
+    use super::*;
+    use crate::block_mod::block_header::BlockHeader;
+    use std::fs;
+
+    #[test]
+    fn test_tx_index_is_reloaded() -> Result<(), MessageError> {
+        let path = std::env::temp_dir().join(format!("tx_index_test_{}.dat", std::process::id()));
+        let path = path.to_string_lossy().to_string();
+        let header = BlockHeader::new(1, vec![0; 32], vec![0; 32], 1, 0, 0);
+        let mut bytes = header.as_bytes();
+        bytes.push(1);
+        bytes.extend([1, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        let block = Block::from_bytes(&mut &bytes[..])?;
+        let coinbase_id = block.get_coinbase().get_id();
+
+        let mut tx_index = TxIndex::load(&path)?;
+        tx_index.connect_block(&block)?;
+        tx_index.connect_block(&block)?;
+        assert_eq!(tx_index.get(&coinbase_id), [(header.get_header(), 0)]);
+
+        let reloaded = TxIndex::load(&path)?;
+        fs::remove_file(&path)?;
+        assert_eq!(reloaded.get(&coinbase_id), [(header.get_header(), 0)]);
+        assert_eq!(reloaded.entries.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_partial_record_is_removed() -> Result<(), MessageError> {
+        let path =
+            std::env::temp_dir().join(format!("tx_index_partial_test_{}.dat", std::process::id()));
+        let path = path.to_string_lossy().to_string();
+        let header = BlockHeader::new(1, vec![0; 32], vec![0; 32], 2, 0, 0);
+        let mut bytes = header.as_bytes();
+        bytes.push(1);
+        bytes.extend([1, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        let block = Block::from_bytes(&mut &bytes[..])?;
+        let coinbase_id = block.get_coinbase().get_id();
+        fs::write(&path, [7; TX_INDEX_RECORD_SIZE + 10])?;
+
+        let mut tx_index = TxIndex::load(&path)?;
+        assert_eq!(tx_index.entries.len(), 1);
+        tx_index.connect_block(&block)?;
+
+        let reloaded = TxIndex::load(&path)?;
+        fs::remove_file(&path)?;
+        assert_eq!(reloaded.get(&coinbase_id), [(header.get_header(), 0)]);
+        assert_eq!(reloaded.entries.len(), 2);
+        Ok(())
+    }
+}
@@ -0,0 +1,230 @@
+use std::{
+    collections::HashMap,
+    fs::{self, File, OpenOptions},
+    io::{Read, Seek, SeekFrom, Write},
+    path::Path,
+};
+
+use crate::messages::message_error::MessageError;
+
+use super::block_undo::BlockUndo;
+
+const MAX_UNDO_FILE_SIZE: u64 = 16 * 1024 * 1024;
+
+/// The position of the undo data of a block in the undo files.
+#[derive(Debug, Clone, Copy)]
+struct IndexEntry {
+    file: u32,
+    offset: u64,
+    size: u32,
+}
+
+/// Keeps the undo data of the blocks of the active chain, to disconnect them from the UTXO set in a reorganization.
+///
+/// Without files, the undo data is kept in memory. With them, each block's undo data is appended to the current undo
+/// file, a new one is started when it reaches `MAX_UNDO_FILE_SIZE`, and it is read back with the index of its positions
+/// when the block is disconnected. A file is removed once none of the undo data in it is kept, so the data of the
+/// disconnected and pruned blocks doesn't take space forever. The files are named after the given path followed by
+/// their number, and the ones of a previous run are removed when they are opened, as the blocks are downloaded again.
+#[derive(Debug, Default)]
+pub struct UndoStore {
+    path: Option<String>,
+    index: HashMap<Vec<u8>, IndexEntry>,
+    file_entries: HashMap<u32, usize>,
+    current_file: u32,
+    current_size: u64,
+    undo_data: HashMap<Vec<u8>, BlockUndo>,
+}
+
+impl UndoStore {
+    /// Creates a store that keeps the undo data in memory.
+    pub fn new() -> UndoStore {
+        UndoStore::default()
+    }
+
+    /// Creates a store that keeps the undo data in files, removing the files of a previous run.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path the names of the files start with.
+    ///
+    /// # Returns
+    ///
+    /// The store, or a `MessageError` if the old files can't be removed.
+    pub fn open(path: &str) -> Result<UndoStore, MessageError> {
+        let mut file = 0;
+        while Path::new(&get_file_path(path, file)).exists() {
+            fs::remove_file(get_file_path(path, file))?;
+            file += 1;
+        }
+        Ok(UndoStore {
+            path: Some(path.to_string()),
+            ..UndoStore::default()
+        })
+    }
+
+    /// Adds the undo data of a block, appending it to the current file if the store has files.
+    ///
+    /// # Returns
+    ///
+    /// An empty result, or a `MessageError` if it can't be written.
+    pub fn insert(&mut self, block_hash: Vec<u8>, undo: BlockUndo) -> Result<(), MessageError> {
+        let path = match &self.path {
+            Some(path) => path,
+            None => {
+                self.undo_data.insert(block_hash, undo);
+                return Ok(());
+            }
+        };
+        let bytes = undo.as_bytes();
+        let size = bytes.len() as u64;
+        if self.current_size > 0 && self.current_size + size > MAX_UNDO_FILE_SIZE {
+            self.current_file += 1;
+            self.current_size = 0;
+        }
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(get_file_path(path, self.current_file))?
+            .write_all(&bytes)?;
+
+        let entry = IndexEntry {
+            file: self.current_file,
+            offset: self.current_size,
+            size: bytes.len() as u32,
+        };
+        if let Some(replaced) = self.index.insert(block_hash, entry) {
+            self.release(replaced)?;
+        }
+        *self.file_entries.entry(self.current_file).or_default() += 1;
+        self.current_size += size;
+        Ok(())
+    }
+
+    /// Removes the undo data of a block and returns it, read from its file if the store has files.
+    ///
+    /// # Returns
+    ///
+    /// The undo data, `None` if it wasn't stored, or a `MessageError` if it can't be read.
+    pub fn remove(&mut self, block_hash: &[u8]) -> Result<Option<BlockUndo>, MessageError> {
+        let (path, entry) = match (&self.path, self.index.remove(block_hash)) {
+            (Some(path), Some(entry)) => (path, entry),
+            _ => return Ok(self.undo_data.remove(block_hash)),
+        };
+        let mut file = File::open(get_file_path(path, entry.file))?;
+        file.seek(SeekFrom::Start(entry.offset))?;
+        let mut bytes = vec![0; entry.size as usize];
+        file.read_exact(&mut bytes)?;
+        self.release(entry)?;
+        Ok(Some(BlockUndo::from_bytes(&mut &bytes[..])?))
+    }
+
+    /// Discards the undo data of a block without reading it, such as the one of a pruned block.
+    ///
+    /// # Returns
+    ///
+    /// An empty result, or a `MessageError` if its file can't be removed.
+    pub fn discard(&mut self, block_hash: &[u8]) -> Result<(), MessageError> {
+        self.undo_data.remove(block_hash);
+        match self.index.remove(block_hash) {
+            Some(entry) => self.release(entry),
+            None => Ok(()),
+        }
+    }
+
+    pub fn contains(&self, block_hash: &[u8]) -> bool {
+        match self.path {
+            Some(_) => self.index.contains_key(block_hash),
+            None => self.undo_data.contains_key(block_hash),
+        }
+    }
+
+    /// Returns the hashes of the blocks whose undo data is stored.
+    pub fn get_hashes(&self) -> Vec<Vec<u8>> {
+        match self.path {
+            Some(_) => self.index.keys().cloned().collect(),
+            None => self.undo_data.keys().cloned().collect(),
+        }
+    }
+
+    /// Forgets an entry of a file, removing the file if no other entry of it is kept and it isn't the current one.
+    fn release(&mut self, entry: IndexEntry) -> Result<(), MessageError> {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        let remaining = self.file_entries.entry(entry.file).or_default();
+        *remaining = remaining.saturating_sub(1);
+        if *remaining == 0 && entry.file != self.current_file {
+            self.file_entries.remove(&entry.file);
+            fs::remove_file(get_file_path(path, entry.file))?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the path of an undo file, the given path followed by the number of the file.
+fn get_file_path(path: &str, file: u32) -> String {
+    format!("{}.{:05}", path, file)
+}
+
+#[cfg(test)]
+mod undo_store_test {
+    use super::*;
+    use crate::block_mod::{outpoint::Outpoint, tx_out::TxOut};
+
+    #[test]
+    fn test_undo_data_is_read_back_from_the_file() -> Result<(), MessageError> {
+        let path = std::env::temp_dir().join(format!("undo_store_test_{}.dat", std::process::id()));
+        let path = path.to_string_lossy().to_string();
+        let mut undo_store = UndoStore::open(&path)?;
+
+        let mut first = BlockUndo::new();
+        first.add_tx_id(vec![1; 32]);
+        let mut second = BlockUndo::new();
+        second.add_tx_id(vec![2; 32]);
+        second.add_tx_id(vec![3; 32]);
+        second.add_spent_output(Outpoint::new(vec![1; 32], 0), TxOut::new(1000, vec![0x51]));
+        undo_store.insert(vec![1; 32], first.clone())?;
+        undo_store.insert(vec![2; 32], second.clone())?;
+        let file_len = fs::metadata(get_file_path(&path, 0))?.len();
+
+        assert_eq!(undo_store.remove(&[2; 32])?, Some(second));
+        assert_eq!(undo_store.remove(&[2; 32])?, None);
+        assert_eq!(undo_store.get_hashes(), vec![vec![1; 32]]);
+        assert_eq!(undo_store.remove(&[1; 32])?, Some(first));
+        fs::remove_file(get_file_path(&path, 0))?;
+        assert_eq!(file_len, undo_store.current_size);
+        Ok(())
+    }
+
+    #[test]
+    fn test_files_without_undo_data_are_removed() -> Result<(), MessageError> {
+        let path =
+            std::env::temp_dir().join(format!("undo_store_files_test_{}.dat", std::process::id()));
+        let path = path.to_string_lossy().to_string();
+        let mut undo_store = UndoStore::open(&path)?;
+
+        let mut undo = BlockUndo::new();
+        let pk_script = vec![0x51; MAX_UNDO_FILE_SIZE as usize / 4];
+        undo.add_spent_output(Outpoint::new(vec![1; 32], 0), TxOut::new(1000, pk_script));
+        for i in 0..5 {
+            undo_store.insert(vec![i; 32], undo.clone())?;
+        }
+        assert!(Path::new(&get_file_path(&path, 1)).exists());
+
+        // The first file holds the undo data of the first three blocks.
+        for i in 0..2 {
+            undo_store.discard(&[i; 32])?;
+        }
+        assert!(Path::new(&get_file_path(&path, 0)).exists());
+        undo_store.discard(&[2; 32])?;
+        assert!(!Path::new(&get_file_path(&path, 0)).exists());
+
+        assert_eq!(undo_store.remove(&[3; 32])?, Some(undo));
+        undo_store.discard(&[4; 32])?;
+        fs::remove_file(get_file_path(&path, 1))?;
+        assert!(undo_store.get_hashes().is_empty());
+        Ok(())
+    }
+}
@@ -1,3 +1,4 @@
+use super::block_undo::BlockUndo;
 use super::outpoint::Outpoint;
 use super::transaction::Transaction;
 use crate::block_mod::block::Block;
@@ -26,16 +27,25 @@ impl UnspentTx {
         }
     }
 
-    /// Updates the `UnspentTx` object by processing a new block.
+    /// Updates the `UnspentTx` object by processing a new block, the outputs of its coinbase included.
     ///
     /// # Arguments
     ///
     /// * `new_block` - A reference to the `Block` object representing the new block to be processed.
-    pub fn update(&mut self, new_block: &Block) {
+    ///
+    /// # Returns
+    ///
+    /// The undo data of the block, to revert the update with `disconnect_block`.
+    pub fn update(&mut self, new_block: &Block) -> BlockUndo {
+        let mut undo = BlockUndo::new();
+        if let Ok(coinbase) = new_block.get_coinbase().as_transaction() {
+            self.update_transaction(&coinbase, &mut undo);
+        }
         new_block
             .get_txn_list()
             .iter()
-            .for_each(|tx| self.update_transaction(tx));
+            .for_each(|tx| self.update_transaction(tx, &mut undo));
+        undo
     }
 
     /// Updates the `UnspentTx` object by processing a new transaction.
@@ -43,11 +53,13 @@ impl UnspentTx {
     /// # Arguments
     ///
     /// * `new_tx` - A reference to the `Transaction` object representing the new transaction to be processed.
-    pub fn update_transaction(&mut self, new_tx: &Transaction) {
-        new_tx
-            .get_tx_in_list()
-            .iter()
-            .for_each(|tx_in| self.remove_tx_out(tx_in));
+    /// * `undo` - The undo data where the created and spent outputs are recorded.
+    pub fn update_transaction(&mut self, new_tx: &Transaction, undo: &mut BlockUndo) {
+        for tx_in in new_tx.get_tx_in_list() {
+            if let Some(tx_out) = self.remove_tx_out(tx_in) {
+                undo.add_spent_output(tx_in.get_prev_output().clone(), tx_out);
+            }
+        }
 
         let new_tx_hash: HashMap<u32, TxOut> = HashMap::from_iter(
             new_tx
@@ -56,25 +68,46 @@ impl UnspentTx {
                 .enumerate()
                 .map(|(index, txout)| (index as u32, txout.clone())),
         );
+        undo.add_tx_id(new_tx.get_id());
         self.utxo.insert(new_tx.get_id(), new_tx_hash);
     }
 
+    /// Reverts the update of a block: restores the outputs it spent and removes the ones it created.
+    ///
+    /// # Arguments
+    ///
+    /// * `undo` - The undo data returned by `update` for the block.
+    pub fn disconnect_block(&mut self, undo: &BlockUndo) {
+        for (outpoint, tx_out) in undo.get_spent_outputs().iter().rev() {
+            self.utxo
+                .entry(outpoint.get_tx_id().clone())
+                .or_default()
+                .insert(outpoint.get_index(), tx_out.clone());
+        }
+        for tx_id in undo.get_tx_ids() {
+            self.utxo.remove(tx_id);
+        }
+    }
+
     /// Removes a transaction output from the `UnspentTx` object based on the provided `TxIn`.
     ///
     /// # Arguments
     ///
     /// * `new_tx_in` - A reference to the `TxIn` object representing the transaction input that spends the output.
-    fn remove_tx_out(&mut self, new_tx_in: &TxIn) {
+    ///
+    /// # Returns
+    ///
+    /// The removed output, or `None` if it wasn't unspent.
+    fn remove_tx_out(&mut self, new_tx_in: &TxIn) -> Option<TxOut> {
         let tx_id = new_tx_in.get_prev_output().get_tx_id();
         let index = new_tx_in.get_prev_output().get_index();
 
-        if let Some(transaction_outputs) = self.utxo.get_mut(tx_id) {
-            if transaction_outputs.remove(&index).is_some()
-                && transaction_outputs.values().len() == 0
-            {
-                self.utxo.remove(tx_id);
-            }
-        };
+        let transaction_outputs = self.utxo.get_mut(tx_id)?;
+        let tx_out = transaction_outputs.remove(&index)?;
+        if transaction_outputs.values().len() == 0 {
+            self.utxo.remove(tx_id);
+        }
+        Some(tx_out)
     }
 
     /// Returns the total number of transactions in the `UnspentTx` object.
@@ -108,3 +141,78 @@ impl Default for UnspentTx {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod utxo_test {
+    use super::*;
+    use crate::{block_mod::block_header::BlockHeader, messages::message_error::MessageError};
+
+    /// Creates a block with an empty coinbase and the given transactions.
+    fn test_block(transactions: &[Transaction]) -> Result<Block, MessageError> {
+        let header = BlockHeader::new(1, vec![0; 32], vec![0; 32], 1, 0, 0);
+        let mut bytes = header.as_bytes();
+        bytes.push(transactions.len() as u8 + 1);
+        bytes.extend([1, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        for transaction in transactions {
+            bytes.extend(transaction.as_bytes());
+        }
+        Block::from_bytes(&mut &bytes[..])
+    }
+
+    #[test]
+    fn test_disconnect_block_reverts_the_update() -> Result<(), MessageError> {
+        let funding = Transaction::new(
+            2,
+            vec![TxIn::new(vec![1; 32], 0, vec![], 0)],
+            vec![TxOut::new(1000, vec![0x51]), TxOut::new(2000, vec![0x52])],
+            0,
+        );
+        let funding_id = funding.get_id();
+        let mut utxo = UnspentTx::new();
+        utxo.update(&test_block(&[funding])?);
+
+        let spending = Transaction::new(
+            2,
+            vec![TxIn::new(funding_id.clone(), 0, vec![], 0)],
+            vec![TxOut::new(900, vec![0x53])],
+            0,
+        );
+        let chained = Transaction::new(
+            2,
+            vec![TxIn::new(spending.get_id(), 0, vec![], 0)],
+            vec![TxOut::new(800, vec![0x54])],
+            0,
+        );
+        let undo = utxo.update(&test_block(&[spending, chained])?);
+        assert_eq!(undo.get_spent_pk_scripts(), vec![vec![0x51], vec![0x53]]);
+        assert_eq!(utxo.tx_count(), 2);
+
+        utxo.disconnect_block(&undo);
+        assert_eq!(utxo.tx_count(), 2);
+        assert!(utxo.contains_key(&Outpoint::new(funding_id.clone(), 0)));
+        assert!(utxo.contains_key(&Outpoint::new(funding_id.clone(), 1)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_update_adds_the_coinbase_outputs() -> Result<(), MessageError> {
+        let header = BlockHeader::new(1, vec![0; 32], vec![0; 32], 1, 0, 0);
+        let mut bytes = header.as_bytes();
+        bytes.extend([1, 1, 0, 0, 0, 1]);
+        bytes.extend([0; 32]);
+        bytes.extend([0xff, 0xff, 0xff, 0xff, 2, 1, 1, 0xff, 0xff, 0xff, 0xff, 1]);
+        bytes.extend([0; 8]);
+        bytes.extend([1, 0x51, 0, 0, 0, 0]);
+        let block = Block::from_bytes(&mut &bytes[..])?;
+        let coinbase_id = block.get_coinbase().get_id();
+        let mut utxo = UnspentTx::new();
+
+        let undo = utxo.update(&block);
+        assert!(utxo.contains_key(&Outpoint::new(coinbase_id.clone(), 0)));
+        assert!(undo.get_spent_outputs().is_empty());
+
+        utxo.disconnect_block(&undo);
+        assert!(!utxo.contains_key(&Outpoint::new(coinbase_id, 0)));
+        Ok(())
+    }
+}
@@ -29,8 +29,7 @@ pub fn wait_new_blocks(
                 }
             };
 
-            locked_utxo.update(&block);
-            locked_blockchain.add(block);
+            locked_blockchain.add(block, &mut locked_utxo);
             println!(
                 "Cantidad actual de bloques: {}",
                 locked_blockchain.get_cant_act_blocks()
@@ -1,5 +1,5 @@
 use std::{
-    net::{TcpListener, TcpStream},
+    net::{SocketAddr, TcpListener, TcpStream},
     sync::{
         mpsc::{self, Receiver, Sender},
         Arc, Mutex,
@@ -13,14 +13,17 @@ use node::{
     },
     block_saver::wait_new_blocks,
     network::{
-        block_download::{block_download, filter_headers, store_blocks_in_file},
+        block_download::{block_download, filter_headers},
         broadcasting::broadcasting,
         handshake::handshake,
         headers_download::headers_download,
         recv_peer_connection::recv_peer_connection,
     },
+    rpc_mod::{rpc_methods::RpcContext, rpc_server::rpc_server},
     settings_mod::{settings::Settings, settings_error::SettingError},
-    wallet_utils::wallet_connect::wallet_connect,
+    wallet_utils::{
+        wallet_connect::wallet_connect, wallet_notifier::WalletNotifier, wallet_state::WalletState,
+    },
 };
 use std::collections::HashMap;
 
@@ -73,10 +76,49 @@ fn main() {
 
     let (block_sender, block_recv): (Sender<Block>, Receiver<Block>) = mpsc::channel();
 
-    let blockchain: Arc<Mutex<BlockChain>> = Arc::new(Mutex::new(BlockChain::new()));
+    let mut blockchain = BlockChain::new(settings.get_network());
+    let base_height = match filtred_headers.first() {
+        Some(first) => headers
+            .iter()
+            .position(|header| header.get_header() == first.get_data())
+            .unwrap_or(0)
+            .saturating_sub(1),
+        None => headers.len().saturating_sub(1),
+    };
+    if let Some(base) = headers.get(base_height) {
+        blockchain.set_chain_base(base.get_header(), base_height);
+    }
+    if let Err(err) = blockchain.enable_block_files(settings.get_blocks_path()) {
+        println!("{:?}", err);
+        return;
+    }
+    if settings.get_address_index() {
+        blockchain.enable_address_index();
+    }
+    if let Some(tx_index_path) = settings.get_tx_index_path() {
+        if let Err(err) = blockchain.enable_tx_index(tx_index_path) {
+            println!("{:?}", err);
+            return;
+        }
+    }
+    if let Some(block_filters_path) = settings.get_block_filters_path() {
+        if let Err(err) = blockchain.enable_block_filters(block_filters_path) {
+            println!("{:?}", err);
+            return;
+        }
+    }
+    if let Some(prune_target) = settings.get_prune_target() {
+        blockchain.enable_pruning(prune_target);
+    }
+    let blockchain: Arc<Mutex<BlockChain>> = Arc::new(Mutex::new(blockchain));
     let utxo: Arc<Mutex<UnspentTx>> = Arc::new(Mutex::new(UnspentTx::new()));
     let mempool: Arc<Mutex<Mempool>> = Arc::new(Mutex::new(Mempool::new()));
 
+    let peers: Vec<SocketAddr> = streams
+        .iter()
+        .filter_map(|stream| stream.peer_addr().ok())
+        .collect();
+
     let streams: Vec<Arc<Mutex<TcpStream>>> = streams
         .into_iter()
         .map(|streams| Arc::new(Mutex::new(streams)))
@@ -86,14 +128,19 @@ fn main() {
 
     let handle_recv_block_download = wait_new_blocks(blockchain.clone(), utxo.clone(), block_recv);
 
+    let notifier = Arc::new(Mutex::new(WalletNotifier::new()));
+
     let handle_wallet_connect = wallet_connect(
         listener,
-        blockchain.clone(),
-        utxo.clone(),
-        mempool.clone(),
-        settings.clone(),
-        &streams,
-        filtred_headers.len(),
+        WalletState::new(
+            blockchain.clone(),
+            utxo.clone(),
+            mempool.clone(),
+            settings.clone(),
+            streams.clone(),
+            notifier.clone(),
+            filtred_headers.len(),
+        ),
     );
 
     match block_download(settings.clone(), &streams, filtred_headers, block_sender) {
@@ -107,19 +154,30 @@ fn main() {
         return;
     }
 
-    if let Err(err) = store_blocks_in_file(settings.get_blocks_path(), &blockchain) {
-        println!("{:?}", err);
-        return;
-    };
-
     let headers_hashmap: HashMap<Vec<u8>, BlockHeader> = headers
         .into_iter()
         .map(|block_header| (block_header.get_header(), block_header)) // Apply the desired transformation
         .collect();
 
     let headers = Arc::new(Mutex::new(headers_hashmap));
-    let handle_broadcasting =
-        broadcasting(&settings, streams, &blockchain, utxo, mempool, &headers);
+    let handle_rpc_server = rpc_server(RpcContext::new(
+        settings.clone(),
+        blockchain.clone(),
+        utxo.clone(),
+        mempool.clone(),
+        headers.clone(),
+        streams.clone(),
+        peers,
+    ));
+    let handle_broadcasting = broadcasting(
+        &settings,
+        streams,
+        &blockchain,
+        utxo,
+        mempool,
+        &headers,
+        notifier,
+    );
 
     recv_peer_connection(&settings, &blockchain, &headers);
 
@@ -130,4 +188,10 @@ fn main() {
     if handle_broadcasting.join().is_err() {
         println!("Join thread wallet connect error");
     }
+
+    if let Some(handle_rpc_server) = handle_rpc_server {
+        if handle_rpc_server.join().is_err() {
+            println!("Join thread rpc server error");
+        }
+    }
 }
@@ -3,7 +3,13 @@
 //! # Modules
 //!
 //! - [`addr`](addr) - Is the response to the "GetAddress" message, and it contains IP addresses of other peers.
+//! - [`cfcheckpt`](cfcheckpt) - Implements the `cfcheckpt` message for sending filter headers every 1000 blocks.
+//! - [`cfheaders`](cfheaders) - Implements the `cfheaders` message for sending the hashes of compact filters.
+//! - [`cfilter`](cfilter) - Implements the `cfilter` message for sending the compact filter of a block.
 //! - [`compact_size`](compact_size) - Provides utilities for working with values of variable length byte size.
+//! - [`get_cfcheckpt`](get_cfcheckpt) - Implements the `getcfcheckpt` message for requesting filter header checkpoints.
+//! - [`get_cfheaders`](get_cfheaders) - Implements the `getcfheaders` message for requesting compact filter headers.
+//! - [`get_cfilters`](get_cfilters) - Implements the `getcfilters` message for requesting compact filters.
 //! - [`get_data`](get_data) - Implements the `getdata` message for requesting different types of data from peers.
 //! - [`get_headers`](get_headers) - Implements the `getheaders` message for requesting block headers from peers.
 //! - [`header`](header) - Defines the structure and operations related to block headers.
@@ -20,8 +26,14 @@
 
 pub mod addr;
 pub mod block;
+pub mod cfcheckpt;
+pub mod cfheaders;
+pub mod cfilter;
 pub mod compact_size;
 pub mod filterload;
+pub mod get_cfcheckpt;
+pub mod get_cfheaders;
+pub mod get_cfilters;
 pub mod get_data;
 pub mod get_headers;
 pub mod header;
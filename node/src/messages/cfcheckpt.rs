@@ -0,0 +1,126 @@
+use super::compact_size::CompactSizeUInt;
+use super::header::MessageHeader;
+use super::message_constants::{CFCHECKPT_COMMAND, HEADER_BYTES_SIZE};
+use super::message_error::MessageError;
+use super::read_from_bytes::{read_u8_from_bytes, read_vec_from_bytes};
+use bitcoin_hashes::sha256d;
+use bitcoin_hashes::Hash;
+use std::io::Read;
+
+/// Represents the `cfcheckpt` message of BIP157, which sends the headers of the compact filters every 1000 blocks up to
+/// a block.
+#[derive(Debug, PartialEq)]
+pub struct CFCheckpt {
+    header: MessageHeader,
+    filter_type: u8,
+    stop_hash: Vec<u8>,
+    filter_headers: Vec<Vec<u8>>,
+}
+
+impl CFCheckpt {
+    /// Creates a new `CFCheckpt` message.
+    ///
+    /// # Arguments
+    ///
+    /// * `start_string` - The start string of the message.
+    /// * `filter_type` - The type of the filters.
+    /// * `stop_hash` - The hash of the last block.
+    /// * `filter_headers` - The filter headers of the blocks at heights 1000, 2000 and so on.
+    ///
+    /// # Returns
+    ///
+    /// A new `CFCheckpt` message.
+    pub fn new(
+        start_string: Vec<u8>,
+        filter_type: u8,
+        stop_hash: Vec<u8>,
+        filter_headers: Vec<Vec<u8>>,
+    ) -> CFCheckpt {
+        let header = MessageHeader::new(start_string, CFCHECKPT_COMMAND.to_string());
+
+        let mut cfcheckpt = CFCheckpt {
+            header,
+            filter_type,
+            stop_hash,
+            filter_headers,
+        };
+
+        let stream: Vec<u8> = cfcheckpt.as_bytes();
+        let payload_size = stream.len() - HEADER_BYTES_SIZE;
+        let checksum =
+            sha256d::Hash::hash(&stream[HEADER_BYTES_SIZE..]).to_byte_array()[..4].to_vec();
+
+        cfcheckpt
+            .header
+            .update_payload(payload_size as u32, checksum);
+        cfcheckpt
+    }
+
+    /// Creates a `CFCheckpt` message by decoding the raw bytes.
+    ///
+    /// # Arguments
+    ///
+    /// * `header` - The message header.
+    /// * `stream` - The byte stream to decode.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the decoded `CFCheckpt` message or an error.
+    pub fn from_bytes(
+        header: MessageHeader,
+        stream: &mut dyn Read,
+    ) -> Result<CFCheckpt, MessageError> {
+        if header.get_command_name() != CFCHECKPT_COMMAND {
+            return Err(MessageError::InvalidInputCompactFilters);
+        }
+
+        let filter_type = read_u8_from_bytes(stream)?;
+        let stop_hash = read_vec_from_bytes(stream, 32)?;
+        let count = CompactSizeUInt::from_bytes(stream)?;
+        let mut filter_headers = vec![];
+        for _ in 0..count.value() {
+            filter_headers.push(read_vec_from_bytes(stream, 32)?);
+        }
+
+        Ok(CFCheckpt {
+            header,
+            filter_type,
+            stop_hash,
+            filter_headers,
+        })
+    }
+
+    /// Converts the `CFCheckpt` message to its raw byte representation.
+    pub fn as_bytes(&self) -> Vec<u8> {
+        let mut buffer = self.header.as_bytes();
+
+        buffer.push(self.filter_type);
+        buffer.extend(&self.stop_hash);
+        buffer.extend(CompactSizeUInt::from_number(self.filter_headers.len() as u64).as_bytes());
+        for filter_header in self.filter_headers.iter() {
+            buffer.extend(filter_header);
+        }
+
+        buffer
+    }
+
+    pub fn get_filter_headers(&self) -> &Vec<Vec<u8>> {
+        &self.filter_headers
+    }
+}
+
+#[cfg(test)]
+mod cfcheckpt_test {
+    use super::*;
+
+    #[test]
+    fn test_new_cfcheckpt_from_bytes() -> Result<(), MessageError> {
+        let cfcheckpt = CFCheckpt::new(vec![11, 17, 9, 7], 0, vec![1; 32], vec![vec![2; 32]]);
+        let bytes = cfcheckpt.as_bytes();
+        let mut stream = bytes.as_slice();
+
+        let header = MessageHeader::from_bytes(&mut stream)?;
+        assert_eq!(CFCheckpt::from_bytes(header, &mut stream)?, cfcheckpt);
+        Ok(())
+    }
+}
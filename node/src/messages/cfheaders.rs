@@ -0,0 +1,143 @@
+use super::compact_size::CompactSizeUInt;
+use super::header::MessageHeader;
+use super::message_constants::{CFHEADERS_COMMAND, HEADER_BYTES_SIZE};
+use super::message_error::MessageError;
+use super::read_from_bytes::{read_u8_from_bytes, read_vec_from_bytes};
+use bitcoin_hashes::sha256d;
+use bitcoin_hashes::Hash;
+use std::io::Read;
+
+/// Represents the `cfheaders` message of BIP157, which sends the hashes of the compact filters of a range of blocks and
+/// the filter header of the block before the range, so the headers of the range can be computed from them.
+#[derive(Debug, PartialEq)]
+pub struct CFHeaders {
+    header: MessageHeader,
+    filter_type: u8,
+    stop_hash: Vec<u8>,
+    previous_filter_header: Vec<u8>,
+    filter_hashes: Vec<Vec<u8>>,
+}
+
+impl CFHeaders {
+    /// Creates a new `CFHeaders` message.
+    ///
+    /// # Arguments
+    ///
+    /// * `start_string` - The start string of the message.
+    /// * `filter_type` - The type of the filters.
+    /// * `stop_hash` - The hash of the last block of the range.
+    /// * `previous_filter_header` - The filter header of the block before the range.
+    /// * `filter_hashes` - The hashes of the filters of the blocks of the range.
+    ///
+    /// # Returns
+    ///
+    /// A new `CFHeaders` message.
+    pub fn new(
+        start_string: Vec<u8>,
+        filter_type: u8,
+        stop_hash: Vec<u8>,
+        previous_filter_header: Vec<u8>,
+        filter_hashes: Vec<Vec<u8>>,
+    ) -> CFHeaders {
+        let header = MessageHeader::new(start_string, CFHEADERS_COMMAND.to_string());
+
+        let mut cfheaders = CFHeaders {
+            header,
+            filter_type,
+            stop_hash,
+            previous_filter_header,
+            filter_hashes,
+        };
+
+        let stream: Vec<u8> = cfheaders.as_bytes();
+        let payload_size = stream.len() - HEADER_BYTES_SIZE;
+        let checksum =
+            sha256d::Hash::hash(&stream[HEADER_BYTES_SIZE..]).to_byte_array()[..4].to_vec();
+
+        cfheaders
+            .header
+            .update_payload(payload_size as u32, checksum);
+        cfheaders
+    }
+
+    /// Creates a `CFHeaders` message by decoding the raw bytes.
+    ///
+    /// # Arguments
+    ///
+    /// * `header` - The message header.
+    /// * `stream` - The byte stream to decode.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the decoded `CFHeaders` message or an error.
+    pub fn from_bytes(
+        header: MessageHeader,
+        stream: &mut dyn Read,
+    ) -> Result<CFHeaders, MessageError> {
+        if header.get_command_name() != CFHEADERS_COMMAND {
+            return Err(MessageError::InvalidInputCompactFilters);
+        }
+
+        let filter_type = read_u8_from_bytes(stream)?;
+        let stop_hash = read_vec_from_bytes(stream, 32)?;
+        let previous_filter_header = read_vec_from_bytes(stream, 32)?;
+        let count = CompactSizeUInt::from_bytes(stream)?;
+        let mut filter_hashes = vec![];
+        for _ in 0..count.value() {
+            filter_hashes.push(read_vec_from_bytes(stream, 32)?);
+        }
+
+        Ok(CFHeaders {
+            header,
+            filter_type,
+            stop_hash,
+            previous_filter_header,
+            filter_hashes,
+        })
+    }
+
+    /// Converts the `CFHeaders` message to its raw byte representation.
+    pub fn as_bytes(&self) -> Vec<u8> {
+        let mut buffer = self.header.as_bytes();
+
+        buffer.push(self.filter_type);
+        buffer.extend(&self.stop_hash);
+        buffer.extend(&self.previous_filter_header);
+        buffer.extend(CompactSizeUInt::from_number(self.filter_hashes.len() as u64).as_bytes());
+        for filter_hash in self.filter_hashes.iter() {
+            buffer.extend(filter_hash);
+        }
+
+        buffer
+    }
+
+    pub fn get_previous_filter_header(&self) -> &Vec<u8> {
+        &self.previous_filter_header
+    }
+
+    pub fn get_filter_hashes(&self) -> &Vec<Vec<u8>> {
+        &self.filter_hashes
+    }
+}
+
+#[cfg(test)]
+mod cfheaders_test {
+    use super::*;
+
+    #[test]
+    fn test_new_cfheaders_from_bytes() -> Result<(), MessageError> {
+        let cfheaders = CFHeaders::new(
+            vec![11, 17, 9, 7],
+            0,
+            vec![1; 32],
+            vec![0; 32],
+            vec![vec![2; 32], vec![3; 32]],
+        );
+        let bytes = cfheaders.as_bytes();
+        let mut stream = bytes.as_slice();
+
+        let header = MessageHeader::from_bytes(&mut stream)?;
+        assert_eq!(CFHeaders::from_bytes(header, &mut stream)?, cfheaders);
+        Ok(())
+    }
+}
@@ -0,0 +1,127 @@
+use super::compact_size::CompactSizeUInt;
+use super::header::MessageHeader;
+use super::message_constants::{CFILTER_COMMAND, HEADER_BYTES_SIZE};
+use super::message_error::MessageError;
+use super::read_from_bytes::{read_u8_from_bytes, read_vec_from_bytes};
+use bitcoin_hashes::sha256d;
+use bitcoin_hashes::Hash;
+use std::io::Read;
+
+/// Represents the `cfilter` message of BIP157, which sends the compact filter of a block.
+#[derive(Debug, PartialEq)]
+pub struct CFilter {
+    header: MessageHeader,
+    filter_type: u8,
+    block_hash: Vec<u8>,
+    filter: Vec<u8>,
+}
+
+impl CFilter {
+    /// Creates a new `CFilter` message.
+    ///
+    /// # Arguments
+    ///
+    /// * `start_string` - The start string of the message.
+    /// * `filter_type` - The type of the filter.
+    /// * `block_hash` - The hash of the block of the filter.
+    /// * `filter` - The serialized filter.
+    ///
+    /// # Returns
+    ///
+    /// A new `CFilter` message.
+    pub fn new(
+        start_string: Vec<u8>,
+        filter_type: u8,
+        block_hash: Vec<u8>,
+        filter: Vec<u8>,
+    ) -> CFilter {
+        let header = MessageHeader::new(start_string, CFILTER_COMMAND.to_string());
+
+        let mut cfilter = CFilter {
+            header,
+            filter_type,
+            block_hash,
+            filter,
+        };
+
+        let stream: Vec<u8> = cfilter.as_bytes();
+        let payload_size = stream.len() - HEADER_BYTES_SIZE;
+        let checksum =
+            sha256d::Hash::hash(&stream[HEADER_BYTES_SIZE..]).to_byte_array()[..4].to_vec();
+
+        cfilter.header.update_payload(payload_size as u32, checksum);
+        cfilter
+    }
+
+    /// Creates a `CFilter` message by decoding the raw bytes.
+    ///
+    /// # Arguments
+    ///
+    /// * `header` - The message header.
+    /// * `stream` - The byte stream to decode.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the decoded `CFilter` message or an error.
+    pub fn from_bytes(
+        header: MessageHeader,
+        stream: &mut dyn Read,
+    ) -> Result<CFilter, MessageError> {
+        if header.get_command_name() != CFILTER_COMMAND {
+            return Err(MessageError::InvalidInputCompactFilters);
+        }
+
+        let filter_type = read_u8_from_bytes(stream)?;
+        let block_hash = read_vec_from_bytes(stream, 32)?;
+        let filter_size = CompactSizeUInt::from_bytes(stream)?;
+        let filter = read_vec_from_bytes(stream, filter_size.value() as usize)?;
+
+        Ok(CFilter {
+            header,
+            filter_type,
+            block_hash,
+            filter,
+        })
+    }
+
+    /// Converts the `CFilter` message to its raw byte representation.
+    pub fn as_bytes(&self) -> Vec<u8> {
+        let mut buffer = self.header.as_bytes();
+
+        buffer.push(self.filter_type);
+        buffer.extend(&self.block_hash);
+        buffer.extend(CompactSizeUInt::from_number(self.filter.len() as u64).as_bytes());
+        buffer.extend(&self.filter);
+
+        buffer
+    }
+
+    pub fn get_block_hash(&self) -> &Vec<u8> {
+        &self.block_hash
+    }
+
+    pub fn get_filter(&self) -> &Vec<u8> {
+        &self.filter
+    }
+}
+
+#[cfg(test)]
+mod cfilter_test {
+    use super::*;
+
+    #[test]
+    fn test_new_cfilter_from_bytes() -> Result<(), MessageError> {
+        let cfilter = CFilter::new(
+            vec![11, 17, 9, 7],
+            0,
+            vec![1; 32],
+            vec![1, 0x9d, 0xfc, 0xa8],
+        );
+        let bytes = cfilter.as_bytes();
+        let mut stream = bytes.as_slice();
+
+        let header = MessageHeader::from_bytes(&mut stream)?;
+        assert_eq!(CFilter::from_bytes(header, &mut stream)?, cfilter);
+        Ok(())
+    }
+}
@@ -0,0 +1,114 @@
+use super::header::MessageHeader;
+use super::message_constants::{GET_CFCHECKPT_COMMAND, HEADER_BYTES_SIZE};
+use super::message_error::MessageError;
+use super::read_from_bytes::{read_u8_from_bytes, read_vec_from_bytes};
+use bitcoin_hashes::sha256d;
+use bitcoin_hashes::Hash;
+use std::io::Read;
+
+/// Represents the `getcfcheckpt` message of BIP157, which requests the headers of the compact filters every 1000 blocks
+/// up to a block.
+#[derive(Debug, PartialEq)]
+pub struct GetCFCheckpt {
+    header: MessageHeader,
+    filter_type: u8,
+    stop_hash: Vec<u8>,
+}
+
+impl GetCFCheckpt {
+    /// Creates a new `GetCFCheckpt` message.
+    ///
+    /// # Arguments
+    ///
+    /// * `start_string` - The start string of the message.
+    /// * `filter_type` - The type of the filters.
+    /// * `stop_hash` - The hash of the last block.
+    ///
+    /// # Returns
+    ///
+    /// A new `GetCFCheckpt` message.
+    pub fn new(start_string: Vec<u8>, filter_type: u8, stop_hash: Vec<u8>) -> GetCFCheckpt {
+        let header = MessageHeader::new(start_string, GET_CFCHECKPT_COMMAND.to_string());
+
+        let mut get_cfcheckpt = GetCFCheckpt {
+            header,
+            filter_type,
+            stop_hash,
+        };
+
+        let stream: Vec<u8> = get_cfcheckpt.as_bytes();
+        let payload_size = stream.len() - HEADER_BYTES_SIZE;
+        let checksum =
+            sha256d::Hash::hash(&stream[HEADER_BYTES_SIZE..]).to_byte_array()[..4].to_vec();
+
+        get_cfcheckpt
+            .header
+            .update_payload(payload_size as u32, checksum);
+        get_cfcheckpt
+    }
+
+    /// Creates a `GetCFCheckpt` message by decoding the raw bytes.
+    ///
+    /// # Arguments
+    ///
+    /// * `header` - The message header.
+    /// * `stream` - The byte stream to decode.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the decoded `GetCFCheckpt` message or an error.
+    pub fn from_bytes(
+        header: MessageHeader,
+        stream: &mut dyn Read,
+    ) -> Result<GetCFCheckpt, MessageError> {
+        if header.get_command_name() != GET_CFCHECKPT_COMMAND {
+            return Err(MessageError::InvalidInputCompactFilters);
+        }
+
+        let filter_type = read_u8_from_bytes(stream)?;
+        let stop_hash = read_vec_from_bytes(stream, 32)?;
+
+        Ok(GetCFCheckpt {
+            header,
+            filter_type,
+            stop_hash,
+        })
+    }
+
+    /// Converts the `GetCFCheckpt` message to its raw byte representation.
+    pub fn as_bytes(&self) -> Vec<u8> {
+        let mut buffer = self.header.as_bytes();
+
+        buffer.push(self.filter_type);
+        buffer.extend(&self.stop_hash);
+
+        buffer
+    }
+
+    pub fn get_filter_type(&self) -> u8 {
+        self.filter_type
+    }
+
+    pub fn get_stop_hash(&self) -> &Vec<u8> {
+        &self.stop_hash
+    }
+}
+
+#[cfg(test)]
+mod get_cfcheckpt_test {
+    use super::*;
+
+    #[test]
+    fn test_new_get_cfcheckpt_from_bytes() -> Result<(), MessageError> {
+        let get_cfcheckpt = GetCFCheckpt::new(vec![11, 17, 9, 7], 0, vec![1; 32]);
+        let bytes = get_cfcheckpt.as_bytes();
+        let mut stream = bytes.as_slice();
+
+        let header = MessageHeader::from_bytes(&mut stream)?;
+        assert_eq!(
+            GetCFCheckpt::from_bytes(header, &mut stream)?,
+            get_cfcheckpt
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,128 @@
+use super::header::MessageHeader;
+use super::message_constants::{GET_CFHEADERS_COMMAND, HEADER_BYTES_SIZE};
+use super::message_error::MessageError;
+use super::read_from_bytes::{read_u32_from_bytes, read_u8_from_bytes, read_vec_from_bytes};
+use bitcoin_hashes::sha256d;
+use bitcoin_hashes::Hash;
+use std::io::Read;
+
+/// Represents the `getcfheaders` message of BIP157, which requests the headers of the compact filters of a range of blocks.
+#[derive(Debug, PartialEq)]
+pub struct GetCFHeaders {
+    header: MessageHeader,
+    filter_type: u8,
+    start_height: u32,
+    stop_hash: Vec<u8>,
+}
+
+impl GetCFHeaders {
+    /// Creates a new `GetCFHeaders` message.
+    ///
+    /// # Arguments
+    ///
+    /// * `start_string` - The start string of the message.
+    /// * `filter_type` - The type of the filters.
+    /// * `start_height` - The height of the first block of the range.
+    /// * `stop_hash` - The hash of the last block of the range.
+    ///
+    /// # Returns
+    ///
+    /// A new `GetCFHeaders` message.
+    pub fn new(
+        start_string: Vec<u8>,
+        filter_type: u8,
+        start_height: u32,
+        stop_hash: Vec<u8>,
+    ) -> GetCFHeaders {
+        let header = MessageHeader::new(start_string, GET_CFHEADERS_COMMAND.to_string());
+
+        let mut get_cfheaders = GetCFHeaders {
+            header,
+            filter_type,
+            start_height,
+            stop_hash,
+        };
+
+        let stream: Vec<u8> = get_cfheaders.as_bytes();
+        let payload_size = stream.len() - HEADER_BYTES_SIZE;
+        let checksum =
+            sha256d::Hash::hash(&stream[HEADER_BYTES_SIZE..]).to_byte_array()[..4].to_vec();
+
+        get_cfheaders
+            .header
+            .update_payload(payload_size as u32, checksum);
+        get_cfheaders
+    }
+
+    /// Creates a `GetCFHeaders` message by decoding the raw bytes.
+    ///
+    /// # Arguments
+    ///
+    /// * `header` - The message header.
+    /// * `stream` - The byte stream to decode.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the decoded `GetCFHeaders` message or an error.
+    pub fn from_bytes(
+        header: MessageHeader,
+        stream: &mut dyn Read,
+    ) -> Result<GetCFHeaders, MessageError> {
+        if header.get_command_name() != GET_CFHEADERS_COMMAND {
+            return Err(MessageError::InvalidInputCompactFilters);
+        }
+
+        let filter_type = read_u8_from_bytes(stream)?;
+        let start_height = read_u32_from_bytes(stream, true)?;
+        let stop_hash = read_vec_from_bytes(stream, 32)?;
+
+        Ok(GetCFHeaders {
+            header,
+            filter_type,
+            start_height,
+            stop_hash,
+        })
+    }
+
+    /// Converts the `GetCFHeaders` message to its raw byte representation.
+    pub fn as_bytes(&self) -> Vec<u8> {
+        let mut buffer = self.header.as_bytes();
+
+        buffer.push(self.filter_type);
+        buffer.extend(self.start_height.to_le_bytes());
+        buffer.extend(&self.stop_hash);
+
+        buffer
+    }
+
+    pub fn get_filter_type(&self) -> u8 {
+        self.filter_type
+    }
+
+    pub fn get_start_height(&self) -> u32 {
+        self.start_height
+    }
+
+    pub fn get_stop_hash(&self) -> &Vec<u8> {
+        &self.stop_hash
+    }
+}
+
+#[cfg(test)]
+mod get_cfheaders_test {
+    use super::*;
+
+    #[test]
+    fn test_new_get_cfheaders_from_bytes() -> Result<(), MessageError> {
+        let get_cfheaders = GetCFHeaders::new(vec![11, 17, 9, 7], 0, 10, vec![1; 32]);
+        let bytes = get_cfheaders.as_bytes();
+        let mut stream = bytes.as_slice();
+
+        let header = MessageHeader::from_bytes(&mut stream)?;
+        assert_eq!(
+            GetCFHeaders::from_bytes(header, &mut stream)?,
+            get_cfheaders
+        );
+        Ok(())
+    }
+}
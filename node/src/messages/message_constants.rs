@@ -14,6 +14,17 @@ pub const TX_COMMAND: &str = "tx";
 pub const FILTER_LOAD_COMMAND: &str = "filterload";
 pub const MERKLE_BLOCK_COMMAND: &str = "merkleblock";
 pub const NOT_FOUND_COMMAND: &str = "notfound";
+pub const GET_CFILTERS_COMMAND: &str = "getcfilters";
+pub const CFILTER_COMMAND: &str = "cfilter";
+pub const GET_CFHEADERS_COMMAND: &str = "getcfheaders";
+pub const CFHEADERS_COMMAND: &str = "cfheaders";
+pub const GET_CFCHECKPT_COMMAND: &str = "getcfcheckpt";
+pub const CFCHECKPT_COMMAND: &str = "cfcheckpt";
+
+pub const BASIC_FILTER_TYPE: u8 = 0;
+pub const MAX_CFILTERS_COUNT: u32 = 1000;
+pub const MAX_CFHEADERS_COUNT: u32 = 2000;
+pub const CFCHECKPT_INTERVAL: usize = 1000;
 
 pub const HEADER_BYTES_SIZE: usize = 24;
 
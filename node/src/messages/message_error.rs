@@ -8,6 +8,9 @@ pub enum MessageError {
     InvalidInputInv,
     InvalidInputPong,
     InvalidInputVersion,
+    InvalidInputWalletFrame,
+    InvalidInputSubscribe,
+    InvalidInputCompactFilters,
 }
 
 impl From<std::io::Error> for MessageError {
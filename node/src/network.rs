@@ -2,14 +2,18 @@
 //!
 //! # Modules
 //!
+//! - [`bitcoin_network`](bitcoin_network) - Defines the parameters of the supported Bitcoin networks (mainnet and testnet).
 //! - [`block_download`](block_download) - Implements block download functionality for syncing with the Bitcoin network.
+//! - [`broadcast_event`](broadcast_event) - Defines what the messages of the peers add to the node, to notify the wallets.
 //! - [`broadcasting`](broadcasting) - Listens for incoming new headers to update the blockchain.
 //! - [`handshake`](handshake) - Implements the handshake protocol for establishing connections with peers.
 //! - [`headers_download`](headers_download) - Implements headers download functionality for syncing block headers with the Bitcoin network.
 //! - [`network_constants`](network_constants) - Defines constants related to the Bitcoin network.
 //! - [`network_error`](network_error) - Implements error handling for network-related operations.
 
+pub mod bitcoin_network;
 pub mod block_download;
+pub mod broadcast_event;
 pub mod broadcasting;
 pub mod handshake;
 pub mod headers_download;
@@ -0,0 +1,154 @@
+use std::str::FromStr;
+
+use super::network_constants::*;
+use crate::block_mod::block_header::BlockHeader;
+
+/// Represents the Bitcoin network the node and the wallet operate on.
+///
+/// Each network defines its own genesis block and the version bytes used to
+/// encode addresses and private keys (WIF).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BitcoinNetwork {
+    Mainnet,
+    #[default]
+    Testnet,
+}
+
+impl BitcoinNetwork {
+    /// Returns the version byte used for pay-to-public-key-hash (P2PKH) addresses.
+    pub fn get_p2pkh_prefix(&self) -> u8 {
+        match self {
+            BitcoinNetwork::Mainnet => MAINNET_P2PKH_PREFIX,
+            BitcoinNetwork::Testnet => TESTNET_P2PKH_PREFIX,
+        }
+    }
+
+    /// Returns the version byte used for pay-to-script-hash (P2SH) addresses.
+    pub fn get_p2sh_prefix(&self) -> u8 {
+        match self {
+            BitcoinNetwork::Mainnet => MAINNET_P2SH_PREFIX,
+            BitcoinNetwork::Testnet => TESTNET_P2SH_PREFIX,
+        }
+    }
+
+    /// Returns the version byte used for private keys in Wallet Import Format (WIF).
+    pub fn get_wif_prefix(&self) -> u8 {
+        match self {
+            BitcoinNetwork::Mainnet => MAINNET_WIF_PREFIX,
+            BitcoinNetwork::Testnet => TESTNET_WIF_PREFIX,
+        }
+    }
+
+    /// Returns the human readable part of native SegWit (bech32) addresses.
+    pub fn get_bech32_hrp(&self) -> &'static str {
+        match self {
+            BitcoinNetwork::Mainnet => MAINNET_BECH32_HRP,
+            BitcoinNetwork::Testnet => TESTNET_BECH32_HRP,
+        }
+    }
+
+    /// Returns the version bytes of BIP32 extended private keys (xprv / tprv).
+    pub fn get_xprv_prefix(&self) -> [u8; 4] {
+        match self {
+            BitcoinNetwork::Mainnet => MAINNET_XPRV_PREFIX,
+            BitcoinNetwork::Testnet => TESTNET_XPRV_PREFIX,
+        }
+    }
+
+    /// Returns the version bytes of BIP32 extended public keys (xpub / tpub).
+    pub fn get_xpub_prefix(&self) -> [u8; 4] {
+        match self {
+            BitcoinNetwork::Mainnet => MAINNET_XPUB_PREFIX,
+            BitcoinNetwork::Testnet => TESTNET_XPUB_PREFIX,
+        }
+    }
+
+    /// Returns the coin type of the BIP44 derivation paths (`m/purpose'/coin_type'/account'`).
+    pub fn get_coin_type(&self) -> u32 {
+        match self {
+            BitcoinNetwork::Mainnet => MAINNET_COIN_TYPE,
+            BitcoinNetwork::Testnet => TESTNET_COIN_TYPE,
+        }
+    }
+
+    /// Builds the header of the genesis block of the network.
+    ///
+    /// # Returns
+    ///
+    /// The `BlockHeader` of the first block of the chain.
+    pub fn get_genesis_header(&self) -> BlockHeader {
+        let (time, nonce) = match self {
+            BitcoinNetwork::Mainnet => (MAINNET_GENESIS_TIME, MAINNET_GENESIS_NONCE),
+            BitcoinNetwork::Testnet => (GENESIS_TIME, GENESIS_NONCE),
+        };
+
+        BlockHeader::new(
+            GENESIS_VERSION,
+            GENESIS_PREVIOUS_BLOCK_HEADER_HASH.to_vec(),
+            GENESIS_MERKLE_ROOT_HASH.to_vec(),
+            time,
+            GENESIS_NBITS,
+            nonce,
+        )
+    }
+}
+
+impl FromStr for BitcoinNetwork {
+    type Err = ();
+
+    fn from_str(network: &str) -> Result<BitcoinNetwork, ()> {
+        match network {
+            MAINNET => Ok(BitcoinNetwork::Mainnet),
+            TESTNET => Ok(BitcoinNetwork::Testnet),
+            _ => Err(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod bitcoin_network_test {
+    use super::*;
+    use crate::messages::read_from_bytes::encode_hex;
+
+    #[test]
+    fn test_mainnet_genesis_header() -> Result<(), std::fmt::Error> {
+        let genesis = BitcoinNetwork::Mainnet.get_genesis_header();
+
+        let mut hash = genesis.get_header();
+        hash.reverse();
+
+        assert_eq!(
+            encode_hex(&hash)?,
+            "000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f"
+        );
+        assert!(genesis.proof_of_work());
+        Ok(())
+    }
+
+    #[test]
+    fn test_testnet_genesis_header() -> Result<(), std::fmt::Error> {
+        let genesis = BitcoinNetwork::Testnet.get_genesis_header();
+
+        let mut hash = genesis.get_header();
+        hash.reverse();
+
+        assert_eq!(
+            encode_hex(&hash)?,
+            "000000000933ea01ad0ee984209779baaec3ced90fa3f408719526f8d77f4943"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_network_from_str() {
+        assert_eq!(
+            BitcoinNetwork::from_str("mainnet"),
+            Ok(BitcoinNetwork::Mainnet)
+        );
+        assert_eq!(
+            BitcoinNetwork::from_str("testnet"),
+            Ok(BitcoinNetwork::Testnet)
+        );
+        assert!(BitcoinNetwork::from_str("regtest").is_err());
+    }
+}
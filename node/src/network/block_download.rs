@@ -3,8 +3,7 @@ use chrono::Utc;
 
 use super::headers_download::handle_other_message;
 
-use crate::block_mod::blockchain::BlockChain;
-use crate::network::network_constants::MSG_BLOCK_DATA_TYPE;
+use crate::network::network_constants::MSG_WITNESS_BLOCK_DATA_TYPE;
 use crate::{
     block_mod::{block::Block, block_header::BlockHeader},
     messages::{
@@ -14,7 +13,6 @@ use crate::{
     network::{network_constants::DATE_FORMAT, network_error::NetworkError},
     settings_mod::settings::Settings,
 };
-use std::fs::OpenOptions;
 use std::{
     io::Write,
     net::TcpStream,
@@ -23,6 +21,8 @@ use std::{
     thread::{self, JoinHandle},
 };
 
+/// Returns the inventories of the blocks to download: every block from the first one mined after the date limit, so
+/// they make a chain without gaps even if a later block has an earlier time.
 pub fn filter_headers(
     settings: &Settings,
     headers: &[BlockHeader],
@@ -34,12 +34,12 @@ pub fn filter_headers(
 
     let headers: Vec<&BlockHeader> = headers
         .iter()
-        .filter(|block_header| block_header.get_time() > date_time as u32)
+        .skip_while(|block_header| block_header.get_time() <= date_time as u32)
         .collect();
 
     let inventories: Vec<Inventory> = headers
         .iter()
-        .map(|block_header| Inventory::new(MSG_BLOCK_DATA_TYPE, block_header.get_header()))
+        .map(|block_header| Inventory::new(MSG_WITNESS_BLOCK_DATA_TYPE, block_header.get_header()))
         .collect();
 
     Ok(inventories)
@@ -188,34 +188,3 @@ pub fn take_n_streams(
     }
     new_list
 }
-
-pub fn store_blocks_in_file(
-    path: &str,
-    blockchain: &Arc<Mutex<BlockChain>>,
-) -> Result<(), NetworkError> {
-    let mut file = OpenOptions::new()
-        .create(true)
-        .truncate(true)
-        .write(true)
-        .open(path)
-        .map_err(|_| NetworkError::BlockDownload)?;
-
-    let mut blocks = vec![];
-
-    let locked_blockchain = blockchain.lock().map_err(|_| NetworkError::BlockDownload)?;
-
-    let mut act_block_header = &locked_blockchain.get_last_block_header();
-    while let Some(block) = locked_blockchain.get_block(act_block_header) {
-        blocks.push(block);
-        act_block_header = block.get_header().get_previuos_block_header();
-    }
-
-    blocks.reverse();
-    
-    for block in blocks {
-        file.write(&block.as_bytes())
-        .map_err(|_| NetworkError::BlockDownload)?;
-    }
-    
-    Ok(())
-}
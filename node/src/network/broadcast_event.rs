@@ -0,0 +1,9 @@
+use crate::block_mod::{block::Block, transaction::Transaction};
+
+/// What a message of a peer added to the node, so the subscribed wallets are notified of it.
+pub enum BroadcastEvent {
+    /// A transaction admitted to the mempool.
+    Transaction(Transaction),
+    /// A block connected to the chain, with its height.
+    Block(Block, usize),
+}
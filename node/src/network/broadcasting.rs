@@ -1,4 +1,7 @@
-use super::network_constants::MSG_BLOCK_DATA_TYPE;
+use super::broadcast_event::BroadcastEvent;
+use super::network_constants::{
+    MSG_BLOCK_DATA_TYPE, MSG_TX_DATA_TYPE, MSG_WITNESS_BLOCK_DATA_TYPE, MSG_WITNESS_TX_DATA_TYPE,
+};
 use super::network_error::NetworkError;
 use crate::block_mod::block_header::BlockHeader;
 use crate::block_mod::blockchain::BlockChain;
@@ -7,6 +10,8 @@ use crate::block_mod::transaction::Transaction;
 use crate::messages::inv::Inv;
 use crate::messages::message_constants::{INV_COMMAND, TX_COMMAND};
 use crate::messages::tx::Tx;
+use crate::rpc_mod::chain_view::ChainView;
+use crate::wallet_utils::wallet_notifier::WalletNotifier;
 use crate::{
     block_mod::{block::Block, utxo::UnspentTx},
     messages::{
@@ -51,7 +56,8 @@ fn store_data_in_file(file_path: &str, data: Vec<u8>) -> Result<(), NetworkError
 /// * `mempool` - An Arc-wrapped Mutex for accessing and modifying the mempool.
 ///
 /// # Returns
-/// * `Result<(), NetworkError>` - A result indicating success or an error of type NetworkError.
+/// * `Result<Option<BroadcastEvent>, NetworkError>` - The transaction, if it wasn't in the mempool yet, or an error of
+///   type NetworkError.
 ///
 /// # Errors
 /// The function can return a NetworkError in the following cases:
@@ -61,13 +67,14 @@ fn store_data_in_file(file_path: &str, data: Vec<u8>) -> Result<(), NetworkError
 fn manage_tx_command(
     stream: &mut TcpStream,
     mempool: &Arc<Mutex<Mempool>>,
-) -> Result<(), NetworkError> {
+) -> Result<Option<BroadcastEvent>, NetworkError> {
     let tx = Transaction::from_bytes(stream).map_err(|_| NetworkError::Broadcasting)?;
-    mempool
-        .lock()
-        .map_err(|_| NetworkError::Broadcasting)?
-        .add(tx);
-    Ok(())
+    let mut locked_mempool = mempool.lock().map_err(|_| NetworkError::Broadcasting)?;
+    if locked_mempool.get_txs().contains_key(&tx.get_id()) {
+        return Ok(None);
+    }
+    locked_mempool.add(tx.clone());
+    Ok(Some(BroadcastEvent::Transaction(tx)))
 }
 
 /// Manages the "block" command received over the network.
@@ -82,7 +89,8 @@ fn manage_tx_command(
 /// * `mempool` - An Arc-wrapped Mutex for accessing and modifying the mempool.
 ///
 /// # Returns
-/// * `Result<(), NetworkError>` - A result indicating success or an error of type NetworkError.
+/// * `Result<Option<BroadcastEvent>, NetworkError>` - The block and its height, if it wasn't downloaded yet, or an
+///   error of type NetworkError.
 ///
 /// # Errors
 /// The function can return a NetworkError in the following cases:
@@ -97,16 +105,13 @@ fn manage_block_command(
     mempool: &Arc<Mutex<Mempool>>,
     headers: &Arc<Mutex<HashMap<Vec<u8>, BlockHeader>>>,
     settings: &Arc<Settings>,
-) -> Result<(), NetworkError> {
+) -> Result<Option<BroadcastEvent>, NetworkError> {
     let block = Block::from_bytes(stream).map_err(|_| NetworkError::Broadcasting)?;
 
     if !block.proof_of_work() || !block.proof_of_inclusion() {
         return Err(NetworkError::Broadcasting);
     }
 
-    utxo.lock()
-        .map_err(|_| NetworkError::Broadcasting)?
-        .update(&block);
     mempool
         .lock()
         .map_err(|_| NetworkError::Broadcasting)?
@@ -121,9 +126,7 @@ fn manage_block_command(
         store_data_in_file(settings.get_headers_path(), block.get_header().as_bytes())?;
     }
 
-    if locked_blockchain.get_block(&block.get_header().get_header()).is_none() {
-        store_data_in_file(settings.get_blocks_path(), block.as_bytes())?;
-    }
+    let is_new_block = !locked_blockchain.contains_block(&block.get_header().get_header());
 
     locked_headers
         .get_mut(&last_block)
@@ -131,15 +134,23 @@ fn manage_block_command(
         .set_next_block_header(block.get_header().get_header());
 
     locked_headers.insert(block.get_header().get_header(), block.get_header().clone());
-    locked_blockchain.add(block);
+    let height = ChainView::new(&locked_blockchain, &locked_headers, settings.get_network())
+        .get_height(&block.get_header().get_header());
+    let event = match (is_new_block, height) {
+        (true, Some(height)) => Some(BroadcastEvent::Block(block.clone(), height)),
+        _ => None,
+    };
+    let mut locked_utxo = utxo.lock().map_err(|_| NetworkError::Broadcasting)?;
+    locked_blockchain.add(block, &mut locked_utxo);
 
-    Ok(())
+    Ok(event)
 }
 
 /// Manages the "inv" command received over the network.
 ///
 /// This function reads an Inv message from the provided `stream`, extracts the inventory,
-/// and sends a GetData message requesting the corresponding data.
+/// and sends a GetData message requesting the corresponding data. Blocks and transactions
+/// are requested with their witnesses.
 ///
 /// # Arguments
 /// * `header` - The MessageHeader of the received message.
@@ -166,6 +177,12 @@ fn manage_inv_command(
         .get_inventories()
         .pop()
         .ok_or(NetworkError::Broadcasting)?;
+    let data_type = match inventory.get_type() {
+        MSG_TX_DATA_TYPE => MSG_WITNESS_TX_DATA_TYPE,
+        MSG_BLOCK_DATA_TYPE => MSG_WITNESS_BLOCK_DATA_TYPE,
+        data_type => data_type,
+    };
+    let inventory = Inventory::new(data_type, inventory.get_data());
     let get_data = GetData::new(settings.get_start_string(), vec![inventory]);
     stream
         .write_all(&get_data.as_bytes())
@@ -201,7 +218,7 @@ fn manage_headers_command(
 
     if block_header.proof_of_work() {
         let inv = vec![Inventory::new(
-            MSG_BLOCK_DATA_TYPE,
+            MSG_WITNESS_BLOCK_DATA_TYPE,
             block_header.get_header(),
         )];
 
@@ -250,7 +267,7 @@ fn manage_ping_command(
 ///
 /// # Returns
 ///
-/// An empty result if successful, or a `NetworkError` if an error occurs.
+/// The transaction or the block the message added to the node, if any, or a `NetworkError` if an error occurs.
 pub fn handle_messages(
     header: MessageHeader,
     settings: &Arc<Settings>,
@@ -259,7 +276,7 @@ pub fn handle_messages(
     utxo: &Arc<Mutex<UnspentTx>>,
     mempool: &Arc<Mutex<Mempool>>,
     headers: &Arc<Mutex<HashMap<Vec<u8>, BlockHeader>>>,
-) -> Result<(), NetworkError> {
+) -> Result<Option<BroadcastEvent>, NetworkError> {
     let command_name: &str = header.get_command_name().as_str();
 
     match command_name {
@@ -273,10 +290,10 @@ pub fn handle_messages(
             manage_inv_command(header, settings, stream)?;
         }
         TX_COMMAND => {
-            manage_tx_command(stream, mempool)?;
+            return manage_tx_command(stream, mempool);
         }
         BLOCK_COMMAND => {
-            manage_block_command(stream, blockchain, utxo, mempool, headers, settings)?;
+            return manage_block_command(stream, blockchain, utxo, mempool, headers, settings);
         }
         _ => {
             stream
@@ -284,6 +301,23 @@ pub fn handle_messages(
                 .map_err(|_| NetworkError::Broadcasting)?;
         }
     };
+    Ok(None)
+}
+
+/// Notifies the subscribed wallets of a transaction or a block added to the node.
+fn notify_wallets(
+    notifier: &Arc<Mutex<WalletNotifier>>,
+    event: BroadcastEvent,
+) -> Result<(), NetworkError> {
+    let mut locked_notifier = notifier.lock().map_err(|_| NetworkError::Broadcasting)?;
+    match event {
+        BroadcastEvent::Transaction(transaction) => {
+            locked_notifier.notify_transaction(&transaction)
+        }
+        BroadcastEvent::Block(block, height) => locked_notifier
+            .notify_block(&block, height)
+            .map_err(|_| NetworkError::Broadcasting)?,
+    }
     Ok(())
 }
 
@@ -295,6 +329,7 @@ pub fn handle_messages(
 /// * `streams` - The vector of TCP streams to broadcast messages to.
 /// * `blocks` - The hashmap storing blocks.
 /// * `utxo_set` - The unspent transaction set.
+/// * `notifier` - The notifier of the wallets, told of the transactions and blocks the peers send.
 ///
 /// # Returns
 ///
@@ -307,6 +342,7 @@ pub fn broadcasting(
     utxo: Arc<Mutex<UnspentTx>>,
     mempool: Arc<Mutex<Mempool>>,
     headers: &Arc<Mutex<HashMap<Vec<u8>, BlockHeader>>>,
+    notifier: Arc<Mutex<WalletNotifier>>,
 ) -> JoinHandle<()> {
     println!("Se inicia el broadcasting\n");
 
@@ -340,7 +376,7 @@ pub fn broadcasting(
 
             println!("\nBroadcasting: {:?}", header);
 
-            if handle_messages(
+            if let Ok(Some(event)) = handle_messages(
                 header,
                 &shared_settings,
                 &mut locked_stream,
@@ -348,11 +384,11 @@ pub fn broadcasting(
                 &utxo,
                 &mempool,
                 &shared_headers,
-            )
-            .is_err()
-            {
-                i += 1;
-                continue;
+            ) {
+                drop(locked_stream);
+                if notify_wallets(&notifier, event).is_err() {
+                    println!("Falla al notificar a las wallets");
+                }
             };
             i += 1;
         }
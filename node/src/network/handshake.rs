@@ -1,7 +1,8 @@
 use super::{
     super::messages::{header::MessageHeader, version::Version},
     network_constants::{
-        DIG_COMMAND, DURATION_TIMEOUT_MILLIS, SERVICES_ACEPTED, SHORT_ARG, VERSION_ACEPTED,
+        DIG_COMMAND, DURATION_TIMEOUT_MILLIS, NODE_NETWORK, NODE_NETWORK_LIMITED, SERVICES_ACEPTED,
+        SHORT_ARG, VERSION_ACEPTED,
     },
     network_error::NetworkError,
 };
@@ -48,7 +49,8 @@ fn peer_discovery(dns_seed: &String) -> Result<Vec<Ipv6Addr>, NetworkError> {
     Ok(ips)
 }
 
-/// Checks if a given version is compatible with the accepted versions.
+/// Checks if a given version is compatible with the accepted versions. The peer must offer the accepted services, and
+/// may offer others too.
 ///
 /// # Arguments
 ///
@@ -58,7 +60,14 @@ fn peer_discovery(dns_seed: &String) -> Result<Vec<Ipv6Addr>, NetworkError> {
 ///
 /// `true` if the version is compatible, `false` otherwise.
 pub fn is_version_compatible(version: &Version) -> bool {
-    version.get_version() == VERSION_ACEPTED && version.get_services() == SERVICES_ACEPTED
+    version.get_version() == VERSION_ACEPTED && are_services_accepted(version.get_services())
+}
+
+/// Checks if a peer offers the accepted services and serves blocks, all of them or only the most recent ones as a pruned
+/// node does.
+fn are_services_accepted(services: u64) -> bool {
+    services & SERVICES_ACEPTED == SERVICES_ACEPTED
+        && services & (NODE_NETWORK | NODE_NETWORK_LIMITED) != 0
 }
 
 /// Performs the handshake with peer nodes.
@@ -150,3 +159,20 @@ pub fn handshake(settings: &Settings) -> Result<Vec<TcpStream>, NetworkError> {
 
     Ok(streams)
 }
+
+#[cfg(test)]
+mod handshake_test {
+    use super::*;
+    use crate::network::network_constants::NODE_WITNESS;
+
+    #[test]
+    fn test_pruned_peers_are_accepted() {
+        assert!(are_services_accepted(
+            NODE_NETWORK | NODE_WITNESS | NODE_NETWORK_LIMITED
+        ));
+        assert!(are_services_accepted(NODE_NETWORK | NODE_WITNESS));
+        assert!(are_services_accepted(NODE_WITNESS | NODE_NETWORK_LIMITED));
+        assert!(!are_services_accepted(NODE_WITNESS));
+        assert!(!are_services_accepted(NODE_NETWORK | NODE_NETWORK_LIMITED));
+    }
+}
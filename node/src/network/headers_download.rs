@@ -114,14 +114,7 @@ pub fn load_headers(
     let mut block_headers: Vec<BlockHeader> = Vec::new();
 
     if is_file_empty(settings.get_headers_path()) {
-        let genesis = BlockHeader::new(
-            GENESIS_VERSION,
-            GENESIS_PREVIOUS_BLOCK_HEADER_HASH.to_vec(),
-            GENESIS_MERKLE_ROOT_HASH.to_vec(),
-            GENESIS_TIME,
-            GENESIS_NBITS,
-            GENESIS_NONCE,
-        );
+        let genesis = settings.get_network().get_genesis_header();
         file.write_all(&genesis.as_bytes())
             .map_err(|_| NetworkError::HeaderDownload)?;
         block_headers.push(genesis);
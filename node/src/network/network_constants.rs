@@ -1,7 +1,12 @@
 //hand shake
 pub const DURATION_TIMEOUT_MILLIS: u64 = 1000;
 pub const VERSION_ACEPTED: i32 = 70016;
-pub const SERVICES_ACEPTED: u64 = 1033;
+pub const SERVICES_ACEPTED: u64 = NODE_WITNESS;
+pub const NODE_NETWORK: u64 = 1;
+pub const NODE_WITNESS: u64 = 1 << 3;
+pub const NODE_COMPACT_FILTERS: u64 = 1 << 6;
+pub const NODE_NETWORK_LIMITED: u64 = 1 << 10;
+pub const NETWORK_LIMITED_MIN_BLOCKS: usize = 288;
 pub const DIG_COMMAND: &str = "dig";
 pub const SHORT_ARG: &str = "+short";
 
@@ -16,11 +21,33 @@ pub const GENESIS_TIME: u32 = 1296688602;
 pub const GENESIS_NBITS: u32 = 486604799;
 pub const GENESIS_NONCE: u32 = 414098458;
 pub const STOPPING_HASH: [u8; 32] = [0; 32];
+pub const MAINNET_GENESIS_TIME: u32 = 1231006505;
+pub const MAINNET_GENESIS_NONCE: u32 = 2083236893;
 
 //block download
 pub const MAX_BLOCKS_GET_DATA: usize = 50000;
 pub const MAX_HEADERS_COUNT: u64 = 2000;
 pub const MSG_BLOCK_DATA_TYPE: u32 = 2;
 pub const MSG_TX_DATA_TYPE: u32 = 1;
+pub const MSG_WITNESS_TX_DATA_TYPE: u32 = 0x40000001;
+pub const MSG_WITNESS_BLOCK_DATA_TYPE: u32 = 0x40000002;
 
 pub const DATE_FORMAT: &str = "%Y-%m-%dT%H:%M:%S%:z";
+
+//bitcoin network
+pub const MAINNET: &str = "mainnet";
+pub const TESTNET: &str = "testnet";
+pub const MAINNET_P2PKH_PREFIX: u8 = 0x00;
+pub const TESTNET_P2PKH_PREFIX: u8 = 0x6f;
+pub const MAINNET_P2SH_PREFIX: u8 = 0x05;
+pub const TESTNET_P2SH_PREFIX: u8 = 0xc4;
+pub const MAINNET_WIF_PREFIX: u8 = 0x80;
+pub const TESTNET_WIF_PREFIX: u8 = 0xef;
+pub const MAINNET_BECH32_HRP: &str = "bc";
+pub const TESTNET_BECH32_HRP: &str = "tb";
+pub const MAINNET_XPRV_PREFIX: [u8; 4] = [0x04, 0x88, 0xad, 0xe4];
+pub const TESTNET_XPRV_PREFIX: [u8; 4] = [0x04, 0x35, 0x83, 0x94];
+pub const MAINNET_XPUB_PREFIX: [u8; 4] = [0x04, 0x88, 0xb2, 0x1e];
+pub const TESTNET_XPUB_PREFIX: [u8; 4] = [0x04, 0x35, 0x87, 0xcf];
+pub const MAINNET_COIN_TYPE: u32 = 0;
+pub const TESTNET_COIN_TYPE: u32 = 1;
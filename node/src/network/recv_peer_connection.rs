@@ -1,18 +1,27 @@
 use super::{handshake::is_version_compatible, network_error::NetworkError};
 use crate::{
-    block_mod::{block_header::BlockHeader, blockchain::BlockChain},
+    block_mod::{block_header::BlockHeader, block_store::BlockLocation, blockchain::BlockChain},
     messages::{
         block::BlockMsg,
+        cfcheckpt::CFCheckpt,
+        cfheaders::CFHeaders,
+        cfilter::CFilter,
+        get_cfcheckpt::GetCFCheckpt,
+        get_cfheaders::GetCFHeaders,
+        get_cfilters::GetCFilters,
         get_data::GetData,
         get_headers::GetHeaders,
         header::MessageHeader,
         headers::Headers,
         message_constants::{
-            GET_DATA_COMMAND, GET_HEADERS_COMMAND, NOT_FOUND_COMMAND, VERACK_COMMAND,
+            BASIC_FILTER_TYPE, BLOCK_COMMAND, CFCHECKPT_INTERVAL, GET_CFCHECKPT_COMMAND,
+            GET_CFHEADERS_COMMAND, GET_CFILTERS_COMMAND, GET_DATA_COMMAND, GET_HEADERS_COMMAND,
+            MAX_CFHEADERS_COUNT, MAX_CFILTERS_COUNT, NOT_FOUND_COMMAND, VERACK_COMMAND,
             VERSION_COMMAND,
         },
         version::Version,
     },
+    rpc_mod::chain_view::ChainView,
     settings_mod::settings::Settings,
 };
 use std::collections::HashMap;
@@ -20,7 +29,7 @@ use std::net::Ipv6Addr;
 use std::{
     io::{Read, Write},
     net::{IpAddr, TcpListener, TcpStream},
-    sync::{Arc, Mutex},
+    sync::{Arc, Mutex, MutexGuard},
     thread,
 };
 
@@ -82,18 +91,32 @@ fn handle_get_header_message(
     Ok(())
 }
 
+/// Sends the requested blocks, or a `notfound` message for each unknown one. A pruned node disconnects the peers that
+/// request a pruned block, as BIP159 allows.
 fn handle_get_data_message(
     start_string: Vec<u8>,
     blockchain: &Arc<Mutex<BlockChain>>,
     get_data: GetData,
     client_node: &mut TcpStream,
 ) -> Result<(), NetworkError> {
-    let locked_blockchain = blockchain
-        .lock()
-        .map_err(|_| NetworkError::PeerConnection)?;
     for inv in get_data.get_inv_list() {
-        if let Some(block) = locked_blockchain.get_block(&inv.get_data()) {
-            let block_message = BlockMsg::new(start_string.clone(), block.clone());
+        let (location, block) = {
+            let locked_blockchain = blockchain
+                .lock()
+                .map_err(|_| NetworkError::PeerConnection)?;
+            if locked_blockchain.is_pruned(&inv.get_data()) {
+                return Err(NetworkError::PeerConnection);
+            }
+            match locked_blockchain.get_block_location(&inv.get_data()) {
+                Some(location) => (Some(location), None),
+                None => (None, locked_blockchain.get_block(&inv.get_data())),
+            }
+        };
+
+        if let Some(location) = location {
+            send_stored_block(start_string.clone(), &location, client_node)?;
+        } else if let Some(block) = block {
+            let block_message = BlockMsg::new(start_string.clone(), block);
             client_node
                 .write_all(&block_message.as_bytes())
                 .map_err(|_| NetworkError::PeerConnection)?;
@@ -109,6 +132,253 @@ fn handle_get_data_message(
     Ok(())
 }
 
+/// Sends a `block` message with a block copied from its file, without reading it into memory. The blockchain isn't
+/// locked while it is copied.
+///
+/// # Arguments
+///
+/// * `start_string` - The start string of the message.
+/// * `location` - Where the block is in the block files.
+/// * `client_node` - The stream of the peer.
+///
+/// # Returns
+///
+/// An empty result, or a `NetworkError` if the block can't be read or sent, in which case the peer is disconnected.
+fn send_stored_block(
+    start_string: Vec<u8>,
+    location: &BlockLocation,
+    client_node: &mut TcpStream,
+) -> Result<(), NetworkError> {
+    let mut header = MessageHeader::new(start_string, BLOCK_COMMAND.to_string());
+    header.update_payload(location.get_size(), location.get_checksum().clone());
+    client_node
+        .write_all(&header.as_bytes())
+        .map_err(|_| NetworkError::PeerConnection)?;
+    location
+        .copy_to(client_node)
+        .map_err(|_| NetworkError::PeerConnection)
+}
+
+/// Returns the hashes of the blocks of the chain from a height up to a block, as the compact filter messages request.
+///
+/// # Arguments
+///
+/// * `chain` - The view of the chain.
+/// * `start_height` - The height of the first block.
+/// * `stop_hash` - The hash of the last block.
+/// * `max_count` - The maximum number of blocks of the range.
+///
+/// # Returns
+///
+/// The hashes from the first block to the last one, or a `NetworkError` if the last block is unknown or the range is
+/// empty or too long, in which case the peer is disconnected.
+fn get_block_range(
+    chain: &ChainView,
+    start_height: u32,
+    stop_hash: &[u8],
+    max_count: u32,
+) -> Result<Vec<Vec<u8>>, NetworkError> {
+    let stop_height = chain
+        .get_height(stop_hash)
+        .ok_or(NetworkError::PeerConnection)?;
+    let start_height = start_height as usize;
+    if start_height > stop_height || stop_height - start_height >= max_count as usize {
+        return Err(NetworkError::PeerConnection);
+    }
+
+    let mut hashes = vec![stop_hash.to_vec()];
+    while hashes.len() <= stop_height - start_height {
+        let previous = chain
+            .get_header(&hashes[hashes.len() - 1])
+            .ok_or(NetworkError::PeerConnection)?
+            .get_previuos_block_header()
+            .clone();
+        hashes.push(previous);
+    }
+    hashes.reverse();
+    Ok(hashes)
+}
+
+/// Locks the blockchain, checking that it serves the requested type of compact filters.
+fn lock_filters_blockchain(
+    blockchain: &Arc<Mutex<BlockChain>>,
+    filter_type: u8,
+) -> Result<MutexGuard<'_, BlockChain>, NetworkError> {
+    let locked_blockchain = blockchain
+        .lock()
+        .map_err(|_| NetworkError::PeerConnection)?;
+    if filter_type != BASIC_FILTER_TYPE || locked_blockchain.get_filter_index().is_none() {
+        return Err(NetworkError::PeerConnection);
+    }
+    Ok(locked_blockchain)
+}
+
+/// Sends a `cfilter` message for each block of the requested range, failing without sending any if some block has no
+/// compact filter.
+fn handle_get_cfilters_message(
+    settings: &Arc<Settings>,
+    blockchain: &Arc<Mutex<BlockChain>>,
+    block_headers: &Arc<Mutex<HashMap<Vec<u8>, BlockHeader>>>,
+    get_cfilters: GetCFilters,
+    client_node: &mut TcpStream,
+) -> Result<(), NetworkError> {
+    let locked_blockchain = lock_filters_blockchain(blockchain, get_cfilters.get_filter_type())?;
+    let locked_headers = block_headers
+        .lock()
+        .map_err(|_| NetworkError::PeerConnection)?;
+    let chain = ChainView::new(&locked_blockchain, &locked_headers, settings.get_network());
+    let range = get_block_range(
+        &chain,
+        get_cfilters.get_start_height(),
+        get_cfilters.get_stop_hash(),
+        MAX_CFILTERS_COUNT,
+    )?;
+
+    let filter_index = locked_blockchain
+        .get_filter_index()
+        .ok_or(NetworkError::PeerConnection)?;
+    let cfilters = range
+        .into_iter()
+        .map(|block_hash| {
+            let filter = filter_index.get_filter(&block_hash)?;
+            Some(CFilter::new(
+                settings.get_start_string(),
+                BASIC_FILTER_TYPE,
+                block_hash,
+                filter.as_bytes().clone(),
+            ))
+        })
+        .collect::<Option<Vec<CFilter>>>()
+        .ok_or(NetworkError::PeerConnection)?;
+    for cfilter in cfilters {
+        client_node
+            .write_all(&cfilter.as_bytes())
+            .map_err(|_| NetworkError::PeerConnection)?;
+    }
+    Ok(())
+}
+
+/// Sends a `cfheaders` message with the hashes of the compact filters of the requested range, which must all have one,
+/// and the header of the filter before the range, which must be chained to the genesis block.
+fn handle_get_cfheaders_message(
+    settings: &Arc<Settings>,
+    blockchain: &Arc<Mutex<BlockChain>>,
+    block_headers: &Arc<Mutex<HashMap<Vec<u8>, BlockHeader>>>,
+    get_cfheaders: GetCFHeaders,
+    client_node: &mut TcpStream,
+) -> Result<(), NetworkError> {
+    let mut locked_blockchain =
+        lock_filters_blockchain(blockchain, get_cfheaders.get_filter_type())?;
+    let locked_headers = block_headers
+        .lock()
+        .map_err(|_| NetworkError::PeerConnection)?;
+    let chain = ChainView::new(&locked_blockchain, &locked_headers, settings.get_network());
+    let range = get_block_range(
+        &chain,
+        get_cfheaders.get_start_height(),
+        get_cfheaders.get_stop_hash(),
+        MAX_CFHEADERS_COUNT,
+    )?;
+    let previous_hash = match chain.get_height(&range[0]) {
+        Some(0) => None,
+        _ => Some(
+            chain
+                .get_header(&range[0])
+                .ok_or(NetworkError::PeerConnection)?
+                .get_previuos_block_header()
+                .clone(),
+        ),
+    };
+    drop(locked_headers);
+
+    let filter_index = locked_blockchain
+        .get_filter_index()
+        .ok_or(NetworkError::PeerConnection)?;
+    let filter_hashes = range
+        .iter()
+        .map(|block_hash| {
+            filter_index
+                .get_filter(block_hash)
+                .map(|filter| filter.get_hash())
+        })
+        .collect::<Option<Vec<Vec<u8>>>>()
+        .ok_or(NetworkError::PeerConnection)?;
+    let previous_filter_header = match previous_hash {
+        Some(previous_hash) => locked_blockchain
+            .get_filter_header(&previous_hash)?
+            .ok_or(NetworkError::PeerConnection)?,
+        None => vec![0; 32],
+    };
+
+    let cfheaders = CFHeaders::new(
+        settings.get_start_string(),
+        BASIC_FILTER_TYPE,
+        get_cfheaders.get_stop_hash().clone(),
+        previous_filter_header,
+        filter_hashes,
+    );
+    client_node
+        .write_all(&cfheaders.as_bytes())
+        .map_err(|_| NetworkError::PeerConnection)?;
+    Ok(())
+}
+
+/// Sends a `cfcheckpt` message with the headers of the compact filters every `CFCHECKPT_INTERVAL` blocks up to the
+/// requested block, failing if some of them isn't chained to the genesis block.
+fn handle_get_cfcheckpt_message(
+    settings: &Arc<Settings>,
+    blockchain: &Arc<Mutex<BlockChain>>,
+    block_headers: &Arc<Mutex<HashMap<Vec<u8>, BlockHeader>>>,
+    get_cfcheckpt: GetCFCheckpt,
+    client_node: &mut TcpStream,
+) -> Result<(), NetworkError> {
+    let mut locked_blockchain =
+        lock_filters_blockchain(blockchain, get_cfcheckpt.get_filter_type())?;
+    let locked_headers = block_headers
+        .lock()
+        .map_err(|_| NetworkError::PeerConnection)?;
+    let chain = ChainView::new(&locked_blockchain, &locked_headers, settings.get_network());
+    let mut height = chain
+        .get_height(get_cfcheckpt.get_stop_hash())
+        .ok_or(NetworkError::PeerConnection)?;
+
+    let mut checkpoints = vec![];
+    let mut current = get_cfcheckpt.get_stop_hash().clone();
+    while height > 0 {
+        let previous = chain
+            .get_header(&current)
+            .ok_or(NetworkError::PeerConnection)?
+            .get_previuos_block_header()
+            .clone();
+        if height % CFCHECKPT_INTERVAL == 0 {
+            checkpoints.push(current);
+        }
+        current = previous;
+        height -= 1;
+    }
+    drop(locked_headers);
+
+    let mut filter_headers = vec![];
+    for block_hash in checkpoints.iter().rev() {
+        filter_headers.push(
+            locked_blockchain
+                .get_filter_header(block_hash)?
+                .ok_or(NetworkError::PeerConnection)?,
+        );
+    }
+
+    let cfcheckpt = CFCheckpt::new(
+        settings.get_start_string(),
+        BASIC_FILTER_TYPE,
+        get_cfcheckpt.get_stop_hash().clone(),
+        filter_headers,
+    );
+    client_node
+        .write_all(&cfcheckpt.as_bytes())
+        .map_err(|_| NetworkError::PeerConnection)?;
+    Ok(())
+}
+
 fn handle_messages(
     peer: &mut TcpStream,
     settings: &Arc<Settings>,
@@ -130,6 +400,21 @@ fn handle_messages(
                     GetData::from_bytes(header, peer).map_err(|_| NetworkError::PeerConnection)?;
                 handle_get_data_message(settings.get_start_string(), blockchain, get_data, peer)?;
             }
+            GET_CFILTERS_COMMAND => {
+                let get_cfilters = GetCFilters::from_bytes(header, peer)
+                    .map_err(|_| NetworkError::PeerConnection)?;
+                handle_get_cfilters_message(settings, blockchain, headers, get_cfilters, peer)?;
+            }
+            GET_CFHEADERS_COMMAND => {
+                let get_cfheaders = GetCFHeaders::from_bytes(header, peer)
+                    .map_err(|_| NetworkError::PeerConnection)?;
+                handle_get_cfheaders_message(settings, blockchain, headers, get_cfheaders, peer)?;
+            }
+            GET_CFCHECKPT_COMMAND => {
+                let get_cfcheckpt = GetCFCheckpt::from_bytes(header, peer)
+                    .map_err(|_| NetworkError::PeerConnection)?;
+                handle_get_cfcheckpt_message(settings, blockchain, headers, get_cfcheckpt, peer)?;
+            }
             _ => {
                 handle_other_message(peer, &header).map_err(|_| NetworkError::PeerConnection)?;
             }
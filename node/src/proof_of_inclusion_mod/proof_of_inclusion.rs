@@ -1,8 +1,9 @@
 use super::proof_of_inclusion_error::ProofOfInclusionError;
 use crate::messages::message_constants::NOT_FOUND_COMMAND;
 use crate::{
-    block_mod::blockchain::BlockChain, messages::read_from_bytes::fill_command,
-    wallet_utils::merkle_block::MerkleBlock,
+    block_mod::blockchain::BlockChain,
+    messages::read_from_bytes::fill_command,
+    wallet_utils::{merkle_block::MerkleBlock, wallet_utils_constants::NULL_BLOCK_HASH},
 };
 use bitcoin_hashes::{sha256d, Hash};
 use std::collections::HashSet;
@@ -134,7 +135,8 @@ fn not_found(stream: &mut dyn Write) -> Result<(), ProofOfInclusionError> {
 ///
 /// # Arguments
 ///
-/// * `block_hash` - The hash of the block containing the transaction.
+/// * `block_hash` - The hash of the block containing the transaction, or all zeros to find the block in the transaction
+///   index.
 /// * `txn` - The transaction to prove inclusion for.
 /// * `blockchain` - A reference to the blockchain as a shared mutable state.
 /// * `stream` - A mutable reference to the stream to write the proof to.
@@ -155,7 +157,14 @@ pub fn send_proof(
     block_hash.reverse();
     txn.reverse();
 
-    let block = match blockchain.get_blocks().get(&block_hash) {
+    if block_hash == NULL_BLOCK_HASH {
+        block_hash = match blockchain.get_indexed_transaction(&txn) {
+            Some((indexed_block_hash, _)) => indexed_block_hash,
+            None => return not_found(stream),
+        };
+    }
+
+    let block = match blockchain.get_block(&block_hash) {
         Some(block) => block,
         None => return not_found(stream),
     };
@@ -0,0 +1,10 @@
+pub mod chain_view;
+pub mod http;
+pub mod http_error;
+pub mod json;
+pub mod json_serializer;
+pub mod rest;
+pub mod rpc_constants;
+pub mod rpc_error;
+pub mod rpc_methods;
+pub mod rpc_server;
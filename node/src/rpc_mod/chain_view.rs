@@ -0,0 +1,206 @@
+use std::collections::HashMap;
+
+use crate::{
+    block_mod::{
+        block::Block, block_header::BlockHeader, blockchain::BlockChain, transaction::Transaction,
+    },
+    network::bitcoin_network::BitcoinNetwork,
+};
+
+use super::{json_serializer::ChainPosition, rpc_error::RpcError};
+
+/// The headers and the blocks of the node, locked, to find where a block is in the chain.
+pub struct ChainView<'a> {
+    blockchain: &'a BlockChain,
+    headers: &'a HashMap<Vec<u8>, BlockHeader>,
+    genesis: BlockHeader,
+}
+
+impl<'a> ChainView<'a> {
+    /// Creates the view of the chain of a network. The blockchain must be locked before the headers, as the
+    /// broadcasting does, so they can't deadlock.
+    pub fn new(
+        blockchain: &'a BlockChain,
+        headers: &'a HashMap<Vec<u8>, BlockHeader>,
+        network: BitcoinNetwork,
+    ) -> ChainView<'a> {
+        ChainView {
+            blockchain,
+            headers,
+            genesis: network.get_genesis_header(),
+        }
+    }
+
+    /// Returns the header of a block, from the downloaded headers or blocks, pruned ones included.
+    pub fn get_header(&self, block_hash: &[u8]) -> Option<BlockHeader> {
+        if block_hash == self.genesis.get_header() {
+            return Some(self.genesis.clone());
+        }
+        match self.headers.get(block_hash) {
+            Some(header) => Some(header.clone()),
+            None => self.blockchain.get_block_header(block_hash).cloned(),
+        }
+    }
+
+    /// Returns the headers of the chain starting at the given block, following the next block of each one.
+    ///
+    /// # Arguments
+    ///
+    /// * `block_hash` - The hash of the first block.
+    /// * `count` - The maximum number of headers.
+    ///
+    /// # Returns
+    ///
+    /// The headers, fewer than `count` if the chain ends before, or none at all if the block is unknown.
+    pub fn get_headers_from(&self, block_hash: &[u8], count: usize) -> Vec<BlockHeader> {
+        let mut headers = vec![];
+        let mut current = self.get_header(block_hash);
+
+        while let Some(header) = current {
+            if headers.len() == count {
+                break;
+            }
+            current = match self.headers.get(&header.get_header()) {
+                Some(stored) => stored
+                    .get_next_block_header()
+                    .as_ref()
+                    .and_then(|next| self.get_header(next)),
+                None => None,
+            };
+            headers.push(header);
+        }
+        headers
+    }
+
+    /// Returns the height of a block, walking back to the genesis block, or `None` if some ancestor is unknown.
+    pub fn get_height(&self, block_hash: &[u8]) -> Option<usize> {
+        let genesis = self.genesis.get_header();
+        let mut current = block_hash.to_vec();
+        let mut height = 0;
+
+        while current != genesis {
+            current = match self.headers.get(&current) {
+                Some(header) => header.get_previuos_block_header().clone(),
+                None => self
+                    .blockchain
+                    .get_block_header(&current)?
+                    .get_previuos_block_header()
+                    .clone(),
+            };
+            height += 1;
+        }
+        Some(height)
+    }
+
+    pub fn get_tip_height(&self) -> usize {
+        self.get_height(&self.blockchain.get_last_block_header())
+            .unwrap_or(0)
+    }
+
+    /// Returns the height of a block and its confirmations, which are -1 if a reorganization left it out of the chain.
+    pub fn get_position(&self, block_hash: &[u8]) -> ChainPosition {
+        let height = self.get_height(block_hash);
+        let disconnected = matches!(
+            self.blockchain.find_fork_point(block_hash),
+            Some((_, disconnected)) if !disconnected.is_empty()
+        );
+        let confirmations = match (height, disconnected) {
+            (Some(height), false) => self.get_tip_height() as i64 - height as i64 + 1,
+            _ => -1,
+        };
+        ChainPosition {
+            height,
+            confirmations,
+        }
+    }
+
+    /// Finds a transaction, coinbases included, in the downloaded blocks or only in the given one. Without a block, the
+    /// transaction index is used if it is enabled instead of looking in every block, only the blocks of the active chain
+    /// being considered.
+    ///
+    /// # Returns
+    ///
+    /// The transaction and the hash of the block that includes it, or `None` if no block does.
+    pub fn find_transaction(
+        &self,
+        tx_id: &[u8],
+        block_hash: Option<&[u8]>,
+    ) -> Result<Option<(Transaction, Vec<u8>)>, RpcError> {
+        if let (None, Some(_)) = (block_hash, self.blockchain.get_tx_index()) {
+            let (hash, position) = match self.blockchain.get_indexed_transaction(tx_id) {
+                Some(entry) => entry,
+                None => return Ok(None),
+            };
+            return match self.blockchain.get_block(&hash) {
+                Some(block) => self.get_transaction(&block, position as usize, &hash),
+                None => Ok(None),
+            };
+        }
+
+        for hash in self.blockchain.get_block_hashes() {
+            if block_hash.is_some_and(|block_hash| block_hash != hash.as_slice()) {
+                continue;
+            }
+            let block = match self.blockchain.get_block(&hash) {
+                Some(block) => block,
+                None => continue,
+            };
+            if let Some(position) = block.get_txn_ids().iter().position(|id| id == tx_id) {
+                return self.get_transaction(&block, position, &hash);
+            }
+        }
+        Ok(None)
+    }
+
+    /// Returns the transaction at a position of the ids of a block, the coinbase being the first one.
+    fn get_transaction(
+        &self,
+        block: &Block,
+        position: usize,
+        block_hash: &[u8],
+    ) -> Result<Option<(Transaction, Vec<u8>)>, RpcError> {
+        let transaction = match position {
+            0 => Some(block.get_coinbase().as_transaction()?),
+            position => block.get_txn_list().get(position - 1).cloned(),
+        };
+        Ok(transaction.map(|transaction| (transaction, block_hash.to_vec())))
+    }
+}
+
+#[cfg(test)]
+mod chain_view_test {
+    use super::*;
+
+    #[test]
+    fn test_headers_and_heights() {
+        let network = BitcoinNetwork::Testnet;
+        let mut genesis = network.get_genesis_header();
+        let first = BlockHeader::new(1, genesis.get_header(), vec![0; 32], 1, 0, 0);
+        let mut first_stored = first.clone();
+        let second = BlockHeader::new(1, first.get_header(), vec![0; 32], 2, 0, 0);
+        genesis.set_next_block_header(first.get_header());
+        first_stored.set_next_block_header(second.get_header());
+
+        let headers: HashMap<Vec<u8>, BlockHeader> =
+            [genesis.clone(), first_stored, second.clone()]
+                .into_iter()
+                .map(|header| (header.get_header(), header))
+                .collect();
+        let blockchain = BlockChain::new(network);
+        let chain = ChainView::new(&blockchain, &headers, network);
+
+        assert_eq!(chain.get_height(&genesis.get_header()), Some(0));
+        assert_eq!(chain.get_height(&second.get_header()), Some(2));
+        assert_eq!(chain.get_height(&[1; 32]), None);
+        assert_eq!(
+            chain
+                .get_headers_from(&first.get_header(), 5)
+                .iter()
+                .map(BlockHeader::get_header)
+                .collect::<Vec<Vec<u8>>>(),
+            vec![first.get_header(), second.get_header()]
+        );
+        assert_eq!(chain.get_headers_from(&genesis.get_header(), 1).len(), 1);
+        assert!(chain.get_headers_from(&[1; 32], 5).is_empty());
+    }
+}
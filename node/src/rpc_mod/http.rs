@@ -0,0 +1,280 @@
+use std::io::{BufRead, Read};
+
+use super::{http_error::HttpError, rpc_constants::*};
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// An HTTP request, with the names of its headers in lowercase.
+#[derive(Debug, PartialEq, Eq)]
+pub struct HttpRequest {
+    method: String,
+    path: String,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl HttpRequest {
+    /// Reads an HTTP/1.x request.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream` - The connection, buffered so the request line and the headers can be read line by line.
+    ///
+    /// # Returns
+    ///
+    /// The request, with the body given by its `Content-Length` header, or an `HttpError` if it is malformed, has more
+    /// than `MAX_HEADERS` headers, a line longer than `MAX_HEADER_LINE_LEN` or a body longer than `MAX_BODY_LEN`.
+    pub fn from_stream(stream: &mut dyn BufRead) -> Result<HttpRequest, HttpError> {
+        let request_line = read_line(stream)?;
+        let mut parts = request_line.split_whitespace();
+        let (method, path) = match (parts.next(), parts.next(), parts.next()) {
+            (Some(method), Some(path), Some(version)) if version.starts_with("HTTP/1.") => {
+                (method.to_string(), path.to_string())
+            }
+            _ => return Err(HttpError::Malformed),
+        };
+
+        let mut headers = vec![];
+        loop {
+            let line = read_line(stream)?;
+            if line.is_empty() {
+                break;
+            }
+            if headers.len() == MAX_HEADERS {
+                return Err(HttpError::TooLarge);
+            }
+            let (name, value) = line.split_once(':').ok_or(HttpError::Malformed)?;
+            headers.push((name.trim().to_lowercase(), value.trim().to_string()));
+        }
+
+        let mut request = HttpRequest {
+            method,
+            path,
+            headers,
+            body: vec![],
+        };
+        let content_length = match request.get_header(CONTENT_LENGTH_HEADER) {
+            Some(length) => length.parse().map_err(|_| HttpError::Malformed)?,
+            None => 0,
+        };
+        if content_length > MAX_BODY_LEN {
+            return Err(HttpError::TooLarge);
+        }
+        request.body = vec![0; content_length];
+        stream.read_exact(&mut request.body)?;
+        Ok(request)
+    }
+
+    pub fn get_method(&self) -> &str {
+        &self.method
+    }
+
+    pub fn get_path(&self) -> &str {
+        &self.path
+    }
+
+    /// Returns the value of a header, whose name must be given in lowercase.
+    pub fn get_header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn get_body(&self) -> &[u8] {
+        &self.body
+    }
+
+    /// Checks the basic authentication of the request.
+    ///
+    /// # Returns
+    ///
+    /// `true` if its `Authorization` header carries the given user and password.
+    pub fn is_authorized(&self, user: &str, password: &str) -> bool {
+        let expected = base64_encode(format!("{}:{}", user, password).as_bytes());
+        match self
+            .get_header(AUTHORIZATION_HEADER)
+            .and_then(|auth| auth.strip_prefix(BASIC_AUTH))
+        {
+            Some(credentials) => credentials.trim() == expected,
+            None => false,
+        }
+    }
+}
+
+/// Reads a line without its line break, which must not be longer than `MAX_HEADER_LINE_LEN`.
+fn read_line(stream: &mut dyn BufRead) -> Result<String, HttpError> {
+    let mut line = vec![];
+    let mut limited = Read::take(&mut *stream, MAX_HEADER_LINE_LEN as u64);
+    match limited.read_until(b'\n', &mut line)? {
+        0 => return Err(HttpError::Malformed),
+        len if len == MAX_HEADER_LINE_LEN && !line.ends_with(b"\n") => {
+            return Err(HttpError::TooLarge)
+        }
+        _ => {}
+    }
+    let line = String::from_utf8(line).map_err(|_| HttpError::Malformed)?;
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+/// An HTTP response.
+#[derive(Debug, PartialEq, Eq)]
+pub struct HttpResponse {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl HttpResponse {
+    /// Creates a response with a body of the given type.
+    pub fn new(status: u16, content_type: &str, body: Vec<u8>) -> HttpResponse {
+        HttpResponse {
+            status,
+            headers: vec![("Content-Type".to_string(), content_type.to_string())],
+            body,
+        }
+    }
+
+    /// Creates the response to a request that isn't authorized, which asks the client for basic authentication.
+    pub fn unauthorized() -> HttpResponse {
+        HttpResponse {
+            status: 401,
+            headers: vec![(
+                "WWW-Authenticate".to_string(),
+                "Basic realm=\"jsonrpc\"".to_string(),
+            )],
+            body: vec![],
+        }
+    }
+
+    pub fn get_status(&self) -> u16 {
+        self.status
+    }
+
+    pub fn get_body(&self) -> &[u8] {
+        &self.body
+    }
+
+    /// Serializes the response. The connection is closed after it, so it is announced with a `Connection` header.
+    pub fn as_bytes(&self) -> Vec<u8> {
+        let mut head = format!(
+            "{} {} {}\r\n",
+            HTTP_VERSION,
+            self.status,
+            reason_phrase(self.status)
+        );
+        for (name, value) in self.headers.iter() {
+            head += &format!("{}: {}\r\n", name, value);
+        }
+        head += &format!(
+            "Content-Length: {}\r\nConnection: close\r\n\r\n",
+            self.body.len()
+        );
+
+        let mut buff = head.into_bytes();
+        buff.extend(&self.body);
+        buff
+    }
+}
+
+/// Returns the reason phrase of the status codes the server answers with.
+fn reason_phrase(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        413 => "Payload Too Large",
+        503 => "Service Unavailable",
+        _ => "Internal Server Error",
+    }
+}
+
+/// Encodes bytes in base64 with padding, as used by basic authentication.
+pub fn base64_encode(bytes: &[u8]) -> String {
+    let mut encoded = String::new();
+    for chunk in bytes.chunks(3) {
+        let group = chunk.iter().enumerate().fold(0u32, |group, (i, byte)| {
+            group | (*byte as u32) << (16 - 8 * i)
+        });
+        for i in 0..4 {
+            if i <= chunk.len() {
+                let index = (group >> (18 - 6 * i)) & 0x3f;
+                encoded.push(BASE64_ALPHABET[index as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod http_test {
+    use super::*;
+
+    #[test]
+    fn test_base64_encode() {
+        assert_eq!(base64_encode(b""), "");
+        assert_eq!(base64_encode(b"f"), "Zg==");
+        assert_eq!(base64_encode(b"fo"), "Zm8=");
+        assert_eq!(base64_encode(b"foo"), "Zm9v");
+        assert_eq!(base64_encode(b"user:pass"), "dXNlcjpwYXNz");
+    }
+
+    #[test]
+    fn test_read_request() -> Result<(), HttpError> {
+        let bytes = b"POST / HTTP/1.1\r\nHost: localhost\r\nAuthorization: Basic dXNlcjpwYXNz\r\nContent-Length: 4\r\n\r\nbodyextra";
+        let request = HttpRequest::from_stream(&mut &bytes[..])?;
+
+        assert_eq!(request.get_method(), "POST");
+        assert_eq!(request.get_path(), "/");
+        assert_eq!(request.get_header("host"), Some("localhost"));
+        assert_eq!(request.get_body(), b"body");
+        assert!(request.is_authorized("user", "pass"));
+        assert!(!request.is_authorized("user", "other"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_malformed_requests() {
+        let too_long = format!(
+            "POST / HTTP/1.1\r\nContent-Length: {}\r\n\r\n",
+            MAX_BODY_LEN + 1
+        );
+        for bytes in [
+            "GET /\r\n\r\n".as_bytes(),
+            b"POST / HTTP/1.1\r\nContent-Length: 10\r\n\r\nshort",
+            b"POST / HTTP/1.1\r\nno header\r\n\r\n",
+            too_long.as_bytes(),
+            b"",
+        ] {
+            assert!(HttpRequest::from_stream(&mut &bytes[..]).is_err());
+        }
+    }
+
+    #[test]
+    fn test_long_header_line() {
+        let header = format!(
+            "POST / HTTP/1.1\r\nHost: {}\r\n\r\n",
+            "a".repeat(MAX_HEADER_LINE_LEN)
+        );
+
+        assert_eq!(
+            HttpRequest::from_stream(&mut header.as_bytes()),
+            Err(HttpError::TooLarge)
+        );
+    }
+
+    #[test]
+    fn test_response_as_bytes() {
+        let response = HttpResponse::new(200, JSON_CONTENT_TYPE, b"{}".to_vec());
+
+        assert_eq!(
+            response.as_bytes(),
+            b"HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: 2\r\nConnection: close\r\n\r\n{}"
+        );
+    }
+}
@@ -0,0 +1,22 @@
+#[derive(Debug, PartialEq, Eq)]
+pub enum HttpError {
+    Io,
+    Malformed,
+    TooLarge,
+}
+
+impl From<std::io::Error> for HttpError {
+    fn from(_: std::io::Error) -> HttpError {
+        HttpError::Io
+    }
+}
+
+impl std::fmt::Display for HttpError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            HttpError::Io => write!(f, "the connection can't be read or written"),
+            HttpError::Malformed => write!(f, "the request isn't valid HTTP"),
+            HttpError::TooLarge => write!(f, "the request is too large"),
+        }
+    }
+}
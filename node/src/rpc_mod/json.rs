@@ -0,0 +1,351 @@
+use std::{iter::Peekable, str::Chars};
+
+use super::{rpc_constants::MAX_JSON_DEPTH, rpc_error::RpcError};
+
+/// A JSON value, as read from a request or written to a response.
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonValue {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+    Array(Vec<JsonValue>),
+    /// The members of an object, in the order they are written.
+    Object(Vec<(String, JsonValue)>),
+}
+
+impl JsonValue {
+    /// Parses a JSON document.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The document, a single value optionally surrounded by whitespace.
+    ///
+    /// # Returns
+    ///
+    /// The value, or `RpcError::Parse` if the text isn't valid JSON or nests more than `MAX_JSON_DEPTH` arrays and
+    /// objects.
+    pub fn parse(text: &str) -> Result<JsonValue, RpcError> {
+        let mut chars = text.chars().peekable();
+        let value = parse_value(&mut chars, 0)?;
+        skip_whitespace(&mut chars);
+        match chars.next() {
+            Some(_) => Err(RpcError::Parse),
+            None => Ok(value),
+        }
+    }
+
+    /// Creates an object from its members.
+    pub fn object(members: Vec<(&str, JsonValue)>) -> JsonValue {
+        JsonValue::Object(
+            members
+                .into_iter()
+                .map(|(name, value)| (name.to_string(), value))
+                .collect(),
+        )
+    }
+
+    /// Adds a member at the end of an object, doing nothing if the value isn't an object.
+    pub fn push(&mut self, name: &str, value: JsonValue) {
+        if let JsonValue::Object(members) = self {
+            members.push((name.to_string(), value));
+        }
+    }
+
+    /// Returns the member of an object with the given name, or `None` if it isn't an object or has no such member.
+    pub fn get(&self, name: &str) -> Option<&JsonValue> {
+        match self {
+            JsonValue::Object(members) => members
+                .iter()
+                .find(|(member, _)| member == name)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            JsonValue::String(text) => Some(text),
+            _ => None,
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            JsonValue::Int(number) => Some(*number),
+            _ => None,
+        }
+    }
+
+    /// Returns a boolean, also accepting the numbers 0 and 1 that bitcoind takes for verbosity flags.
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            JsonValue::Bool(flag) => Some(*flag),
+            JsonValue::Int(0) => Some(false),
+            JsonValue::Int(1) => Some(true),
+            _ => None,
+        }
+    }
+}
+
+impl From<&str> for JsonValue {
+    fn from(text: &str) -> JsonValue {
+        JsonValue::String(text.to_string())
+    }
+}
+
+impl From<String> for JsonValue {
+    fn from(text: String) -> JsonValue {
+        JsonValue::String(text)
+    }
+}
+
+impl std::fmt::Display for JsonValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            JsonValue::Null => write!(f, "null"),
+            JsonValue::Bool(flag) => write!(f, "{}", flag),
+            JsonValue::Int(number) => write!(f, "{}", number),
+            JsonValue::Float(number) if number.is_finite() => write!(f, "{}", number),
+            JsonValue::Float(_) => write!(f, "null"),
+            JsonValue::String(text) => write_string(f, text),
+            JsonValue::Array(values) => {
+                write!(f, "[")?;
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", value)?;
+                }
+                write!(f, "]")
+            }
+            JsonValue::Object(members) => {
+                write!(f, "{{")?;
+                for (i, (name, value)) in members.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write_string(f, name)?;
+                    write!(f, ":{}", value)?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+/// Writes a string with its quotes, escaping the characters JSON doesn't allow inside one.
+fn write_string(f: &mut std::fmt::Formatter, text: &str) -> std::fmt::Result {
+    write!(f, "\"")?;
+    for c in text.chars() {
+        match c {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{}", c)?,
+        }
+    }
+    write!(f, "\"")
+}
+
+fn skip_whitespace(chars: &mut Peekable<Chars>) {
+    while chars.next_if(|c| c.is_ascii_whitespace()).is_some() {}
+}
+
+/// Parses the value that starts at the next non whitespace character, inside `depth` arrays and objects.
+fn parse_value(chars: &mut Peekable<Chars>, depth: usize) -> Result<JsonValue, RpcError> {
+    skip_whitespace(chars);
+    match chars.peek().ok_or(RpcError::Parse)? {
+        '{' | '[' if depth >= MAX_JSON_DEPTH => Err(RpcError::Parse),
+        '{' => parse_object(chars, depth + 1),
+        '[' => parse_array(chars, depth + 1),
+        '"' => parse_string(chars).map(JsonValue::String),
+        't' => parse_literal(chars, "true", JsonValue::Bool(true)),
+        'f' => parse_literal(chars, "false", JsonValue::Bool(false)),
+        'n' => parse_literal(chars, "null", JsonValue::Null),
+        c if *c == '-' || c.is_ascii_digit() => parse_number(chars),
+        _ => Err(RpcError::Parse),
+    }
+}
+
+fn parse_literal(
+    chars: &mut Peekable<Chars>,
+    literal: &str,
+    value: JsonValue,
+) -> Result<JsonValue, RpcError> {
+    for expected in literal.chars() {
+        if chars.next() != Some(expected) {
+            return Err(RpcError::Parse);
+        }
+    }
+    Ok(value)
+}
+
+fn parse_number(chars: &mut Peekable<Chars>) -> Result<JsonValue, RpcError> {
+    let mut number = String::new();
+    while let Some(c) = chars.next_if(|c| c.is_ascii_digit() || "+-.eE".contains(*c)) {
+        number.push(c);
+    }
+    if let Ok(number) = number.parse::<i64>() {
+        return Ok(JsonValue::Int(number));
+    }
+    number
+        .parse::<f64>()
+        .map(JsonValue::Float)
+        .map_err(|_| RpcError::Parse)
+}
+
+fn parse_string(chars: &mut Peekable<Chars>) -> Result<String, RpcError> {
+    if chars.next() != Some('"') {
+        return Err(RpcError::Parse);
+    }
+    let mut text = String::new();
+    loop {
+        match chars.next().ok_or(RpcError::Parse)? {
+            '"' => return Ok(text),
+            '\\' => match chars.next().ok_or(RpcError::Parse)? {
+                '"' => text.push('"'),
+                '\\' => text.push('\\'),
+                '/' => text.push('/'),
+                'b' => text.push('\u{8}'),
+                'f' => text.push('\u{c}'),
+                'n' => text.push('\n'),
+                'r' => text.push('\r'),
+                't' => text.push('\t'),
+                'u' => {
+                    let code: String = chars.by_ref().take(4).collect();
+                    let code = u32::from_str_radix(&code, 16).map_err(|_| RpcError::Parse)?;
+                    text.push(char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER));
+                }
+                _ => return Err(RpcError::Parse),
+            },
+            c => text.push(c),
+        }
+    }
+}
+
+fn parse_array(chars: &mut Peekable<Chars>, depth: usize) -> Result<JsonValue, RpcError> {
+    chars.next();
+    let mut values = vec![];
+    skip_whitespace(chars);
+    if chars.next_if_eq(&']').is_some() {
+        return Ok(JsonValue::Array(values));
+    }
+    loop {
+        values.push(parse_value(chars, depth)?);
+        skip_whitespace(chars);
+        match chars.next() {
+            Some(',') => continue,
+            Some(']') => return Ok(JsonValue::Array(values)),
+            _ => return Err(RpcError::Parse),
+        }
+    }
+}
+
+fn parse_object(chars: &mut Peekable<Chars>, depth: usize) -> Result<JsonValue, RpcError> {
+    chars.next();
+    let mut members = vec![];
+    skip_whitespace(chars);
+    if chars.next_if_eq(&'}').is_some() {
+        return Ok(JsonValue::Object(members));
+    }
+    loop {
+        skip_whitespace(chars);
+        let name = parse_string(chars)?;
+        skip_whitespace(chars);
+        if chars.next() != Some(':') {
+            return Err(RpcError::Parse);
+        }
+        members.push((name, parse_value(chars, depth)?));
+        skip_whitespace(chars);
+        match chars.next() {
+            Some(',') => continue,
+            Some('}') => return Ok(JsonValue::Object(members)),
+            _ => return Err(RpcError::Parse),
+        }
+    }
+}
+
+#[cfg(test)]
+mod json_test {
+    use super::*;
+
+    #[test]
+    fn test_parse_request() -> Result<(), RpcError> {
+        let request = JsonValue::parse(
+            " {\"jsonrpc\": \"1.0\", \"id\": \"curl\", \"method\": \"getblock\", \"params\": [\"00ab\", 2, true, null, -1.5, \"a\\\"\\u00e9\"]} ",
+        )?;
+
+        assert_eq!(request.get("method"), Some(&JsonValue::from("getblock")));
+        assert_eq!(
+            request.get("params"),
+            Some(&JsonValue::Array(vec![
+                JsonValue::from("00ab"),
+                JsonValue::Int(2),
+                JsonValue::Bool(true),
+                JsonValue::Null,
+                JsonValue::Float(-1.5),
+                JsonValue::from("a\"é"),
+            ]))
+        );
+        assert_eq!(request.get("missing"), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_invalid_json() {
+        for text in [
+            "",
+            "{",
+            "[1,]",
+            "{\"a\" 1}",
+            "tru",
+            "\"open",
+            "1 2",
+            "{1:2}",
+        ] {
+            assert_eq!(JsonValue::parse(text), Err(RpcError::Parse), "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_parse_nesting_limit() -> Result<(), RpcError> {
+        let nested = |depth| "[".repeat(depth) + &"]".repeat(depth);
+        JsonValue::parse(&nested(MAX_JSON_DEPTH))?;
+        assert_eq!(
+            JsonValue::parse(&nested(MAX_JSON_DEPTH + 1)),
+            Err(RpcError::Parse)
+        );
+        assert_eq!(
+            JsonValue::parse(&"{\"a\":".repeat(100_000)),
+            Err(RpcError::Parse)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_serialize_round_trip() -> Result<(), RpcError> {
+        let value = JsonValue::object(vec![
+            ("txid", JsonValue::from("ab\n\"c\"")),
+            ("value", JsonValue::Float(0.0015)),
+            ("n", JsonValue::Int(0)),
+            (
+                "vin",
+                JsonValue::Array(vec![JsonValue::Null, JsonValue::Bool(false)]),
+            ),
+            ("empty", JsonValue::Object(vec![])),
+        ]);
+        let text = value.to_string();
+
+        assert_eq!(
+            text,
+            "{\"txid\":\"ab\\n\\\"c\\\"\",\"value\":0.0015,\"n\":0,\"vin\":[null,false],\"empty\":{}}"
+        );
+        assert_eq!(JsonValue::parse(&text)?, value);
+        Ok(())
+    }
+}
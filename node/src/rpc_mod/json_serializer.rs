@@ -0,0 +1,296 @@
+use bitcoin_hashes::{sha256d, Hash};
+
+use crate::{
+    block_mod::{
+        block::Block, block_header::BlockHeader, script_type::ScriptType, transaction::Transaction,
+        tx_in::TxIn, tx_out::TxOut,
+    },
+    messages::read_from_bytes::{decode_hex, encode_hex},
+};
+
+use super::{
+    json::JsonValue,
+    rpc_constants::{HASH_HEX_LEN, NULL_OUTPOINT_INDEX, SATOSHIS_PER_BTC},
+    rpc_error::RpcError,
+};
+
+/// Where a block is in the chain, as far as the node knows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChainPosition {
+    /// The height of the block, `None` if its ancestors down to the genesis block aren't known.
+    pub height: Option<usize>,
+    /// The number of blocks of the chain from the block to the tip, the block included, or -1 if it isn't in the chain.
+    pub confirmations: i64,
+}
+
+/// Encodes a block hash or a transaction id in hex, in the reversed byte order bitcoind shows them.
+pub fn hash_to_hex(hash: &[u8]) -> Result<String, RpcError> {
+    let mut hash = hash.to_vec();
+    hash.reverse();
+    Ok(encode_hex(&hash)?)
+}
+
+/// Decodes a block hash or a transaction id given in hex as bitcoind shows them, in the byte order the node stores them.
+///
+/// # Arguments
+///
+/// * `hex` - The 64 hex digits of the hash.
+/// * `name` - The name of the hash in the error message.
+pub fn hash_from_hex(hex: &str, name: &str) -> Result<Vec<u8>, RpcError> {
+    let invalid =
+        || RpcError::InvalidParams(format!("{} must be of length {}", name, HASH_HEX_LEN));
+    if hex.len() != HASH_HEX_LEN || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(invalid());
+    }
+    let mut hash = decode_hex(hex).map_err(|_| invalid())?;
+    hash.reverse();
+    Ok(hash)
+}
+
+/// Converts satoshis to a BTC amount.
+pub fn btc_value(satoshis: i64) -> JsonValue {
+    JsonValue::Float(satoshis as f64 / SATOSHIS_PER_BTC)
+}
+
+/// Returns the name bitcoind gives to the kind of a public key script.
+fn script_type_name(pk_script: &[u8]) -> &'static str {
+    match ScriptType::from_pk_script(pk_script) {
+        ScriptType::P2pkh => "pubkeyhash",
+        ScriptType::P2sh => "scripthash",
+        ScriptType::P2wpkh => "witness_v0_keyhash",
+        ScriptType::P2wsh => "witness_v0_scripthash",
+        ScriptType::P2tr => "witness_v1_taproot",
+        ScriptType::Unknown => "nonstandard",
+    }
+}
+
+/// Serializes a public key script with the kind of script it is, as in the `scriptPubKey` of bitcoind.
+pub fn script_pub_key_to_json(pk_script: &[u8]) -> Result<JsonValue, RpcError> {
+    Ok(JsonValue::object(vec![
+        ("hex", JsonValue::from(encode_hex(pk_script)?)),
+        ("type", JsonValue::from(script_type_name(pk_script))),
+    ]))
+}
+
+/// Serializes an output, as in the `vout` array of bitcoind.
+///
+/// # Arguments
+///
+/// * `tx_out` - The output.
+/// * `index` - The index of the output in its transaction.
+pub fn tx_out_to_json(tx_out: &TxOut, index: usize) -> Result<JsonValue, RpcError> {
+    Ok(JsonValue::object(vec![
+        ("value", btc_value(tx_out.get_value())),
+        ("n", JsonValue::Int(index as i64)),
+        (
+            "scriptPubKey",
+            script_pub_key_to_json(&tx_out.get_pk_script())?,
+        ),
+    ]))
+}
+
+/// Serializes an input, as in the `vin` array of bitcoind. The input of a coinbase, which spends the null outpoint, only
+/// shows its script as `coinbase`.
+pub fn tx_in_to_json(tx_in: &TxIn) -> Result<JsonValue, RpcError> {
+    let outpoint = tx_in.get_prev_output();
+    let script_sig = encode_hex(tx_in.get_signature_script())?;
+    let mut members = match outpoint.get_tx_id().iter().all(|byte| *byte == 0)
+        && outpoint.get_index() == NULL_OUTPOINT_INDEX
+    {
+        true => vec![("coinbase", JsonValue::from(script_sig))],
+        false => vec![
+            ("txid", JsonValue::from(hash_to_hex(outpoint.get_tx_id())?)),
+            ("vout", JsonValue::Int(outpoint.get_index() as i64)),
+            (
+                "scriptSig",
+                JsonValue::object(vec![("hex", JsonValue::from(script_sig))]),
+            ),
+        ],
+    };
+
+    if !tx_in.get_witness().is_empty() {
+        let witness = tx_in
+            .get_witness()
+            .iter()
+            .map(|item| Ok(JsonValue::from(encode_hex(item)?)))
+            .collect::<Result<Vec<JsonValue>, RpcError>>()?;
+        members.push(("txinwitness", JsonValue::Array(witness)));
+    }
+    members.push(("sequence", JsonValue::Int(tx_in.get_sequence() as i64)));
+    Ok(JsonValue::object(members))
+}
+
+/// Serializes a transaction as bitcoind decodes it, with its inputs, its outputs and its raw hex.
+pub fn transaction_to_json(transaction: &Transaction) -> Result<JsonValue, RpcError> {
+    let bytes = transaction.as_bytes();
+    let base_size = transaction.as_bytes_without_witness().len();
+    let wtxid = sha256d::Hash::hash(&bytes).to_byte_array().to_vec();
+
+    let vin = transaction
+        .get_tx_in_list()
+        .iter()
+        .map(tx_in_to_json)
+        .collect::<Result<Vec<JsonValue>, RpcError>>()?;
+    let vout = transaction
+        .get_tx_out_list()
+        .iter()
+        .enumerate()
+        .map(|(index, tx_out)| tx_out_to_json(tx_out, index))
+        .collect::<Result<Vec<JsonValue>, RpcError>>()?;
+
+    Ok(JsonValue::object(vec![
+        ("txid", JsonValue::from(hash_to_hex(&transaction.get_id())?)),
+        ("hash", JsonValue::from(hash_to_hex(&wtxid)?)),
+        ("version", JsonValue::Int(transaction.get_version() as i64)),
+        ("size", JsonValue::Int(bytes.len() as i64)),
+        ("vsize", JsonValue::Int(transaction.get_vsize() as i64)),
+        (
+            "weight",
+            JsonValue::Int((base_size * 3 + bytes.len()) as i64),
+        ),
+        (
+            "locktime",
+            JsonValue::Int(transaction.get_lock_time() as i64),
+        ),
+        ("vin", JsonValue::Array(vin)),
+        ("vout", JsonValue::Array(vout)),
+        ("hex", JsonValue::from(encode_hex(&bytes)?)),
+    ]))
+}
+
+/// Returns the members bitcoind shows for a block header.
+fn header_members(
+    header: &BlockHeader,
+    position: ChainPosition,
+) -> Result<Vec<(&'static str, JsonValue)>, RpcError> {
+    let mut members = vec![
+        ("hash", JsonValue::from(hash_to_hex(&header.get_header())?)),
+        ("confirmations", JsonValue::Int(position.confirmations)),
+    ];
+    if let Some(height) = position.height {
+        members.push(("height", JsonValue::Int(height as i64)));
+    }
+    members.extend(vec![
+        ("version", JsonValue::Int(header.get_version() as i64)),
+        (
+            "versionHex",
+            JsonValue::from(format!("{:08x}", header.get_version())),
+        ),
+        (
+            "merkleroot",
+            JsonValue::from(hash_to_hex(header.get_merkle_root())?),
+        ),
+        ("time", JsonValue::Int(header.get_time() as i64)),
+        ("nonce", JsonValue::Int(header.get_nonce() as i64)),
+        (
+            "bits",
+            JsonValue::from(format!("{:08x}", header.get_nbits())),
+        ),
+    ]);
+    if position.height != Some(0) {
+        members.push((
+            "previousblockhash",
+            JsonValue::from(hash_to_hex(header.get_previuos_block_header())?),
+        ));
+    }
+    Ok(members)
+}
+
+/// Serializes a block header as the verbose output of bitcoind's `getblockheader`.
+pub fn header_to_json(
+    header: &BlockHeader,
+    position: ChainPosition,
+) -> Result<JsonValue, RpcError> {
+    Ok(JsonValue::object(header_members(header, position)?))
+}
+
+/// Serializes a block as the verbose output of bitcoind's `getblock`.
+///
+/// # Arguments
+///
+/// * `block` - The block.
+/// * `position` - Where the block is in the chain.
+/// * `with_transactions` - Whether to serialize the transactions, otherwise only their ids are listed.
+pub fn block_to_json(
+    block: &Block,
+    position: ChainPosition,
+    with_transactions: bool,
+) -> Result<JsonValue, RpcError> {
+    let mut members = header_members(block.get_header(), position)?;
+    let tx = match with_transactions {
+        true => {
+            let mut transactions = vec![transaction_to_json(
+                &block.get_coinbase().as_transaction()?,
+            )?];
+            for transaction in block.get_txn_list() {
+                transactions.push(transaction_to_json(transaction)?);
+            }
+            transactions
+        }
+        false => block
+            .get_txn_ids()
+            .iter()
+            .map(|tx_id| Ok(JsonValue::from(hash_to_hex(tx_id)?)))
+            .collect::<Result<Vec<JsonValue>, RpcError>>()?,
+    };
+
+    members.push(("size", JsonValue::Int(block.as_bytes().len() as i64)));
+    members.push(("nTx", JsonValue::Int(tx.len() as i64)));
+    members.push(("tx", JsonValue::Array(tx)));
+    Ok(JsonValue::object(members))
+}
+
+#[cfg(test)]
+mod json_serializer_test {
+    use super::*;
+
+    #[test]
+    fn test_transaction_to_json() -> Result<(), RpcError> {
+        let mut pk_script = vec![0x00, 20];
+        pk_script.extend([7; 20]);
+        let mut transaction = Transaction::new(
+            2,
+            vec![TxIn::new(vec![1; 32], 3, vec![], 0xfffffffd)],
+            vec![TxOut::new(150_000, pk_script)],
+            0,
+        );
+        transaction.set_witness(0, vec![vec![0xab; 2]]);
+
+        let json = transaction_to_json(&transaction)?;
+        let vin = json.get("vin").cloned();
+        let vout = json.get("vout").cloned();
+
+        assert_eq!(json.get("version"), Some(&JsonValue::Int(2)));
+        assert_eq!(
+            json.get("txid"),
+            Some(&JsonValue::from(hash_to_hex(&transaction.get_id())?))
+        );
+        assert_ne!(json.get("txid"), json.get("hash"));
+        assert_eq!(
+            vin.map(|vin| vin.to_string()),
+            Some(format!(
+                "[{{\"txid\":\"{}\",\"vout\":3,\"scriptSig\":{{\"hex\":\"\"}},\"txinwitness\":[\"abab\"],\"sequence\":4294967293}}]",
+                "01".repeat(32)
+            ))
+        );
+        assert_eq!(
+            vout.map(|vout| vout.to_string()),
+            Some(format!(
+                "[{{\"value\":0.0015,\"n\":0,\"scriptPubKey\":{{\"hex\":\"0014{}\",\"type\":\"witness_v0_keyhash\"}}}}]",
+                "07".repeat(20)
+            ))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_coinbase_input_to_json() -> Result<(), RpcError> {
+        let tx_in = TxIn::new(vec![0; 32], NULL_OUTPOINT_INDEX, vec![3, 1, 2, 3], 0);
+
+        assert_eq!(
+            tx_in_to_json(&tx_in)?.to_string(),
+            "{\"coinbase\":\"03010203\",\"sequence\":0}"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,255 @@
+use crate::{block_mod::transaction::Transaction, messages::read_from_bytes::encode_hex};
+
+use super::{
+    chain_view::ChainView,
+    http::HttpResponse,
+    json::JsonValue,
+    json_serializer::*,
+    rpc_constants::*,
+    rpc_error::RpcError,
+    rpc_methods::{get_mempool_info, get_raw_mempool, RpcContext},
+};
+
+/// The formats a REST resource can be served in, given by the extension of its path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RestFormat {
+    Bin,
+    Hex,
+    Json,
+}
+
+/// Answers a GET request of the REST interface.
+///
+/// # Arguments
+///
+/// * `path` - The path of the request, starting with `/rest/`.
+/// * `context` - The state of the node.
+///
+/// # Returns
+///
+/// The resource, or a plain text error with status 400 if the path is invalid or 404 if the resource isn't found.
+///
+/// # Description
+///
+/// The resources are the ones of bitcoind: `/rest/block/<hash>`, `/rest/headers/<count>/<hash>` and `/rest/tx/<txid>`,
+/// each with the extension `.bin`, `.hex` or `.json`, and `/rest/mempool/contents.json` and `/rest/mempool/info.json`.
+/// Blocks and confirmed transactions are served from the downloaded blocks, and headers from the headers of the chain.
+pub fn handle_rest(path: &str, context: &RpcContext) -> HttpResponse {
+    let resource = path.strip_prefix(REST_PREFIX).unwrap_or(path);
+    let resource = resource.split('?').next().unwrap_or(resource);
+    let parts: Vec<&str> = resource.split('/').collect();
+
+    let response = match parts.as_slice() {
+        [BLOCK_RESOURCE, hash] => get_block(hash, context),
+        [HEADERS_RESOURCE, count, hash] => get_headers(count, hash, context),
+        [TX_RESOURCE, tx_id] => get_tx(tx_id, context),
+        [MEMPOOL_RESOURCE, resource] => get_mempool(resource, context),
+        _ => Err(RpcError::InvalidParams("unknown REST resource".to_string())),
+    };
+
+    match response {
+        Ok(response) => response,
+        Err(err) => {
+            let status = match err {
+                RpcError::BlockNotFound | RpcError::TxNotFound => 404,
+                RpcError::InvalidParams(_) => 400,
+                _ => 500,
+            };
+            HttpResponse::new(
+                status,
+                TEXT_CONTENT_TYPE,
+                format!("{}\r\n", err).into_bytes(),
+            )
+        }
+    }
+}
+
+/// Splits the extension of the last part of a path.
+///
+/// # Returns
+///
+/// The part without its extension and the format it asks for.
+fn split_format(part: &str) -> Result<(&str, RestFormat), RpcError> {
+    let format = match part.rsplit_once('.') {
+        Some((name, BIN_FORMAT)) => (name, RestFormat::Bin),
+        Some((name, HEX_FORMAT)) => (name, RestFormat::Hex),
+        Some((name, JSON_FORMAT)) => (name, RestFormat::Json),
+        None => (part, RestFormat::Json),
+        Some(_) => {
+            return Err(RpcError::InvalidParams(
+                "output format not found (available: .bin, .hex, .json)".to_string(),
+            ))
+        }
+    };
+    Ok(format)
+}
+
+/// Creates the response with some bytes in the given format, or with the JSON if it is asked for.
+fn format_response(
+    format: RestFormat,
+    bytes: Vec<u8>,
+    json: impl FnOnce() -> Result<JsonValue, RpcError>,
+) -> Result<HttpResponse, RpcError> {
+    Ok(match format {
+        RestFormat::Bin => HttpResponse::new(200, BINARY_CONTENT_TYPE, bytes),
+        RestFormat::Hex => HttpResponse::new(
+            200,
+            TEXT_CONTENT_TYPE,
+            format!("{}\n", encode_hex(&bytes)?).into_bytes(),
+        ),
+        RestFormat::Json => {
+            HttpResponse::new(200, JSON_CONTENT_TYPE, json()?.to_string().into_bytes())
+        }
+    })
+}
+
+/// `/rest/block/<hash>`: a downloaded block, with its transactions decoded in JSON.
+fn get_block(part: &str, context: &RpcContext) -> Result<HttpResponse, RpcError> {
+    let (hash, format) = split_format(part)?;
+    let block_hash = hash_from_hex(hash, "hash")?;
+
+    let blockchain = context
+        .get_blockchain()
+        .lock()
+        .map_err(|_| RpcError::Lock)?;
+    let headers = context.get_headers().lock().map_err(|_| RpcError::Lock)?;
+    let chain = ChainView::new(&blockchain, &headers, context.get_settings().get_network());
+    let block = blockchain
+        .get_block(&block_hash)
+        .ok_or(RpcError::BlockNotFound)?;
+
+    format_response(format, block.as_bytes(), || {
+        block_to_json(&block, chain.get_position(&block_hash), true)
+    })
+}
+
+/// `/rest/headers/<count>/<hash>`: up to `count` headers of the chain, starting at the given block.
+fn get_headers(count: &str, part: &str, context: &RpcContext) -> Result<HttpResponse, RpcError> {
+    let (hash, format) = split_format(part)?;
+    let block_hash = hash_from_hex(hash, "hash")?;
+    let count = count
+        .parse::<usize>()
+        .ok()
+        .filter(|count| (1..=MAX_REST_HEADERS).contains(count))
+        .ok_or_else(|| {
+            RpcError::InvalidParams(format!(
+                "Header count is invalid or out of acceptable range (1-{}): {}",
+                MAX_REST_HEADERS, count
+            ))
+        })?;
+
+    let blockchain = context
+        .get_blockchain()
+        .lock()
+        .map_err(|_| RpcError::Lock)?;
+    let headers = context.get_headers().lock().map_err(|_| RpcError::Lock)?;
+    let chain = ChainView::new(&blockchain, &headers, context.get_settings().get_network());
+    let chain_headers = chain.get_headers_from(&block_hash, count);
+    if chain_headers.is_empty() {
+        return Err(RpcError::BlockNotFound);
+    }
+
+    let bytes = chain_headers
+        .iter()
+        .flat_map(|header| header.as_bytes())
+        .collect();
+    format_response(format, bytes, || {
+        Ok(JsonValue::Array(
+            chain_headers
+                .iter()
+                .map(|header| header_to_json(header, chain.get_position(&header.get_header())))
+                .collect::<Result<Vec<JsonValue>, RpcError>>()?,
+        ))
+    })
+}
+
+/// `/rest/tx/<txid>`: a transaction of the mempool or of a downloaded block. In JSON, confirmed transactions also
+/// show the hash of their block.
+fn get_tx(part: &str, context: &RpcContext) -> Result<HttpResponse, RpcError> {
+    let (hash, format) = split_format(part)?;
+    let tx_id = hash_from_hex(hash, "hash")?;
+
+    let mempool = context.get_mempool().lock().map_err(|_| RpcError::Lock)?;
+    let unconfirmed = mempool.get_txs().get(&tx_id).cloned();
+    drop(mempool);
+
+    let (transaction, block_hash): (Transaction, Option<Vec<u8>>) = match unconfirmed {
+        Some(transaction) => (transaction, None),
+        None => {
+            let blockchain = context
+                .get_blockchain()
+                .lock()
+                .map_err(|_| RpcError::Lock)?;
+            let headers = context.get_headers().lock().map_err(|_| RpcError::Lock)?;
+            let chain = ChainView::new(&blockchain, &headers, context.get_settings().get_network());
+            let (transaction, block_hash) = chain
+                .find_transaction(&tx_id, None)?
+                .ok_or(RpcError::TxNotFound)?;
+            (transaction, Some(block_hash))
+        }
+    };
+
+    format_response(format, transaction.as_bytes(), || {
+        let mut json = transaction_to_json(&transaction)?;
+        if let Some(block_hash) = &block_hash {
+            json.push("blockhash", JsonValue::from(hash_to_hex(block_hash)?));
+        }
+        Ok(json)
+    })
+}
+
+/// `/rest/mempool/contents` and `/rest/mempool/info`: the transactions of the mempool and its size, only in JSON.
+fn get_mempool(part: &str, context: &RpcContext) -> Result<HttpResponse, RpcError> {
+    let json = match split_format(part)? {
+        (MEMPOOL_CONTENTS, RestFormat::Json) => get_raw_mempool(context.get_mempool(), true)?,
+        (MEMPOOL_INFO, RestFormat::Json) => get_mempool_info(context.get_mempool())?,
+        (MEMPOOL_CONTENTS | MEMPOOL_INFO, _) => {
+            return Err(RpcError::InvalidParams(
+                "output format not found (available: json)".to_string(),
+            ))
+        }
+        _ => return Err(RpcError::InvalidParams("unknown REST resource".to_string())),
+    };
+    Ok(HttpResponse::new(
+        200,
+        JSON_CONTENT_TYPE,
+        json.to_string().into_bytes(),
+    ))
+}
+
+#[cfg(test)]
+mod rest_test {
+    use super::*;
+
+    #[test]
+    fn test_split_format() -> Result<(), RpcError> {
+        assert_eq!(split_format("abc.bin")?, ("abc", RestFormat::Bin));
+        assert_eq!(split_format("abc.hex")?, ("abc", RestFormat::Hex));
+        assert_eq!(
+            split_format("contents.json")?,
+            ("contents", RestFormat::Json)
+        );
+        assert_eq!(split_format("abc")?, ("abc", RestFormat::Json));
+        assert!(split_format("abc.xml").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_format_response() -> Result<(), RpcError> {
+        let json = || Ok(JsonValue::Int(1));
+
+        assert_eq!(
+            format_response(RestFormat::Bin, vec![0xab, 1], json)?.get_body(),
+            [0xab, 1]
+        );
+        assert_eq!(
+            format_response(RestFormat::Hex, vec![0xab, 1], json)?.get_body(),
+            b"ab01\n"
+        );
+        assert_eq!(
+            format_response(RestFormat::Json, vec![0xab, 1], json)?.get_body(),
+            b"1"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,57 @@
+//methods
+pub const GET_BLOCKCHAIN_INFO: &str = "getblockchaininfo";
+pub const GET_BEST_BLOCK_HASH: &str = "getbestblockhash";
+pub const GET_BLOCK_COUNT: &str = "getblockcount";
+pub const GET_BLOCK: &str = "getblock";
+pub const GET_BLOCK_HEADER: &str = "getblockheader";
+pub const GET_RAW_TRANSACTION: &str = "getrawtransaction";
+pub const SEND_RAW_TRANSACTION: &str = "sendrawtransaction";
+pub const GET_MEMPOOL_INFO: &str = "getmempoolinfo";
+pub const GET_RAW_MEMPOOL: &str = "getrawmempool";
+pub const GET_TX_OUT: &str = "gettxout";
+pub const GET_PEER_INFO: &str = "getpeerinfo";
+
+//error codes, the same ones bitcoind uses
+pub const RPC_PARSE_ERROR: i64 = -32700;
+pub const RPC_INVALID_REQUEST: i64 = -32600;
+pub const RPC_METHOD_NOT_FOUND: i64 = -32601;
+pub const RPC_MISC_ERROR: i64 = -1;
+pub const RPC_INVALID_ADDRESS_OR_KEY: i64 = -5;
+pub const RPC_INVALID_PARAMETER: i64 = -8;
+pub const RPC_DESERIALIZATION_ERROR: i64 = -22;
+
+//http
+pub const HTTP_VERSION: &str = "HTTP/1.1";
+pub const POST_METHOD: &str = "POST";
+pub const CONTENT_LENGTH_HEADER: &str = "content-length";
+pub const AUTHORIZATION_HEADER: &str = "authorization";
+pub const BASIC_AUTH: &str = "Basic ";
+pub const JSON_CONTENT_TYPE: &str = "application/json";
+pub const MAX_BODY_LEN: usize = 4_000_000;
+pub const MAX_HEADERS: usize = 100;
+pub const MAX_HEADER_LINE_LEN: usize = 8192;
+pub const MAX_JSON_DEPTH: usize = 64;
+pub const RPC_TIMEOUT_SECONDS: u64 = 30;
+pub const MAX_RPC_CONNECTIONS: usize = 16;
+
+pub const MAINNET_CHAIN: &str = "main";
+pub const TESTNET_CHAIN: &str = "test";
+pub const SATOSHIS_PER_BTC: f64 = 100_000_000.0;
+pub const NULL_OUTPOINT_INDEX: u32 = 0xffffffff;
+pub const HASH_HEX_LEN: usize = 64;
+
+//rest
+pub const GET_METHOD: &str = "GET";
+pub const REST_PREFIX: &str = "/rest/";
+pub const BLOCK_RESOURCE: &str = "block";
+pub const HEADERS_RESOURCE: &str = "headers";
+pub const TX_RESOURCE: &str = "tx";
+pub const MEMPOOL_RESOURCE: &str = "mempool";
+pub const MEMPOOL_CONTENTS: &str = "contents";
+pub const MEMPOOL_INFO: &str = "info";
+pub const BIN_FORMAT: &str = "bin";
+pub const HEX_FORMAT: &str = "hex";
+pub const JSON_FORMAT: &str = "json";
+pub const MAX_REST_HEADERS: usize = 2000;
+pub const BINARY_CONTENT_TYPE: &str = "application/octet-stream";
+pub const TEXT_CONTENT_TYPE: &str = "text/plain";
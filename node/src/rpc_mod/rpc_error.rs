@@ -0,0 +1,62 @@
+use crate::messages::message_error::MessageError;
+
+use super::rpc_constants::*;
+
+/// The errors a JSON-RPC request can get, each with the code bitcoind answers for it.
+#[derive(Debug, PartialEq, Eq)]
+pub enum RpcError {
+    Parse,
+    InvalidRequest,
+    MethodNotFound(String),
+    InvalidParams(String),
+    BlockNotFound,
+    TxNotFound,
+    Deserialization,
+    Broadcast,
+    Lock,
+    Encode,
+}
+
+impl RpcError {
+    /// Returns the JSON-RPC error code of the error.
+    pub fn get_code(&self) -> i64 {
+        match self {
+            RpcError::Parse => RPC_PARSE_ERROR,
+            RpcError::InvalidRequest => RPC_INVALID_REQUEST,
+            RpcError::MethodNotFound(_) => RPC_METHOD_NOT_FOUND,
+            RpcError::InvalidParams(_) => RPC_INVALID_PARAMETER,
+            RpcError::BlockNotFound | RpcError::TxNotFound => RPC_INVALID_ADDRESS_OR_KEY,
+            RpcError::Deserialization => RPC_DESERIALIZATION_ERROR,
+            RpcError::Broadcast | RpcError::Lock | RpcError::Encode => RPC_MISC_ERROR,
+        }
+    }
+}
+
+impl From<std::fmt::Error> for RpcError {
+    fn from(_: std::fmt::Error) -> RpcError {
+        RpcError::Encode
+    }
+}
+
+impl From<MessageError> for RpcError {
+    fn from(_: MessageError) -> RpcError {
+        RpcError::Deserialization
+    }
+}
+
+impl std::fmt::Display for RpcError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            RpcError::Parse => write!(f, "Parse error"),
+            RpcError::InvalidRequest => write!(f, "Invalid Request object"),
+            RpcError::MethodNotFound(method) => write!(f, "Method not found: {}", method),
+            RpcError::InvalidParams(message) => write!(f, "{}", message),
+            RpcError::BlockNotFound => write!(f, "Block not found"),
+            RpcError::TxNotFound => write!(f, "No such mempool or blockchain transaction"),
+            RpcError::Deserialization => write!(f, "TX decode failed"),
+            RpcError::Broadcast => write!(f, "Error broadcasting the transaction"),
+            RpcError::Lock => write!(f, "Internal error"),
+            RpcError::Encode => write!(f, "Encoding error"),
+        }
+    }
+}
@@ -0,0 +1,527 @@
+use std::{
+    collections::HashMap,
+    net::{SocketAddr, TcpStream},
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    block_mod::{
+        block_header::BlockHeader, blockchain::BlockChain, mempool::Mempool,
+        transaction::Transaction, utxo::UnspentTx,
+    },
+    messages::{
+        read_from_bytes::{decode_hex, encode_hex},
+        tx::Tx,
+    },
+    network::{bitcoin_network::BitcoinNetwork, broadcasting::broadcast_new_txn},
+    settings_mod::settings::Settings,
+};
+
+use super::{
+    chain_view::ChainView, json::JsonValue, json_serializer::*, rpc_constants::*,
+    rpc_error::RpcError,
+};
+
+/// The state of the node the JSON-RPC methods read and update.
+pub struct RpcContext {
+    settings: Arc<Settings>,
+    blockchain: Arc<Mutex<BlockChain>>,
+    utxo: Arc<Mutex<UnspentTx>>,
+    mempool: Arc<Mutex<Mempool>>,
+    headers: Arc<Mutex<HashMap<Vec<u8>, BlockHeader>>>,
+    streams: Vec<Arc<Mutex<TcpStream>>>,
+    peers: Vec<SocketAddr>,
+}
+
+impl RpcContext {
+    /// Creates the context of the JSON-RPC server.
+    ///
+    /// # Arguments
+    ///
+    /// * `settings` - The settings of the node.
+    /// * `blockchain` - The downloaded blocks.
+    /// * `utxo` - The unspent outputs of the downloaded blocks.
+    /// * `mempool` - The unconfirmed transactions.
+    /// * `headers` - The headers of the chain, by hash.
+    /// * `streams` - The connections to the peers, used to broadcast transactions.
+    /// * `peers` - The addresses of the peers, taken when they connected since the streams are locked while they are read.
+    pub fn new(
+        settings: Arc<Settings>,
+        blockchain: Arc<Mutex<BlockChain>>,
+        utxo: Arc<Mutex<UnspentTx>>,
+        mempool: Arc<Mutex<Mempool>>,
+        headers: Arc<Mutex<HashMap<Vec<u8>, BlockHeader>>>,
+        streams: Vec<Arc<Mutex<TcpStream>>>,
+        peers: Vec<SocketAddr>,
+    ) -> RpcContext {
+        RpcContext {
+            settings,
+            blockchain,
+            utxo,
+            mempool,
+            headers,
+            streams,
+            peers,
+        }
+    }
+
+    pub fn get_settings(&self) -> &Settings {
+        &self.settings
+    }
+
+    pub fn get_blockchain(&self) -> &Mutex<BlockChain> {
+        &self.blockchain
+    }
+
+    pub fn get_mempool(&self) -> &Mutex<Mempool> {
+        &self.mempool
+    }
+
+    pub fn get_headers(&self) -> &Mutex<HashMap<Vec<u8>, BlockHeader>> {
+        &self.headers
+    }
+
+    /// Calls a JSON-RPC method.
+    ///
+    /// # Arguments
+    ///
+    /// * `method` - The name of the method, as in bitcoind.
+    /// * `params` - The positional parameters of the method. Missing optional parameters, or `null` ones, take the
+    ///   default value bitcoind gives them.
+    ///
+    /// # Returns
+    ///
+    /// The result of the method, or the `RpcError` to answer with.
+    pub fn call(&self, method: &str, params: &[JsonValue]) -> Result<JsonValue, RpcError> {
+        match method {
+            GET_BLOCKCHAIN_INFO => self.get_blockchain_info(),
+            GET_BEST_BLOCK_HASH => {
+                let blockchain = self.blockchain.lock().map_err(|_| RpcError::Lock)?;
+                Ok(JsonValue::from(hash_to_hex(
+                    &blockchain.get_last_block_header(),
+                )?))
+            }
+            GET_BLOCK_COUNT => {
+                let blockchain = self.blockchain.lock().map_err(|_| RpcError::Lock)?;
+                let headers = self.headers.lock().map_err(|_| RpcError::Lock)?;
+                let chain = ChainView::new(&blockchain, &headers, self.settings.get_network());
+                Ok(JsonValue::Int(chain.get_tip_height() as i64))
+            }
+            GET_BLOCK => self.get_block(params),
+            GET_BLOCK_HEADER => self.get_block_header(params),
+            GET_RAW_TRANSACTION => self.get_raw_transaction(params),
+            SEND_RAW_TRANSACTION => self.send_raw_transaction(params),
+            GET_MEMPOOL_INFO => get_mempool_info(&self.mempool),
+            GET_RAW_MEMPOOL => get_raw_mempool(&self.mempool, bool_param(params, 0, false)?),
+            GET_TX_OUT => self.get_tx_out(params),
+            GET_PEER_INFO => Ok(get_peer_info(&self.peers)),
+            method => Err(RpcError::MethodNotFound(method.to_string())),
+        }
+    }
+
+    fn get_blockchain_info(&self) -> Result<JsonValue, RpcError> {
+        let blockchain = self.blockchain.lock().map_err(|_| RpcError::Lock)?;
+        let headers = self.headers.lock().map_err(|_| RpcError::Lock)?;
+        let chain = ChainView::new(&blockchain, &headers, self.settings.get_network());
+        let blocks = chain.get_tip_height();
+        let chain_name = match self.settings.get_network() {
+            BitcoinNetwork::Mainnet => MAINNET_CHAIN,
+            BitcoinNetwork::Testnet => TESTNET_CHAIN,
+        };
+
+        Ok(JsonValue::object(vec![
+            ("chain", JsonValue::from(chain_name)),
+            ("blocks", JsonValue::Int(blocks as i64)),
+            ("headers", JsonValue::Int(headers.len().max(blocks) as i64)),
+            (
+                "bestblockhash",
+                JsonValue::from(hash_to_hex(&blockchain.get_last_block_header())?),
+            ),
+            ("pruned", JsonValue::Bool(false)),
+            ("warnings", JsonValue::from("")),
+        ]))
+    }
+
+    /// `getblock "blockhash" ( verbosity )`: the block in hex with verbosity 0, as JSON with the ids of its transactions
+    /// with verbosity 1, the default, or as JSON with its decoded transactions with verbosity 2.
+    fn get_block(&self, params: &[JsonValue]) -> Result<JsonValue, RpcError> {
+        let block_hash = hash_param(params, 0, "blockhash")?;
+        let verbosity = match param(params, 1) {
+            None => 1,
+            Some(JsonValue::Bool(verbose)) => *verbose as i64,
+            Some(JsonValue::Int(verbosity @ 0..=2)) => *verbosity,
+            Some(_) => {
+                return Err(RpcError::InvalidParams(
+                    "verbosity must be 0, 1 or 2".to_string(),
+                ))
+            }
+        };
+
+        let blockchain = self.blockchain.lock().map_err(|_| RpcError::Lock)?;
+        let headers = self.headers.lock().map_err(|_| RpcError::Lock)?;
+        let chain = ChainView::new(&blockchain, &headers, self.settings.get_network());
+        let block = blockchain
+            .get_block(&block_hash)
+            .ok_or(RpcError::BlockNotFound)?;
+
+        match verbosity {
+            0 => Ok(JsonValue::from(encode_hex(&block.as_bytes())?)),
+            verbosity => block_to_json(&block, chain.get_position(&block_hash), verbosity == 2),
+        }
+    }
+
+    /// `getblockheader "blockhash" ( verbose )`: the header as JSON, or in hex if `verbose` is false. Headers are served
+    /// even for blocks the node didn't download.
+    fn get_block_header(&self, params: &[JsonValue]) -> Result<JsonValue, RpcError> {
+        let block_hash = hash_param(params, 0, "blockhash")?;
+        let verbose = bool_param(params, 1, true)?;
+
+        let blockchain = self.blockchain.lock().map_err(|_| RpcError::Lock)?;
+        let headers = self.headers.lock().map_err(|_| RpcError::Lock)?;
+        let chain = ChainView::new(&blockchain, &headers, self.settings.get_network());
+        let header = chain
+            .get_header(&block_hash)
+            .ok_or(RpcError::BlockNotFound)?;
+
+        match verbose {
+            true => header_to_json(&header, chain.get_position(&block_hash)),
+            false => Ok(JsonValue::from(encode_hex(&header.as_bytes())?)),
+        }
+    }
+
+    /// `getrawtransaction "txid" ( verbose "blockhash" )`: a transaction of the mempool or of a downloaded block, in hex
+    /// or, if `verbose` is true, as JSON with the block that confirms it.
+    fn get_raw_transaction(&self, params: &[JsonValue]) -> Result<JsonValue, RpcError> {
+        let tx_id = hash_param(params, 0, "txid")?;
+        let verbose = bool_param(params, 1, false)?;
+        let block_hash = match param(params, 2) {
+            Some(_) => Some(hash_param(params, 2, "blockhash")?),
+            None => None,
+        };
+
+        if block_hash.is_none() {
+            let mempool = self.mempool.lock().map_err(|_| RpcError::Lock)?;
+            if let Some(transaction) = mempool.get_txs().get(&tx_id) {
+                return match verbose {
+                    true => transaction_to_json(transaction),
+                    false => Ok(JsonValue::from(encode_hex(&transaction.as_bytes())?)),
+                };
+            }
+        }
+
+        let blockchain = self.blockchain.lock().map_err(|_| RpcError::Lock)?;
+        let headers = self.headers.lock().map_err(|_| RpcError::Lock)?;
+        let chain = ChainView::new(&blockchain, &headers, self.settings.get_network());
+        if let Some(block_hash) = &block_hash {
+            if !blockchain.contains_block(block_hash) {
+                return Err(RpcError::BlockNotFound);
+            }
+        }
+        let (transaction, block_hash) = chain
+            .find_transaction(&tx_id, block_hash.as_deref())?
+            .ok_or(RpcError::TxNotFound)?;
+
+        if !verbose {
+            return Ok(JsonValue::from(encode_hex(&transaction.as_bytes())?));
+        }
+        let mut json = transaction_to_json(&transaction)?;
+        json.push("blockhash", JsonValue::from(hash_to_hex(&block_hash)?));
+        json.push(
+            "confirmations",
+            JsonValue::Int(chain.get_position(&block_hash).confirmations),
+        );
+        if let Some(header) = chain.get_header(&block_hash) {
+            json.push("time", JsonValue::Int(header.get_time() as i64));
+            json.push("blocktime", JsonValue::Int(header.get_time() as i64));
+        }
+        Ok(json)
+    }
+
+    /// `sendrawtransaction "hexstring"`: adds a transaction to the mempool and broadcasts it to the peers.
+    fn send_raw_transaction(&self, params: &[JsonValue]) -> Result<JsonValue, RpcError> {
+        let transaction = decode_transaction(
+            param(params, 0)
+                .and_then(JsonValue::as_str)
+                .ok_or_else(|| RpcError::InvalidParams("hexstring must be a string".to_string()))?,
+        )?;
+        let tx_id = transaction.get_id();
+
+        let mut mempool = self.mempool.lock().map_err(|_| RpcError::Lock)?;
+        mempool.add(transaction.clone());
+        drop(mempool);
+
+        broadcast_new_txn(
+            Tx::new(self.settings.get_start_string(), transaction),
+            &self.streams,
+        )
+        .map_err(|_| RpcError::Broadcast)?;
+        Ok(JsonValue::from(hash_to_hex(&tx_id)?))
+    }
+
+    /// `gettxout "txid" n ( include_mempool )`: an unspent output, or `null` if it is spent or unknown. With
+    /// `include_mempool`, the default, outputs spent by the mempool are left out and the ones it creates are included.
+    fn get_tx_out(&self, params: &[JsonValue]) -> Result<JsonValue, RpcError> {
+        let tx_id = hash_param(params, 0, "txid")?;
+        let index = param(params, 1)
+            .and_then(JsonValue::as_i64)
+            .and_then(|index| u32::try_from(index).ok())
+            .ok_or_else(|| RpcError::InvalidParams("n must be an output index".to_string()))?;
+        let include_mempool = bool_param(params, 2, true)?;
+
+        let utxo = self.utxo.lock().map_err(|_| RpcError::Lock)?;
+        let confirmed = utxo
+            .get_utxo()
+            .get(&tx_id)
+            .and_then(|outputs| outputs.get(&index))
+            .cloned();
+        drop(utxo);
+
+        let mempool = self.mempool.lock().map_err(|_| RpcError::Lock)?;
+        if include_mempool && is_spent_in_mempool(&mempool, &tx_id, index) {
+            return Ok(JsonValue::Null);
+        }
+        let unconfirmed = match include_mempool {
+            true => mempool
+                .get_txs()
+                .get(&tx_id)
+                .and_then(|transaction| transaction.get_tx_out_list().get(index as usize))
+                .cloned(),
+            false => None,
+        };
+        drop(mempool);
+
+        let blockchain = self.blockchain.lock().map_err(|_| RpcError::Lock)?;
+        let headers = self.headers.lock().map_err(|_| RpcError::Lock)?;
+        let chain = ChainView::new(&blockchain, &headers, self.settings.get_network());
+        let (tx_out, confirmations) = match (confirmed, unconfirmed) {
+            (Some(tx_out), _) => {
+                let confirmations = match chain.find_transaction(&tx_id, None)? {
+                    Some((_, block_hash)) => chain.get_position(&block_hash).confirmations,
+                    None => 1,
+                };
+                (tx_out, confirmations)
+            }
+            (None, Some(tx_out)) => (tx_out, 0),
+            (None, None) => return Ok(JsonValue::Null),
+        };
+
+        Ok(JsonValue::object(vec![
+            (
+                "bestblock",
+                JsonValue::from(hash_to_hex(&blockchain.get_last_block_header())?),
+            ),
+            ("confirmations", JsonValue::Int(confirmations)),
+            ("value", btc_value(tx_out.get_value())),
+            (
+                "scriptPubKey",
+                script_pub_key_to_json(&tx_out.get_pk_script())?,
+            ),
+            ("coinbase", JsonValue::Bool(false)),
+        ]))
+    }
+}
+
+/// Returns a parameter, or `None` if it is missing or `null`.
+fn param(params: &[JsonValue], index: usize) -> Option<&JsonValue> {
+    params.get(index).filter(|value| **value != JsonValue::Null)
+}
+
+/// Returns a boolean parameter, or its default value if it is missing.
+fn bool_param(params: &[JsonValue], index: usize, default: bool) -> Result<bool, RpcError> {
+    match param(params, index) {
+        Some(value) => value.as_bool().ok_or_else(|| {
+            RpcError::InvalidParams(format!("parameter {} must be a boolean", index))
+        }),
+        None => Ok(default),
+    }
+}
+
+/// Returns a block hash or a transaction id parameter, given in hex as bitcoind shows them, in the byte order the node
+/// stores them.
+fn hash_param(params: &[JsonValue], index: usize, name: &str) -> Result<Vec<u8>, RpcError> {
+    hash_from_hex(
+        param(params, index)
+            .and_then(JsonValue::as_str)
+            .unwrap_or_default(),
+        name,
+    )
+}
+
+/// Decodes a transaction in hex, which must not have any bytes after it.
+fn decode_transaction(hex: &str) -> Result<Transaction, RpcError> {
+    let bytes = decode_hex(hex).map_err(|_| RpcError::Deserialization)?;
+    let mut stream = &bytes[..];
+    let transaction = Transaction::from_bytes(&mut stream)?;
+    match stream.is_empty() {
+        true => Ok(transaction),
+        false => Err(RpcError::Deserialization),
+    }
+}
+
+/// Returns whether a transaction of the mempool spends the given output.
+fn is_spent_in_mempool(mempool: &Mempool, tx_id: &[u8], index: u32) -> bool {
+    mempool.get_txs().values().any(|transaction| {
+        transaction.get_tx_in_list().iter().any(|tx_in| {
+            let outpoint = tx_in.get_prev_output();
+            outpoint.get_tx_id() == tx_id && outpoint.get_index() == index
+        })
+    })
+}
+
+/// `getmempoolinfo`: the number of transactions of the mempool and their total virtual size.
+pub fn get_mempool_info(mempool: &Mutex<Mempool>) -> Result<JsonValue, RpcError> {
+    let mempool = mempool.lock().map_err(|_| RpcError::Lock)?;
+    let bytes: usize = mempool.get_txs().values().map(Transaction::get_vsize).sum();
+
+    Ok(JsonValue::object(vec![
+        ("loaded", JsonValue::Bool(true)),
+        ("size", JsonValue::Int(mempool.cant_txs() as i64)),
+        ("bytes", JsonValue::Int(bytes as i64)),
+    ]))
+}
+
+/// `getrawmempool ( verbose )`: the ids of the transactions of the mempool or, if `verbose` is true, an object with
+/// the size of each one by id.
+pub fn get_raw_mempool(mempool: &Mutex<Mempool>, verbose: bool) -> Result<JsonValue, RpcError> {
+    let mempool = mempool.lock().map_err(|_| RpcError::Lock)?;
+    let mut tx_ids: Vec<&Vec<u8>> = mempool.get_txs().keys().collect();
+    tx_ids.sort();
+
+    match verbose {
+        true => {
+            let mut entries = vec![];
+            for tx_id in tx_ids {
+                let transaction = &mempool.get_txs()[tx_id];
+                let size = transaction.as_bytes().len();
+                let base_size = transaction.as_bytes_without_witness().len();
+                entries.push((
+                    hash_to_hex(tx_id)?,
+                    JsonValue::object(vec![
+                        ("vsize", JsonValue::Int(transaction.get_vsize() as i64)),
+                        ("weight", JsonValue::Int((base_size * 3 + size) as i64)),
+                    ]),
+                ));
+            }
+            Ok(JsonValue::Object(entries))
+        }
+        false => Ok(JsonValue::Array(
+            tx_ids
+                .into_iter()
+                .map(|tx_id| Ok(JsonValue::from(hash_to_hex(tx_id)?)))
+                .collect::<Result<Vec<JsonValue>, RpcError>>()?,
+        )),
+    }
+}
+
+/// `getpeerinfo`: the address of each peer the node connected to.
+fn get_peer_info(peers: &[SocketAddr]) -> JsonValue {
+    JsonValue::Array(
+        peers
+            .iter()
+            .enumerate()
+            .map(|(id, peer)| {
+                let network = match peer {
+                    SocketAddr::V4(_) => "ipv4",
+                    SocketAddr::V6(_) => "ipv6",
+                };
+                JsonValue::object(vec![
+                    ("id", JsonValue::Int(id as i64)),
+                    ("addr", JsonValue::from(peer.to_string())),
+                    ("network", JsonValue::from(network)),
+                ])
+            })
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod rpc_methods_test {
+    use super::*;
+    use crate::block_mod::{tx_in::TxIn, tx_out::TxOut};
+
+    fn test_transaction(prev_tx: Vec<u8>, value: i64) -> Transaction {
+        Transaction::new(
+            1,
+            vec![TxIn::new(prev_tx, 0, vec![], 0xffffffff)],
+            vec![TxOut::new(value, vec![0x51])],
+            0,
+        )
+    }
+
+    #[test]
+    fn test_hash_param_is_reversed() -> Result<(), RpcError> {
+        let params = [JsonValue::from(format!("{}ff", "00".repeat(31)))];
+        let mut expected = vec![0; 32];
+        expected[0] = 0xff;
+
+        assert_eq!(hash_param(&params, 0, "txid")?, expected);
+        assert!(hash_param(&[JsonValue::from("00ff")], 0, "txid").is_err());
+        assert!(hash_param(&[JsonValue::Int(1)], 0, "txid").is_err());
+        assert!(hash_param(&[], 0, "txid").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_bool_param_defaults() -> Result<(), RpcError> {
+        assert!(bool_param(&[], 0, true)?);
+        assert!(!bool_param(&[JsonValue::Null], 0, false)?);
+        assert!(bool_param(&[JsonValue::Int(1)], 0, false)?);
+        assert!(bool_param(&[JsonValue::from("yes")], 0, false).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_transaction() -> Result<(), RpcError> {
+        let transaction = test_transaction(vec![1; 32], 5000);
+        let hex = encode_hex(&transaction.as_bytes())?;
+
+        assert_eq!(decode_transaction(&hex)?.get_id(), transaction.get_id());
+        assert_eq!(
+            decode_transaction(&format!("{}00", hex)).err(),
+            Some(RpcError::Deserialization)
+        );
+        assert_eq!(
+            decode_transaction("zz").err(),
+            Some(RpcError::Deserialization)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_mempool_methods() -> Result<(), RpcError> {
+        let first = test_transaction(vec![1; 32], 5000);
+        let second = test_transaction(first.get_id(), 4000);
+        let mut mempool = Mempool::new();
+        mempool.add(first.clone());
+        mempool.add(second.clone());
+        let vsize = first.get_vsize() + second.get_vsize();
+        let mempool = Mutex::new(mempool);
+
+        let info = get_mempool_info(&mempool)?;
+        let raw_mempool = get_raw_mempool(&mempool, false)?;
+
+        assert_eq!(info.get("size"), Some(&JsonValue::Int(2)));
+        assert_eq!(info.get("bytes"), Some(&JsonValue::Int(vsize as i64)));
+        match raw_mempool {
+            JsonValue::Array(tx_ids) => {
+                assert!(tx_ids.contains(&JsonValue::from(hash_to_hex(&first.get_id())?)));
+                assert!(tx_ids.contains(&JsonValue::from(hash_to_hex(&second.get_id())?)));
+            }
+            _ => panic!("getrawmempool should return an array"),
+        }
+        let locked_mempool = mempool.lock().map_err(|_| RpcError::Lock)?;
+        assert!(is_spent_in_mempool(&locked_mempool, &first.get_id(), 0));
+        assert!(!is_spent_in_mempool(&locked_mempool, &second.get_id(), 0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_peer_info() {
+        let peers = [SocketAddr::from(([127, 0, 0, 1], 18333))];
+
+        assert_eq!(
+            get_peer_info(&peers).to_string(),
+            "[{\"id\":0,\"addr\":\"127.0.0.1:18333\",\"network\":\"ipv4\"}]"
+        );
+    }
+}
@@ -0,0 +1,228 @@
+use std::{
+    io::{BufReader, Write},
+    net::{TcpListener, TcpStream},
+    sync::{Arc, Mutex},
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use super::{
+    http::{HttpRequest, HttpResponse},
+    http_error::HttpError,
+    json::JsonValue,
+    rest::handle_rest,
+    rpc_constants::*,
+    rpc_error::RpcError,
+    rpc_methods::RpcContext,
+};
+
+/// A place among the connections served at the same time, given back when it is dropped.
+struct RpcSlot {
+    active_connections: Arc<Mutex<usize>>,
+}
+
+impl RpcSlot {
+    /// Takes a place if fewer than `MAX_RPC_CONNECTIONS` connections are being served.
+    fn reserve(active_connections: &Arc<Mutex<usize>>) -> Option<RpcSlot> {
+        let mut locked_active_connections = active_connections.lock().ok()?;
+        if *locked_active_connections >= MAX_RPC_CONNECTIONS {
+            return None;
+        }
+        *locked_active_connections += 1;
+
+        Some(RpcSlot {
+            active_connections: active_connections.clone(),
+        })
+    }
+}
+
+impl Drop for RpcSlot {
+    fn drop(&mut self) {
+        if let Ok(mut locked_active_connections) = self.active_connections.lock() {
+            *locked_active_connections -= 1;
+        }
+    }
+}
+
+/// Starts the JSON-RPC server, if the settings give it an address.
+///
+/// # Arguments
+///
+/// * `context` - The state of the node the methods work on.
+///
+/// # Returns
+///
+/// The handle of the thread that serves the requests, or `None` if `rpc_addr` isn't set or the address can't be bound.
+///
+/// # Description
+///
+/// Requests are HTTP POSTs with a JSON-RPC 1.0 or 2.0 body, a single call or a batch of them, authenticated with the
+/// `rpc_user` and `rpc_password` of the settings as bitcoind does. If `rest` is set, GETs of the REST interface are
+/// served too, without authentication. Each connection is served in its own thread and closed after the response. Up
+/// to `MAX_RPC_CONNECTIONS` are served at the same time, and further ones are answered with status 503, so a slow
+/// client doesn't keep the others waiting.
+pub fn rpc_server(context: RpcContext) -> Option<JoinHandle<()>> {
+    let address = context.get_settings().get_rpc_addr().clone()?;
+    let listener = match TcpListener::bind(&address) {
+        Ok(listener) => listener,
+        Err(err) => {
+            println!(
+                "No se pudo iniciar el servidor JSON-RPC en {}: {:?}",
+                address, err
+            );
+            return None;
+        }
+    };
+
+    let context = Arc::new(context);
+    let active_connections = Arc::new(Mutex::new(0));
+    Some(thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = match stream {
+                Ok(stream) => stream,
+                Err(err) => {
+                    println!("{:?}", err);
+                    continue;
+                }
+            };
+            let slot = match RpcSlot::reserve(&active_connections) {
+                Some(slot) => slot,
+                None => {
+                    let response = HttpResponse::new(503, JSON_CONTENT_TYPE, vec![]);
+                    let _ = stream.write_all(&response.as_bytes());
+                    continue;
+                }
+            };
+
+            let context = context.clone();
+            thread::spawn(move || {
+                if let Err(err) = handle_connection(stream, &context) {
+                    println!("Error en una conexion JSON-RPC: {}", err);
+                }
+                drop(slot);
+            });
+        }
+    }))
+}
+
+/// Reads a request from a connection and writes its response.
+fn handle_connection(mut stream: TcpStream, context: &RpcContext) -> Result<(), HttpError> {
+    stream.set_read_timeout(Some(Duration::from_secs(RPC_TIMEOUT_SECONDS)))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+
+    let response = match HttpRequest::from_stream(&mut reader) {
+        Ok(request) => handle_request(&request, context),
+        Err(HttpError::Io) => return Err(HttpError::Io),
+        Err(HttpError::TooLarge) => HttpResponse::new(413, JSON_CONTENT_TYPE, vec![]),
+        Err(HttpError::Malformed) => HttpResponse::new(400, JSON_CONTENT_TYPE, vec![]),
+    };
+    stream.write_all(&response.as_bytes())?;
+    stream.flush()?;
+    Ok(())
+}
+
+/// Answers an HTTP request.
+///
+/// # Returns
+///
+/// The resource for a GET of the REST interface, if it is enabled. Otherwise 401 if the credentials are wrong, 405 if it isn't a POST, and otherwise the JSON-RPC response with the status
+/// bitcoind gives it: 200 on success, 404 for an unknown method and 500 for any other error. The responses of a batch
+/// always have status 200.
+pub fn handle_request(request: &HttpRequest, context: &RpcContext) -> HttpResponse {
+    let settings = context.get_settings();
+    if settings.get_rest()
+        && request.get_method() == GET_METHOD
+        && request.get_path().starts_with(REST_PREFIX)
+    {
+        return handle_rest(request.get_path(), context);
+    }
+    if !request.is_authorized(settings.get_rpc_user(), settings.get_rpc_password()) {
+        return HttpResponse::unauthorized();
+    }
+    if request.get_method() != POST_METHOD {
+        return HttpResponse::new(405, JSON_CONTENT_TYPE, vec![]);
+    }
+
+    let body = String::from_utf8_lossy(request.get_body());
+    let (status, response) = match JsonValue::parse(&body) {
+        Ok(JsonValue::Array(calls)) => {
+            let responses = calls
+                .iter()
+                .map(|call| handle_call(call, context).1)
+                .collect();
+            (200, JsonValue::Array(responses))
+        }
+        Ok(call) => handle_call(&call, context),
+        Err(err) => (500, error_response(&err, JsonValue::Null)),
+    };
+    HttpResponse::new(status, JSON_CONTENT_TYPE, response.to_string().into_bytes())
+}
+
+/// Runs a single call.
+///
+/// # Returns
+///
+/// The HTTP status and the response, with the `result`, the `error` and the `id` of the call.
+fn handle_call(call: &JsonValue, context: &RpcContext) -> (u16, JsonValue) {
+    let id = call.get("id").cloned().unwrap_or(JsonValue::Null);
+    let method = match call.get("method").and_then(JsonValue::as_str) {
+        Some(method) => method,
+        None => return (400, error_response(&RpcError::InvalidRequest, id)),
+    };
+    let params = match call.get("params") {
+        Some(JsonValue::Array(params)) => params.as_slice(),
+        None | Some(JsonValue::Null) => &[],
+        Some(_) => return (400, error_response(&RpcError::InvalidRequest, id)),
+    };
+
+    match context.call(method, params) {
+        Ok(result) => (
+            200,
+            JsonValue::object(vec![
+                ("result", result),
+                ("error", JsonValue::Null),
+                ("id", id),
+            ]),
+        ),
+        Err(err) => {
+            let status = match err {
+                RpcError::MethodNotFound(_) => 404,
+                _ => 500,
+            };
+            (status, error_response(&err, id))
+        }
+    }
+}
+
+fn error_response(err: &RpcError, id: JsonValue) -> JsonValue {
+    JsonValue::object(vec![
+        ("result", JsonValue::Null),
+        (
+            "error",
+            JsonValue::object(vec![
+                ("code", JsonValue::Int(err.get_code())),
+                ("message", JsonValue::from(err.to_string())),
+            ]),
+        ),
+        ("id", id),
+    ])
+}
+
+#[cfg(test)]
+mod rpc_server_test {
+    use super::*;
+
+    #[test]
+    fn test_rpc_slots_are_limited() {
+        let active_connections = Arc::new(Mutex::new(0));
+
+        let slots: Vec<Option<RpcSlot>> = (0..MAX_RPC_CONNECTIONS)
+            .map(|_| RpcSlot::reserve(&active_connections))
+            .collect();
+        assert!(slots.iter().all(Option::is_some));
+        assert!(RpcSlot::reserve(&active_connections).is_none());
+
+        drop(slots);
+        assert!(RpcSlot::reserve(&active_connections).is_some());
+    }
+}
@@ -1,6 +1,12 @@
+use crate::block_mod::prune_target::PruneTarget;
 use crate::messages::read_from_bytes::decode_hex;
+use crate::network::bitcoin_network::BitcoinNetwork;
+use crate::network::network_constants::{
+    DATE_FORMAT, NODE_COMPACT_FILTERS, NODE_NETWORK, NODE_NETWORK_LIMITED,
+};
 use crate::settings_mod::settings_constants::*;
 use crate::settings_mod::settings_error::SettingError;
+use chrono::DateTime;
 use std::collections::HashMap;
 use std::fs;
 use std::net::Ipv6Addr;
@@ -24,6 +30,17 @@ pub struct Settings {
     headers_path: String,
     server_addr: String,
     blocks_path: String,
+    network: BitcoinNetwork,
+    rpc_addr: Option<String>,
+    rpc_user: String,
+    rpc_password: String,
+    rest: bool,
+    max_wallets: usize,
+    wallet_timeout: u64,
+    address_index: bool,
+    tx_index_path: Option<String>,
+    block_filters_path: Option<String>,
+    prune_target: Option<PruneTarget>,
 }
 
 impl Settings {
@@ -51,12 +68,29 @@ impl Settings {
     /// - `SettingError::FieldNotFound`: If a required field is missing in the settings file.
     /// - `SettingError::ParseError`: If there was an error in parsing a field value from the settings file.
     /// - `SettingError::DecodeError`: If there was an error in decoding a hex string from the settings file.
+    /// - `SettingError::FiltersNeedGenesis`: If `block_filters_path` is present but `date_limit` isn't before the genesis
+    ///   block.
+    ///
+    /// # Optional fields
+    ///
+    /// - `network`: The network, testnet by default.
+    /// - `rpc_addr`: The address of the JSON-RPC server, which is only started when it is present. It requires the
+    ///   `rpc_user` and `rpc_password` the clients authenticate with, or a `SettingError::FieldNotFound` is returned.
+    /// - `rest`: Whether the REST interface is also served on `rpc_addr`, `false` by default.
+    /// - `max_wallets`: The wallets connected at the same time, 8 by default.
+    /// - `wallet_timeout`: The seconds a wallet connection can be idle before it is closed, 300 by default or none if 0.
+    /// - `address_index`: Whether the transactions of each script are indexed to answer the wallets, `false` by default.
+    /// - `tx_index_path`: The file the transactions are indexed by their id in, not indexed if it is missing.
+    /// - `block_filters_path`: The file the headers of the compact filters are kept in, which are only served when it is
+    ///   present. The filters need the scripts of every spent output, so the whole chain must be downloaded.
+    /// - `prune`: How many of the most recent blocks are kept, a number of blocks or of megabytes followed by `MB`, never
+    ///   fewer than 288. Every block is kept if it is missing.
     pub fn from_file(path: &str) -> Result<Settings, SettingError> {
         let mut parser_config: HashMap<String, String> = HashMap::new();
         let file = fs::read_to_string(path)?;
 
         for line in file.lines() {
-            let token: Vec<&str> = line.split(EQUAL).collect();
+            let token: Vec<&str> = line.splitn(2, EQUAL).collect();
 
             if matches!(
                 token[0],
@@ -75,6 +109,17 @@ impl Settings {
                     | HEADERS_PATH
                     | SERVER_ADDR
                     | BLOCKS_PATH
+                    | NETWORK
+                    | RPC_ADDR
+                    | RPC_USER
+                    | RPC_PASSWORD
+                    | REST
+                    | MAX_WALLETS
+                    | WALLET_TIMEOUT
+                    | ADDRESS_INDEX
+                    | TX_INDEX_PATH
+                    | BLOCK_FILTERS_PATH
+                    | PRUNE
             ) {
                 parser_config.insert(token[0].to_string(), token[1].to_string());
             } else {
@@ -82,7 +127,7 @@ impl Settings {
             }
         }
 
-        Ok(Settings {
+        let settings = Settings {
             dns_seed: parser_config.get(DNS_SEED).cloned(),
             ips_to_connect: parser_config.get(IPS_TO_CONNECT).map(|ip_str| {
                 ip_str
@@ -144,7 +189,55 @@ impl Settings {
                 .get(BLOCKS_PATH)
                 .ok_or(SettingError::FieldNotFound)?
                 .to_string(),
-        })
+            network: match parser_config.get(NETWORK) {
+                Some(network) => {
+                    BitcoinNetwork::from_str(network).map_err(|_| SettingError::ParseError)?
+                }
+                None => BitcoinNetwork::default(),
+            },
+            rpc_addr: parser_config.get(RPC_ADDR).cloned(),
+            rpc_user: parser_config.get(RPC_USER).cloned().unwrap_or_default(),
+            rpc_password: parser_config.get(RPC_PASSWORD).cloned().unwrap_or_default(),
+            rest: match parser_config.get(REST) {
+                Some(rest) => rest.parse()?,
+                None => false,
+            },
+            max_wallets: match parser_config.get(MAX_WALLETS) {
+                Some(max_wallets) => max_wallets.parse()?,
+                None => DEFAULT_MAX_WALLETS,
+            },
+            wallet_timeout: match parser_config.get(WALLET_TIMEOUT) {
+                Some(wallet_timeout) => wallet_timeout.parse()?,
+                None => DEFAULT_WALLET_TIMEOUT,
+            },
+            address_index: match parser_config.get(ADDRESS_INDEX) {
+                Some(address_index) => address_index.parse()?,
+                None => false,
+            },
+            tx_index_path: parser_config.get(TX_INDEX_PATH).cloned(),
+            block_filters_path: parser_config.get(BLOCK_FILTERS_PATH).cloned(),
+            prune_target: match parser_config.get(PRUNE) {
+                Some(prune_target) => Some(
+                    PruneTarget::from_str(prune_target).map_err(|_| SettingError::ParseError)?,
+                ),
+                None => None,
+            },
+        };
+
+        if settings.rpc_addr.is_some()
+            && (settings.rpc_user.is_empty() || settings.rpc_password.is_empty())
+        {
+            return Err(SettingError::FieldNotFound);
+        }
+        if settings.block_filters_path.is_some() {
+            let date_limit = DateTime::parse_from_str(&settings.date_limit, DATE_FORMAT)
+                .map_err(|_| SettingError::ParseError)?
+                .timestamp();
+            if date_limit >= settings.network.get_genesis_header().get_time() as i64 {
+                return Err(SettingError::FiltersNeedGenesis);
+            }
+        }
+        Ok(settings)
     }
 
     pub fn get_dns_seed(&self) -> &Option<String> {
@@ -156,8 +249,17 @@ impl Settings {
     pub fn get_protocol_version(&self) -> i32 {
         self.protocol_version
     }
+    /// Returns the services the node advertises, including the compact filters when they are enabled. A pruned node
+    /// advertises that it only serves the most recent blocks instead of all of them.
     pub fn get_services(&self) -> u64 {
-        self.services
+        let mut services = self.services;
+        if self.block_filters_path.is_some() {
+            services |= NODE_COMPACT_FILTERS;
+        }
+        if self.prune_target.is_some() {
+            services = services & !NODE_NETWORK | NODE_NETWORK_LIMITED;
+        }
+        services
     }
     pub fn get_port(&self) -> u16 {
         self.port
@@ -192,4 +294,37 @@ impl Settings {
     pub fn get_blocks_path(&self) -> &str {
         &self.blocks_path
     }
+    pub fn get_network(&self) -> BitcoinNetwork {
+        self.network
+    }
+    pub fn get_rpc_addr(&self) -> &Option<String> {
+        &self.rpc_addr
+    }
+    pub fn get_rpc_user(&self) -> &str {
+        &self.rpc_user
+    }
+    pub fn get_rpc_password(&self) -> &str {
+        &self.rpc_password
+    }
+    pub fn get_rest(&self) -> bool {
+        self.rest
+    }
+    pub fn get_max_wallets(&self) -> usize {
+        self.max_wallets
+    }
+    pub fn get_wallet_timeout(&self) -> u64 {
+        self.wallet_timeout
+    }
+    pub fn get_address_index(&self) -> bool {
+        self.address_index
+    }
+    pub fn get_tx_index_path(&self) -> &Option<String> {
+        &self.tx_index_path
+    }
+    pub fn get_block_filters_path(&self) -> &Option<String> {
+        &self.block_filters_path
+    }
+    pub fn get_prune_target(&self) -> Option<PruneTarget> {
+        self.prune_target
+    }
 }
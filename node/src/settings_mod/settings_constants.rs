@@ -13,5 +13,19 @@ pub const WALLET_CONNECTION_ADDR: &str = "wallet_connection_addr";
 pub const HEADERS_PATH: &str = "headers_path";
 pub const SERVER_ADDR: &str = "server_addr";
 pub const BLOCKS_PATH: &str = "blocks_path";
+pub const NETWORK: &str = "network";
+pub const RPC_ADDR: &str = "rpc_addr";
+pub const RPC_USER: &str = "rpc_user";
+pub const RPC_PASSWORD: &str = "rpc_password";
+pub const REST: &str = "rest";
+pub const MAX_WALLETS: &str = "max_wallets";
+pub const WALLET_TIMEOUT: &str = "wallet_timeout";
+pub const ADDRESS_INDEX: &str = "address_index";
+pub const TX_INDEX_PATH: &str = "tx_index_path";
+pub const BLOCK_FILTERS_PATH: &str = "block_filters_path";
+pub const PRUNE: &str = "prune";
+
+pub const DEFAULT_MAX_WALLETS: usize = 8;
+pub const DEFAULT_WALLET_TIMEOUT: u64 = 300;
 
 pub const EQUAL: &str = "=";
@@ -7,6 +7,9 @@ pub enum SettingError {
     TokenNotFound,
     FileNotFound,
     FieldNotFound,
+    ParseError,
+    /// The compact filters are enabled but the chain doesn't start at the genesis block: `date_limit` is after it.
+    FiltersNeedGenesis,
 }
 
 impl From<std::io::Error> for SettingError {
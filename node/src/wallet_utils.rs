@@ -1,13 +1,22 @@
+pub mod block_notification;
 pub mod broadcast_txn;
+pub mod chain_sync;
+pub mod error_response;
 pub mod get_proof;
 pub mod get_transactions;
+pub mod hello;
 pub mod merkle_block;
 pub mod progress;
+pub mod subscribe;
 pub mod transactions;
 pub mod tx_filter;
 pub mod tx_filter_error;
+pub mod tx_notification;
 pub mod update_wallet;
 pub mod update_wallet_error;
 pub mod wallet_connect;
+pub mod wallet_frame;
+pub mod wallet_notifier;
+pub mod wallet_state;
 pub mod wallet_tx;
 pub mod wallet_utils_constants;
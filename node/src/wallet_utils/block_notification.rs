@@ -0,0 +1,89 @@
+use std::io::Read;
+
+use crate::messages::{
+    message_error::MessageError,
+    read_from_bytes::{fill_command, read_u32_from_bytes, read_vec_from_bytes},
+};
+
+use super::wallet_utils_constants::BLOCK_NOTIFICATION_COMMAND;
+
+/// Pushed to every subscribed wallet when the node connects a block.
+///
+/// It carries the confirmations of the transactions of the wallet confirmed in the last `TRACKED_CONFIRMATIONS` blocks,
+/// so the wallet can show them without asking the node.
+#[derive(Debug, PartialEq, Eq)]
+pub struct BlockNotification {
+    command_name: String,
+    block_hash: Vec<u8>,
+    height: u32,
+    confirmations: Vec<(Vec<u8>, u32)>,
+}
+
+impl BlockNotification {
+    /// Creates the notification of a block.
+    ///
+    /// # Arguments
+    ///
+    /// * `block_hash` - The hash of the block.
+    /// * `height` - The height of the block.
+    /// * `confirmations` - The ids of the transactions of the wallet with their confirmations.
+    pub fn new(
+        block_hash: Vec<u8>,
+        height: u32,
+        confirmations: Vec<(Vec<u8>, u32)>,
+    ) -> BlockNotification {
+        BlockNotification {
+            command_name: BLOCK_NOTIFICATION_COMMAND.to_string(),
+            block_hash,
+            height,
+            confirmations,
+        }
+    }
+
+    /// Serializes the notification as `block hash || height || count || (tx id || confirmations)*`.
+    pub fn as_bytes(&self) -> Vec<u8> {
+        let mut buffer = fill_command(self.command_name.as_str()).as_bytes().to_vec();
+        buffer.extend(&self.block_hash);
+        buffer.extend(self.height.to_le_bytes());
+        buffer.extend((self.confirmations.len() as u32).to_le_bytes());
+        for (tx_id, confirmations) in self.confirmations.iter() {
+            buffer.extend(tx_id);
+            buffer.extend(confirmations.to_le_bytes());
+        }
+        buffer
+    }
+
+    /// Reads the notification after its command.
+    pub fn from_bytes(
+        command_name: String,
+        stream: &mut dyn Read,
+    ) -> Result<BlockNotification, MessageError> {
+        let block_hash = read_vec_from_bytes(stream, 32)?;
+        let height = read_u32_from_bytes(stream, true)?;
+        let count = read_u32_from_bytes(stream, true)?;
+        let mut confirmations = vec![];
+        for _ in 0..count {
+            let tx_id = read_vec_from_bytes(stream, 32)?;
+            confirmations.push((tx_id, read_u32_from_bytes(stream, true)?));
+        }
+
+        Ok(BlockNotification {
+            command_name,
+            block_hash,
+            height,
+            confirmations,
+        })
+    }
+
+    pub fn get_block_hash(&self) -> &Vec<u8> {
+        &self.block_hash
+    }
+
+    pub fn get_height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn get_confirmations(&self) -> &Vec<(Vec<u8>, u32)> {
+        &self.confirmations
+    }
+}
@@ -0,0 +1,90 @@
+use crate::messages::{
+    message_error::MessageError,
+    read_from_bytes::{read_u32_from_bytes, read_u8_from_bytes, read_vec_from_bytes},
+};
+use std::io::Read;
+
+/// Represents how the confirmed transactions sent to the wallet relate to the ones it already had.
+///
+/// The wallet remembers the last block it was synced to. If that block is still part of the chain, only the blocks after
+/// it are scanned. If it was left out by a reorganization, the blocks between it and the fork point are disconnected,
+/// so the wallet drops the transactions it found in them, and the scan starts from the fork point. If the node doesn't
+/// know that block, the whole chain is scanned again.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChainSync {
+    last_block_hash: Vec<u8>,
+    disconnected_blocks: Vec<Vec<u8>>,
+    full_rescan: bool,
+}
+
+impl ChainSync {
+    /// Creates a new `ChainSync`.
+    ///
+    /// # Arguments
+    ///
+    /// * `last_block_hash` - The hash of the last block of the chain, the one the wallet is synced to after the update.
+    /// * `disconnected_blocks` - The hashes of the blocks the wallet was synced to that are no longer part of the chain.
+    /// * `full_rescan` - Whether the whole chain was scanned, so the transactions replace all the confirmed ones of the wallet.
+    pub fn new(
+        last_block_hash: Vec<u8>,
+        disconnected_blocks: Vec<Vec<u8>>,
+        full_rescan: bool,
+    ) -> ChainSync {
+        ChainSync {
+            last_block_hash,
+            disconnected_blocks,
+            full_rescan,
+        }
+    }
+
+    /// Serializes the `ChainSync` as `last block hash || disconnected blocks count || disconnected blocks || full rescan`.
+    pub fn as_bytes(&self) -> Vec<u8> {
+        let mut buffer = self.last_block_hash.clone();
+
+        buffer.extend((self.disconnected_blocks.len() as u32).to_le_bytes());
+        for block_hash in self.disconnected_blocks.iter() {
+            buffer.extend(block_hash);
+        }
+        buffer.push(self.full_rescan as u8);
+
+        buffer
+    }
+
+    /// Parses a `ChainSync` serialized with `as_bytes`.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream` - A mutable reference to a type that implements the `Read` trait, representing the byte stream to parse.
+    ///
+    /// # Returns
+    ///
+    /// A result containing the parsed `ChainSync` if successful, or a `MessageError` if an error occurred during parsing.
+    pub fn from_bytes(stream: &mut dyn Read) -> Result<ChainSync, MessageError> {
+        let last_block_hash = read_vec_from_bytes(stream, 32)?;
+
+        let disconnected_count = read_u32_from_bytes(stream, true)?;
+        let mut disconnected_blocks = vec![];
+        for _ in 0..disconnected_count {
+            disconnected_blocks.push(read_vec_from_bytes(stream, 32)?);
+        }
+        let full_rescan = read_u8_from_bytes(stream)? != 0;
+
+        Ok(ChainSync {
+            last_block_hash,
+            disconnected_blocks,
+            full_rescan,
+        })
+    }
+
+    pub fn get_last_block_hash(&self) -> &Vec<u8> {
+        &self.last_block_hash
+    }
+
+    pub fn get_disconnected_blocks(&self) -> &Vec<Vec<u8>> {
+        &self.disconnected_blocks
+    }
+
+    pub fn is_full_rescan(&self) -> bool {
+        self.full_rescan
+    }
+}
@@ -0,0 +1,133 @@
+use std::io::Read;
+
+use crate::messages::{
+    compact_size::CompactSizeUInt,
+    message_error::MessageError,
+    read_from_bytes::{fill_command, read_string_from_bytes, read_u32_from_bytes},
+};
+
+use super::wallet_utils_constants::ERROR_COMMAND;
+
+/// The reasons the node can refuse a request of the framed node–wallet protocol.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorCode {
+    /// The node doesn't support the version of the wallet. The node closes the connection after it.
+    UnsupportedVersion,
+    /// The command is unknown to the node.
+    UnknownCommand,
+    /// The payload doesn't have the layout of the command.
+    Malformed,
+    /// The block or the transaction asked for isn't known to the node.
+    NotFound,
+    /// The node failed to answer the request.
+    Internal,
+}
+
+impl ErrorCode {
+    pub fn as_u32(&self) -> u32 {
+        match self {
+            ErrorCode::UnsupportedVersion => 1,
+            ErrorCode::UnknownCommand => 2,
+            ErrorCode::Malformed => 3,
+            ErrorCode::NotFound => 4,
+            ErrorCode::Internal => 5,
+        }
+    }
+
+    /// Returns the code with the given number, unknown numbers being treated as internal errors.
+    pub fn from_u32(code: u32) -> ErrorCode {
+        match code {
+            1 => ErrorCode::UnsupportedVersion,
+            2 => ErrorCode::UnknownCommand,
+            3 => ErrorCode::Malformed,
+            4 => ErrorCode::NotFound,
+            _ => ErrorCode::Internal,
+        }
+    }
+}
+
+/// The answer of the node to a request it can't serve, with the code of the error and a message for the user.
+#[derive(Debug, PartialEq, Eq)]
+pub struct ErrorResponse {
+    command_name: String,
+    code: ErrorCode,
+    message: String,
+}
+
+impl ErrorResponse {
+    pub fn new(code: ErrorCode, message: &str) -> ErrorResponse {
+        ErrorResponse {
+            command_name: ERROR_COMMAND.to_string(),
+            code,
+            message: message.to_string(),
+        }
+    }
+
+    /// Reads the error after its command.
+    pub fn from_bytes(
+        command_name: String,
+        stream: &mut dyn Read,
+    ) -> Result<ErrorResponse, MessageError> {
+        let code = ErrorCode::from_u32(read_u32_from_bytes(stream, true)?);
+        let message_len = CompactSizeUInt::from_bytes(stream)?;
+        let message = read_string_from_bytes(stream, message_len.value() as usize)?;
+
+        Ok(ErrorResponse {
+            command_name,
+            code,
+            message,
+        })
+    }
+
+    pub fn as_bytes(&self) -> Vec<u8> {
+        let mut buffer = fill_command(self.command_name.as_str()).as_bytes().to_vec();
+        buffer.extend(self.code.as_u32().to_le_bytes());
+        buffer.extend(CompactSizeUInt::from_number(self.message.len() as u64).as_bytes());
+        buffer.extend(self.message.as_bytes());
+        buffer
+    }
+
+    pub fn get_code(&self) -> ErrorCode {
+        self.code
+    }
+
+    pub fn get_message(&self) -> &str {
+        &self.message
+    }
+}
+
+#[cfg(test)]
+mod error_response_test {
+    use super::*;
+    use crate::wallet_utils::wallet_frame::WalletFrame;
+
+    #[test]
+    fn test_error_response_round_trip() -> Result<(), MessageError> {
+        let error = ErrorResponse::new(ErrorCode::UnknownCommand, "unknown command mine");
+        let frame = WalletFrame::from_message(3, &error.as_bytes())?;
+
+        assert_eq!(frame.get_command_name(), ERROR_COMMAND);
+        assert_eq!(
+            frame.read_payload(|stream| ErrorResponse::from_bytes(
+                ERROR_COMMAND.to_string(),
+                stream
+            ))?,
+            error
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_error_codes() {
+        for code in [
+            ErrorCode::UnsupportedVersion,
+            ErrorCode::UnknownCommand,
+            ErrorCode::Malformed,
+            ErrorCode::NotFound,
+            ErrorCode::Internal,
+        ] {
+            assert_eq!(ErrorCode::from_u32(code.as_u32()), code);
+        }
+        assert_eq!(ErrorCode::from_u32(99), ErrorCode::Internal);
+    }
+}
@@ -1,5 +1,4 @@
 use crate::messages::compact_size::CompactSizeUInt;
-use crate::messages::read_from_bytes::read_u32_from_bytes;
 use crate::messages::{
     message_error::MessageError,
     read_from_bytes::{fill_command, read_vec_from_bytes},
@@ -14,7 +13,7 @@ pub struct GetTransactions {
     command_name: String,
     pk_script: Vec<u8>,
     public_key: Vec<u8>,
-    last_update: u32,
+    last_block_hash: Vec<u8>,
 }
 
 impl GetTransactions {
@@ -23,18 +22,22 @@ impl GetTransactions {
     /// # Arguments
     ///
     /// * `pk_script` - The public key script.
-    /// * `public_key` - The public key.
-    /// * `last_update` - The last update timestamp.
+    /// * `public_key` - The public key, empty for addresses locked to a script, such as multisig ones.
+    /// * `last_block_hash` - The hash of the last block the wallet was synced to, all zeros if it never was.
     ///
     /// # Returns
     ///
     /// A new `GetTransactions` instance.
-    pub fn new(pk_script: Vec<u8>, public_key: Vec<u8>, last_update: u32) -> GetTransactions {
+    pub fn new(
+        pk_script: Vec<u8>,
+        public_key: Vec<u8>,
+        last_block_hash: Vec<u8>,
+    ) -> GetTransactions {
         GetTransactions {
             command_name: GET_TX_COMMAND.to_string(),
             pk_script,
             public_key,
-            last_update,
+            last_block_hash,
         }
     }
 
@@ -50,15 +53,16 @@ impl GetTransactions {
         let mut buffer = fill_command(self.command_name.as_str()).as_bytes().to_vec();
         buffer.extend(CompactSizeUInt::from_number(self.pk_script.len() as u64).as_bytes());
         buffer.extend(&self.pk_script);
+        buffer.extend(CompactSizeUInt::from_number(self.public_key.len() as u64).as_bytes());
         buffer.extend(&self.public_key);
-        buffer.extend(self.last_update.to_le_bytes());
+        buffer.extend(&self.last_block_hash);
         buffer
     }
 
     /// Parses a byte stream into a `GetTransactions` struct.
     ///
     /// This function attempts to parse a byte stream into a `GetTransactions` struct,
-    /// extracting the `pk_script`, `public_key`, and `last_update` fields.
+    /// extracting the `pk_script`, `public_key`, and `last_block_hash` fields.
     ///
     /// # Arguments
     ///
@@ -75,19 +79,20 @@ impl GetTransactions {
     ) -> Result<GetTransactions, MessageError> {
         let pk_script_bytes = CompactSizeUInt::from_bytes(stream)?;
         let pk_script = read_vec_from_bytes(stream, pk_script_bytes.value() as usize)?;
-        let public_key = read_vec_from_bytes(stream, 33)?;
-        let last_update = read_u32_from_bytes(stream, true)?;
+        let public_key_bytes = CompactSizeUInt::from_bytes(stream)?;
+        let public_key = read_vec_from_bytes(stream, public_key_bytes.value() as usize)?;
+        let last_block_hash = read_vec_from_bytes(stream, 32)?;
 
         Ok(GetTransactions {
             command_name,
             pk_script,
             public_key,
-            last_update,
+            last_block_hash,
         })
     }
 
-    pub fn get_last_update(&self) -> u32 {
-        self.last_update
+    pub fn get_last_block_hash(&self) -> &Vec<u8> {
+        &self.last_block_hash
     }
 
     pub fn get_pk_script(&self) -> &Vec<u8> {
@@ -0,0 +1,69 @@
+use std::io::Read;
+
+use crate::messages::{
+    message_error::MessageError,
+    read_from_bytes::{fill_command, read_u32_from_bytes},
+};
+
+use super::wallet_utils_constants::HELLO_COMMAND;
+
+/// The handshake of the framed node–wallet protocol.
+///
+/// The wallet starts the connection with this message, unframed, so the node can tell it from the commands of the
+/// legacy protocol. The node answers with a frame carrying another `Hello` with the version both will use, or with an
+/// `ErrorResponse` if it doesn't support the version of the wallet.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Hello {
+    command_name: String,
+    version: u32,
+}
+
+impl Hello {
+    /// Creates the handshake for a version of the protocol.
+    pub fn new(version: u32) -> Hello {
+        Hello {
+            command_name: HELLO_COMMAND.to_string(),
+            version,
+        }
+    }
+
+    /// Reads the handshake after its command.
+    pub fn from_bytes(command_name: String, stream: &mut dyn Read) -> Result<Hello, MessageError> {
+        let version = read_u32_from_bytes(stream, true)?;
+
+        Ok(Hello {
+            command_name,
+            version,
+        })
+    }
+
+    pub fn as_bytes(&self) -> Vec<u8> {
+        let mut buffer = fill_command(self.command_name.as_str()).as_bytes().to_vec();
+        buffer.extend(self.version.to_le_bytes());
+        buffer
+    }
+
+    pub fn get_version(&self) -> u32 {
+        self.version
+    }
+}
+
+#[cfg(test)]
+mod hello_test {
+    use super::*;
+    use crate::messages::read_from_bytes::read_string_from_bytes;
+
+    #[test]
+    fn test_hello_round_trip() -> Result<(), MessageError> {
+        let bytes = Hello::new(2).as_bytes();
+        let mut stream = &bytes[..];
+
+        let command_name = read_string_from_bytes(&mut stream, 12)?;
+        assert_eq!(command_name, HELLO_COMMAND);
+        assert_eq!(
+            Hello::from_bytes(command_name, &mut stream)?.get_version(),
+            2
+        );
+        Ok(())
+    }
+}
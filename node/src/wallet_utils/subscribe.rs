@@ -0,0 +1,73 @@
+use std::io::Read;
+
+use crate::messages::{
+    compact_size::CompactSizeUInt,
+    message_error::MessageError,
+    read_from_bytes::{fill_command, read_u32_from_bytes, read_vec_from_bytes},
+};
+
+use super::wallet_utils_constants::{MAX_SUBSCRIBED_ADDRESSES, SUBSCRIBE_COMMAND};
+
+/// Asks the node to push the transactions of some addresses and the blocks it connects, see `WalletNotifier`.
+///
+/// Each address is given by its public key script and its public key, empty for addresses locked to a script, as in
+/// `GetTransactions`. A subscription replaces the previous one of the connection, and an empty one cancels it.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Subscribe {
+    command_name: String,
+    addresses: Vec<(Vec<u8>, Vec<u8>)>,
+}
+
+impl Subscribe {
+    pub fn new(addresses: Vec<(Vec<u8>, Vec<u8>)>) -> Subscribe {
+        Subscribe {
+            command_name: SUBSCRIBE_COMMAND.to_string(),
+            addresses,
+        }
+    }
+
+    /// Serializes the subscription as `count || (pk_script length || pk_script || public key length || public key)*`.
+    pub fn as_bytes(&self) -> Vec<u8> {
+        let mut buffer = fill_command(self.command_name.as_str()).as_bytes().to_vec();
+        buffer.extend((self.addresses.len() as u32).to_le_bytes());
+        for (pk_script, public_key) in self.addresses.iter() {
+            buffer.extend(CompactSizeUInt::from_number(pk_script.len() as u64).as_bytes());
+            buffer.extend(pk_script);
+            buffer.extend(CompactSizeUInt::from_number(public_key.len() as u64).as_bytes());
+            buffer.extend(public_key);
+        }
+        buffer
+    }
+
+    /// Reads the subscription after its command.
+    ///
+    /// # Returns
+    ///
+    /// The subscription, or a `MessageError` if it can't be read or has more than `MAX_SUBSCRIBED_ADDRESSES` addresses.
+    pub fn from_bytes(
+        command_name: String,
+        stream: &mut dyn Read,
+    ) -> Result<Subscribe, MessageError> {
+        let count = read_u32_from_bytes(stream, true)? as usize;
+        if count > MAX_SUBSCRIBED_ADDRESSES {
+            return Err(MessageError::InvalidInputSubscribe);
+        }
+        let mut addresses = vec![];
+        for _ in 0..count {
+            let pk_script_bytes = CompactSizeUInt::from_bytes(stream)?;
+            let pk_script = read_vec_from_bytes(stream, pk_script_bytes.value() as usize)?;
+            let public_key_bytes = CompactSizeUInt::from_bytes(stream)?;
+            let public_key = read_vec_from_bytes(stream, public_key_bytes.value() as usize)?;
+            addresses.push((pk_script, public_key));
+        }
+
+        Ok(Subscribe {
+            command_name,
+            addresses,
+        })
+    }
+
+    pub fn get_addresses(&self) -> &Vec<(Vec<u8>, Vec<u8>)> {
+        &self.addresses
+    }
+}
@@ -1,4 +1,6 @@
-use super::{wallet_tx::WalletTx, wallet_utils_constants::TRANSACTIONS_COMMAND};
+use super::{
+    chain_sync::ChainSync, wallet_tx::WalletTx, wallet_utils_constants::TRANSACTIONS_COMMAND,
+};
 use crate::{
     block_mod::tx_out::TxOut,
     messages::{
@@ -17,7 +19,7 @@ pub struct Transactions {
     unconfirmed_txs_send: Vec<WalletTx>,
     unconfirmed_txs_recv: Vec<WalletTx>,
     utxo: Vec<(Vec<u8>, u32, TxOut)>,
-    last_update_time: u32,
+    chain_sync: ChainSync,
 }
 
 impl Transactions {
@@ -30,7 +32,7 @@ impl Transactions {
     /// * `unconfirmed_txs_send`: A vector of `WalletTx` representing the unconfirmed outgoing transactions.
     /// * `unconfirmed_txs_recv`: A vector of `WalletTx` representing the unconfirmed incoming transactions.
     /// * `utxo`: A vector of tuples `(Vec<u8>, u32, TxOut)` representing the Unspent Transaction Outputs (UTXOs).
-    /// * `last_update_time`: An unsigned integer representing the last time the transaction data was updated.
+    /// * `chain_sync`: The `ChainSync` describing the block the wallet is synced to and the blocks it has to disconnect.
     ///
     /// # Returns
     ///
@@ -41,7 +43,7 @@ impl Transactions {
         unconfirmed_txs_send: Vec<WalletTx>,
         unconfirmed_txs_recv: Vec<WalletTx>,
         utxo: Vec<(Vec<u8>, u32, TxOut)>,
-        last_update_time: u32,
+        chain_sync: ChainSync,
     ) -> Transactions {
         Transactions {
             command_name: TRANSACTIONS_COMMAND.to_string(),
@@ -50,7 +52,7 @@ impl Transactions {
             unconfirmed_txs_send,
             unconfirmed_txs_recv,
             utxo,
-            last_update_time,
+            chain_sync,
         }
     }
 
@@ -89,7 +91,7 @@ impl Transactions {
             buffer.extend(utxo.2.as_bytes());
         }
 
-        buffer.extend(self.last_update_time.to_le_bytes());
+        buffer.extend(self.chain_sync.as_bytes());
 
         buffer
     }
@@ -138,7 +140,7 @@ impl Transactions {
             utxo.push((txid, index, txout));
         }
 
-        let last_update_time = read_u32_from_bytes(stream, true)?;
+        let chain_sync = ChainSync::from_bytes(stream)?;
 
         Ok(Transactions {
             command_name: TRANSACTIONS_COMMAND.to_string(),
@@ -147,7 +149,7 @@ impl Transactions {
             unconfirmed_txs_send,
             unconfirmed_txs_recv,
             utxo,
-            last_update_time,
+            chain_sync,
         })
     }
 
@@ -179,7 +181,7 @@ impl Transactions {
         self.utxo.clone()
     }
 
-    pub fn get_last_update(&self) -> u32 {
-        self.last_update_time
+    pub fn get_chain_sync(&self) -> &ChainSync {
+        &self.chain_sync
     }
 }
@@ -1,72 +1,299 @@
-use super::{tx_filter_error::TxFilterError, wallet_utils_constants::DATE_FORMAT};
+use super::{
+    chain_sync::ChainSync,
+    tx_filter_error::TxFilterError,
+    wallet_utils_constants::{DATE_FORMAT, NULL_BLOCK_HASH},
+};
 use crate::{
-    block_mod::{blockchain::BlockChain, mempool::Mempool, tx_out::TxOut, utxo::UnspentTx},
+    block_mod::{
+        address_index::AddressIndex,
+        block::Block,
+        blockchain::BlockChain,
+        mempool::Mempool,
+        script_type::{
+            get_pubkey_hash, get_pubkey_hash_scripts, get_pushes, get_script_hash, ScriptType,
+        },
+        transaction::Transaction,
+        tx_in::TxIn,
+        tx_out::TxOut,
+        utxo::UnspentTx,
+    },
     wallet_utils::{
         get_transactions::GetTransactions, transactions::Transactions, wallet_tx::WalletTx,
     },
 };
+use bitcoin_hashes::{hash160, sha256, Hash};
 use chrono::{Local, NaiveDateTime};
-use std::sync::{Arc, Mutex};
+use std::{
+    cmp::Reverse,
+    sync::{Arc, Mutex},
+};
+
+/// Checks if an output script pays to the wallet.
+///
+/// # Arguments
+///
+/// * `output_pk_script` - The public key script of the output.
+/// * `wallet_pk_script` - The public key script the wallet asked for.
+///
+/// # Returns
+///
+/// `true` if both scripts are equal, or if they are P2PKH and P2WPKH scripts locked to the same public key hash, so coins
+/// received on the native SegWit address of a key are found along with the legacy ones.
+fn is_wallet_pk_script(output_pk_script: &[u8], wallet_pk_script: &[u8]) -> bool {
+    get_pubkey_hash_scripts(wallet_pk_script)
+        .iter()
+        .any(|pk_script| pk_script == output_pk_script)
+}
+
+/// Checks if an input is signed with the given public key.
+///
+/// # Arguments
+///
+/// * `txin` - The transaction input.
+/// * `public_key` - The compressed public key of the wallet.
+///
+/// # Returns
+///
+/// `true` if the public key is at the end of the signature script (P2PKH) or is the last item of the witness (P2WPKH).
+/// Addresses that aren't of a single key, such as multisig ones, send an empty public key, which matches no input.
+fn is_spent_by_public_key(txin: &TxIn, public_key: &[u8]) -> bool {
+    if public_key.is_empty() {
+        return false;
+    }
+    let signature = txin.get_signature_script();
+    if signature.len() >= 33 && signature[(signature.len() - 33)..] == *public_key {
+        return true;
+    }
+    txin.get_witness()
+        .last()
+        .is_some_and(|item| item.as_slice() == public_key)
+}
+
+/// Checks if an input spends an output locked to the hash of a script, such as a multisig one.
+///
+/// # Arguments
+///
+/// * `txin` - The transaction input.
+/// * `pk_script` - The P2SH or P2WSH public key script of the wallet.
+///
+/// # Returns
+///
+/// `true` if the input reveals the script `pk_script` is locked to: the last push of the signature script hashes to the
+/// P2SH hash, or the last item of the witness to the P2WSH program. Any other `pk_script` matches no input.
+fn is_spent_by_script(txin: &TxIn, pk_script: &[u8]) -> bool {
+    let script_hash = match get_script_hash(pk_script) {
+        Some(script_hash) => script_hash,
+        None => return false,
+    };
+    match ScriptType::from_pk_script(pk_script) {
+        ScriptType::P2sh => get_pushes(txin.get_signature_script())
+            .and_then(|pushes| pushes.last().copied())
+            .is_some_and(|script| hash160::Hash::hash(script).to_byte_array() == script_hash),
+        _ => txin
+            .get_witness()
+            .last()
+            .is_some_and(|script| sha256::Hash::hash(script).to_byte_array() == script_hash),
+    }
+}
+
+/// Checks if an input spends an output locked to the hash of a public key the wallet doesn't know, such as the one of a
+/// watched address.
+///
+/// # Arguments
+///
+/// * `txin` - The transaction input.
+/// * `pk_script` - The P2PKH or P2WPKH public key script of the wallet.
+///
+/// # Returns
+///
+/// `true` if the last push of the signature script (P2PKH) or the last item of the witness (P2WPKH) is a public key whose
+/// hash160 is the hash of `pk_script`. Any other `pk_script` matches no input.
+fn is_spent_by_pubkey_hash(txin: &TxIn, pk_script: &[u8]) -> bool {
+    let pubkey_hash = match get_pubkey_hash(pk_script) {
+        Some(pubkey_hash) => pubkey_hash,
+        None => return false,
+    };
+    let is_wallet_key =
+        |public_key: &[u8]| hash160::Hash::hash(public_key).to_byte_array() == pubkey_hash;
+    get_pushes(txin.get_signature_script())
+        .and_then(|pushes| pushes.last().copied())
+        .is_some_and(is_wallet_key)
+        || txin
+            .get_witness()
+            .last()
+            .is_some_and(|item| is_wallet_key(item))
+}
+
+/// Checks if an input spends an output of the wallet, see `is_spent_by_public_key`, `is_spent_by_pubkey_hash` and
+/// `is_spent_by_script`.
+fn is_spent_by_wallet(txin: &TxIn, pk_script: &[u8], public_key: &[u8]) -> bool {
+    is_spent_by_public_key(txin, public_key)
+        || is_spent_by_pubkey_hash(txin, pk_script)
+        || is_spent_by_script(txin, pk_script)
+}
+
+/// Checks if any input of a transaction is spent by the wallet, see `is_spent_by_wallet`.
+fn is_sent_by_wallet(transaction: &Transaction, pk_script: &[u8], public_key: &[u8]) -> bool {
+    transaction
+        .get_tx_in_list()
+        .iter()
+        .any(|txin| is_spent_by_wallet(txin, pk_script, public_key))
+}
+
+/// Checks if any output of a transaction pays to the wallet, see `is_wallet_pk_script`.
+fn is_received_by_wallet(transaction: &Transaction, pk_script: &[u8]) -> bool {
+    transaction
+        .get_tx_out_list()
+        .iter()
+        .any(|tx_out| is_wallet_pk_script(&tx_out.get_pk_script(), pk_script))
+}
+
+/// Checks if a transaction belongs to an address of the wallet, the same way the confirmed and unconfirmed
+/// transactions are filtered: any of its inputs is spent by the wallet or any output pays to it.
+pub fn is_wallet_transaction(
+    transaction: &Transaction,
+    pk_script: &[u8],
+    public_key: &[u8],
+) -> bool {
+    is_sent_by_wallet(transaction, pk_script, public_key)
+        || is_received_by_wallet(transaction, pk_script)
+}
+
+/// Adds a transaction of the wallet to the sent ones if any of its inputs is spent by the wallet, or else to the
+/// received ones if any output pays to it. Any other transaction is left out.
+fn classify_transaction(
+    wallet_tx: WalletTx,
+    pk_script: &[u8],
+    public_key: &[u8],
+    txs_send: &mut Vec<WalletTx>,
+    txs_recv: &mut Vec<WalletTx>,
+) {
+    if is_sent_by_wallet(wallet_tx.get_tx(), pk_script, public_key) {
+        txs_send.push(wallet_tx);
+    } else if is_received_by_wallet(wallet_tx.get_tx(), pk_script) {
+        txs_recv.push(wallet_tx);
+    }
+}
+
+/// Returns the date a block was mined at, in the format the wallet shows.
+fn get_block_date(block: &Block) -> Result<String, TxFilterError> {
+    let datetime = NaiveDateTime::from_timestamp_opt(block.get_header().get_time() as i64, 0)
+        .ok_or(TxFilterError::DateTimeError)?;
+    Ok(datetime.format(DATE_FORMAT).to_string())
+}
 
 /// Filters confirmed transactions from the blockchain based on the provided criteria.
 ///
 /// # Arguments
 ///
-/// * `blockchain` - An `Arc<Mutex<BlockChain>>` representing the blockchain to filter transactions from.
+/// * `blockchain` - The locked `BlockChain` to filter transactions from.
 /// * `pk_script` - A reference to a vector of bytes representing the public key script to filter transactions.
 /// * `public_key` - A vector of bytes representing the public key.
-/// * `last_update` - The last update time to use as a filter.
+/// * `fork_point` - The hash of the block where the scan stops, which is not scanned.
 ///
 /// # Returns
 ///
-/// A tuple containing two vectors of `WalletTx`: `confirmed_txs_send` and `confirmed_txs_recv`.
-/// `confirmed_txs_send` contains filtered transactions where the provided public key matches the signature script.
-/// `confirmed_txs_recv` contains filtered transactions where the provided public key script matches any of the transaction outputs.
+/// A tuple containing two vectors of `WalletTx`: `confirmed_txs_send` and `confirmed_txs_recv`, found in the blocks after
+/// the fork point, or in the whole chain if it isn't part of it. The chain is walked with the headers, so only the
+/// blocks that are scanned are read, and the pruned ones are skipped.
+/// `confirmed_txs_send` contains filtered transactions where any input is signed with the provided public key or the key of `pk_script`, or reveals its script, see `is_spent_by_wallet`.
+/// `confirmed_txs_recv` contains filtered transactions where any of the transaction outputs pays to the wallet, see `is_wallet_pk_script`.
 pub fn filter_confirmed_transactions(
-    blockchain: &Arc<Mutex<BlockChain>>,
-    pk_script: &Vec<u8>,
+    blockchain: &BlockChain,
+    pk_script: &[u8],
     public_key: Vec<u8>,
-    last_update: u32,
+    fork_point: &[u8],
 ) -> Result<(Vec<WalletTx>, Vec<WalletTx>), TxFilterError> {
     let mut confirmed_txs_send: Vec<WalletTx> = vec![];
     let mut confirmed_txs_recv: Vec<WalletTx> = vec![];
-    let locked_blockchain = blockchain
-        .lock()
-        .map_err(|_| TxFilterError::LockBlockchain)?;
-    let mut last_block_header = &locked_blockchain.get_last_block_header();
+    let mut block_hash = blockchain.get_last_block_header();
 
-    while let Some(block) = locked_blockchain.get_block(last_block_header) {
-        let datetime = NaiveDateTime::from_timestamp_opt(block.get_header().get_time() as i64, 0)
-            .ok_or(TxFilterError::DateTimeError)?;
-        let date = datetime.format(DATE_FORMAT).to_string();
+    while block_hash != fork_point {
+        let previous = match blockchain.get_block_header(&block_hash) {
+            Some(header) => header.get_previuos_block_header().clone(),
+            None => break,
+        };
+        if let Some(block) = blockchain.get_block(&block_hash) {
+            let date = get_block_date(&block)?;
 
-        if block.get_header().get_time() <= last_update {
-            break;
+            for transaction in block.get_txn_list() {
+                classify_transaction(
+                    WalletTx::new(transaction.clone(), block_hash.clone(), date.clone()),
+                    pk_script,
+                    &public_key,
+                    &mut confirmed_txs_send,
+                    &mut confirmed_txs_recv,
+                );
+            }
         }
+        block_hash = previous;
+    }
+    Ok((confirmed_txs_send, confirmed_txs_recv))
+}
 
-        for transaction in block.get_txn_list() {
-            if let Some(first_txin) = transaction.get_tx_in_list().get(0) {
-                let signature = first_txin.get_signature_script();
+/// Filters confirmed transactions with the address index, instead of scanning the blocks.
+///
+/// # Arguments
+///
+/// * `blockchain` - The locked `BlockChain` the transactions are taken from.
+/// * `address_index` - The address index of the blockchain.
+/// * `pk_script` - The public key script of the address.
+/// * `public_key` - The public key of the address, empty if it is locked to a script.
+/// * `fork_point` - The hash of the block where the chain is no longer followed, whose transactions are left out.
+///
+/// # Returns
+///
+/// The same transactions `filter_confirmed_transactions` returns, in the same order.
+///
+/// # Description
+///
+/// The transactions of the address are looked up in the index, and only the ones of blocks of the active chain after
+/// the fork point are kept, comparing each block with the block of the chain at its height, so the work depends on the
+/// history of the address instead of on the length of the chain. The index can list transactions that don't belong to
+/// the address, which are checked as in `filter_confirmed_transactions`.
+pub fn filter_indexed_transactions(
+    blockchain: &BlockChain,
+    address_index: &AddressIndex,
+    pk_script: &[u8],
+    public_key: &[u8],
+    fork_point: &[u8],
+) -> Result<(Vec<WalletTx>, Vec<WalletTx>), TxFilterError> {
+    let fork_height = match blockchain.is_in_active_chain(fork_point) {
+        true => blockchain.get_height(fork_point),
+        false => None,
+    };
 
-                if signature.len() >= 33 && signature[(signature.len() - 33)..] == public_key {
-                    confirmed_txs_send.push(WalletTx::new(transaction.clone(), date.clone()));
-                    continue;
-                }
+    let mut entries: Vec<(Reverse<usize>, Vec<u8>, usize)> = address_index
+        .get_entries(pk_script, public_key)
+        .into_iter()
+        .filter(|(block_hash, _)| blockchain.is_in_active_chain(block_hash))
+        .filter_map(|(block_hash, position)| {
+            let height = blockchain.get_height(&block_hash)?;
+            match fork_height {
+                Some(fork_height) if height <= fork_height => None,
+                _ => Some((Reverse(height), block_hash, position)),
             }
+        })
+        .collect();
+    entries.sort();
 
-            let tx_outs: Vec<Vec<u8>> = transaction
-                .get_tx_out_list()
-                .iter()
-                .map(|tx_out| tx_out.get_pk_script())
-                .collect();
-            if tx_outs.contains(pk_script) {
-                confirmed_txs_recv.push(WalletTx::new(transaction.clone(), date.clone()));
-            }
+    let mut confirmed_txs_send: Vec<WalletTx> = vec![];
+    let mut confirmed_txs_recv: Vec<WalletTx> = vec![];
+    for (_, block_hash, position) in entries {
+        let block = match blockchain.get_block(&block_hash) {
+            Some(block) => block,
+            None => continue,
+        };
+        if let Some(transaction) = block.get_txn_list().get(position) {
+            classify_transaction(
+                WalletTx::new(transaction.clone(), block_hash, get_block_date(&block)?),
+                pk_script,
+                public_key,
+                &mut confirmed_txs_send,
+                &mut confirmed_txs_recv,
+            );
         }
-
-        last_block_header = block.get_previuos_block_header();
     }
-    drop(locked_blockchain);
     Ok((confirmed_txs_send, confirmed_txs_recv))
 }
 
@@ -81,11 +308,11 @@ pub fn filter_confirmed_transactions(
 /// # Returns
 ///
 /// A tuple containing two vectors of `WalletTx`: `unconfirmed_txs_send` and `unconfirmed_txs_recv`.
-/// `unconfirmed_txs_send` contains filtered transactions from the mempool where the provided public key matches the signature script.
-/// `unconfirmed_txs_recv` contains filtered transactions from the mempool where the provided public key script matches any of the transaction outputs.
+/// `unconfirmed_txs_send` contains filtered transactions from the mempool where any input is signed with the provided public key or the key of `pk_script`, or reveals its script, see `is_spent_by_wallet`.
+/// `unconfirmed_txs_recv` contains filtered transactions from the mempool where any of the transaction outputs pays to the wallet, see `is_wallet_pk_script`.
 pub fn filter_unconfirmed_transactions(
     mempool: &Arc<Mutex<Mempool>>,
-    pk_script: &Vec<u8>,
+    pk_script: &[u8],
     public_key: Vec<u8>,
 ) -> Result<(Vec<WalletTx>, Vec<WalletTx>), TxFilterError> {
     let mut unconfirmed_txs_send: Vec<WalletTx> = vec![];
@@ -94,23 +321,14 @@ pub fn filter_unconfirmed_transactions(
 
     let locked_mempool = mempool.lock().map_err(|_| TxFilterError::LockMempool)?;
 
-    for transaction in locked_mempool.get_txs().iter() {
-        if let Some(first_txin) = transaction.1.get_tx_in_list().get(0) {
-            let signature = first_txin.get_signature_script();
-            if signature.len() >= 33 && signature[(signature.len() - 33)..] == public_key {
-                unconfirmed_txs_send.push(WalletTx::new(transaction.1.clone(), date.clone()));
-                continue;
-            }
-        }
-        let txouts: Vec<Vec<u8>> = transaction
-            .1
-            .get_tx_out_list()
-            .iter()
-            .map(|txout| txout.get_pk_script())
-            .collect();
-        if txouts.contains(pk_script) {
-            unconfirmed_txs_recv.push(WalletTx::new(transaction.1.clone(), date.clone()));
-        }
+    for transaction in locked_mempool.get_txs().values() {
+        classify_transaction(
+            WalletTx::new(transaction.clone(), NULL_BLOCK_HASH.to_vec(), date.clone()),
+            pk_script,
+            &public_key,
+            &mut unconfirmed_txs_send,
+            &mut unconfirmed_txs_recv,
+        );
     }
 
     drop(locked_mempool);
@@ -132,7 +350,7 @@ pub fn filter_unconfirmed_transactions(
 pub fn filter_utxo(
     utxo_hash: &Arc<Mutex<UnspentTx>>,
     confirmed_txs: &[WalletTx],
-    pk_script: &Vec<u8>,
+    pk_script: &[u8],
 ) -> Result<Vec<(Vec<u8>, u32, TxOut)>, TxFilterError> {
     let mut utxo_txs: Vec<(Vec<u8>, u32, TxOut)> = vec![];
     let locked_utxo_hash = utxo_hash.lock().map_err(|_| TxFilterError::LockUtxo)?;
@@ -143,7 +361,7 @@ pub fn filter_utxo(
             .get(&transaction.get_tx().get_id())
         {
             let current_txouts = outputs.iter().filter_map(|(&index, tx_out)| {
-                if tx_out.get_pk_script() == *pk_script {
+                if is_wallet_pk_script(&tx_out.get_pk_script(), pk_script) {
                     Some((transaction.get_tx().get_id(), index, tx_out.clone()))
                 } else {
                     None
@@ -169,6 +387,14 @@ pub fn filter_utxo(
 /// # Returns
 ///
 /// A `Transactions` object containing the wallet transactions that match the specified criteria.
+///
+/// # Description
+///
+/// Only the blocks after the last one the wallet was synced to are scanned. If a reorganization left that block out of the
+/// chain, the scan starts from the fork point and the `ChainSync` of the answer lists the blocks the wallet has to
+/// disconnect. If the block is unknown, the whole chain is scanned. The blockchain stays locked during the scan, so the
+/// last block sent to the wallet is the one the scan started from. With the address index enabled the transactions of
+/// those blocks are looked up in it instead, see `filter_indexed_transactions`.
 pub fn get_wallet_txns(
     blockchain: &Arc<Mutex<BlockChain>>,
     utxo_hash: &Arc<Mutex<UnspentTx>>,
@@ -177,10 +403,38 @@ pub fn get_wallet_txns(
 ) -> Result<Transactions, TxFilterError> {
     let pk_script = get_transactions.get_pk_script();
     let public_key = get_transactions.get_public_key();
-    let mut last_update = get_transactions.get_last_update();
 
+    let locked_blockchain = blockchain
+        .lock()
+        .map_err(|_| TxFilterError::LockBlockchain)?;
+    let (fork_point, disconnected_blocks, full_rescan) =
+        match locked_blockchain.find_fork_point(get_transactions.get_last_block_hash()) {
+            Some((fork_point, disconnected_blocks)) => (fork_point, disconnected_blocks, false),
+            None => (NULL_BLOCK_HASH.to_vec(), vec![], true),
+        };
     let (confirmed_txs_send, confirmed_txs_recv): (Vec<WalletTx>, Vec<WalletTx>) =
-        filter_confirmed_transactions(blockchain, pk_script, public_key.clone(), last_update)?;
+        match locked_blockchain.get_address_index() {
+            Some(address_index) => filter_indexed_transactions(
+                &locked_blockchain,
+                address_index,
+                pk_script,
+                public_key,
+                &fork_point,
+            )?,
+            None => filter_confirmed_transactions(
+                &locked_blockchain,
+                pk_script,
+                public_key.clone(),
+                &fork_point,
+            )?,
+        };
+    let chain_sync = ChainSync::new(
+        locked_blockchain.get_last_block_header(),
+        disconnected_blocks,
+        full_rescan,
+    );
+    drop(locked_blockchain);
+
     let (unconfirmed_txs_send, unconfirmed_txs_recv): (Vec<WalletTx>, Vec<WalletTx>) =
         filter_unconfirmed_transactions(mempool, pk_script, public_key.clone())?;
 
@@ -190,20 +444,157 @@ pub fn get_wallet_txns(
         pk_script,
     )?;
 
-    let locked_blockchain = blockchain
-        .lock()
-        .map_err(|_| TxFilterError::LockBlockchain)?;
-    let last_block = locked_blockchain
-        .get_block(&locked_blockchain.get_last_block_header())
-        .ok_or(TxFilterError::UnfoundBlock)?;
-    last_update = last_block.get_header().get_time();
-
     Ok(Transactions::new(
         confirmed_txs_send,
         confirmed_txs_recv,
         unconfirmed_txs_send,
         unconfirmed_txs_recv,
         utxo_txs,
-        last_update,
+        chain_sync,
     ))
 }
+
+#[cfg(test)]
+mod tx_filter_test {
+    use super::*;
+    use crate::{block_mod::block_header::BlockHeader, network::bitcoin_network::BitcoinNetwork};
+
+    /// Creates a block on top of the given one, with an empty coinbase and the given transactions.
+    fn test_block(
+        previous: &[u8],
+        time: u32,
+        transactions: &[Transaction],
+    ) -> Result<Block, TxFilterError> {
+        let header = BlockHeader::new(1, previous.to_vec(), vec![0; 32], time, 0, 0);
+        let mut bytes = header.as_bytes();
+        bytes.push(transactions.len() as u8 + 1);
+        bytes.extend([1, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        for transaction in transactions {
+            bytes.extend(transaction.as_bytes());
+        }
+        Block::from_bytes(&mut &bytes[..]).map_err(|_| TxFilterError::UnfoundBlock)
+    }
+
+    fn get_ids(wallet_txs: &[WalletTx]) -> Vec<Vec<u8>> {
+        wallet_txs
+            .iter()
+            .map(|wallet_tx| wallet_tx.get_tx().get_id())
+            .collect()
+    }
+
+    #[test]
+    fn test_indexed_transactions_match_the_scan() -> Result<(), TxFilterError> {
+        let public_key = vec![2; 33];
+        let pk_script = [
+            &[0x76, 0xa9, 20],
+            &hash160::Hash::hash(&public_key).to_byte_array()[..],
+            &[0x88, 0xac],
+        ]
+        .concat();
+        let received = Transaction::new(
+            2,
+            vec![TxIn::new(vec![1; 32], 0, vec![], 0)],
+            vec![TxOut::new(1000, pk_script.clone())],
+            0,
+        );
+        let sent = Transaction::new(
+            2,
+            vec![
+                TxIn::new(vec![1; 32], 1, vec![], 0),
+                TxIn::new(
+                    received.get_id(),
+                    0,
+                    [&[71], &[3; 71][..], &[33], &public_key[..]].concat(),
+                    0,
+                ),
+            ],
+            vec![TxOut::new(900, vec![0x51])],
+            0,
+        );
+        let genesis = BitcoinNetwork::default().get_genesis_header();
+        let first = test_block(&genesis.get_header(), genesis.get_time() + 1, &[received])?;
+        let second = test_block(
+            &first.get_header().get_header(),
+            genesis.get_time() + 2,
+            &[sent],
+        )?;
+        let mut blockchain = BlockChain::default();
+        let mut utxo = UnspentTx::new();
+        blockchain.enable_address_index();
+        blockchain.add(first, &mut utxo);
+        blockchain.add(second, &mut utxo);
+
+        let (scanned_send, scanned_recv) = filter_confirmed_transactions(
+            &blockchain,
+            &pk_script,
+            public_key.clone(),
+            &NULL_BLOCK_HASH,
+        )?;
+        let address_index = blockchain
+            .get_address_index()
+            .ok_or(TxFilterError::UnfoundBlock)?;
+        let (indexed_send, indexed_recv) = filter_indexed_transactions(
+            &blockchain,
+            address_index,
+            &pk_script,
+            &public_key,
+            &NULL_BLOCK_HASH,
+        )?;
+
+        assert_eq!(scanned_send.len(), 1);
+        assert_eq!(scanned_recv.len(), 1);
+        assert_eq!(get_ids(&indexed_send), get_ids(&scanned_send));
+        assert_eq!(get_ids(&indexed_recv), get_ids(&scanned_recv));
+        Ok(())
+    }
+
+    #[test]
+    fn test_indexed_transactions_leave_with_their_block() -> Result<(), TxFilterError> {
+        let pk_script = vec![0x51];
+        let received = Transaction::new(
+            2,
+            vec![TxIn::new(vec![1; 32], 0, vec![], 0)],
+            vec![TxOut::new(1000, pk_script.clone())],
+            0,
+        );
+        let genesis = BitcoinNetwork::default().get_genesis_header();
+        let time = genesis.get_time();
+        let first = test_block(&genesis.get_header(), time + 1, &[received])?;
+        let first_hash = first.get_header().get_header();
+        let other = test_block(&genesis.get_header(), time + 2, &[])?;
+        let next = test_block(&other.get_header().get_header(), time + 3, &[])?;
+        let mut blockchain = BlockChain::default();
+        let mut utxo = UnspentTx::new();
+        blockchain.enable_address_index();
+        blockchain.add(first, &mut utxo);
+        blockchain.add(other, &mut utxo);
+
+        let address_index = blockchain
+            .get_address_index()
+            .ok_or(TxFilterError::UnfoundBlock)?;
+        let (_, indexed_recv) = filter_indexed_transactions(
+            &blockchain,
+            address_index,
+            &pk_script,
+            &[],
+            &NULL_BLOCK_HASH,
+        )?;
+        assert_eq!(indexed_recv.len(), 1);
+
+        blockchain.add(next, &mut utxo);
+        assert!(!blockchain.is_in_active_chain(&first_hash));
+        let address_index = blockchain
+            .get_address_index()
+            .ok_or(TxFilterError::UnfoundBlock)?;
+        assert!(address_index.get_entries(&pk_script, &[]).is_empty());
+        let (_, indexed_recv) = filter_indexed_transactions(
+            &blockchain,
+            address_index,
+            &pk_script,
+            &[],
+            &NULL_BLOCK_HASH,
+        )?;
+        assert!(indexed_recv.is_empty());
+        Ok(())
+    }
+}
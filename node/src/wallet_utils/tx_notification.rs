@@ -0,0 +1,63 @@
+use std::io::Read;
+
+use crate::messages::{
+    compact_size::CompactSizeUInt,
+    message_error::MessageError,
+    read_from_bytes::{fill_command, read_vec_from_bytes},
+};
+
+use super::{wallet_tx::WalletTx, wallet_utils_constants::TX_NOTIFICATION_COMMAND};
+
+/// Pushed to a subscribed wallet when a transaction of one of its addresses enters the mempool or a connected block.
+///
+/// The transaction is unconfirmed if the block hash of the `WalletTx` is all zeros.
+#[derive(Debug, PartialEq)]
+pub struct TxNotification {
+    command_name: String,
+    pk_script: Vec<u8>,
+    wallet_tx: WalletTx,
+}
+
+impl TxNotification {
+    /// Creates the notification of a transaction of the address with the given public key script.
+    pub fn new(pk_script: Vec<u8>, wallet_tx: WalletTx) -> TxNotification {
+        TxNotification {
+            command_name: TX_NOTIFICATION_COMMAND.to_string(),
+            pk_script,
+            wallet_tx,
+        }
+    }
+
+    /// Serializes the notification as `pk_script length || pk_script || wallet transaction`.
+    pub fn as_bytes(&self) -> Vec<u8> {
+        let mut buffer = fill_command(self.command_name.as_str()).as_bytes().to_vec();
+        buffer.extend(CompactSizeUInt::from_number(self.pk_script.len() as u64).as_bytes());
+        buffer.extend(&self.pk_script);
+        buffer.extend(self.wallet_tx.as_bytes());
+        buffer
+    }
+
+    /// Reads the notification after its command.
+    pub fn from_bytes(
+        command_name: String,
+        stream: &mut dyn Read,
+    ) -> Result<TxNotification, MessageError> {
+        let pk_script_bytes = CompactSizeUInt::from_bytes(stream)?;
+        let pk_script = read_vec_from_bytes(stream, pk_script_bytes.value() as usize)?;
+        let wallet_tx = WalletTx::from_bytes(stream)?;
+
+        Ok(TxNotification {
+            command_name,
+            pk_script,
+            wallet_tx,
+        })
+    }
+
+    pub fn get_pk_script(&self) -> &Vec<u8> {
+        &self.pk_script
+    }
+
+    pub fn get_wallet_tx(&self) -> &WalletTx {
+        &self.wallet_tx
+    }
+}
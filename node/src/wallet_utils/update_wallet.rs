@@ -1,15 +1,18 @@
 use std::{
-    io::Write,
+    io::{Read, Write},
     net::TcpStream,
-    sync::{Arc, Mutex},
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
 };
 
 use crate::{
-    block_mod::{blockchain::BlockChain, mempool::Mempool, utxo::UnspentTx},
-    messages::{read_from_bytes::read_string_from_bytes, tx::Tx},
+    messages::{
+        message_constants::NOT_FOUND_COMMAND,
+        read_from_bytes::{fill_command, read_string_from_bytes},
+        tx::Tx,
+    },
     network::broadcasting::broadcast_new_txn,
     proof_of_inclusion_mod::proof_of_inclusion::send_proof,
-    settings_mod::settings::Settings,
     wallet_utils::{
         broadcast_txn::BroadcastTxn, get_proof::GetProof, get_transactions::GetTransactions,
         transactions::Transactions, tx_filter::get_wallet_txns,
@@ -17,87 +20,295 @@ use crate::{
 };
 
 use super::{
+    error_response::{ErrorCode, ErrorResponse},
+    hello::Hello,
     progress::Progress,
+    subscribe::Subscribe,
     update_wallet_error::UpdateWalletError,
+    wallet_frame::WalletFrame,
+    wallet_state::WalletState,
     wallet_utils_constants::{
-        BROADCAST_TX_COMMAND, EXIT_COMMAND, GET_PROGRESS_COMMAND, GET_PROOF_COMMAND, GET_TX_COMMAND,
+        BROADCAST_TX_COMMAND, COMMAND_NAME_SIZE, EXIT_COMMAND, GET_PROGRESS_COMMAND,
+        GET_PROOF_COMMAND, GET_TX_COMMAND, HELLO_COMMAND, MIN_WALLET_PROTOCOL_VERSION, OK_COMMAND,
+        PROGRESS_COMMAND, SUBSCRIBE_COMMAND, UNSOLICITED_REQUEST_ID, WALLET_PROTOCOL_VERSION,
     },
 };
 
+/// The outcome of a command, before it is written in the format of the protocol of the wallet.
+enum CommandResponse {
+    /// A message that starts with its command, such as `transactions`, `merkleblock` or `notfound`.
+    Message(Vec<u8>),
+    Progress(Progress),
+    Broadcasted,
+    Subscribed(Subscribe),
+    Exit,
+    Unknown,
+}
+
 /// Updates the wallet by processing incoming commands from the connected `TcpStream`.
 ///
 /// # Arguments
 ///
 /// * `wallet` - The `TcpStream` representing the connection to the wallet.
-/// * `blockchain` - A reference to the `BlockChain` wrapped in an `Arc<Mutex>`.
-/// * `utxo` - A reference to the `UnspentTx` wrapped in an `Arc<Mutex>`.
-/// * `mempool` - A reference to the `Mempool` wrapped in an `Arc<Mutex>`.
-/// * `settings` - A reference to the `Settings` wrapped in an `Arc`.
-/// * `streams` - A vector of `TcpStream` wrapped in `Arc<Mutex>`, representing connections to other nodes.
+/// * `state` - The `WalletState` with the blockchain, the UTXO set, the mempool, the settings, the connections to other
+///   nodes and the notifier of the wallets.
 ///
 /// # Errors
 ///
 /// Returns an `UpdateWalletError` if there is an error reading from or writing to the `TcpStream`,
 /// parsing the incoming command, sending the proof, or broadcasting the transaction.
-pub fn update_wallet(
-    mut wallet: TcpStream,
-    blockchain: &Arc<Mutex<BlockChain>>,
-    utxo: &Arc<Mutex<UnspentTx>>,
-    mempool: &Arc<Mutex<Mempool>>,
-    settings: &Arc<Settings>,
-    streams: &Vec<Arc<Mutex<TcpStream>>>,
-    cant_total_blocks: usize,
+///
+/// # Description
+///
+/// A wallet that starts the connection with a `hello` speaks the framed protocol, where every request and response is
+/// a `WalletFrame` and the node answers the errors of a request with an `ErrorResponse`. Any other command starts the
+/// legacy protocol, where the messages aren't framed and the connection is closed on errors or unknown commands. Only
+/// framed connections can subscribe to notifications.
+pub fn update_wallet(mut wallet: TcpStream, state: &WalletState) -> Result<(), UpdateWalletError> {
+    let command_name = read_string_from_bytes(&mut wallet, COMMAND_NAME_SIZE)
+        .map_err(|_| UpdateWalletError::Read)?;
+
+    match command_name.as_str() {
+        HELLO_COMMAND => update_framed_wallet(&mut wallet, state),
+        _ => update_legacy_wallet(&mut wallet, command_name, state),
+    }
+}
+
+/// Serves the legacy protocol, starting with a command already read.
+fn update_legacy_wallet(
+    wallet: &mut TcpStream,
+    mut command_name: String,
+    state: &WalletState,
 ) -> Result<(), UpdateWalletError> {
     loop {
-        let command_name =
-            read_string_from_bytes(&mut wallet, 12).map_err(|_| UpdateWalletError::Read)?;
-        match command_name.as_str() {
-            GET_TX_COMMAND => {
-                let get_transactions =
-                    GetTransactions::from_bytes(command_name.to_string(), &mut wallet)
-                        .map_err(|_| UpdateWalletError::Read)?;
-                let transactions: Transactions =
-                    get_wallet_txns(blockchain, utxo, mempool, get_transactions)
-                        .map_err(|_| UpdateWalletError::GetTxn)?;
-                wallet
-                    .write_all(&transactions.as_bytes())
-                    .map_err(|_| UpdateWalletError::Write)?;
-            }
-            GET_PROOF_COMMAND => {
-                let get_proof = GetProof::from_bytes(command_name.to_string(), &mut wallet)
-                    .map_err(|_| UpdateWalletError::Read)?;
-                send_proof(
-                    get_proof.get_block_header(),
-                    get_proof.get_tx_id(),
-                    blockchain,
-                    &mut wallet,
-                )
-                .map_err(|_| UpdateWalletError::SendProof)?;
+        match run_command(&command_name, wallet, state)? {
+            CommandResponse::Message(message) => wallet
+                .write_all(&message)
+                .map_err(|_| UpdateWalletError::Write)?,
+            CommandResponse::Progress(progress) => wallet
+                .write_all(&progress.as_bytes())
+                .map_err(|_| UpdateWalletError::Write)?,
+            CommandResponse::Broadcasted => {}
+            CommandResponse::Exit => return Ok(()),
+            CommandResponse::Subscribed(_) | CommandResponse::Unknown => {
+                return Err(UpdateWalletError::UnknownCommand)
             }
-            BROADCAST_TX_COMMAND => {
-                let broadcast_txn = BroadcastTxn::from_bytes(command_name.to_string(), &mut wallet)
-                    .map_err(|_| UpdateWalletError::Read)?;
-                let tx_msg = Tx::new(settings.get_start_string(), broadcast_txn.get_txn());
+        }
+        command_name = read_string_from_bytes(wallet, COMMAND_NAME_SIZE)
+            .map_err(|_| UpdateWalletError::Read)?;
+    }
+}
 
-                broadcast_new_txn(tx_msg, streams).map_err(|_| UpdateWalletError::BroadcastTx)?;
-            }
-            GET_PROGRESS_COMMAND => {
-                let locked_blockchain = blockchain
-                    .lock()
-                    .map_err(|_| UpdateWalletError::LockBlockchain)?;
+/// Serves the framed protocol, once the `hello` command has been read.
+///
+/// # Description
+///
+/// The node answers the handshake with the version both will use, and then answers each frame with a frame that has
+/// the same request id. Errors of a request are answered with an `ErrorResponse`, and only errors reading or writing
+/// the frames close the connection.
+///
+/// The frames are written by their own thread, from a channel shared by the answers and the notifications the
+/// `WalletNotifier` pushes, so a notification is never written in the middle of an answer.
+fn update_framed_wallet(
+    wallet: &mut TcpStream,
+    state: &WalletState,
+) -> Result<(), UpdateWalletError> {
+    let hello = Hello::from_bytes(HELLO_COMMAND.to_string(), wallet)
+        .map_err(|_| UpdateWalletError::Read)?;
+    if hello.get_version() < MIN_WALLET_PROTOCOL_VERSION {
+        let error = ErrorResponse::new(
+            ErrorCode::UnsupportedVersion,
+            &format!(
+                "unsupported protocol version {}, the minimum is {}",
+                hello.get_version(),
+                MIN_WALLET_PROTOCOL_VERSION
+            ),
+        );
+        write_frame(wallet, UNSOLICITED_REQUEST_ID, &error.as_bytes())?;
+        return Err(UpdateWalletError::UnsupportedVersion);
+    }
+    let version = hello.get_version().min(WALLET_PROTOCOL_VERSION);
+    write_frame(
+        wallet,
+        UNSOLICITED_REQUEST_ID,
+        &Hello::new(version).as_bytes(),
+    )?;
+
+    let writer = wallet.try_clone().map_err(|_| UpdateWalletError::Write)?;
+    let (sender, receiver): (Sender<WalletFrame>, Receiver<WalletFrame>) = mpsc::channel();
+    thread::spawn(move || write_frames(writer, receiver));
+    let subscriber_id = state
+        .get_notifier()
+        .lock()
+        .map_err(|_| UpdateWalletError::LockNotifier)?
+        .register(sender.clone());
 
-                let act_blocks = locked_blockchain.get_cant_act_blocks() as u64;
+    let served = serve_frames(wallet, state, &sender, subscriber_id);
+    if let Ok(mut notifier) = state.get_notifier().lock() {
+        notifier.unregister(subscriber_id);
+    }
+    served
+}
 
-                let progress = Progress::new(act_blocks, cant_total_blocks as u64);
+/// Answers the frames of a wallet until it exits, sending the answers to the thread that writes them.
+fn serve_frames(
+    wallet: &mut TcpStream,
+    state: &WalletState,
+    sender: &Sender<WalletFrame>,
+    subscriber_id: u64,
+) -> Result<(), UpdateWalletError> {
+    loop {
+        let request = WalletFrame::from_bytes(wallet).map_err(|_| UpdateWalletError::Read)?;
+        let request_id = request.get_request_id();
+        let mut payload = &request.get_payload()[..];
 
-                wallet
-                    .write_all(&progress.as_bytes())
-                    .map_err(|_| UpdateWalletError::Write)?;
+        let response = match run_command(request.get_command_name(), &mut payload, state) {
+            Ok(CommandResponse::Exit) => return Ok(()),
+            Ok(CommandResponse::Unknown) => ErrorResponse::new(
+                ErrorCode::UnknownCommand,
+                &format!("unknown command {}", request.get_command_name()),
+            )
+            .as_bytes(),
+            Ok(_) if !payload.is_empty() => ErrorResponse::new(
+                ErrorCode::Malformed,
+                "the payload is longer than the command",
+            )
+            .as_bytes(),
+            Ok(CommandResponse::Message(message))
+                if message.starts_with(fill_command(NOT_FOUND_COMMAND).as_bytes()) =>
+            {
+                ErrorResponse::new(
+                    ErrorCode::NotFound,
+                    "the block or the transaction is not found",
+                )
+                .as_bytes()
             }
-            EXIT_COMMAND => {
-                return Ok(());
+            Ok(CommandResponse::Message(message)) => message,
+            Ok(CommandResponse::Progress(progress)) => {
+                let mut message = fill_command(PROGRESS_COMMAND).as_bytes().to_vec();
+                message.extend(progress.as_bytes());
+                message
             }
-            _ => {}
+            Ok(CommandResponse::Broadcasted) => fill_command(OK_COMMAND).as_bytes().to_vec(),
+            Ok(CommandResponse::Subscribed(subscribe)) => match state.get_notifier().lock() {
+                Ok(mut notifier) => {
+                    notifier.subscribe(subscriber_id, subscribe.get_addresses().clone());
+                    fill_command(OK_COMMAND).as_bytes().to_vec()
+                }
+                Err(_) => ErrorResponse::new(
+                    ErrorCode::Internal,
+                    &format!("{:?}", UpdateWalletError::LockNotifier),
+                )
+                .as_bytes(),
+            },
+            Err(UpdateWalletError::Read) => ErrorResponse::new(
+                ErrorCode::Malformed,
+                "the payload doesn't match the command",
+            )
+            .as_bytes(),
+            Err(err) => ErrorResponse::new(ErrorCode::Internal, &format!("{:?}", err)).as_bytes(),
+        };
+        let frame = WalletFrame::from_message(request_id, &response)
+            .map_err(|_| UpdateWalletError::Write)?;
+        sender.send(frame).map_err(|_| UpdateWalletError::Write)?;
+    }
+}
+
+/// Writes the frames sent to a wallet until the channel is closed or the connection fails.
+fn write_frames(mut writer: TcpStream, receiver: Receiver<WalletFrame>) {
+    for frame in receiver {
+        if writer.write_all(&frame.as_bytes()).is_err() {
+            return;
+        }
+    }
+}
+
+/// Runs a command of the wallet, reading its arguments from the stream.
+///
+/// # Arguments
+///
+/// * `command_name` - The command, without its padding.
+/// * `stream` - The stream with the arguments of the command, which is left after them.
+/// * `state` - The state of the node.
+///
+/// # Returns
+///
+/// The response to the command, or an `UpdateWalletError` if its arguments can't be read or it fails.
+fn run_command(
+    command_name: &str,
+    stream: &mut dyn Read,
+    state: &WalletState,
+) -> Result<CommandResponse, UpdateWalletError> {
+    match command_name {
+        GET_TX_COMMAND => {
+            let get_transactions = GetTransactions::from_bytes(command_name.to_string(), stream)
+                .map_err(|_| UpdateWalletError::Read)?;
+            let transactions: Transactions = get_wallet_txns(
+                state.get_blockchain(),
+                state.get_utxo(),
+                state.get_mempool(),
+                get_transactions,
+            )
+            .map_err(|_| UpdateWalletError::GetTxn)?;
+            Ok(CommandResponse::Message(transactions.as_bytes()))
+        }
+        GET_PROOF_COMMAND => {
+            let get_proof = GetProof::from_bytes(command_name.to_string(), stream)
+                .map_err(|_| UpdateWalletError::Read)?;
+            let mut message = vec![];
+            send_proof(
+                get_proof.get_block_header(),
+                get_proof.get_tx_id(),
+                state.get_blockchain(),
+                &mut message,
+            )
+            .map_err(|_| UpdateWalletError::SendProof)?;
+            Ok(CommandResponse::Message(message))
+        }
+        BROADCAST_TX_COMMAND => {
+            let broadcast_txn = BroadcastTxn::from_bytes(command_name.to_string(), stream)
+                .map_err(|_| UpdateWalletError::Read)?;
+            let tx_msg = Tx::new(
+                state.get_settings().get_start_string(),
+                broadcast_txn.get_txn(),
+            );
+
+            broadcast_new_txn(tx_msg, state.get_streams())
+                .map_err(|_| UpdateWalletError::BroadcastTx)?;
+            Ok(CommandResponse::Broadcasted)
+        }
+        GET_PROGRESS_COMMAND => {
+            let locked_blockchain = state
+                .get_blockchain()
+                .lock()
+                .map_err(|_| UpdateWalletError::LockBlockchain)?;
+
+            let act_blocks = locked_blockchain.get_cant_act_blocks() as u64;
+
+            Ok(CommandResponse::Progress(Progress::new(
+                act_blocks,
+                state.get_cant_total_blocks() as u64,
+            )))
+        }
+        SUBSCRIBE_COMMAND => {
+            let subscribe = Subscribe::from_bytes(command_name.to_string(), stream)
+                .map_err(|_| UpdateWalletError::Read)?;
+            Ok(CommandResponse::Subscribed(subscribe))
         }
+        EXIT_COMMAND => Ok(CommandResponse::Exit),
+        _ => Ok(CommandResponse::Unknown),
     }
 }
+
+/// Frames a message with its command and writes it to the wallet.
+fn write_frame(
+    wallet: &mut TcpStream,
+    request_id: u32,
+    message: &[u8],
+) -> Result<(), UpdateWalletError> {
+    let frame =
+        WalletFrame::from_message(request_id, message).map_err(|_| UpdateWalletError::Write)?;
+    wallet
+        .write_all(&frame.as_bytes())
+        .map_err(|_| UpdateWalletError::Write)
+}
@@ -9,4 +9,7 @@ pub enum UpdateWalletError {
     GetTxn,
     AcceptConnection,
     Progress,
+    UnknownCommand,
+    UnsupportedVersion,
+    LockNotifier,
 }
@@ -1,28 +1,60 @@
 use std::{
-    net::{TcpListener, TcpStream},
+    net::TcpListener,
     sync::{Arc, Mutex},
     thread::{self, JoinHandle},
+    time::Duration,
 };
 
-use crate::{
-    block_mod::{blockchain::BlockChain, mempool::Mempool, utxo::UnspentTx},
-    settings_mod::settings::Settings,
-};
+use super::{update_wallet::update_wallet, wallet_state::WalletState};
+
+/// A place among the wallets connected at the same time, given back when it is dropped.
+struct WalletSlot {
+    active_wallets: Arc<Mutex<usize>>,
+}
+
+impl WalletSlot {
+    /// Takes a place if fewer than `max_wallets` wallets are connected.
+    fn reserve(active_wallets: &Arc<Mutex<usize>>, max_wallets: usize) -> Option<WalletSlot> {
+        let mut locked_active_wallets = active_wallets.lock().ok()?;
+        if *locked_active_wallets >= max_wallets {
+            return None;
+        }
+        *locked_active_wallets += 1;
+
+        Some(WalletSlot {
+            active_wallets: active_wallets.clone(),
+        })
+    }
+}
+
+impl Drop for WalletSlot {
+    fn drop(&mut self) {
+        if let Ok(mut locked_active_wallets) = self.active_wallets.lock() {
+            *locked_active_wallets -= 1;
+        }
+    }
+}
+
+/// Accepts the connections of the wallets, serving each one in its own thread.
+///
+/// # Arguments
+///
+/// * `listener` - The listener bound to the wallet connection address.
+/// * `state` - The state of the node the wallets are served with.
+///
+/// # Returns
+///
+/// The handle of the thread that accepts the connections.
+///
+/// # Description
+///
+/// Up to `max_wallets` wallets are served at the same time, and further connections are closed as soon as they are
+/// accepted. A wallet that sends nothing for `wallet_timeout` seconds is disconnected, so it doesn't keep its place.
+pub fn wallet_connect(listener: TcpListener, state: WalletState) -> JoinHandle<()> {
+    let active_wallets = Arc::new(Mutex::new(0));
 
-use super::update_wallet::update_wallet;
-
-pub fn wallet_connect(
-    listener: TcpListener,
-    blockchain: Arc<Mutex<BlockChain>>,
-    utxo: Arc<Mutex<UnspentTx>>,
-    mempool: Arc<Mutex<Mempool>>,
-    settings: Arc<Settings>,
-    streams: &[Arc<Mutex<TcpStream>>],
-    cant_total_blocks: usize,
-) -> JoinHandle<()> {
-    let shared_streams = streams.to_owned();
     thread::spawn(move || loop {
-        let (wallet, _addr) = match listener.accept() {
+        let (wallet, addr) = match listener.accept() {
             Ok(conection) => conection,
             Err(err) => {
                 println!("{:?}", err);
@@ -30,16 +62,52 @@ pub fn wallet_connect(
             }
         };
 
-        if let Err(err) = update_wallet(
-            wallet,
-            &blockchain,
-            &utxo,
-            &mempool,
-            &settings,
-            &shared_streams,
-            cant_total_blocks,
-        ) {
-            println!("{:?}", err);
+        let slot =
+            match WalletSlot::reserve(&active_wallets, state.get_settings().get_max_wallets()) {
+                Some(slot) => slot,
+                None => {
+                    println!(
+                        "Wallet {} rejected, {} wallets are already connected",
+                        addr,
+                        state.get_settings().get_max_wallets()
+                    );
+                    continue;
+                }
+            };
+        let timeout = match state.get_settings().get_wallet_timeout() {
+            0 => None,
+            seconds => Some(Duration::from_secs(seconds)),
         };
+        if let Err(err) = wallet.set_read_timeout(timeout) {
+            println!("{:?}", err);
+            continue;
+        }
+
+        let state = state.clone();
+        thread::spawn(move || {
+            if let Err(err) = update_wallet(wallet, &state) {
+                println!("Wallet {}: {:?}", addr, err);
+            };
+            drop(slot);
+        });
     })
 }
+
+#[cfg(test)]
+mod wallet_connect_test {
+    use super::*;
+
+    #[test]
+    fn test_wallet_slots_are_limited() {
+        let active_wallets = Arc::new(Mutex::new(0));
+
+        let first = WalletSlot::reserve(&active_wallets, 2);
+        let second = WalletSlot::reserve(&active_wallets, 2);
+        assert!(first.is_some() && second.is_some());
+        assert!(WalletSlot::reserve(&active_wallets, 2).is_none());
+
+        drop(first);
+        assert!(WalletSlot::reserve(&active_wallets, 2).is_some());
+        assert_eq!(*active_wallets.lock().unwrap(), 1);
+    }
+}
@@ -0,0 +1,161 @@
+use std::io::Read;
+
+use crate::messages::{
+    message_error::MessageError,
+    read_from_bytes::{
+        fill_command, read_string_from_bytes, read_u32_from_bytes, read_vec_from_bytes,
+    },
+};
+
+use super::wallet_utils_constants::{COMMAND_NAME_SIZE, MAX_FRAME_LEN};
+
+/// A frame of the node–wallet protocol.
+///
+/// A frame starts with its length as a little endian u32, which counts the bytes after it: the id of the request as a
+/// little endian u32, the 12 bytes of the command, padded with zeros, and the payload. The payload of each command has
+/// the same layout as the message of the legacy protocol, so any message can be framed as it is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WalletFrame {
+    request_id: u32,
+    command_name: String,
+    payload: Vec<u8>,
+}
+
+impl WalletFrame {
+    /// Creates a frame from its command and its payload.
+    pub fn new(request_id: u32, command_name: &str, payload: Vec<u8>) -> WalletFrame {
+        WalletFrame {
+            request_id,
+            command_name: command_name.to_string(),
+            payload,
+        }
+    }
+
+    /// Frames a message of the legacy protocol, which starts with its 12 bytes command.
+    ///
+    /// # Arguments
+    ///
+    /// * `request_id` - The id of the request, or of the request it answers.
+    /// * `message` - The bytes of the message.
+    ///
+    /// # Returns
+    ///
+    /// The frame, or a `MessageError` if the message is shorter than a command.
+    pub fn from_message(request_id: u32, message: &[u8]) -> Result<WalletFrame, MessageError> {
+        let mut stream = message;
+        let command_name = read_string_from_bytes(&mut stream, COMMAND_NAME_SIZE)?;
+
+        Ok(WalletFrame {
+            request_id,
+            command_name,
+            payload: stream.to_vec(),
+        })
+    }
+
+    /// Reads a frame from a stream.
+    ///
+    /// # Returns
+    ///
+    /// The frame, or a `MessageError` if the stream can't be read or the length is shorter than the id and the command
+    /// or longer than `MAX_FRAME_LEN`, in which case the stream can't be trusted anymore.
+    pub fn from_bytes(stream: &mut dyn Read) -> Result<WalletFrame, MessageError> {
+        let len = read_u32_from_bytes(stream, true)? as usize;
+        if !(4 + COMMAND_NAME_SIZE..=MAX_FRAME_LEN).contains(&len) {
+            return Err(MessageError::InvalidInputWalletFrame);
+        }
+        let request_id = read_u32_from_bytes(stream, true)?;
+        let command_name = read_string_from_bytes(stream, COMMAND_NAME_SIZE)?;
+        let payload = read_vec_from_bytes(stream, len - 4 - COMMAND_NAME_SIZE)?;
+
+        Ok(WalletFrame {
+            request_id,
+            command_name,
+            payload,
+        })
+    }
+
+    pub fn as_bytes(&self) -> Vec<u8> {
+        let len = 4 + COMMAND_NAME_SIZE + self.payload.len();
+        let mut buffer = (len as u32).to_le_bytes().to_vec();
+        buffer.extend(self.request_id.to_le_bytes());
+        buffer.extend(fill_command(&self.command_name).as_bytes());
+        buffer.extend(&self.payload);
+        buffer
+    }
+
+    /// Parses the payload of the frame, which must be read completely.
+    ///
+    /// # Arguments
+    ///
+    /// * `parse` - The function that reads the payload, such as the `from_bytes` of a message.
+    ///
+    /// # Returns
+    ///
+    /// What the function returns, or `MessageError::InvalidInputWalletFrame` if it left bytes unread.
+    pub fn read_payload<T>(
+        &self,
+        parse: impl FnOnce(&mut dyn Read) -> Result<T, MessageError>,
+    ) -> Result<T, MessageError> {
+        let mut stream = &self.payload[..];
+        let value = parse(&mut stream)?;
+        match stream.is_empty() {
+            true => Ok(value),
+            false => Err(MessageError::InvalidInputWalletFrame),
+        }
+    }
+
+    pub fn get_request_id(&self) -> u32 {
+        self.request_id
+    }
+
+    pub fn get_command_name(&self) -> &str {
+        &self.command_name
+    }
+
+    pub fn get_payload(&self) -> &Vec<u8> {
+        &self.payload
+    }
+}
+
+#[cfg(test)]
+mod wallet_frame_test {
+    use super::*;
+    use crate::wallet_utils::{get_proof::GetProof, wallet_utils_constants::GET_PROOF_COMMAND};
+
+    #[test]
+    fn test_frame_round_trip() -> Result<(), MessageError> {
+        let message = GetProof::new(vec![1; 32], vec![2; 32]).as_bytes();
+        let frame = WalletFrame::from_message(7, &message)?;
+        let bytes = frame.as_bytes();
+
+        assert_eq!(bytes[..4], ((bytes.len() - 4) as u32).to_le_bytes());
+        assert_eq!(bytes[8..], message);
+        assert_eq!(WalletFrame::from_bytes(&mut &bytes[..])?, frame);
+        assert_eq!(frame.get_request_id(), 7);
+        assert_eq!(frame.get_command_name(), GET_PROOF_COMMAND);
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_payload_must_consume_it() -> Result<(), MessageError> {
+        let frame = WalletFrame::new(1, GET_PROOF_COMMAND, vec![1; 64]);
+        let longer = WalletFrame::new(1, GET_PROOF_COMMAND, vec![1; 65]);
+
+        let get_proof = frame
+            .read_payload(|stream| GetProof::from_bytes(GET_PROOF_COMMAND.to_string(), stream))?;
+        assert_eq!(get_proof.get_tx_id(), vec![1; 32]);
+        assert!(longer
+            .read_payload(|stream| GetProof::from_bytes(GET_PROOF_COMMAND.to_string(), stream))
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_lengths() {
+        let too_short = 4u32.to_le_bytes();
+        let too_long = (MAX_FRAME_LEN as u32 + 1).to_le_bytes();
+
+        assert!(WalletFrame::from_bytes(&mut &too_short[..]).is_err());
+        assert!(WalletFrame::from_bytes(&mut &too_long[..]).is_err());
+    }
+}
@@ -0,0 +1,210 @@
+use std::{collections::HashMap, sync::mpsc::Sender};
+
+use chrono::{Local, NaiveDateTime};
+
+use crate::block_mod::{block::Block, transaction::Transaction};
+
+use super::{
+    block_notification::BlockNotification,
+    tx_filter::is_wallet_transaction,
+    tx_filter_error::TxFilterError,
+    tx_notification::TxNotification,
+    wallet_frame::WalletFrame,
+    wallet_tx::WalletTx,
+    wallet_utils_constants::{
+        DATE_FORMAT, NULL_BLOCK_HASH, TRACKED_CONFIRMATIONS, UNSOLICITED_REQUEST_ID,
+    },
+};
+
+/// A wallet connection that can be pushed notifications.
+struct Subscriber {
+    sender: Sender<WalletFrame>,
+    addresses: Vec<(Vec<u8>, Vec<u8>)>,
+    tracked_txs: Vec<(Vec<u8>, usize)>,
+}
+
+impl Subscriber {
+    /// Sends a message to the wallet, returning `false` if its connection is closed.
+    fn push(&self, message: &[u8]) -> bool {
+        match WalletFrame::from_message(UNSOLICITED_REQUEST_ID, message) {
+            Ok(frame) => self.sender.send(frame).is_ok(),
+            Err(_) => true,
+        }
+    }
+
+    /// Pushes the transactions of the subscribed addresses, returning `false` if the connection is closed.
+    fn push_transaction(&self, transaction: &Transaction, block_hash: &[u8], date: &str) -> bool {
+        self.addresses
+            .iter()
+            .filter(|(pk_script, public_key)| {
+                is_wallet_transaction(transaction, pk_script, public_key)
+            })
+            .all(|(pk_script, _)| {
+                let wallet_tx =
+                    WalletTx::new(transaction.clone(), block_hash.to_vec(), date.to_string());
+                self.push(&TxNotification::new(pk_script.clone(), wallet_tx).as_bytes())
+            })
+    }
+}
+
+/// Pushes to the subscribed wallets the transactions of their addresses and the blocks the node connects.
+///
+/// Each framed wallet connection registers the channel its frames are written from, and a `Subscribe` sets the addresses
+/// it is notified of. The broadcasting calls `notify_transaction` for each transaction admitted to the mempool and
+/// `notify_block` for each connected block, so the wallets don't have to poll the node. Connections whose channel is
+/// closed are dropped the next time they are notified.
+#[derive(Default)]
+pub struct WalletNotifier {
+    subscribers: HashMap<u64, Subscriber>,
+    next_subscriber_id: u64,
+}
+
+impl WalletNotifier {
+    pub fn new() -> WalletNotifier {
+        WalletNotifier::default()
+    }
+
+    /// Registers a wallet connection, without addresses until it subscribes.
+    ///
+    /// # Returns
+    ///
+    /// The id of the connection, to subscribe and unregister it.
+    pub fn register(&mut self, sender: Sender<WalletFrame>) -> u64 {
+        let subscriber_id = self.next_subscriber_id;
+        self.next_subscriber_id += 1;
+        self.subscribers.insert(
+            subscriber_id,
+            Subscriber {
+                sender,
+                addresses: vec![],
+                tracked_txs: vec![],
+            },
+        );
+        subscriber_id
+    }
+
+    /// Replaces the addresses a connection is notified of. With no addresses it isn't notified at all.
+    pub fn subscribe(&mut self, subscriber_id: u64, addresses: Vec<(Vec<u8>, Vec<u8>)>) {
+        if let Some(subscriber) = self.subscribers.get_mut(&subscriber_id) {
+            subscriber.addresses = addresses;
+        }
+    }
+
+    pub fn unregister(&mut self, subscriber_id: u64) {
+        self.subscribers.remove(&subscriber_id);
+    }
+
+    /// Notifies a transaction admitted to the mempool to the wallets it belongs to.
+    pub fn notify_transaction(&mut self, transaction: &Transaction) {
+        let date = Local::now().naive_local().format(DATE_FORMAT).to_string();
+
+        self.subscribers.retain(|_, subscriber| {
+            subscriber.push_transaction(transaction, &NULL_BLOCK_HASH, &date)
+        });
+    }
+
+    /// Notifies a block connected to the chain to the subscribed wallets.
+    ///
+    /// # Arguments
+    ///
+    /// * `block` - The block.
+    /// * `height` - The height of the block.
+    ///
+    /// # Returns
+    ///
+    /// An empty result, or `TxFilterError::DateTimeError` if the time of the block is invalid.
+    ///
+    /// # Description
+    ///
+    /// Each wallet is pushed a `TxNotification` for each transaction of the block that belongs to it, and a
+    /// `BlockNotification` with the confirmations of its transactions confirmed in the last `TRACKED_CONFIRMATIONS` blocks.
+    pub fn notify_block(&mut self, block: &Block, height: usize) -> Result<(), TxFilterError> {
+        let block_hash = block.get_header().get_header();
+        let datetime = NaiveDateTime::from_timestamp_opt(block.get_header().get_time() as i64, 0)
+            .ok_or(TxFilterError::DateTimeError)?;
+        let date = datetime.format(DATE_FORMAT).to_string();
+
+        self.subscribers.retain(|_, subscriber| {
+            if subscriber.addresses.is_empty() {
+                return true;
+            }
+            for transaction in block.get_txn_list() {
+                if subscriber.addresses.iter().any(|(pk_script, public_key)| {
+                    is_wallet_transaction(transaction, pk_script, public_key)
+                }) {
+                    if !subscriber.push_transaction(transaction, &block_hash, &date) {
+                        return false;
+                    }
+                    subscriber.tracked_txs.push((transaction.get_id(), height));
+                }
+            }
+
+            let confirmations: Vec<(Vec<u8>, u32)> = subscriber
+                .tracked_txs
+                .iter()
+                .map(|(tx_id, tx_height)| {
+                    (
+                        tx_id.clone(),
+                        (height + 1).saturating_sub(*tx_height) as u32,
+                    )
+                })
+                .collect();
+            subscriber.tracked_txs.retain(|(_, tx_height)| {
+                ((height + 1).saturating_sub(*tx_height) as u32) < TRACKED_CONFIRMATIONS
+            });
+            subscriber.push(
+                &BlockNotification::new(block_hash.clone(), height as u32, confirmations)
+                    .as_bytes(),
+            )
+        });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod wallet_notifier_test {
+    use super::*;
+    use crate::{
+        block_mod::tx_out::TxOut, wallet_utils::wallet_utils_constants::TX_NOTIFICATION_COMMAND,
+    };
+    use std::sync::mpsc;
+
+    #[test]
+    fn test_only_subscribed_transactions_are_notified() {
+        let pk_script = vec![0x51];
+        let transaction = Transaction::new(2, vec![], vec![TxOut::new(1000, pk_script.clone())], 0);
+        let other = Transaction::new(2, vec![], vec![TxOut::new(1000, vec![0x52])], 0);
+        let (sender, receiver) = mpsc::channel();
+        let (unsubscribed_sender, unsubscribed_receiver) = mpsc::channel();
+
+        let mut notifier = WalletNotifier::new();
+        let subscriber_id = notifier.register(sender);
+        notifier.register(unsubscribed_sender);
+        notifier.subscribe(subscriber_id, vec![(pk_script.clone(), vec![])]);
+        notifier.notify_transaction(&other);
+        notifier.notify_transaction(&transaction);
+
+        let frames: Vec<WalletFrame> = receiver.try_iter().collect();
+        assert_eq!(frames.len(), 1);
+        assert_eq!(frames[0].get_command_name(), TX_NOTIFICATION_COMMAND);
+        assert_eq!(frames[0].get_request_id(), UNSOLICITED_REQUEST_ID);
+        assert!(unsubscribed_receiver.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_closed_connections_are_dropped() {
+        let (sender, receiver) = mpsc::channel();
+        let mut notifier = WalletNotifier::new();
+        let subscriber_id = notifier.register(sender);
+        notifier.subscribe(subscriber_id, vec![(vec![0x51], vec![])]);
+        drop(receiver);
+
+        notifier.notify_transaction(&Transaction::new(
+            2,
+            vec![],
+            vec![TxOut::new(1000, vec![0x51])],
+            0,
+        ));
+        assert!(notifier.subscribers.is_empty());
+    }
+}
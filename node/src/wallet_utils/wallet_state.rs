@@ -0,0 +1,84 @@
+use std::{
+    net::TcpStream,
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    block_mod::{blockchain::BlockChain, mempool::Mempool, utxo::UnspentTx},
+    settings_mod::settings::Settings,
+};
+
+use super::wallet_notifier::WalletNotifier;
+
+/// The state of the node the wallet connections are served with, shared by all of them.
+#[derive(Clone)]
+pub struct WalletState {
+    blockchain: Arc<Mutex<BlockChain>>,
+    utxo: Arc<Mutex<UnspentTx>>,
+    mempool: Arc<Mutex<Mempool>>,
+    settings: Arc<Settings>,
+    streams: Vec<Arc<Mutex<TcpStream>>>,
+    notifier: Arc<Mutex<WalletNotifier>>,
+    cant_total_blocks: usize,
+}
+
+impl WalletState {
+    /// Creates the state of the wallet connections.
+    ///
+    /// # Arguments
+    ///
+    /// * `blockchain` - The downloaded blocks.
+    /// * `utxo` - The unspent outputs.
+    /// * `mempool` - The unconfirmed transactions.
+    /// * `settings` - The settings of the node, with the limits of the wallet connections.
+    /// * `streams` - The connections to other nodes, to broadcast the transactions of the wallets.
+    /// * `notifier` - The notifier the wallets subscribe to.
+    /// * `cant_total_blocks` - The number of blocks to download, to answer the progress of the download.
+    pub fn new(
+        blockchain: Arc<Mutex<BlockChain>>,
+        utxo: Arc<Mutex<UnspentTx>>,
+        mempool: Arc<Mutex<Mempool>>,
+        settings: Arc<Settings>,
+        streams: Vec<Arc<Mutex<TcpStream>>>,
+        notifier: Arc<Mutex<WalletNotifier>>,
+        cant_total_blocks: usize,
+    ) -> WalletState {
+        WalletState {
+            blockchain,
+            utxo,
+            mempool,
+            settings,
+            streams,
+            notifier,
+            cant_total_blocks,
+        }
+    }
+
+    pub fn get_blockchain(&self) -> &Arc<Mutex<BlockChain>> {
+        &self.blockchain
+    }
+
+    pub fn get_utxo(&self) -> &Arc<Mutex<UnspentTx>> {
+        &self.utxo
+    }
+
+    pub fn get_mempool(&self) -> &Arc<Mutex<Mempool>> {
+        &self.mempool
+    }
+
+    pub fn get_settings(&self) -> &Arc<Settings> {
+        &self.settings
+    }
+
+    pub fn get_streams(&self) -> &Vec<Arc<Mutex<TcpStream>>> {
+        &self.streams
+    }
+
+    pub fn get_notifier(&self) -> &Arc<Mutex<WalletNotifier>> {
+        &self.notifier
+    }
+
+    pub fn get_cant_total_blocks(&self) -> usize {
+        self.cant_total_blocks
+    }
+}
@@ -1,6 +1,9 @@
 use crate::{
     block_mod::transaction::Transaction,
-    messages::{message_error::MessageError, read_from_bytes::read_string_from_bytes},
+    messages::{
+        message_error::MessageError,
+        read_from_bytes::{read_string_from_bytes, read_vec_from_bytes},
+    },
 };
 use std::io::Read;
 
@@ -8,6 +11,7 @@ use std::io::Read;
 #[derive(Clone, Debug, PartialEq)]
 pub struct WalletTx {
     transaction: Transaction,
+    block_hash: Vec<u8>,
     date: String,
 }
 
@@ -17,13 +21,18 @@ impl WalletTx {
     /// # Arguments
     ///
     /// * `transaction`: A `Transaction` object representing the underlying transaction.
+    /// * `block_hash`: The hash of the block that confirms the transaction, all zeros if it is unconfirmed.
     /// * `date`: A `String` representing the date of the transaction.
     ///
     /// # Returns
     ///
     /// A `WalletTx` object initialized with the provided transaction and date.
-    pub fn new(transaction: Transaction, date: String) -> WalletTx {
-        WalletTx { transaction, date }
+    pub fn new(transaction: Transaction, block_hash: Vec<u8>, date: String) -> WalletTx {
+        WalletTx {
+            transaction,
+            block_hash,
+            date,
+        }
     }
 
     /// Creates a `WalletTx` object by deserializing it from a byte stream.
@@ -41,9 +50,14 @@ impl WalletTx {
     /// The function can return a `MessageError` if there is an error during deserialization.
     pub fn from_bytes(stream: &mut dyn Read) -> Result<WalletTx, MessageError> {
         let transaction = Transaction::from_bytes(stream)?;
+        let block_hash = read_vec_from_bytes(stream, 32)?;
         let date = read_string_from_bytes(stream, 17)?;
 
-        Ok(WalletTx { transaction, date })
+        Ok(WalletTx {
+            transaction,
+            block_hash,
+            date,
+        })
     }
 
     /// Serializes the `WalletTx` object into a byte vector.
@@ -55,6 +69,7 @@ impl WalletTx {
         let mut buffer = Vec::new();
 
         buffer.extend(&self.transaction.as_bytes());
+        buffer.extend(&self.block_hash);
         buffer.extend(self.date.as_bytes());
 
         buffer
@@ -64,6 +79,10 @@ impl WalletTx {
         &self.transaction
     }
 
+    pub fn get_block_hash(&self) -> &Vec<u8> {
+        &self.block_hash
+    }
+
     pub fn get_date(&self) -> &String {
         &self.date
     }
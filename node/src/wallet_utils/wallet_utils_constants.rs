@@ -5,5 +5,26 @@ pub const MERKLE_BLOCK_COMMAND: &str = "merkleblock";
 pub const TRANSACTIONS_COMMAND: &str = "transactions";
 pub const GET_PROGRESS_COMMAND: &str = "get_progress";
 pub const EXIT_COMMAND: &str = "exit";
+pub const HELLO_COMMAND: &str = "hello";
+pub const PROGRESS_COMMAND: &str = "progress";
+pub const OK_COMMAND: &str = "ok";
+pub const ERROR_COMMAND: &str = "error";
+pub const SUBSCRIBE_COMMAND: &str = "subscribe";
+pub const TX_NOTIFICATION_COMMAND: &str = "tx_notify";
+pub const BLOCK_NOTIFICATION_COMMAND: &str = "block_notify";
+
+pub const COMMAND_NAME_SIZE: usize = 12;
+/// The version of the framed protocol, the legacy protocol without frames being version 1.
+pub const WALLET_PROTOCOL_VERSION: u32 = 2;
+pub const MIN_WALLET_PROTOCOL_VERSION: u32 = 2;
+/// The request id of the frames the node sends without being asked, such as the answer to the handshake.
+pub const UNSOLICITED_REQUEST_ID: u32 = 0;
+pub const MAX_FRAME_LEN: usize = 32_000_000;
+/// The addresses a wallet connection can subscribe to.
+pub const MAX_SUBSCRIBED_ADDRESSES: usize = 10_000;
+/// The confirmations after which the node stops pushing the confirmations of a transaction.
+pub const TRACKED_CONFIRMATIONS: u32 = 6;
+
+pub const NULL_BLOCK_HASH: [u8; 32] = [0; 32];
 
 pub const DATE_FORMAT: &str = "%Y-%m-%d  %H:%M";
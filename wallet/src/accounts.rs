@@ -1,28 +1,141 @@
+use crate::hd_wallet::{
+    bip39::seed_from_mnemonic, derivation_path::AddressPurpose, hd_wallet_error::HdWalletError,
+};
+use crate::keystore::{
+    encrypted_keystore::Keystore,
+    keystore_constants::{MAINNET_KEYSTORE_PATH, TESTNET_KEYSTORE_PATH},
+    keystore_entry::{AccountSecret, KeystoreEntry, WatchedKey},
+    keystore_error::KeystoreError,
+};
+use crate::multisig::multisig_script::{MultisigKind, MultisigScript};
+use crate::tx_cache::{
+    tx_cache_constants::{MAINNET_TX_CACHE_PATH, TESTNET_TX_CACHE_PATH},
+    tx_cache_error::TxCacheError,
+    tx_cache_file::TxCache,
+};
 use crate::user_info::UserInfo;
+use node::network::bitcoin_network::BitcoinNetwork;
 use node::wallet_utils::transactions::Transactions;
 use std::collections::HashMap;
+use std::path::Path;
 
 #[derive(Debug)]
 /// Represents a collection of user accounts and tracks the currently active account.
+///
+/// The secrets of the accounts are saved in an encrypted keystore, one for each network, and their transactions in a
+/// transaction cache, so the wallet resumes syncing from the last block it saw.
 pub struct Accounts {
     accounts: HashMap<String, UserInfo>,
     actual_username: String,
+    network: BitcoinNetwork,
+    keystore: Keystore,
+    tx_cache: TxCache,
 }
 
 impl Accounts {
-    pub fn new() -> Accounts {
+    pub fn new(network: BitcoinNetwork) -> Accounts {
+        let (keystore_path, tx_cache_path) = match network {
+            BitcoinNetwork::Mainnet => (MAINNET_KEYSTORE_PATH, MAINNET_TX_CACHE_PATH),
+            BitcoinNetwork::Testnet => (TESTNET_KEYSTORE_PATH, TESTNET_TX_CACHE_PATH),
+        };
+        let tx_cache = TxCache::open(Path::new(tx_cache_path)).unwrap_or_else(|err| {
+            println!("{}", err);
+            TxCache::new(Path::new(tx_cache_path))
+        });
+
         Accounts {
             accounts: HashMap::new(),
             actual_username: String::default(),
+            network,
+            keystore: Keystore::new(Path::new(keystore_path)),
+            tx_cache,
+        }
+    }
+
+    /// Adds an account, restoring its history from the transaction cache if it was saved.
+    fn insert_account(&mut self, user_name: String, mut user_info: UserInfo) {
+        if let Some(history) = self.tx_cache.get_history(&user_name) {
+            if let Err(err) = user_info.restore_history(&mut &history[..]) {
+                println!("{}", err);
+            }
         }
+        self.accounts.insert(user_name, user_info);
     }
 
     pub fn add_account(&mut self, user_name: String, public_key: Vec<u8>, private_key: Vec<u8>) {
-        self.accounts
-            .insert(user_name.clone(), UserInfo::new(public_key, private_key));
+        self.insert_account(user_name.clone(), UserInfo::new(public_key, private_key));
+        self.actual_username = user_name;
+    }
+
+    /// Adds an HD account restored from a BIP39 mnemonic and makes it the active one.
+    ///
+    /// # Arguments
+    ///
+    /// * `user_name` - The name of the account.
+    /// * `mnemonic` - The recovery phrase of the wallet.
+    /// * `purpose` - Whether the account uses BIP44 (P2PKH) or BIP84 (P2WPKH) addresses.
+    ///
+    /// # Returns
+    ///
+    /// An `HdWalletError` if the mnemonic is invalid, in which case no account is added.
+    pub fn add_hd_account(
+        &mut self,
+        user_name: String,
+        mnemonic: &str,
+        purpose: AddressPurpose,
+    ) -> Result<(), HdWalletError> {
+        let seed = seed_from_mnemonic(mnemonic, "")?;
+        let user_info = UserInfo::from_seed(&seed, purpose, self.network)?;
+
+        self.insert_account(user_name.clone(), user_info);
+        self.actual_username = user_name;
+        Ok(())
+    }
+
+    /// Adds an m-of-n multisig account and makes it the active one.
+    ///
+    /// # Arguments
+    ///
+    /// * `user_name` - The name of the account.
+    /// * `multisig` - The script with the public keys of every cosigner.
+    /// * `kind` - Whether the account receives to a P2SH or a P2WSH address.
+    /// * `private_keys` - The keys of the script held by this wallet, which may be none of them.
+    pub fn add_multisig_account(
+        &mut self,
+        user_name: String,
+        multisig: MultisigScript,
+        kind: MultisigKind,
+        private_keys: Vec<Vec<u8>>,
+    ) {
+        self.insert_account(
+            user_name.clone(),
+            UserInfo::from_multisig(multisig, kind, private_keys),
+        );
         self.actual_username = user_name;
     }
 
+    /// Adds a watch-only account and makes it the active one.
+    ///
+    /// # Arguments
+    ///
+    /// * `user_name` - The name of the account.
+    /// * `watched` - The address, public key or BIP32 account public key the account follows.
+    ///
+    /// # Returns
+    ///
+    /// An `HdWalletError` if the addresses of an account public key can't be derived, in which case no account is added.
+    pub fn add_watch_only_account(
+        &mut self,
+        user_name: String,
+        watched: WatchedKey,
+    ) -> Result<(), HdWalletError> {
+        let user_info = UserInfo::from_secret(AccountSecret::WatchOnly(watched))?;
+
+        self.insert_account(user_name.clone(), user_info);
+        self.actual_username = user_name;
+        Ok(())
+    }
+
     pub fn is_empty(&self) -> bool {
         self.accounts.is_empty()
     }
@@ -31,12 +144,23 @@ impl Accounts {
         self.accounts.get(&self.actual_username)
     }
 
-    pub fn update(&mut self, transactions: &Transactions) {
+    pub fn update(&mut self, pk_script: &[u8], transactions: &Transactions) {
+        if let Some(user_info) = self.accounts.get_mut(&self.actual_username) {
+            user_info.update(pk_script, transactions);
+        }
+    }
+
+    /// Reserves a change address of the active account after a transaction that sends change to it is broadcast.
+    pub fn reserve_change(&mut self, change_pk_script: &[u8]) {
         if let Some(user_info) = self.accounts.get_mut(&self.actual_username) {
-            user_info.update(transactions);
+            user_info.reserve_change(change_pk_script);
         }
     }
 
+    pub fn get_actual_username(&self) -> &String {
+        &self.actual_username
+    }
+
     pub fn get_accounts_count(&self) -> usize {
         self.accounts.iter().len()
     }
@@ -46,10 +170,112 @@ impl Accounts {
             self.actual_username = active_account;
         }
     }
+
+    pub fn get_network(&self) -> BitcoinNetwork {
+        self.network
+    }
+
+    /// Returns whether there is a keystore file with saved accounts to unlock.
+    pub fn has_keystore(&self) -> bool {
+        self.keystore.exists()
+    }
+
+    pub fn is_keystore_unlocked(&self) -> bool {
+        self.keystore.is_unlocked()
+    }
+
+    /// Prepares a new keystore encrypted with the given passphrase, written when the accounts are saved.
+    pub fn create_keystore(&mut self, passphrase: &str) -> Result<(), KeystoreError> {
+        self.keystore.create(passphrase)
+    }
+
+    /// Encrypts the secrets of all the accounts and writes them to the keystore.
+    ///
+    /// # Returns
+    ///
+    /// A Result with `KeystoreError::Locked` if the wallet is locked, or another `KeystoreError` if the file can't be written.
+    pub fn save(&self) -> Result<(), KeystoreError> {
+        let mut entries = self
+            .accounts
+            .iter()
+            .map(|(name, user_info)| {
+                let secret = user_info.get_secret().ok_or(KeystoreError::Locked)?;
+                Ok(KeystoreEntry::new(name.clone(), secret.clone()))
+            })
+            .collect::<Result<Vec<KeystoreEntry>, KeystoreError>>()?;
+        entries.sort_by(|a, b| a.get_name().cmp(b.get_name()));
+
+        self.keystore.save(&entries)
+    }
+
+    /// Unlocks the keystore, restoring the secrets of the accounts.
+    ///
+    /// # Arguments
+    ///
+    /// * `passphrase` - The passphrase of the keystore.
+    ///
+    /// # Returns
+    ///
+    /// A Result containing the names of the accounts that weren't loaded yet, which is all of them when the wallet starts,
+    /// or a `KeystoreError` if the passphrase is wrong or the keystore can't be read.
+    ///
+    /// # Description
+    ///
+    /// Accounts that were locked after some inactivity get their secret back, keeping their addresses and transactions. The
+    /// other accounts are created from their secret, and the first of them becomes the active one if there was none.
+    pub fn unlock(&mut self, passphrase: &str) -> Result<Vec<String>, KeystoreError> {
+        let mut new_accounts = vec![];
+
+        for entry in self.keystore.unlock(passphrase)? {
+            let name = entry.get_name().clone();
+            match self.accounts.get_mut(&name) {
+                Some(user_info) => user_info.unlock(entry.get_secret().clone()),
+                None => {
+                    let user_info = UserInfo::from_secret(entry.get_secret().clone())
+                        .map_err(|_| KeystoreError::InvalidFormat)?;
+                    self.insert_account(name.clone(), user_info);
+                    new_accounts.push(name);
+                }
+            }
+        }
+        if !self.accounts.contains_key(&self.actual_username) {
+            if let Some(name) = new_accounts.first() {
+                self.actual_username = name.clone();
+            }
+        }
+        Ok(new_accounts)
+    }
+
+    /// Writes the history of the active account to the transaction cache.
+    ///
+    /// # Returns
+    ///
+    /// A Result with `TxCacheError::Io` if the file can't be written.
+    pub fn save_history(&mut self) -> Result<(), TxCacheError> {
+        if let Some(user_info) = self.accounts.get(&self.actual_username) {
+            self.tx_cache
+                .set_history(self.actual_username.clone(), user_info.history_as_bytes());
+        }
+        self.tx_cache.save()
+    }
+
+    /// Locks the wallet, forgetting the secrets of every account and the key of the keystore.
+    pub fn lock(&mut self) {
+        for user_info in self.accounts.values_mut() {
+            user_info.lock();
+        }
+        self.keystore.lock();
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.accounts
+            .values()
+            .any(|user_info| user_info.is_locked())
+    }
 }
 
 impl Default for Accounts {
     fn default() -> Self {
-        Self::new()
+        Self::new(BitcoinNetwork::default())
     }
 }
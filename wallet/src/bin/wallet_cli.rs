@@ -0,0 +1,17 @@
+use std::process::exit;
+
+use wallet::cli::{cli_command::parse_args, cli_output::format_error, run_cli::run_cli};
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let json = args.iter().any(|arg| arg == "--json");
+
+    let result = parse_args(&args).and_then(|(options, command)| run_cli(&options, command));
+    match result {
+        Ok(output) => println!("{}", output),
+        Err(err) => {
+            eprintln!("{}", format_error(&err, json));
+            exit(1);
+        }
+    }
+}
@@ -0,0 +1,5 @@
+pub mod cli_command;
+pub mod cli_constants;
+pub mod cli_error;
+pub mod cli_output;
+pub mod run_cli;
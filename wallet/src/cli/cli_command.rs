@@ -0,0 +1,270 @@
+use std::str::FromStr;
+
+use node::{
+    messages::read_from_bytes::decode_hex, network::bitcoin_network::BitcoinNetwork,
+    wallet_utils::wallet_utils_constants::NULL_BLOCK_HASH,
+};
+
+use super::{cli_constants::*, cli_error::CliError};
+
+#[derive(Debug, PartialEq, Eq)]
+/// The options given before the command, shared by all of them.
+pub struct CliOptions {
+    pub network: BitcoinNetwork,
+    pub account: Option<String>,
+    pub json: bool,
+    pub yes: bool,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+/// A command of the command line wallet, with its arguments already validated.
+pub enum CliCommand {
+    Balance,
+    History,
+    Receive,
+    Send {
+        targets: Vec<(Vec<u8>, i64)>,
+        fee_rate: i64,
+    },
+    Proof {
+        block_hash: Vec<u8>,
+        tx_id: Vec<u8>,
+    },
+    Help,
+}
+
+/// Parses the arguments of the command line wallet.
+///
+/// # Arguments
+///
+/// * `args` - The arguments, without the name of the program.
+///
+/// # Returns
+///
+/// The options and the command, or a `CliError` describing the first invalid argument. No arguments at all, or the
+/// `help` command, return `CliCommand::Help`.
+///
+/// # Description
+///
+/// The options go before the command: `--network` followed by `mainnet` or `testnet`, `--account` followed by the name of
+/// an account, `--json` to print the output as JSON and `--yes` to send without asking for confirmation. The `send`
+/// command takes an optional `--fee-rate` in satoshis per virtual byte followed by pairs of address and amount in BTC,
+/// and the `proof` command takes the hash of a block and the id of a transaction in hex, as shown by `history`. Without
+/// the hash of the block, the node finds it in its transaction index.
+pub fn parse_args(args: &[String]) -> Result<(CliOptions, CliCommand), CliError> {
+    let mut options = CliOptions {
+        network: BitcoinNetwork::default(),
+        account: None,
+        json: false,
+        yes: false,
+    };
+    let mut args = args.iter();
+
+    let command = loop {
+        match args.next().map(String::as_str) {
+            Some(NETWORK_FLAG) => {
+                let network = next_value(&mut args, NETWORK_FLAG)?;
+                options.network = BitcoinNetwork::from_str(network)
+                    .map_err(|_| CliError::InvalidNetwork(network.to_string()))?;
+            }
+            Some(ACCOUNT_FLAG) => {
+                options.account = Some(next_value(&mut args, ACCOUNT_FLAG)?.to_string())
+            }
+            Some(JSON_FLAG) => options.json = true,
+            Some(YES_FLAG) => options.yes = true,
+            Some(command) => break command,
+            None => return Ok((options, CliCommand::Help)),
+        }
+    };
+    let rest: Vec<&String> = args.collect();
+
+    let command = match (command, rest.as_slice()) {
+        (BALANCE_COMMAND, []) => CliCommand::Balance,
+        (HISTORY_COMMAND, []) => CliCommand::History,
+        (RECEIVE_COMMAND, []) => CliCommand::Receive,
+        (HELP_COMMAND, []) => CliCommand::Help,
+        (SEND_COMMAND, rest) => parse_send(rest)?,
+        (PROOF_COMMAND, [block_hash, tx_id]) => CliCommand::Proof {
+            block_hash: parse_hash(block_hash)?,
+            tx_id: parse_hash(tx_id)?,
+        },
+        (PROOF_COMMAND, [tx_id]) => CliCommand::Proof {
+            block_hash: NULL_BLOCK_HASH.to_vec(),
+            tx_id: parse_hash(tx_id)?,
+        },
+        (BALANCE_COMMAND | HISTORY_COMMAND | RECEIVE_COMMAND | HELP_COMMAND | PROOF_COMMAND, _) => {
+            return Err(CliError::Usage(format!(
+                "wrong arguments for the {} command",
+                command
+            )))
+        }
+        (command, _) => return Err(CliError::Usage(format!("unknown command {}", command))),
+    };
+    Ok((options, command))
+}
+
+/// Returns the value that follows a flag.
+fn next_value<'a>(
+    args: &mut dyn Iterator<Item = &'a String>,
+    flag: &str,
+) -> Result<&'a str, CliError> {
+    args.next()
+        .map(String::as_str)
+        .ok_or_else(|| CliError::Usage(format!("{} needs a value", flag)))
+}
+
+/// Parses the arguments of the `send` command: an optional fee rate followed by pairs of address and amount.
+fn parse_send(args: &[&String]) -> Result<CliCommand, CliError> {
+    let (fee_rate, targets) = match args {
+        [flag, fee_rate, targets @ ..] if flag.as_str() == FEE_RATE_FLAG => {
+            let fee_rate = fee_rate
+                .parse::<i64>()
+                .ok()
+                .filter(|fee_rate| *fee_rate > 0)
+                .ok_or_else(|| {
+                    CliError::Usage(format!(
+                        "the fee rate {} isn't a positive integer",
+                        fee_rate
+                    ))
+                })?;
+            (fee_rate, targets)
+        }
+        targets => (DEFAULT_FEE_RATE, targets),
+    };
+    if targets.is_empty() || targets.len() % 2 != 0 {
+        return Err(CliError::Usage(
+            "send needs pairs of address and amount".to_string(),
+        ));
+    }
+
+    let targets = targets
+        .chunks(2)
+        .map(|target| Ok((target[0].as_bytes().to_vec(), parse_amount(target[1])?)))
+        .collect::<Result<Vec<(Vec<u8>, i64)>, CliError>>()?;
+    Ok(CliCommand::Send { targets, fee_rate })
+}
+
+/// Parses an amount of BTC into satoshis, without the rounding errors of a floating point value.
+///
+/// # Arguments
+///
+/// * `amount` - The amount in BTC, with up to 8 decimals, such as `0.0015`.
+///
+/// # Returns
+///
+/// The amount in satoshis, or `CliError::InvalidAmount` if it isn't a positive amount with up to 8 decimals.
+pub fn parse_amount(amount: &str) -> Result<i64, CliError> {
+    let invalid = || CliError::InvalidAmount(amount.to_string());
+    let (integer, decimals) = amount.split_once('.').unwrap_or((amount, ""));
+    let all_digits = |part: &str| part.chars().all(|c| c.is_ascii_digit());
+
+    if (integer.is_empty() && decimals.is_empty())
+        || decimals.len() > BTC_DECIMALS
+        || !all_digits(integer)
+        || !all_digits(decimals)
+    {
+        return Err(invalid());
+    }
+    let integer: i64 = match integer {
+        "" => 0,
+        integer => integer.parse().map_err(|_| invalid())?,
+    };
+    let decimals: i64 = match decimals {
+        "" => 0,
+        decimals => format!("{:0<width$}", decimals, width = BTC_DECIMALS)
+            .parse()
+            .map_err(|_| invalid())?,
+    };
+
+    integer
+        .checked_mul(SATOSHIS_PER_BTC)
+        .and_then(|satoshis| satoshis.checked_add(decimals))
+        .filter(|satoshis| *satoshis > 0)
+        .ok_or_else(invalid)
+}
+
+/// Parses a block hash or a transaction id of 64 hex digits.
+fn parse_hash(hash: &str) -> Result<Vec<u8>, CliError> {
+    if hash.len() != HASH_HEX_LEN || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(CliError::InvalidHash(hash.to_string()));
+    }
+    decode_hex(hash).map_err(|_| CliError::InvalidHash(hash.to_string()))
+}
+
+#[cfg(test)]
+mod cli_command_test {
+    use super::*;
+
+    fn args(args: &str) -> Vec<String> {
+        args.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_parse_amount() {
+        assert_eq!(parse_amount("1").ok(), Some(100_000_000));
+        assert_eq!(parse_amount("0.0015").ok(), Some(150_000));
+        assert_eq!(parse_amount(".00000001").ok(), Some(1));
+        assert_eq!(parse_amount("21.12345678").ok(), Some(2_112_345_678));
+        assert_eq!(parse_amount("0.1").ok(), Some(10_000_000));
+
+        for amount in ["", ".", "0", "0.000000001", "-1", "1e3", "1.2.3", "one"] {
+            assert!(parse_amount(amount).is_err(), "{}", amount);
+        }
+    }
+
+    #[test]
+    fn test_parse_options_and_command() -> Result<(), CliError> {
+        let (options, command) =
+            parse_args(&args("--network mainnet --account savings --json balance"))?;
+
+        assert_eq!(options.network, BitcoinNetwork::Mainnet);
+        assert_eq!(options.account, Some("savings".to_string()));
+        assert!(options.json);
+        assert!(!options.yes);
+        assert_eq!(command, CliCommand::Balance);
+        assert_eq!(parse_args(&[])?.1, CliCommand::Help);
+        assert_eq!(
+            parse_args(&args(&format!("proof {}", "ab".repeat(32))))?.1,
+            CliCommand::Proof {
+                block_hash: NULL_BLOCK_HASH.to_vec(),
+                tx_id: vec![0xab; 32],
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_send() -> Result<(), CliError> {
+        let (options, command) = parse_args(&args(
+            "--yes send --fee-rate 5 mp3PDnKDtxPYrPKcYLGX1pXMe6KwAsfquD 0.01 n1mDu5Zd5qS75vqK1yqnKmEZQzDyncQqj4 2",
+        ))?;
+
+        assert!(options.yes);
+        assert_eq!(
+            command,
+            CliCommand::Send {
+                targets: vec![
+                    (b"mp3PDnKDtxPYrPKcYLGX1pXMe6KwAsfquD".to_vec(), 1_000_000),
+                    (b"n1mDu5Zd5qS75vqK1yqnKmEZQzDyncQqj4".to_vec(), 200_000_000),
+                ],
+                fee_rate: 5,
+            }
+        );
+        assert!(parse_args(&args("send mp3PDnKDtxPYrPKcYLGX1pXMe6KwAsfquD")).is_err());
+        assert!(parse_args(&args(
+            "send --fee-rate 0 mp3PDnKDtxPYrPKcYLGX1pXMe6KwAsfquD 1"
+        ))
+        .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_invalid_arguments() {
+        assert!(parse_args(&args("--network regtest balance")).is_err());
+        assert!(parse_args(&args("--account")).is_err());
+        assert!(parse_args(&args("balance now")).is_err());
+        assert!(parse_args(&args("mine")).is_err());
+        assert!(parse_args(&args("proof 00ff 00ff")).is_err());
+        assert!(parse_args(&args("proof")).is_err());
+    }
+}
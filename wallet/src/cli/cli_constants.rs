@@ -0,0 +1,32 @@
+pub const PASSPHRASE_ENV: &str = "WALLET_PASSPHRASE";
+pub const DEFAULT_FEE_RATE: i64 = 1;
+pub const BTC_DECIMALS: usize = 8;
+pub const SATOSHIS_PER_BTC: i64 = 100_000_000;
+pub const HASH_HEX_LEN: usize = 64;
+
+pub const NETWORK_FLAG: &str = "--network";
+pub const ACCOUNT_FLAG: &str = "--account";
+pub const JSON_FLAG: &str = "--json";
+pub const YES_FLAG: &str = "--yes";
+pub const FEE_RATE_FLAG: &str = "--fee-rate";
+
+pub const BALANCE_COMMAND: &str = "balance";
+pub const HISTORY_COMMAND: &str = "history";
+pub const RECEIVE_COMMAND: &str = "receive";
+pub const SEND_COMMAND: &str = "send";
+pub const PROOF_COMMAND: &str = "proof";
+pub const HELP_COMMAND: &str = "help";
+
+pub const USAGE: &str = "Usage: wallet-cli [--network mainnet|testnet] [--account NAME] [--json] [--yes] <command>
+
+Commands:
+  balance                                        Available, pending and total balance of the account
+  history                                        Transactions of the account, newest first
+  receive                                        Address to receive payments to
+  send [--fee-rate SAT/VB] <address> <btc>...    Sends to one or more addresses and prints the transaction id
+  proof [<block_hash>] <tx_id>                   Checks that a transaction is included in a block, found by the
+                                                 transaction index of the node if no block is given
+  help                                           Shows this message
+
+The passphrase of the keystore is read from the WALLET_PASSPHRASE environment variable, or from the standard input.
+A transaction is only sent after it is confirmed on the standard input, unless --yes is given.";
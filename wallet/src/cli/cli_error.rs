@@ -0,0 +1,100 @@
+use crate::{
+    interface_error::InterfaceError, keystore::keystore_error::KeystoreError,
+    transactions::create_transaction_error::TransactionCreateError,
+    tx_cache::tx_cache_error::TxCacheError,
+};
+
+#[derive(Debug)]
+pub enum CliError {
+    Usage(String),
+    InvalidAmount(String),
+    InvalidHash(String),
+    InvalidNetwork(String),
+    NoKeystore,
+    Keystore(KeystoreError),
+    UnknownAccount(String),
+    NoAccount,
+    NodeConnection,
+    Node(InterfaceError),
+    TxCache(TxCacheError),
+    Transaction(TransactionCreateError),
+    Cancelled,
+    Io,
+}
+
+impl From<KeystoreError> for CliError {
+    fn from(err: KeystoreError) -> CliError {
+        CliError::Keystore(err)
+    }
+}
+
+impl From<InterfaceError> for CliError {
+    fn from(err: InterfaceError) -> CliError {
+        CliError::Node(err)
+    }
+}
+
+impl From<TxCacheError> for CliError {
+    fn from(err: TxCacheError) -> CliError {
+        CliError::TxCache(err)
+    }
+}
+
+impl From<TransactionCreateError> for CliError {
+    fn from(err: TransactionCreateError) -> CliError {
+        CliError::Transaction(err)
+    }
+}
+
+impl From<std::io::Error> for CliError {
+    fn from(_: std::io::Error) -> CliError {
+        CliError::Io
+    }
+}
+
+impl From<std::fmt::Error> for CliError {
+    fn from(_: std::fmt::Error) -> CliError {
+        CliError::Io
+    }
+}
+
+impl std::fmt::Display for CliError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            CliError::Usage(message) => write!(f, "{}, see wallet-cli help", message),
+            CliError::InvalidAmount(amount) => {
+                write!(f, "\"{}\" isn't an amount of BTC with up to 8 decimals", amount)
+            }
+            CliError::InvalidHash(hash) => write!(f, "\"{}\" isn't a hash of 64 hex digits", hash),
+            CliError::InvalidNetwork(network) => {
+                write!(f, "unknown network {}, use mainnet or testnet", network)
+            }
+            CliError::NoKeystore => {
+                write!(f, "there is no keystore, log in with the wallet interface first")
+            }
+            CliError::Keystore(err) => write!(f, "{}", err),
+            CliError::UnknownAccount(name) => write!(f, "there is no account named {}", name),
+            CliError::NoAccount => write!(f, "the keystore has no accounts"),
+            CliError::NodeConnection => write!(f, "can't connect to the node"),
+            CliError::Node(err) => write!(f, "the node didn't answer as expected: {:?}", err),
+            CliError::TxCache(err) => write!(f, "{}", err),
+            CliError::Transaction(TransactionCreateError::InsufficientFounds) => {
+                write!(f, "insufficient funds")
+            }
+            CliError::Transaction(TransactionCreateError::InvalidAddress(address, err)) => {
+                write!(f, "the address \"{}\" is invalid: {}", address, err)
+            }
+            CliError::Transaction(TransactionCreateError::MissingSignatures) => write!(
+                f,
+                "this multisig account needs signatures of other cosigners, create a PSBT in the wallet interface"
+            ),
+            CliError::Transaction(TransactionCreateError::WatchOnly) => write!(
+                f,
+                "this account has no private keys, create a PSBT in the wallet interface"
+            ),
+            CliError::Transaction(err) => write!(f, "the transaction can't be created: {:?}", err),
+            CliError::Cancelled => write!(f, "nothing was sent"),
+            CliError::Io => write!(f, "the standard input or output can't be used"),
+        }
+    }
+}
@@ -0,0 +1,254 @@
+use node::{messages::read_from_bytes::encode_hex, wallet_utils::wallet_tx::WalletTx};
+
+use crate::transactions::tx_balance::{received_value, sent_value};
+
+use super::{
+    cli_constants::{BTC_DECIMALS, SATOSHIS_PER_BTC},
+    cli_error::CliError,
+};
+
+#[derive(Debug, PartialEq, Eq)]
+/// A transaction of the history of an account, as it is printed.
+pub struct HistoryEntry {
+    tx_id: String,
+    block_hash: String,
+    date: String,
+    received: bool,
+    confirmed: bool,
+    amount: i64,
+}
+
+impl HistoryEntry {
+    /// Creates the entry of a transaction of an account.
+    ///
+    /// # Arguments
+    ///
+    /// * `wallet_tx` - The transaction, with the block it was found in.
+    /// * `own_pk_scripts` - The scripts of every address of the account.
+    /// * `received` - Whether the account received the transaction, otherwise it sent it.
+    /// * `confirmed` - Whether the transaction is in a block.
+    ///
+    /// # Returns
+    ///
+    /// The entry, with the transaction id and the block hash in the byte order explorers show, which is also the one the
+    /// `proof` command takes, or a `CliError` if they can't be encoded.
+    pub fn new(
+        wallet_tx: &WalletTx,
+        own_pk_scripts: &[Vec<u8>],
+        received: bool,
+        confirmed: bool,
+    ) -> Result<HistoryEntry, CliError> {
+        let transaction = wallet_tx.get_tx();
+        let amount = match received {
+            true => received_value(transaction, own_pk_scripts),
+            false => sent_value(transaction, own_pk_scripts),
+        };
+        let mut block_hash = wallet_tx.get_block_hash().clone();
+        block_hash.reverse();
+
+        Ok(HistoryEntry {
+            tx_id: display_id(&transaction.get_id())?,
+            block_hash: encode_hex(&block_hash)?,
+            date: wallet_tx.get_date().clone(),
+            received,
+            confirmed,
+            amount,
+        })
+    }
+
+    pub fn get_date(&self) -> &String {
+        &self.date
+    }
+}
+
+/// Encodes a transaction id in hex, in the byte order explorers show.
+pub fn display_id(tx_id: &[u8]) -> Result<String, CliError> {
+    let mut tx_id = tx_id.to_vec();
+    tx_id.reverse();
+    Ok(encode_hex(&tx_id)?)
+}
+
+/// Formats satoshis as BTC with all its decimals, such as `-0.00150000`.
+pub fn format_btc(satoshis: i64) -> String {
+    let sign = if satoshis < 0 { "-" } else { "" };
+    let satoshis = satoshis.unsigned_abs();
+    let per_btc = SATOSHIS_PER_BTC as u64;
+    format!(
+        "{}{}.{:0width$}",
+        sign,
+        satoshis / per_btc,
+        satoshis % per_btc,
+        width = BTC_DECIMALS
+    )
+}
+
+/// Escapes a string as a JSON string, quotes included.
+pub fn json_string(text: &str) -> String {
+    let mut json = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            c if c.is_control() => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+/// Formats the balance of an account, in satoshis for JSON and in BTC for humans.
+///
+/// # Arguments
+///
+/// * `available` - The satoshis of the confirmed transactions.
+/// * `pending` - The satoshis of the transactions that aren't in a block yet.
+/// * `json` - Whether to format it as JSON.
+pub fn format_balance(available: i64, pending: i64, json: bool) -> String {
+    let total = available + pending;
+    match json {
+        true => format!(
+            "{{\"available\":{},\"pending\":{},\"total\":{}}}",
+            available, pending, total
+        ),
+        false => format!(
+            "Available: {} BTC\nPending:   {} BTC\nTotal:     {} BTC",
+            format_btc(available),
+            format_btc(pending),
+            format_btc(total)
+        ),
+    }
+}
+
+/// Formats the history of an account, one line per transaction for humans or an array of objects for JSON, with the
+/// amounts in satoshis.
+pub fn format_history(entries: &[HistoryEntry], json: bool) -> String {
+    if json {
+        let entries: Vec<String> = entries
+            .iter()
+            .map(|entry| {
+                format!(
+                    "{{\"txid\":{},\"block_hash\":{},\"date\":{},\"type\":{},\"confirmed\":{},\"amount\":{}}}",
+                    json_string(&entry.tx_id),
+                    json_string(&entry.block_hash),
+                    json_string(&entry.date),
+                    json_string(entry_type(entry)),
+                    entry.confirmed,
+                    entry.amount
+                )
+            })
+            .collect();
+        return format!("[{}]", entries.join(","));
+    }
+    if entries.is_empty() {
+        return "No transactions".to_string();
+    }
+    entries
+        .iter()
+        .map(|entry| {
+            format!(
+                "{}  {:<8}  {:<11}  {:>15} BTC  {}",
+                entry.date,
+                entry_type(entry),
+                if entry.confirmed {
+                    "confirmed"
+                } else {
+                    "unconfirmed"
+                },
+                format_btc(entry.amount),
+                entry.tx_id
+            )
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Returns whether the account received or sent the transaction of an entry.
+fn entry_type(entry: &HistoryEntry) -> &'static str {
+    match entry.received {
+        true => "received",
+        false => "sent",
+    }
+}
+
+/// Formats the address to receive payments to.
+pub fn format_receive(address: &str, json: bool) -> String {
+    match json {
+        true => format!("{{\"address\":{}}}", json_string(address)),
+        false => address.to_string(),
+    }
+}
+
+/// Formats a transaction that was sent, with its id and its fee.
+pub fn format_sent(tx_id: &str, fee: i64, json: bool) -> String {
+    match json {
+        true => format!("{{\"txid\":{},\"fee\":{}}}", json_string(tx_id), fee),
+        false => format!("Sent {} (fee {} BTC)", tx_id, format_btc(fee)),
+    }
+}
+
+/// Formats the result of a proof of inclusion.
+pub fn format_proof(included: bool, json: bool) -> String {
+    match (json, included) {
+        (true, included) => format!("{{\"included\":{}}}", included),
+        (false, true) => "The transaction is included in the block".to_string(),
+        (false, false) => "The transaction isn't included in the block".to_string(),
+    }
+}
+
+/// Formats an error, as an object with an `error` field for JSON.
+pub fn format_error(err: &CliError, json: bool) -> String {
+    match json {
+        true => format!("{{\"error\":{}}}", json_string(&err.to_string())),
+        false => format!("Error: {}", err),
+    }
+}
+
+#[cfg(test)]
+mod cli_output_test {
+    use node::block_mod::{transaction::Transaction, tx_in::TxIn, tx_out::TxOut};
+
+    use super::*;
+
+    #[test]
+    fn test_format_btc() {
+        assert_eq!(format_btc(0), "0.00000000");
+        assert_eq!(format_btc(150_000), "0.00150000");
+        assert_eq!(format_btc(2_112_345_678), "21.12345678");
+        assert_eq!(format_btc(-1), "-0.00000001");
+    }
+
+    #[test]
+    fn test_json_output() -> Result<(), CliError> {
+        let transaction = Transaction::new(
+            1,
+            vec![TxIn::new(vec![3; 32], 0, vec![], 0xffffffff)],
+            vec![
+                TxOut::new(30000, vec![1; 22]),
+                TxOut::new(5000, vec![4; 22]),
+            ],
+            0,
+        );
+        let mut block_hash = vec![0; 32];
+        block_hash[0] = 0xab;
+        let wallet_tx = WalletTx::new(transaction, block_hash, "2023-06-01  10:00".to_string());
+        let entry = HistoryEntry::new(&wallet_tx, &[vec![1; 22]], true, true)?;
+
+        let history = format_history(std::slice::from_ref(&entry), true);
+
+        assert!(history.starts_with("[{\"txid\":\""));
+        assert!(history.contains(&format!("\"block_hash\":\"{}ab\"", "0".repeat(62))));
+        assert!(history.contains("\"date\":\"2023-06-01  10:00\""));
+        assert!(history.ends_with("\"type\":\"received\",\"confirmed\":true,\"amount\":30000}]"));
+        assert_eq!(
+            format_balance(30000, -5000, true),
+            "{\"available\":30000,\"pending\":-5000,\"total\":25000}"
+        );
+        assert_eq!(
+            format_error(&CliError::Usage("a \"b\"".to_string()), true),
+            "{\"error\":\"a \\\"b\\\", see wallet-cli help\"}"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,205 @@
+use std::{
+    io::{BufRead, Write},
+    net::SocketAddr,
+    sync::Mutex,
+};
+
+use node::{
+    block_mod::transaction::SIGHASH_ALL,
+    wallet_utils::{broadcast_txn::BroadcastTxn, wallet_tx::WalletTx},
+};
+
+use crate::{
+    accounts::Accounts,
+    interface_error::InterfaceError,
+    node_connection::NodeConnection,
+    proof_of_inclusion::request_proof::request_proof,
+    transactions::{coin_selection::lowest_waste::LowestWaste, tx_balance::get_value},
+    update_wallet::sync_actual_account,
+    user_info::UserInfo,
+    wallet_constants::{NODE_IP, NODE_PORT},
+};
+
+use super::{
+    cli_command::{CliCommand, CliOptions},
+    cli_constants::{PASSPHRASE_ENV, USAGE},
+    cli_error::CliError,
+    cli_output::*,
+};
+
+/// Runs a command of the command line wallet.
+///
+/// # Arguments
+///
+/// * `options` - The options given before the command.
+/// * `command` - The command to run.
+///
+/// # Returns
+///
+/// The output of the command, formatted as JSON if the `--json` option was given, or a `CliError` if it fails.
+///
+/// # Description
+///
+/// The wallet connects to the node at `NODE_IP` and `NODE_PORT`. Except for `proof`, the keystore of the network is
+/// unlocked and the account is synced with the node and saved to the transaction cache before the command runs, just as
+/// the interface does. The connection is closed with the exit command even if the command fails, so the node can serve
+/// other wallets.
+pub fn run_cli(options: &CliOptions, command: CliCommand) -> Result<String, CliError> {
+    if command == CliCommand::Help {
+        return Ok(USAGE.to_string());
+    }
+    let socket = SocketAddr::from((NODE_IP, NODE_PORT));
+    let node = Mutex::new(NodeConnection::connect(socket).map_err(|err| match err {
+        InterfaceError::Connect => CliError::NodeConnection,
+        err => CliError::Node(err),
+    })?);
+
+    let output = run_command(options, command, &node);
+
+    let mut locked_node = node.lock().map_err(|_| InterfaceError::LockNode)?;
+    let disconnected = locked_node.disconnect();
+    let output = output?;
+    disconnected?;
+    Ok(output)
+}
+
+/// Runs a command connected to the node, see `run_cli`.
+fn run_command(
+    options: &CliOptions,
+    command: CliCommand,
+    node: &Mutex<NodeConnection>,
+) -> Result<String, CliError> {
+    if let CliCommand::Proof { block_hash, tx_id } = command {
+        let mut locked_node = node.lock().map_err(|_| InterfaceError::LockNode)?;
+        let included = request_proof(&mut locked_node, block_hash, tx_id)?;
+        return Ok(format_proof(included, options.json));
+    }
+
+    let accounts = Mutex::new(open_account(options)?);
+    sync_actual_account(&accounts, node)?;
+    let mut accounts = accounts
+        .into_inner()
+        .map_err(|_| InterfaceError::LockAccounts)?;
+    accounts.save_history()?;
+    let network = accounts.get_network();
+    let user_info = accounts.get_actual_account().ok_or(CliError::NoAccount)?;
+
+    match command {
+        CliCommand::Balance => {
+            let own_pk_scripts = user_info.get_pk_scripts();
+            let available = get_value(
+                &user_info.get_confirmed_txs_recv(),
+                &user_info.get_confirmed_txs_send(),
+                &own_pk_scripts,
+            );
+            let pending = get_value(
+                &user_info.get_unconfirmed_txs_recv(),
+                &user_info.get_unconfirmed_txs_send(),
+                &own_pk_scripts,
+            );
+            Ok(format_balance(available, pending, options.json))
+        }
+        CliCommand::History => Ok(format_history(&get_history(user_info)?, options.json)),
+        CliCommand::Receive => Ok(format_receive(
+            &user_info.get_receive_address(network),
+            options.json,
+        )),
+        CliCommand::Send { targets, fee_rate } => {
+            let amount: i64 = targets.iter().map(|(_, amount)| amount).sum();
+            let (transaction, fee, change_pk_script) = user_info.create_transaction(
+                targets,
+                fee_rate,
+                SIGHASH_ALL,
+                network,
+                &LowestWaste::default(),
+            )?;
+            let tx_id = display_id(&transaction.get_id())?;
+
+            if !options.yes {
+                let vsize = transaction.get_vsize();
+                eprintln!(
+                    "Amount: {} BTC\nFee: {} BTC ({:.2} sat/vB, {} vB)\nTotal: {} BTC",
+                    format_btc(amount),
+                    format_btc(fee),
+                    fee as f64 / vsize as f64,
+                    vsize,
+                    format_btc(amount + fee)
+                );
+                let answer = prompt("Send it? [y/N] ")?;
+                if !matches!(answer.trim().to_lowercase().as_str(), "y" | "yes") {
+                    return Err(CliError::Cancelled);
+                }
+            }
+
+            let broadcast_txn = BroadcastTxn::new(transaction);
+            let mut locked_node = node.lock().map_err(|_| InterfaceError::LockNode)?;
+            locked_node.broadcast(&broadcast_txn)?;
+            drop(locked_node);
+
+            accounts.reserve_change(&change_pk_script);
+            accounts.save_history()?;
+            Ok(format_sent(&tx_id, fee, options.json))
+        }
+        CliCommand::Proof { .. } | CliCommand::Help => Ok(USAGE.to_string()),
+    }
+}
+
+/// Unlocks the keystore of the network and chooses the account the command works on.
+///
+/// # Returns
+///
+/// The accounts, with the one given with `--account` as the actual one, or the first one of the keystore if none was
+/// given. A `CliError` is returned if there is no keystore, the passphrase is wrong or the account doesn't exist.
+fn open_account(options: &CliOptions) -> Result<Accounts, CliError> {
+    let mut accounts = Accounts::new(options.network);
+    if !accounts.has_keystore() {
+        return Err(CliError::NoKeystore);
+    }
+    let passphrase = match std::env::var(PASSPHRASE_ENV) {
+        Ok(passphrase) => passphrase,
+        Err(_) => prompt("Passphrase: ")?,
+    };
+    let names = accounts.unlock(&passphrase)?;
+
+    match &options.account {
+        Some(name) if names.contains(name) => accounts.set_actual_account(name.clone()),
+        Some(name) => return Err(CliError::UnknownAccount(name.clone())),
+        None if accounts.is_empty() => return Err(CliError::NoAccount),
+        None => {}
+    }
+    Ok(accounts)
+}
+
+/// Returns the transactions of an account, newest first.
+fn get_history(user_info: &UserInfo) -> Result<Vec<HistoryEntry>, CliError> {
+    let own_pk_scripts = user_info.get_pk_scripts();
+    let lists: [(Vec<WalletTx>, bool, bool); 4] = [
+        (user_info.get_confirmed_txs_recv(), true, true),
+        (user_info.get_confirmed_txs_send(), false, true),
+        (user_info.get_unconfirmed_txs_recv(), true, false),
+        (user_info.get_unconfirmed_txs_send(), false, false),
+    ];
+
+    let mut history = vec![];
+    for (txs, received, confirmed) in lists.iter() {
+        for wallet_tx in txs {
+            history.push(HistoryEntry::new(
+                wallet_tx,
+                &own_pk_scripts,
+                *received,
+                *confirmed,
+            )?);
+        }
+    }
+    history.sort_by(|a, b| b.get_date().cmp(a.get_date()));
+    Ok(history)
+}
+
+/// Asks for a line on the standard input, showing the question on the standard error so the output can be piped.
+fn prompt(question: &str) -> Result<String, CliError> {
+    eprint!("{}", question);
+    std::io::stderr().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().lock().read_line(&mut answer)?;
+    Ok(answer.trim_end_matches(['\r', '\n']).to_string())
+}
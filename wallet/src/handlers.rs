@@ -2,9 +2,11 @@ pub mod button_setting;
 pub mod handler_accounts;
 pub mod handler_constants;
 pub mod handler_content;
+pub mod handler_keystore;
 pub mod handler_login;
 pub mod handler_overview;
 pub mod handler_proof;
+pub mod handler_psbt;
 pub mod handler_receive;
 pub mod handler_send;
 pub mod handler_styles;
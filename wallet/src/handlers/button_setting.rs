@@ -1,19 +1,29 @@
 use crate::accounts::Accounts;
 use crate::interface_error::InterfaceError;
+use crate::node_connection::NodeConnection;
 use gtk::{Builder, ListStore};
-use std::net::TcpStream;
+use std::cell::RefCell;
+use std::rc::Rc;
 use std::sync::{Arc, Mutex};
 
 use super::handler_accounts::set_new_account_button;
-use super::handler_login::{set_login_button, set_login_ok_button, set_return_button};
+use super::handler_keystore::set_unlock_window;
+use super::handler_login::{
+    set_generate_mnemonic_button, set_login_button, set_login_ok_button, set_return_button,
+};
 use super::handler_overview::set_overview_button;
 use super::handler_proof::{
     set_make_proof_button, set_poi_button, set_poi_error_ok_button, set_poi_success_ok_button,
 };
+use super::handler_psbt::{
+    set_psbt_broadcast_button, set_psbt_button, set_psbt_combine_button, set_psbt_file_buttons,
+    set_psbt_finalize_button, set_psbt_sign_button,
+};
 use super::handler_receive::{set_copy_button, set_receive_button};
 use super::handler_send::{
-    set_add_recipient_button, set_clear_all_button, set_insuficient_ok_button, set_send_button,
-    set_send_transaction_button,
+    set_add_recipient_button, set_clear_all_button, set_coin_control_button,
+    set_confirm_transaction_buttons, set_create_psbt_button, set_insuficient_ok_button,
+    set_send_button, set_send_transaction_button, CoinControl, PendingTransaction,
 };
 use super::handler_transactions::set_transactions_button;
 
@@ -23,7 +33,7 @@ use super::handler_transactions::set_transactions_button;
 ///
 /// * `builder` - A reference to the builder object.
 /// * `accounts` - A shared mutable reference to the accounts.
-/// * `node` - A shared mutable reference to the TCP stream node.
+/// * `node` - A shared mutable reference to the connection to the node.
 /// * `store` - A reference to the list store.
 ///
 /// # Errors
@@ -32,10 +42,12 @@ use super::handler_transactions::set_transactions_button;
 pub fn set_buttons(
     builder: &Builder,
     accounts: Arc<Mutex<Accounts>>,
-    node: Arc<Mutex<TcpStream>>,
+    node: Arc<Mutex<NodeConnection>>,
     store: &ListStore,
 ) -> Result<(), InterfaceError> {
     set_login_button(builder, accounts.clone())?;
+    set_generate_mnemonic_button(builder)?;
+    set_unlock_window(builder, accounts.clone())?;
     set_overview_button(builder)?;
     set_send_button(builder)?;
     set_receive_button(builder)?;
@@ -44,7 +56,29 @@ pub fn set_buttons(
     set_clear_all_button(builder)?;
     set_copy_button(builder)?;
     set_poi_button(builder)?;
-    set_send_transaction_button(builder, node.clone(), accounts)?;
+    let coin_control: CoinControl = Rc::new(RefCell::new(vec![]));
+    let pending: PendingTransaction = Rc::new(RefCell::new(None));
+    set_coin_control_button(builder, accounts.clone(), coin_control.clone())?;
+    set_send_transaction_button(
+        builder,
+        accounts.clone(),
+        coin_control.clone(),
+        pending.clone(),
+    )?;
+    set_create_psbt_button(builder, accounts.clone(), coin_control.clone())?;
+    set_confirm_transaction_buttons(
+        builder,
+        node.clone(),
+        accounts.clone(),
+        coin_control,
+        pending,
+    )?;
+    set_psbt_button(builder)?;
+    set_psbt_sign_button(builder, accounts)?;
+    set_psbt_combine_button(builder)?;
+    set_psbt_finalize_button(builder)?;
+    set_psbt_broadcast_button(builder, node.clone())?;
+    set_psbt_file_buttons(builder)?;
     set_make_proof_button(builder, node)?;
     set_new_account_button(builder)?;
     set_return_button(builder)?;
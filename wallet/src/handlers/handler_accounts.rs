@@ -1,7 +1,9 @@
 use gtk::prelude::*;
-use gtk::{Builder, Button, Window};
+use gtk::{Builder, Button, Label, Window};
+use node::messages::read_from_bytes::encode_hex;
+use std::sync::{Arc, Mutex};
 
-use crate::interface_error::InterfaceError;
+use crate::{accounts::Accounts, interface_error::InterfaceError};
 
 use super::handler_constants::*;
 
@@ -15,7 +17,7 @@ use super::handler_constants::*;
 /// # Arguments
 ///
 /// * `builder` - The Builder object for accessing UI elements.
-/// * `node` - An Arc-wrapped Mutex-wrapped TcpStream for communication with the node.
+/// * `node` - An Arc-wrapped Mutex-wrapped connection to the node.
 /// * `accounts` - An Arc-wrapped Mutex for accessing account information.
 ///
 /// # Returns
@@ -59,3 +61,51 @@ pub fn set_new_account_button(builder: &Builder) -> Result<(), InterfaceError> {
 
     Ok(())
 }
+
+/// Creates the button that makes an account the active one.
+///
+/// # Arguments
+///
+/// * `username` - The name of the account.
+/// * `accounts` - An `Arc<Mutex<Accounts>>` representing the shared account data.
+/// * `actual_account_label` - The label showing the name of the active account.
+/// * `public_key_to_copy` - The label showing the key or address to share.
+///
+/// # Returns
+///
+/// The button, which the caller adds to the accounts box.
+pub fn create_account_button(
+    username: &str,
+    accounts: Arc<Mutex<Accounts>>,
+    actual_account_label: &Label,
+    public_key_to_copy: &Label,
+) -> Button {
+    let account_button: Button = Button::new();
+    account_button.set_label(username);
+
+    let username_account = username.to_string();
+    let shared_actual_account_label = actual_account_label.clone();
+    let shared_public_key_to_copy = public_key_to_copy.clone();
+
+    account_button.connect_clicked(move |_| {
+        if let Ok(mut locked_accounts) = accounts.lock() {
+            locked_accounts.set_actual_account(username_account.clone());
+            shared_actual_account_label.set_text(&username_account.clone());
+            shared_public_key_to_copy.set_text(&get_shared_key(&locked_accounts));
+        }
+    });
+    account_button
+}
+
+/// Returns the text shown to receive funds in the active account: its receive address for HD accounts and accounts
+/// locked to a script, such as multisig ones or watched addresses, or the public key in hexadecimal for accounts of a
+/// single key.
+pub fn get_shared_key(accounts: &Accounts) -> String {
+    match accounts.get_actual_account() {
+        Some(user_info) if user_info.is_hd() || user_info.get_public_key().is_empty() => {
+            user_info.get_receive_address(accounts.get_network())
+        }
+        Some(user_info) => encode_hex(&user_info.get_public_key()).unwrap_or_default(),
+        None => String::new(),
+    }
+}
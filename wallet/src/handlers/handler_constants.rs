@@ -36,18 +36,41 @@ pub const SHARED_PUBKEY: &str = "public_key_to_copy";
 pub const ADD_RECIPIENT_BUTTON: &str = "add_recipient_button";
 pub const CLEAR_ALL_BUTTON: &str = "clear_all_button";
 
+pub const COIN_CONTROL_BUTTON: &str = "coin_control_button";
+pub const COIN_CONTROL_WINDOW: &str = "coin_control_window";
+pub const COIN_CONTROL_BOX: &str = "coin_control_box";
+pub const COIN_CONTROL_TOTAL_LABEL: &str = "coin_control_total_label";
+pub const COIN_CONTROL_OK_BUTTON: &str = "coin_control_ok_button";
+pub const COIN_CONTROL_CLEAR_BUTTON: &str = "coin_control_clear_button";
+
 pub const POI_BUTTON: &str = "poi_button";
 pub const POI_BOX: &str = "poi_box";
 
+pub const PSBT_BUTTON: &str = "psbt_button";
+pub const PSBT_BOX: &str = "psbt_box";
+pub const CREATE_PSBT_BUTTON: &str = "create_psbt_button";
+pub const PSBT_TEXT_VIEW: &str = "psbt_text_view";
+pub const PSBT_SIGN_BUTTON: &str = "psbt_sign_button";
+pub const PSBT_FINALIZE_BUTTON: &str = "psbt_finalize_button";
+pub const PSBT_BROADCAST_BUTTON: &str = "psbt_broadcast_button";
+pub const PSBT_COMBINE_ENTRY: &str = "psbt_combine_entry";
+pub const PSBT_COMBINE_BUTTON: &str = "psbt_combine_button";
+pub const PSBT_PATH_ENTRY: &str = "psbt_path_entry";
+pub const PSBT_LOAD_BUTTON: &str = "psbt_load_button";
+pub const PSBT_SAVE_BUTTON: &str = "psbt_save_button";
+pub const PSBT_STATUS_LABEL: &str = "psbt_status_label";
+
 pub const MAKE_PROOF_BUTTON: &str = "make_proof_button";
 pub const BLOCK_HEADER_ENTRY: &str = "block_header_entry";
 pub const TRANSACTION_ID_ENTRY: &str = "transaction_id_entry";
 
-pub const MERKLE_BLOCK: &str = "merkleblock";
-
 pub const SEND_TX_BUTTON: &str = "send_transaction_button";
 pub const TX_BOX: &str = "transaction_box";
 pub const FEE_SPIN_BUTTON: &str = "fee_spin_button";
+pub const CONFIRM_TX_WINDOW: &str = "confirm_transaction_window";
+pub const CONFIRM_TX_LABEL: &str = "confirm_transaction_label";
+pub const CONFIRM_TX_SEND_BUTTON: &str = "confirm_transaction_send_button";
+pub const CONFIRM_TX_CANCEL_BUTTON: &str = "confirm_transaction_cancel_button";
 pub const NEW_ACCOUNT_BUTTON: &str = "new_account_button";
 pub const RETURN_BUTTON: &str = "return_button";
 
@@ -59,3 +82,18 @@ pub const PROGRESS_BAR: &str = "progress_bar";
 
 pub const INSUFFICIENT_FUNDS_DIALOG: &str = "insufficient_funds_error_window";
 pub const INSUFFICIENT_FUNDS_OK_BUTTON: &str = "insufficient_funds_ok_button";
+
+pub const MNEMONIC_ENTRY: &str = "mnemonic_entry";
+pub const GENERATE_MNEMONIC_BUTTON: &str = "generate_mnemonic_button";
+pub const LEGACY_ADDRESS_CHECK: &str = "legacy_address_check";
+pub const MULTISIG_KEYS_ENTRY: &str = "multisig_keys_entry";
+pub const MULTISIG_REQUIRED_SPIN: &str = "multisig_required_spin";
+
+pub const UNLOCK_WINDOW: &str = "unlock_window";
+pub const UNLOCK_BUTTON: &str = "unlock_button";
+pub const UNLOCK_PASSPHRASE_ENTRY: &str = "unlock_passphrase_entry";
+pub const UNLOCK_TITLE_LABEL: &str = "unlock_title_label";
+pub const UNLOCK_ERROR_LABEL: &str = "unlock_error_label";
+pub const KEYSTORE_PASSPHRASE_LABEL: &str = "keystore_passphrase_label";
+pub const KEYSTORE_PASSPHRASE_ENTRY: &str = "keystore_passphrase_entry";
+pub const ACTUAL_ACCOUNT_LABEL: &str = "actual_account_label";
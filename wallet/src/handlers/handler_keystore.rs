@@ -0,0 +1,150 @@
+use gtk::prelude::*;
+use gtk::{Box, Builder, Button, Entry, Label, Window};
+use std::cell::Cell;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use super::{
+    handler_accounts::{create_account_button, get_shared_key},
+    handler_constants::*,
+    handler_content::clean_entry,
+};
+use crate::keystore::keystore_constants::{AUTO_LOCK_CHECK_SECONDS, AUTO_LOCK_SECONDS};
+use crate::{accounts::Accounts, interface_error::InterfaceError};
+
+/// Sets up the unlock window and the automatic lock of the wallet.
+///
+/// # Arguments
+///
+/// * `builder` - The Builder object for accessing UI elements.
+/// * `accounts` - An `Arc<Mutex<Accounts>>` representing the shared account data.
+///
+/// # Returns
+///
+/// Returns `Ok(())` if the function executes successfully, or an `InterfaceError` if any UI elements are missing.
+///
+/// # Description
+///
+/// The unlock window is shown at startup when there is a keystore, and after the wallet is locked. Clicking the unlock
+/// button decrypts the keystore with the entered password, adds a button for every account loaded for the first time and
+/// shows the main window. A wrong password is reported in the window itself.
+///
+/// Every event of the main window counts as activity. If there is none for `AUTO_LOCK_SECONDS`, the secrets of the
+/// accounts are erased from memory, the main window is hidden and the password is asked again. The wallet keeps syncing
+/// while it is locked.
+pub fn set_unlock_window(
+    builder: &Builder,
+    accounts: Arc<Mutex<Accounts>>,
+) -> Result<(), InterfaceError> {
+    let unlock_window: Window = builder
+        .get_object(UNLOCK_WINDOW)
+        .ok_or(InterfaceError::MissingWindow)?;
+    let main_window: Window = builder
+        .get_object(MAIN_WINDOW)
+        .ok_or(InterfaceError::MissingWindow)?;
+    let login_window: Window = builder
+        .get_object(LOGIN_WINDOW)
+        .ok_or(InterfaceError::MissingWindow)?;
+    let unlock_button: Button = builder
+        .get_object(UNLOCK_BUTTON)
+        .ok_or(InterfaceError::MissingButton)?;
+    let passphrase_entry: Entry = builder
+        .get_object(UNLOCK_PASSPHRASE_ENTRY)
+        .ok_or(InterfaceError::MissingEntry)?;
+    let title_label: Label = builder
+        .get_object(UNLOCK_TITLE_LABEL)
+        .ok_or(InterfaceError::MissingLabel)?;
+    let error_label: Label = builder
+        .get_object(UNLOCK_ERROR_LABEL)
+        .ok_or(InterfaceError::MissingLabel)?;
+    let accounts_box: Box = builder
+        .get_object(ACCOUNTS_BOX)
+        .ok_or(InterfaceError::MissingBox)?;
+    let actual_account_label: Label = builder
+        .get_object(ACTUAL_ACCOUNT_LABEL)
+        .ok_or(InterfaceError::MissingLabel)?;
+    let public_key_to_copy: Label = builder
+        .get_object(SHARED_PUBKEY)
+        .ok_or(InterfaceError::MissingLabel)?;
+
+    unlock_window.connect_delete_event(|_, _| {
+        gtk::main_quit();
+        Inhibit(false)
+    });
+
+    let last_activity = Rc::new(Cell::new(Instant::now()));
+
+    let shared_accounts = accounts.clone();
+    let shared_main_window = main_window.clone();
+    let shared_unlock_window = unlock_window.clone();
+    let shared_last_activity = last_activity.clone();
+    let shared_passphrase_entry = passphrase_entry.clone();
+    unlock_button.connect_clicked(move |_| {
+        let mut locked_accounts = match shared_accounts.lock() {
+            Ok(locked_accounts) => locked_accounts,
+            Err(_) => return,
+        };
+        let passphrase = shared_passphrase_entry.get_text();
+        clean_entry(&shared_passphrase_entry);
+
+        let new_accounts = match locked_accounts.unlock(passphrase.as_str()) {
+            Ok(new_accounts) => new_accounts,
+            Err(err) => {
+                error_label.set_text(&err.to_string());
+                return;
+            }
+        };
+        for username in new_accounts {
+            let account_button = create_account_button(
+                &username,
+                shared_accounts.clone(),
+                &actual_account_label,
+                &public_key_to_copy,
+            );
+            accounts_box.add(&account_button);
+        }
+        actual_account_label.set_text(locked_accounts.get_actual_username());
+        public_key_to_copy.set_text(&get_shared_key(&locked_accounts));
+        let is_empty = locked_accounts.is_empty();
+        drop(locked_accounts);
+
+        error_label.set_text(EMPTY);
+        shared_last_activity.set(Instant::now());
+        shared_unlock_window.hide();
+        if is_empty {
+            login_window.show();
+        } else {
+            shared_main_window.show_all();
+        }
+    });
+
+    passphrase_entry.connect_activate(move |_| {
+        unlock_button.clicked();
+    });
+
+    let shared_last_activity = last_activity.clone();
+    main_window.connect_event(move |_, _| {
+        shared_last_activity.set(Instant::now());
+        Inhibit(false)
+    });
+
+    glib::timeout_add_seconds_local(AUTO_LOCK_CHECK_SECONDS, move || {
+        if !main_window.get_visible()
+            || last_activity.get().elapsed() < Duration::from_secs(AUTO_LOCK_SECONDS)
+        {
+            return Continue(true);
+        }
+        if let Ok(mut locked_accounts) = accounts.lock() {
+            if locked_accounts.has_keystore() && locked_accounts.is_keystore_unlocked() {
+                locked_accounts.lock();
+                main_window.hide();
+                title_label.set_text("Wallet locked after inactivity");
+                unlock_window.show_all();
+            }
+        }
+        Continue(true)
+    });
+
+    Ok(())
+}
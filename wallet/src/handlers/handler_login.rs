@@ -1,8 +1,23 @@
-use super::{handler_constants::*, handler_content::clean_entry};
+use super::{
+    handler_accounts::{create_account_button, get_shared_key},
+    handler_constants::*,
+    handler_content::clean_entry,
+};
+use crate::hd_wallet::{
+    bip32::ExtendedPublicKey, bip39::generate_mnemonic, derivation_path::AddressPurpose,
+};
+use crate::keystore::keystore_entry::WatchedKey;
+use crate::multisig::multisig_script::{MultisigKind, MultisigScript};
+use crate::transactions::{address::Address, create_transactions::private_key_from_wif};
 use crate::{accounts::Accounts, interface_error::InterfaceError};
 use gtk::prelude::*;
-use gtk::{Box, Builder, Button, CssProvider, Dialog, Entry, Label, Widget, Window};
+use gtk::{
+    Box, Builder, Button, CheckButton, CssProvider, Dialog, Entry, Label, SpinButton, Widget,
+    Window,
+};
+use node::block_mod::script_type::ScriptType;
 use node::messages::read_from_bytes::decode_hex;
+use node::network::bitcoin_network::BitcoinNetwork;
 use std::sync::{Arc, Mutex};
 
 /// Sets up the login button and its associated functionality.
@@ -18,7 +33,7 @@ use std::sync::{Arc, Mutex};
 ///
 /// # Description
 ///
-/// This function sets up the login button by retrieving the necessary GTK objects from the builder, connecting the click event, and defining the login logic. Upon clicking the login button, the function performs input validation, shows an authentication error dialog if the input is invalid, clears the entry fields, hides the login window, and displays the main window with the updated account information.
+/// This function sets up the login button by retrieving the necessary GTK objects from the builder, connecting the click event, and defining the login logic. Upon clicking the login button, the function adds a multisig account if the public keys of its cosigners were entered, an HD account if a recovery phrase was entered, a watch-only account if no private key was entered, or an account of a single key otherwise. If there is no keystore yet, it is created with the wallet password entered. It performs input validation, shows an authentication error dialog if the input is invalid, saves the accounts in the keystore, clears the entry fields, hides the login window, and displays the main window with the updated account information.
 pub fn set_login_button(
     builder: &Builder,
     accounts: Arc<Mutex<Accounts>>,
@@ -55,7 +70,7 @@ pub fn set_login_button(
         .get_object(ACCOUNTS_BOX)
         .ok_or(InterfaceError::MissingBox)?;
     let actual_account_label: Label = builder
-        .get_object("actual_account_label")
+        .get_object(ACTUAL_ACCOUNT_LABEL)
         .ok_or(InterfaceError::MissingLabel)?;
 
     // Create a CSS provider and load CSS data to define the color
@@ -86,79 +101,413 @@ pub fn set_login_button(
         .get_object(SHARED_PUBKEY)
         .ok_or(InterfaceError::MissingLabel)?;
 
-    login_button.connect_clicked(move |_| {
-        let username = username_entry.get_text();
-        let public_key = public_key_entry.get_text();
-        let private_key = private_key_entry.get_text();
+    let mnemonic_entry: Entry = builder
+        .get_object(MNEMONIC_ENTRY)
+        .ok_or(InterfaceError::MissingEntry)?;
 
-        if !valid_username(username.as_str())
-            || !valid_public_key(public_key.as_str())
-            || !valid_private_key(private_key.as_str())
-        {
-            let mut auth_text = "Please complete the entries correctly".to_string();
+    let legacy_address_check: CheckButton = builder
+        .get_object(LEGACY_ADDRESS_CHECK)
+        .ok_or(InterfaceError::MissingButton)?;
 
-            if !valid_username(username.as_str()) {
-                auth_text += "\n \n - Username is invalid";
-            }
+    let multisig_keys_entry: Entry = builder
+        .get_object(MULTISIG_KEYS_ENTRY)
+        .ok_or(InterfaceError::MissingEntry)?;
+    let multisig_required_spin: SpinButton = builder
+        .get_object(MULTISIG_REQUIRED_SPIN)
+        .ok_or(InterfaceError::MissingSpinButton)?;
 
-            if !valid_public_key(public_key.as_str()) {
-                auth_text += "\n \n - Public key is invalid";
-            }
+    let keystore_passphrase_label: Label = builder
+        .get_object(KEYSTORE_PASSPHRASE_LABEL)
+        .ok_or(InterfaceError::MissingLabel)?;
+    let keystore_passphrase_entry: Entry = builder
+        .get_object(KEYSTORE_PASSPHRASE_ENTRY)
+        .ok_or(InterfaceError::MissingEntry)?;
 
-            if !valid_private_key(private_key.as_str()) {
-                auth_text += "\n \n - Private key is invalid";
+    login_button.connect_clicked(move |_| {
+        let username = username_entry.get_text();
+        let mnemonic = mnemonic_entry.get_text();
+        let mut locked_accounts = match accounts.lock() {
+            Ok(locked_accounts) => locked_accounts,
+            Err(_) => return,
+        };
+
+        if !locked_accounts.is_keystore_unlocked() {
+            if let Err(err) =
+                locked_accounts.create_keystore(keystore_passphrase_entry.get_text().as_str())
+            {
+                title_label.set_text("Login Authentication Error");
+                advice_label.set_text(&format!(
+                    "Please complete the entries correctly\n \n - Wallet password: {}",
+                    err
+                ));
+                user_authentication_dialog.show_all();
+                return;
             }
+        }
 
+        let multisig_keys = multisig_keys_entry.get_text();
+        let added = if !multisig_keys.trim().is_empty() {
+            let kind = if legacy_address_check.get_active() {
+                MultisigKind::P2sh
+            } else {
+                MultisigKind::P2wsh
+            };
+            add_multisig_account(
+                &mut locked_accounts,
+                username.as_str(),
+                multisig_keys.as_str(),
+                multisig_required_spin.get_value_as_int() as usize,
+                kind,
+                private_key_entry.get_text().as_str(),
+            )
+        } else if mnemonic.trim().is_empty() && private_key_entry.get_text().is_empty() {
+            let purpose = if legacy_address_check.get_active() {
+                AddressPurpose::Bip44
+            } else {
+                AddressPurpose::Bip84
+            };
+            add_watch_only_account(
+                &mut locked_accounts,
+                username.as_str(),
+                public_key_entry.get_text().as_str(),
+                purpose,
+            )
+        } else if mnemonic.trim().is_empty() {
+            add_key_account(
+                &mut locked_accounts,
+                username.as_str(),
+                public_key_entry.get_text().as_str(),
+                private_key_entry.get_text().as_str(),
+            )
+        } else {
+            let purpose = if legacy_address_check.get_active() {
+                AddressPurpose::Bip44
+            } else {
+                AddressPurpose::Bip84
+            };
+            add_mnemonic_account(
+                &mut locked_accounts,
+                username.as_str(),
+                mnemonic.as_str(),
+                purpose,
+            )
+        };
+
+        if let Err(auth_text) = added {
+            if !locked_accounts.has_keystore() {
+                // Forget the key of the keystore that was just created, so the password can be changed.
+                locked_accounts.lock();
+            }
             title_label.set_text("Login Authentication Error");
             advice_label.set_text(auth_text.as_str());
             user_authentication_dialog.show_all();
             return;
         }
+        if let Err(err) = locked_accounts.save() {
+            title_label.set_text("Keystore Error");
+            advice_label.set_text(&format!(
+                "The account was added but it couldn't be saved:\n \n - {}",
+                err
+            ));
+            user_authentication_dialog.show_all();
+        }
+        let shared_key = get_shared_key(&locked_accounts);
+        drop(locked_accounts);
 
-        let new_account_button: Button = Button::new();
-        new_account_button.set_label(&username);
-
-        let username_account = username.to_string();
-        let shared_accounts = accounts.clone();
-        let shared_actual_account_label = actual_account_label.clone();
-        let shared_public_key_to_copy = public_key_to_copy.clone();
-        let shared_public_key = public_key.to_string();
-
-        new_account_button.connect_clicked(move |_| {
-            if let Ok(mut locked_accounts) = shared_accounts.lock() {
-                locked_accounts.set_actual_account(username_account.clone());
-                shared_actual_account_label.set_text(&username_account.clone());
-                shared_public_key_to_copy.set_text(&shared_public_key);
-            }
-        });
+        let new_account_button = create_account_button(
+            &username,
+            accounts.clone(),
+            &actual_account_label,
+            &public_key_to_copy,
+        );
 
         clean_entry(&username_entry);
         clean_entry(&public_key_entry);
         clean_entry(&private_key_entry);
+        clean_entry(&mnemonic_entry);
+        clean_entry(&multisig_keys_entry);
+        clean_entry(&keystore_passphrase_entry);
+        keystore_passphrase_label.hide();
+        keystore_passphrase_entry.hide();
 
         login_window.hide();
 
-        public_key_to_copy.set_text(&public_key);
-
-        if let Ok(mut accounts) = accounts.lock() {
-            if let Ok(public_key_bytes) = decode_hex(&public_key) {
-                if let Ok(private_key_bytes) = decode_hex(&private_key) {
-                    actual_account_label.set_text(&username);
-                    accounts_box.add(&new_account_button);
-                    accounts.add_account(username.to_string(), public_key_bytes, private_key_bytes);
-                    drop(accounts);
-                    main_window.show_all();
-                }
-            }
+        public_key_to_copy.set_text(&shared_key);
+        actual_account_label.set_text(&username);
+        accounts_box.add(&new_account_button);
+        main_window.show_all();
+    });
+    Ok(())
+}
+
+/// Adds an account of a single key pair, given as entered in the login window.
+///
+/// # Arguments
+///
+/// * `accounts` - The accounts of the wallet.
+/// * `username` - The name of the new account.
+/// * `public_key` - The public key, in hexadecimal.
+/// * `private_key` - The private key, in hexadecimal or in Wallet Import Format.
+///
+/// # Returns
+///
+/// Returns `Ok(())` if the account was added, or the text explaining which entries are invalid.
+fn add_key_account(
+    accounts: &mut Accounts,
+    username: &str,
+    public_key: &str,
+    private_key: &str,
+) -> Result<(), String> {
+    let network = accounts.get_network();
+
+    if !valid_username(username)
+        || !valid_public_key(public_key)
+        || !valid_private_key(private_key, network)
+    {
+        let mut auth_text = "Please complete the entries correctly".to_string();
+
+        if !valid_username(username) {
+            auth_text += "\n \n - Username is invalid";
+        }
+
+        if !valid_public_key(public_key) {
+            auth_text += "\n \n - Public key is invalid";
+        }
+
+        if !valid_private_key(private_key, network) {
+            auth_text += "\n \n - Private key is invalid";
+        }
+        return Err(auth_text);
+    }
+
+    let public_key_bytes =
+        decode_hex(public_key).map_err(|_| "Public key is invalid".to_string())?;
+    let private_key_bytes = parse_private_key(private_key, network)
+        .ok_or_else(|| "Private key is invalid".to_string())?;
+    accounts.add_account(username.to_string(), public_key_bytes, private_key_bytes);
+    Ok(())
+}
+
+/// Adds a watch-only account, which follows the balance and history of keys held somewhere else.
+///
+/// # Arguments
+///
+/// * `accounts` - The accounts of the wallet.
+/// * `username` - The name of the new account.
+/// * `watched` - What the account follows, as entered in the public key entry: see `parse_watched_key`.
+/// * `purpose` - Whether the addresses derived from an extended public key are BIP44 or BIP84 ones.
+///
+/// # Returns
+///
+/// Returns `Ok(())` if the account was added, or the text explaining which entries are invalid.
+fn add_watch_only_account(
+    accounts: &mut Accounts,
+    username: &str,
+    watched: &str,
+    purpose: AddressPurpose,
+) -> Result<(), String> {
+    let watched = parse_watched_key(watched.trim(), purpose, accounts.get_network());
+
+    if !valid_username(username) || watched.is_err() {
+        let mut auth_text = "Please complete the entries correctly".to_string();
+
+        if !valid_username(username) {
+            auth_text += "\n \n - Username is invalid";
         }
+
+        if let Err(err) = &watched {
+            auth_text += &format!("\n \n - {}", err);
+        }
+        return Err(auth_text);
+    }
+
+    accounts
+        .add_watch_only_account(username.to_string(), watched?)
+        .map_err(|err| format!("Please complete the entries correctly\n \n - {}", err))
+}
+
+/// Parses what a watch-only account follows.
+///
+/// # Arguments
+///
+/// * `watched` - A compressed public key in hexadecimal, an address, or the BIP32 extended public key of an account
+///   (`m/purpose'/coin_type'/account'`), as exported by other wallets.
+/// * `purpose` - The purpose of the account of an extended public key.
+/// * `network` - The network the wallet is running on.
+///
+/// # Returns
+///
+/// Returns the watched key, or the text explaining why it is invalid. Addresses and extended keys of another network are
+/// rejected, and so are Taproot addresses, whose spends don't reveal anything the node can match.
+fn parse_watched_key(
+    watched: &str,
+    purpose: AddressPurpose,
+    network: BitcoinNetwork,
+) -> Result<WatchedKey, String> {
+    if valid_public_key(watched) {
+        return decode_hex(watched)
+            .map(WatchedKey::PublicKey)
+            .map_err(|_| "Public key is invalid".to_string());
+    }
+    if let Ok(account_public_key) = ExtendedPublicKey::decode(watched) {
+        if account_public_key.get_network() != network {
+            return Err("The extended public key belongs to another network".to_string());
+        }
+        return Ok(WatchedKey::Extended {
+            account_public_key,
+            purpose,
+        });
+    }
+    let pk_script = Address::parse(watched.as_bytes(), network)
+        .map_err(|err| {
+            format!(
+                "Public key, address or extended public key is invalid: {}",
+                err
+            )
+        })?
+        .get_pk_script();
+    if ScriptType::from_pk_script(&pk_script) == ScriptType::P2tr {
+        return Err("Taproot addresses can't be watched".to_string());
+    }
+    Ok(WatchedKey::Address(pk_script))
+}
+
+/// Adds an HD account restored from the recovery phrase entered in the login window.
+///
+/// # Arguments
+///
+/// * `accounts` - The accounts of the wallet.
+/// * `username` - The name of the new account.
+/// * `mnemonic` - The BIP39 recovery phrase.
+/// * `purpose` - Whether the account derives BIP44 or BIP84 addresses.
+///
+/// # Returns
+///
+/// Returns `Ok(())` if the account was added, or the text explaining why the entries are invalid.
+fn add_mnemonic_account(
+    accounts: &mut Accounts,
+    username: &str,
+    mnemonic: &str,
+    purpose: AddressPurpose,
+) -> Result<(), String> {
+    if !valid_username(username) {
+        return Err("Please complete the entries correctly\n \n - Username is invalid".to_string());
+    }
+
+    accounts
+        .add_hd_account(username.to_string(), mnemonic, purpose)
+        .map_err(|err| format!("Please complete the entries correctly\n \n - {}", err))
+}
+
+/// Adds an m-of-n multisig account, given as entered in the login window.
+///
+/// # Arguments
+///
+/// * `accounts` - The accounts of the wallet.
+/// * `username` - The name of the new account.
+/// * `public_keys` - The public keys of every cosigner, in hexadecimal and separated by commas.
+/// * `required` - The number of signatures needed to spend, `m`.
+/// * `kind` - Whether the account receives in P2SH or P2WSH addresses.
+/// * `private_keys` - The keys of the cosigners that sign in this wallet, separated by commas. It may be empty, in which
+///   case the account only creates PSBTs for the other cosigners to sign.
+///
+/// # Returns
+///
+/// Returns `Ok(())` if the account was added, or the text explaining which entries are invalid.
+fn add_multisig_account(
+    accounts: &mut Accounts,
+    username: &str,
+    public_keys: &str,
+    required: usize,
+    kind: MultisigKind,
+    private_keys: &str,
+) -> Result<(), String> {
+    let network = accounts.get_network();
+    let public_keys: Vec<&str> = public_keys.split(',').map(str::trim).collect();
+    let private_keys: Vec<&str> = private_keys
+        .split(',')
+        .map(str::trim)
+        .filter(|private_key| !private_key.is_empty())
+        .collect();
+    let valid_public_keys = public_keys
+        .iter()
+        .all(|public_key| valid_public_key(public_key));
+    let valid_private_keys = private_keys
+        .iter()
+        .all(|private_key| valid_private_key(private_key, network));
+    let multisig = MultisigScript::new(
+        required,
+        public_keys
+            .iter()
+            .filter_map(|public_key| decode_hex(public_key).ok())
+            .collect(),
+    );
+
+    if !valid_username(username) || !valid_public_keys || !valid_private_keys || multisig.is_err() {
+        let mut auth_text = "Please complete the entries correctly".to_string();
+
+        if !valid_username(username) {
+            auth_text += "\n \n - Username is invalid";
+        }
+
+        if !valid_public_keys {
+            auth_text += "\n \n - A public key of the cosigners is invalid";
+        }
+
+        if !valid_private_keys {
+            auth_text += "\n \n - Private key is invalid";
+        }
+
+        if let Err(err) = &multisig {
+            auth_text += &format!("\n \n - Multisig: {}", err);
+        }
+        return Err(auth_text);
+    }
+
+    let multisig = multisig.map_err(|err| err.to_string())?;
+    let private_keys = private_keys
+        .iter()
+        .filter_map(|private_key| parse_private_key(private_key, network))
+        .collect();
+    accounts.add_multisig_account(username.to_string(), multisig, kind, private_keys);
+    Ok(())
+}
+
+/// Sets up the button that generates a new recovery phrase.
+///
+/// # Arguments
+///
+/// * `builder` - The Builder object for accessing UI elements.
+///
+/// # Returns
+///
+/// Returns `Ok(())` if the function executes successfully, or an `InterfaceError` if any UI elements are missing.
+///
+/// # Description
+///
+/// When clicked, a new random 12 words mnemonic is written in the recovery phrase entry, so logging in creates a new HD
+/// account. The user must write the words down, since they are the only way to restore the account.
+///
+pub fn set_generate_mnemonic_button(builder: &Builder) -> Result<(), InterfaceError> {
+    let generate_mnemonic_button: Button = builder
+        .get_object(GENERATE_MNEMONIC_BUTTON)
+        .ok_or(InterfaceError::MissingButton)?;
+    let mnemonic_entry: Entry = builder
+        .get_object(MNEMONIC_ENTRY)
+        .ok_or(InterfaceError::MissingEntry)?;
+
+    generate_mnemonic_button.connect_clicked(move |_| match generate_mnemonic() {
+        Ok(mnemonic) => mnemonic_entry.set_text(&mnemonic),
+        Err(err) => println!("{}", err),
     });
+
     Ok(())
 }
 
 /// Sets up the functionality for the "Return" button.
 ///
 /// This function connects the "Return" button to a click event handler. When clicked, it hides the login window,
-/// cleans the username, public key, and private key entries, and shows the main window.
+/// cleans the username, public key, private key, recovery phrase and multisig entries, and shows the main window.
 ///
 /// # Arguments
 ///
@@ -199,12 +548,20 @@ pub fn set_return_button(builder: &Builder) -> Result<(), InterfaceError> {
     let private_key_entry: Entry = builder
         .get_object(PRIVATE_KEY_ENTRY)
         .ok_or(InterfaceError::MissingEntry)?;
+    let mnemonic_entry: Entry = builder
+        .get_object(MNEMONIC_ENTRY)
+        .ok_or(InterfaceError::MissingEntry)?;
+    let multisig_keys_entry: Entry = builder
+        .get_object(MULTISIG_KEYS_ENTRY)
+        .ok_or(InterfaceError::MissingEntry)?;
 
     return_button.connect_clicked(move |_| {
         login_window.hide();
         clean_entry(&username_entry);
         clean_entry(&public_key_entry);
         clean_entry(&private_key_entry);
+        clean_entry(&mnemonic_entry);
+        clean_entry(&multisig_keys_entry);
         main_window.show_all();
     });
 
@@ -252,6 +609,7 @@ fn valid_public_key(public_key: &str) -> bool {
 /// # Arguments
 ///
 /// * `private_key` - A string slice representing the private key to be validated.
+/// * `network` - The network the wallet is running on.
 ///
 /// # Returns
 ///
@@ -259,10 +617,28 @@ fn valid_public_key(public_key: &str) -> bool {
 ///
 /// # Description
 ///
-/// This function validates the provided private key by checking its length and ensuring that all characters are ASCII hexadecimal digits. It returns `true` if the length of the private key is 64 (indicating the expected length for a valid private key) and all characters in the private key are ASCII hexadecimal digits.
+/// This function validates the provided private key by trying to parse it with `parse_private_key`. Both the 64 hexadecimal digits form and the Wallet Import Format of the wallet's network are accepted.
+///
+fn valid_private_key(private_key: &str, network: BitcoinNetwork) -> bool {
+    parse_private_key(private_key, network).is_some()
+}
+
+/// Parses a private key given either as hexadecimal or in Wallet Import Format (WIF).
+///
+/// # Arguments
+///
+/// * `private_key` - A string slice representing the private key.
+/// * `network` - The network the wallet is running on.
+///
+/// # Returns
+///
+/// Returns the 32 bytes of the private key, or `None` if it isn't valid. A WIF of another network is rejected.
 ///
-fn valid_private_key(private_key: &str) -> bool {
-    private_key.len() == 64 && private_key.chars().all(|c| c.is_ascii_hexdigit())
+fn parse_private_key(private_key: &str, network: BitcoinNetwork) -> Option<Vec<u8>> {
+    if private_key.len() == 64 && private_key.chars().all(|c| c.is_ascii_hexdigit()) {
+        return decode_hex(private_key).ok();
+    }
+    private_key_from_wif(private_key.as_bytes(), network).ok()
 }
 
 /// Sets up the functionality for the "OK" buttons in the user authentication and proof of inclusion dialogs.
@@ -1,18 +1,17 @@
 use gtk::prelude::*;
 use gtk::{Box, Builder, Button, Dialog, Entry, Label};
-use node::messages::read_from_bytes::{decode_hex, read_string_from_bytes};
-use node::wallet_utils::get_proof::GetProof;
-use node::wallet_utils::merkle_block::MerkleBlock;
-use std::io::Write;
+use node::{
+    messages::read_from_bytes::decode_hex, wallet_utils::wallet_utils_constants::NULL_BLOCK_HASH,
+};
 
 use crate::interface_error::InterfaceError;
-use crate::proof_of_inclusion::get_proof_of_inclusion::get_proof_of_inclusion;
+use crate::proof_of_inclusion::request_proof::request_proof;
 
 use super::{handler_constants::*, handler_content::replace_content};
 
 use std::sync::{Arc, Mutex};
 
-use std::net::TcpStream;
+use crate::node_connection::NodeConnection;
 
 /// Sets up the functionality for the Point of Interest (POI) button.
 ///
@@ -61,7 +60,7 @@ pub fn set_poi_button(builder: &Builder) -> Result<(), InterfaceError> {
 /// # Arguments
 ///
 /// * `builder` - The builder object containing the UI definition.
-/// * `node` - The TCP stream representing the connection to the node.
+/// * `node` - The connection to the node.
 ///
 /// # Returns
 ///
@@ -74,13 +73,14 @@ pub fn set_poi_button(builder: &Builder) -> Result<(), InterfaceError> {
 /// 1. Retrieves the "Make Proof" button, block header entry, and transaction ID entry from the builder.
 /// 2. Retrieves the necessary dialog elements for error display.
 /// 3. Connects the "Make Proof" button's click event to the corresponding action.
-/// 4. Validates the block header and transaction ID input provided by the user.
-/// 5. Sends a request to the specified node to obtain a proof of inclusion.
+/// 4. Validates the block header and transaction ID input provided by the user. The block header can be left empty.
+/// 5. Sends a request to the specified node to obtain a proof of inclusion. Without a block header, the node finds the
+///    block in its transaction index.
 /// 6. Displays the result in a dialog window.
 ///
 pub fn set_make_proof_button(
     builder: &Builder,
-    node: Arc<Mutex<TcpStream>>,
+    node: Arc<Mutex<NodeConnection>>,
 ) -> Result<(), InterfaceError> {
     let make_proof_button: Button = builder
         .get_object(MAKE_PROOF_BUTTON)
@@ -113,12 +113,12 @@ pub fn set_make_proof_button(
         let block_header_text = block_header_entry.get_text();
         let transaction_id_text = transaction_id_entry.get_text();
 
-        if !valid_block_header(block_header_text.as_str())
-            || !valid_transaction_id(transaction_id_text.as_str())
-        {
+        let valid_block =
+            block_header_text.is_empty() || valid_block_header(block_header_text.as_str());
+        if !valid_block || !valid_transaction_id(transaction_id_text.as_str()) {
             let mut auth_text = "Please complete the entries correctly".to_string();
 
-            if !valid_block_header(block_header_text.as_str()) {
+            if !valid_block {
                 auth_text += "\n \n - Block header is invalid";
             }
 
@@ -132,51 +132,27 @@ pub fn set_make_proof_button(
             user_authentication_dialog.show_all();
             return;
         }
-        let block_header: Vec<u8> = match decode_hex(&block_header_entry.get_text()) {
-            Ok(header) => header,
-            Err(_) => return,
+        let block_header: Vec<u8> = match block_header_text.as_str() {
+            "" => NULL_BLOCK_HASH.to_vec(),
+            block_header_text => match decode_hex(block_header_text) {
+                Ok(header) => header,
+                Err(_) => return,
+            },
         };
 
         let tx_id: Vec<u8> = match decode_hex(&transaction_id_entry.get_text()) {
             Ok(tx_id) => tx_id,
             Err(_) => return,
         };
-        let get_proof = GetProof::new(block_header, tx_id);
-
         let mut locked_node = match node.lock() {
             Ok(locked_node) => locked_node,
             Err(_) => return,
         };
 
-        if locked_node.write_all(&get_proof.as_bytes()).is_err() {
-            return;
-        };
-
-        let command_name = match read_string_from_bytes(&mut *locked_node, 12) {
-            Ok(command_name) => command_name,
-            Err(_) => return,
-        };
-
-        if command_name != MERKLE_BLOCK {
-            poi_error_dialog.show();
-            drop(locked_node);
-            return;
-        }
-
-        let merkle_block = match MerkleBlock::from_bytes(command_name, &mut *locked_node) {
-            Ok(merkle_block) => merkle_block,
-            Err(_) => return,
-        };
-
-        let proof_of_inclusion = match get_proof_of_inclusion(merkle_block) {
-            Ok(proof) => proof,
-            Err(_) => return,
-        };
-
-        if proof_of_inclusion {
-            poi_success_dialog.show();
-        } else {
-            poi_error_dialog.show();
+        match request_proof(&mut locked_node, block_header, tx_id) {
+            Ok(true) => poi_success_dialog.show(),
+            Ok(false) => poi_error_dialog.show(),
+            Err(_) => {}
         }
         drop(locked_node);
     });
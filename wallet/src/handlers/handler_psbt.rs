@@ -0,0 +1,293 @@
+use gtk::prelude::*;
+use gtk::{Box, Builder, Button, Entry, Label, TextView, Widget};
+use node::wallet_utils::broadcast_txn::BroadcastTxn;
+use secp256k1::SecretKey;
+use std::path::Path;
+
+use crate::accounts::Accounts;
+use crate::interface_error::InterfaceError;
+use crate::psbt::{psbt_error::PsbtError, psbt_transaction::Psbt};
+use crate::transactions::create_transactios_constants::BTC_TO_SATOSHI;
+
+use super::{handler_constants::*, handler_content::replace_content};
+
+use std::sync::{Arc, Mutex};
+
+use crate::node_connection::NodeConnection;
+
+/// Sets up the PSBT button, which shows the section where partially signed transactions are signed, combined and
+/// broadcast.
+///
+/// # Arguments
+///
+/// * `builder` - A reference to the GTK builder used to access UI elements.
+///
+/// # Returns
+///
+/// Returns `Ok(())` if the PSBT button setup is successful, or an `InterfaceError` if any required UI element is missing.
+pub fn set_psbt_button(builder: &Builder) -> Result<(), InterfaceError> {
+    let psbt_button: Button = builder
+        .get_object(PSBT_BUTTON)
+        .ok_or(InterfaceError::MissingButton)?;
+    let psbt_box: Widget = builder
+        .get_object(PSBT_BOX)
+        .ok_or(InterfaceError::MissingBox)?;
+    let content_box: Box = builder
+        .get_object(CONTENT_BOX)
+        .ok_or(InterfaceError::MissingBox)?;
+
+    psbt_button.connect_clicked(move |_| {
+        replace_content(&content_box, &psbt_box);
+        psbt_box.show_all();
+    });
+    Ok(())
+}
+
+/// Sets up the "Sign" button, which signs the inputs of the PSBT that spend outputs of the actual account.
+///
+/// # Arguments
+///
+/// * `builder` - A reference to the GTK builder used to access UI elements.
+/// * `accounts` - An Arc-wrapped Mutex for accessing account information.
+///
+/// # Returns
+///
+/// Returns `Ok(())` if the setup is successful, or an `InterfaceError` if any required UI element is missing.
+///
+/// # Description
+///
+/// The PSBT in the text view is signed with the keys of the actual account, see `Psbt::sign`, and replaced by the signed
+/// one. An account whose keystore is locked has no keys, so nothing is signed until it is unlocked.
+pub fn set_psbt_sign_button(
+    builder: &Builder,
+    accounts: Arc<Mutex<Accounts>>,
+) -> Result<(), InterfaceError> {
+    let sign_button: Button = builder
+        .get_object(PSBT_SIGN_BUTTON)
+        .ok_or(InterfaceError::MissingButton)?;
+    let (text_view, status_label) = get_psbt_widgets(builder)?;
+
+    sign_button.connect_clicked(move |_| {
+        let private_keys: Vec<SecretKey> = match accounts.lock() {
+            Ok(locked_accounts) => match locked_accounts.get_actual_account() {
+                Some(user_info) => user_info
+                    .get_private_keys()
+                    .iter()
+                    .filter_map(|private_key| SecretKey::from_slice(private_key).ok())
+                    .collect(),
+                None => return,
+            },
+            Err(_) => return,
+        };
+        let result = read_psbt(&text_view).and_then(|mut psbt| {
+            let signed = psbt.sign(&private_keys)?;
+            Ok((psbt, signed))
+        });
+        match result {
+            Ok((_, 0)) => status_label.set_text("No input can be signed by this account"),
+            Ok((psbt, signed)) => show_psbt(
+                &text_view,
+                &status_label,
+                &psbt,
+                &format!("Signed {} inputs", signed),
+            ),
+            Err(err) => status_label.set_text(&err.to_string()),
+        }
+    });
+    Ok(())
+}
+
+/// Sets up the "Combine" button, which adds the data of the PSBT in the combine entry, such as the signatures of another
+/// party, to the PSBT in the text view.
+///
+/// # Arguments
+///
+/// * `builder` - A reference to the GTK builder used to access UI elements.
+///
+/// # Returns
+///
+/// Returns `Ok(())` if the setup is successful, or an `InterfaceError` if any required UI element is missing.
+pub fn set_psbt_combine_button(builder: &Builder) -> Result<(), InterfaceError> {
+    let combine_button: Button = builder
+        .get_object(PSBT_COMBINE_BUTTON)
+        .ok_or(InterfaceError::MissingButton)?;
+    let combine_entry: Entry = builder
+        .get_object(PSBT_COMBINE_ENTRY)
+        .ok_or(InterfaceError::MissingEntry)?;
+    let (text_view, status_label) = get_psbt_widgets(builder)?;
+
+    combine_button.connect_clicked(move |_| {
+        let result = read_psbt(&text_view).and_then(|mut psbt| {
+            psbt.combine(&Psbt::from_base64(&combine_entry.get_text())?)?;
+            Ok(psbt)
+        });
+        match result {
+            Ok(psbt) => {
+                combine_entry.set_text(EMPTY);
+                show_psbt(&text_view, &status_label, &psbt, "Combined");
+            }
+            Err(err) => status_label.set_text(&err.to_string()),
+        }
+    });
+    Ok(())
+}
+
+/// Sets up the "Finalize" button, which builds the final signature script or witness of every input of the PSBT in the
+/// text view, see `Psbt::finalize`.
+///
+/// # Arguments
+///
+/// * `builder` - A reference to the GTK builder used to access UI elements.
+///
+/// # Returns
+///
+/// Returns `Ok(())` if the setup is successful, or an `InterfaceError` if any required UI element is missing.
+pub fn set_psbt_finalize_button(builder: &Builder) -> Result<(), InterfaceError> {
+    let finalize_button: Button = builder
+        .get_object(PSBT_FINALIZE_BUTTON)
+        .ok_or(InterfaceError::MissingButton)?;
+    let (text_view, status_label) = get_psbt_widgets(builder)?;
+
+    finalize_button.connect_clicked(move |_| {
+        let result = read_psbt(&text_view).and_then(|mut psbt| {
+            psbt.finalize()?;
+            Ok(psbt)
+        });
+        match result {
+            Ok(psbt) => show_psbt(&text_view, &status_label, &psbt, "Finalized"),
+            Err(err) => status_label.set_text(&err.to_string()),
+        }
+    });
+    Ok(())
+}
+
+/// Sets up the "Broadcast" button, which extracts the signed transaction of the finalized PSBT in the text view and sends
+/// it to the node with a `BroadcastTxn` message.
+///
+/// # Arguments
+///
+/// * `builder` - A reference to the GTK builder used to access UI elements.
+/// * `node` - An Arc-wrapped Mutex-wrapped connection to the node.
+///
+/// # Returns
+///
+/// Returns `Ok(())` if the setup is successful, or an `InterfaceError` if any required UI element is missing.
+pub fn set_psbt_broadcast_button(
+    builder: &Builder,
+    node: Arc<Mutex<NodeConnection>>,
+) -> Result<(), InterfaceError> {
+    let broadcast_button: Button = builder
+        .get_object(PSBT_BROADCAST_BUTTON)
+        .ok_or(InterfaceError::MissingButton)?;
+    let (text_view, status_label) = get_psbt_widgets(builder)?;
+
+    broadcast_button.connect_clicked(move |_| {
+        let transaction = match read_psbt(&text_view).and_then(|psbt| psbt.extract()) {
+            Ok(transaction) => transaction,
+            Err(err) => {
+                status_label.set_text(&err.to_string());
+                return;
+            }
+        };
+        let broadcast_txn = BroadcastTxn::new(transaction);
+        let sent = match node.lock() {
+            Ok(mut locked_node) => locked_node.broadcast(&broadcast_txn).is_ok(),
+            Err(_) => false,
+        };
+        match sent {
+            true => status_label.set_text("Transaction broadcast"),
+            false => status_label.set_text("The transaction couldn't be sent to the node"),
+        }
+    });
+    Ok(())
+}
+
+/// Sets up the "Load" and "Save" buttons, which read the PSBT in the text view from the file in the path entry, either
+/// binary or base64, and write it in the binary format used by other wallets.
+///
+/// # Arguments
+///
+/// * `builder` - A reference to the GTK builder used to access UI elements.
+///
+/// # Returns
+///
+/// Returns `Ok(())` if the setup is successful, or an `InterfaceError` if any required UI element is missing.
+pub fn set_psbt_file_buttons(builder: &Builder) -> Result<(), InterfaceError> {
+    let load_button: Button = builder
+        .get_object(PSBT_LOAD_BUTTON)
+        .ok_or(InterfaceError::MissingButton)?;
+    let save_button: Button = builder
+        .get_object(PSBT_SAVE_BUTTON)
+        .ok_or(InterfaceError::MissingButton)?;
+    let path_entry: Entry = builder
+        .get_object(PSBT_PATH_ENTRY)
+        .ok_or(InterfaceError::MissingEntry)?;
+    let (text_view, status_label) = get_psbt_widgets(builder)?;
+
+    let (view, label, entry) = (text_view.clone(), status_label.clone(), path_entry.clone());
+    load_button.connect_clicked(
+        move |_| match Psbt::open(Path::new(entry.get_text().as_str())) {
+            Ok(psbt) => show_psbt(&view, &label, &psbt, "Loaded"),
+            Err(err) => label.set_text(&err.to_string()),
+        },
+    );
+
+    save_button.connect_clicked(move |_| {
+        let result = read_psbt(&text_view)
+            .and_then(|psbt| psbt.save(Path::new(path_entry.get_text().as_str())));
+        match result {
+            Ok(()) => status_label.set_text("Saved"),
+            Err(err) => status_label.set_text(&err.to_string()),
+        }
+    });
+    Ok(())
+}
+
+/// Shows a PSBT in the text view, encoded in base64, and a summary of it in the status label.
+///
+/// # Arguments
+///
+/// * `text_view` - The text view of the PSBT section.
+/// * `status_label` - The label where the result of the last action is shown.
+/// * `psbt` - The PSBT to show.
+/// * `action` - What was done to the PSBT, shown before the summary.
+pub fn show_psbt(text_view: &TextView, status_label: &Label, psbt: &Psbt, action: &str) {
+    if let Some(buffer) = text_view.get_buffer() {
+        buffer.set_text(&psbt.to_base64());
+    }
+    let inputs = psbt.get_inputs();
+    let fee = match psbt.get_fee() {
+        Some(fee) => format!("{} BTC", fee as f64 / BTC_TO_SATOSHI),
+        None => "unknown".to_string(),
+    };
+    status_label.set_text(&format!(
+        "{}. {} inputs, {} outputs, fee: {}. {} of {} inputs finalized.",
+        action,
+        inputs.len(),
+        psbt.get_unsigned_tx().get_tx_out_list().len(),
+        fee,
+        inputs.iter().filter(|input| input.is_finalized()).count(),
+        inputs.len()
+    ));
+}
+
+/// Parses the PSBT in the text view.
+fn read_psbt(text_view: &TextView) -> Result<Psbt, PsbtError> {
+    let buffer = text_view.get_buffer().ok_or(PsbtError::InvalidBase64)?;
+    let (start, end) = buffer.get_bounds();
+    let text = buffer
+        .get_text(&start, &end, false)
+        .ok_or(PsbtError::InvalidBase64)?;
+    Psbt::from_base64(text.as_str())
+}
+
+/// Returns the text view of the PSBT section and its status label.
+fn get_psbt_widgets(builder: &Builder) -> Result<(TextView, Label), InterfaceError> {
+    let text_view: TextView = builder
+        .get_object(PSBT_TEXT_VIEW)
+        .ok_or(InterfaceError::MissingTextView)?;
+    let status_label: Label = builder
+        .get_object(PSBT_STATUS_LABEL)
+        .ok_or(InterfaceError::MissingLabel)?;
+    Ok((text_view, status_label))
+}
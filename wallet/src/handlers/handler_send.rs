@@ -1,18 +1,29 @@
-use gtk::{prelude::*, Dialog};
-use gtk::{Box, Builder, Button, Fixed, SpinButton, Widget};
+use gtk::{prelude::*, CheckButton, Dialog};
+use gtk::{Box, Builder, Button, Fixed, Label, SpinButton, TextView, Widget};
+use node::block_mod::{
+    transaction::{Transaction, SIGHASH_ALL},
+    tx_out::TxOut,
+};
+use node::messages::read_from_bytes::encode_hex;
 use node::wallet_utils::broadcast_txn::BroadcastTxn;
-use std::io::Write;
+use std::cell::RefCell;
+use std::rc::Rc;
 
 use crate::accounts::Accounts;
+use crate::psbt::psbt_transaction::Psbt;
+use crate::transactions::coin_selection::{
+    coin_selector::CoinSelector, lowest_waste::LowestWaste, manual_selection::ManualSelection,
+};
 use crate::transactions::create_transaction_error::TransactionCreateError;
-use crate::transactions::create_transactions::create_transaction;
+use crate::transactions::create_transactions::create_unsigned_transaction;
+use crate::transactions::create_transactios_constants::BTC_TO_SATOSHI;
 use crate::{interface_error::InterfaceError, views::transaction_view::create_transaction_view};
 
-use super::{handler_constants::*, handler_content::replace_content};
+use super::{handler_constants::*, handler_content::replace_content, handler_psbt::show_psbt};
 
 use std::sync::{Arc, Mutex};
 
-use std::net::TcpStream;
+use crate::node_connection::NodeConnection;
 
 /// Sets the functionality of the send button.
 ///
@@ -110,18 +121,26 @@ pub fn set_clear_all_button(builder: &Builder) -> Result<(), InterfaceError> {
     Ok(())
 }
 
+/// The outpoints chosen in the coin control window, as transaction id and output index.
+pub type CoinControl = Rc<RefCell<Vec<(Vec<u8>, u32)>>>;
+
+/// A transaction created by the "Send Transaction" button that waits for the user to confirm it, with the script of its
+/// change address.
+pub type PendingTransaction = Rc<RefCell<Option<(Transaction, Vec<u8>)>>>;
+
 /// Sets up the functionality for the "Send Transaction" button.
 ///
 /// This function connects the "Send Transaction" button to a click event handler. When clicked, it
-/// retrieves the target list, fee value, and private key from the UI elements and attempts to create
-/// a transaction. If the transaction creation is successful, it creates a new transaction view and
-/// updates the transaction box accordingly.
+/// retrieves the target list, fee rate, and private key from the UI elements and attempts to create
+/// a transaction. If the transaction creation is successful, it shows its fee in the confirmation
+/// window, which sends it.
 ///
 /// # Arguments
 ///
 /// * `builder` - The Builder object for accessing UI elements.
-/// * `node` - An Arc-wrapped Mutex-wrapped TcpStream for communication with the node.
 /// * `accounts` - An Arc-wrapped Mutex for accessing account information.
+/// * `coin_control` - The outpoints chosen in the coin control window, empty to let the wallet choose them.
+/// * `pending` - Where the created transaction is kept until the user confirms it.
 ///
 /// # Returns
 ///
@@ -132,20 +151,26 @@ pub fn set_clear_all_button(builder: &Builder) -> Result<(), InterfaceError> {
 /// This function sets up the functionality for the "Send Transaction" button by performing the following steps:
 ///
 /// 1. Retrieves the necessary UI elements from the builder, including the "Send Transaction" button,
-///    transaction box, fee spin button, and account information.
+///    transaction box, fee rate spin button, confirmation window, and account information.
 /// 2. Connects the "Send Transaction" button to a click event handler using the `connect_clicked` method.
-/// 3. In the click event handler, retrieves the target list, fee value, and private key from the UI elements.
+/// 3. In the click event handler, retrieves the target list, fee rate in satoshis per virtual byte, and private key from the UI elements.
 /// 4. Acquires a lock on the accounts Mutex to access the account information.
-/// 5. If the account information is available, attempts to create a transaction using the `create_transaction` function.
-/// 6. If the transaction creation is successful, creates a new transaction view and updates the transaction box.
-/// 7. Handles any errors that may occur during the transaction creation process, such as insufficient funds.
+/// 5. If the account information is available, attempts to create a transaction using `UserInfo::create_transaction`,
+///    sending the change to a fresh change address of the account. The outputs chosen in the coin control window are
+///    spent, or if there are none, the selection with the lowest waste among the strategies of `LowestWaste`.
+/// 6. If the transaction creation is successful, keeps it in `pending` and shows the amount sent, the fee, the effective
+///    fee rate of the signed transaction and the total in the confirmation window. Nothing is sent until it is confirmed,
+///    see `set_confirm_transaction_buttons`.
+/// 7. Handles any errors that may occur during the transaction creation process, such as insufficient funds or a mistyped address.
+///    Watch-only accounts can't sign, so nothing is created and the user is told to create a PSBT instead.
 /// 8. Drops the lock on the accounts Mutex.
 /// 9. Returns `Ok(())` if the function executes successfully.
 ///
 pub fn set_send_transaction_button(
     builder: &Builder,
-    node: Arc<Mutex<TcpStream>>,
     accounts: Arc<Mutex<Accounts>>,
+    coin_control: CoinControl,
+    pending: PendingTransaction,
 ) -> Result<(), InterfaceError> {
     let send_transaction_button: Button = builder
         .get_object(SEND_TX_BUTTON)
@@ -156,42 +181,290 @@ pub fn set_send_transaction_button(
     let spin_button_fee: SpinButton = builder
         .get_object(FEE_SPIN_BUTTON)
         .ok_or(InterfaceError::MissingSpinButton)?;
+    let confirm_dialog: Dialog = builder
+        .get_object(CONFIRM_TX_WINDOW)
+        .ok_or(InterfaceError::MissingDialog)?;
+    let confirm_label: Label = builder
+        .get_object(CONFIRM_TX_LABEL)
+        .ok_or(InterfaceError::MissingLabel)?;
     let insuficient_funds_dialog: Dialog = builder
         .get_object(INSUFFICIENT_FUNDS_DIALOG)
         .ok_or(InterfaceError::MissingDialog)?;
+    let error_dialog: Dialog = builder
+        .get_object("user_authentication_window")
+        .ok_or(InterfaceError::MissingDialog)?;
+    let title_label: Label = builder
+        .get_object("title_error_label")
+        .ok_or(InterfaceError::MissingLabel)?;
+    let advice_label: Label = builder
+        .get_object("advice_label")
+        .ok_or(InterfaceError::MissingLabel)?;
 
     send_transaction_button.connect_clicked(move |_| {
         let target_list = get_target_list(&transaction_box);
+        let amount: i64 = target_list.iter().map(|(_, amount)| amount).sum();
         if let Ok(locked_accounts) = accounts.lock() {
-            if let Some(user_info) = locked_accounts.get_actual_account() {
-                let private_key = user_info.get_private_key();
-                let fee = spin_button_fee.get_value() * 100000000.0;
-                match create_transaction(target_list, user_info.get_utxo(), private_key, fee as i64)
-                {
-                    Ok(transaction) => {
-                        if let Ok(mut locked_node) = node.lock() {
-                            let broadcast_txn = BroadcastTxn::new(transaction);
-
-                            if locked_node.write_all(&broadcast_txn.as_bytes()).is_err() {
-                                return;
-                            }
-
-                            if let Ok(new_transaction) =
-                                create_transaction_view(transaction_box.clone())
-                            {
-                                clear_and_add_widget(
-                                    &transaction_box,
-                                    new_transaction.upcast_ref(),
-                                );
-                            }
-                            drop(locked_node);
-                        }
-                    }
-                    Err(TransactionCreateError::InsufficientFounds) => {
-                        insuficient_funds_dialog.show()
+            let network = locked_accounts.get_network();
+            let fee_rate = spin_button_fee.get_value_as_int() as i64;
+            let coin_selector = get_coin_selector(&coin_control);
+            let result = locked_accounts.get_actual_account().map(|user_info| {
+                user_info.create_transaction(
+                    target_list,
+                    fee_rate,
+                    SIGHASH_ALL,
+                    network,
+                    coin_selector.as_ref(),
+                )
+            });
+            match result {
+                Some(Ok((transaction, fee, change_pk_script))) => {
+                    let vsize = transaction.get_vsize();
+                    confirm_label.set_text(&format!(
+                        "Amount: {} BTC\nFee: {} BTC ({:.2} sat/vB, {} vB)\nTotal: {} BTC",
+                        amount as f64 / BTC_TO_SATOSHI,
+                        fee as f64 / BTC_TO_SATOSHI,
+                        fee as f64 / vsize as f64,
+                        vsize,
+                        (amount + fee) as f64 / BTC_TO_SATOSHI
+                    ));
+                    pending.replace(Some((transaction, change_pk_script)));
+                    confirm_dialog.show_all();
+                }
+                Some(Err(TransactionCreateError::InsufficientFounds)) => {
+                    insuficient_funds_dialog.show()
+                }
+                Some(Err(TransactionCreateError::InvalidAddress(address, err))) => {
+                    let advice_text = format!(
+                        "Nothing was sent.\n \n The address \"{}\" is invalid: {}",
+                        address, err
+                    );
+                    title_label.set_text("Invalid Address");
+                    advice_label.set_text(advice_text.as_str());
+                    error_dialog.set_size_request(600, 200);
+                    error_dialog.show_all();
+                }
+                Some(Err(TransactionCreateError::MissingSignatures)) => {
+                    title_label.set_text("Signatures missing");
+                    advice_label.set_text(
+                        "Nothing was sent.\n \n This multisig account needs signatures of other cosigners. Create a PSBT, \
+                         sign it and combine it with theirs in the PSBT section.",
+                    );
+                    error_dialog.set_size_request(600, 200);
+                    error_dialog.show_all();
+                }
+                Some(Err(TransactionCreateError::WatchOnly)) => {
+                    title_label.set_text("Watch-only account");
+                    advice_label.set_text(
+                        "Nothing was sent.\n \n This account has no private keys. Create a PSBT and sign it in the \
+                         wallet that holds them.",
+                    );
+                    error_dialog.set_size_request(600, 200);
+                    error_dialog.show_all();
+                }
+                _ => {}
+            }
+            drop(locked_accounts);
+        }
+    });
+
+    Ok(())
+}
+
+/// Sets up the buttons of the window where the user confirms a transaction before it is sent.
+///
+/// # Arguments
+///
+/// * `builder` - The Builder object for accessing UI elements.
+/// * `node` - An Arc-wrapped Mutex-wrapped connection to the node.
+/// * `accounts` - An Arc-wrapped Mutex for accessing account information.
+/// * `coin_control` - The outpoints chosen in the coin control window, cleared once they are spent.
+/// * `pending` - The transaction waiting to be confirmed.
+///
+/// # Returns
+///
+/// Returns `Ok(())` if the function executes successfully, or an `InterfaceError` if any UI elements are missing.
+///
+/// # Description
+///
+/// The "Send" button broadcasts the pending transaction with a `BroadcastTxn` message, reserves its change address so the
+/// next transaction gets another one, clears the coin control choice and creates a new transaction view to update the
+/// transaction box. The node is unlocked before the accounts are locked, so the two locks are never held at once. The
+/// "Cancel" button drops the pending transaction, so nothing is sent and the outputs are kept.
+///
+pub fn set_confirm_transaction_buttons(
+    builder: &Builder,
+    node: Arc<Mutex<NodeConnection>>,
+    accounts: Arc<Mutex<Accounts>>,
+    coin_control: CoinControl,
+    pending: PendingTransaction,
+) -> Result<(), InterfaceError> {
+    let confirm_dialog: Dialog = builder
+        .get_object(CONFIRM_TX_WINDOW)
+        .ok_or(InterfaceError::MissingDialog)?;
+    let send_button: Button = builder
+        .get_object(CONFIRM_TX_SEND_BUTTON)
+        .ok_or(InterfaceError::MissingButton)?;
+    let cancel_button: Button = builder
+        .get_object(CONFIRM_TX_CANCEL_BUTTON)
+        .ok_or(InterfaceError::MissingButton)?;
+    let transaction_box: Box = builder
+        .get_object(TX_BOX)
+        .ok_or(InterfaceError::MissingBox)?;
+
+    let dialog = confirm_dialog.clone();
+    let pending_transaction = pending.clone();
+    send_button.connect_clicked(move |_| {
+        dialog.hide();
+        let (transaction, change_pk_script) = match pending_transaction.take() {
+            Some(pending) => pending,
+            None => return,
+        };
+        let broadcast_txn = BroadcastTxn::new(transaction);
+        let sent = match node.lock() {
+            Ok(mut locked_node) => locked_node.broadcast(&broadcast_txn).is_ok(),
+            Err(_) => false,
+        };
+        if !sent {
+            return;
+        }
+        if let Ok(mut locked_accounts) = accounts.lock() {
+            locked_accounts.reserve_change(&change_pk_script);
+        }
+        coin_control.borrow_mut().clear();
+
+        if let Ok(new_transaction) = create_transaction_view(transaction_box.clone()) {
+            clear_and_add_widget(&transaction_box, new_transaction.upcast_ref());
+        }
+    });
+
+    cancel_button.connect_clicked(move |_| {
+        pending.replace(None);
+        confirm_dialog.hide();
+    });
+    Ok(())
+}
+
+/// Sets up the "Create PSBT" button, which creates the transaction of the send form without signing it.
+///
+/// # Arguments
+///
+/// * `builder` - The Builder object for accessing UI elements.
+/// * `accounts` - An Arc-wrapped Mutex for accessing account information.
+/// * `coin_control` - The outpoints chosen in the coin control window, cleared once they are used.
+///
+/// # Returns
+///
+/// Returns `Ok(())` if the function executes successfully, or an `InterfaceError` if any UI elements are missing.
+///
+/// # Description
+///
+/// The transaction is created with `create_unsigned_transaction`, from the same targets, fee rate and outputs as the
+/// "Send Transaction" button, and wrapped in a PSBT with the outputs it spends. The PSBT is shown in the PSBT section,
+/// where it can be saved or copied to sign it elsewhere, for example on a device that holds the keys. Its change address is
+/// reserved, since the transaction may be broadcast by another wallet. Errors are shown as in the send flow.
+///
+pub fn set_create_psbt_button(
+    builder: &Builder,
+    accounts: Arc<Mutex<Accounts>>,
+    coin_control: CoinControl,
+) -> Result<(), InterfaceError> {
+    let create_psbt_button: Button = builder
+        .get_object(CREATE_PSBT_BUTTON)
+        .ok_or(InterfaceError::MissingButton)?;
+    let transaction_box: Box = builder
+        .get_object(TX_BOX)
+        .ok_or(InterfaceError::MissingBox)?;
+    let spin_button_fee: SpinButton = builder
+        .get_object(FEE_SPIN_BUTTON)
+        .ok_or(InterfaceError::MissingSpinButton)?;
+    let content_box: Box = builder
+        .get_object(CONTENT_BOX)
+        .ok_or(InterfaceError::MissingBox)?;
+    let psbt_box: Widget = builder
+        .get_object(PSBT_BOX)
+        .ok_or(InterfaceError::MissingBox)?;
+    let psbt_text_view: TextView = builder
+        .get_object(PSBT_TEXT_VIEW)
+        .ok_or(InterfaceError::MissingTextView)?;
+    let psbt_status_label: Label = builder
+        .get_object(PSBT_STATUS_LABEL)
+        .ok_or(InterfaceError::MissingLabel)?;
+    let insuficient_funds_dialog: Dialog = builder
+        .get_object(INSUFFICIENT_FUNDS_DIALOG)
+        .ok_or(InterfaceError::MissingDialog)?;
+    let error_dialog: Dialog = builder
+        .get_object("user_authentication_window")
+        .ok_or(InterfaceError::MissingDialog)?;
+    let title_label: Label = builder
+        .get_object("title_error_label")
+        .ok_or(InterfaceError::MissingLabel)?;
+    let advice_label: Label = builder
+        .get_object("advice_label")
+        .ok_or(InterfaceError::MissingLabel)?;
+
+    create_psbt_button.connect_clicked(move |_| {
+        let target_list = get_target_list(&transaction_box);
+        if let Ok(mut locked_accounts) = accounts.lock() {
+            let network = locked_accounts.get_network();
+            let result = locked_accounts.get_actual_account().map(
+                |user_info| -> Result<_, TransactionCreateError> {
+                    let change_pk_script = user_info.get_change_pk_script();
+                    let scripts = user_info.get_scripts();
+                    let (transaction, prev_outputs, _) = create_unsigned_transaction(
+                        target_list,
+                        &user_info.get_utxo(),
+                        &change_pk_script,
+                        &scripts,
+                        spin_button_fee.get_value_as_int() as i64,
+                        network,
+                        get_coin_selector(&coin_control).as_ref(),
+                    )?;
+                    let psbt = Psbt::new(transaction, &prev_outputs, &scripts, &|tx_id| {
+                        user_info.get_transaction(tx_id)
+                    });
+                    Ok((psbt, change_pk_script))
+                },
+            );
+            match result {
+                Some(Ok((Ok(psbt), change_pk_script))) => {
+                    locked_accounts.reserve_change(&change_pk_script);
+                    coin_control.borrow_mut().clear();
+                    if let Ok(new_transaction) = create_transaction_view(transaction_box.clone()) {
+                        clear_and_add_widget(&transaction_box, new_transaction.upcast_ref());
                     }
-                    _ => {}
+                    show_psbt(&psbt_text_view, &psbt_status_label, &psbt, "PSBT created");
+                    replace_content(&content_box, &psbt_box);
+                    psbt_box.show_all();
+                }
+                Some(Ok((Err(err), _))) => {
+                    title_label.set_text("Can't create the PSBT");
+                    advice_label.set_text(&err.to_string());
+                    error_dialog.set_size_request(600, 200);
+                    error_dialog.show_all();
                 }
+                Some(Err(TransactionCreateError::InsufficientFounds)) => {
+                    insuficient_funds_dialog.show()
+                }
+                Some(Err(TransactionCreateError::InvalidAddress(address, err))) => {
+                    let advice_text = format!(
+                        "Nothing was created.\n \n The address \"{}\" is invalid: {}",
+                        address, err
+                    );
+                    title_label.set_text("Invalid Address");
+                    advice_label.set_text(advice_text.as_str());
+                    error_dialog.set_size_request(600, 200);
+                    error_dialog.show_all();
+                }
+                Some(Err(TransactionCreateError::WatchOnly)) => {
+                    title_label.set_text("Watch-only account");
+                    advice_label.set_text(
+                        "Nothing was sent.\n \n This account has no private keys. Create a PSBT and sign it in the \
+                         wallet that holds them.",
+                    );
+                    error_dialog.set_size_request(600, 200);
+                    error_dialog.show_all();
+                }
+                _ => {}
             }
             drop(locked_accounts);
         }
@@ -214,6 +487,146 @@ pub fn set_insuficient_ok_button(builder: &Builder) -> Result<(), InterfaceError
     Ok(())
 }
 
+/// Sets up the coin control window, where the user chooses which outputs of the account a transaction spends.
+///
+/// # Arguments
+///
+/// * `builder` - A reference to the GTK builder.
+/// * `accounts` - An Arc-wrapped Mutex for accessing account information.
+/// * `coin_control` - The chosen outpoints, shared with the "Send Transaction" button.
+///
+/// # Returns
+///
+/// Returns `Ok(())` if the function executes successfully, or an `InterfaceError` if any UI elements are missing.
+///
+/// # Description
+///
+/// Clicking the "Coin control" button lists the UTXOs of the actual account with a check button each, checked if the
+/// output was already chosen. Chosen outputs that are no longer unspent are dropped first. Toggling a check button adds or
+/// removes its outpoint and updates the total of the chosen outputs. The "Clear" button empties the choice, so the wallet
+/// chooses the outputs again, and the "OK" button closes the window.
+///
+pub fn set_coin_control_button(
+    builder: &Builder,
+    accounts: Arc<Mutex<Accounts>>,
+    coin_control: CoinControl,
+) -> Result<(), InterfaceError> {
+    let coin_control_button: Button = builder
+        .get_object(COIN_CONTROL_BUTTON)
+        .ok_or(InterfaceError::MissingButton)?;
+    let coin_control_window: Dialog = builder
+        .get_object(COIN_CONTROL_WINDOW)
+        .ok_or(InterfaceError::MissingDialog)?;
+    let coin_control_box: Box = builder
+        .get_object(COIN_CONTROL_BOX)
+        .ok_or(InterfaceError::MissingBox)?;
+    let total_label: Label = builder
+        .get_object(COIN_CONTROL_TOTAL_LABEL)
+        .ok_or(InterfaceError::MissingLabel)?;
+    let ok_button: Button = builder
+        .get_object(COIN_CONTROL_OK_BUTTON)
+        .ok_or(InterfaceError::MissingButton)?;
+    let clear_button: Button = builder
+        .get_object(COIN_CONTROL_CLEAR_BUTTON)
+        .ok_or(InterfaceError::MissingButton)?;
+
+    let window = coin_control_window.clone();
+    let selection = coin_control.clone();
+    let label = total_label.clone();
+    coin_control_button.connect_clicked(move |_| {
+        let utxo = match accounts.lock() {
+            Ok(locked_accounts) => locked_accounts
+                .get_actual_account()
+                .map(|user_info| user_info.get_utxo())
+                .unwrap_or_default(),
+            Err(_) => return,
+        };
+        selection.borrow_mut().retain(|outpoint| {
+            utxo.iter()
+                .any(|(tx_id, index, _)| *outpoint == (tx_id.clone(), *index))
+        });
+
+        coin_control_box.foreach(|child| {
+            coin_control_box.remove(child);
+        });
+        for (tx_id, index, txout) in utxo.iter() {
+            let mut displayed_id = tx_id.clone();
+            displayed_id.reverse();
+            let check_button = CheckButton::with_label(&format!(
+                "{}:{}    {} BTC",
+                encode_hex(&displayed_id).unwrap_or_default(),
+                index,
+                txout.get_value() as f64 / BTC_TO_SATOSHI
+            ));
+            let outpoint = (tx_id.clone(), *index);
+            check_button.set_active(selection.borrow().contains(&outpoint));
+
+            let selection = selection.clone();
+            let label = label.clone();
+            let utxo = utxo.clone();
+            check_button.connect_toggled(move |check_button| {
+                if check_button.get_active() {
+                    selection.borrow_mut().push(outpoint.clone());
+                } else {
+                    selection.borrow_mut().retain(|chosen| *chosen != outpoint);
+                }
+                update_coin_control_total(&label, &selection.borrow(), &utxo);
+            });
+            coin_control_box.add(&check_button);
+        }
+        update_coin_control_total(&label, &selection.borrow(), &utxo);
+        window.show_all();
+    });
+
+    let selection = coin_control;
+    let label = total_label;
+    clear_button.connect_clicked(move |_| {
+        selection.borrow_mut().clear();
+        coin_control_window.hide();
+        update_coin_control_total(&label, &[], &[]);
+    });
+
+    let window: Dialog = builder
+        .get_object(COIN_CONTROL_WINDOW)
+        .ok_or(InterfaceError::MissingDialog)?;
+    ok_button.connect_clicked(move |_| {
+        window.hide();
+    });
+    Ok(())
+}
+
+/// Returns the strategy that chooses the outputs a transaction spends: the outputs chosen in the coin control window, or if
+/// there are none, the selection with the lowest waste among the strategies of `LowestWaste`.
+fn get_coin_selector(coin_control: &CoinControl) -> std::boxed::Box<dyn CoinSelector> {
+    if coin_control.borrow().is_empty() {
+        std::boxed::Box::new(LowestWaste::default())
+    } else {
+        std::boxed::Box::new(ManualSelection::new(coin_control.borrow().clone()))
+    }
+}
+
+/// Shows the number and total value of the outputs chosen in the coin control window.
+fn update_coin_control_total(
+    label: &Label,
+    chosen: &[(Vec<u8>, u32)],
+    utxo: &[(Vec<u8>, u32, TxOut)],
+) {
+    if chosen.is_empty() {
+        label.set_text("No outputs selected, the wallet chooses them");
+        return;
+    }
+    let total: i64 = utxo
+        .iter()
+        .filter(|(tx_id, index, _)| chosen.contains(&(tx_id.clone(), *index)))
+        .map(|(_, _, txout)| txout.get_value())
+        .sum();
+    label.set_text(&format!(
+        "{} outputs selected: {} BTC",
+        chosen.len(),
+        total as f64 / BTC_TO_SATOSHI
+    ));
+}
+
 /// Clears the contents of a GTK box and adds a new widget to it.
 ///
 /// # Arguments
@@ -0,0 +1,5 @@
+pub mod bip32;
+pub mod bip39;
+pub mod derivation_path;
+pub mod hd_wallet_constants;
+pub mod hd_wallet_error;
@@ -0,0 +1,511 @@
+use bitcoin_hashes::{hash160, hmac, sha256d, sha512, Hash, HashEngine};
+use node::network::bitcoin_network::BitcoinNetwork;
+use secp256k1::{PublicKey, Scalar, Secp256k1, SecretKey};
+use zeroize::{Zeroize, Zeroizing};
+
+use super::{
+    derivation_path::DerivationPath,
+    hd_wallet_constants::{EXTENDED_KEY_LEN, HARDENED_OFFSET, MASTER_KEY_HMAC_KEY},
+    hd_wallet_error::HdWalletError,
+};
+
+/// Computes HMAC-SHA512 of `data` with the given key.
+pub fn hmac_sha512(key: &[u8], data: &[u8]) -> [u8; 64] {
+    let mut engine = hmac::HmacEngine::<sha512::Hash>::new(key);
+    engine.input(data);
+    hmac::Hmac::<sha512::Hash>::from_engine(engine).to_byte_array()
+}
+
+/// Splits the result of the HMAC of a derivation step into the key tweak and the chain code.
+fn split_hmac(hmac: [u8; 64]) -> Result<(Scalar, [u8; 32]), HdWalletError> {
+    let mut tweak = [0; 32];
+    let mut chain_code = [0; 32];
+    tweak.copy_from_slice(&hmac[..32]);
+    chain_code.copy_from_slice(&hmac[32..]);
+
+    let tweak = Scalar::from_be_bytes(tweak).map_err(|_| HdWalletError::InvalidChildKey)?;
+    Ok((tweak, chain_code))
+}
+
+/// Serializes the fields shared by extended private and public keys in the 78 bytes format of BIP32.
+fn serialize_extended_key(
+    version: [u8; 4],
+    depth: u8,
+    parent_fingerprint: [u8; 4],
+    child_number: u32,
+    chain_code: &[u8; 32],
+    key: &[u8],
+) -> Vec<u8> {
+    let mut data = Vec::with_capacity(EXTENDED_KEY_LEN);
+    data.extend(version);
+    data.push(depth);
+    data.extend(parent_fingerprint);
+    data.extend(child_number.to_be_bytes());
+    data.extend(chain_code);
+    data.extend(key);
+    data
+}
+
+/// Encodes a serialized extended key with Base58Check.
+fn encode_extended_key(data: &[u8]) -> String {
+    let checksum = &sha256d::Hash::hash(data)[..4];
+    bs58::encode([data, checksum].concat()).into_string()
+}
+
+/// Represents a BIP32 extended private key, a private key together with the chain code used to derive its children.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtendedPrivateKey {
+    network: BitcoinNetwork,
+    depth: u8,
+    parent_fingerprint: [u8; 4],
+    child_number: u32,
+    chain_code: [u8; 32],
+    private_key: SecretKey,
+}
+
+impl ExtendedPrivateKey {
+    /// Creates the master key of a seed.
+    ///
+    /// # Arguments
+    ///
+    /// * `seed` - The seed, usually the 64 bytes derived from a BIP39 mnemonic.
+    /// * `network` - The network whose version bytes are used when the key is encoded.
+    ///
+    /// # Returns
+    ///
+    /// A Result containing the master key, the HMAC-SHA512 of the seed with the key "Bitcoin seed", or an `HdWalletError` if
+    /// the resulting key is invalid.
+    pub fn new_master(
+        seed: &[u8],
+        network: BitcoinNetwork,
+    ) -> Result<ExtendedPrivateKey, HdWalletError> {
+        let hmac = hmac_sha512(MASTER_KEY_HMAC_KEY, seed);
+        let mut chain_code = [0; 32];
+        chain_code.copy_from_slice(&hmac[32..]);
+
+        Ok(ExtendedPrivateKey {
+            network,
+            depth: 0,
+            parent_fingerprint: [0; 4],
+            child_number: 0,
+            chain_code,
+            private_key: SecretKey::from_slice(&hmac[..32])?,
+        })
+    }
+
+    /// Derives a child key.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index of the child. Indexes from `HARDENED_OFFSET` on derive hardened keys.
+    ///
+    /// # Returns
+    ///
+    /// A Result containing the child key, or `HdWalletError::InvalidChildKey` in the (negligible) case the derived key is
+    /// invalid, in which BIP32 says the next index should be used.
+    ///
+    /// # Description
+    ///
+    /// The HMAC-SHA512 with the chain code as key is calculated over the private key for hardened children, or over the
+    /// compressed public key for normal ones, followed by the index. Its left half is added to the private key and the right
+    /// half is the chain code of the child.
+    pub fn derive_child(&self, index: u32) -> Result<ExtendedPrivateKey, HdWalletError> {
+        let data = if index >= HARDENED_OFFSET {
+            [
+                &[0],
+                &self.private_key.secret_bytes()[..],
+                &index.to_be_bytes(),
+            ]
+            .concat()
+        } else {
+            [&self.get_public_key()[..], &index.to_be_bytes()].concat()
+        };
+        let (tweak, chain_code) = split_hmac(hmac_sha512(&self.chain_code, &data))?;
+        let private_key = self
+            .private_key
+            .add_tweak(&tweak)
+            .map_err(|_| HdWalletError::InvalidChildKey)?;
+
+        Ok(ExtendedPrivateKey {
+            network: self.network,
+            depth: self.depth.wrapping_add(1),
+            parent_fingerprint: self.get_fingerprint(),
+            child_number: index,
+            chain_code,
+            private_key,
+        })
+    }
+
+    /// Derives the descendant of the key at the given path, relative to this key.
+    pub fn derive_path(&self, path: &DerivationPath) -> Result<ExtendedPrivateKey, HdWalletError> {
+        path.get_indexes()
+            .iter()
+            .try_fold(self.clone(), |key, index| key.derive_child(*index))
+    }
+
+    /// Returns the 32 bytes of the private key.
+    pub fn get_private_key(&self) -> Vec<u8> {
+        self.private_key.secret_bytes().to_vec()
+    }
+
+    /// Returns the compressed public key.
+    pub fn get_public_key(&self) -> Vec<u8> {
+        PublicKey::from_secret_key(&Secp256k1::new(), &self.private_key)
+            .serialize()
+            .to_vec()
+    }
+
+    /// Returns the first 4 bytes of the hash160 of the public key, which identify the key in its children.
+    pub fn get_fingerprint(&self) -> [u8; 4] {
+        let mut fingerprint = [0; 4];
+        fingerprint.copy_from_slice(&hash160::Hash::hash(&self.get_public_key())[..4]);
+        fingerprint
+    }
+
+    /// Returns the extended public key with the same chain code, which derives the public keys of the normal children.
+    pub fn get_extended_public_key(&self) -> ExtendedPublicKey {
+        ExtendedPublicKey {
+            network: self.network,
+            depth: self.depth,
+            parent_fingerprint: self.parent_fingerprint,
+            child_number: self.child_number,
+            chain_code: self.chain_code,
+            public_key: PublicKey::from_secret_key(&Secp256k1::new(), &self.private_key),
+        }
+    }
+
+    /// Serializes the key in the 78 bytes format of BIP32, whose version bytes identify the network.
+    pub fn as_bytes(&self) -> Vec<u8> {
+        serialize_extended_key(
+            self.network.get_xprv_prefix(),
+            self.depth,
+            self.parent_fingerprint,
+            self.child_number,
+            &self.chain_code,
+            &[&[0], &self.private_key.secret_bytes()[..]].concat(),
+        )
+    }
+
+    /// Parses a key serialized with `as_bytes`.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The 78 bytes of the serialized key.
+    ///
+    /// # Returns
+    ///
+    /// A Result containing the key, or `HdWalletError::InvalidExtendedKey` if the length or the version bytes are wrong or
+    /// the private key is invalid.
+    pub fn from_bytes(bytes: &[u8]) -> Result<ExtendedPrivateKey, HdWalletError> {
+        if bytes.len() != EXTENDED_KEY_LEN || bytes[45] != 0 {
+            return Err(HdWalletError::InvalidExtendedKey);
+        }
+        let network = [BitcoinNetwork::Mainnet, BitcoinNetwork::Testnet]
+            .into_iter()
+            .find(|network| network.get_xprv_prefix() == bytes[..4])
+            .ok_or(HdWalletError::InvalidExtendedKey)?;
+
+        let mut parent_fingerprint = [0; 4];
+        parent_fingerprint.copy_from_slice(&bytes[5..9]);
+        let mut child_number = [0; 4];
+        child_number.copy_from_slice(&bytes[9..13]);
+        let mut chain_code = [0; 32];
+        chain_code.copy_from_slice(&bytes[13..45]);
+
+        Ok(ExtendedPrivateKey {
+            network,
+            depth: bytes[4],
+            parent_fingerprint,
+            child_number: u32::from_be_bytes(child_number),
+            chain_code,
+            private_key: SecretKey::from_slice(&bytes[46..])
+                .map_err(|_| HdWalletError::InvalidExtendedKey)?,
+        })
+    }
+
+    /// Encodes the key in the Base58Check format of BIP32 (xprv on mainnet, tprv on testnet).
+    pub fn encode(&self) -> String {
+        encode_extended_key(&Zeroizing::new(self.as_bytes()))
+    }
+}
+
+impl Drop for ExtendedPrivateKey {
+    /// Overwrites the private key and the chain code, so they don't stay in memory once the key is no longer used.
+    fn drop(&mut self) {
+        self.private_key.non_secure_erase();
+        self.chain_code.zeroize();
+    }
+}
+
+/// Represents a BIP32 extended public key, which can derive the public keys of the normal children but no private key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtendedPublicKey {
+    network: BitcoinNetwork,
+    depth: u8,
+    parent_fingerprint: [u8; 4],
+    child_number: u32,
+    chain_code: [u8; 32],
+    public_key: PublicKey,
+}
+
+impl ExtendedPublicKey {
+    /// Derives a normal child public key.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index of the child, lower than `HARDENED_OFFSET`.
+    ///
+    /// # Returns
+    ///
+    /// A Result containing the child key, or `HdWalletError::HardenedFromPublicKey` if the index is hardened.
+    pub fn derive_child(&self, index: u32) -> Result<ExtendedPublicKey, HdWalletError> {
+        if index >= HARDENED_OFFSET {
+            return Err(HdWalletError::HardenedFromPublicKey);
+        }
+        let data = [&self.get_public_key()[..], &index.to_be_bytes()].concat();
+        let (tweak, chain_code) = split_hmac(hmac_sha512(&self.chain_code, &data))?;
+        let public_key = self
+            .public_key
+            .add_exp_tweak(&Secp256k1::new(), &tweak)
+            .map_err(|_| HdWalletError::InvalidChildKey)?;
+
+        Ok(ExtendedPublicKey {
+            network: self.network,
+            depth: self.depth.wrapping_add(1),
+            parent_fingerprint: self.get_fingerprint(),
+            child_number: index,
+            chain_code,
+            public_key,
+        })
+    }
+
+    /// Returns the compressed public key.
+    pub fn get_public_key(&self) -> Vec<u8> {
+        self.public_key.serialize().to_vec()
+    }
+
+    /// Returns the first 4 bytes of the hash160 of the public key.
+    pub fn get_fingerprint(&self) -> [u8; 4] {
+        let mut fingerprint = [0; 4];
+        fingerprint.copy_from_slice(&hash160::Hash::hash(&self.get_public_key())[..4]);
+        fingerprint
+    }
+
+    pub fn get_network(&self) -> BitcoinNetwork {
+        self.network
+    }
+
+    /// Serializes the key in the 78 bytes format of BIP32, whose version bytes identify the network.
+    pub fn as_bytes(&self) -> Vec<u8> {
+        serialize_extended_key(
+            self.network.get_xpub_prefix(),
+            self.depth,
+            self.parent_fingerprint,
+            self.child_number,
+            &self.chain_code,
+            &self.get_public_key(),
+        )
+    }
+
+    /// Parses a key serialized with `as_bytes`.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The 78 bytes of the serialized key.
+    ///
+    /// # Returns
+    ///
+    /// A Result containing the key, or `HdWalletError::InvalidExtendedKey` if the length or the version bytes are wrong or
+    /// the public key is invalid.
+    pub fn from_bytes(bytes: &[u8]) -> Result<ExtendedPublicKey, HdWalletError> {
+        if bytes.len() != EXTENDED_KEY_LEN {
+            return Err(HdWalletError::InvalidExtendedKey);
+        }
+        let network = [BitcoinNetwork::Mainnet, BitcoinNetwork::Testnet]
+            .into_iter()
+            .find(|network| network.get_xpub_prefix() == bytes[..4])
+            .ok_or(HdWalletError::InvalidExtendedKey)?;
+
+        let mut parent_fingerprint = [0; 4];
+        parent_fingerprint.copy_from_slice(&bytes[5..9]);
+        let mut child_number = [0; 4];
+        child_number.copy_from_slice(&bytes[9..13]);
+        let mut chain_code = [0; 32];
+        chain_code.copy_from_slice(&bytes[13..45]);
+
+        Ok(ExtendedPublicKey {
+            network,
+            depth: bytes[4],
+            parent_fingerprint,
+            child_number: u32::from_be_bytes(child_number),
+            chain_code,
+            public_key: PublicKey::from_slice(&bytes[45..])
+                .map_err(|_| HdWalletError::InvalidExtendedKey)?,
+        })
+    }
+
+    /// Parses a key encoded in the Base58Check format of BIP32, such as an xpub exported by another wallet.
+    ///
+    /// # Returns
+    ///
+    /// A Result containing the key, or `HdWalletError::InvalidExtendedKey` if it isn't valid Base58Check or isn't an
+    /// extended public key.
+    pub fn decode(encoded: &str) -> Result<ExtendedPublicKey, HdWalletError> {
+        let data = bs58::decode(encoded.trim())
+            .into_vec()
+            .map_err(|_| HdWalletError::InvalidExtendedKey)?;
+        if data.len() != EXTENDED_KEY_LEN + 4 {
+            return Err(HdWalletError::InvalidExtendedKey);
+        }
+        let (bytes, checksum) = data.split_at(EXTENDED_KEY_LEN);
+        if sha256d::Hash::hash(bytes)[..4] != *checksum {
+            return Err(HdWalletError::InvalidExtendedKey);
+        }
+        ExtendedPublicKey::from_bytes(bytes)
+    }
+
+    /// Encodes the key in the Base58Check format of BIP32 (xpub on mainnet, tpub on testnet).
+    pub fn encode(&self) -> String {
+        encode_extended_key(&self.as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod bip32_test {
+    use std::str::FromStr;
+
+    use node::{messages::read_from_bytes::decode_hex, network::bitcoin_network::BitcoinNetwork};
+
+    use super::*;
+    use crate::hd_wallet::bip39::seed_from_mnemonic;
+
+    #[test]
+    fn test_vector_1() -> Result<(), HdWalletError> {
+        // Test vector 1 of BIP32
+        let seed = decode_hex("000102030405060708090a0b0c0d0e0f")
+            .map_err(|_| HdWalletError::InvalidKey)?;
+        let master = ExtendedPrivateKey::new_master(&seed, BitcoinNetwork::Mainnet)?;
+
+        let vectors = [
+            (
+                "m",
+                "xpub661MyMwAqRbcFtXgS5sYJABqqG9YLmC4Q1Rdap9gSE8NqtwybGhePY2gZ29ESFjqJoCu1Rupje8YtGqsefD265TMg7usUDFdp6W1EGMcet8",
+                "xprv9s21ZrQH143K3QTDL4LXw2F7HEK3wJUD2nW2nRk4stbPy6cq3jPPqjiChkVvvNKmPGJxWUtg6LnF5kejMRNNU3TGtRBeJgk33yuGBxrMPHi",
+            ),
+            (
+                "m/0'",
+                "xpub68Gmy5EdvgibQVfPdqkBBCHxA5htiqg55crXYuXoQRKfDBFA1WEjWgP6LHhwBZeNK1VTsfTFUHCdrfp1bgwQ9xv5ski8PX9rL2dZXvgGDnw",
+                "xprv9uHRZZhk6KAJC1avXpDAp4MDc3sQKNxDiPvvkX8Br5ngLNv1TxvUxt4cV1rGL5hj6KCesnDYUhd7oWgT11eZG7XnxHrnYeSvkzY7d2bhkJ7",
+            ),
+            (
+                "m/0'/1",
+                "xpub6ASuArnXKPbfEwhqN6e3mwBcDTgzisQN1wXN9BJcM47sSikHjJf3UFHKkNAWbWMiGj7Wf5uMash7SyYq527Hqck2AxYysAA7xmALppuCkwQ",
+                "xprv9wTYmMFdV23N2TdNG573QoEsfRrWKQgWeibmLntzniatZvR9BmLnvSxqu53Kw1UmYPxLgboyZQaXwTCg8MSY3H2EU4pWcQDnRnrVA1xe8fs",
+            ),
+            (
+                "m/0'/1/2'",
+                "xpub6D4BDPcP2GT577Vvch3R8wDkScZWzQzMMUm3PWbmWvVJrZwQY4VUNgqFJPMM3No2dFDFGTsxxpG5uJh7n7epu4trkrX7x7DogT5Uv6fcLW5",
+                "xprv9z4pot5VBttmtdRTWfWQmoH1taj2axGVzFqSb8C9xaxKymcFzXBDptWmT7FwuEzG3ryjH4ktypQSAewRiNMjANTtpgP4mLTj34bhnZX7UiM",
+            ),
+        ];
+
+        for (path, xpub, xprv) in vectors {
+            let key = master.derive_path(&DerivationPath::from_str(path)?)?;
+            assert_eq!(key.encode(), xprv);
+            assert_eq!(key.get_extended_public_key().encode(), xpub);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_serialized_key_roundtrip() -> Result<(), HdWalletError> {
+        let seed = decode_hex("000102030405060708090a0b0c0d0e0f")
+            .map_err(|_| HdWalletError::InvalidKey)?;
+        let key = ExtendedPrivateKey::new_master(&seed, BitcoinNetwork::Testnet)?
+            .derive_path(&DerivationPath::from_str("m/84'/1'/0'")?)?;
+        let bytes = key.as_bytes();
+
+        assert_eq!(bytes.len(), EXTENDED_KEY_LEN);
+        assert_eq!(ExtendedPrivateKey::from_bytes(&bytes), Ok(key));
+        assert_eq!(
+            ExtendedPrivateKey::from_bytes(&bytes[1..]),
+            Err(HdWalletError::InvalidExtendedKey)
+        );
+        assert_eq!(
+            ExtendedPrivateKey::from_bytes(&[&[0; 4], &bytes[4..]].concat()),
+            Err(HdWalletError::InvalidExtendedKey)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_public_derivation_matches_private_derivation() -> Result<(), HdWalletError> {
+        let seed = seed_from_mnemonic(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+            "",
+        )?;
+        let account = ExtendedPrivateKey::new_master(&seed, BitcoinNetwork::Testnet)?
+            .derive_path(&DerivationPath::from_str("m/84'/1'/0'")?)?;
+        let account_xpub = account.get_extended_public_key();
+
+        for index in 0..3 {
+            assert_eq!(
+                account
+                    .derive_child(0)?
+                    .derive_child(index)?
+                    .get_public_key(),
+                account_xpub
+                    .derive_child(0)?
+                    .derive_child(index)?
+                    .get_public_key()
+            );
+        }
+        assert_eq!(
+            account_xpub.derive_child(HARDENED_OFFSET),
+            Err(HdWalletError::HardenedFromPublicKey)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_extended_public_key() {
+        // Test vector 1 of BIP32, m/0'/1
+        let xpub = "xpub6ASuArnXKPbfEwhqN6e3mwBcDTgzisQN1wXN9BJcM47sSikHjJf3UFHKkNAWbWMiGj7Wf5uMash7SyYq527Hqck2AxYysAA7xmALppuCkwQ";
+        let key = ExtendedPublicKey::decode(xpub);
+
+        assert_eq!(
+            key.as_ref().map(ExtendedPublicKey::encode),
+            Ok(xpub.to_string())
+        );
+        assert_eq!(
+            key.map(|key| key.get_network()),
+            Ok(BitcoinNetwork::Mainnet)
+        );
+        assert_eq!(
+            ExtendedPublicKey::decode(&xpub.replace('E', "F")),
+            Err(HdWalletError::InvalidExtendedKey)
+        );
+        assert_eq!(
+            ExtendedPublicKey::decode("xprv9wTYmMFdV23N2TdNG573QoEsfRrWKQgWeibmLntzniatZvR9BmLnvSxqu53Kw1UmYPxLgboyZQaXwTCg8MSY3H2EU4pWcQDnRnrVA1xe8fs"),
+            Err(HdWalletError::InvalidExtendedKey)
+        );
+    }
+
+    #[test]
+    fn test_bip84_first_address_key() -> Result<(), HdWalletError> {
+        // First receive key of the BIP84 test vector, m/84'/0'/0'/0/0
+        let seed = seed_from_mnemonic(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+            "",
+        )?;
+        let key = ExtendedPrivateKey::new_master(&seed, BitcoinNetwork::Mainnet)?
+            .derive_path(&DerivationPath::from_str("m/84'/0'/0'/0/0")?)?;
+
+        assert_eq!(
+            key.get_public_key(),
+            decode_hex("0330d54fd0dd420a6e5f8d3624f5f3482cae350f79d5f0753bf5beef9c2d91af3c")
+                .map_err(|_| HdWalletError::InvalidKey)?
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,217 @@
+use bitcoin_hashes::{sha256, Hash};
+use rand::RngCore;
+
+use super::{
+    bip32::hmac_sha512,
+    hd_wallet_constants::{
+        BIP39_WORDLIST, BIP39_WORD_BITS, MNEMONIC_SALT, NEW_MNEMONIC_WORDS, PBKDF2_ROUNDS,
+    },
+    hd_wallet_error::HdWalletError,
+};
+
+/// Returns the 2048 words of the English BIP39 wordlist, in order.
+fn get_wordlist() -> Vec<&'static str> {
+    BIP39_WORDLIST.lines().collect()
+}
+
+/// Encodes entropy as a mnemonic sentence.
+///
+/// # Arguments
+///
+/// * `entropy` - Between 16 and 32 bytes of entropy, a multiple of 4.
+///
+/// # Returns
+///
+/// A Result containing the words separated by spaces, or `HdWalletError::InvalidEntropy` if the length is invalid.
+///
+/// # Description
+///
+/// The first `entropy.len() / 4` bits of the sha256 of the entropy are appended as checksum, and the resulting bits are split
+/// in groups of 11, each one the index of a word of the wordlist.
+pub fn mnemonic_from_entropy(entropy: &[u8]) -> Result<String, HdWalletError> {
+    if !(16..=32).contains(&entropy.len()) || !entropy.len().is_multiple_of(4) {
+        return Err(HdWalletError::InvalidEntropy);
+    }
+    let wordlist = get_wordlist();
+    let checksum = sha256::Hash::hash(entropy).to_byte_array();
+    let bits_len = entropy.len() * 8 + entropy.len() / 4;
+    let data = [entropy, &checksum].concat();
+
+    let words: Vec<&str> = (0..bits_len / BIP39_WORD_BITS)
+        .map(|word| {
+            let index = (0..BIP39_WORD_BITS).fold(0, |index, i| {
+                let bit = word * BIP39_WORD_BITS + i;
+                (index << 1) | ((data[bit / 8] >> (7 - bit % 8)) & 1) as usize
+            });
+            wordlist[index]
+        })
+        .collect();
+    Ok(words.join(" "))
+}
+
+/// Generates a new random mnemonic sentence.
+///
+/// # Returns
+///
+/// A mnemonic of `NEW_MNEMONIC_WORDS` words, encoding 128 bits of entropy from the thread random generator.
+pub fn generate_mnemonic() -> Result<String, HdWalletError> {
+    let mut entropy = vec![0; NEW_MNEMONIC_WORDS * 4 / 3];
+    rand::thread_rng().fill_bytes(&mut entropy);
+    mnemonic_from_entropy(&entropy)
+}
+
+/// Decodes a mnemonic sentence back into its entropy.
+///
+/// # Arguments
+///
+/// * `mnemonic` - The words of the mnemonic, separated by whitespace. Case is ignored.
+///
+/// # Returns
+///
+/// A Result containing the entropy, or an `HdWalletError` if the number of words is invalid, a word isn't in the wordlist or
+/// the checksum doesn't match.
+pub fn entropy_from_mnemonic(mnemonic: &str) -> Result<Vec<u8>, HdWalletError> {
+    let wordlist = get_wordlist();
+    let words: Vec<String> = mnemonic
+        .split_whitespace()
+        .map(|word| word.to_lowercase())
+        .collect();
+    if !(12..=24).contains(&words.len()) || !words.len().is_multiple_of(3) {
+        return Err(HdWalletError::InvalidMnemonicLength);
+    }
+
+    let mut bits: Vec<bool> = vec![];
+    for word in words.iter() {
+        let index = wordlist
+            .binary_search(&word.as_str())
+            .map_err(|_| HdWalletError::UnknownWord(word.to_string()))?;
+        bits.extend((0..BIP39_WORD_BITS).rev().map(|i| (index >> i) & 1 == 1));
+    }
+
+    let checksum_len = bits.len() / 33;
+    let (entropy_bits, checksum_bits) = bits.split_at(bits.len() - checksum_len);
+    let entropy: Vec<u8> = entropy_bits
+        .chunks(8)
+        .map(|byte| byte.iter().fold(0, |acc, bit| (acc << 1) | *bit as u8))
+        .collect();
+
+    let checksum = sha256::Hash::hash(&entropy).to_byte_array();
+    let valid = checksum_bits
+        .iter()
+        .enumerate()
+        .all(|(i, bit)| ((checksum[i / 8] >> (7 - i % 8)) & 1 == 1) == *bit);
+    if !valid {
+        return Err(HdWalletError::InvalidMnemonicChecksum);
+    }
+    Ok(entropy)
+}
+
+/// Derives the BIP39 seed of a mnemonic sentence.
+///
+/// # Arguments
+///
+/// * `mnemonic` - The mnemonic sentence, which is validated first.
+/// * `passphrase` - The optional passphrase, empty if not used.
+///
+/// # Returns
+///
+/// A Result containing the 64 bytes seed, or an `HdWalletError` if the mnemonic is invalid.
+///
+/// # Description
+///
+/// The seed is PBKDF2-HMAC-SHA512 of the lowercase words joined by single spaces, with `"mnemonic" + passphrase` as salt and
+/// 2048 rounds. The English wordlist is ASCII, so its NFKD normalization is the same text; the passphrase is used as given.
+pub fn seed_from_mnemonic(mnemonic: &str, passphrase: &str) -> Result<[u8; 64], HdWalletError> {
+    entropy_from_mnemonic(mnemonic)?;
+    let words: Vec<String> = mnemonic
+        .split_whitespace()
+        .map(|word| word.to_lowercase())
+        .collect();
+    let salt = format!("{}{}", MNEMONIC_SALT, passphrase);
+
+    Ok(pbkdf2_hmac_sha512(
+        words.join(" ").as_bytes(),
+        salt.as_bytes(),
+        PBKDF2_ROUNDS,
+    ))
+}
+
+/// Computes the first 64 bytes block of PBKDF2 with HMAC-SHA512, which is the whole output BIP39 needs.
+fn pbkdf2_hmac_sha512(password: &[u8], salt: &[u8], rounds: u32) -> [u8; 64] {
+    let mut block = hmac_sha512(password, &[salt, &1_u32.to_be_bytes()].concat());
+    let mut result = block;
+
+    for _ in 1..rounds {
+        block = hmac_sha512(password, &block);
+        for (result_byte, block_byte) in result.iter_mut().zip(block.iter()) {
+            *result_byte ^= block_byte;
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod bip39_test {
+    use node::messages::read_from_bytes::{decode_hex, encode_hex};
+    use std::num::ParseIntError;
+
+    use super::*;
+
+    #[test]
+    fn test_mnemonic_vectors() -> Result<(), ParseIntError> {
+        // Vectors from the BIP39 reference implementation, all with the passphrase "TREZOR"
+        let vectors = [
+            (
+                "00000000000000000000000000000000",
+                "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+                "c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04",
+            ),
+            (
+                "7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f",
+                "legal winner thank year wave sausage worth useful legal winner thank yellow",
+                "2e8905819b8723fe2c1d161860e5ee1830318dbf49a83bd451cfb8440c28bd6fa457fe1296106559a3c80937a1c1069be3a3a5bd381ee6260e8d9739fce1f607",
+            ),
+            (
+                "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                "zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo vote",
+                "dd48c104698c30cfe2b6142103248622fb7bb0ff692eebb00089b32d22484e1613912f0a5b694407be899ffd31ed3992c456cdf60f5d4564b8ba3f05a69890ad",
+            ),
+        ];
+
+        for (entropy, mnemonic, seed) in vectors {
+            let entropy = decode_hex(entropy)?;
+            assert_eq!(mnemonic_from_entropy(&entropy).as_deref(), Ok(mnemonic));
+            assert_eq!(entropy_from_mnemonic(mnemonic), Ok(entropy));
+            let calculated_seed =
+                seed_from_mnemonic(mnemonic, "TREZOR").map(|seed| encode_hex(&seed));
+            assert!(matches!(calculated_seed, Ok(Ok(calculated)) if calculated == seed));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_mnemonics() {
+        assert_eq!(
+            entropy_from_mnemonic("abandon abandon abandon"),
+            Err(HdWalletError::InvalidMnemonicLength)
+        );
+        assert_eq!(
+            entropy_from_mnemonic("abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon"),
+            Err(HdWalletError::InvalidMnemonicChecksum)
+        );
+        assert_eq!(
+            entropy_from_mnemonic("abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon bitcoin"),
+            Err(HdWalletError::UnknownWord("bitcoin".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_generated_mnemonic_is_valid() {
+        let mnemonic = generate_mnemonic();
+
+        assert!(matches!(&mnemonic, Ok(words) if words.split(' ').count() == NEW_MNEMONIC_WORDS));
+        assert!(mnemonic
+            .and_then(|words| entropy_from_mnemonic(&words))
+            .is_ok());
+    }
+}
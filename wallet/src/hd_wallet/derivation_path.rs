@@ -0,0 +1,167 @@
+use std::str::FromStr;
+
+use node::network::bitcoin_network::BitcoinNetwork;
+
+use super::{
+    hd_wallet_constants::{BIP44_PURPOSE, BIP84_PURPOSE, HARDENED_OFFSET},
+    hd_wallet_error::HdWalletError,
+};
+use crate::transactions::{
+    address::Address,
+    create_transactions::{pk_script_from_public_key, witness_pk_script_from_public_key},
+};
+
+/// The standards an HD account can follow, which define its derivation path and the kind of its addresses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AddressPurpose {
+    /// BIP44, legacy P2PKH addresses.
+    Bip44,
+    /// BIP84, native SegWit P2WPKH addresses.
+    Bip84,
+}
+
+impl AddressPurpose {
+    /// Returns the purpose level of the derivation path, 44 or 84.
+    pub fn get_purpose(&self) -> u32 {
+        match self {
+            AddressPurpose::Bip44 => BIP44_PURPOSE,
+            AddressPurpose::Bip84 => BIP84_PURPOSE,
+        }
+    }
+
+    /// Returns the address of the kind of this purpose for a public key.
+    pub fn get_address(&self, public_key: &[u8], network: BitcoinNetwork) -> Address {
+        match self {
+            AddressPurpose::Bip44 => Address::from_public_key(public_key, network),
+            AddressPurpose::Bip84 => Address::p2wpkh_from_public_key(public_key, network),
+        }
+    }
+
+    /// Returns the output script of the kind of this purpose for a public key.
+    pub fn get_pk_script(&self, public_key: &[u8]) -> Vec<u8> {
+        match self {
+            AddressPurpose::Bip44 => pk_script_from_public_key(public_key),
+            AddressPurpose::Bip84 => witness_pk_script_from_public_key(public_key),
+        }
+    }
+}
+
+/// Represents a BIP32 derivation path, the list of child indexes from the master key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DerivationPath {
+    indexes: Vec<u32>,
+}
+
+impl DerivationPath {
+    pub fn new(indexes: Vec<u32>) -> DerivationPath {
+        DerivationPath { indexes }
+    }
+
+    /// Builds the path of an account, `m/purpose'/coin_type'/account'`.
+    ///
+    /// # Arguments
+    ///
+    /// * `purpose` - The standard followed by the account.
+    /// * `network` - The network, which defines the coin type (0 on mainnet, 1 on testnet).
+    /// * `account` - The index of the account.
+    ///
+    /// # Returns
+    ///
+    /// The hardened path of the account, whose children 0 and 1 are the receive and change chains.
+    pub fn account(
+        purpose: AddressPurpose,
+        network: BitcoinNetwork,
+        account: u32,
+    ) -> DerivationPath {
+        DerivationPath::new(vec![
+            purpose.get_purpose() + HARDENED_OFFSET,
+            network.get_coin_type() + HARDENED_OFFSET,
+            account + HARDENED_OFFSET,
+        ])
+    }
+
+    pub fn get_indexes(&self) -> &Vec<u32> {
+        &self.indexes
+    }
+}
+
+impl FromStr for DerivationPath {
+    type Err = HdWalletError;
+
+    /// Parses a path such as `m/84'/1'/0'/0/5`, where hardened indexes end in `'` or `h`.
+    fn from_str(path: &str) -> Result<DerivationPath, HdWalletError> {
+        let mut levels = path.split('/');
+        if levels.next() != Some("m") {
+            return Err(HdWalletError::InvalidDerivationPath);
+        }
+
+        let indexes = levels
+            .map(|level| {
+                let (number, offset) = match level.strip_suffix(['\'', 'h']) {
+                    Some(number) => (number, HARDENED_OFFSET),
+                    None => (level, 0),
+                };
+                match number.parse::<u32>() {
+                    Ok(index) if index < HARDENED_OFFSET => Ok(index + offset),
+                    _ => Err(HdWalletError::InvalidDerivationPath),
+                }
+            })
+            .collect::<Result<Vec<u32>, HdWalletError>>()?;
+
+        Ok(DerivationPath::new(indexes))
+    }
+}
+
+impl std::fmt::Display for DerivationPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "m")?;
+        for index in self.indexes.iter() {
+            if *index >= HARDENED_OFFSET {
+                write!(f, "/{}'", index - HARDENED_OFFSET)?;
+            } else {
+                write!(f, "/{}", index)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod derivation_path_test {
+    use super::*;
+
+    #[test]
+    fn test_parse_derivation_path() {
+        let path = DerivationPath::from_str("m/84'/1h/0'/0/5");
+
+        assert_eq!(
+            path,
+            Ok(DerivationPath::new(vec![
+                84 + HARDENED_OFFSET,
+                1 + HARDENED_OFFSET,
+                HARDENED_OFFSET,
+                0,
+                5
+            ]))
+        );
+        assert_eq!(
+            path.map(|path| path.to_string()),
+            Ok("m/84'/1'/0'/0/5".to_string())
+        );
+        assert!(DerivationPath::from_str("84'/0'").is_err());
+        assert!(DerivationPath::from_str("m/2147483648").is_err());
+        assert!(DerivationPath::from_str("m/a").is_err());
+    }
+
+    #[test]
+    fn test_account_path() {
+        assert_eq!(
+            DerivationPath::account(AddressPurpose::Bip44, BitcoinNetwork::Mainnet, 0).to_string(),
+            "m/44'/0'/0'"
+        );
+        assert_eq!(
+            DerivationPath::account(AddressPurpose::Bip84, BitcoinNetwork::Testnet, 2).to_string(),
+            "m/84'/1'/2'"
+        );
+    }
+}
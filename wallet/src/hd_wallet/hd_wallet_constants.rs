@@ -0,0 +1,15 @@
+pub const BIP39_WORDLIST: &str = include_str!("bip39_english.txt");
+pub const BIP39_WORD_BITS: usize = 11;
+pub const MNEMONIC_SALT: &str = "mnemonic";
+pub const PBKDF2_ROUNDS: u32 = 2048;
+pub const NEW_MNEMONIC_WORDS: usize = 12;
+
+pub const MASTER_KEY_HMAC_KEY: &[u8] = b"Bitcoin seed";
+pub const HARDENED_OFFSET: u32 = 0x80000000;
+pub const EXTENDED_KEY_LEN: usize = 78;
+
+pub const BIP44_PURPOSE: u32 = 44;
+pub const BIP84_PURPOSE: u32 = 84;
+pub const RECEIVE_CHAIN: u32 = 0;
+pub const CHANGE_CHAIN: u32 = 1;
+pub const GAP_LIMIT: usize = 20;
@@ -0,0 +1,50 @@
+#[derive(Debug, PartialEq, Eq)]
+pub enum HdWalletError {
+    InvalidEntropy,
+    InvalidMnemonicLength,
+    UnknownWord(String),
+    InvalidMnemonicChecksum,
+    InvalidDerivationPath,
+    InvalidChildKey,
+    HardenedFromPublicKey,
+    InvalidKey,
+    InvalidExtendedKey,
+}
+
+impl From<secp256k1::Error> for HdWalletError {
+    fn from(_: secp256k1::Error) -> HdWalletError {
+        HdWalletError::InvalidKey
+    }
+}
+
+impl std::fmt::Display for HdWalletError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            HdWalletError::InvalidEntropy => {
+                write!(f, "the entropy must have 16 to 32 bytes, multiple of 4")
+            }
+            HdWalletError::InvalidMnemonicLength => {
+                write!(
+                    f,
+                    "the recovery phrase must have 12, 15, 18, 21 or 24 words"
+                )
+            }
+            HdWalletError::UnknownWord(word) => {
+                write!(f, "\"{}\" is not a recovery phrase word", word)
+            }
+            HdWalletError::InvalidMnemonicChecksum => {
+                write!(
+                    f,
+                    "the checksum of the recovery phrase doesn't match, check for typos"
+                )
+            }
+            HdWalletError::InvalidDerivationPath => write!(f, "the derivation path is invalid"),
+            HdWalletError::InvalidChildKey => write!(f, "the derived key is invalid"),
+            HdWalletError::HardenedFromPublicKey => {
+                write!(f, "hardened keys can't be derived from a public key")
+            }
+            HdWalletError::InvalidKey => write!(f, "the key is invalid"),
+            HdWalletError::InvalidExtendedKey => write!(f, "the extended key is invalid"),
+        }
+    }
+}
@@ -1,3 +1,5 @@
+use node::wallet_utils::error_response::ErrorCode;
+
 #[derive(Debug)]
 pub enum InterfaceError {
     MissingButton,
@@ -24,9 +26,13 @@ pub enum InterfaceError {
     InvalidResponse,
     MissingProgressBar,
     MissingSpinner,
+    MissingTextView,
     UpdateRecentTransactions,
+    Connect,
+    NodeError(ErrorCode),
 }
 
+#[cfg(feature = "gui")]
 impl From<glib::Error> for InterfaceError {
     fn from(_: glib::Error) -> InterfaceError {
         InterfaceError::LoadCssFile
@@ -0,0 +1,4 @@
+pub mod encrypted_keystore;
+pub mod keystore_constants;
+pub mod keystore_entry;
+pub mod keystore_error;
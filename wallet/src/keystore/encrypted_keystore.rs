@@ -0,0 +1,371 @@
+use std::{
+    fs,
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+use chacha20poly1305::{
+    aead::{Aead, KeyInit, Payload},
+    ChaCha20Poly1305, Key, Nonce,
+};
+use node::messages::read_from_bytes::{
+    read_u32_from_bytes, read_u8_from_bytes, read_vec_from_bytes,
+};
+use rand::RngCore;
+use zeroize::Zeroizing;
+
+use super::{
+    keystore_constants::{
+        KEYSTORE_MAGIC, KEYSTORE_VERSION, KEY_LEN, MIN_PASSPHRASE_LEN, NONCE_LEN, SALT_LEN,
+        SCRYPT_LOG_N, SCRYPT_P, SCRYPT_R,
+    },
+    keystore_entry::KeystoreEntry,
+    keystore_error::KeystoreError,
+};
+
+/// Represents the file where the accounts of the wallet are saved, encrypted with a key derived from a passphrase.
+///
+/// The file is `header || ciphertext`, where the header is the magic bytes, the version, the scrypt parameters, the salt
+/// and the nonce, and the ciphertext is the ChaCha20-Poly1305 encryption of the entries with the header as associated
+/// data, so neither of them can be modified without the decryption failing.
+///
+/// While unlocked, the keystore keeps the derived key in memory to save new accounts without asking for the passphrase
+/// again. Locking it overwrites the key.
+#[derive(Debug)]
+pub struct Keystore {
+    path: PathBuf,
+    log_n: u8,
+    salt: [u8; SALT_LEN],
+    key: Option<Zeroizing<[u8; KEY_LEN]>>,
+}
+
+impl Keystore {
+    pub fn new(path: &Path) -> Keystore {
+        Keystore {
+            path: path.to_path_buf(),
+            log_n: SCRYPT_LOG_N,
+            salt: [0; SALT_LEN],
+            key: None,
+        }
+    }
+
+    /// Returns whether the keystore file was already created.
+    pub fn exists(&self) -> bool {
+        self.path.exists()
+    }
+
+    pub fn is_unlocked(&self) -> bool {
+        self.key.is_some()
+    }
+
+    /// Prepares a new keystore, which is written on the first call to `save`.
+    ///
+    /// # Arguments
+    ///
+    /// * `passphrase` - The passphrase that will be needed to unlock the keystore, of at least `MIN_PASSPHRASE_LEN` characters.
+    ///
+    /// # Returns
+    ///
+    /// A Result with `KeystoreError::ShortPassphrase` if the passphrase is too short.
+    pub fn create(&mut self, passphrase: &str) -> Result<(), KeystoreError> {
+        if passphrase.chars().count() < MIN_PASSPHRASE_LEN {
+            return Err(KeystoreError::ShortPassphrase);
+        }
+        rand::thread_rng().fill_bytes(&mut self.salt);
+        self.key = Some(derive_key(
+            passphrase, &self.salt, self.log_n, SCRYPT_R, SCRYPT_P,
+        )?);
+        Ok(())
+    }
+
+    /// Decrypts the keystore file.
+    ///
+    /// # Arguments
+    ///
+    /// * `passphrase` - The passphrase the keystore was created with.
+    ///
+    /// # Returns
+    ///
+    /// A Result containing the saved accounts, or a `KeystoreError` if the file can't be read, the passphrase is wrong or
+    /// the file was modified. On success the keystore stays unlocked.
+    pub fn unlock(&mut self, passphrase: &str) -> Result<Vec<KeystoreEntry>, KeystoreError> {
+        let file = fs::read(&self.path)?;
+        let mut stream = &file[..];
+
+        if read_vec_from_bytes(&mut stream, KEYSTORE_MAGIC.len())? != KEYSTORE_MAGIC {
+            return Err(KeystoreError::InvalidFormat);
+        }
+        if read_u8_from_bytes(&mut stream)? != KEYSTORE_VERSION {
+            return Err(KeystoreError::UnsupportedVersion);
+        }
+        let log_n = read_u8_from_bytes(&mut stream)?;
+        let r = read_u32_from_bytes(&mut stream, true)?;
+        let p = read_u32_from_bytes(&mut stream, true)?;
+        let mut salt = [0; SALT_LEN];
+        salt.copy_from_slice(&read_vec_from_bytes(&mut stream, SALT_LEN)?);
+        let nonce = read_vec_from_bytes(&mut stream, NONCE_LEN)?;
+        let header = &file[..file.len() - stream.len()];
+
+        let key = derive_key(passphrase, &salt, log_n, r, p)?;
+        let plaintext = Zeroizing::new(
+            ChaCha20Poly1305::new(Key::from_slice(&key[..]))
+                .decrypt(
+                    Nonce::from_slice(&nonce),
+                    Payload {
+                        msg: stream,
+                        aad: header,
+                    },
+                )
+                .map_err(|_| KeystoreError::WrongPassphrase)?,
+        );
+
+        let entries = read_entries(&mut &plaintext[..])?;
+        self.log_n = log_n;
+        self.salt = salt;
+        self.key = Some(key);
+        Ok(entries)
+    }
+
+    /// Encrypts the accounts and writes them to the keystore file, replacing its content.
+    ///
+    /// # Arguments
+    ///
+    /// * `entries` - All the accounts of the wallet.
+    ///
+    /// # Returns
+    ///
+    /// A Result with `KeystoreError::Locked` if the keystore wasn't created or unlocked, or another `KeystoreError` if the
+    /// file can't be written.
+    ///
+    /// # Description
+    ///
+    /// A new random nonce is used every time the file is written. The file is first written to a temporary file and then
+    /// renamed, so a failure while writing doesn't lose the saved accounts.
+    pub fn save(&self, entries: &[KeystoreEntry]) -> Result<(), KeystoreError> {
+        let key = self.key.as_ref().ok_or(KeystoreError::Locked)?;
+
+        let mut nonce = [0; NONCE_LEN];
+        rand::thread_rng().fill_bytes(&mut nonce);
+        let mut header = KEYSTORE_MAGIC.to_vec();
+        header.push(KEYSTORE_VERSION);
+        header.push(self.log_n);
+        header.extend(SCRYPT_R.to_le_bytes());
+        header.extend(SCRYPT_P.to_le_bytes());
+        header.extend(self.salt);
+        header.extend(nonce);
+
+        let mut plaintext = Zeroizing::new((entries.len() as u32).to_le_bytes().to_vec());
+        for entry in entries {
+            plaintext.extend(entry.as_bytes().iter());
+        }
+        let ciphertext = ChaCha20Poly1305::new(Key::from_slice(&key[..]))
+            .encrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: &plaintext,
+                    aad: &header,
+                },
+            )
+            .map_err(|_| KeystoreError::Encryption)?;
+
+        let temporary_path = self.path.with_extension("tmp");
+        fs::write(&temporary_path, [header, ciphertext].concat())?;
+        fs::rename(&temporary_path, &self.path)?;
+        Ok(())
+    }
+
+    /// Forgets the derived key, so the keystore can't be saved until it is unlocked again.
+    pub fn lock(&mut self) {
+        self.key = None;
+    }
+}
+
+/// Derives the encryption key of a keystore from its passphrase with scrypt.
+fn derive_key(
+    passphrase: &str,
+    salt: &[u8],
+    log_n: u8,
+    r: u32,
+    p: u32,
+) -> Result<Zeroizing<[u8; KEY_LEN]>, KeystoreError> {
+    let params =
+        scrypt::Params::new(log_n, r, p, KEY_LEN).map_err(|_| KeystoreError::InvalidFormat)?;
+    let mut key = Zeroizing::new([0; KEY_LEN]);
+    scrypt::scrypt(passphrase.as_bytes(), salt, &params, &mut key[..])
+        .map_err(|_| KeystoreError::InvalidFormat)?;
+    Ok(key)
+}
+
+/// Reads the entries of a decrypted keystore, preceded by their count.
+fn read_entries(stream: &mut dyn Read) -> Result<Vec<KeystoreEntry>, KeystoreError> {
+    let count = read_u32_from_bytes(stream, true)?;
+    (0..count)
+        .map(|_| KeystoreEntry::from_bytes(stream))
+        .collect()
+}
+
+#[cfg(test)]
+mod encrypted_keystore_test {
+    use std::env;
+
+    use node::network::bitcoin_network::BitcoinNetwork;
+    use secp256k1::{PublicKey, Secp256k1, SecretKey};
+
+    use super::*;
+    use crate::{
+        hd_wallet::{bip32::ExtendedPrivateKey, derivation_path::AddressPurpose},
+        keystore::keystore_entry::{AccountSecret, WatchedKey},
+        multisig::{
+            multisig_constants::MAX_MULTISIG_KEYS,
+            multisig_script::{MultisigKind, MultisigScript},
+        },
+    };
+
+    /// Creates a keystore in the temporary directory with a low scrypt cost, so the tests run fast.
+    fn test_keystore(name: &str) -> Keystore {
+        let path = env::temp_dir().join(format!("{}_{}.keystore", name, std::process::id()));
+        let _ = fs::remove_file(&path);
+        Keystore {
+            log_n: 4,
+            ..Keystore::new(&path)
+        }
+    }
+
+    fn test_entries() -> Result<Vec<KeystoreEntry>, KeystoreError> {
+        let account_key = ExtendedPrivateKey::new_master(&[7; 32], BitcoinNetwork::Testnet)
+            .map_err(|_| KeystoreError::InvalidFormat)?;
+        let account_public_key = account_key.get_extended_public_key();
+        let secp = Secp256k1::new();
+        let public_keys = (1..=3)
+            .map(|byte| {
+                let private_key = SecretKey::from_slice(&[byte; 32])?;
+                Ok(PublicKey::from_secret_key(&secp, &private_key)
+                    .serialize()
+                    .to_vec())
+            })
+            .collect::<Result<Vec<Vec<u8>>, secp256k1::Error>>()
+            .map_err(|_| KeystoreError::InvalidFormat)?;
+        let multisig =
+            MultisigScript::new(2, public_keys).map_err(|_| KeystoreError::InvalidFormat)?;
+        Ok(vec![
+            KeystoreEntry::new(
+                "single".to_string(),
+                AccountSecret::Key {
+                    public_key: vec![2; 33],
+                    private_key: Zeroizing::new(vec![1; 32]),
+                },
+            ),
+            KeystoreEntry::new(
+                "hd".to_string(),
+                AccountSecret::Hd {
+                    account_key,
+                    purpose: AddressPurpose::Bip84,
+                },
+            ),
+            KeystoreEntry::new(
+                "multisig".to_string(),
+                AccountSecret::Multisig {
+                    multisig,
+                    kind: MultisigKind::P2wsh,
+                    private_keys: Zeroizing::new(vec![vec![1; 32]]),
+                },
+            ),
+            KeystoreEntry::new(
+                "watched".to_string(),
+                AccountSecret::WatchOnly(WatchedKey::Extended {
+                    account_public_key,
+                    purpose: AddressPurpose::Bip44,
+                }),
+            ),
+            KeystoreEntry::new(
+                "treasury".to_string(),
+                AccountSecret::WatchOnly(WatchedKey::Address(vec![0; 22])),
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_save_and_unlock() -> Result<(), KeystoreError> {
+        let mut keystore = test_keystore("save_and_unlock");
+        let entries = test_entries()?;
+
+        assert_eq!(keystore.save(&entries), Err(KeystoreError::Locked));
+        assert_eq!(
+            keystore.create("short"),
+            Err(KeystoreError::ShortPassphrase)
+        );
+        keystore.create("correct horse")?;
+        keystore.save(&entries)?;
+        keystore.lock();
+
+        let file = fs::read(&keystore.path)?;
+        assert!(!file.windows(32).any(|window| window == [1; 32]));
+        assert_eq!(
+            keystore.unlock("wrong horse"),
+            Err(KeystoreError::WrongPassphrase)
+        );
+        assert!(!keystore.is_unlocked());
+        assert_eq!(keystore.unlock("correct horse")?, entries);
+        assert!(keystore.is_unlocked());
+
+        fs::remove_file(&keystore.path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_save_and_unlock_the_largest_multisig() -> Result<(), KeystoreError> {
+        let mut keystore = test_keystore("largest_multisig");
+        let secp = Secp256k1::new();
+        let public_keys = (1..=MAX_MULTISIG_KEYS as u8)
+            .map(|byte| {
+                let private_key = SecretKey::from_slice(&[byte; 32])?;
+                Ok(PublicKey::from_secret_key(&secp, &private_key)
+                    .serialize()
+                    .to_vec())
+            })
+            .collect::<Result<Vec<Vec<u8>>, secp256k1::Error>>()
+            .map_err(|_| KeystoreError::InvalidFormat)?;
+        let multisig = MultisigScript::new(MAX_MULTISIG_KEYS, public_keys)
+            .map_err(|_| KeystoreError::InvalidFormat)?;
+        assert!(multisig.as_bytes().len() > 255);
+        let mut entries = test_entries()?;
+        entries.insert(
+            0,
+            KeystoreEntry::new(
+                "largest".to_string(),
+                AccountSecret::Multisig {
+                    multisig,
+                    kind: MultisigKind::P2wsh,
+                    private_keys: Zeroizing::new(vec![vec![1; 32], vec![2; 32]]),
+                },
+            ),
+        );
+
+        keystore.create("correct horse")?;
+        keystore.save(&entries)?;
+        keystore.lock();
+        assert_eq!(keystore.unlock("correct horse")?, entries);
+
+        fs::remove_file(&keystore.path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_modified_keystore_is_rejected() -> Result<(), KeystoreError> {
+        let mut keystore = test_keystore("modified");
+        keystore.create("correct horse")?;
+        keystore.save(&test_entries()?)?;
+
+        let mut file = fs::read(&keystore.path)?;
+        let last = file.len() - 1;
+        file[last] ^= 1;
+        fs::write(&keystore.path, &file)?;
+
+        assert_eq!(
+            keystore.unlock("correct horse"),
+            Err(KeystoreError::WrongPassphrase)
+        );
+        fs::remove_file(&keystore.path)?;
+        Ok(())
+    }
+}
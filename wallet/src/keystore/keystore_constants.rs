@@ -0,0 +1,25 @@
+pub const KEYSTORE_MAGIC: &[u8; 4] = b"RKST";
+pub const KEYSTORE_VERSION: u8 = 1;
+pub const MAINNET_KEYSTORE_PATH: &str = "mainnet_accounts.keystore";
+pub const TESTNET_KEYSTORE_PATH: &str = "testnet_accounts.keystore";
+
+pub const SALT_LEN: usize = 16;
+pub const NONCE_LEN: usize = 12;
+pub const KEY_LEN: usize = 32;
+pub const SCRYPT_LOG_N: u8 = 15;
+pub const SCRYPT_R: u32 = 8;
+pub const SCRYPT_P: u32 = 1;
+pub const MIN_PASSPHRASE_LEN: usize = 8;
+
+pub const KEY_ACCOUNT: u8 = 0;
+pub const HD_ACCOUNT: u8 = 1;
+pub const MULTISIG_ACCOUNT: u8 = 2;
+pub const P2SH_MULTISIG: u8 = 0;
+pub const P2WSH_MULTISIG: u8 = 1;
+pub const WATCH_ONLY_ACCOUNT: u8 = 3;
+pub const WATCHED_ADDRESS: u8 = 0;
+pub const WATCHED_PUBLIC_KEY: u8 = 1;
+pub const WATCHED_EXTENDED_KEY: u8 = 2;
+
+pub const AUTO_LOCK_SECONDS: u64 = 300;
+pub const AUTO_LOCK_CHECK_SECONDS: u32 = 10;
@@ -0,0 +1,242 @@
+use std::io::Read;
+
+use node::messages::{
+    compact_size::CompactSizeUInt,
+    read_from_bytes::{read_u32_from_bytes, read_u8_from_bytes, read_vec_from_bytes},
+};
+use zeroize::Zeroizing;
+
+use super::{
+    keystore_constants::{
+        HD_ACCOUNT, KEY_ACCOUNT, MULTISIG_ACCOUNT, P2SH_MULTISIG, P2WSH_MULTISIG, WATCHED_ADDRESS,
+        WATCHED_EXTENDED_KEY, WATCHED_PUBLIC_KEY, WATCH_ONLY_ACCOUNT,
+    },
+    keystore_error::KeystoreError,
+};
+use crate::hd_wallet::{
+    bip32::{ExtendedPrivateKey, ExtendedPublicKey},
+    derivation_path::AddressPurpose,
+    hd_wallet_constants::EXTENDED_KEY_LEN,
+};
+use crate::multisig::multisig_script::{MultisigKind, MultisigScript};
+
+/// The secret an account needs to sign transactions, the only part of it stored encrypted on disk.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AccountSecret {
+    /// A single imported key pair.
+    Key {
+        public_key: Vec<u8>,
+        private_key: Zeroizing<Vec<u8>>,
+    },
+    /// The BIP32 account key `m/purpose'/coin_type'/0'` of an HD account.
+    Hd {
+        account_key: ExtendedPrivateKey,
+        purpose: AddressPurpose,
+    },
+    /// An m-of-n multisig script and the keys of it held by this wallet, which may be none.
+    Multisig {
+        multisig: MultisigScript,
+        kind: MultisigKind,
+        private_keys: Zeroizing<Vec<Vec<u8>>>,
+    },
+    /// What a watch-only account follows. It has nothing secret, but it is saved with the other accounts so it is
+    /// restored when the wallet is unlocked.
+    WatchOnly(WatchedKey),
+}
+
+/// The public data a watch-only account is created from, which shows its balance and history but can't sign.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WatchedKey {
+    /// The output script of a single address.
+    Address(Vec<u8>),
+    /// A compressed public key, whose P2PKH and P2WPKH addresses are followed.
+    PublicKey(Vec<u8>),
+    /// The BIP32 account public key of an HD account, from which its addresses are derived.
+    Extended {
+        account_public_key: ExtendedPublicKey,
+        purpose: AddressPurpose,
+    },
+}
+
+/// Represents an account saved in the keystore: its name and its secret.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeystoreEntry {
+    name: String,
+    secret: AccountSecret,
+}
+
+impl KeystoreEntry {
+    pub fn new(name: String, secret: AccountSecret) -> KeystoreEntry {
+        KeystoreEntry { name, secret }
+    }
+
+    pub fn get_name(&self) -> &String {
+        &self.name
+    }
+
+    pub fn get_secret(&self) -> &AccountSecret {
+        &self.secret
+    }
+
+    /// Serializes the entry as `name length || name || kind || secret`, where the secret of a single key account is its
+    /// public key preceded by its length and the 32 bytes of the private key, the one of an HD account is its purpose
+    /// and the 78 bytes BIP32 serialization of the account key, and the one of a multisig account is its kind, its script
+    /// preceded by its length as a compact size, as it can be longer than 255 bytes, and its private keys preceded by
+    /// their count. A watch-only account saves the kind of what
+    /// it watches followed by the script or the public key preceded by its length, or the purpose and the 78 bytes BIP32
+    /// serialization of the account public key.
+    pub fn as_bytes(&self) -> Zeroizing<Vec<u8>> {
+        let mut bytes = Zeroizing::new(vec![self.name.len() as u8]);
+        bytes.extend(self.name.as_bytes());
+
+        match &self.secret {
+            AccountSecret::Key {
+                public_key,
+                private_key,
+            } => {
+                bytes.push(KEY_ACCOUNT);
+                bytes.push(public_key.len() as u8);
+                bytes.extend(public_key);
+                bytes.extend(private_key.iter());
+            }
+            AccountSecret::Hd {
+                account_key,
+                purpose,
+            } => {
+                bytes.push(HD_ACCOUNT);
+                bytes.extend(purpose.get_purpose().to_le_bytes());
+                bytes.extend(Zeroizing::new(account_key.as_bytes()).iter());
+            }
+            AccountSecret::Multisig {
+                multisig,
+                kind,
+                private_keys,
+            } => {
+                bytes.push(MULTISIG_ACCOUNT);
+                bytes.push(match kind {
+                    MultisigKind::P2sh => P2SH_MULTISIG,
+                    MultisigKind::P2wsh => P2WSH_MULTISIG,
+                });
+                let script = multisig.as_bytes();
+                bytes.extend(CompactSizeUInt::from_number(script.len() as u64).as_bytes());
+                bytes.extend(script);
+                bytes.push(private_keys.len() as u8);
+                for private_key in private_keys.iter() {
+                    bytes.extend(private_key);
+                }
+            }
+            AccountSecret::WatchOnly(watched) => {
+                bytes.push(WATCH_ONLY_ACCOUNT);
+                match watched {
+                    WatchedKey::Address(pk_script) => {
+                        bytes.push(WATCHED_ADDRESS);
+                        bytes.push(pk_script.len() as u8);
+                        bytes.extend(pk_script);
+                    }
+                    WatchedKey::PublicKey(public_key) => {
+                        bytes.push(WATCHED_PUBLIC_KEY);
+                        bytes.push(public_key.len() as u8);
+                        bytes.extend(public_key);
+                    }
+                    WatchedKey::Extended {
+                        account_public_key,
+                        purpose,
+                    } => {
+                        bytes.push(WATCHED_EXTENDED_KEY);
+                        bytes.extend(purpose.get_purpose().to_le_bytes());
+                        bytes.extend(account_public_key.as_bytes());
+                    }
+                }
+            }
+        }
+        bytes
+    }
+
+    /// Parses an entry serialized with `as_bytes`.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream` - The decrypted content of the keystore, positioned at the start of the entry.
+    ///
+    /// # Returns
+    ///
+    /// A Result containing the entry, or `KeystoreError::InvalidFormat` if it can't be parsed.
+    pub fn from_bytes(stream: &mut dyn Read) -> Result<KeystoreEntry, KeystoreError> {
+        let name_len = read_u8_from_bytes(stream)?;
+        let name = String::from_utf8(read_vec_from_bytes(stream, name_len as usize)?)
+            .map_err(|_| KeystoreError::InvalidFormat)?;
+
+        let secret = match read_u8_from_bytes(stream)? {
+            KEY_ACCOUNT => {
+                let public_key_len = read_u8_from_bytes(stream)?;
+                AccountSecret::Key {
+                    public_key: read_vec_from_bytes(stream, public_key_len as usize)?,
+                    private_key: Zeroizing::new(read_vec_from_bytes(stream, 32)?),
+                }
+            }
+            HD_ACCOUNT => {
+                let purpose = read_purpose(stream)?;
+                let account_key = Zeroizing::new(read_vec_from_bytes(stream, EXTENDED_KEY_LEN)?);
+                AccountSecret::Hd {
+                    account_key: ExtendedPrivateKey::from_bytes(&account_key)
+                        .map_err(|_| KeystoreError::InvalidFormat)?,
+                    purpose,
+                }
+            }
+            MULTISIG_ACCOUNT => {
+                let kind = match read_u8_from_bytes(stream)? {
+                    P2SH_MULTISIG => MultisigKind::P2sh,
+                    P2WSH_MULTISIG => MultisigKind::P2wsh,
+                    _ => return Err(KeystoreError::InvalidFormat),
+                };
+                let script_len = CompactSizeUInt::from_bytes(stream)?.value();
+                let multisig =
+                    MultisigScript::from_bytes(&read_vec_from_bytes(stream, script_len as usize)?)
+                        .map_err(|_| KeystoreError::InvalidFormat)?;
+                let mut private_keys = Zeroizing::new(vec![]);
+                for _ in 0..read_u8_from_bytes(stream)? {
+                    private_keys.push(read_vec_from_bytes(stream, 32)?);
+                }
+                AccountSecret::Multisig {
+                    multisig,
+                    kind,
+                    private_keys,
+                }
+            }
+            WATCH_ONLY_ACCOUNT => AccountSecret::WatchOnly(match read_u8_from_bytes(stream)? {
+                WATCHED_ADDRESS => {
+                    let pk_script_len = read_u8_from_bytes(stream)?;
+                    WatchedKey::Address(read_vec_from_bytes(stream, pk_script_len as usize)?)
+                }
+                WATCHED_PUBLIC_KEY => {
+                    let public_key_len = read_u8_from_bytes(stream)?;
+                    WatchedKey::PublicKey(read_vec_from_bytes(stream, public_key_len as usize)?)
+                }
+                WATCHED_EXTENDED_KEY => {
+                    let purpose = read_purpose(stream)?;
+                    WatchedKey::Extended {
+                        account_public_key: ExtendedPublicKey::from_bytes(&read_vec_from_bytes(
+                            stream,
+                            EXTENDED_KEY_LEN,
+                        )?)
+                        .map_err(|_| KeystoreError::InvalidFormat)?,
+                        purpose,
+                    }
+                }
+                _ => return Err(KeystoreError::InvalidFormat),
+            }),
+            _ => return Err(KeystoreError::InvalidFormat),
+        };
+
+        Ok(KeystoreEntry { name, secret })
+    }
+}
+
+/// Reads the purpose of an HD account, saved as its purpose level.
+fn read_purpose(stream: &mut dyn Read) -> Result<AddressPurpose, KeystoreError> {
+    match read_u32_from_bytes(stream, true)? {
+        purpose if purpose == AddressPurpose::Bip44.get_purpose() => Ok(AddressPurpose::Bip44),
+        purpose if purpose == AddressPurpose::Bip84.get_purpose() => Ok(AddressPurpose::Bip84),
+        _ => Err(KeystoreError::InvalidFormat),
+    }
+}
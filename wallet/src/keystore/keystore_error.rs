@@ -0,0 +1,47 @@
+use node::messages::message_error::MessageError;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum KeystoreError {
+    Io,
+    NotFound,
+    Locked,
+    WrongPassphrase,
+    ShortPassphrase,
+    InvalidFormat,
+    UnsupportedVersion,
+    Encryption,
+}
+
+impl From<std::io::Error> for KeystoreError {
+    fn from(err: std::io::Error) -> KeystoreError {
+        match err.kind() {
+            std::io::ErrorKind::NotFound => KeystoreError::NotFound,
+            _ => KeystoreError::Io,
+        }
+    }
+}
+
+impl From<MessageError> for KeystoreError {
+    fn from(_: MessageError) -> KeystoreError {
+        KeystoreError::InvalidFormat
+    }
+}
+
+impl std::fmt::Display for KeystoreError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            KeystoreError::Io => write!(f, "the keystore file can't be read or written"),
+            KeystoreError::NotFound => write!(f, "there is no keystore file"),
+            KeystoreError::Locked => write!(f, "the wallet is locked"),
+            KeystoreError::WrongPassphrase => {
+                write!(f, "wrong password, or the keystore file was modified")
+            }
+            KeystoreError::ShortPassphrase => write!(f, "the password is too short"),
+            KeystoreError::InvalidFormat => write!(f, "the keystore file is corrupted"),
+            KeystoreError::UnsupportedVersion => {
+                write!(f, "the keystore file was created by a newer version")
+            }
+            KeystoreError::Encryption => write!(f, "the accounts can't be encrypted"),
+        }
+    }
+}
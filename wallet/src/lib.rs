@@ -1,10 +1,21 @@
 pub mod accounts;
+pub mod cli;
+#[cfg(feature = "gui")]
 pub mod handlers;
+pub mod hd_wallet;
 pub mod interface_error;
+pub mod keystore;
+pub mod multisig;
+pub mod node_connection;
 pub mod proof_of_inclusion;
+pub mod psbt;
 pub mod transactions;
+pub mod tx_cache;
+#[cfg(feature = "gui")]
 pub mod update_progress;
 pub mod update_wallet;
 pub mod user_info;
+#[cfg(feature = "gui")]
 pub mod views;
+pub mod wallet_address;
 pub mod wallet_constants;
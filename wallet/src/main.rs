@@ -3,8 +3,9 @@ use glib::Priority;
 use glib::Type;
 use gtk::Builder;
 use gtk::ListStore;
+use node::network::bitcoin_network::BitcoinNetwork;
 use std::net::SocketAddr;
-use std::net::TcpStream;
+use std::str::FromStr;
 use std::sync::mpsc;
 use std::sync::mpsc::Receiver;
 use std::sync::mpsc::Sender;
@@ -14,6 +15,7 @@ use std::thread;
 use wallet::accounts::Accounts;
 use wallet::handlers::button_setting::set_buttons;
 use wallet::handlers::handler_windows::set_windows;
+use wallet::node_connection::NodeConnection;
 use wallet::transactions::transaction_view::update_wallet_interface;
 use wallet::update_progress::update_progress;
 use wallet::update_progress::update_progress_bar_view;
@@ -21,16 +23,27 @@ use wallet::update_wallet::update_wallet;
 use wallet::wallet_constants::NODE_IP;
 use wallet::wallet_constants::NODE_PORT;
 fn main() {
+    let network = match std::env::args().nth(1) {
+        Some(network) => match BitcoinNetwork::from_str(&network) {
+            Ok(network) => network,
+            Err(_) => {
+                println!("Unknown network {}, use mainnet or testnet", network);
+                return;
+            }
+        },
+        None => BitcoinNetwork::default(),
+    };
+
     let socket = SocketAddr::from((NODE_IP, NODE_PORT));
-    let node: Arc<Mutex<TcpStream>> = match TcpStream::connect(socket) {
+    let node: Arc<Mutex<NodeConnection>> = match NodeConnection::connect(socket) {
         Ok(conexion) => Arc::new(Mutex::new(conexion)),
-        Err(_) => {
-            println!("Node conection error");
+        Err(err) => {
+            println!("Node conection error: {:?}", err);
             return;
         }
     };
 
-    let accounts: Arc<Mutex<Accounts>> = Arc::new(Mutex::new(Accounts::new()));
+    let accounts: Arc<Mutex<Accounts>> = Arc::new(Mutex::new(Accounts::new(network)));
 
     let (exit_sender, exit_recv): (Sender<bool>, Receiver<bool>) = mpsc::channel();
     let (update_sender, update_recv): (glib::Sender<bool>, glib::Receiver<bool>) =
@@ -66,7 +79,7 @@ fn main() {
             println!("{:?}", err);
         };
 
-        match update_progress_bar_view(&builder, progress_recv) {
+        match update_progress_bar_view(&builder, shared_accounts.clone(), progress_recv) {
             Ok(()) => {}
             Err(err) => {
                 println!("{:?}", err);
@@ -0,0 +1,3 @@
+pub mod multisig_constants;
+pub mod multisig_error;
+pub mod multisig_script;
@@ -0,0 +1,4 @@
+pub const OP_1: u8 = 0x51;
+pub const OP_CHECKMULTISIG: u8 = 0xae;
+
+pub const MAX_MULTISIG_KEYS: usize = 15;
@@ -0,0 +1,33 @@
+#[derive(Debug, PartialEq, Eq)]
+pub enum MultisigError {
+    InvalidThreshold,
+    InvalidPublicKey,
+    DuplicatePublicKey,
+    InvalidScript,
+    MissingSignatures,
+}
+
+impl From<secp256k1::Error> for MultisigError {
+    fn from(_: secp256k1::Error) -> MultisigError {
+        MultisigError::InvalidPublicKey
+    }
+}
+
+impl std::fmt::Display for MultisigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            MultisigError::InvalidThreshold => write!(
+                f,
+                "the required signatures must be between 1 and the number of keys, at most 15"
+            ),
+            MultisigError::InvalidPublicKey => {
+                write!(f, "the public keys must be compressed and valid")
+            }
+            MultisigError::DuplicatePublicKey => write!(f, "a public key is repeated"),
+            MultisigError::InvalidScript => write!(f, "the script isn't a multisig script"),
+            MultisigError::MissingSignatures => {
+                write!(f, "there aren't enough signatures to spend the output")
+            }
+        }
+    }
+}
@@ -0,0 +1,341 @@
+use std::collections::BTreeMap;
+
+use bitcoin_hashes::{hash160, sha256, Hash};
+use node::{
+    block_mod::{
+        script::Script,
+        script_type::{get_script_hash, ScriptType},
+    },
+    network::bitcoin_network::BitcoinNetwork,
+};
+use secp256k1::PublicKey;
+
+use super::{
+    multisig_constants::{MAX_MULTISIG_KEYS, OP_1, OP_CHECKMULTISIG},
+    multisig_error::MultisigError,
+};
+use crate::transactions::{
+    address::{p2sh, segwit, Address},
+    create_transactios_constants::COMPRESSED_PUBLIC_KEY_LEN,
+};
+
+/// Kind of output a multisig script is locked in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MultisigKind {
+    /// BIP16 pay to script hash, the script is revealed at the end of the signature script.
+    P2sh,
+    /// BIP141 pay to witness script hash, the script is revealed as the last item of the witness.
+    P2wsh,
+}
+
+impl MultisigKind {
+    /// Returns the output script locked to a redeem or witness script.
+    pub fn get_pk_script(&self, script: &[u8]) -> Vec<u8> {
+        match self {
+            MultisigKind::P2sh => p2sh(hash160::Hash::hash(script).to_byte_array().to_vec()),
+            MultisigKind::P2wsh => segwit(0, sha256::Hash::hash(script).to_byte_array().to_vec()),
+        }
+        .as_bytes()
+    }
+
+    /// Returns the address locked to a redeem or witness script.
+    pub fn get_address(&self, script: &[u8], network: BitcoinNetwork) -> Address {
+        match self {
+            MultisigKind::P2sh => Address::p2sh_from_script(script, network),
+            MultisigKind::P2wsh => Address::p2wsh_from_script(script, network),
+        }
+    }
+}
+
+/// Represents an m-of-n multisig script, `OP_m <public keys> OP_n OP_CHECKMULTISIG`, which is spent by signatures of `m`
+/// of its `n` public keys.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MultisigScript {
+    required: usize,
+    public_keys: Vec<Vec<u8>>,
+}
+
+impl MultisigScript {
+    /// Creates the script that requires signatures of some of the given keys.
+    ///
+    /// # Arguments
+    ///
+    /// * `required` - The number of signatures needed to spend, `m`.
+    /// * `public_keys` - The compressed public keys that can sign, `n`.
+    ///
+    /// # Returns
+    ///
+    /// A Result containing the script, or a `MultisigError` if `m` isn't between 1 and `n`, there are more than 15 keys, or
+    /// a key is invalid or repeated.
+    ///
+    /// # Description
+    ///
+    /// The keys are sorted as BIP67 describes, so every cosigner gets the same script and address no matter the order in
+    /// which they entered the keys.
+    pub fn new(
+        required: usize,
+        mut public_keys: Vec<Vec<u8>>,
+    ) -> Result<MultisigScript, MultisigError> {
+        public_keys.sort();
+        if public_keys.windows(2).any(|keys| keys[0] == keys[1]) {
+            return Err(MultisigError::DuplicatePublicKey);
+        }
+        check_keys(required, &public_keys)?;
+
+        Ok(MultisigScript {
+            required,
+            public_keys,
+        })
+    }
+
+    /// Parses a multisig script, keeping its keys in the order they appear.
+    ///
+    /// # Returns
+    ///
+    /// A Result containing the script, or `MultisigError::InvalidScript` if it isn't a multisig script of compressed keys.
+    pub fn from_bytes(script: &[u8]) -> Result<MultisigScript, MultisigError> {
+        let (required_opcode, mut rest) =
+            script.split_first().ok_or(MultisigError::InvalidScript)?;
+        let mut public_keys = vec![];
+        while let Some((&len, after_len)) = rest.split_first() {
+            if len as usize != COMPRESSED_PUBLIC_KEY_LEN {
+                break;
+            }
+            let public_key = after_len
+                .get(..len as usize)
+                .ok_or(MultisigError::InvalidScript)?;
+            public_keys.push(public_key.to_vec());
+            rest = &after_len[len as usize..];
+        }
+        if public_keys.len() > MAX_MULTISIG_KEYS {
+            return Err(MultisigError::InvalidScript);
+        }
+        let total_opcode = public_keys.len() as u8 + OP_1 - 1;
+        if rest != [total_opcode, OP_CHECKMULTISIG] || *required_opcode < OP_1 {
+            return Err(MultisigError::InvalidScript);
+        }
+        let required = (required_opcode - OP_1 + 1) as usize;
+        check_keys(required, &public_keys).map_err(|_| MultisigError::InvalidScript)?;
+
+        Ok(MultisigScript {
+            required,
+            public_keys,
+        })
+    }
+
+    /// Serializes the script, the redeem script of P2SH outputs and the witness script of P2WSH ones. `m` and `n` fit in
+    /// the `OP_1` to `OP_16` opcodes, as there are at most `MAX_MULTISIG_KEYS` keys.
+    pub fn as_bytes(&self) -> Vec<u8> {
+        let mut cmds = vec![vec![self.required as u8 + OP_1 - 1]];
+        cmds.extend(self.public_keys.iter().cloned());
+        cmds.push(vec![self.public_keys.len() as u8 + OP_1 - 1]);
+        cmds.push(vec![OP_CHECKMULTISIG]);
+        Script::new(Some(cmds)).as_bytes()
+    }
+
+    pub fn get_required(&self) -> usize {
+        self.required
+    }
+
+    pub fn get_public_keys(&self) -> &Vec<Vec<u8>> {
+        &self.public_keys
+    }
+
+    /// Builds the data that spends an output locked to the script: the dummy element `OP_CHECKMULTISIG` pops because of an
+    /// off-by-one bug, followed by `m` signatures.
+    ///
+    /// # Arguments
+    ///
+    /// * `signatures` - The signatures collected so far, by public key.
+    ///
+    /// # Returns
+    ///
+    /// A Result containing the empty dummy element and the signatures, or `MultisigError::MissingSignatures` if there are
+    /// less than `m` of them.
+    ///
+    /// # Description
+    ///
+    /// `OP_CHECKMULTISIG` checks the signatures against the keys in order, so they are taken in the order of the keys in the
+    /// script. The caller adds the script itself, at the end of the signature script or of the witness.
+    pub fn get_unlocking_data(
+        &self,
+        signatures: &BTreeMap<Vec<u8>, Vec<u8>>,
+    ) -> Result<Vec<Vec<u8>>, MultisigError> {
+        let mut unlocking_data = vec![vec![]];
+        unlocking_data.extend(
+            self.public_keys
+                .iter()
+                .filter_map(|public_key| signatures.get(public_key).cloned())
+                .take(self.required),
+        );
+        if unlocking_data.len() <= self.required {
+            return Err(MultisigError::MissingSignatures);
+        }
+        Ok(unlocking_data)
+    }
+}
+
+/// Checks that `required` is a valid `m` for the keys and that all of them are valid compressed keys.
+fn check_keys(required: usize, public_keys: &[Vec<u8>]) -> Result<(), MultisigError> {
+    if required == 0 || required > public_keys.len() || public_keys.len() > MAX_MULTISIG_KEYS {
+        return Err(MultisigError::InvalidThreshold);
+    }
+    for public_key in public_keys {
+        if public_key.len() != COMPRESSED_PUBLIC_KEY_LEN {
+            return Err(MultisigError::InvalidPublicKey);
+        }
+        PublicKey::from_slice(public_key)?;
+    }
+    Ok(())
+}
+
+/// Finds the redeem or witness script an output script is locked to.
+///
+/// # Arguments
+///
+/// * `pk_script` - The script of the output.
+/// * `scripts` - The scripts known by the wallet.
+///
+/// # Returns
+///
+/// The script whose hash160 is the hash of a P2SH output or whose sha256 is the program of a P2WSH output, or `None` if
+/// the output isn't locked to any of them.
+pub fn find_script<'a>(pk_script: &[u8], scripts: &'a [Vec<u8>]) -> Option<&'a Vec<u8>> {
+    let script_hash = get_script_hash(pk_script)?;
+    scripts
+        .iter()
+        .find(|script| match ScriptType::from_pk_script(pk_script) {
+            ScriptType::P2sh => hash160::Hash::hash(script).to_byte_array() == script_hash,
+            _ => sha256::Hash::hash(script).to_byte_array() == script_hash,
+        })
+}
+
+#[cfg(test)]
+mod multisig_script_test {
+    use secp256k1::{Secp256k1, SecretKey};
+
+    use super::*;
+
+    fn test_public_keys() -> Vec<Vec<u8>> {
+        let secp = Secp256k1::new();
+        (1..=3)
+            .filter_map(|byte| SecretKey::from_slice(&[byte; 32]).ok())
+            .map(|private_key| {
+                PublicKey::from_secret_key(&secp, &private_key)
+                    .serialize()
+                    .to_vec()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_script_round_trip() -> Result<(), MultisigError> {
+        let multisig = MultisigScript::new(2, test_public_keys())?;
+        let script = multisig.as_bytes();
+
+        assert_eq!(script.len(), 3 + 3 * (1 + COMPRESSED_PUBLIC_KEY_LEN));
+        assert_eq!(script[0], 0x52);
+        assert_eq!(script[script.len() - 2..], [0x53, OP_CHECKMULTISIG]);
+        assert_eq!(MultisigScript::from_bytes(&script)?, multisig);
+        Ok(())
+    }
+
+    #[test]
+    fn test_keys_are_sorted() -> Result<(), MultisigError> {
+        let mut public_keys = test_public_keys();
+        let multisig = MultisigScript::new(2, public_keys.clone())?;
+        public_keys.reverse();
+
+        assert_eq!(MultisigScript::new(2, public_keys)?, multisig);
+        assert!(multisig
+            .get_public_keys()
+            .windows(2)
+            .all(|keys| keys[0] < keys[1]));
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_scripts_are_rejected() {
+        let public_keys = test_public_keys();
+
+        assert_eq!(
+            MultisigScript::new(4, public_keys.clone()),
+            Err(MultisigError::InvalidThreshold)
+        );
+        assert_eq!(
+            MultisigScript::new(1, vec![public_keys[0].clone(); 2]),
+            Err(MultisigError::DuplicatePublicKey)
+        );
+        assert_eq!(
+            MultisigScript::new(1, vec![vec![2; 32]]),
+            Err(MultisigError::InvalidPublicKey)
+        );
+        assert_eq!(
+            MultisigScript::from_bytes(&[0x51, OP_CHECKMULTISIG]),
+            Err(MultisigError::InvalidScript)
+        );
+
+        let mut too_many_keys = vec![0x51];
+        for _ in 0..200 {
+            too_many_keys.push(COMPRESSED_PUBLIC_KEY_LEN as u8);
+            too_many_keys.extend(&public_keys[0]);
+        }
+        too_many_keys.extend([0x60, OP_CHECKMULTISIG]);
+        assert_eq!(
+            MultisigScript::from_bytes(&too_many_keys),
+            Err(MultisigError::InvalidScript)
+        );
+    }
+
+    #[test]
+    fn test_unlocking_data_follows_key_order() -> Result<(), MultisigError> {
+        let multisig = MultisigScript::new(2, test_public_keys())?;
+        let keys = multisig.get_public_keys();
+        let mut signatures = BTreeMap::new();
+        signatures.insert(keys[2].clone(), vec![2]);
+        assert_eq!(
+            multisig.get_unlocking_data(&signatures),
+            Err(MultisigError::MissingSignatures)
+        );
+
+        signatures.insert(keys[0].clone(), vec![0]);
+        signatures.insert(keys[1].clone(), vec![1]);
+        assert_eq!(
+            multisig.get_unlocking_data(&signatures)?,
+            vec![vec![], vec![0], vec![1]]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_addresses_and_scripts() -> Result<(), MultisigError> {
+        let script = MultisigScript::new(2, test_public_keys())?.as_bytes();
+        let p2sh_pk_script = MultisigKind::P2sh.get_pk_script(&script);
+        let p2wsh_pk_script = MultisigKind::P2wsh.get_pk_script(&script);
+
+        assert_eq!(
+            ScriptType::from_pk_script(&p2sh_pk_script),
+            ScriptType::P2sh
+        );
+        assert_eq!(
+            ScriptType::from_pk_script(&p2wsh_pk_script),
+            ScriptType::P2wsh
+        );
+        assert_eq!(
+            MultisigKind::P2wsh
+                .get_address(&script, BitcoinNetwork::Testnet)
+                .get_pk_script(),
+            p2wsh_pk_script
+        );
+        assert!(MultisigKind::P2sh
+            .get_address(&script, BitcoinNetwork::Mainnet)
+            .to_string()
+            .starts_with('3'));
+        assert_eq!(
+            find_script(&p2sh_pk_script, std::slice::from_ref(&script)),
+            Some(&script)
+        );
+        assert_eq!(find_script(&p2wsh_pk_script, &[vec![1]]), None);
+        Ok(())
+    }
+}
@@ -0,0 +1,178 @@
+use std::{
+    collections::VecDeque,
+    io::{ErrorKind, Write},
+    net::{SocketAddr, TcpStream},
+};
+
+use node::{
+    messages::read_from_bytes::fill_command,
+    wallet_utils::{
+        broadcast_txn::BroadcastTxn,
+        error_response::ErrorResponse,
+        hello::Hello,
+        subscribe::Subscribe,
+        wallet_frame::WalletFrame,
+        wallet_utils_constants::{
+            ERROR_COMMAND, EXIT_COMMAND, HELLO_COMMAND, OK_COMMAND, UNSOLICITED_REQUEST_ID,
+            WALLET_PROTOCOL_VERSION,
+        },
+    },
+};
+
+use crate::interface_error::InterfaceError;
+
+/// The connection of the wallet to the node, speaking the framed protocol.
+///
+/// Each request gets its own id, and the node answers it with a frame that has the same id, so an answer can't be
+/// taken for the answer of another request. Frames the node sends without being asked, the notifications of the
+/// addresses the wallet subscribed to, are kept until `next_event` returns them.
+pub struct NodeConnection {
+    stream: TcpStream,
+    next_request_id: u32,
+    version: u32,
+    events: VecDeque<WalletFrame>,
+}
+
+impl NodeConnection {
+    /// Connects to the node and agrees on the version of the protocol.
+    ///
+    /// # Arguments
+    ///
+    /// * `socket` - The address of the node.
+    ///
+    /// # Returns
+    ///
+    /// The connection, or `InterfaceError::Connect` if the node can't be reached, `InterfaceError::NodeError` if it
+    /// doesn't support the version of the wallet, or another `InterfaceError` if the handshake fails.
+    pub fn connect(socket: SocketAddr) -> Result<NodeConnection, InterfaceError> {
+        let mut stream = TcpStream::connect(socket).map_err(|_| InterfaceError::Connect)?;
+        stream
+            .write_all(&Hello::new(WALLET_PROTOCOL_VERSION).as_bytes())
+            .map_err(|_| InterfaceError::Write)?;
+
+        let mut connection = NodeConnection {
+            stream,
+            next_request_id: UNSOLICITED_REQUEST_ID + 1,
+            version: WALLET_PROTOCOL_VERSION,
+            events: VecDeque::new(),
+        };
+        let response = connection.read_response(UNSOLICITED_REQUEST_ID)?;
+        if response.get_command_name() != HELLO_COMMAND {
+            return Err(InterfaceError::InvalidResponse);
+        }
+        let hello = response
+            .read_payload(|payload| Hello::from_bytes(HELLO_COMMAND.to_string(), payload))
+            .map_err(|_| InterfaceError::Read)?;
+        connection.version = hello.get_version();
+        Ok(connection)
+    }
+
+    /// Sends a request to the node and waits for its answer.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - The message of the request, starting with its command, such as the bytes of a `GetProof`.
+    ///
+    /// # Returns
+    ///
+    /// The frame the node answered with, or `InterfaceError::NodeError` with the code of the error if the node couldn't
+    /// serve the request, or another `InterfaceError` if the communication fails.
+    pub fn request(&mut self, message: &[u8]) -> Result<WalletFrame, InterfaceError> {
+        let request_id = self.next_request_id;
+        self.next_request_id = self.next_request_id.checked_add(1).unwrap_or(1);
+        self.send(request_id, message)?;
+        self.read_response(request_id)
+    }
+
+    /// Sends a transaction to the node, which broadcasts it to its peers.
+    pub fn broadcast(&mut self, broadcast_txn: &BroadcastTxn) -> Result<(), InterfaceError> {
+        match self.request(&broadcast_txn.as_bytes())?.get_command_name() {
+            OK_COMMAND => Ok(()),
+            _ => Err(InterfaceError::InvalidResponse),
+        }
+    }
+
+    /// Subscribes the wallet to the notifications of its addresses, replacing the addresses it was subscribed to.
+    ///
+    /// # Arguments
+    ///
+    /// * `addresses` - The script and the public key of each address, the public key empty if it is locked to a script.
+    pub fn subscribe(&mut self, addresses: Vec<(Vec<u8>, Vec<u8>)>) -> Result<(), InterfaceError> {
+        match self
+            .request(&Subscribe::new(addresses).as_bytes())?
+            .get_command_name()
+        {
+            OK_COMMAND => Ok(()),
+            _ => Err(InterfaceError::InvalidResponse),
+        }
+    }
+
+    /// Returns the next notification pushed by the node, without waiting for one.
+    ///
+    /// # Returns
+    ///
+    /// The frame of the notification, `None` if the node hasn't sent any, or `InterfaceError::Read` if the connection
+    /// is closed.
+    pub fn next_event(&mut self) -> Result<Option<WalletFrame>, InterfaceError> {
+        if let Some(event) = self.events.pop_front() {
+            return Ok(Some(event));
+        }
+
+        self.stream
+            .set_nonblocking(true)
+            .map_err(|_| InterfaceError::Read)?;
+        let pending = self.stream.peek(&mut [0; 1]);
+        self.stream
+            .set_nonblocking(false)
+            .map_err(|_| InterfaceError::Read)?;
+        match pending {
+            Ok(0) => Err(InterfaceError::Read),
+            Ok(_) => self.read_response(UNSOLICITED_REQUEST_ID).map(Some),
+            Err(err) if err.kind() == ErrorKind::WouldBlock => Ok(None),
+            Err(_) => Err(InterfaceError::Read),
+        }
+    }
+
+    /// Tells the node the wallet is done, so it closes the connection.
+    pub fn disconnect(&mut self) -> Result<(), InterfaceError> {
+        let request_id = self.next_request_id;
+        self.send(request_id, fill_command(EXIT_COMMAND).as_bytes())
+    }
+
+    /// Returns the version of the protocol agreed with the node.
+    pub fn get_version(&self) -> u32 {
+        self.version
+    }
+
+    fn send(&mut self, request_id: u32, message: &[u8]) -> Result<(), InterfaceError> {
+        let frame =
+            WalletFrame::from_message(request_id, message).map_err(|_| InterfaceError::Write)?;
+        self.stream
+            .write_all(&frame.as_bytes())
+            .map_err(|_| InterfaceError::Write)
+    }
+
+    /// Reads frames until the one with the given request id, turning an `ErrorResponse` into an error. The
+    /// notifications read meanwhile are kept for `next_event`.
+    fn read_response(&mut self, request_id: u32) -> Result<WalletFrame, InterfaceError> {
+        loop {
+            let frame =
+                WalletFrame::from_bytes(&mut self.stream).map_err(|_| InterfaceError::Read)?;
+            if frame.get_request_id() != request_id {
+                if frame.get_request_id() == UNSOLICITED_REQUEST_ID {
+                    self.events.push_back(frame);
+                }
+                continue;
+            }
+            if frame.get_command_name() == ERROR_COMMAND {
+                let error = frame
+                    .read_payload(|payload| {
+                        ErrorResponse::from_bytes(ERROR_COMMAND.to_string(), payload)
+                    })
+                    .map_err(|_| InterfaceError::Read)?;
+                return Err(InterfaceError::NodeError(error.get_code()));
+            }
+            return Ok(frame);
+        }
+    }
+}
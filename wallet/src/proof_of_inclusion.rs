@@ -1,2 +1,3 @@
 pub mod check_proof_error;
 pub mod get_proof_of_inclusion;
+pub mod request_proof;
@@ -0,0 +1,41 @@
+use node::wallet_utils::{
+    error_response::ErrorCode, get_proof::GetProof, merkle_block::MerkleBlock,
+    wallet_utils_constants::MERKLE_BLOCK_COMMAND,
+};
+
+use crate::{interface_error::InterfaceError, node_connection::NodeConnection};
+
+use super::get_proof_of_inclusion::get_proof_of_inclusion;
+
+/// Asks the node for the proof that a transaction is included in a block and checks it.
+///
+/// # Arguments
+///
+/// * `node` - The connection to the node.
+/// * `block_hash` - The hash of the block.
+/// * `tx_id` - The id of the transaction.
+///
+/// # Returns
+///
+/// `true` if the node answered with a `MerkleBlock` whose merkle proof is valid, `false` if the node didn't find the
+/// transaction in the block or the proof doesn't match, or an `InterfaceError` if the communication fails.
+pub fn request_proof(
+    node: &mut NodeConnection,
+    block_hash: Vec<u8>,
+    tx_id: Vec<u8>,
+) -> Result<bool, InterfaceError> {
+    let get_proof = GetProof::new(block_hash, tx_id);
+    let response = match node.request(&get_proof.as_bytes()) {
+        Ok(response) => response,
+        Err(InterfaceError::NodeError(ErrorCode::NotFound)) => return Ok(false),
+        Err(err) => return Err(err),
+    };
+    if response.get_command_name() != MERKLE_BLOCK_COMMAND {
+        return Err(InterfaceError::InvalidResponse);
+    }
+
+    let merkle_block = response
+        .read_payload(|payload| MerkleBlock::from_bytes(MERKLE_BLOCK_COMMAND.to_string(), payload))
+        .map_err(|_| InterfaceError::Read)?;
+    get_proof_of_inclusion(merkle_block).map_err(|_| InterfaceError::InvalidResponse)
+}
@@ -0,0 +1,7 @@
+pub mod base64;
+pub mod psbt_constants;
+pub mod psbt_error;
+pub mod psbt_input;
+pub mod psbt_map;
+pub mod psbt_output;
+pub mod psbt_transaction;
@@ -0,0 +1,95 @@
+use super::{
+    psbt_constants::{BASE64_CHARSET, BASE64_PADDING},
+    psbt_error::PsbtError,
+};
+
+/// Encodes bytes in base64 with padding, as defined by RFC 4648, the text format of a PSBT.
+pub fn encode_base64(bytes: &[u8]) -> String {
+    let mut encoded = String::new();
+
+    for chunk in bytes.chunks(3) {
+        let group = chunk.iter().enumerate().fold(0u32, |group, (i, byte)| {
+            group | (*byte as u32) << (16 - 8 * i)
+        });
+        for i in 0..4 {
+            if i <= chunk.len() {
+                let index = (group >> (18 - 6 * i)) & 0x3f;
+                encoded.push(BASE64_CHARSET[index as usize] as char);
+            } else {
+                encoded.push(BASE64_PADDING as char);
+            }
+        }
+    }
+    encoded
+}
+
+/// Decodes a padded base64 string.
+///
+/// # Arguments
+///
+/// * `encoded` - The base64 string. Whitespace is ignored, so a PSBT can be pasted split in lines.
+///
+/// # Returns
+///
+/// A Result containing the decoded bytes, or `PsbtError::InvalidBase64` if the string has characters out of the alphabet,
+/// its length isn't a multiple of four or the padding is misplaced.
+pub fn decode_base64(encoded: &str) -> Result<Vec<u8>, PsbtError> {
+    let encoded: Vec<u8> = encoded
+        .bytes()
+        .filter(|byte| !byte.is_ascii_whitespace())
+        .collect();
+    if !encoded.len().is_multiple_of(4) {
+        return Err(PsbtError::InvalidBase64);
+    }
+    let mut decoded = vec![];
+
+    for (n, chunk) in encoded.chunks(4).enumerate() {
+        let padding = chunk
+            .iter()
+            .rev()
+            .take_while(|byte| **byte == BASE64_PADDING)
+            .count();
+        if padding > 2 || (padding > 0 && n != encoded.len() / 4 - 1) {
+            return Err(PsbtError::InvalidBase64);
+        }
+
+        let mut group = 0u32;
+        for (i, byte) in chunk[..4 - padding].iter().enumerate() {
+            let index = BASE64_CHARSET
+                .iter()
+                .position(|c| c == byte)
+                .ok_or(PsbtError::InvalidBase64)?;
+            group |= (index as u32) << (18 - 6 * i);
+        }
+        decoded.extend(&group.to_be_bytes()[1..4 - padding]);
+    }
+    Ok(decoded)
+}
+
+#[cfg(test)]
+mod base64_test {
+    use super::*;
+
+    #[test]
+    fn test_rfc4648_vectors() -> Result<(), PsbtError> {
+        let vectors = [
+            ("", ""),
+            ("f", "Zg=="),
+            ("fo", "Zm8="),
+            ("foo", "Zm9v"),
+            ("foob", "Zm9vYg=="),
+            ("fooba", "Zm9vYmE="),
+            ("foobar", "Zm9vYmFy"),
+        ];
+
+        for (decoded, encoded) in vectors {
+            assert_eq!(encode_base64(decoded.as_bytes()), encoded);
+            assert_eq!(decode_base64(encoded)?, decoded.as_bytes());
+        }
+        assert_eq!(decode_base64("Zm9v\nYmFy")?, b"foobar");
+        assert_eq!(decode_base64("Zm9"), Err(PsbtError::InvalidBase64));
+        assert_eq!(decode_base64("Zg==Zm9v"), Err(PsbtError::InvalidBase64));
+        assert_eq!(decode_base64("Zm9*"), Err(PsbtError::InvalidBase64));
+        Ok(())
+    }
+}
@@ -0,0 +1,68 @@
+use node::messages::message_error::MessageError;
+
+use crate::transactions::create_transaction_error::TransactionCreateError;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum PsbtError {
+    Io,
+    InvalidBase64,
+    InvalidFormat,
+    DuplicateKey,
+    UnsupportedVersion,
+    SignedUnsignedTx,
+    MissingUtxo(usize),
+    TransactionMismatch,
+    CantFinalize(usize),
+    NotFinalized(usize),
+    Signing,
+}
+
+impl From<std::io::Error> for PsbtError {
+    fn from(_: std::io::Error) -> PsbtError {
+        PsbtError::Io
+    }
+}
+
+impl From<MessageError> for PsbtError {
+    fn from(_: MessageError) -> PsbtError {
+        PsbtError::InvalidFormat
+    }
+}
+
+impl From<TransactionCreateError> for PsbtError {
+    fn from(_: TransactionCreateError) -> PsbtError {
+        PsbtError::Signing
+    }
+}
+
+impl From<secp256k1::Error> for PsbtError {
+    fn from(_: secp256k1::Error) -> PsbtError {
+        PsbtError::Signing
+    }
+}
+
+impl std::fmt::Display for PsbtError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            PsbtError::Io => write!(f, "the PSBT file can't be read or written"),
+            PsbtError::InvalidBase64 => write!(f, "the PSBT isn't valid base64"),
+            PsbtError::InvalidFormat => write!(f, "the PSBT is malformed"),
+            PsbtError::DuplicateKey => write!(f, "the PSBT has a repeated key"),
+            PsbtError::UnsupportedVersion => write!(f, "the PSBT version isn't supported"),
+            PsbtError::SignedUnsignedTx => {
+                write!(f, "the unsigned transaction of the PSBT has signatures")
+            }
+            PsbtError::MissingUtxo(index) => {
+                write!(f, "the output spent by input {} is unknown", index)
+            }
+            PsbtError::TransactionMismatch => {
+                write!(f, "the PSBTs are for different transactions")
+            }
+            PsbtError::CantFinalize(index) => {
+                write!(f, "input {} doesn't have the signatures it needs", index)
+            }
+            PsbtError::NotFinalized(index) => write!(f, "input {} isn't finalized", index),
+            PsbtError::Signing => write!(f, "an input can't be signed"),
+        }
+    }
+}
@@ -0,0 +1,283 @@
+use std::collections::BTreeMap;
+
+use node::{
+    block_mod::{
+        outpoint::Outpoint,
+        transaction::{Transaction, SIGHASH_ALL},
+        tx_out::TxOut,
+    },
+    messages::read_from_bytes::read_u32_from_bytes,
+};
+
+use super::{
+    psbt_constants::*,
+    psbt_error::PsbtError,
+    psbt_map::{
+        expect_no_key_data, witness_as_bytes, witness_from_bytes, write_pair, write_raw_pairs,
+        PsbtPair,
+    },
+};
+
+/// Represents the map of a PSBT input: what signers need to know about the output it spends, the signatures collected
+/// so far and, once finalized, its complete signature script and witness.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct PsbtInput {
+    non_witness_utxo: Option<Transaction>,
+    witness_utxo: Option<TxOut>,
+    partial_sigs: BTreeMap<Vec<u8>, Vec<u8>>,
+    sighash_type: Option<u32>,
+    redeem_script: Option<Vec<u8>>,
+    witness_script: Option<Vec<u8>>,
+    bip32_derivation: BTreeMap<Vec<u8>, Vec<u8>>,
+    final_script_sig: Option<Vec<u8>>,
+    final_script_witness: Option<Vec<Vec<u8>>>,
+    unknown: BTreeMap<Vec<u8>, Vec<u8>>,
+}
+
+impl PsbtInput {
+    /// Creates the map of an input from its key-value pairs, see `read_map`.
+    ///
+    /// # Returns
+    ///
+    /// A Result containing the input, or `PsbtError::InvalidFormat` if a value can't be parsed. Keys of unknown types are
+    /// kept as they are, so they aren't lost when the PSBT is passed on.
+    pub fn from_pairs(pairs: Vec<PsbtPair>) -> Result<PsbtInput, PsbtError> {
+        let mut input = PsbtInput::default();
+
+        for (key, value) in pairs {
+            let (key_type, key_data) = key.split_first().ok_or(PsbtError::InvalidFormat)?;
+            match *key_type {
+                PSBT_IN_NON_WITNESS_UTXO => {
+                    expect_no_key_data(key_data)?;
+                    input.non_witness_utxo = Some(Transaction::from_bytes(&mut &value[..])?);
+                }
+                PSBT_IN_WITNESS_UTXO => {
+                    expect_no_key_data(key_data)?;
+                    input.witness_utxo = Some(TxOut::from_bytes(&mut &value[..])?);
+                }
+                PSBT_IN_PARTIAL_SIG => {
+                    input.partial_sigs.insert(key_data.to_vec(), value);
+                }
+                PSBT_IN_SIGHASH_TYPE => {
+                    expect_no_key_data(key_data)?;
+                    input.sighash_type = Some(read_u32_from_bytes(&mut &value[..], true)?);
+                }
+                PSBT_IN_REDEEM_SCRIPT => {
+                    expect_no_key_data(key_data)?;
+                    input.redeem_script = Some(value);
+                }
+                PSBT_IN_WITNESS_SCRIPT => {
+                    expect_no_key_data(key_data)?;
+                    input.witness_script = Some(value);
+                }
+                PSBT_IN_BIP32_DERIVATION => {
+                    input.bip32_derivation.insert(key_data.to_vec(), value);
+                }
+                PSBT_IN_FINAL_SCRIPTSIG => {
+                    expect_no_key_data(key_data)?;
+                    input.final_script_sig = Some(value);
+                }
+                PSBT_IN_FINAL_SCRIPTWITNESS => {
+                    expect_no_key_data(key_data)?;
+                    input.final_script_witness = Some(witness_from_bytes(&mut &value[..])?);
+                }
+                _ => {
+                    input.unknown.insert(key, value);
+                }
+            }
+        }
+        Ok(input)
+    }
+
+    /// Serializes the map of the input with its pairs sorted by key type, followed by the separator.
+    pub fn as_bytes(&self) -> Vec<u8> {
+        let mut buffer = vec![];
+
+        if let Some(non_witness_utxo) = &self.non_witness_utxo {
+            write_pair(
+                &mut buffer,
+                PSBT_IN_NON_WITNESS_UTXO,
+                &[],
+                &non_witness_utxo.as_bytes(),
+            );
+        }
+        if let Some(witness_utxo) = &self.witness_utxo {
+            write_pair(
+                &mut buffer,
+                PSBT_IN_WITNESS_UTXO,
+                &[],
+                &witness_utxo.as_bytes(),
+            );
+        }
+        for (public_key, signature) in self.partial_sigs.iter() {
+            write_pair(&mut buffer, PSBT_IN_PARTIAL_SIG, public_key, signature);
+        }
+        if let Some(sighash_type) = self.sighash_type {
+            write_pair(
+                &mut buffer,
+                PSBT_IN_SIGHASH_TYPE,
+                &[],
+                &sighash_type.to_le_bytes(),
+            );
+        }
+        if let Some(redeem_script) = &self.redeem_script {
+            write_pair(&mut buffer, PSBT_IN_REDEEM_SCRIPT, &[], redeem_script);
+        }
+        if let Some(witness_script) = &self.witness_script {
+            write_pair(&mut buffer, PSBT_IN_WITNESS_SCRIPT, &[], witness_script);
+        }
+        for (public_key, derivation) in self.bip32_derivation.iter() {
+            write_pair(
+                &mut buffer,
+                PSBT_IN_BIP32_DERIVATION,
+                public_key,
+                derivation,
+            );
+        }
+        if let Some(final_script_sig) = &self.final_script_sig {
+            write_pair(&mut buffer, PSBT_IN_FINAL_SCRIPTSIG, &[], final_script_sig);
+        }
+        if let Some(final_script_witness) = &self.final_script_witness {
+            write_pair(
+                &mut buffer,
+                PSBT_IN_FINAL_SCRIPTWITNESS,
+                &[],
+                &witness_as_bytes(final_script_witness),
+            );
+        }
+        write_raw_pairs(&mut buffer, self.unknown.iter());
+        buffer.push(PSBT_SEPARATOR);
+        buffer
+    }
+
+    pub fn set_non_witness_utxo(&mut self, transaction: Transaction) {
+        self.non_witness_utxo = Some(transaction);
+    }
+
+    pub fn set_witness_utxo(&mut self, tx_out: TxOut) {
+        self.witness_utxo = Some(tx_out);
+    }
+
+    /// Returns the output spent by the input.
+    ///
+    /// # Arguments
+    ///
+    /// * `prev_output` - The outpoint the input of the unsigned transaction spends.
+    ///
+    /// # Returns
+    ///
+    /// The witness UTXO if there is one, or else the output of the non-witness UTXO, which is only trusted if its id is
+    /// the one of the outpoint. `None` if neither is known.
+    pub fn get_spent_output(&self, prev_output: &Outpoint) -> Option<TxOut> {
+        if let Some(witness_utxo) = &self.witness_utxo {
+            return Some(witness_utxo.clone());
+        }
+        let transaction = self.non_witness_utxo.as_ref()?;
+        if transaction.get_id() != *prev_output.get_tx_id() {
+            return None;
+        }
+        transaction
+            .get_tx_out_list()
+            .get(prev_output.get_index() as usize)
+            .cloned()
+    }
+
+    /// Returns the signatures collected for the input, by public key.
+    pub fn get_partial_sigs(&self) -> &BTreeMap<Vec<u8>, Vec<u8>> {
+        &self.partial_sigs
+    }
+
+    /// Adds the signature of a public key, replacing the previous one of the same key.
+    pub fn add_partial_sig(&mut self, public_key: Vec<u8>, signature: Vec<u8>) {
+        self.partial_sigs.insert(public_key, signature);
+    }
+
+    /// Returns the sighash type signers must use, `SIGHASH_ALL` if the PSBT doesn't ask for another one.
+    pub fn get_sighash_type(&self) -> u32 {
+        self.sighash_type.unwrap_or(SIGHASH_ALL)
+    }
+
+    /// Sets the script a P2SH output is locked to, which signers need to sign the input.
+    pub fn set_redeem_script(&mut self, redeem_script: Vec<u8>) {
+        self.redeem_script = Some(redeem_script);
+    }
+
+    /// Sets the script a P2WSH output is locked to, which signers need to sign the input.
+    pub fn set_witness_script(&mut self, witness_script: Vec<u8>) {
+        self.witness_script = Some(witness_script);
+    }
+
+    pub fn get_redeem_script(&self) -> Option<&Vec<u8>> {
+        self.redeem_script.as_ref()
+    }
+
+    pub fn get_witness_script(&self) -> Option<&Vec<u8>> {
+        self.witness_script.as_ref()
+    }
+
+    pub fn get_final_script_sig(&self) -> Option<&Vec<u8>> {
+        self.final_script_sig.as_ref()
+    }
+
+    pub fn get_final_script_witness(&self) -> Option<&Vec<Vec<u8>>> {
+        self.final_script_witness.as_ref()
+    }
+
+    /// Checks if the input has its final signature script or witness, so it needs no more signatures.
+    pub fn is_finalized(&self) -> bool {
+        self.final_script_sig.is_some() || self.final_script_witness.is_some()
+    }
+
+    /// Sets the final signature script and witness of the input.
+    ///
+    /// # Description
+    ///
+    /// As BIP174 asks of the finalizer, everything that was only needed to sign is removed: the partial signatures, the
+    /// sighash type, the scripts and the key derivations. Only the UTXO is kept, so the fee can still be checked.
+    pub fn finalize(
+        &mut self,
+        final_script_sig: Option<Vec<u8>>,
+        final_script_witness: Option<Vec<Vec<u8>>>,
+    ) {
+        self.final_script_sig = final_script_sig;
+        self.final_script_witness = final_script_witness;
+        self.partial_sigs.clear();
+        self.sighash_type = None;
+        self.redeem_script = None;
+        self.witness_script = None;
+        self.bip32_derivation.clear();
+    }
+
+    /// Adds the data of another map of the same input, keeping the values this one already had.
+    pub fn combine(&mut self, other: &PsbtInput) {
+        self.non_witness_utxo = self
+            .non_witness_utxo
+            .take()
+            .or(other.non_witness_utxo.clone());
+        self.witness_utxo = self.witness_utxo.take().or(other.witness_utxo.clone());
+        self.sighash_type = self.sighash_type.or(other.sighash_type);
+        self.redeem_script = self.redeem_script.take().or(other.redeem_script.clone());
+        self.witness_script = self.witness_script.take().or(other.witness_script.clone());
+        self.final_script_sig = self
+            .final_script_sig
+            .take()
+            .or(other.final_script_sig.clone());
+        self.final_script_witness = self
+            .final_script_witness
+            .take()
+            .or(other.final_script_witness.clone());
+        for (key, value) in other.partial_sigs.iter() {
+            self.partial_sigs
+                .entry(key.clone())
+                .or_insert(value.clone());
+        }
+        for (key, value) in other.bip32_derivation.iter() {
+            self.bip32_derivation
+                .entry(key.clone())
+                .or_insert(value.clone());
+        }
+        for (key, value) in other.unknown.iter() {
+            self.unknown.entry(key.clone()).or_insert(value.clone());
+        }
+    }
+}
@@ -0,0 +1,87 @@
+use std::io::Read;
+
+use node::messages::{compact_size::CompactSizeUInt, read_from_bytes::read_vec_from_bytes};
+
+use super::{psbt_constants::PSBT_SEPARATOR, psbt_error::PsbtError};
+
+/// A key-value pair of a PSBT map, where the first byte of the key is its type.
+pub type PsbtPair = (Vec<u8>, Vec<u8>);
+
+/// Reads a map of a PSBT: key-value pairs, each preceded by its length, up to a separator byte.
+///
+/// # Arguments
+///
+/// * `stream` - The serialized PSBT, positioned at the start of the map.
+///
+/// # Returns
+///
+/// A Result containing the pairs in the order they were read, where the first byte of each key is its type, or a
+/// `PsbtError` if the map is truncated or has the same key twice.
+pub fn read_map(stream: &mut dyn Read) -> Result<Vec<PsbtPair>, PsbtError> {
+    let mut pairs: Vec<PsbtPair> = vec![];
+
+    loop {
+        let key_len = CompactSizeUInt::from_bytes(stream)?.value();
+        if key_len == PSBT_SEPARATOR as u64 {
+            return Ok(pairs);
+        }
+        let key = read_vec_from_bytes(stream, key_len as usize)?;
+        let value_len = CompactSizeUInt::from_bytes(stream)?.value();
+        let value = read_vec_from_bytes(stream, value_len as usize)?;
+
+        if pairs.iter().any(|(read_key, _)| *read_key == key) {
+            return Err(PsbtError::DuplicateKey);
+        }
+        pairs.push((key, value));
+    }
+}
+
+/// Appends a key-value pair to a serialized map as `key length || key type || key data || value length || value`.
+pub fn write_pair(buffer: &mut Vec<u8>, key_type: u8, key_data: &[u8], value: &[u8]) {
+    buffer.extend(CompactSizeUInt::from_number(key_data.len() as u64 + 1).as_bytes());
+    buffer.push(key_type);
+    buffer.extend(key_data);
+    buffer.extend(CompactSizeUInt::from_number(value.len() as u64).as_bytes());
+    buffer.extend(value);
+}
+
+/// Appends pairs whose keys are stored whole, with their type, such as the ones this wallet doesn't know.
+pub fn write_raw_pairs<'a>(
+    buffer: &mut Vec<u8>,
+    pairs: impl Iterator<Item = (&'a Vec<u8>, &'a Vec<u8>)>,
+) {
+    for (key, value) in pairs {
+        if let Some((key_type, key_data)) = key.split_first() {
+            write_pair(buffer, *key_type, key_data, value);
+        }
+    }
+}
+
+/// Returns an error if a key that must be only its type has key data.
+pub fn expect_no_key_data(key_data: &[u8]) -> Result<(), PsbtError> {
+    match key_data.is_empty() {
+        true => Ok(()),
+        false => Err(PsbtError::InvalidFormat),
+    }
+}
+
+/// Serializes a witness stack as `item count || items`, each item preceded by its length.
+pub fn witness_as_bytes(witness: &[Vec<u8>]) -> Vec<u8> {
+    let mut buffer = CompactSizeUInt::from_number(witness.len() as u64).as_bytes();
+    for item in witness {
+        buffer.extend(CompactSizeUInt::from_number(item.len() as u64).as_bytes());
+        buffer.extend(item);
+    }
+    buffer
+}
+
+/// Parses a witness stack serialized with `witness_as_bytes`.
+pub fn witness_from_bytes(stream: &mut dyn Read) -> Result<Vec<Vec<u8>>, PsbtError> {
+    let count = CompactSizeUInt::from_bytes(stream)?.value();
+    (0..count)
+        .map(|_| {
+            let len = CompactSizeUInt::from_bytes(stream)?.value();
+            Ok(read_vec_from_bytes(stream, len as usize)?)
+        })
+        .collect()
+}
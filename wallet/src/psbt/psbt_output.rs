@@ -0,0 +1,81 @@
+use std::collections::BTreeMap;
+
+use super::{
+    psbt_constants::*,
+    psbt_error::PsbtError,
+    psbt_map::{expect_no_key_data, write_pair, write_raw_pairs, PsbtPair},
+};
+
+/// Represents the map of a PSBT output, the scripts and key derivations a signer needs to recognize its own change.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct PsbtOutput {
+    redeem_script: Option<Vec<u8>>,
+    witness_script: Option<Vec<u8>>,
+    bip32_derivation: BTreeMap<Vec<u8>, Vec<u8>>,
+    unknown: BTreeMap<Vec<u8>, Vec<u8>>,
+}
+
+impl PsbtOutput {
+    /// Creates the map of an output from its key-value pairs, see `read_map`.
+    pub fn from_pairs(pairs: Vec<PsbtPair>) -> Result<PsbtOutput, PsbtError> {
+        let mut output = PsbtOutput::default();
+
+        for (key, value) in pairs {
+            let (key_type, key_data) = key.split_first().ok_or(PsbtError::InvalidFormat)?;
+            match *key_type {
+                PSBT_OUT_REDEEM_SCRIPT => {
+                    expect_no_key_data(key_data)?;
+                    output.redeem_script = Some(value);
+                }
+                PSBT_OUT_WITNESS_SCRIPT => {
+                    expect_no_key_data(key_data)?;
+                    output.witness_script = Some(value);
+                }
+                PSBT_OUT_BIP32_DERIVATION => {
+                    output.bip32_derivation.insert(key_data.to_vec(), value);
+                }
+                _ => {
+                    output.unknown.insert(key, value);
+                }
+            }
+        }
+        Ok(output)
+    }
+
+    /// Serializes the map of the output with its pairs sorted by key type, followed by the separator.
+    pub fn as_bytes(&self) -> Vec<u8> {
+        let mut buffer = vec![];
+
+        if let Some(redeem_script) = &self.redeem_script {
+            write_pair(&mut buffer, PSBT_OUT_REDEEM_SCRIPT, &[], redeem_script);
+        }
+        if let Some(witness_script) = &self.witness_script {
+            write_pair(&mut buffer, PSBT_OUT_WITNESS_SCRIPT, &[], witness_script);
+        }
+        for (public_key, derivation) in self.bip32_derivation.iter() {
+            write_pair(
+                &mut buffer,
+                PSBT_OUT_BIP32_DERIVATION,
+                public_key,
+                derivation,
+            );
+        }
+        write_raw_pairs(&mut buffer, self.unknown.iter());
+        buffer.push(PSBT_SEPARATOR);
+        buffer
+    }
+
+    /// Adds the data of another map of the same output, keeping the values this one already had.
+    pub fn combine(&mut self, other: &PsbtOutput) {
+        self.redeem_script = self.redeem_script.take().or(other.redeem_script.clone());
+        self.witness_script = self.witness_script.take().or(other.witness_script.clone());
+        for (key, value) in other.bip32_derivation.iter() {
+            self.bip32_derivation
+                .entry(key.clone())
+                .or_insert(value.clone());
+        }
+        for (key, value) in other.unknown.iter() {
+            self.unknown.entry(key.clone()).or_insert(value.clone());
+        }
+    }
+}
@@ -0,0 +1,730 @@
+use std::{collections::BTreeMap, fs, io::Read, path::Path};
+
+use bitcoin_hashes::{hash160, Hash};
+use node::{
+    block_mod::{
+        script::Script,
+        script_type::{get_pubkey_hash, ScriptType},
+        transaction::Transaction,
+        tx_out::TxOut,
+    },
+    messages::read_from_bytes::{read_u32_from_bytes, read_vec_from_bytes},
+};
+use secp256k1::{PublicKey, Secp256k1, SecretKey};
+
+use super::{
+    base64::{decode_base64, encode_base64},
+    psbt_constants::*,
+    psbt_error::PsbtError,
+    psbt_input::PsbtInput,
+    psbt_map::{expect_no_key_data, read_map, write_pair, write_raw_pairs, PsbtPair},
+    psbt_output::PsbtOutput,
+};
+use crate::multisig::multisig_script::{find_script, MultisigScript};
+use crate::transactions::create_transactions::{sign_input, sign_script_input};
+
+/// Represents a Partially Signed Bitcoin Transaction (PSBT) as defined by BIP174, version 0.
+///
+/// A PSBT carries an unsigned transaction together with what each participant needs to sign it: the outputs its inputs
+/// spend and the signatures collected so far. It lets a transaction be created on one device, signed on another one that
+/// holds the keys, possibly by several parties whose PSBTs are then combined, and finally assembled and broadcast.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Psbt {
+    unsigned_tx: Transaction,
+    unknown: BTreeMap<Vec<u8>, Vec<u8>>,
+    inputs: Vec<PsbtInput>,
+    outputs: Vec<PsbtOutput>,
+}
+
+impl Psbt {
+    /// Creates a PSBT for an unsigned transaction.
+    ///
+    /// # Arguments
+    ///
+    /// * `unsigned_tx` - The transaction, without signature scripts or witnesses.
+    /// * `prev_outputs` - The outputs spent by its inputs, in the same order.
+    /// * `scripts` - The redeem and witness scripts of the wallet, such as the ones of its multisig accounts.
+    /// * `get_transaction` - Finds a transaction of the wallet by its id.
+    ///
+    /// # Returns
+    ///
+    /// A Result containing the PSBT or a `PsbtError` if the transaction is signed or a UTXO is missing.
+    ///
+    /// # Description
+    ///
+    /// The input of a SegWit output gets the output as its witness UTXO, since the signature commits to its amount. The
+    /// signature of a legacy input doesn't, so it gets the whole transaction that created the output as its non-witness
+    /// UTXO, which lets the signer check the amount it is spending. The input of a P2SH or P2WSH output locked to one of
+    /// `scripts` gets it as its redeem or witness script, which the signers need to sign it.
+    pub fn new(
+        unsigned_tx: Transaction,
+        prev_outputs: &[TxOut],
+        scripts: &[Vec<u8>],
+        get_transaction: &dyn Fn(&[u8]) -> Option<Transaction>,
+    ) -> Result<Psbt, PsbtError> {
+        check_unsigned(&unsigned_tx)?;
+        let mut inputs = vec![];
+
+        for (i, txin) in unsigned_tx.get_tx_in_list().iter().enumerate() {
+            let prev_output = prev_outputs.get(i).ok_or(PsbtError::MissingUtxo(i))?;
+            let pk_script = prev_output.get_pk_script();
+            let mut input = PsbtInput::default();
+            match ScriptType::from_pk_script(&pk_script) {
+                ScriptType::P2wpkh | ScriptType::P2wsh | ScriptType::P2tr => {
+                    input.set_witness_utxo(prev_output.clone())
+                }
+                _ => input.set_non_witness_utxo(
+                    get_transaction(txin.get_prev_output().get_tx_id())
+                        .ok_or(PsbtError::MissingUtxo(i))?,
+                ),
+            }
+            if let Some(script) = find_script(&pk_script, scripts) {
+                match ScriptType::from_pk_script(&pk_script) {
+                    ScriptType::P2sh => input.set_redeem_script(script.clone()),
+                    _ => input.set_witness_script(script.clone()),
+                }
+            }
+            inputs.push(input);
+        }
+
+        Ok(Psbt {
+            outputs: vec![PsbtOutput::default(); unsigned_tx.get_tx_out_list().len()],
+            unsigned_tx,
+            unknown: BTreeMap::new(),
+            inputs,
+        })
+    }
+
+    /// Parses a PSBT in its binary format.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The serialized PSBT, starting with the magic bytes `psbt` and `0xff`.
+    ///
+    /// # Returns
+    ///
+    /// A Result containing the PSBT, or a `PsbtError` if it is malformed, isn't version 0 or its transaction is signed.
+    /// There must be a map for each input and output of the unsigned transaction, and nothing after the last one.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Psbt, PsbtError> {
+        let mut stream = bytes;
+        if read_vec_from_bytes(&mut stream, PSBT_MAGIC.len())? != PSBT_MAGIC {
+            return Err(PsbtError::InvalidFormat);
+        }
+
+        let mut unsigned_tx = None;
+        let mut unknown = BTreeMap::new();
+        for (key, value) in read_map(&mut stream)? {
+            let (key_type, key_data) = key.split_first().ok_or(PsbtError::InvalidFormat)?;
+            match *key_type {
+                PSBT_GLOBAL_UNSIGNED_TX => {
+                    expect_no_key_data(key_data)?;
+                    unsigned_tx = Some(Transaction::from_bytes(&mut &value[..])?);
+                }
+                PSBT_GLOBAL_VERSION => {
+                    expect_no_key_data(key_data)?;
+                    if read_u32_from_bytes(&mut &value[..], true)? != PSBT_VERSION {
+                        return Err(PsbtError::UnsupportedVersion);
+                    }
+                }
+                _ => {
+                    unknown.insert(key, value);
+                }
+            }
+        }
+        let unsigned_tx = unsigned_tx.ok_or(PsbtError::InvalidFormat)?;
+        check_unsigned(&unsigned_tx)?;
+
+        let inputs = read_maps(
+            &mut stream,
+            unsigned_tx.get_tx_in_list().len(),
+            PsbtInput::from_pairs,
+        )?;
+        let outputs = read_maps(
+            &mut stream,
+            unsigned_tx.get_tx_out_list().len(),
+            PsbtOutput::from_pairs,
+        )?;
+        if !stream.is_empty() {
+            return Err(PsbtError::InvalidFormat);
+        }
+
+        Ok(Psbt {
+            unsigned_tx,
+            unknown,
+            inputs,
+            outputs,
+        })
+    }
+
+    /// Serializes the PSBT in its binary format: the magic bytes, the global map and the maps of the inputs and outputs.
+    pub fn as_bytes(&self) -> Vec<u8> {
+        let mut buffer = PSBT_MAGIC.to_vec();
+
+        write_pair(
+            &mut buffer,
+            PSBT_GLOBAL_UNSIGNED_TX,
+            &[],
+            &self.unsigned_tx.as_bytes_without_witness(),
+        );
+        write_raw_pairs(&mut buffer, self.unknown.iter());
+        buffer.push(PSBT_SEPARATOR);
+
+        for input in self.inputs.iter() {
+            buffer.extend(input.as_bytes());
+        }
+        for output in self.outputs.iter() {
+            buffer.extend(output.as_bytes());
+        }
+        buffer
+    }
+
+    /// Parses a PSBT encoded in base64, the format used to copy it as text.
+    pub fn from_base64(encoded: &str) -> Result<Psbt, PsbtError> {
+        Psbt::from_bytes(&decode_base64(encoded)?)
+    }
+
+    /// Encodes the PSBT in base64.
+    pub fn to_base64(&self) -> String {
+        encode_base64(&self.as_bytes())
+    }
+
+    /// Reads a PSBT from a file, either in its binary format or encoded in base64.
+    pub fn open(path: &Path) -> Result<Psbt, PsbtError> {
+        let bytes = fs::read(path)?;
+        if bytes.starts_with(&PSBT_MAGIC) {
+            return Psbt::from_bytes(&bytes);
+        }
+        Psbt::from_base64(&String::from_utf8(bytes).map_err(|_| PsbtError::InvalidBase64)?)
+    }
+
+    /// Writes the PSBT to a file in its binary format.
+    pub fn save(&self, path: &Path) -> Result<(), PsbtError> {
+        fs::write(path, self.as_bytes())?;
+        Ok(())
+    }
+
+    pub fn get_unsigned_tx(&self) -> &Transaction {
+        &self.unsigned_tx
+    }
+
+    pub fn get_inputs(&self) -> &Vec<PsbtInput> {
+        &self.inputs
+    }
+
+    /// Returns the outputs spent by the inputs, `None` if any of them is unknown.
+    pub fn get_spent_outputs(&self) -> Option<Vec<TxOut>> {
+        self.unsigned_tx
+            .get_tx_in_list()
+            .iter()
+            .zip(self.inputs.iter())
+            .map(|(txin, input)| input.get_spent_output(txin.get_prev_output()))
+            .collect()
+    }
+
+    /// Returns the fee of the transaction, `None` if the amount of a spent output is unknown.
+    pub fn get_fee(&self) -> Option<i64> {
+        let spent: i64 = self.get_spent_outputs()?.iter().map(TxOut::get_value).sum();
+        let sent: i64 = self
+            .unsigned_tx
+            .get_tx_out_list()
+            .iter()
+            .map(TxOut::get_value)
+            .sum();
+        Some(spent - sent)
+    }
+
+    /// Signs every input that spends an output locked to one of the keys.
+    ///
+    /// # Arguments
+    ///
+    /// * `private_keys` - The keys of the signer.
+    ///
+    /// # Returns
+    ///
+    /// A Result containing the number of inputs signed, or a `PsbtError` if a signature can't be made.
+    ///
+    /// # Description
+    ///
+    /// Finalized inputs and inputs whose UTXO is unknown are skipped, since there is nothing to sign or the amount being
+    /// spent can't be checked. The signature uses the sighash type of the input, see `sign_input`, and is added as a
+    /// partial signature of the public key of the key. An input locked to a multisig script is signed with every key of
+    /// the script, see `sign_script_input`, so each cosigner adds their signatures until there are enough of them.
+    pub fn sign(&mut self, private_keys: &[SecretKey]) -> Result<usize, PsbtError> {
+        let secp = Secp256k1::signing_only();
+        let keys: Vec<(&SecretKey, Vec<u8>)> = private_keys
+            .iter()
+            .map(|private_key| {
+                let sec = PublicKey::from_secret_key(&secp, private_key).serialize();
+                (private_key, sec.to_vec())
+            })
+            .collect();
+        let mut signed = 0;
+
+        for (i, txin) in self.unsigned_tx.get_tx_in_list().iter().enumerate() {
+            let input = &mut self.inputs[i];
+            if input.is_finalized() {
+                continue;
+            }
+            let prev_output = match input.get_spent_output(txin.get_prev_output()) {
+                Some(prev_output) => prev_output,
+                None => continue,
+            };
+            let pk_script = prev_output.get_pk_script();
+            let multisig = get_multisig(input, &pk_script);
+            let mut is_signed = false;
+
+            for (private_key, sec) in keys.iter() {
+                let sighash_type = input.get_sighash_type();
+                let signature = match (&multisig, get_pubkey_hash(&pk_script)) {
+                    (Some((script, multisig)), _) if multisig.get_public_keys().contains(sec) => {
+                        sign_script_input(
+                            &self.unsigned_tx,
+                            i,
+                            &prev_output,
+                            script,
+                            private_key,
+                            sighash_type,
+                        )?
+                    }
+                    (None, Some(h160)) if hash160::Hash::hash(sec).to_byte_array() == h160 => {
+                        sign_input(
+                            &self.unsigned_tx,
+                            i,
+                            &prev_output,
+                            private_key,
+                            sighash_type,
+                        )?
+                    }
+                    _ => continue,
+                };
+                input.add_partial_sig(sec.clone(), signature);
+                is_signed = true;
+            }
+            if is_signed {
+                signed += 1;
+            }
+        }
+        Ok(signed)
+    }
+
+    /// Combines the data of another PSBT of the same transaction into this one, such as the signatures of other parties.
+    ///
+    /// # Returns
+    ///
+    /// `PsbtError::TransactionMismatch` if the unsigned transactions are different.
+    pub fn combine(&mut self, other: &Psbt) -> Result<(), PsbtError> {
+        if self.unsigned_tx.get_id() != other.unsigned_tx.get_id() {
+            return Err(PsbtError::TransactionMismatch);
+        }
+        for (input, other_input) in self.inputs.iter_mut().zip(other.inputs.iter()) {
+            input.combine(other_input);
+        }
+        for (output, other_output) in self.outputs.iter_mut().zip(other.outputs.iter()) {
+            output.combine(other_output);
+        }
+        for (key, value) in other.unknown.iter() {
+            self.unknown.entry(key.clone()).or_insert(value.clone());
+        }
+        Ok(())
+    }
+
+    /// Builds the final signature script or witness of every input from its partial signatures.
+    ///
+    /// # Returns
+    ///
+    /// `PsbtError::CantFinalize` with the index of the first input that lacks a signature or spends an output of a type
+    /// that can't be finalized, in which case the PSBT isn't changed.
+    ///
+    /// # Description
+    ///
+    /// A P2PKH input gets the signature and public key as its signature script, and a P2WPKH input as its witness, with
+    /// the signature of the key whose hash the output is locked to. An input locked to a multisig script gets the dummy
+    /// element, the signatures it requires and the script, see `MultisigScript::get_unlocking_data`, as its signature
+    /// script if it is P2SH or as its witness if it is P2WSH. Inputs that are already finalized are kept.
+    pub fn finalize(&mut self) -> Result<(), PsbtError> {
+        let mut finalized = self.inputs.clone();
+
+        for (i, txin) in self.unsigned_tx.get_tx_in_list().iter().enumerate() {
+            let input = &mut finalized[i];
+            if input.is_finalized() {
+                continue;
+            }
+            let pk_script = input
+                .get_spent_output(txin.get_prev_output())
+                .ok_or(PsbtError::MissingUtxo(i))?
+                .get_pk_script();
+            if let Some((script, multisig)) = get_multisig(input, &pk_script) {
+                let mut unlocking_data = multisig
+                    .get_unlocking_data(input.get_partial_sigs())
+                    .map_err(|_| PsbtError::CantFinalize(i))?;
+                unlocking_data.push(script);
+                match ScriptType::from_pk_script(&pk_script) {
+                    ScriptType::P2wsh => input.finalize(None, Some(unlocking_data)),
+                    _ => input.finalize(Some(Script::new(Some(unlocking_data)).as_bytes()), None),
+                }
+                continue;
+            }
+            let h160 = get_pubkey_hash(&pk_script).ok_or(PsbtError::CantFinalize(i))?;
+            let (sec, sig) = input
+                .get_partial_sigs()
+                .iter()
+                .find(|(sec, _)| hash160::Hash::hash(sec).to_byte_array() == h160)
+                .map(|(sec, sig)| (sec.clone(), sig.clone()))
+                .ok_or(PsbtError::CantFinalize(i))?;
+
+            match ScriptType::from_pk_script(&pk_script) {
+                ScriptType::P2wpkh => input.finalize(None, Some(vec![sig, sec])),
+                ScriptType::P2pkh => {
+                    input.finalize(Some(Script::new(Some(vec![sig, sec])).as_bytes()), None)
+                }
+                _ => return Err(PsbtError::CantFinalize(i)),
+            }
+        }
+        self.inputs = finalized;
+        Ok(())
+    }
+
+    /// Checks if every input is finalized, so the transaction can be extracted.
+    pub fn is_finalized(&self) -> bool {
+        self.inputs.iter().all(PsbtInput::is_finalized)
+    }
+
+    /// Extracts the signed transaction, ready to be broadcast.
+    ///
+    /// # Returns
+    ///
+    /// A Result containing the unsigned transaction with the final signature scripts and witnesses of the inputs, or
+    /// `PsbtError::NotFinalized` with the index of the first input that isn't finalized.
+    pub fn extract(&self) -> Result<Transaction, PsbtError> {
+        let mut transaction = self.unsigned_tx.clone();
+
+        for (i, input) in self.inputs.iter().enumerate() {
+            if !input.is_finalized() {
+                return Err(PsbtError::NotFinalized(i));
+            }
+            if let Some(final_script_sig) = input.get_final_script_sig() {
+                transaction.set_signature(i, final_script_sig.clone());
+            }
+            if let Some(final_script_witness) = input.get_final_script_witness() {
+                transaction.set_witness(i, final_script_witness.clone());
+            }
+        }
+        Ok(transaction)
+    }
+}
+
+/// Returns an error if an input of the unsigned transaction of a PSBT has a signature script or a witness.
+fn check_unsigned(transaction: &Transaction) -> Result<(), PsbtError> {
+    match transaction
+        .get_tx_in_list()
+        .iter()
+        .all(|txin| txin.get_signature_script().is_empty() && txin.get_witness().is_empty())
+    {
+        true => Ok(()),
+        false => Err(PsbtError::SignedUnsignedTx),
+    }
+}
+
+/// Returns the multisig script of an input that spends a P2SH or P2WSH output, along with its serialization, or `None` if
+/// the input has no redeem or witness script or it isn't a multisig one.
+fn get_multisig(input: &PsbtInput, pk_script: &[u8]) -> Option<(Vec<u8>, MultisigScript)> {
+    let script = match ScriptType::from_pk_script(pk_script) {
+        ScriptType::P2sh => input.get_redeem_script(),
+        ScriptType::P2wsh => input.get_witness_script(),
+        _ => None,
+    }?;
+    Some((script.clone(), MultisigScript::from_bytes(script).ok()?))
+}
+
+/// Reads the maps of the inputs or the outputs of a PSBT.
+fn read_maps<T>(
+    stream: &mut dyn Read,
+    count: usize,
+    from_pairs: fn(Vec<PsbtPair>) -> Result<T, PsbtError>,
+) -> Result<Vec<T>, PsbtError> {
+    (0..count).map(|_| from_pairs(read_map(stream)?)).collect()
+}
+
+#[cfg(test)]
+mod psbt_transaction_test {
+    use node::block_mod::{script_type::get_pushes, tx_in::TxIn};
+    use secp256k1::{ecdsa::Signature, Message};
+
+    use super::*;
+    use crate::multisig::multisig_script::MultisigKind;
+    use crate::transactions::create_transactions::{
+        pk_script_from_public_key, verify_input_signature, witness_pk_script_from_public_key,
+    };
+
+    fn public_key(private_key: &SecretKey) -> Vec<u8> {
+        PublicKey::from_secret_key(&Secp256k1::new(), private_key)
+            .serialize()
+            .to_vec()
+    }
+
+    /// Creates a PSBT that spends a P2PKH output of the first key and a P2WPKH output of the second one.
+    fn test_psbt(keys: &[SecretKey]) -> Result<Psbt, PsbtError> {
+        let prev_tx = Transaction::new(
+            1,
+            vec![TxIn::new(vec![9; 32], 0, vec![1], 0xffffffff)],
+            vec![TxOut::new(
+                300000,
+                pk_script_from_public_key(&public_key(&keys[0])),
+            )],
+            0,
+        );
+        let prev_outputs = vec![
+            prev_tx.get_tx_out_list()[0].clone(),
+            TxOut::new(
+                200000,
+                witness_pk_script_from_public_key(&public_key(&keys[1])),
+            ),
+        ];
+        let unsigned_tx = Transaction::new(
+            1,
+            vec![
+                TxIn::new(prev_tx.get_id(), 0, vec![], 0xffffffff),
+                TxIn::new(vec![2; 32], 1, vec![], 0xffffffff),
+            ],
+            vec![TxOut::new(
+                490000,
+                witness_pk_script_from_public_key(&[2; 33]),
+            )],
+            0,
+        );
+        let prev_tx_id = prev_tx.get_id();
+
+        Psbt::new(unsigned_tx, &prev_outputs, &[], &|tx_id| {
+            (tx_id == prev_tx_id).then(|| prev_tx.clone())
+        })
+    }
+
+    #[test]
+    fn test_serialization_round_trip() -> Result<(), PsbtError> {
+        let keys = [
+            SecretKey::from_slice(&[1; 32])?,
+            SecretKey::from_slice(&[2; 32])?,
+        ];
+        let mut psbt = test_psbt(&keys)?;
+        assert_eq!(psbt.get_fee(), Some(10000));
+
+        assert_eq!(Psbt::from_bytes(&psbt.as_bytes())?, psbt);
+        assert!(psbt.to_base64().starts_with("cHNidP8B"));
+        psbt.sign(&keys[..1])?;
+        assert_eq!(Psbt::from_base64(&psbt.to_base64())?, psbt);
+
+        assert_eq!(
+            Psbt::from_bytes(&psbt.as_bytes()[1..]),
+            Err(PsbtError::InvalidFormat)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_sign_combine_finalize_and_extract() -> Result<(), PsbtError> {
+        let keys = [
+            SecretKey::from_slice(&[1; 32])?,
+            SecretKey::from_slice(&[2; 32])?,
+        ];
+        let mut first = test_psbt(&keys)?;
+        let mut second = first.clone();
+
+        assert_eq!(first.sign(&keys[..1])?, 1);
+        assert_eq!(second.sign(&keys[1..])?, 1);
+        assert_eq!(first.finalize(), Err(PsbtError::CantFinalize(1)));
+        assert_eq!(first.extract(), Err(PsbtError::NotFinalized(0)));
+
+        first.combine(&second)?;
+        first.finalize()?;
+        assert!(first.is_finalized());
+        let transaction = first.extract()?;
+
+        let prev_outputs = first.get_spent_outputs().ok_or(PsbtError::MissingUtxo(0))?;
+        for (i, prev_output) in prev_outputs.iter().enumerate() {
+            assert!(verify_input_signature(&transaction, i, prev_output));
+        }
+        assert_eq!(
+            transaction.get_tx_out_list(),
+            first.get_unsigned_tx().get_tx_out_list()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_multisig_inputs_are_signed_by_each_cosigner() -> Result<(), PsbtError> {
+        let keys = [
+            SecretKey::from_slice(&[1; 32])?,
+            SecretKey::from_slice(&[2; 32])?,
+            SecretKey::from_slice(&[3; 32])?,
+        ];
+        let multisig = MultisigScript::new(2, keys.iter().map(public_key).collect())
+            .map_err(|_| PsbtError::Signing)?;
+        let script = multisig.as_bytes();
+        let prev_tx = Transaction::new(
+            1,
+            vec![TxIn::new(vec![9; 32], 0, vec![1], 0xffffffff)],
+            vec![TxOut::new(
+                300000,
+                MultisigKind::P2sh.get_pk_script(&script),
+            )],
+            0,
+        );
+        let prev_outputs = vec![
+            prev_tx.get_tx_out_list()[0].clone(),
+            TxOut::new(200000, MultisigKind::P2wsh.get_pk_script(&script)),
+        ];
+        let unsigned_tx = Transaction::new(
+            1,
+            vec![
+                TxIn::new(prev_tx.get_id(), 0, vec![], 0xffffffff),
+                TxIn::new(vec![2; 32], 1, vec![], 0xffffffff),
+            ],
+            vec![TxOut::new(490000, vec![0x51])],
+            0,
+        );
+        let mut first = Psbt::new(
+            unsigned_tx,
+            &prev_outputs,
+            std::slice::from_ref(&script),
+            &|_| Some(prev_tx.clone()),
+        )?;
+        let mut second = first.clone();
+
+        assert_eq!(first.sign(&keys[..1])?, 2);
+        assert_eq!(first.finalize(), Err(PsbtError::CantFinalize(0)));
+        assert_eq!(second.sign(&keys[2..])?, 2);
+        first.combine(&second)?;
+        first.finalize()?;
+        let transaction = first.extract()?;
+
+        let txins = transaction.get_tx_in_list();
+        let pushes = get_pushes(txins[0].get_signature_script()).ok_or(PsbtError::Signing)?;
+        assert_eq!(pushes.len(), 4);
+        assert!(pushes[0].is_empty());
+        assert_eq!(pushes[3], &script[..]);
+
+        let witness = txins[1].get_witness();
+        assert!(txins[1].get_signature_script().is_empty());
+        assert_eq!(witness.len(), 4);
+        assert_eq!(witness[3], script);
+        let signature_hash = transaction.witness_v0_sig_hash(
+            1,
+            &script,
+            200000,
+            witness[1][witness[1].len() - 1] as u32,
+        );
+        let message = Message::from_slice(&signature_hash)?;
+        let signers = multisig
+            .get_public_keys()
+            .iter()
+            .filter(|signer| **signer != public_key(&keys[1]));
+        let secp = Secp256k1::verification_only();
+        for (signature, signer) in witness[1..3].iter().zip(signers) {
+            let der = &signature[..signature.len() - 1];
+            assert!(secp
+                .verify_ecdsa(
+                    &message,
+                    &Signature::from_der(der)?,
+                    &PublicKey::from_slice(signer)?
+                )
+                .is_ok());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_combine_other_transaction_fails() -> Result<(), PsbtError> {
+        let keys = [
+            SecretKey::from_slice(&[1; 32])?,
+            SecretKey::from_slice(&[2; 32])?,
+        ];
+        let mut psbt = test_psbt(&keys)?;
+        let other = test_psbt(&[keys[1], keys[0]])?;
+
+        assert_eq!(psbt.combine(&other), Err(PsbtError::TransactionMismatch));
+        Ok(())
+    }
+
+    /// The first two valid PSBTs of the BIP 174 test vectors, a P2PKH input with empty outputs and a finalized P2PKH
+    /// input with an unsigned P2SH-P2WPKH one.
+    const BIP174_VALID: [&str; 2] = [
+        "cHNidP8BAHUCAAAAASaBcTce3/KF6Tet7qSze3gADAVmy7OtZGQXE8pCFxv2AAAAAAD+////AtPf9QUAAAAAGXapFNDFmQPFusKGh2DpD9UhpGZap2UgiKwA4fUFAAAAABepFDVF5uM7gyxHBQ8k0+65PJwDlIvHh7MuEwAAAQD9pQEBAAAAAAECiaPHHqtNIOA3G7ukzGmPopXJRjr6Ljl/hTPMti+VZ+UBAAAAFxYAFL4Y0VKpsBIDna89p95PUzSe7LmF/////4b4qkOnHf8USIk6UwpyN+9rRgi7st0tAXHmOuxqSJC0AQAAABcWABT+Pp7xp0XpdNkCxDVZQ6vLNL1TU/////8CAMLrCwAAAAAZdqkUhc/xCX/Z4Ai7NK9wnGIZeziXikiIrHL++E4sAAAAF6kUM5cluiHv1irHU6m80GfWx6ajnQWHAkcwRAIgJxK+IuAnDzlPVoMR3HyppolwuAJf3TskAinwf4pfOiQCIAGLONfc0xTnNMkna9b7QPZzMlvEuqFEyADS8vAtsnZcASED0uFWdJQbrUqZY3LLh+GFbTZSYG2YVi/jnF6efkE/IQUCSDBFAiEA0SuFLYXc2WHS9fSrZgZU327tzHlMDDPOXMMJ/7X85Y0CIGczio4OFyXBl/saiK9Z9R5E5CVbIBZ8hoQDHAXR8lkqASECI7cr7vCWXRC+B3jv7NYfysb3mk6haTkzgHNEZPhPKrMAAAAAAAAA",
+        "cHNidP8BAKACAAAAAqsJSaCMWvfEm4IS9Bfi8Vqz9cM9zxU4IagTn4d6W3vkAAAAAAD+////qwlJoIxa98SbghL0F+LxWrP1wz3PFTghqBOfh3pbe+QBAAAAAP7///8CYDvqCwAAAAAZdqkUdopAu9dAy+gdmI5x3ipNXHE5ax2IrI4kAAAAAAAAGXapFG9GILVT+glechue4O/p+gOcykWXiKwAAAAAAAEHakcwRAIgR1lmF5fAGwNrJZKJSGhiGDR9iYZLcZ4ff89X0eURZYcCIFMJ6r9Wqk2Ikf/REf3xM286KdqGbX+EhtdVRs7tr5MZASEDXNxh/HupccC1AaZGoqg7ECy0OIEhfKaC3Ibi1z+ogpIAAQEgAOH1BQAAAAAXqRQ1RebjO4MsRwUPJNPuuTycA5SLx4cBBBYAFIXRNTfy4mVAWjTbr6nj3aAfuCMIAAAA",
+    ];
+
+    /// The signed transaction of the first valid vector, which BIP 174 gives as an invalid PSBT without magic bytes.
+    const BIP174_NETWORK_TX: &str = "AgAAAAEmgXE3Ht/yhek3re6ks3t4AAwFZsuzrWRkFxPKQhcb9gAAAABqRzBEAiBwsiRRI+a/R01gxbUMBD1MaRpdJDXwmjSnZiqdwlF5CgIgATKcqdrPKAvfMHQOwDkEIkIsgctFg5RXrrdvwS7dlbMBIQJlfRGNM1e44PTCzUbbezn22cONmnCry5st5dyNv+TOMf7///8C09/1BQAAAAAZdqkU0MWZA8W6woaHYOkP1SGkZlqnZSCIrADh9QUAAAAAF6kUNUXm4zuDLEcFDyTT7rk8nAOUi8eHsy4TAA==";
+
+    #[test]
+    fn test_bip174_valid_vectors() -> Result<(), PsbtError> {
+        for encoded in BIP174_VALID {
+            let psbt = Psbt::from_base64(encoded)?;
+            assert_eq!(Psbt::from_bytes(&psbt.as_bytes())?, psbt);
+        }
+
+        let psbt = Psbt::from_base64(BIP174_VALID[0])?;
+        assert_eq!(psbt.get_inputs().len(), 1);
+        assert_eq!(psbt.get_unsigned_tx().get_tx_out_list().len(), 2);
+        assert_eq!(psbt.to_base64(), BIP174_VALID[0]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_bip174_invalid_vectors() -> Result<(), PsbtError> {
+        assert!(Psbt::from_base64(BIP174_NETWORK_TX).is_err());
+
+        let bytes = decode_base64(BIP174_VALID[0])?;
+        let unsigned_tx = Psbt::from_bytes(&bytes)?.get_unsigned_tx().clone();
+
+        // The maps of the outputs are missing.
+        assert!(Psbt::from_bytes(&bytes[..bytes.len() - 2]).is_err());
+
+        // There are bytes after the last map.
+        let mut trailing = bytes.clone();
+        trailing.push(PSBT_SEPARATOR);
+        assert_eq!(Psbt::from_bytes(&trailing), Err(PsbtError::InvalidFormat));
+
+        // The unsigned transaction has a filled signature script.
+        let network_tx = Transaction::from_bytes(&mut &decode_base64(BIP174_NETWORK_TX)?[..])?;
+        let mut signed = PSBT_MAGIC.to_vec();
+        write_pair(
+            &mut signed,
+            PSBT_GLOBAL_UNSIGNED_TX,
+            &[],
+            &network_tx.as_bytes(),
+        );
+        signed.extend([PSBT_SEPARATOR; 4]);
+        assert_eq!(Psbt::from_bytes(&signed), Err(PsbtError::SignedUnsignedTx));
+
+        // There is no unsigned transaction in the global map.
+        let mut missing_tx = PSBT_MAGIC.to_vec();
+        missing_tx.extend([PSBT_SEPARATOR; 4]);
+        assert_eq!(Psbt::from_bytes(&missing_tx), Err(PsbtError::InvalidFormat));
+
+        // The key of the unsigned transaction has key data.
+        let mut key_data = PSBT_MAGIC.to_vec();
+        write_pair(
+            &mut key_data,
+            PSBT_GLOBAL_UNSIGNED_TX,
+            &[0],
+            &unsigned_tx.as_bytes(),
+        );
+        key_data.extend([PSBT_SEPARATOR; 4]);
+        assert_eq!(Psbt::from_bytes(&key_data), Err(PsbtError::InvalidFormat));
+
+        // The map of the input has a repeated key.
+        let mut duplicate = PSBT_MAGIC.to_vec();
+        write_pair(
+            &mut duplicate,
+            PSBT_GLOBAL_UNSIGNED_TX,
+            &[],
+            &unsigned_tx.as_bytes(),
+        );
+        duplicate.push(PSBT_SEPARATOR);
+        write_pair(&mut duplicate, PSBT_IN_SIGHASH_TYPE, &[], &[1, 0, 0, 0]);
+        write_pair(&mut duplicate, PSBT_IN_SIGHASH_TYPE, &[], &[1, 0, 0, 0]);
+        duplicate.extend([PSBT_SEPARATOR; 3]);
+        assert_eq!(Psbt::from_bytes(&duplicate), Err(PsbtError::DuplicateKey));
+        Ok(())
+    }
+}
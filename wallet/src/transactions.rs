@@ -1,4 +1,10 @@
+pub mod address;
+pub mod address_error;
+pub mod bech32;
+pub mod coin_selection;
 pub mod create_transaction_error;
 pub mod create_transactions;
 pub mod create_transactios_constants;
+#[cfg(feature = "gui")]
 pub mod transaction_view;
+pub mod tx_balance;
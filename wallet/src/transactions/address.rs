@@ -0,0 +1,443 @@
+use bitcoin_hashes::{hash160, sha256, sha256d, Hash};
+use node::{
+    block_mod::{
+        script::Script,
+        script_type::{get_pubkey_hash, get_script_hash, ScriptType},
+    },
+    network::bitcoin_network::BitcoinNetwork,
+};
+
+use super::{
+    address_error::AddressError,
+    bech32::{decode_segwit_address, encode_segwit_address},
+    create_transactios_constants::ADDRESS_LEN,
+};
+
+/// Kind of output an address pays to, together with the hash or witness program it commits to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AddressPayload {
+    PubkeyHash(Vec<u8>),
+    ScriptHash(Vec<u8>),
+    WitnessProgram { version: u8, program: Vec<u8> },
+}
+
+/// Represents a Bitcoin address of a given network.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Address {
+    network: BitcoinNetwork,
+    payload: AddressPayload,
+}
+
+impl Address {
+    /// Parses a Base58Check or a native SegWit (bech32/bech32m) address.
+    ///
+    /// # Arguments
+    ///
+    /// * `address`: A slice of bytes representing the address.
+    /// * `network`: The network the address is expected to belong to.
+    ///
+    /// # Returns
+    ///
+    /// A Result containing the parsed `Address`, or an `AddressError` describing why it is invalid.
+    ///
+    /// # Description
+    ///
+    /// Addresses starting with the bech32 human readable part of a known network followed by `1` are decoded as SegWit
+    /// addresses, the rest as Base58Check ones.
+    pub fn parse(address: &[u8], network: BitcoinNetwork) -> Result<Address, AddressError> {
+        let text = String::from_utf8_lossy(address).to_lowercase();
+        let is_segwit = [BitcoinNetwork::Mainnet, BitcoinNetwork::Testnet]
+            .iter()
+            .any(|network| text.starts_with(&format!("{}1", network.get_bech32_hrp())));
+
+        if is_segwit {
+            let (version, program) =
+                decode_segwit_address(network.get_bech32_hrp(), &String::from_utf8_lossy(address))?;
+            return Ok(Address {
+                network,
+                payload: AddressPayload::WitnessProgram { version, program },
+            });
+        }
+        Address::parse_base58(address, network)
+    }
+
+    /// Parses a Base58Check encoded address.
+    ///
+    /// # Arguments
+    ///
+    /// * `address`: A slice of bytes representing the Base58 encoded address.
+    /// * `network`: The network the address is expected to belong to.
+    ///
+    /// # Returns
+    ///
+    /// A Result containing the parsed `Address`, or an `AddressError` describing why it is invalid.
+    ///
+    /// # Description
+    ///
+    /// The decoded address must be 25 bytes long: a version prefix, a 20 bytes hash and a 4 bytes checksum. The checksum is the
+    /// first 4 bytes of the double sha256 of the prefix and the hash, so a mistyped character is detected here instead of
+    /// producing a script nobody can spend. The prefix selects between P2PKH and P2SH and must belong to the given network.
+    fn parse_base58(address: &[u8], network: BitcoinNetwork) -> Result<Address, AddressError> {
+        let combined = bs58::decode(address)
+            .into_vec()
+            .map_err(|_| AddressError::InvalidBase58)?;
+
+        if combined.len() != ADDRESS_LEN {
+            return Err(AddressError::InvalidLength);
+        }
+
+        let (data, checksum) = combined.split_at(ADDRESS_LEN - 4);
+        if sha256d::Hash::hash(data)[..4] != *checksum {
+            return Err(AddressError::InvalidChecksum);
+        }
+
+        let hash = data[1..].to_vec();
+        let payload = match data[0] {
+            version if version == network.get_p2pkh_prefix() => AddressPayload::PubkeyHash(hash),
+            version if version == network.get_p2sh_prefix() => AddressPayload::ScriptHash(hash),
+            version if is_known_version(version) => return Err(AddressError::WrongNetwork),
+            _ => return Err(AddressError::UnknownVersion),
+        };
+
+        Ok(Address { network, payload })
+    }
+
+    /// Creates the P2PKH address of a public key.
+    pub fn from_public_key(public_key: &[u8], network: BitcoinNetwork) -> Address {
+        let h160 = hash160::Hash::hash(public_key).to_byte_array().to_vec();
+
+        Address {
+            network,
+            payload: AddressPayload::PubkeyHash(h160),
+        }
+    }
+
+    /// Creates the native SegWit (P2WPKH) address of a public key.
+    pub fn p2wpkh_from_public_key(public_key: &[u8], network: BitcoinNetwork) -> Address {
+        let h160 = hash160::Hash::hash(public_key).to_byte_array().to_vec();
+
+        Address {
+            network,
+            payload: AddressPayload::WitnessProgram {
+                version: 0,
+                program: h160,
+            },
+        }
+    }
+
+    /// Creates the P2SH address of a redeem script.
+    pub fn p2sh_from_script(script: &[u8], network: BitcoinNetwork) -> Address {
+        Address {
+            network,
+            payload: AddressPayload::ScriptHash(
+                hash160::Hash::hash(script).to_byte_array().to_vec(),
+            ),
+        }
+    }
+
+    /// Creates the native SegWit (P2WSH) address of a witness script.
+    pub fn p2wsh_from_script(script: &[u8], network: BitcoinNetwork) -> Address {
+        Address {
+            network,
+            payload: AddressPayload::WitnessProgram {
+                version: 0,
+                program: sha256::Hash::hash(script).to_byte_array().to_vec(),
+            },
+        }
+    }
+
+    /// Creates the address an output script pays to.
+    ///
+    /// # Returns
+    ///
+    /// The address of a P2PKH, P2SH, P2WPKH, P2WSH or P2TR script, or `None` if the script isn't of a standard kind.
+    pub fn from_pk_script(pk_script: &[u8], network: BitcoinNetwork) -> Option<Address> {
+        let payload = match ScriptType::from_pk_script(pk_script) {
+            ScriptType::P2pkh => AddressPayload::PubkeyHash(get_pubkey_hash(pk_script)?.to_vec()),
+            ScriptType::P2sh => AddressPayload::ScriptHash(get_script_hash(pk_script)?.to_vec()),
+            ScriptType::P2wpkh | ScriptType::P2wsh => AddressPayload::WitnessProgram {
+                version: 0,
+                program: pk_script[2..].to_vec(),
+            },
+            ScriptType::P2tr => AddressPayload::WitnessProgram {
+                version: 1,
+                program: pk_script[2..].to_vec(),
+            },
+            ScriptType::Unknown => return None,
+        };
+        Some(Address { network, payload })
+    }
+
+    pub fn get_network(&self) -> BitcoinNetwork {
+        self.network
+    }
+
+    pub fn get_payload(&self) -> &AddressPayload {
+        &self.payload
+    }
+
+    /// Returns the script (public key script) that locks an output to this address.
+    pub fn get_pk_script(&self) -> Vec<u8> {
+        match &self.payload {
+            AddressPayload::PubkeyHash(h160) => p2pkh(h160.clone()).as_bytes(),
+            AddressPayload::ScriptHash(h160) => p2sh(h160.clone()).as_bytes(),
+            AddressPayload::WitnessProgram { version, program } => {
+                segwit(*version, program.clone()).as_bytes()
+            }
+        }
+    }
+
+    /// Returns the Base58Check encoding of the address.
+    pub fn as_bytes(&self) -> Vec<u8> {
+        match &self.payload {
+            AddressPayload::PubkeyHash(h160) => {
+                encode_base58_check(self.network.get_p2pkh_prefix(), h160)
+            }
+            AddressPayload::ScriptHash(h160) => {
+                encode_base58_check(self.network.get_p2sh_prefix(), h160)
+            }
+            AddressPayload::WitnessProgram { version, program } => {
+                encode_segwit_address(self.network.get_bech32_hrp(), *version, program).into_bytes()
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for Address {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", String::from_utf8_lossy(&self.as_bytes()))
+    }
+}
+
+/// Checks if a version prefix belongs to an address of any supported network.
+fn is_known_version(version: u8) -> bool {
+    [BitcoinNetwork::Mainnet, BitcoinNetwork::Testnet]
+        .iter()
+        .any(|network| {
+            version == network.get_p2pkh_prefix() || version == network.get_p2sh_prefix()
+        })
+}
+
+/// Creates a pay-to-public-key-hash (P2PKH) script with the given hash160 value.
+///
+/// # Arguments
+///
+/// * `h160`: A vector of bytes representing the hash160 value.
+///
+/// # Returns
+///
+/// A `Script` object representing the P2PKH script.
+///
+/// # Description
+///
+/// This function creates a P2PKH script using the given hash160 value. The P2PKH script consists of a series of opcodes and data values.
+/// The script starts with the opcode `OP_DUP` (0x76) to duplicate the top stack item, followed by `OP_HASH160` (0xa9) to hash the duplicated
+/// value. Then, the hash160 value is added to the script. After that, `OP_EQUALVERIFY` (0x88) is used to verify that the top two stack items
+/// are equal. Finally, `OP_CHECKSIG` (0xac) is used to verify the signature using the public key.
+///
+pub fn p2pkh(h160: Vec<u8>) -> Script {
+    Script::new(Some(vec![
+        vec![0x76],
+        vec![0xa9],
+        h160,
+        vec![0x88],
+        vec![0xac],
+    ]))
+}
+
+/// Creates a pay-to-script-hash (P2SH) script with the given hash160 value.
+///
+/// # Description
+///
+/// The script is `OP_HASH160` (0xa9), the hash160 of the redeem script and `OP_EQUAL` (0x87).
+pub fn p2sh(h160: Vec<u8>) -> Script {
+    Script::new(Some(vec![vec![0xa9], h160, vec![0x87]]))
+}
+
+/// Creates a native SegWit script with the given witness version and program.
+///
+/// # Description
+///
+/// The script is the opcode pushing the version (`OP_0` (0x00) or `OP_1` (0x51) to `OP_16` (0x60)) followed by the program.
+/// Version 0 with a 20 bytes program is P2WPKH and version 1 with a 32 bytes program is P2TR.
+pub fn segwit(version: u8, program: Vec<u8>) -> Script {
+    let version_opcode = match version {
+        0 => 0x00,
+        version => 0x50 + version,
+    };
+    Script::new(Some(vec![vec![version_opcode], program]))
+}
+
+/// Encodes a version prefix and a payload using Base58Check.
+///
+/// # Arguments
+///
+/// * `version`: The version prefix byte.
+/// * `payload`: The bytes to encode after the prefix.
+///
+/// # Returns
+///
+/// A vector of bytes with the Base58Check encoding of `version || payload || checksum`.
+pub fn encode_base58_check(version: u8, payload: &[u8]) -> Vec<u8> {
+    let data = [&[version][..], payload].concat();
+    let doble_hash = sha256d::Hash::hash(&data);
+    let checksum = &doble_hash[..4];
+
+    bs58::encode([&data[..], checksum].concat()).into_vec()
+}
+
+#[cfg(test)]
+mod address_test {
+    use node::{messages::read_from_bytes::decode_hex, network::bitcoin_network::BitcoinNetwork};
+
+    use crate::transactions::{
+        address_error::AddressError, create_transaction_error::TransactionCreateError,
+    };
+
+    use super::{Address, AddressPayload};
+
+    #[test]
+    fn test_parse_valid_address() -> Result<(), TransactionCreateError> {
+        let address = Address::parse(
+            b"mzx5YhAH9kNHtcN481u6WkjeHjYtVeKVh2",
+            BitcoinNetwork::Testnet,
+        );
+        let h160 = decode_hex("d52ad7ca9b3d096a38e752c2018e6fbc40cdf26f")?;
+
+        assert_eq!(
+            address.as_ref().map(|address| address.get_payload()),
+            Ok(&AddressPayload::PubkeyHash(h160))
+        );
+        assert_eq!(
+            address.map(|address| address.to_string()),
+            Ok("mzx5YhAH9kNHtcN481u6WkjeHjYtVeKVh2".to_string())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_p2sh_address() -> Result<(), TransactionCreateError> {
+        let address = Address::parse(
+            b"3J98t1WpEZ73CNmQviecrnyiWrnqRhWNLy",
+            BitcoinNetwork::Mainnet,
+        );
+        let h160 = decode_hex("b472a266d0bd89c13706a4132ccfb16f7c3b9fcb")?;
+
+        assert_eq!(
+            address.map(|address| address.get_pk_script()),
+            Ok([vec![0xa9, 20], h160, vec![0x87]].concat())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_segwit_addresses() -> Result<(), TransactionCreateError> {
+        let p2wpkh = Address::parse(
+            b"tb1qw508d6qejxtdg4y5r3zarvary0c5xw7kxpjzsx",
+            BitcoinNetwork::Testnet,
+        );
+        let p2tr = Address::parse(
+            b"bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqzk5jj0",
+            BitcoinNetwork::Mainnet,
+        );
+
+        assert_eq!(
+            p2wpkh.map(|address| address.get_pk_script()),
+            Ok(decode_hex("0014751e76e8199196d454941c45d1b3a323f1433bd6")?)
+        );
+        assert_eq!(
+            p2tr.map(|address| address.get_pk_script()),
+            Ok(decode_hex(
+                "512079be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798"
+            )?)
+        );
+        assert_eq!(
+            Address::parse(
+                b"bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4",
+                BitcoinNetwork::Testnet
+            ),
+            Err(AddressError::WrongNetwork)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_address_from_pk_script() -> Result<(), AddressError> {
+        for (address, network) in [
+            (
+                "mzx5YhAH9kNHtcN481u6WkjeHjYtVeKVh2",
+                BitcoinNetwork::Testnet,
+            ),
+            (
+                "3J98t1WpEZ73CNmQviecrnyiWrnqRhWNLy",
+                BitcoinNetwork::Mainnet,
+            ),
+            (
+                "tb1qw508d6qejxtdg4y5r3zarvary0c5xw7kxpjzsx",
+                BitcoinNetwork::Testnet,
+            ),
+            (
+                "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqzk5jj0",
+                BitcoinNetwork::Mainnet,
+            ),
+        ] {
+            let pk_script = Address::parse(address.as_bytes(), network)?.get_pk_script();
+            assert_eq!(
+                Address::from_pk_script(&pk_script, network).map(|address| address.to_string()),
+                Some(address.to_string())
+            );
+        }
+        assert_eq!(
+            Address::from_pk_script(&[0x6a], BitcoinNetwork::Mainnet),
+            None
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_p2wpkh_from_public_key() -> Result<(), TransactionCreateError> {
+        let public_key =
+            decode_hex("0279BE667EF9DCBBAC55A06295CE870B07029BFCDB2DCE28D959F2815B16F81798")?;
+
+        let address = Address::p2wpkh_from_public_key(&public_key, BitcoinNetwork::Mainnet);
+
+        assert_eq!(
+            address.to_string(),
+            "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_mistyped_address_fails_checksum() {
+        let mistyped = b"mzx5YhAH9kNHtcN481u6WkjeHjYtVeKVh3";
+
+        assert_eq!(
+            Address::parse(mistyped, BitcoinNetwork::Testnet),
+            Err(AddressError::InvalidChecksum)
+        );
+    }
+
+    #[test]
+    fn test_invalid_addresses() {
+        assert_eq!(
+            Address::parse(b"", BitcoinNetwork::Testnet),
+            Err(AddressError::InvalidLength)
+        );
+        assert_eq!(
+            Address::parse(b"mzx5YhAH9kNHtcN481u6Wkje0", BitcoinNetwork::Testnet),
+            Err(AddressError::InvalidBase58)
+        );
+        assert_eq!(
+            Address::parse(b"mzx5YhAH9kNHtcN481u6Wkje", BitcoinNetwork::Testnet),
+            Err(AddressError::InvalidLength)
+        );
+        assert_eq!(
+            Address::parse(
+                b"1BgGZ9tcN4rm9KBzDn7KprQz87SZ26SAMH",
+                BitcoinNetwork::Testnet
+            ),
+            Err(AddressError::WrongNetwork)
+        );
+    }
+}
@@ -0,0 +1,25 @@
+#[derive(Debug, PartialEq, Eq)]
+pub enum AddressError {
+    InvalidBase58,
+    InvalidLength,
+    InvalidChecksum,
+    UnknownVersion,
+    WrongNetwork,
+    InvalidBech32,
+    InvalidWitnessProgram,
+}
+
+impl std::fmt::Display for AddressError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let description = match self {
+            AddressError::InvalidBase58 => "it contains characters that are not valid in Base58",
+            AddressError::InvalidLength => "it has an invalid length",
+            AddressError::InvalidChecksum => "the checksum doesn't match, check for typos",
+            AddressError::UnknownVersion => "it is not a P2PKH, P2SH or SegWit address",
+            AddressError::WrongNetwork => "it belongs to another network",
+            AddressError::InvalidBech32 => "it is not a valid bech32 string",
+            AddressError::InvalidWitnessProgram => "its witness version or program is invalid",
+        };
+        write!(f, "{}", description)
+    }
+}
@@ -0,0 +1,336 @@
+use super::{
+    address_error::AddressError,
+    create_transactios_constants::{BECH32M_CONST, BECH32_CHARSET, BECH32_CONST, BECH32_MAX_LEN},
+};
+
+/// Checksum variants of the bech32 encoding.
+///
+/// Witness version 0 addresses use `Bech32` (BIP173), later versions use `Bech32m` (BIP350).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bech32Variant {
+    Bech32,
+    Bech32m,
+}
+
+impl Bech32Variant {
+    fn get_const(&self) -> u32 {
+        match self {
+            Bech32Variant::Bech32 => BECH32_CONST,
+            Bech32Variant::Bech32m => BECH32M_CONST,
+        }
+    }
+}
+
+/// Computes the BCH checksum of a sequence of 5 bit values.
+fn polymod(values: &[u8]) -> u32 {
+    let generator: [u32; 5] = [0x3b6a57b2, 0x26508e6d, 0x1ea119fa, 0x3d4233dd, 0x2a1462b3];
+    let mut checksum: u32 = 1;
+
+    for value in values {
+        let top = checksum >> 25;
+        checksum = ((checksum & 0x1ffffff) << 5) ^ (*value as u32);
+        for (i, generator) in generator.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                checksum ^= generator;
+            }
+        }
+    }
+    checksum
+}
+
+/// Expands the human readable part so it can be included in the checksum.
+fn hrp_expand(hrp: &str) -> Vec<u8> {
+    let mut expanded: Vec<u8> = hrp.bytes().map(|c| c >> 5).collect();
+    expanded.push(0);
+    expanded.extend(hrp.bytes().map(|c| c & 31));
+    expanded
+}
+
+fn create_checksum(hrp: &str, data: &[u8], variant: Bech32Variant) -> Vec<u8> {
+    let values = [hrp_expand(hrp), data.to_vec(), vec![0; 6]].concat();
+    let checksum = polymod(&values) ^ variant.get_const();
+
+    (0..6)
+        .map(|i| ((checksum >> (5 * (5 - i))) & 31) as u8)
+        .collect()
+}
+
+/// Encodes a human readable part and a sequence of 5 bit values.
+///
+/// # Arguments
+///
+/// * `hrp` - The human readable part, in lowercase.
+/// * `data` - The 5 bit values to encode.
+/// * `variant` - The checksum variant.
+///
+/// # Returns
+///
+/// The bech32 string.
+pub fn encode(hrp: &str, data: &[u8], variant: Bech32Variant) -> String {
+    let checksum = create_checksum(hrp, data, variant);
+    let charset = BECH32_CHARSET.as_bytes();
+
+    let mut encoded = format!("{}1", hrp);
+    for value in data.iter().chain(checksum.iter()) {
+        encoded.push(charset[*value as usize] as char);
+    }
+    encoded
+}
+
+/// Decodes a bech32 or bech32m string.
+///
+/// # Arguments
+///
+/// * `bech` - The string to decode.
+///
+/// # Returns
+///
+/// A Result containing the lowercase human readable part, the 5 bit values without the checksum and the checksum variant, or
+/// `AddressError::InvalidBech32` if the string isn't valid.
+///
+/// # Description
+///
+/// Mixed case strings are rejected, the separator is the last `1` of the string and the checksum is verified against both
+/// variants.
+pub fn decode(bech: &str) -> Result<(String, Vec<u8>, Bech32Variant), AddressError> {
+    if bech.len() > BECH32_MAX_LEN
+        || bech.bytes().any(|c| !(33..=126).contains(&c))
+        || (bech.to_lowercase() != bech && bech.to_uppercase() != bech)
+    {
+        return Err(AddressError::InvalidBech32);
+    }
+
+    let bech = bech.to_lowercase();
+    let separator = bech.rfind('1').ok_or(AddressError::InvalidBech32)?;
+    if separator < 1 || separator + 7 > bech.len() {
+        return Err(AddressError::InvalidBech32);
+    }
+
+    let hrp = &bech[..separator];
+    let data = bech[separator + 1..]
+        .chars()
+        .map(|c| BECH32_CHARSET.find(c).map(|value| value as u8))
+        .collect::<Option<Vec<u8>>>()
+        .ok_or(AddressError::InvalidBech32)?;
+
+    let variant = match polymod(&[hrp_expand(hrp), data.clone()].concat()) {
+        BECH32_CONST => Bech32Variant::Bech32,
+        BECH32M_CONST => Bech32Variant::Bech32m,
+        _ => return Err(AddressError::InvalidChecksum),
+    };
+
+    Ok((hrp.to_string(), data[..data.len() - 6].to_vec(), variant))
+}
+
+/// Regroups a sequence of values of `from` bits into values of `to` bits.
+///
+/// # Arguments
+///
+/// * `data` - The values to regroup.
+/// * `from` - The number of bits of each input value.
+/// * `to` - The number of bits of each output value.
+/// * `pad` - Whether the remaining bits are padded with zeros into a last value.
+///
+/// # Returns
+///
+/// The regrouped values, or `AddressError::InvalidWitnessProgram` if the padding isn't valid.
+pub fn convert_bits(data: &[u8], from: u32, to: u32, pad: bool) -> Result<Vec<u8>, AddressError> {
+    let mut acc: u32 = 0;
+    let mut bits: u32 = 0;
+    let max_value: u32 = (1 << to) - 1;
+    let mut converted = vec![];
+
+    for value in data {
+        if (*value as u32) >> from != 0 {
+            return Err(AddressError::InvalidWitnessProgram);
+        }
+        acc = (acc << from) | *value as u32;
+        bits += from;
+        while bits >= to {
+            bits -= to;
+            converted.push(((acc >> bits) & max_value) as u8);
+        }
+    }
+
+    if pad {
+        if bits > 0 {
+            converted.push(((acc << (to - bits)) & max_value) as u8);
+        }
+    } else if bits >= from || ((acc << (to - bits)) & max_value) != 0 {
+        return Err(AddressError::InvalidWitnessProgram);
+    }
+
+    Ok(converted)
+}
+
+/// Regroups bytes into values of 5 bits, padding the remaining bits with zeros. Unlike `convert_bits`, it can't fail, as
+/// every byte fits in 8 bits and the padding is always allowed.
+fn to_base32(data: &[u8]) -> Vec<u8> {
+    let mut acc: u32 = 0;
+    let mut bits: u32 = 0;
+    let mut converted = vec![];
+
+    for value in data {
+        acc = (acc << 8) | *value as u32;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            converted.push(((acc >> bits) & 31) as u8);
+        }
+    }
+    if bits > 0 {
+        converted.push(((acc << (5 - bits)) & 31) as u8);
+    }
+    converted
+}
+
+/// Encodes a native SegWit address.
+///
+/// # Arguments
+///
+/// * `hrp` - The human readable part of the network.
+/// * `version` - The witness version, from 0 to 16.
+/// * `program` - The witness program.
+///
+/// # Returns
+///
+/// The address, using bech32 for version 0 and bech32m for the rest.
+pub fn encode_segwit_address(hrp: &str, version: u8, program: &[u8]) -> String {
+    let variant = if version == 0 {
+        Bech32Variant::Bech32
+    } else {
+        Bech32Variant::Bech32m
+    };
+    let data = [vec![version], to_base32(program)].concat();
+
+    encode(hrp, &data, variant)
+}
+
+/// Decodes a native SegWit address.
+///
+/// # Arguments
+///
+/// * `hrp` - The human readable part the address must have.
+/// * `address` - The address to decode.
+///
+/// # Returns
+///
+/// A Result containing the witness version and program, or an `AddressError` if the address is invalid.
+///
+/// # Description
+///
+/// Besides the bech32 rules, the witness version must be at most 16, version 0 must use bech32 with a program of 20 or 32
+/// bytes and the later versions must use bech32m with a program between 2 and 40 bytes.
+pub fn decode_segwit_address(hrp: &str, address: &str) -> Result<(u8, Vec<u8>), AddressError> {
+    let (address_hrp, data, variant) = decode(address)?;
+    if address_hrp != hrp {
+        return Err(AddressError::WrongNetwork);
+    }
+
+    let (version, program) = data
+        .split_first()
+        .ok_or(AddressError::InvalidWitnessProgram)?;
+    let program = convert_bits(program, 5, 8, false)?;
+
+    let valid = match version {
+        0 => variant == Bech32Variant::Bech32 && (program.len() == 20 || program.len() == 32),
+        1..=16 => variant == Bech32Variant::Bech32m && (2..=40).contains(&program.len()),
+        _ => false,
+    };
+    if !valid {
+        return Err(AddressError::InvalidWitnessProgram);
+    }
+
+    Ok((*version, program))
+}
+
+#[cfg(test)]
+mod bech32_test {
+    use node::messages::read_from_bytes::decode_hex;
+    use std::num::ParseIntError;
+
+    use super::{decode, decode_segwit_address, encode_segwit_address, Bech32Variant};
+    use crate::transactions::address_error::AddressError;
+
+    #[test]
+    fn test_valid_checksums() {
+        assert!(matches!(
+            decode("A12UEL5L"),
+            Ok((_, _, Bech32Variant::Bech32))
+        ));
+        assert!(matches!(
+            decode("abcdef1qpzry9x8gf2tvdw0s3jn54khce6mua7lmqqqxw"),
+            Ok((_, _, Bech32Variant::Bech32))
+        ));
+        assert!(matches!(
+            decode("A1LQFN3A"),
+            Ok((_, _, Bech32Variant::Bech32m))
+        ));
+        assert!(matches!(
+            decode("abcdef1l7aum6echk45nj3s0wdvt2fg8x9yrzpqzd3ryx"),
+            Ok((_, _, Bech32Variant::Bech32m))
+        ));
+    }
+
+    #[test]
+    fn test_valid_segwit_addresses() -> Result<(), ParseIntError> {
+        let p2wpkh = decode_hex("751e76e8199196d454941c45d1b3a323f1433bd6")?;
+        let p2wsh = decode_hex("1863143c14c5166804bd19203356da136c985678cd4d27a1b8c6329604903262")?;
+        let p2tr = decode_hex("79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798")?;
+
+        assert_eq!(
+            decode_segwit_address("bc", "BC1QW508D6QEJXTDG4Y5R3ZARVARY0C5XW7KV8F3T4"),
+            Ok((0, p2wpkh.clone()))
+        );
+        assert_eq!(
+            decode_segwit_address(
+                "tb",
+                "tb1qrp33g0q5c5txsp9arysrx4k6zdkfs4nce4xj0gdcccefvpysxf3q0sl5k7"
+            ),
+            Ok((0, p2wsh))
+        );
+        assert_eq!(
+            decode_segwit_address(
+                "bc",
+                "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqzk5jj0"
+            ),
+            Ok((1, p2tr.clone()))
+        );
+        assert_eq!(
+            encode_segwit_address("bc", 0, &p2wpkh),
+            "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4"
+        );
+        assert_eq!(
+            encode_segwit_address("bc", 1, &p2tr),
+            "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqzk5jj0"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_segwit_addresses() {
+        // Taproot program with a bech32 checksum instead of bech32m.
+        assert!(decode_segwit_address(
+            "bc",
+            "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqh2y7hd"
+        )
+        .is_err());
+        // Version 0 program with a bech32m checksum.
+        assert!(decode_segwit_address("bc", "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kemeawh").is_err());
+        // Mistyped character.
+        assert_eq!(
+            decode_segwit_address("bc", "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t5"),
+            Err(AddressError::InvalidChecksum)
+        );
+        // Address of another network.
+        assert_eq!(
+            decode_segwit_address("tb", "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4"),
+            Err(AddressError::WrongNetwork)
+        );
+        // Mixed case.
+        assert_eq!(
+            decode_segwit_address("bc", "bc1qW508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4"),
+            Err(AddressError::InvalidBech32)
+        );
+    }
+}
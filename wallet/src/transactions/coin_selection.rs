@@ -0,0 +1,8 @@
+pub mod branch_and_bound;
+pub mod coin_selection_constants;
+pub mod coin_selector;
+pub mod largest_first;
+pub mod lowest_waste;
+pub mod manual_selection;
+pub mod privacy_first;
+pub mod smallest_first;
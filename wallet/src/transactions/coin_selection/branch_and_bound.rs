@@ -0,0 +1,186 @@
+use std::cmp::Reverse;
+
+use node::block_mod::tx_out::TxOut;
+
+use super::{
+    coin_selection_constants::BNB_MAX_TRIES,
+    coin_selector::{CoinSelector, Selection, SelectionParams},
+};
+
+/// Searches for a set of UTXOs that covers the target without a change output, as Bitcoin Core's branch and bound.
+///
+/// A changeless transaction is smaller and doesn't link a change address to the payment. The search only accepts
+/// selections whose excess is lower than the cost of a change output, so giving the excess away as fee is cheaper.
+pub struct BranchAndBound {
+    max_tries: usize,
+}
+
+impl BranchAndBound {
+    pub fn new() -> BranchAndBound {
+        BranchAndBound {
+            max_tries: BNB_MAX_TRIES,
+        }
+    }
+}
+
+impl Default for BranchAndBound {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CoinSelector for BranchAndBound {
+    /// Explores a binary tree where each level decides whether a UTXO is included, with the UTXOs sorted by descending
+    /// effective value.
+    ///
+    /// # Description
+    ///
+    /// A branch is cut when its value exceeds the target plus the cost of change, when the UTXOs left can't reach the
+    /// target, or when its waste is already higher than the best selection found while fees are above the long term rate.
+    /// Omitting a UTXO equal to the one just omitted is skipped, since it leads to the same selections. The search stops
+    /// after `max_tries` steps and returns the selection with the lowest waste found.
+    fn select(
+        &self,
+        utxo: &[(Vec<u8>, u32, TxOut)],
+        params: &SelectionParams,
+    ) -> Option<Selection> {
+        let mut pool: Vec<&(Vec<u8>, u32, TxOut)> = utxo
+            .iter()
+            .filter(|utxo| params.get_effective_value(utxo) > 0)
+            .collect();
+        pool.sort_by_key(|utxo| Reverse(params.get_effective_value(utxo)));
+        let values: Vec<i64> = pool
+            .iter()
+            .map(|utxo| params.get_effective_value(utxo))
+            .collect();
+        let wastes: Vec<i64> = pool
+            .iter()
+            .map(|utxo| params.get_input_waste(utxo))
+            .collect();
+
+        let target = params.get_target();
+        let upper_bound = target + params.get_change_cost();
+        let mut available: i64 = values.iter().sum();
+        if available < target {
+            return None;
+        }
+
+        let mut current: Vec<usize> = vec![];
+        let mut current_value = 0;
+        let mut current_waste = 0;
+        let mut best: Option<(Vec<usize>, i64)> = None;
+        let mut index = 0;
+
+        for _ in 0..self.max_tries {
+            let is_worse = best.as_ref().is_some_and(|(_, best_waste)| {
+                current_waste > *best_waste
+                    && params.get_fee_rate() > params.get_long_term_fee_rate()
+            });
+            let backtrack =
+                if current_value + available < target || current_value > upper_bound || is_worse {
+                    true
+                } else if current_value >= target {
+                    let waste = current_waste + current_value - target;
+                    if best
+                        .as_ref()
+                        .is_none_or(|(_, best_waste)| waste <= *best_waste)
+                    {
+                        best = Some((current.clone(), waste));
+                    }
+                    true
+                } else {
+                    false
+                };
+
+            if backtrack {
+                let last = match current.last() {
+                    Some(&last) => last,
+                    None => break,
+                };
+                // The omitted UTXOs after the last included one are available again for its omission branch.
+                index -= 1;
+                while index > last {
+                    available += values[index];
+                    index -= 1;
+                }
+                current_value -= values[index];
+                current_waste -= wastes[index];
+                current.pop();
+            } else {
+                available -= values[index];
+                let repeats_omitted = current.last().is_some_and(|&last| last != index - 1)
+                    && values[index] == values[index - 1]
+                    && wastes[index] == wastes[index - 1];
+                if !repeats_omitted {
+                    current.push(index);
+                    current_value += values[index];
+                    current_waste += wastes[index];
+                }
+            }
+            index += 1;
+        }
+
+        best.map(|(indexes, _)| {
+            Selection::new(
+                indexes.iter().map(|&index| pool[index].clone()).collect(),
+                params,
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod branch_and_bound_test {
+    use super::*;
+    use crate::transactions::create_transactions::witness_pk_script_from_public_key;
+
+    fn test_utxo(values: &[i64]) -> Vec<(Vec<u8>, u32, TxOut)> {
+        values
+            .iter()
+            .enumerate()
+            .map(|(index, value)| {
+                (
+                    vec![1; 32],
+                    index as u32,
+                    TxOut::new(*value, witness_pk_script_from_public_key(&[2; 33])),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_finds_exact_match() {
+        let change_pk_script = witness_pk_script_from_public_key(&[3; 33]);
+        let utxo = test_utxo(&[100000, 70000, 50000, 30000, 20000]);
+        let params = SelectionParams::new(90000, 0, 0, &change_pk_script);
+
+        let selection = BranchAndBound::new().select(&utxo, &params);
+
+        assert_eq!(selection.as_ref().map(Selection::get_value), Some(90000));
+        assert_eq!(
+            selection.map(|selection| selection.has_change()),
+            Some(false)
+        );
+    }
+
+    #[test]
+    fn test_accepts_excess_below_change_cost() {
+        let change_pk_script = witness_pk_script_from_public_key(&[3; 33]);
+        let utxo = test_utxo(&[60000, 45000, 35000]);
+        // Every input costs 69 * 10 satoshis, a change output 31 * 10 + 69 * 5.
+        let params = SelectionParams::new(78200, 10, 5, &change_pk_script);
+
+        let selection = BranchAndBound::new().select(&utxo, &params);
+
+        assert_eq!(
+            selection.map(|selection| selection.get_value()),
+            Some(80000)
+        );
+        assert!(BranchAndBound::new()
+            .select(
+                &utxo,
+                &SelectionParams::new(75000, 10, 5, &change_pk_script)
+            )
+            .is_none());
+    }
+}
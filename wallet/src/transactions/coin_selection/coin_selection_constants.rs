@@ -0,0 +1,3 @@
+pub const BNB_MAX_TRIES: usize = 100000;
+pub const CHANGE_OUTPUT_VSIZE: i64 = 31;
+pub const LONG_TERM_FEE_RATE: i64 = 10;
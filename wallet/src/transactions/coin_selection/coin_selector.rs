@@ -0,0 +1,206 @@
+use node::block_mod::tx_out::TxOut;
+
+use crate::transactions::create_transactions::{get_dust_limit, get_input_vsize};
+
+use super::coin_selection_constants::CHANGE_OUTPUT_VSIZE;
+
+/// A strategy to choose which UTXOs a transaction spends.
+pub trait CoinSelector {
+    /// Chooses the UTXOs to spend.
+    ///
+    /// # Arguments
+    ///
+    /// * `utxo` - The UTXOs the wallet can spend, as tuples of transaction id, output index and output.
+    /// * `params` - The amount to cover and the fee rates used to weigh the choices.
+    ///
+    /// # Returns
+    ///
+    /// The chosen UTXOs, or `None` if the strategy can't cover the target with them.
+    fn select(&self, utxo: &[(Vec<u8>, u32, TxOut)], params: &SelectionParams)
+        -> Option<Selection>;
+}
+
+/// Represents what a coin selection has to cover and how its cost is measured.
+#[derive(Debug, Clone)]
+pub struct SelectionParams {
+    target: i64,
+    fee_rate: i64,
+    long_term_fee_rate: i64,
+    change_cost: i64,
+    min_change: i64,
+    scripts: Vec<Vec<u8>>,
+}
+
+impl SelectionParams {
+    /// Creates the parameters of a coin selection.
+    ///
+    /// # Arguments
+    ///
+    /// * `target` - The amount the inputs must cover after paying for themselves: the outputs and the part of the fee that
+    ///   doesn't depend on the inputs.
+    /// * `fee_rate` - The fee rate of the transaction, in satoshis per virtual byte, paid for every input.
+    /// * `long_term_fee_rate` - The fee rate the wallet expects to pay in the future, used to estimate the cost of spending
+    ///   the UTXOs later instead of now.
+    /// * `change_pk_script` - The script the change would be sent to.
+    ///
+    /// # Returns
+    ///
+    /// The parameters, with the cost of a change output being the fee of adding it now plus the fee of spending it later.
+    pub fn new(
+        target: i64,
+        fee_rate: i64,
+        long_term_fee_rate: i64,
+        change_pk_script: &[u8],
+    ) -> SelectionParams {
+        SelectionParams::with_scripts(target, fee_rate, long_term_fee_rate, change_pk_script, &[])
+    }
+
+    /// Creates the parameters of a coin selection whose UTXOs may be locked to scripts of the wallet, such as multisig ones.
+    ///
+    /// # Arguments
+    ///
+    /// * `scripts` - The redeem and witness scripts of the wallet, which set the cost of spending its P2SH and P2WSH
+    ///   outputs, see `get_input_vsize`.
+    ///
+    /// The rest of the arguments are the ones of `new`.
+    pub fn with_scripts(
+        target: i64,
+        fee_rate: i64,
+        long_term_fee_rate: i64,
+        change_pk_script: &[u8],
+        scripts: &[Vec<u8>],
+    ) -> SelectionParams {
+        SelectionParams {
+            target,
+            fee_rate,
+            long_term_fee_rate,
+            change_cost: CHANGE_OUTPUT_VSIZE * fee_rate
+                + get_input_vsize(change_pk_script, scripts) * long_term_fee_rate,
+            min_change: get_dust_limit(change_pk_script),
+            scripts: scripts.to_vec(),
+        }
+    }
+
+    pub fn get_target(&self) -> i64 {
+        self.target
+    }
+
+    pub fn get_change_cost(&self) -> i64 {
+        self.change_cost
+    }
+
+    pub fn get_fee_rate(&self) -> i64 {
+        self.fee_rate
+    }
+
+    pub fn get_long_term_fee_rate(&self) -> i64 {
+        self.long_term_fee_rate
+    }
+
+    /// Returns the value of a UTXO minus the fee of the input that spends it.
+    pub fn get_effective_value(&self, utxo: &(Vec<u8>, u32, TxOut)) -> i64 {
+        utxo.2.get_value() - get_input_vsize(&utxo.2.get_pk_script(), &self.scripts) * self.fee_rate
+    }
+
+    /// Returns how much more spending a UTXO now costs than spending it at the long term fee rate, negative if it is cheaper.
+    pub fn get_input_waste(&self, utxo: &(Vec<u8>, u32, TxOut)) -> i64 {
+        get_input_vsize(&utxo.2.get_pk_script(), &self.scripts)
+            * (self.fee_rate - self.long_term_fee_rate)
+    }
+}
+
+/// Represents the UTXOs chosen by a `CoinSelector` and the waste of spending them.
+///
+/// The waste, as defined by Bitcoin Core, adds up the extra fee of spending the inputs now instead of at the long term fee
+/// rate, and either the cost of the change output or, if the excess is too small for one, the excess given away as fee.
+/// The selection with the lowest waste is the cheapest in the long run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Selection {
+    utxo: Vec<(Vec<u8>, u32, TxOut)>,
+    value: i64,
+    waste: i64,
+    has_change: bool,
+}
+
+impl Selection {
+    /// Creates a selection of UTXOs, computing its waste.
+    ///
+    /// # Arguments
+    ///
+    /// * `utxo` - The chosen UTXOs, whose effective values must cover the target.
+    /// * `params` - The parameters of the selection.
+    pub fn new(utxo: Vec<(Vec<u8>, u32, TxOut)>, params: &SelectionParams) -> Selection {
+        let effective_value: i64 = utxo
+            .iter()
+            .map(|utxo| params.get_effective_value(utxo))
+            .sum();
+        let excess = effective_value - params.target;
+        let has_change = excess >= params.change_cost + params.min_change;
+        let waste = utxo
+            .iter()
+            .map(|utxo| params.get_input_waste(utxo))
+            .sum::<i64>()
+            + if has_change {
+                params.change_cost
+            } else {
+                excess
+            };
+
+        Selection {
+            value: utxo.iter().map(|utxo| utxo.2.get_value()).sum(),
+            utxo,
+            waste,
+            has_change,
+        }
+    }
+
+    pub fn get_utxo(&self) -> &Vec<(Vec<u8>, u32, TxOut)> {
+        &self.utxo
+    }
+
+    /// Returns the total value of the chosen UTXOs.
+    pub fn get_value(&self) -> i64 {
+        self.value
+    }
+
+    pub fn get_waste(&self) -> i64 {
+        self.waste
+    }
+
+    /// Returns whether the excess of the selection is worth a change output.
+    pub fn has_change(&self) -> bool {
+        self.has_change
+    }
+}
+
+/// Chooses UTXOs in the given order until their effective values cover the target.
+///
+/// # Arguments
+///
+/// * `utxo` - The UTXOs in the order they are tried.
+/// * `params` - The parameters of the selection.
+///
+/// # Returns
+///
+/// The selection, or `None` if all the UTXOs together don't cover the target. UTXOs that cost more to spend than they are
+/// worth are skipped.
+pub fn select_in_order<'a>(
+    utxo: impl Iterator<Item = &'a (Vec<u8>, u32, TxOut)>,
+    params: &SelectionParams,
+) -> Option<Selection> {
+    let mut selected = vec![];
+    let mut effective_value = 0;
+
+    for utxo in utxo.filter(|utxo| params.get_effective_value(utxo) > 0) {
+        if effective_value >= params.get_target() {
+            break;
+        }
+        effective_value += params.get_effective_value(utxo);
+        selected.push(utxo.clone());
+    }
+
+    if effective_value < params.get_target() {
+        return None;
+    }
+    Some(Selection::new(selected, params))
+}
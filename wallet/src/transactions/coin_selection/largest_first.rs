@@ -0,0 +1,20 @@
+use std::cmp::Reverse;
+
+use node::block_mod::tx_out::TxOut;
+
+use super::coin_selector::{select_in_order, CoinSelector, Selection, SelectionParams};
+
+/// Spends the UTXOs with the highest effective value first, which needs the fewest inputs.
+pub struct LargestFirst;
+
+impl CoinSelector for LargestFirst {
+    fn select(
+        &self,
+        utxo: &[(Vec<u8>, u32, TxOut)],
+        params: &SelectionParams,
+    ) -> Option<Selection> {
+        let mut sorted: Vec<&(Vec<u8>, u32, TxOut)> = utxo.iter().collect();
+        sorted.sort_by_key(|utxo| Reverse(params.get_effective_value(utxo)));
+        select_in_order(sorted.into_iter(), params)
+    }
+}
@@ -0,0 +1,118 @@
+use node::block_mod::tx_out::TxOut;
+
+use super::{
+    branch_and_bound::BranchAndBound,
+    coin_selector::{CoinSelector, Selection, SelectionParams},
+    largest_first::LargestFirst,
+    privacy_first::PrivacyFirst,
+    smallest_first::SmallestFirst,
+};
+
+/// Runs several strategies and keeps the selection with the lowest waste, the one with fewer inputs on a tie.
+pub struct LowestWaste {
+    strategies: Vec<Box<dyn CoinSelector>>,
+}
+
+impl LowestWaste {
+    pub fn new(strategies: Vec<Box<dyn CoinSelector>>) -> LowestWaste {
+        LowestWaste { strategies }
+    }
+}
+
+impl Default for LowestWaste {
+    /// Compares branch and bound, privacy first, largest first and smallest first.
+    fn default() -> Self {
+        Self::new(vec![
+            Box::new(BranchAndBound::new()),
+            Box::new(PrivacyFirst),
+            Box::new(LargestFirst),
+            Box::new(SmallestFirst),
+        ])
+    }
+}
+
+impl CoinSelector for LowestWaste {
+    fn select(
+        &self,
+        utxo: &[(Vec<u8>, u32, TxOut)],
+        params: &SelectionParams,
+    ) -> Option<Selection> {
+        self.strategies
+            .iter()
+            .filter_map(|strategy| strategy.select(utxo, params))
+            .min_by_key(|selection| (selection.get_waste(), selection.get_utxo().len()))
+    }
+}
+
+#[cfg(test)]
+mod lowest_waste_test {
+    use super::*;
+    use crate::transactions::{
+        coin_selection::manual_selection::ManualSelection,
+        create_transactions::witness_pk_script_from_public_key,
+    };
+
+    fn test_utxo(values: &[(i64, u8)]) -> Vec<(Vec<u8>, u32, TxOut)> {
+        values
+            .iter()
+            .enumerate()
+            .map(|(index, (value, key))| {
+                (
+                    vec![1; 32],
+                    index as u32,
+                    TxOut::new(*value, witness_pk_script_from_public_key(&[*key; 33])),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_prefers_changeless_selection() {
+        let change_pk_script = witness_pk_script_from_public_key(&[3; 33]);
+        let utxo = test_utxo(&[(100000, 2), (70000, 2), (50000, 2), (20000, 2)]);
+        let params = SelectionParams::new(120000, 0, 0, &change_pk_script);
+
+        let selection = LowestWaste::default().select(&utxo, &params);
+
+        assert_eq!(selection.as_ref().map(Selection::get_value), Some(120000));
+        assert_eq!(
+            selection.map(|selection| selection.has_change()),
+            Some(false)
+        );
+    }
+
+    #[test]
+    fn test_privacy_first_spends_a_single_address() {
+        let change_pk_script = witness_pk_script_from_public_key(&[3; 33]);
+        let utxo = test_utxo(&[(60000, 2), (50000, 4), (30000, 4)]);
+        let params = SelectionParams::new(70000, 1, 1, &change_pk_script);
+
+        let selection = PrivacyFirst.select(&utxo, &params);
+
+        assert_eq!(
+            selection.map(|selection| selection.get_utxo().iter().map(|utxo| utxo.1).collect()),
+            Some(vec![1, 2])
+        );
+        assert!(PrivacyFirst
+            .select(&utxo, &SelectionParams::new(90000, 1, 1, &change_pk_script))
+            .is_none());
+    }
+
+    #[test]
+    fn test_manual_selection_spends_the_chosen_utxo() {
+        let change_pk_script = witness_pk_script_from_public_key(&[3; 33]);
+        let utxo = test_utxo(&[(60000, 2), (50000, 4), (30000, 4)]);
+        let params = SelectionParams::new(70000, 0, 0, &change_pk_script);
+
+        let selection =
+            ManualSelection::new(vec![(vec![1; 32], 0), (vec![1; 32], 2)]).select(&utxo, &params);
+
+        assert_eq!(
+            selection.map(|selection| selection.get_value()),
+            Some(90000)
+        );
+        assert!(ManualSelection::new(vec![(vec![1; 32], 0)])
+            .select(&utxo, &params)
+            .is_none());
+    }
+}
@@ -0,0 +1,43 @@
+use node::block_mod::tx_out::TxOut;
+
+use super::coin_selector::{CoinSelector, Selection, SelectionParams};
+
+/// Spends exactly the UTXOs the user chose.
+pub struct ManualSelection {
+    outpoints: Vec<(Vec<u8>, u32)>,
+}
+
+impl ManualSelection {
+    /// Creates a manual selection.
+    ///
+    /// # Arguments
+    ///
+    /// * `outpoints` - The transaction id and output index of each chosen UTXO.
+    pub fn new(outpoints: Vec<(Vec<u8>, u32)>) -> ManualSelection {
+        ManualSelection { outpoints }
+    }
+}
+
+impl CoinSelector for ManualSelection {
+    /// Returns the chosen UTXOs that the wallet can spend, or `None` if their effective value doesn't cover the target.
+    fn select(
+        &self,
+        utxo: &[(Vec<u8>, u32, TxOut)],
+        params: &SelectionParams,
+    ) -> Option<Selection> {
+        let selected: Vec<(Vec<u8>, u32, TxOut)> = utxo
+            .iter()
+            .filter(|(tx_id, index, _)| self.outpoints.contains(&(tx_id.clone(), *index)))
+            .cloned()
+            .collect();
+        let effective_value: i64 = selected
+            .iter()
+            .map(|utxo| params.get_effective_value(utxo))
+            .sum();
+
+        if selected.is_empty() || effective_value < params.get_target() {
+            return None;
+        }
+        Some(Selection::new(selected, params))
+    }
+}
@@ -0,0 +1,35 @@
+use std::{cmp::Reverse, collections::HashMap};
+
+use node::block_mod::tx_out::TxOut;
+
+use super::coin_selector::{select_in_order, CoinSelector, Selection, SelectionParams};
+
+/// Spends UTXOs of a single address, so the transaction doesn't reveal that several addresses belong to the same wallet.
+///
+/// Among the addresses that can cover the target on their own, the selection with the lowest waste is chosen, spending the
+/// UTXOs of the address with the highest effective value first.
+pub struct PrivacyFirst;
+
+impl CoinSelector for PrivacyFirst {
+    fn select(
+        &self,
+        utxo: &[(Vec<u8>, u32, TxOut)],
+        params: &SelectionParams,
+    ) -> Option<Selection> {
+        let mut by_address = HashMap::new();
+        for utxo in utxo.iter() {
+            by_address
+                .entry(utxo.2.get_pk_script())
+                .or_insert_with(Vec::new)
+                .push(utxo);
+        }
+
+        by_address
+            .into_values()
+            .filter_map(|mut address_utxo| {
+                address_utxo.sort_by_key(|utxo| Reverse(params.get_effective_value(utxo)));
+                select_in_order(address_utxo.into_iter(), params)
+            })
+            .min_by_key(|selection| (selection.get_waste(), selection.get_utxo().len()))
+    }
+}
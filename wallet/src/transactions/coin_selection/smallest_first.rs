@@ -0,0 +1,18 @@
+use node::block_mod::tx_out::TxOut;
+
+use super::coin_selector::{select_in_order, CoinSelector, Selection, SelectionParams};
+
+/// Spends the UTXOs with the lowest effective value first, consolidating small UTXOs while fees are low.
+pub struct SmallestFirst;
+
+impl CoinSelector for SmallestFirst {
+    fn select(
+        &self,
+        utxo: &[(Vec<u8>, u32, TxOut)],
+        params: &SelectionParams,
+    ) -> Option<Selection> {
+        let mut sorted: Vec<&(Vec<u8>, u32, TxOut)> = utxo.iter().collect();
+        sorted.sort_by_key(|utxo| params.get_effective_value(utxo));
+        select_in_order(sorted.into_iter(), params)
+    }
+}
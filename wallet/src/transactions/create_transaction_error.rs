@@ -1,5 +1,7 @@
 use std::num::ParseIntError;
 
+use super::address_error::AddressError;
+
 #[derive(Debug)]
 pub enum TransactionCreateError {
     InsufficientFounds,
@@ -8,6 +10,10 @@ pub enum TransactionCreateError {
     DecodeHex,
     EncodeHex,
     GetPrivateKey,
+    WrongNetwork,
+    InvalidAddress(String, AddressError),
+    MissingSignatures,
+    WatchOnly,
 }
 
 impl From<ParseIntError> for TransactionCreateError {
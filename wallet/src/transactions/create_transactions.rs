@@ -1,63 +1,76 @@
-use bitcoin_hashes::{hash160, sha256, sha256d, Hash};
-use node::block_mod::{script::Script, transaction::Transaction, tx_in::TxIn, tx_out::TxOut};
-use secp256k1::{Message, PublicKey, Secp256k1, SecretKey};
-
+use std::collections::BTreeMap;
+
+use bitcoin_hashes::{hash160, sha256d, Hash};
+use node::block_mod::{
+    script::Script,
+    script_type::{get_pubkey_hash, get_pubkey_hash_scripts, ScriptType},
+    transaction::{Transaction, SIGHASH_ALL},
+    tx_in::TxIn,
+    tx_out::TxOut,
+};
+use rand::Rng;
+use secp256k1::{ecdsa::Signature, Message, PublicKey, Secp256k1, SecretKey};
+
+use node::messages::compact_size::CompactSizeUInt;
+use node::network::bitcoin_network::BitcoinNetwork;
+
+use super::address::{encode_base58_check, p2pkh, segwit, Address};
+use super::coin_selection::{
+    coin_selection_constants::LONG_TERM_FEE_RATE,
+    coin_selector::{CoinSelector, SelectionParams},
+};
 use super::create_transaction_error::TransactionCreateError;
+use super::create_transactios_constants::{
+    COMPRESSED_PUBLIC_KEY_LEN, DUST_RELAY_FEE_RATE, LEGACY_INPUT_SIZE, MAX_FEE_ITERATIONS,
+    MAX_SIGNATURE_LEN, TXIN_FIXED_SIZE, TXOUT_FIXED_SIZE, WIF_COMPRESSED_LEN, WIF_LEN,
+    WITNESS_INPUT_VSIZE, WITNESS_SCALE_FACTOR,
+};
+use crate::multisig::multisig_script::{find_script, MultisigScript};
 
-/// Creates a pay-to-public-key-hash (P2PKH) script with the given hash160 value.
+/// Generates a Base58-encoded Bitcoin address from a public key.
 ///
 /// # Arguments
 ///
-/// * `h160`: A vector of bytes representing the hash160 value.
+/// * `public_key`: A vector of bytes representing the public key.
+/// * `network`: The network whose P2PKH prefix is used.
 ///
 /// # Returns
 ///
-/// A `Script` object representing the P2PKH script.
+/// A vector of bytes representing the Base58-encoded Bitcoin address.
 ///
 /// # Description
 ///
-/// This function creates a P2PKH script using the given hash160 value. The P2PKH script consists of a series of opcodes and data values.
-/// The script starts with the opcode `OP_DUP` (0x76) to duplicate the top stack item, followed by `OP_HASH160` (0xa9) to hash the duplicated
-/// value. Then, the hash160 value is added to the script. After that, `OP_EQUALVERIFY` (0x88) is used to verify that the top two stack items
-/// are equal. Finally, `OP_CHECKSIG` (0xac) is used to verify the signature using the public key.
-///
-fn p2pkh(h160: Vec<u8>) -> Script {
-    Script::new(Some(vec![
-        vec![0x76],
-        vec![0xa9],
-        h160,
-        vec![0x88],
-        vec![0xac],
-    ]))
+/// This function takes a public key as input and generates a Base58-encoded Bitcoin address. It first calculates the hash160 value of the public key using the hash160 algorithm. Then, it combines the version prefix of the network (0x00 on mainnet, 0x6f on testnet) with the hash160 value and calculates the double hash (sha256d) of the combined data. The first 4 bytes of the double hash are used as the checksum. Finally, the version prefix, hash160 value, and checksum are concatenated and encoded using Base58 to obtain the Bitcoin address.
+pub fn address_from_public_key(public_key: &[u8], network: BitcoinNetwork) -> Vec<u8> {
+    Address::from_public_key(public_key, network).as_bytes()
 }
 
-/// Decodes a Base58 encoded address into its corresponding payload.
+/// Generates a script (public key script) from a Base58-encoded Bitcoin address.
 ///
 /// # Arguments
 ///
-/// * `address`: A vector of bytes representing the Base58 encoded address.
+/// * `address`: A vector of bytes representing the Base58-encoded Bitcoin address.
+/// * `network`: The network the address is expected to belong to.
 ///
 /// # Returns
 ///
-/// A vector of bytes representing the decoded payload.
+/// A Result containing the script as a vector of bytes, or `TransactionCreateError::InvalidAddress` with the address and the reason it is invalid.
 ///
 /// # Description
 ///
-/// This function decodes a Base58 encoded address into its corresponding payload. The Base58 encoded address typically represents a
-/// cryptocurrency address. The decoding process involves reversing the Base58 encoded address, decoding it into a byte vector, and
-/// verifying the checksum to ensure the integrity of the payload. If the checksum is valid, the payload is returned. Otherwise,
-/// an empty vector is returned.
-fn decode_base58(address: &Vec<u8>) -> Vec<u8> {
-    if let Ok(/*mut*/ combined) = bs58::decode(address).into_vec() {
-        //let checksum = &combined[combined.len() - 4..];
-        //let payload = &combined[..combined.len() - 4];
-
-        return combined[1..combined.len() - 4].to_vec();
-    };
-    Vec::new()
+/// This function takes a Base58-encoded Bitcoin address as input and generates the corresponding script (public key script). It parses the address with `Address::parse`, which validates its length, version and checksum, so a mistyped address is reported instead of producing a script nobody can spend. The resulting script is returned as a vector of bytes.
+pub fn pk_script_from_address(
+    address: &[u8],
+    network: BitcoinNetwork,
+) -> Result<Vec<u8>, TransactionCreateError> {
+    let address = Address::parse(address, network).map_err(|err| {
+        TransactionCreateError::InvalidAddress(String::from_utf8_lossy(address).to_string(), err)
+    })?;
+
+    Ok(address.get_pk_script())
 }
 
-/// Generates a Base58-encoded Bitcoin address from a public key.
+/// Generates a script (public key script) from a public key.
 ///
 /// # Arguments
 ///
@@ -65,238 +78,755 @@ fn decode_base58(address: &Vec<u8>) -> Vec<u8> {
 ///
 /// # Returns
 ///
-/// A vector of bytes representing the Base58-encoded Bitcoin address.
+/// A vector of bytes representing the script.
 ///
 /// # Description
 ///
-/// This function takes a public key as input and generates a Base58-encoded Bitcoin address. It first calculates the hash160 value of the public key using the hash160 algorithm. Then, it combines the version prefix (0x6f) with the hash160 value and calculates the double hash (sha256d) of the combined data. The first 4 bytes of the double hash are used as the checksum. Finally, the version prefix, hash160 value, and checksum are concatenated and encoded using Base58 to obtain the Bitcoin address.
-pub fn address_from_public_key(public_key: &[u8]) -> Vec<u8> {
-    let h160 = hash160::Hash::hash(public_key).to_byte_array();
+/// This function generates a script (public key script) from a given public key. It calculates the hash160 value of the public key and calls the `p2pkh` function to create the script, returning it as a vector of bytes. The script doesn't depend on the network, only the address encoding does.
+///
+pub fn pk_script_from_public_key(public_key: &[u8]) -> Vec<u8> {
+    let h160 = hash160::Hash::hash(public_key).to_byte_array().to_vec();
 
-    let version_prefix: [u8; 1] = [0x6f];
-    let doble_hash = sha256d::Hash::hash(&[&version_prefix[..], &h160[..]].concat());
-    let checksum = &doble_hash[..4];
+    p2pkh(h160).as_bytes()
+}
 
-    let input = [&version_prefix[..], &h160[..], checksum].concat();
+/// Generates a native SegWit (P2WPKH) script from a public key.
+///
+/// # Arguments
+///
+/// * `public_key`: A vector of bytes representing the compressed public key.
+///
+/// # Returns
+///
+/// A vector of bytes with the script `OP_0 <hash160 of the public key>`.
+pub fn witness_pk_script_from_public_key(public_key: &[u8]) -> Vec<u8> {
+    let h160 = hash160::Hash::hash(public_key).to_byte_array().to_vec();
 
-    bs58::encode(input).into_vec()
+    segwit(0, h160).as_bytes()
 }
 
-/// Generates a script (public key script) from a Base58-encoded Bitcoin address.
+/// Decodes a private key in Wallet Import Format (WIF).
 ///
 /// # Arguments
 ///
-/// * `address`: A vector of bytes representing the Base58-encoded Bitcoin address.
+/// * `wif`: A vector of bytes representing the Base58-encoded private key.
+/// * `network`: The network the private key is expected to belong to.
 ///
 /// # Returns
 ///
-/// A vector of bytes representing the script.
+/// A Result containing the 32 bytes of the private key, or a `TransactionCreateError` if the WIF is invalid.
 ///
 /// # Description
 ///
-/// This function takes a Base58-encoded Bitcoin address as input and generates the corresponding script (public key script). It first decodes the Base58-encoded address using the `decode_base58` function to obtain the hash160 value. Then, it calls the `p2pkh` function to create the script using the hash160 value. The resulting script is returned as a vector of bytes.
-pub fn pk_script_from_address(address: &Vec<u8>) -> Vec<u8> {
-    let h160 = decode_base58(address);
+/// A WIF is `prefix || key || [0x01] || checksum`, where the prefix is 0x80 on mainnet and 0xef on testnet and the optional 0x01
+/// marks a key whose public key is used compressed. The checksum is verified and a key of another network is rejected.
+pub fn private_key_from_wif(
+    wif: &[u8],
+    network: BitcoinNetwork,
+) -> Result<Vec<u8>, TransactionCreateError> {
+    let combined = bs58::decode(wif)
+        .into_vec()
+        .map_err(|_| TransactionCreateError::Decode58)?;
+
+    if combined.len() != WIF_LEN && combined.len() != WIF_COMPRESSED_LEN {
+        return Err(TransactionCreateError::Decode58);
+    }
 
-    p2pkh(h160).as_bytes()
+    let (data, checksum) = combined.split_at(combined.len() - 4);
+    if sha256d::Hash::hash(data)[..4] != *checksum {
+        return Err(TransactionCreateError::Decode58);
+    }
+    if data[0] != network.get_wif_prefix() {
+        return Err(TransactionCreateError::WrongNetwork);
+    }
+    if data.len() == WIF_COMPRESSED_LEN - 4 && data[data.len() - 1] != 0x01 {
+        return Err(TransactionCreateError::Decode58);
+    }
+
+    Ok(data[1..33].to_vec())
 }
 
-/// Generates a script (public key script) from a public key.
+/// Encodes a private key in Wallet Import Format (WIF) for a compressed public key.
 ///
 /// # Arguments
 ///
-/// * `public_key`: A vector of bytes representing the public key.
+/// * `private_key`: A slice with the 32 bytes of the private key.
+/// * `network`: The network whose WIF prefix is used.
 ///
 /// # Returns
 ///
-/// A vector of bytes representing the script.
-///
-/// # Description
-///
-/// This function generates a script (public key script) from a given public key. It first calls the `address_from_public_key` function to obtain the Base58-encoded Bitcoin address corresponding to the public key. Then, it decodes the Base58-encoded address using the `decode_base58` function to obtain the hash160 value. Finally, it calls the `p2pkh` function to create the script using the hash160 value, and returns the resulting script as a vector of bytes.
-///
-pub fn pk_script_from_public_key(public_key: &[u8]) -> Vec<u8> {
-    let address = address_from_public_key(public_key);
-    let pub_key_dec = decode_base58(&address);
-
-    p2pkh(pub_key_dec).as_bytes()
+/// A vector of bytes representing the Base58-encoded private key.
+pub fn wif_from_private_key(private_key: &[u8], network: BitcoinNetwork) -> Vec<u8> {
+    encode_base58_check(network.get_wif_prefix(), &[private_key, &[0x01]].concat())
 }
 
-/// Creates a list of transaction outputs (TxOut) from a list of target addresses and amounts, along with a fee.
+/// Creates a list of transaction outputs (TxOut) from a list of target addresses and amounts.
 ///
 /// # Arguments
 ///
 /// * `targets`: A vector of tuples representing the target addresses and amounts. Each tuple contains a vector of bytes representing the address and an `i64` amount.
-/// * `fee`: The fee amount to be deducted from the total amount.
+/// * `network`: The network the target addresses must belong to.
 ///
 /// # Returns
 ///
-/// A tuple containing the list of transaction outputs (TxOut) and the total amount including the fee.
+/// A Result containing a tuple with the list of transaction outputs (TxOut) and the total amount sent, or a `TransactionCreateError` if an address is invalid.
 ///
 /// # Description
 ///
-/// This function creates a list of transaction outputs (TxOut) from a given list of target addresses and amounts, along with a fee. It iterates over each target, decodes the Base58-encoded address using the `decode_base58` function, and creates a script (public key script) using the decoded hash160 value. The script and amount are then used to create a new transaction output (TxOut), which is added to the `txout_list`. The total amount is updated by adding the current amount. Finally, the function returns a tuple containing the list of transaction outputs (TxOut) and the total amount including the fee.
+/// This function creates a list of transaction outputs (TxOut) from a given list of target addresses and amounts. It iterates over each target and creates its script (public key script) with `pk_script_from_address`, stopping at the first invalid address. The script and amount are then used to create a new transaction output (TxOut), which is added to the `txout_list`. The total amount is updated by adding the current amount. Finally, the function returns a tuple containing the list of transaction outputs (TxOut) and the total amount.
 ///
-fn create_txout_list(targets: Vec<(Vec<u8>, i64)>, fee: i64) -> (Vec<TxOut>, i64) {
-    let mut total_amount = fee;
+fn create_txout_list(
+    targets: Vec<(Vec<u8>, i64)>,
+    network: BitcoinNetwork,
+) -> Result<(Vec<TxOut>, i64), TransactionCreateError> {
+    let mut total_amount = 0;
     let mut txout_list = vec![];
 
     for (address, amount) in targets {
-        let h160 = decode_base58(&address);
-        let script = Script::new(Some(vec![
-            vec![0x76],
-            vec![0xa9],
-            h160,
-            vec![0x88],
-            vec![0xac],
-        ]));
-        let txout = TxOut::new(amount, script.as_bytes());
+        let script = pk_script_from_address(&address, network)?;
+        let txout = TxOut::new(amount, script);
         total_amount += amount;
         txout_list.push(txout);
     }
-    (txout_list, total_amount)
+    Ok((txout_list, total_amount))
+}
+
+/// Returns the estimated virtual size of an input that spends an output script, once signed.
+///
+/// # Arguments
+///
+/// * `pk_script`: The public key script of the spent output.
+/// * `scripts`: The redeem and witness scripts of the wallet, which unlock its P2SH and P2WSH outputs.
+///
+/// # Returns
+///
+/// The virtual size in bytes of the input, assuming signatures of `MAX_SIGNATURE_LEN` bytes and a compressed public key,
+/// or the signatures a multisig script requires and the script, see `get_unlocking_placeholder`. For SegWit outputs they
+/// are witness data, so they count a quarter of their size.
+///
+/// # Description
+///
+/// A DER-encoded signature is 71 or 72 bytes long depending on its values, plus the sighash type byte, and isn't known
+/// until the transaction is signed. Taking the longest one means the fee rate of the signed transaction is never below
+/// the chosen one.
+pub fn get_input_vsize(pk_script: &[u8], scripts: &[Vec<u8>]) -> i64 {
+    let unlocking_data = get_unlocking_placeholder(pk_script, scripts);
+    match ScriptType::from_pk_script(pk_script) {
+        ScriptType::P2wpkh | ScriptType::P2wsh | ScriptType::P2tr => {
+            let witness_size: i64 = 1 + unlocking_data
+                .iter()
+                .map(|item| 1 + item.len() as i64)
+                .sum::<i64>();
+            TXIN_FIXED_SIZE + (witness_size + WITNESS_SCALE_FACTOR - 1) / WITNESS_SCALE_FACTOR
+        }
+        _ => {
+            let script_len = Script::new(Some(unlocking_data)).as_bytes().len();
+            let len_size = CompactSizeUInt::from_number(script_len as u64)
+                .as_bytes()
+                .len();
+            TXIN_FIXED_SIZE - 1 + (len_size + script_len) as i64
+        }
+    }
 }
 
-/// Creates a list of transaction inputs (TxIn) from a list of unspent transaction outputs (UTXO) and the total amount required.
+/// Returns placeholders of the data that unlocks an output, as long as the one of the signed input can be.
+///
+/// # Description
+///
+/// An output locked to a known multisig script is unlocked by the dummy element, `m` signatures and the script, see
+/// `MultisigScript::get_unlocking_data`. Any other one by a signature and a compressed public key.
+fn get_unlocking_placeholder(pk_script: &[u8], scripts: &[Vec<u8>]) -> Vec<Vec<u8>> {
+    let script = find_script(pk_script, scripts);
+    match script.and_then(|script| MultisigScript::from_bytes(script).ok()) {
+        Some(multisig) => {
+            let mut unlocking_data = vec![vec![]];
+            unlocking_data.extend(vec![vec![0; MAX_SIGNATURE_LEN]; multisig.get_required()]);
+            unlocking_data.extend(script.cloned());
+            unlocking_data
+        }
+        None => vec![
+            vec![0; MAX_SIGNATURE_LEN],
+            vec![0; COMPRESSED_PUBLIC_KEY_LEN],
+        ],
+    }
+}
+
+/// Returns the estimated virtual size of a transaction once signed.
 ///
 /// # Arguments
 ///
-/// * `utxo`: A mutable vector of tuples representing the unspent transaction outputs. Each tuple contains a vector of bytes representing the transaction ID, a u32 representing the output index, and a TxOut struct representing the output details.
-/// * `total_amount`: The total amount required for the transaction.
+/// * `transaction`: The unsigned transaction.
+/// * `prev_outputs`: The outputs spent by the transaction inputs, in the same order.
+/// * `scripts`: The redeem and witness scripts of the wallet, which unlock its P2SH and P2WSH outputs.
 ///
 /// # Returns
 ///
-/// A Result containing a tuple with the list of transaction inputs (TxIn) and the change amount, or an error of type TransactionCreateError if there are insufficient funds.
+/// The virtual size of a copy of the transaction whose inputs are filled as they will be once signed, with placeholders
+/// of the longest signature, see `get_input_vsize`.
+pub fn estimate_vsize(
+    transaction: &Transaction,
+    prev_outputs: &[TxOut],
+    scripts: &[Vec<u8>],
+) -> i64 {
+    let mut transaction = transaction.clone();
+
+    for (i, prev_output) in prev_outputs.iter().enumerate() {
+        let pk_script = prev_output.get_pk_script();
+        let unlocking_data = get_unlocking_placeholder(&pk_script, scripts);
+        match ScriptType::from_pk_script(&pk_script) {
+            ScriptType::P2wpkh | ScriptType::P2wsh => transaction.set_witness(i, unlocking_data),
+            _ => transaction.set_signature(i, Script::new(Some(unlocking_data)).as_bytes()),
+        }
+    }
+    transaction.get_vsize() as i64
+}
+
+/// Returns the dust limit of an output script, the smallest amount worth sending to it.
+///
+/// # Arguments
+///
+/// * `pk_script`: The public key script of the output.
+///
+/// # Returns
+///
+/// The amount in satoshis below which an output to the script is dust.
 ///
 /// # Description
 ///
-/// This function creates a list of transaction inputs (TxIn) from a given list of unspent transaction outputs (UTXO) and the total amount required for the transaction. It iterates over the UTXO list in reverse order, popping the last element. For each UTXO, it creates a new transaction input (TxIn) using the transaction ID, output index, empty scriptSig, and a sequence value of 0xffffffff. The amount of the UTXO is added to the accumulated amount. The transaction input is added to the `txin_list`. If there are no more UTXO available before reaching the total required amount, an error of type TransactionCreateError::InsufficientFunds is returned. Finally, the function calculates the change amount by subtracting the total required amount from the accumulated amount and returns a tuple containing the list of transaction inputs (TxIn) and the change amount.
+/// As in Bitcoin Core, an output is dust when spending it costs more than it is worth at `DUST_RELAY_FEE_RATE` satoshis per
+/// virtual byte, counting the size of the output and of the input that spends it. The input sizes are the fixed ones
+/// Bitcoin Core uses, `LEGACY_INPUT_SIZE` and `WITNESS_INPUT_VSIZE`, so the limits are the same the nodes apply: 546
+/// satoshis for P2PKH and 294 for P2WPKH.
+pub fn get_dust_limit(pk_script: &[u8]) -> i64 {
+    let input_size = match ScriptType::from_pk_script(pk_script) {
+        ScriptType::P2wpkh | ScriptType::P2wsh | ScriptType::P2tr => WITNESS_INPUT_VSIZE,
+        _ => LEGACY_INPUT_SIZE,
+    };
+    (TXOUT_FIXED_SIZE + pk_script.len() as i64 + input_size) * DUST_RELAY_FEE_RATE
+}
+
+/// Creates a list of transaction inputs (TxIn) from the unspent transaction outputs (UTXO) chosen by a coin selector.
+///
+/// # Arguments
+///
+/// * `utxo`: A slice of tuples representing the unspent transaction outputs. Each tuple contains a vector of bytes representing the transaction ID, a u32 representing the output index, and a TxOut struct representing the output details.
+/// * `target`: The amount the chosen UTXOs must cover after paying the fee of their own inputs.
+/// * `fee_rate`: The fee rate of the transaction, in satoshis per virtual byte.
+/// * `change_pk_script`: The script the change would be sent to, which sets the cost of a change output and its dust limit.
+/// * `scripts`: The redeem and witness scripts of the wallet, which set the cost of spending its P2SH and P2WSH outputs.
+/// * `coin_selector`: The strategy that chooses which UTXOs are spent.
+///
+/// # Returns
+///
+/// A Result containing a tuple with the list of transaction inputs (TxIn), the outputs they spend and their total value, or an error of type TransactionCreateError if there are insufficient funds.
+///
+/// # Description
+///
+/// The coin selector chooses the UTXOs that cover the target, weighing them with `fee_rate` and `LONG_TERM_FEE_RATE`. For each of them, a new transaction input (TxIn) is created using the transaction ID, output index, empty scriptSig, and a sequence value of 0xffffffff, and the spent output is added to `prev_outputs`, since signing needs its script and amount. If the selector can't cover the target, an error of type TransactionCreateError::InsufficientFounds is returned.
 ///
 fn create_txin_list(
-    mut utxo: Vec<(Vec<u8>, u32, TxOut)>,
-    total_amount: i64,
-) -> Result<(Vec<TxIn>, i64), TransactionCreateError> {
-    let mut acum_amount = 0;
+    utxo: &[(Vec<u8>, u32, TxOut)],
+    target: i64,
+    fee_rate: i64,
+    change_pk_script: &[u8],
+    scripts: &[Vec<u8>],
+    coin_selector: &dyn CoinSelector,
+) -> Result<(Vec<TxIn>, Vec<TxOut>, i64), TransactionCreateError> {
+    let params = SelectionParams::with_scripts(
+        target,
+        fee_rate,
+        LONG_TERM_FEE_RATE,
+        change_pk_script,
+        scripts,
+    );
+    let selection = coin_selector
+        .select(utxo, &params)
+        .ok_or(TransactionCreateError::InsufficientFounds)?;
+
     let mut txin_list = vec![];
+    let mut prev_outputs = vec![];
+    for (tx_id, index, txout) in selection.get_utxo().iter().cloned() {
+        txin_list.push(TxIn::new(tx_id, index, vec![], 0xffffffff));
+        prev_outputs.push(txout);
+    }
 
-    while acum_amount < total_amount {
-        if let Some(txout) = utxo.pop() {
-            let txin = TxIn::new(txout.0, txout.1, vec![], 0xffffffff);
-            acum_amount += txout.2.get_value();
-            txin_list.push(txin);
+    Ok((txin_list, prev_outputs, selection.get_value()))
+}
+
+/// Signs a transaction by adding the signature scripts or witnesses to each transaction input.
+///
+/// # Arguments
+///
+/// * `transaction`: A mutable reference to a Transaction struct representing the transaction to be signed.
+/// * `private_keys`: The private keys of the wallet, the one each input is locked to is used to sign it.
+/// * `prev_outputs`: The outputs spent by the transaction inputs, in the same order.
+/// * `sighash_type`: The signature hash type (`SIGHASH_ALL`, `SIGHASH_NONE` or `SIGHASH_SINGLE`, optionally combined with `SIGHASH_ANYONECANPAY`).
+///
+/// # Returns
+///
+/// * `Result<(), TransactionCreateError>`: An error if an input isn't locked to any of the keys or a signature hash can't be used as a message.
+///
+/// # Description
+///
+/// This function iterates over each transaction input in the transaction and looks for the private key whose public key hash is the one of the spent output. Then it signs the input depending on the script of that output:
+///
+/// * P2WPKH: the signature hash is calculated with `transaction.witness_v0_sig_hash` (BIP143), using as script code the P2PKH script of the key hash and the amount of the spent output. The signature and the serialized public key are set as the witness of the input and the signature script is left empty.
+/// * Otherwise (P2PKH): the signature hash is calculated with `transaction.sig_hash` using the public key script of the spent output, and the DER-encoded signature and the serialized public key are set as the signature script.
+///
+/// In both cases the signature is followed by the sighash type byte, so the verifier knows which parts of the transaction it commits to.
+///
+pub fn sign_transaction(
+    transaction: &mut Transaction,
+    private_keys: &[SecretKey],
+    prev_outputs: &[TxOut],
+    sighash_type: u32,
+) -> Result<(), TransactionCreateError> {
+    let secp = Secp256k1::new();
+    let keys: Vec<(SecretKey, Vec<u8>)> = private_keys
+        .iter()
+        .map(|private_key| {
+            let sec = PublicKey::from_secret_key(&secp, private_key).serialize();
+            (*private_key, sec.to_vec())
+        })
+        .collect();
+
+    for (i, prev_output) in prev_outputs.iter().enumerate() {
+        let pk_script = prev_output.get_pk_script();
+        let h160 = get_pubkey_hash(&pk_script).ok_or(TransactionCreateError::PrivateKey)?;
+        let (private_key, sec) = keys
+            .iter()
+            .find(|(_, sec)| hash160::Hash::hash(sec).to_byte_array() == h160)
+            .ok_or(TransactionCreateError::PrivateKey)?;
+        let sig = sign_input(transaction, i, prev_output, private_key, sighash_type)?;
+
+        if ScriptType::from_pk_script(&pk_script) == ScriptType::P2wpkh {
+            transaction.set_witness(i, vec![sig, sec.clone()]);
         } else {
-            return Err(TransactionCreateError::InsufficientFounds);
+            let signature_script = Script::new(Some(vec![sig, sec.clone()]));
+            transaction.set_signature(i, signature_script.as_bytes());
+        }
+    }
+    Ok(())
+}
+
+/// Signs a transaction whose inputs spend outputs locked to multisig scripts of the wallet.
+///
+/// # Arguments
+///
+/// * `transaction`: The transaction to sign.
+/// * `private_keys`: The keys of the scripts held by the wallet.
+/// * `prev_outputs`: The outputs spent by the transaction inputs, in the same order.
+/// * `scripts`: The redeem and witness scripts of the wallet.
+/// * `sighash_type`: The sighash type every signature commits to, appended to it.
+///
+/// # Returns
+///
+/// * `Result<(), TransactionCreateError>`: `TransactionCreateError::PrivateKey` if an input isn't locked to a multisig script of `scripts`, or `TransactionCreateError::MissingSignatures` if the keys aren't enough to spend one.
+///
+/// # Description
+///
+/// Each input is signed with every key of its script, see `sign_script_input`, and gets the dummy element, the signatures and the script as its signature script if it spends a P2SH output, or as its witness if it spends a P2WSH one. Accounts whose keys are held by several cosigners need a PSBT instead, see `Psbt::sign`.
+///
+pub fn sign_multisig_transaction(
+    transaction: &mut Transaction,
+    private_keys: &[SecretKey],
+    prev_outputs: &[TxOut],
+    scripts: &[Vec<u8>],
+    sighash_type: u32,
+) -> Result<(), TransactionCreateError> {
+    let secp = Secp256k1::new();
+
+    for (i, prev_output) in prev_outputs.iter().enumerate() {
+        let pk_script = prev_output.get_pk_script();
+        let script = find_script(&pk_script, scripts).ok_or(TransactionCreateError::PrivateKey)?;
+        let multisig =
+            MultisigScript::from_bytes(script).map_err(|_| TransactionCreateError::PrivateKey)?;
+
+        let mut signatures = BTreeMap::new();
+        for private_key in private_keys {
+            let sec = PublicKey::from_secret_key(&secp, private_key)
+                .serialize()
+                .to_vec();
+            if multisig.get_public_keys().contains(&sec) {
+                let sig = sign_script_input(
+                    transaction,
+                    i,
+                    prev_output,
+                    script,
+                    private_key,
+                    sighash_type,
+                )?;
+                signatures.insert(sec, sig);
+            }
+        }
+        let mut unlocking_data = multisig
+            .get_unlocking_data(&signatures)
+            .map_err(|_| TransactionCreateError::MissingSignatures)?;
+        unlocking_data.push(script.clone());
+
+        match ScriptType::from_pk_script(&pk_script) {
+            ScriptType::P2wsh => transaction.set_witness(i, unlocking_data),
+            _ => transaction.set_signature(i, Script::new(Some(unlocking_data)).as_bytes()),
         }
     }
+    Ok(())
+}
 
-    let change_amount = acum_amount - total_amount;
+/// Signs an input of a transaction that spends a P2PKH or P2WPKH output.
+///
+/// # Arguments
+///
+/// * `transaction`: The transaction being signed.
+/// * `index`: The index of the input.
+/// * `prev_output`: The output spent by the input.
+/// * `private_key`: The key the output is locked to.
+/// * `sighash_type`: The signature hash type, see `sign_transaction`.
+///
+/// # Returns
+///
+/// * `Result<Vec<u8>, TransactionCreateError>`: The DER-encoded signature followed by the sighash type byte, or an error if the signature hash can't be used as a message.
+///
+/// # Description
+///
+/// The signature hash of a P2WPKH output is calculated with `transaction.witness_v0_sig_hash` (BIP143), using as script code the P2PKH script of the key hash and the amount of the output. The one of any other output with `transaction.sig_hash` and its public key script.
+///
+pub fn sign_input(
+    transaction: &Transaction,
+    index: usize,
+    prev_output: &TxOut,
+    private_key: &SecretKey,
+    sighash_type: u32,
+) -> Result<Vec<u8>, TransactionCreateError> {
+    let pk_script = prev_output.get_pk_script();
+    let signature_hash = match get_pubkey_hash(&pk_script) {
+        Some(h160) if ScriptType::from_pk_script(&pk_script) == ScriptType::P2wpkh => {
+            let script_code = p2pkh(h160.to_vec()).as_bytes();
+            transaction.witness_v0_sig_hash(
+                index,
+                &script_code,
+                prev_output.get_value(),
+                sighash_type,
+            )
+        }
+        _ => transaction.sig_hash(index, &pk_script, sighash_type),
+    };
+    sign_hash(&signature_hash, private_key, sighash_type)
+}
 
-    Ok((txin_list, change_amount))
+/// Signs an input of a transaction that spends a P2SH or P2WSH output with one of the keys of its script.
+///
+/// # Arguments
+///
+/// * `transaction`: The transaction being signed.
+/// * `index`: The index of the input.
+/// * `prev_output`: The output spent by the input.
+/// * `script`: The redeem script of a P2SH output or the witness script of a P2WSH one.
+/// * `private_key`: A key of the script.
+/// * `sighash_type`: The signature hash type, see `sign_transaction`.
+///
+/// # Returns
+///
+/// * `Result<Vec<u8>, TransactionCreateError>`: The DER-encoded signature followed by the sighash type byte, or an error if the signature hash can't be used as a message.
+///
+/// # Description
+///
+/// The script takes the place of the public key script as the script code: the signature hash of a P2WSH output is calculated with `transaction.witness_v0_sig_hash` (BIP143) and the amount of the output, the one of a P2SH output with `transaction.sig_hash`.
+///
+pub fn sign_script_input(
+    transaction: &Transaction,
+    index: usize,
+    prev_output: &TxOut,
+    script: &[u8],
+    private_key: &SecretKey,
+    sighash_type: u32,
+) -> Result<Vec<u8>, TransactionCreateError> {
+    let signature_hash = match ScriptType::from_pk_script(&prev_output.get_pk_script()) {
+        ScriptType::P2wsh => {
+            transaction.witness_v0_sig_hash(index, script, prev_output.get_value(), sighash_type)
+        }
+        _ => transaction.sig_hash(index, script, sighash_type),
+    };
+    sign_hash(&signature_hash, private_key, sighash_type)
 }
 
-/// Signs a transaction by adding the signature scripts to each transaction input.
+/// Signs a signature hash, returning the DER-encoded signature followed by the sighash type byte.
+fn sign_hash(
+    signature_hash: &[u8],
+    private_key: &SecretKey,
+    sighash_type: u32,
+) -> Result<Vec<u8>, TransactionCreateError> {
+    let message = Message::from_slice(signature_hash)?;
+    let der = Secp256k1::signing_only()
+        .sign_ecdsa(&message, private_key)
+        .serialize_der()
+        .to_vec();
+    Ok([der, vec![sighash_type as u8]].concat())
+}
+
+/// Splits the signature script of a P2PKH input into its signature and public key.
+fn split_signature_script(signature_script: &[u8]) -> Option<(Vec<u8>, Vec<u8>)> {
+    let (sig_len, rest) = signature_script.split_first()?;
+    let sig = rest.get(..*sig_len as usize)?;
+    let (sec_len, sec) = rest.get(*sig_len as usize..)?.split_first()?;
+    if sec.len() != *sec_len as usize {
+        return None;
+    }
+    Some((sig.to_vec(), sec.to_vec()))
+}
+
+/// Verifies the signature of a transaction input that spends a P2PKH or P2WPKH output.
 ///
 /// # Arguments
 ///
-/// * `transaction`: A mutable reference to a Transaction struct representing the transaction to be signed.
-/// * `private_key`: A SecretKey representing the private key used for signing.
-/// * `pk_script`: A reference to a vector of bytes representing the public key script associated with the transaction inputs.
+/// * `transaction`: The signed transaction.
+/// * `index`: The index of the input to verify.
+/// * `prev_output`: The output spent by the input.
+///
+/// # Returns
+///
+/// * `bool`: Whether the input has a valid signature of the key the output is locked to.
 ///
 /// # Description
 ///
-/// This function iterates over each transaction input in the transaction and adds the signature scripts. It uses the provided private key to sign each input individually. For each input, it calculates the signature hash by calling `transaction.sig_hash` with the input index and the public key script. It then creates a message from the hashed data using the sha256::Hash algorithm. The private key is used to sign the message with the secp256k1 algorithm, producing a DER-encoded signature. The DER-encoded signature and the serialized public key are concatenated into a signature script. Finally, the signature script is set for the corresponding input in the transaction using `transaction.set_signature`.
+/// The signature and public key are read from the witness for P2WPKH outputs and from the signature script for P2PKH ones. The last byte of the signature is the sighash type it was made with, so the signature hash is recalculated with that same type before verifying the DER-encoded signature.
 ///
-fn sign_transaction(transaction: &mut Transaction, private_key: SecretKey, pk_script: &[u8]) {
-    let secp = Secp256k1::new();
+pub fn verify_input_signature(
+    transaction: &Transaction,
+    index: usize,
+    prev_output: &TxOut,
+) -> bool {
+    let pk_script = prev_output.get_pk_script();
+    let h160 = match get_pubkey_hash(&pk_script) {
+        Some(h160) => h160.to_vec(),
+        None => return false,
+    };
+    let txin = match transaction.get_tx_in_list().get(index) {
+        Some(txin) => txin,
+        None => return false,
+    };
 
-    for i in 0..transaction.get_tx_in_list().len() {
-        let signature_hash = transaction.sig_hash(i, pk_script);
-        let message = Message::from_hashed_data::<sha256::Hash>(&signature_hash);
-        let der = secp
-            .sign_ecdsa(&message, &private_key)
-            .serialize_der()
-            .to_vec();
-        let sig = vec![der, vec![1_u8]].concat();
-        let sec = PublicKey::from_secret_key(&secp, &private_key)
-            .serialize()
-            .to_vec();
-        let signature_script = Script::new(Some(vec![sig, sec]));
+    let (sig, sec) = match ScriptType::from_pk_script(&pk_script) {
+        ScriptType::P2wpkh => match txin.get_witness().as_slice() {
+            [sig, sec] => (sig.clone(), sec.clone()),
+            _ => return false,
+        },
+        _ => match split_signature_script(txin.get_signature_script()) {
+            Some(sig_and_sec) => sig_and_sec,
+            None => return false,
+        },
+    };
+    if hash160::Hash::hash(&sec).to_byte_array().to_vec() != h160 {
+        return false;
+    }
+
+    let (sighash_type, der) = match sig.split_last() {
+        Some((sighash_type, der)) => (*sighash_type as u32, der),
+        None => return false,
+    };
+    let signature_hash = match ScriptType::from_pk_script(&pk_script) {
+        ScriptType::P2wpkh => transaction.witness_v0_sig_hash(
+            index,
+            &p2pkh(h160).as_bytes(),
+            prev_output.get_value(),
+            sighash_type,
+        ),
+        _ => transaction.sig_hash(index, &pk_script, sighash_type),
+    };
+
+    let secp = Secp256k1::verification_only();
+    match (
+        Message::from_slice(&signature_hash),
+        Signature::from_der(der),
+        PublicKey::from_slice(&sec),
+    ) {
+        (Ok(message), Ok(signature), Ok(public_key)) => {
+            secp.verify_ecdsa(&message, &signature, &public_key).is_ok()
+        }
+        _ => false,
+    }
+}
+
+/// Creates an unsigned transaction that pays the targets with some of the given UTXOs at a fee rate.
+///
+/// # Arguments
+///
+/// * `targets`: A vector of tuples containing the recipient addresses and corresponding amounts to be sent.
+/// * `utxo`: The unspent transaction outputs (UTXOs) that can be spent, P2PKH or P2WPKH outputs of the wallet or P2SH and P2WSH outputs locked to one of `scripts`.
+/// * `change_pk_script`: The script the change is sent to, a fresh address of the wallet so the change can't be linked to the inputs.
+/// * `scripts`: The redeem and witness scripts of the wallet, needed to estimate the size of the inputs that spend its P2SH and P2WSH outputs.
+/// * `fee_rate`: The fee rate to pay, in satoshis per virtual byte.
+/// * `network`: The network the target addresses must belong to.
+/// * `coin_selector`: The strategy that chooses which UTXOs are spent, see `coin_selection`.
+///
+/// # Returns
+///
+/// * `Result<(Transaction, Vec<TxOut>, i64), TransactionCreateError>`: The unsigned transaction, the outputs spent by its inputs in the same order and the fee it pays, or an error of type TransactionCreateError.
+///
+/// # Description
+///
+/// 1. Create the list of transaction outputs (txout_list) and calculate the total amount sent by calling the create_txout_list function with the targets and network parameters. If an address is invalid or belongs to another network, return the corresponding error.
+/// 2. The inputs must cover the total amount plus the fee of the parts of the transaction that don't depend on them: the version, lock time, counts and outputs.
+/// 3. Choose the inputs by calling the create_txin_list function, and estimate the virtual size of the signed transaction with `estimate_vsize`. If the chosen UTXOs don't cover the total amount plus the fee of that size, which can happen because of the SegWit marker or the rounding of the input sizes, raise the target by the difference and choose again, up to `MAX_FEE_ITERATIONS` times. If the coin selector can't cover the target, return an error of type TransactionCreateError::InsufficientFounds.
+/// 4. Send the change to `change_pk_script` in an output inserted at a random position, so it can't be told apart from the targets by its place. The change is what is left after paying the fee of the transaction with the change output. If it is below the dust limit of the script, see `get_dust_limit`, it is left out and added to the fee.
+///
+pub fn create_unsigned_transaction(
+    targets: Vec<(Vec<u8>, i64)>,
+    utxo: &[(Vec<u8>, u32, TxOut)],
+    change_pk_script: &[u8],
+    scripts: &[Vec<u8>],
+    fee_rate: i64,
+    network: BitcoinNetwork,
+    coin_selector: &dyn CoinSelector,
+) -> Result<(Transaction, Vec<TxOut>, i64), TransactionCreateError> {
+    let (txout_list, total_amount) = create_txout_list(targets, network)?;
+
+    let fixed_vsize = Transaction::new(1, vec![], txout_list.clone(), 0).get_vsize() as i64;
+    let mut target = total_amount + fixed_vsize * fee_rate;
+
+    for _ in 0..MAX_FEE_ITERATIONS {
+        let (txin_list, prev_outputs, value) = create_txin_list(
+            utxo,
+            target,
+            fee_rate,
+            change_pk_script,
+            scripts,
+            coin_selector,
+        )?;
+        let mut transaction = Transaction::new(1, txin_list.clone(), txout_list.clone(), 0);
+        let fee = estimate_vsize(&transaction, &prev_outputs, scripts) * fee_rate;
+        if value < total_amount + fee {
+            target += total_amount + fee - value;
+            continue;
+        }
+
+        let mut txout_list = txout_list.clone();
+        let position = rand::thread_rng().gen_range(0..=txout_list.len());
+        txout_list.insert(position, TxOut::new(0, change_pk_script.to_vec()));
+        let with_change = Transaction::new(1, txin_list.clone(), txout_list.clone(), 0);
+        let change_amount =
+            value - total_amount - estimate_vsize(&with_change, &prev_outputs, scripts) * fee_rate;
+
+        if change_amount >= get_dust_limit(change_pk_script) {
+            txout_list[position] = TxOut::new(change_amount, change_pk_script.to_vec());
+            transaction = Transaction::new(1, txin_list, txout_list, 0);
+        }
 
-        transaction.set_signature(i, signature_script.as_bytes());
+        let sent: i64 = transaction
+            .get_tx_out_list()
+            .iter()
+            .map(|txout| txout.get_value())
+            .sum();
+        return Ok((transaction, prev_outputs, value - sent));
     }
+    Err(TransactionCreateError::InsufficientFounds)
 }
 
-/// Creates a new transaction by assembling inputs, outputs, and signing it with a private key.
+/// Creates a new transaction by assembling inputs, outputs, and signing it with the keys of the wallet.
 ///
 /// # Arguments
 ///
 /// * `targets`: A vector of tuples containing the recipient addresses and corresponding amounts to be sent.
 /// * `utxo`: A vector of tuples representing the unspent transaction outputs (UTXOs) available for spending.
-/// * `private_key`: A reference to a vector of bytes representing the private key used for signing the transaction.
-/// * `fee`: The fee amount to be deducted from the total transaction amount.
+/// * `private_keys`: The private keys of the wallet.
+/// * `change_pk_script`: The script the change is sent to, a fresh address of the wallet so the change can't be linked to the inputs.
+/// * `fee_rate`: The fee rate to pay, in satoshis per virtual byte.
+/// * `network`: The network the target addresses must belong to.
+/// * `coin_selector`: The strategy that chooses which UTXOs are spent, see `coin_selection`.
 ///
 /// # Returns
 ///
-/// * `Result<Transaction, TransactionCreateError>`: A Result enum with either a Transaction struct representing the created transaction or an error of type TransactionCreateError.
+/// * `Result<(Transaction, i64), TransactionCreateError>`: A Result enum with either the created transaction and the fee it pays or an error of type TransactionCreateError.
 ///
 /// # Description
 ///
 /// This function creates a new transaction by following the steps below:
 ///
 /// 1. Initialize a new instance of the secp256k1::Secp256k1 struct.
-/// 2. Deserialize the provided private_key vector of bytes into a SecretKey. If deserialization fails, return an error of type TransactionCreateError::PrivateKey.
-/// 3. Generate the public key from the private key using the secp256k1 algorithm and serialize it into a vector of bytes.
-/// 4. Obtain the public key script (pk_script) from the public key by calling the pk_script_from_public_key function.
-/// 5. Create the list of transaction outputs (txout_list) and calculate the total transaction amount by calling the create_txout_list function with the targets and fee parameters.
-/// 6. Create the list of transaction inputs (txin_list) and calculate the change amount by calling the create_txin_list function with the utxo and total_amount parameters. If there are insufficient funds to cover the total amount, return an error of type TransactionCreateError::InsufficientFounds.
-/// 7. Create a new transaction (Transaction) object with the version 1, txin_list, txout_list, and lock_time set to 0.
-/// 8. Sign the transaction by calling the sign_transaction function, passing in a mutable reference to the transaction, the private key, and the pk_script.
-/// 9. Return the signed transaction as Ok(transaction).
+/// 2. Deserialize the provided private keys into SecretKeys. If there are none or a deserialization fails, return an error of type TransactionCreateError::PrivateKey.
+/// 3. Obtain the P2PKH and P2WPKH scripts of the public key of every private key.
+/// 4. Keep only the UTXOs locked to those scripts, since those are the only ones this function can sign, and create the unsigned transaction with `create_unsigned_transaction`.
+/// 5. Sign the transaction by calling the sign_transaction function, passing in a mutable reference to the transaction, the private keys, and the spent outputs. Every input is signed with SIGHASH_ALL; a transaction signed with another type is built with `create_unsigned_transaction` and `sign_transaction`, as `UserInfo::create_transaction` does.
+/// 6. Return the signed transaction and its fee.
 ///
 pub fn create_transaction(
     targets: Vec<(Vec<u8>, i64)>,
     utxo: Vec<(Vec<u8>, u32, TxOut)>,
-    private_key: &[u8],
-    fee: i64,
-) -> Result<Transaction, TransactionCreateError> {
+    private_keys: &[Vec<u8>],
+    change_pk_script: &[u8],
+    fee_rate: i64,
+    network: BitcoinNetwork,
+    coin_selector: &dyn CoinSelector,
+) -> Result<(Transaction, i64), TransactionCreateError> {
     let secp = Secp256k1::new();
 
-    let private_key =
-        SecretKey::from_slice(private_key).map_err(|_| TransactionCreateError::PrivateKey)?;
-    let public_key = PublicKey::from_secret_key(&secp, &private_key)
-        .serialize()
-        .to_vec();
-    let pk_script = pk_script_from_public_key(&public_key);
-
-    let (mut txout_list, total_amount) = create_txout_list(targets, fee);
-    let (txin_list, change_amount) = create_txin_list(utxo, total_amount)?;
-
-    let change = TxOut::new(change_amount, pk_script.clone());
-    txout_list.push(change);
-
-    let mut transaction = Transaction::new(1, txin_list, txout_list, 0);
-
-    sign_transaction(&mut transaction, private_key, &pk_script);
+    let private_keys = private_keys
+        .iter()
+        .map(|private_key| SecretKey::from_slice(private_key))
+        .collect::<Result<Vec<SecretKey>, _>>()
+        .map_err(|_| TransactionCreateError::PrivateKey)?;
+    let public_keys: Vec<Vec<u8>> = private_keys
+        .iter()
+        .map(|private_key| {
+            PublicKey::from_secret_key(&secp, private_key)
+                .serialize()
+                .to_vec()
+        })
+        .collect();
+    if private_keys.is_empty() {
+        return Err(TransactionCreateError::PrivateKey);
+    }
 
-    Ok(transaction)
+    let own_pk_scripts: Vec<Vec<u8>> = public_keys
+        .iter()
+        .flat_map(|public_key| get_pubkey_hash_scripts(&pk_script_from_public_key(public_key)))
+        .collect();
+    let utxo: Vec<(Vec<u8>, u32, TxOut)> = utxo
+        .into_iter()
+        .filter(|(_, _, txout)| own_pk_scripts.contains(&txout.get_pk_script()))
+        .collect();
+
+    let (mut transaction, prev_outputs, fee) = create_unsigned_transaction(
+        targets,
+        &utxo,
+        change_pk_script,
+        &[],
+        fee_rate,
+        network,
+        coin_selector,
+    )?;
+    sign_transaction(&mut transaction, &private_keys, &prev_outputs, SIGHASH_ALL)?;
+
+    Ok((transaction, fee))
 }
 
 #[cfg(test)]
 mod create_transactions_test {
     use std::str::FromStr;
 
-    use bitcoin_hashes::*;
     use node::{
-        block_mod::{script::Script, transaction::Transaction, tx_in::TxIn, tx_out::TxOut},
+        block_mod::{
+            script::Script,
+            transaction::{
+                Transaction, SIGHASH_ALL, SIGHASH_ANYONECANPAY, SIGHASH_NONE, SIGHASH_SINGLE,
+            },
+            tx_in::TxIn,
+            tx_out::TxOut,
+        },
         messages::read_from_bytes::{decode_hex, encode_hex},
+        network::bitcoin_network::BitcoinNetwork,
     };
     use secp256k1::{Message, PublicKey, Secp256k1, SecretKey};
 
     use crate::transactions::{
-        create_transaction_error::TransactionCreateError, create_transactions::decode_base58,
+        address_error::AddressError, coin_selection::largest_first::LargestFirst,
+        create_transaction_error::TransactionCreateError,
     };
 
-    use super::{address_from_public_key, pk_script_from_address};
+    use super::{
+        address_from_public_key, get_dust_limit, pk_script_from_address, pk_script_from_public_key,
+        private_key_from_wif, sign_transaction, verify_input_signature, wif_from_private_key,
+        witness_pk_script_from_public_key,
+    };
 
     #[test]
     pub fn create_transaction() -> Result<(), TransactionCreateError> {
@@ -318,7 +848,7 @@ mod create_transactions_test {
 
         // calculo el cambio
         let change_amount = 0.0009 * 100000000.0;
-        let change_h160 = decode_base58(&address);
+        let change_h160 = decode_hex("de155d570f91c18bc92a2faecbef20fd00fca7b4")?;
         let change_script = Script::new(Some(vec![
             vec![0x76],
             vec![0xa9],
@@ -327,9 +857,13 @@ mod create_transactions_test {
             vec![0xac],
         ]));
         let change_txout = TxOut::new(change_amount as i64, change_script.as_bytes());
+        assert_eq!(
+            pk_script_from_address(&address, BitcoinNetwork::Testnet)?,
+            change_script.as_bytes()
+        );
 
         let target_amount = 0.0021 * 100000000.0;
-        let target_h160 = decode_base58(&target);
+        let target_h160 = decode_hex("5d824ae729e679cdbd897f9ae9987395b7042c6c")?;
         let target_script = Script::new(Some(vec![
             vec![0x76],
             vec![0xa9],
@@ -338,16 +872,20 @@ mod create_transactions_test {
             vec![0xac],
         ]));
         let target_txout = TxOut::new(target_amount as i64, target_script.as_bytes());
+        assert_eq!(
+            pk_script_from_address(&target, BitcoinNetwork::Testnet)?,
+            target_script.as_bytes()
+        );
 
         let mut tx = Transaction::new(1, vec![txin], vec![change_txout, target_txout], 0);
 
         let secp = Secp256k1::new();
 
-        let signature_hash = tx.sig_hash(0, &change_script.as_bytes());
+        let signature_hash = tx.sig_hash(0, &change_script.as_bytes(), SIGHASH_ALL);
 
         let private_key = SecretKey::from_str(private_key)?;
 
-        let message = Message::from_hashed_data::<sha256::Hash>(&signature_hash);
+        let message = Message::from_slice(&signature_hash)?;
 
         let der = secp
             .sign_ecdsa(&message, &private_key)
@@ -374,9 +912,261 @@ mod create_transactions_test {
             decode_hex("02E641B11A0FB5A761814D0F166ADC4E654037C844B44226219AE3D6947EBC4DA6")?;
         let address = b"n1mDu5Zd5qS75vqK1yqnKmEZQzDyncQqj4".to_vec();
 
-        let address_calculated = address_from_public_key(&public_key);
+        let address_calculated = address_from_public_key(&public_key, BitcoinNetwork::Testnet);
+
+        assert_eq!(address_calculated, address);
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_mainnet_address_from_public_key() -> Result<(), TransactionCreateError> {
+        let public_key =
+            decode_hex("0279BE667EF9DCBBAC55A06295CE870B07029BFCDB2DCE28D959F2815B16F81798")?;
+        let address = b"1BgGZ9tcN4rm9KBzDn7KprQz87SZ26SAMH".to_vec();
+
+        let address_calculated = address_from_public_key(&public_key, BitcoinNetwork::Mainnet);
 
         assert_eq!(address_calculated, address);
+        assert!(pk_script_from_address(&address, BitcoinNetwork::Mainnet).is_ok());
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_address_of_other_network_is_rejected() {
+        let testnet_address = b"mzx5YhAH9kNHtcN481u6WkjeHjYtVeKVh2".to_vec();
+        let mainnet_address = b"1BgGZ9tcN4rm9KBzDn7KprQz87SZ26SAMH".to_vec();
+
+        assert!(matches!(
+            pk_script_from_address(&testnet_address, BitcoinNetwork::Mainnet),
+            Err(TransactionCreateError::InvalidAddress(
+                _,
+                AddressError::WrongNetwork
+            ))
+        ));
+        assert!(matches!(
+            pk_script_from_address(&mainnet_address, BitcoinNetwork::Testnet),
+            Err(TransactionCreateError::InvalidAddress(
+                _,
+                AddressError::WrongNetwork
+            ))
+        ));
+    }
+
+    #[test]
+    pub fn test_private_key_from_wif() -> Result<(), TransactionCreateError> {
+        let private_key =
+            decode_hex("0000000000000000000000000000000000000000000000000000000000000001")?;
+        let mainnet_wif = b"KwDiBf89QgGbjEhKnhXJuH7LrciVrZi3qYjgd9M7rFU73sVHnoWn".to_vec();
+        let testnet_wif = b"cMahea7zqjxrtgAbB7LSGbcQUr1uX1ojuat9jZodMN87JcbXMTcA".to_vec();
+
+        assert_eq!(
+            wif_from_private_key(&private_key, BitcoinNetwork::Mainnet),
+            mainnet_wif
+        );
+        assert_eq!(
+            wif_from_private_key(&private_key, BitcoinNetwork::Testnet),
+            testnet_wif
+        );
+        assert_eq!(
+            private_key_from_wif(&mainnet_wif, BitcoinNetwork::Mainnet)?,
+            private_key
+        );
+        assert!(matches!(
+            private_key_from_wif(&mainnet_wif, BitcoinNetwork::Testnet),
+            Err(TransactionCreateError::WrongNetwork)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_create_transaction_spending_p2wpkh() -> Result<(), TransactionCreateError> {
+        let private_key =
+            decode_hex("740A9C5D2BD171E99DDDC268A26179FCAD9BFE9A7A8188725EDA0D1D9F6D2264")?;
+        let secp = Secp256k1::new();
+        let secret_key = SecretKey::from_slice(&private_key)?;
+        let public_key = PublicKey::from_secret_key(&secp, &secret_key);
+
+        let pk_script = witness_pk_script_from_public_key(&public_key.serialize());
+        let prev_tx =
+            decode_hex("7a56640d6c89ce4744ab77c5332c87fec02c58720a7fc1ba19d6b6546f5b29e8")?;
+        let utxo = vec![(prev_tx, 0, TxOut::new(1000000, pk_script.clone()))];
+        let targets = vec![(b"mp3PDnKDtxPYrPKcYLGX1pXMe6KwAsfquD".to_vec(), 500000)];
+
+        let (transaction, fee) = super::create_transaction(
+            targets,
+            utxo,
+            &[private_key],
+            &pk_script,
+            10,
+            BitcoinNetwork::Testnet,
+            &LargestFirst,
+        )?;
+        let txin = &transaction.get_tx_in_list()[0];
+
+        assert!(txin.get_signature_script().is_empty());
+        assert_eq!(txin.get_witness().len(), 2);
+        assert_eq!(txin.get_witness()[1], public_key.serialize().to_vec());
+        // The estimate takes the longest signature, so the signed transaction may be a byte smaller.
+        assert_eq!(fee, 1440);
+        assert!(transaction.get_vsize() <= 144);
+
+        let der = &txin.get_witness()[0][..txin.get_witness()[0].len() - 1];
+        let script_code = pk_script_from_public_key(&public_key.serialize());
+        let signature_hash = transaction.witness_v0_sig_hash(0, &script_code, 1000000, SIGHASH_ALL);
+        let message = Message::from_slice(&signature_hash)?;
+        let signature = secp256k1::ecdsa::Signature::from_der(der)?;
+
+        assert!(secp.verify_ecdsa(&message, &signature, &public_key).is_ok());
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_sign_with_sighash_types() -> Result<(), TransactionCreateError> {
+        let private_key = SecretKey::from_str(
+            "740A9C5D2BD171E99DDDC268A26179FCAD9BFE9A7A8188725EDA0D1D9F6D2264",
+        )?;
+        let public_key = PublicKey::from_secret_key(&Secp256k1::new(), &private_key).serialize();
+        let prev_outputs = vec![
+            TxOut::new(1000000, pk_script_from_public_key(&public_key)),
+            TxOut::new(2000000, witness_pk_script_from_public_key(&public_key)),
+        ];
+        let target_script = pk_script_from_address(
+            b"mp3PDnKDtxPYrPKcYLGX1pXMe6KwAsfquD",
+            BitcoinNetwork::Testnet,
+        )?;
+        let txin_list = vec![
+            TxIn::new(vec![1; 32], 0, vec![], 0xffffffff),
+            TxIn::new(vec![2; 32], 1, vec![], 0xffffffff),
+        ];
+
+        for sighash_type in [SIGHASH_ALL, SIGHASH_NONE, SIGHASH_SINGLE] {
+            for sighash_type in [sighash_type, sighash_type | SIGHASH_ANYONECANPAY] {
+                let txout_list = vec![
+                    TxOut::new(500000, target_script.clone()),
+                    TxOut::new(2400000, target_script.clone()),
+                ];
+                let mut transaction = Transaction::new(1, txin_list.clone(), txout_list, 0);
+                sign_transaction(
+                    &mut transaction,
+                    &[private_key],
+                    &prev_outputs,
+                    sighash_type,
+                )?;
+
+                for (i, prev_output) in prev_outputs.iter().enumerate() {
+                    assert!(verify_input_signature(&transaction, i, prev_output));
+                }
+
+                // Only SIGHASH_NONE leaves the second output free to be changed.
+                let txout_list = vec![
+                    TxOut::new(500000, target_script.clone()),
+                    TxOut::new(1, target_script.clone()),
+                ];
+                let modified =
+                    Transaction::new(1, transaction.get_tx_in_list().clone(), txout_list, 0);
+                assert_eq!(
+                    verify_input_signature(&modified, 0, &prev_outputs[0]),
+                    sighash_type & 0x1f != SIGHASH_ALL
+                );
+                assert_eq!(
+                    verify_input_signature(&modified, 1, &prev_outputs[1]),
+                    sighash_type & 0x1f == SIGHASH_NONE
+                );
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_create_transaction_with_several_keys() -> Result<(), TransactionCreateError> {
+        let private_keys = vec![vec![1; 32], vec![2; 32]];
+        let secp = Secp256k1::new();
+        let public_keys = private_keys
+            .iter()
+            .map(|private_key| {
+                let secret_key = SecretKey::from_slice(private_key)?;
+                Ok(PublicKey::from_secret_key(&secp, &secret_key).serialize())
+            })
+            .collect::<Result<Vec<[u8; 33]>, TransactionCreateError>>()?;
+        let utxo = vec![
+            (
+                vec![1; 32],
+                0,
+                TxOut::new(300000, pk_script_from_public_key(&public_keys[0])),
+            ),
+            (
+                vec![2; 32],
+                1,
+                TxOut::new(400000, witness_pk_script_from_public_key(&public_keys[1])),
+            ),
+            (
+                vec![3; 32],
+                0,
+                TxOut::new(900000, pk_script_from_public_key(&[3; 33])),
+            ),
+        ];
+        let targets = vec![(b"mp3PDnKDtxPYrPKcYLGX1pXMe6KwAsfquD".to_vec(), 600000)];
+        let change_pk_script = witness_pk_script_from_public_key(&[4; 33]);
+
+        let (transaction, fee) = super::create_transaction(
+            targets,
+            utxo.clone(),
+            &private_keys,
+            &change_pk_script,
+            10,
+            BitcoinNetwork::Testnet,
+            &LargestFirst,
+        )?;
+
+        // The output of an unknown key is never spent.
+        assert_eq!(transaction.get_tx_in_list().len(), 2);
+        assert!(verify_input_signature(&transaction, 0, &utxo[1].2));
+        assert!(verify_input_signature(&transaction, 1, &utxo[0].2));
+        assert!(transaction.get_tx_out_list().iter().any(|tx_out| {
+            tx_out.get_pk_script() == change_pk_script && tx_out.get_value() == 100000 - fee
+        }));
+        assert!(fee >= transaction.get_vsize() as i64 * 10);
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_dust_change_is_added_to_fee() -> Result<(), TransactionCreateError> {
+        let private_keys = vec![vec![1; 32]];
+        let secp = Secp256k1::new();
+        let public_key =
+            PublicKey::from_secret_key(&secp, &SecretKey::from_slice(&private_keys[0])?)
+                .serialize();
+        let pk_script = witness_pk_script_from_public_key(&public_key);
+        let utxo = vec![(vec![1; 32], 0, TxOut::new(100000, pk_script.clone()))];
+        let target = |amount| vec![(b"mp3PDnKDtxPYrPKcYLGX1pXMe6KwAsfquD".to_vec(), amount)];
+
+        assert_eq!(get_dust_limit(&pk_script), 294);
+        assert_eq!(get_dust_limit(&pk_script_from_public_key(&public_key)), 546);
+
+        // Without change the transaction has 113 virtual bytes, with a P2WPKH change output 144.
+        let (transaction, fee) = super::create_transaction(
+            target(98267),
+            utxo.clone(),
+            &private_keys,
+            &pk_script,
+            10,
+            BitcoinNetwork::Testnet,
+            &LargestFirst,
+        )?;
+        assert_eq!(transaction.get_tx_out_list().len(), 1);
+        assert_eq!(fee, 100000 - 98267);
+
+        let (transaction, fee) = super::create_transaction(
+            target(98266),
+            utxo,
+            &private_keys,
+            &pk_script,
+            10,
+            BitcoinNetwork::Testnet,
+            &LargestFirst,
+        )?;
+        assert_eq!(transaction.get_tx_out_list().len(), 2);
+        assert_eq!(fee, 1440);
         Ok(())
     }
 
@@ -395,7 +1185,7 @@ mod create_transactions_test {
         ]
         .concat();
 
-        let pk_script_calculated = pk_script_from_address(&address);
+        let pk_script_calculated = pk_script_from_address(&address, BitcoinNetwork::Testnet)?;
 
         assert_eq!(pk_script, pk_script_calculated);
 
@@ -408,9 +1198,9 @@ mod create_transactions_test {
             decode_hex("0362599B444272856B51E7EE10A4B70A683A9965AD3859E4D75E9B9EC136F84144")?;
 
         println!("{}", public_key.len());
-        let address = address_from_public_key(&public_key);
+        let address = address_from_public_key(&public_key, BitcoinNetwork::Testnet);
 
-        let pk_script = pk_script_from_address(&address);
+        let pk_script = pk_script_from_address(&address, BitcoinNetwork::Testnet)?;
 
         println!("{:?}", pk_script);
 
@@ -433,7 +1223,7 @@ mod create_transactions_test {
 
         // calculo el cambio
         let change_amount = 0.0009 * 100000000.0;
-        let change_h160 = decode_base58(&address);
+        let change_h160 = decode_hex("68e5bfff52953b4179bf03c990d8ac81aa65173e")?;
         let change_script = Script::new(Some(vec![
             vec![0x76],
             vec![0xa9],
@@ -442,9 +1232,13 @@ mod create_transactions_test {
             vec![0xac],
         ]));
         let change_txout = TxOut::new(change_amount as i64, change_script.as_bytes());
+        assert_eq!(
+            pk_script_from_address(&address, BitcoinNetwork::Testnet)?,
+            change_script.as_bytes()
+        );
 
         let target_amount = 0.0021 * 100000000.0;
-        let target_h160 = decode_base58(&target);
+        let target_h160 = decode_hex("f64fd1289550f634e20bf2ac6e95fb5d5fdbd50d")?;
         let target_script = Script::new(Some(vec![
             vec![0x76],
             vec![0xa9],
@@ -453,16 +1247,20 @@ mod create_transactions_test {
             vec![0xac],
         ]));
         let target_txout = TxOut::new(target_amount as i64, target_script.as_bytes());
+        assert_eq!(
+            pk_script_from_address(&target, BitcoinNetwork::Testnet)?,
+            target_script.as_bytes()
+        );
 
         let mut tx = Transaction::new(1, vec![txin], vec![change_txout, target_txout], 0);
 
         let secp = Secp256k1::new();
 
-        let signature_hash = tx.sig_hash(0, &change_script.as_bytes());
+        let signature_hash = tx.sig_hash(0, &change_script.as_bytes(), SIGHASH_ALL);
 
         let private_key = SecretKey::from_str(private_key)?;
 
-        let message = Message::from_hashed_data::<sha256::Hash>(&signature_hash);
+        let message = Message::from_slice(&signature_hash)?;
 
         let der = secp
             .sign_ecdsa(&message, &private_key)
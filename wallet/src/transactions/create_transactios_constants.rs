@@ -12,3 +12,22 @@ pub const PENDIENTE_VALUE: &str = "pendiente_value";
 pub const TOTAL_VALUE: &str = "total_value";
 
 pub const RECENT_TRANSACTION_BOX: &str = "recent_transactions_box";
+
+pub const DUST_RELAY_FEE_RATE: i64 = 3;
+pub const TXOUT_FIXED_SIZE: i64 = 9;
+pub const LEGACY_INPUT_SIZE: i64 = 148;
+pub const WITNESS_INPUT_VSIZE: i64 = 67;
+pub const TXIN_FIXED_SIZE: i64 = 41;
+pub const MAX_SIGNATURE_LEN: usize = 73;
+pub const COMPRESSED_PUBLIC_KEY_LEN: usize = 33;
+pub const WITNESS_SCALE_FACTOR: i64 = 4;
+pub const MAX_FEE_ITERATIONS: usize = 10;
+
+pub const ADDRESS_LEN: usize = 25;
+pub const WIF_LEN: usize = 37;
+pub const WIF_COMPRESSED_LEN: usize = 38;
+
+pub const BECH32_CHARSET: &str = "qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+pub const BECH32_CONST: u32 = 1;
+pub const BECH32M_CONST: u32 = 0x2bc830a3;
+pub const BECH32_MAX_LEN: usize = 90;
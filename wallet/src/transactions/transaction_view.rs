@@ -3,10 +3,7 @@ use gtk::{
 };
 use std::sync::{Arc, Mutex};
 
-use node::{
-    block_mod::transaction::Transaction, messages::read_from_bytes::encode_hex,
-    wallet_utils::wallet_tx::WalletTx,
-};
+use node::{messages::read_from_bytes::encode_hex, wallet_utils::wallet_tx::WalletTx};
 
 use glib::{clone, Continue, Receiver};
 use gtk::{prelude::GtkListStoreExtManual, GtkListStoreExt, ListStore};
@@ -16,99 +13,10 @@ use crate::{
     views::recent_transaction_view::create_recent_transaction_view,
 };
 
-use super::{create_transactions::pk_script_from_public_key, create_transactios_constants::*};
-
-/// Calculates the received balance for a specific public key script in a transaction.
-///
-/// This function takes a transaction and a public key script as input and calculates
-/// the total received balance for that specific public key script in the transaction.
-/// It iterates through each transaction output (tx_out) in the transaction and checks
-/// if the provided public key script matches the tx_out's script. If there is a match,
-/// the value of the tx_out is added to the balance.
-///
-/// # Arguments
-///
-/// * `transaction` - The transaction to calculate the received balance from.
-/// * `pk_script` - The public key script to match against the transaction outputs.
-///
-/// # Returns
-///
-/// The received balance for the specified public key script in the transaction,
-/// expressed as a floating-point value in BTC.
-pub fn received_balance(transaction: &Transaction, pk_script: &Vec<u8>) -> f64 {
-    let mut balance = 0;
-
-    for tx_out in transaction.get_tx_out_list() {
-        if *pk_script == tx_out.get_pk_script() {
-            balance += tx_out.get_value();
-        }
-    }
-    balance as f64 / BTC_TO_SATOSHI
-}
-
-/// Calculates the sent balance for a specific public key script in a transaction.
-///
-/// This function takes a transaction and a public key script as input and calculates
-/// the total sent balance for that specific public key script in the transaction.
-/// It iterates through each transaction output (tx_out) in the transaction and checks
-/// if the provided public key script does not match the tx_out's script. If there is no
-/// match, indicating that the funds are being sent from the specified script, the value
-/// of the tx_out is added to the balance.
-///
-/// # Arguments
-///
-/// * `transaction` - The transaction to calculate the sent balance from.
-/// * `pk_script` - The public key script to match against the transaction outputs.
-///
-/// # Returns
-///
-/// The sent balance for the specified public key script in the transaction,
-/// expressed as a floating-point value in BTC.
-pub fn send_balance(transaction: &Transaction, pk_script: &Vec<u8>) -> f64 {
-    let mut balance = 0;
-
-    for tx_out in transaction.get_tx_out_list() {
-        if *pk_script != tx_out.get_pk_script() {
-            balance += tx_out.get_value();
-        }
-    }
-
-    balance as f64 / BTC_TO_SATOSHI
-}
-
-/// Calculates the balance of a specific account.
-///
-/// This function takes two vectors of received and sent transactions, along with the public key script
-/// of the account. It iterates over the received transactions and adds up the received balances using the
-/// `received_balance` function. It then iterates over the sent transactions and subtracts the sent balances
-/// using the `send_balance` function. The resulting balance is returned as a floating-point value.
-///
-/// # Arguments
-///
-/// * `recv_transactions` - The vector of received transactions.
-/// * `send_transactions` - The vector of sent transactions.
-/// * `pk_script` - The public key script of the account.
-///
-/// # Returns
-///
-/// Returns the balance of the account as a floating-point value.
-fn get_balance(
-    recv_transactions: &Vec<WalletTx>,
-    send_transactions: &Vec<WalletTx>,
-    pk_script: &Vec<u8>,
-) -> f64 {
-    let mut value = 0.0;
-
-    for transaction in recv_transactions {
-        value += received_balance(transaction.get_tx(), pk_script);
-    }
-
-    for transaction in send_transactions {
-        value -= send_balance(transaction.get_tx(), pk_script);
-    }
-
-    value
-}
+use super::{
+    create_transactios_constants::*,
+    tx_balance::{get_balance, received_balance, send_balance},
+};
 
 /// Updates the transaction list view for sent transactions.
 ///
@@ -124,7 +32,7 @@ fn get_balance(
 /// * `store` - The reference to the `ListStore` where the transaction data is displayed.
 /// * `state` - The current state of the transactions.
 /// * `tx_type` - The type of the transactions.
-/// * `pub_key` - The public key script of the recipient.
+/// * `own_pk_scripts` - The public key scripts of the account.
 ///
 /// # Returns
 ///
@@ -135,7 +43,7 @@ fn update_tx_send(
     store: &ListStore,
     state: &str,
     tx_type: &str,
-    pub_key: &Vec<u8>,
+    own_pk_scripts: &[Vec<u8>],
 ) -> Result<(), InterfaceError> {
     for tx in transactions {
         let mut txn = tx.get_tx().get_id();
@@ -149,7 +57,7 @@ fn update_tx_send(
                 &tx.get_date(),
                 &tx_type,
                 &encode_hex(&txn).map_err(|_| InterfaceError::DecodeHex)?,
-                &format!("-{}", send_balance(tx.get_tx(), pub_key)),
+                &format!("-{}", send_balance(tx.get_tx(), own_pk_scripts)),
             ],
         );
     }
@@ -170,7 +78,7 @@ fn update_tx_send(
 /// * `store` - The reference to the `ListStore` where the transaction data is displayed.
 /// * `state` - The current state of the transactions.
 /// * `tx_type` - The type of the transactions.
-/// * `pk_script` - The public key script of the recipient.
+/// * `own_pk_scripts` - The public key scripts of the account.
 ///
 /// # Returns
 ///
@@ -181,7 +89,7 @@ fn update_tx_recv(
     store: &ListStore,
     state: &str,
     tx_type: &str,
-    pk_script: &Vec<u8>,
+    own_pk_scripts: &[Vec<u8>],
 ) -> Result<(), InterfaceError> {
     for tx in transactions {
         let mut txn = tx.get_tx().get_id();
@@ -195,7 +103,7 @@ fn update_tx_recv(
                 &tx.get_date(),
                 &tx_type,
                 &encode_hex(&txn).map_err(|_| InterfaceError::DecodeHex)?,
-                &format!("{}", received_balance(tx.get_tx(), pk_script)),
+                &format!("{}", received_balance(tx.get_tx(), own_pk_scripts)),
             ],
         );
     }
@@ -206,7 +114,7 @@ fn update_tx_recv(
 ///
 /// This function takes a `ListStore` where the transaction data is displayed and a shared reference
 /// to the `Accounts` structure. It clears the list store and retrieves the actual account from the
-/// locked accounts. It obtains the public key and generates the public key script. It then calls the
+/// locked accounts. It obtains the public key scripts of all its addresses. It then calls the
 /// `update_tx_send` and `update_tx_recv` functions to update the transaction list view for confirmed
 /// and unconfirmed sent and received transactions. Finally, it drops the lock on the accounts and
 /// returns `Ok(())` if the update is successful.
@@ -230,37 +138,36 @@ pub fn update_transactions(
     let actual_account = locked_accounts
         .get_actual_account()
         .ok_or(InterfaceError::LockAccounts)?;
-    let pub_key = actual_account.get_public_key();
-    let pk_script = pk_script_from_public_key(&pub_key);
+    let own_pk_scripts = actual_account.get_pk_scripts();
 
     update_tx_send(
-        actual_account.get_confirmed_txs_send(),
+        &actual_account.get_confirmed_txs_send(),
         &store,
         CONFIRMED,
         SENT,
-        &pk_script,
+        &own_pk_scripts,
     )?;
     update_tx_recv(
-        actual_account.get_confirmed_txs_recv(),
+        &actual_account.get_confirmed_txs_recv(),
         &store,
         CONFIRMED,
         RECEIVED,
-        &pk_script,
+        &own_pk_scripts,
     )?;
 
     update_tx_send(
-        actual_account.get_unconfirmed_txs_send(),
+        &actual_account.get_unconfirmed_txs_send(),
         &store,
         UNCONFIRMED,
         SENT,
-        &pk_script,
+        &own_pk_scripts,
     )?;
     update_tx_recv(
-        actual_account.get_unconfirmed_txs_recv(),
+        &actual_account.get_unconfirmed_txs_recv(),
         &store,
         UNCONFIRMED,
         RECEIVED,
-        &pk_script,
+        &own_pk_scripts,
     )?;
 
     drop(locked_accounts);
@@ -297,16 +204,16 @@ fn update_balance_labels(
     let mut available_value = 0.0;
     let mut pending_value = 0.0;
     if let Some(user) = locked_accounts.get_actual_account() {
-        let pk_script = user.get_pk_script();
+        let own_pk_scripts = user.get_pk_scripts();
         available_value = get_balance(
-            user.get_confirmed_txs_recv(),
-            user.get_confirmed_txs_send(),
-            &pk_script,
+            &user.get_confirmed_txs_recv(),
+            &user.get_confirmed_txs_send(),
+            &own_pk_scripts,
         );
         pending_value = get_balance(
-            user.get_unconfirmed_txs_recv(),
-            user.get_unconfirmed_txs_send(),
-            &pk_script,
+            &user.get_unconfirmed_txs_recv(),
+            &user.get_unconfirmed_txs_send(),
+            &own_pk_scripts,
         );
     }
     let total_value = available_value + pending_value;
@@ -357,7 +264,7 @@ pub fn update_recent_transactions(
     let actual_account = locked_accounts
         .get_actual_account()
         .ok_or(InterfaceError::UpdateRecentTransactions)?;
-    let pk_script = actual_account.get_pk_script();
+    let own_pk_scripts = actual_account.get_pk_scripts();
 
     let confirmed_txs_send = actual_account.get_confirmed_txs_send();
     let confirmed_txs_recv = actual_account.get_confirmed_txs_recv();
@@ -365,10 +272,10 @@ pub fn update_recent_transactions(
     let unconfirmed_txs_recv = actual_account.get_unconfirmed_txs_recv();
 
     let recent_transaction = filter_recent_transactions(
-        confirmed_txs_send,
-        confirmed_txs_recv,
-        unconfirmed_txs_send,
-        unconfirmed_txs_recv,
+        &confirmed_txs_send,
+        &confirmed_txs_recv,
+        &unconfirmed_txs_send,
+        &unconfirmed_txs_recv,
     );
 
     recent_transaction_box.foreach(|widget| {
@@ -391,9 +298,9 @@ pub fn update_recent_transactions(
         };
 
         let amount = if tx_type == RECEIVED {
-            received_balance(tx.get_tx(), &pk_script)
+            received_balance(tx.get_tx(), &own_pk_scripts)
         } else {
-            send_balance(tx.get_tx(), &pk_script)
+            send_balance(tx.get_tx(), &own_pk_scripts)
         };
 
         let mut txn = tx.get_tx().get_id();
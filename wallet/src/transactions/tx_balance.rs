@@ -0,0 +1,135 @@
+use node::{block_mod::transaction::Transaction, wallet_utils::wallet_tx::WalletTx};
+
+use super::create_transactios_constants::BTC_TO_SATOSHI;
+
+/// Calculates the satoshis a transaction pays to the scripts of an account.
+///
+/// # Arguments
+///
+/// * `transaction` - The transaction to calculate the received value from.
+/// * `own_pk_scripts` - The P2PKH and P2WPKH scripts of every address of the account.
+///
+/// # Returns
+///
+/// The sum of the values of the outputs whose script is one of the provided scripts.
+pub fn received_value(transaction: &Transaction, own_pk_scripts: &[Vec<u8>]) -> i64 {
+    transaction
+        .get_tx_out_list()
+        .iter()
+        .filter(|tx_out| own_pk_scripts.contains(&tx_out.get_pk_script()))
+        .map(|tx_out| tx_out.get_value())
+        .sum()
+}
+
+/// Calculates the satoshis a transaction sends out of an account.
+///
+/// # Arguments
+///
+/// * `transaction` - The transaction to calculate the sent value from.
+/// * `own_pk_scripts` - The P2PKH and P2WPKH scripts of every address of the account.
+///
+/// # Returns
+///
+/// The sum of the values of the outputs whose script is none of the provided scripts. Change sent to any address of the
+/// account isn't counted.
+pub fn sent_value(transaction: &Transaction, own_pk_scripts: &[Vec<u8>]) -> i64 {
+    transaction
+        .get_tx_out_list()
+        .iter()
+        .filter(|tx_out| !own_pk_scripts.contains(&tx_out.get_pk_script()))
+        .map(|tx_out| tx_out.get_value())
+        .sum()
+}
+
+/// Calculates the received balance for the scripts of an account in a transaction, see `received_value`.
+///
+/// # Returns
+///
+/// The received balance for the account in the transaction, expressed as a floating-point value in BTC.
+pub fn received_balance(transaction: &Transaction, own_pk_scripts: &[Vec<u8>]) -> f64 {
+    received_value(transaction, own_pk_scripts) as f64 / BTC_TO_SATOSHI
+}
+
+/// Calculates the sent balance for the scripts of an account in a transaction, see `sent_value`.
+///
+/// # Returns
+///
+/// The sent balance for the account in the transaction, expressed as a floating-point value in BTC.
+pub fn send_balance(transaction: &Transaction, own_pk_scripts: &[Vec<u8>]) -> f64 {
+    sent_value(transaction, own_pk_scripts) as f64 / BTC_TO_SATOSHI
+}
+
+/// Calculates the satoshis an account gained with some transactions.
+///
+/// # Arguments
+///
+/// * `recv_transactions` - The received transactions.
+/// * `send_transactions` - The sent transactions.
+/// * `own_pk_scripts` - The public key scripts of the account.
+///
+/// # Returns
+///
+/// The value received by the account minus the value it sent, which is negative if it sent more.
+pub fn get_value(
+    recv_transactions: &[WalletTx],
+    send_transactions: &[WalletTx],
+    own_pk_scripts: &[Vec<u8>],
+) -> i64 {
+    let received: i64 = recv_transactions
+        .iter()
+        .map(|transaction| received_value(transaction.get_tx(), own_pk_scripts))
+        .sum();
+    let sent: i64 = send_transactions
+        .iter()
+        .map(|transaction| sent_value(transaction.get_tx(), own_pk_scripts))
+        .sum();
+    received - sent
+}
+
+/// Calculates the balance of an account, see `get_value`.
+///
+/// # Returns
+///
+/// Returns the balance of the account in BTC, as a floating-point value.
+pub fn get_balance(
+    recv_transactions: &[WalletTx],
+    send_transactions: &[WalletTx],
+    own_pk_scripts: &[Vec<u8>],
+) -> f64 {
+    get_value(recv_transactions, send_transactions, own_pk_scripts) as f64 / BTC_TO_SATOSHI
+}
+
+#[cfg(test)]
+mod tx_balance_test {
+    use node::block_mod::{transaction::Transaction, tx_in::TxIn, tx_out::TxOut};
+
+    use super::*;
+
+    #[test]
+    fn test_change_is_not_sent() {
+        let own_pk_scripts = vec![vec![1; 22], vec![2; 25]];
+        let transaction = Transaction::new(
+            1,
+            vec![TxIn::new(vec![3; 32], 0, vec![], 0xffffffff)],
+            vec![
+                TxOut::new(60000, vec![4; 22]),
+                TxOut::new(30000, vec![1; 22]),
+                TxOut::new(10000, vec![2; 25]),
+            ],
+            0,
+        );
+        let wallet_tx = WalletTx::new(transaction.clone(), vec![], String::new());
+
+        assert_eq!(received_value(&transaction, &own_pk_scripts), 40000);
+        assert_eq!(sent_value(&transaction, &own_pk_scripts), 60000);
+        assert_eq!(
+            get_value(
+                std::slice::from_ref(&wallet_tx),
+                std::slice::from_ref(&wallet_tx),
+                &own_pk_scripts
+            ),
+            -20000
+        );
+        assert_eq!(send_balance(&transaction, &own_pk_scripts), 0.0006);
+    }
+}
@@ -0,0 +1,3 @@
+pub mod tx_cache_constants;
+pub mod tx_cache_error;
+pub mod tx_cache_file;
@@ -0,0 +1,4 @@
+pub const TX_CACHE_MAGIC: &[u8; 4] = b"RTXC";
+pub const TX_CACHE_VERSION: u8 = 3;
+pub const MAINNET_TX_CACHE_PATH: &str = "mainnet_transactions.cache";
+pub const TESTNET_TX_CACHE_PATH: &str = "testnet_transactions.cache";
@@ -0,0 +1,36 @@
+use node::messages::message_error::MessageError;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum TxCacheError {
+    Io,
+    InvalidFormat,
+    UnsupportedVersion,
+    AccountMismatch,
+}
+
+impl From<std::io::Error> for TxCacheError {
+    fn from(_: std::io::Error) -> TxCacheError {
+        TxCacheError::Io
+    }
+}
+
+impl From<MessageError> for TxCacheError {
+    fn from(_: MessageError) -> TxCacheError {
+        TxCacheError::InvalidFormat
+    }
+}
+
+impl std::fmt::Display for TxCacheError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            TxCacheError::Io => write!(f, "the transaction cache can't be read or written"),
+            TxCacheError::InvalidFormat => write!(f, "the transaction cache is corrupted"),
+            TxCacheError::UnsupportedVersion => {
+                write!(f, "the transaction cache was created by a newer version")
+            }
+            TxCacheError::AccountMismatch => {
+                write!(f, "the cached transactions belong to other keys")
+            }
+        }
+    }
+}
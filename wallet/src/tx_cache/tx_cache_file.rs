@@ -0,0 +1,145 @@
+use std::{
+    collections::HashMap,
+    fs,
+    io::{ErrorKind, Read},
+    path::{Path, PathBuf},
+};
+
+use node::messages::read_from_bytes::{
+    read_u32_from_bytes, read_u8_from_bytes, read_vec_from_bytes,
+};
+
+use super::{
+    tx_cache_constants::{TX_CACHE_MAGIC, TX_CACHE_VERSION},
+    tx_cache_error::TxCacheError,
+};
+
+/// Represents the file where the history of each account is saved, so the wallet doesn't ask the node to scan the whole
+/// chain again every time it starts.
+///
+/// The history of an account holds its addresses with their transactions, UTXOs and the last block they were synced to.
+/// It is kept serialized until the account is loaded, which for the accounts of the keystore happens once it is unlocked.
+/// Only public data is saved, so the file isn't encrypted.
+#[derive(Debug)]
+pub struct TxCache {
+    path: PathBuf,
+    histories: HashMap<String, Vec<u8>>,
+}
+
+impl TxCache {
+    pub fn new(path: &Path) -> TxCache {
+        TxCache {
+            path: path.to_path_buf(),
+            histories: HashMap::new(),
+        }
+    }
+
+    /// Reads the transaction cache file.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the file.
+    ///
+    /// # Returns
+    ///
+    /// A Result containing the cache, which is empty if the file doesn't exist yet, or a `TxCacheError` if it can't be read.
+    pub fn open(path: &Path) -> Result<TxCache, TxCacheError> {
+        let mut tx_cache = TxCache::new(path);
+        let file = match fs::read(path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(tx_cache),
+            Err(err) => return Err(err.into()),
+        };
+        let mut stream = &file[..];
+
+        if read_vec_from_bytes(&mut stream, TX_CACHE_MAGIC.len())? != TX_CACHE_MAGIC {
+            return Err(TxCacheError::InvalidFormat);
+        }
+        if read_u8_from_bytes(&mut stream)? != TX_CACHE_VERSION {
+            return Err(TxCacheError::UnsupportedVersion);
+        }
+        let count = read_u32_from_bytes(&mut stream, true)?;
+        for _ in 0..count {
+            let (name, history) = read_history(&mut stream)?;
+            tx_cache.histories.insert(name, history);
+        }
+        Ok(tx_cache)
+    }
+
+    /// Returns the serialized history of an account, if it was saved.
+    pub fn get_history(&self, name: &str) -> Option<&Vec<u8>> {
+        self.histories.get(name)
+    }
+
+    /// Replaces the serialized history of an account, which is written on the next call to `save`.
+    pub fn set_history(&mut self, name: String, history: Vec<u8>) {
+        self.histories.insert(name, history);
+    }
+
+    /// Writes the histories of all the accounts to the file.
+    ///
+    /// # Description
+    ///
+    /// The file is first written to a temporary file and then renamed, so a failure while writing doesn't leave it
+    /// corrupted.
+    pub fn save(&self) -> Result<(), TxCacheError> {
+        let mut names: Vec<&String> = self.histories.keys().collect();
+        names.sort();
+
+        let mut bytes = TX_CACHE_MAGIC.to_vec();
+        bytes.push(TX_CACHE_VERSION);
+        bytes.extend((names.len() as u32).to_le_bytes());
+        for name in names {
+            let history = &self.histories[name];
+            bytes.push(name.len() as u8);
+            bytes.extend(name.as_bytes());
+            bytes.extend((history.len() as u32).to_le_bytes());
+            bytes.extend(history);
+        }
+
+        let temporary_path = self.path.with_extension("tmp");
+        fs::write(&temporary_path, bytes)?;
+        fs::rename(&temporary_path, &self.path)?;
+        Ok(())
+    }
+}
+
+/// Reads the name of an account and its serialized history, preceded by their lengths.
+fn read_history(stream: &mut dyn Read) -> Result<(String, Vec<u8>), TxCacheError> {
+    let name_len = read_u8_from_bytes(stream)?;
+    let name = String::from_utf8(read_vec_from_bytes(stream, name_len as usize)?)
+        .map_err(|_| TxCacheError::InvalidFormat)?;
+    let history_len = read_u32_from_bytes(stream, true)?;
+    Ok((name, read_vec_from_bytes(stream, history_len as usize)?))
+}
+
+#[cfg(test)]
+mod tx_cache_file_test {
+    use std::env;
+
+    use super::*;
+
+    #[test]
+    fn test_save_and_open() -> Result<(), TxCacheError> {
+        let path = env::temp_dir().join(format!("tx_cache_{}.cache", std::process::id()));
+        let _ = fs::remove_file(&path);
+        assert!(TxCache::open(&path)?.get_history("first").is_none());
+
+        let mut tx_cache = TxCache::new(&path);
+        tx_cache.set_history("first".to_string(), vec![1, 2, 3]);
+        tx_cache.set_history("second".to_string(), vec![]);
+        tx_cache.save()?;
+
+        let opened = TxCache::open(&path)?;
+        assert_eq!(opened.get_history("first"), Some(&vec![1, 2, 3]));
+        assert_eq!(opened.get_history("second"), Some(&vec![]));
+
+        fs::write(&path, b"XXXX")?;
+        assert_eq!(
+            TxCache::open(&path).map(|_| ()),
+            Err(TxCacheError::InvalidFormat)
+        );
+        fs::remove_file(&path)?;
+        Ok(())
+    }
+}
@@ -1,26 +1,38 @@
-use std::{
-    io::Write,
-    net::TcpStream,
-    sync::{Arc, Mutex},
-};
+use std::sync::{Arc, Mutex};
 
 use glib::{clone, Continue};
-use gtk::{prelude::BuilderExtManual, Builder, ProgressBar, ProgressBarExt, WidgetExt, Window};
-use node::wallet_utils::{progress::Progress, wallet_utils_constants::GET_PROGRESS_COMMAND};
+use gtk::{
+    prelude::BuilderExtManual, Builder, Entry, Label, ProgressBar, ProgressBarExt, WidgetExt,
+    Window,
+};
+use node::{
+    messages::read_from_bytes::fill_command,
+    wallet_utils::{
+        progress::Progress,
+        wallet_utils_constants::{GET_PROGRESS_COMMAND, PROGRESS_COMMAND},
+    },
+};
 
-use crate::{handlers::handler_constants::*, interface_error::InterfaceError};
+use crate::{
+    accounts::Accounts, handlers::handler_constants::*, interface_error::InterfaceError,
+    node_connection::NodeConnection,
+};
 
 pub fn update_progress(
-    node: &Arc<Mutex<TcpStream>>,
+    node: &Arc<Mutex<NodeConnection>>,
     progress_sender: glib::Sender<f64>,
 ) -> Result<(), InterfaceError> {
     loop {
         let mut locked_node = node.lock().map_err(|_| InterfaceError::LockNode)?;
-        locked_node
-            .write_all(GET_PROGRESS_COMMAND.as_bytes())
-            .map_err(|_| InterfaceError::Write)?;
+        let response = locked_node.request(fill_command(GET_PROGRESS_COMMAND).as_bytes())?;
+        drop(locked_node);
+        if response.get_command_name() != PROGRESS_COMMAND {
+            return Err(InterfaceError::InvalidResponse);
+        }
 
-        let progress = Progress::from_bytes(&mut *locked_node).map_err(|_| InterfaceError::Read)?;
+        let progress = response
+            .read_payload(Progress::from_bytes)
+            .map_err(|_| InterfaceError::Read)?;
 
         if progress_sender.send(progress.get_progress()).is_err() {
             return Err(InterfaceError::Send);
@@ -34,8 +46,21 @@ pub fn update_progress(
     Ok(())
 }
 
+/// Shows the progress of the block download and, once it finishes, the window to enter the wallet.
+///
+/// # Arguments
+///
+/// * `builder` - The builder object containing the user interface elements.
+/// * `accounts` - The shared `Accounts` object.
+/// * `progress_recv` - The receiver of the download progress.
+///
+/// # Description
+///
+/// When the download finishes, the unlock window is shown if there is a keystore with saved accounts. Otherwise the login
+/// window is shown, with the entry of the password that will encrypt the new keystore.
 pub fn update_progress_bar_view(
     builder: &Builder,
+    accounts: Arc<Mutex<Accounts>>,
     progress_recv: glib::Receiver<f64>,
 ) -> Result<(), InterfaceError> {
     let progress_bar: ProgressBar = builder
@@ -47,6 +72,15 @@ pub fn update_progress_bar_view(
     let loading_window: Window = builder
         .get_object(LOADING_WINDOW)
         .ok_or(InterfaceError::MissingWindow)?;
+    let unlock_window: Window = builder
+        .get_object(UNLOCK_WINDOW)
+        .ok_or(InterfaceError::MissingWindow)?;
+    let keystore_passphrase_label: Label = builder
+        .get_object(KEYSTORE_PASSPHRASE_LABEL)
+        .ok_or(InterfaceError::MissingLabel)?;
+    let keystore_passphrase_entry: Entry = builder
+        .get_object(KEYSTORE_PASSPHRASE_ENTRY)
+        .ok_or(InterfaceError::MissingEntry)?;
 
     progress_recv.attach(
         None,
@@ -55,7 +89,17 @@ pub fn update_progress_bar_view(
                 progress_bar.set_fraction(progress);
                 if progress == 1.0 {
                     loading_window.hide();
-                    login_window.show();
+                    let has_keystore = accounts
+                        .lock()
+                        .map(|locked_accounts| locked_accounts.has_keystore())
+                        .unwrap_or(false);
+                    if has_keystore {
+                        unlock_window.show_all();
+                    } else {
+                        keystore_passphrase_label.show();
+                        keystore_passphrase_entry.show();
+                        login_window.show();
+                    }
                 }
                 Continue(true)
             }
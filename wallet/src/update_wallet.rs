@@ -1,93 +1,228 @@
-use crate::{
-    accounts::Accounts, interface_error::InterfaceError,
-    transactions::create_transactions::pk_script_from_public_key,
+#[cfg(feature = "gui")]
+use crate::wallet_constants::{EVENT_POLL_INTERVAL, FALLBACK_SYNC_INTERVAL};
+use crate::{accounts::Accounts, interface_error::InterfaceError, node_connection::NodeConnection};
+#[cfg(feature = "gui")]
+use node::wallet_utils::wallet_utils_constants::{
+    BLOCK_NOTIFICATION_COMMAND, TX_NOTIFICATION_COMMAND,
 };
-use node::{
-    messages::read_from_bytes::{fill_command, read_string_from_bytes},
-    wallet_utils::{
-        get_transactions::GetTransactions,
-        transactions::Transactions,
-        wallet_utils_constants::{EXIT_COMMAND, TRANSACTIONS_COMMAND},
-    },
+use node::wallet_utils::{
+    get_transactions::GetTransactions, transactions::Transactions,
+    wallet_utils_constants::TRANSACTIONS_COMMAND,
 };
+use std::sync::Mutex;
+#[cfg(feature = "gui")]
 use std::{
-    io::Write,
-    net::TcpStream,
-    sync::{mpsc::Receiver, Arc, Mutex},
+    sync::{mpsc::Receiver, Arc},
     thread,
-    time::Duration,
+    time::{Duration, Instant},
 };
 
-/// Updates the wallet by retrieving and processing transactions from the node.
-///
-/// This function continuously loops and updates the wallet by retrieving and processing
-/// transactions from the node. It takes the shared `Accounts` object, the shared `TcpStream`
-/// representing the connection to the node, and a sender for transaction update signals as input.
-/// Within the loop, it locks the `Accounts` object to access the current user's information. If
-/// there is an actual account, it retrieves the necessary information, such as the public key and
-/// the last update timestamp. It then locks the `TcpStream` to communicate with the node and
-/// requests transactions using the `GetTransactions` command. The retrieved transactions are
-/// processed and updated in the user's account. Finally, a transaction update signal is sent using
-/// the provided sender. The loop continues to execute after a brief sleep of 10 seconds.
+/// Requests to the node the transactions of one address of the wallet.
+///
+/// # Arguments
+///
+/// * `node` - The connection to the node.
+/// * `pk_script` - The script of the address.
+/// * `public_key` - The public key of the address, empty if it is locked to a script such as a multisig one.
+/// * `last_block_hash` - The hash of the last block the address was synced to, all zeros to scan the whole chain.
+///
+/// # Returns
+///
+/// The `Transactions` answered by the node, or an `InterfaceError` if the communication fails.
+fn request_transactions(
+    node: &mut NodeConnection,
+    pk_script: &[u8],
+    public_key: &[u8],
+    last_block_hash: Vec<u8>,
+) -> Result<Transactions, InterfaceError> {
+    let get_transactions =
+        GetTransactions::new(pk_script.to_vec(), public_key.to_vec(), last_block_hash);
+    let response = node.request(&get_transactions.as_bytes())?;
+
+    if response.get_command_name() != TRANSACTIONS_COMMAND {
+        return Err(InterfaceError::InvalidResponse);
+    }
+
+    response
+        .read_payload(Transactions::from_bytes)
+        .map_err(|_| InterfaceError::Read)
+}
+
+/// Returns the script, the public key and the last block synced of the addresses of the actual account from a position
+/// on, to request their transactions without holding the lock of the accounts.
+fn get_addresses_to_sync(accounts: &Accounts, from: usize) -> Vec<(Vec<u8>, Vec<u8>, Vec<u8>)> {
+    accounts
+        .get_actual_account()
+        .map(|user_info| {
+            user_info
+                .get_addresses()
+                .iter()
+                .skip(from)
+                .map(|address| {
+                    (
+                        address.get_pk_script(),
+                        address.get_public_key(),
+                        address.get_last_block_hash(),
+                    )
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Syncs every address of the actual account with the node.
+///
+/// # Arguments
+///
+/// * `accounts` - The shared accounts of the wallet, locked only to take the addresses and to apply the answers.
+/// * `node` - The shared connection to the node, locked only while each request is answered.
+///
+/// # Returns
+///
+/// The number of addresses synced, zero if there is no actual account, or an `InterfaceError` if the communication
+/// with the node fails.
+///
+/// # Description
+///
+/// The addresses are copied and the transactions of each one since the last block it was synced to are requested with
+/// `GetTransactions` without holding the lock of the accounts, so the interface isn't blocked during the sync. The
+/// answers are then given to the account, which derives new addresses when a used one shrinks the gap limit. Those are
+/// scanned in another pass, so a restored seed discovers all its used addresses. If another account is chosen in the
+/// meantime, the answers are discarded and the sync stops.
+pub fn sync_actual_account(
+    accounts: &Mutex<Accounts>,
+    node: &Mutex<NodeConnection>,
+) -> Result<usize, InterfaceError> {
+    let mut index = 0;
+    loop {
+        let (username, addresses) = {
+            let locked_accounts = accounts.lock().map_err(|_| InterfaceError::LockAccounts)?;
+            (
+                locked_accounts.get_actual_username().clone(),
+                get_addresses_to_sync(&locked_accounts, index),
+            )
+        };
+        if addresses.is_empty() {
+            return Ok(index);
+        }
+
+        let mut answers = vec![];
+        for (pk_script, public_key, last_block_hash) in addresses {
+            let mut locked_node = node.lock().map_err(|_| InterfaceError::LockNode)?;
+            let transactions =
+                request_transactions(&mut locked_node, &pk_script, &public_key, last_block_hash)?;
+            drop(locked_node);
+            answers.push((pk_script, transactions));
+        }
+
+        let mut locked_accounts = accounts.lock().map_err(|_| InterfaceError::LockAccounts)?;
+        if *locked_accounts.get_actual_username() != username {
+            return Ok(index);
+        }
+        for (pk_script, transactions) in answers {
+            locked_accounts.update(&pk_script, &transactions);
+            index += 1;
+        }
+    }
+}
+
+/// Returns the script and the public key of each address of the actual account, the addresses the wallet subscribes to.
+#[cfg(feature = "gui")]
+fn get_subscribed_addresses(accounts: &Accounts) -> Vec<(Vec<u8>, Vec<u8>)> {
+    accounts
+        .get_actual_account()
+        .map(|user_info| {
+            user_info
+                .get_addresses()
+                .iter()
+                .map(|address| (address.get_pk_script(), address.get_public_key()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Takes the notifications pushed by the node, returning whether any of them asks for a sync.
+#[cfg(feature = "gui")]
+fn take_events(node: &Mutex<NodeConnection>) -> Result<bool, InterfaceError> {
+    let mut locked_node = node.lock().map_err(|_| InterfaceError::LockNode)?;
+    let mut notified = false;
+    while let Some(event) = locked_node.next_event()? {
+        notified |= matches!(
+            event.get_command_name(),
+            TX_NOTIFICATION_COMMAND | BLOCK_NOTIFICATION_COMMAND
+        );
+    }
+    Ok(notified)
+}
+
+/// Updates the wallet with the transactions of its addresses, as the node notifies them.
 ///
 /// # Arguments
 ///
 /// * `accounts` - The shared `Accounts` object.
-/// * `node` - The shared `TcpStream` representing the connection to the node.
+/// * `node` - The shared connection to the node.
 /// * `txs_sender` - The sender for transaction update signals.
+/// * `exit_recv` - The receiver of the signal to disconnect from the node and stop.
 ///
 /// # Returns
 ///
-/// Returns `Ok(())` if the wallet is successfully updated, or an `InterfaceError` if there is an
-/// error while retrieving transactions, processing them, or sending the transaction update signal.
+/// Returns `Ok(())` once the wallet is told to exit, or an `InterfaceError` if there is an error while retrieving
+/// transactions, processing them, or sending the transaction update signal.
+///
+/// # Description
+///
+/// The wallet subscribes to the addresses of the actual account, and subscribes again whenever they change, such as
+/// when another account is chosen or new addresses are derived. The node pushes a notification when a block is
+/// connected or a transaction of those addresses enters its mempool, and only then the actual account is synced with
+/// `sync_actual_account`, its history saved to the transaction cache and a transaction update signal sent. The account
+/// is also synced every `FALLBACK_SYNC_INTERVAL` seconds, which keeps the connection alive while nothing happens.
+#[cfg(feature = "gui")]
 pub fn update_wallet(
     accounts: Arc<Mutex<Accounts>>,
-    node: Arc<Mutex<TcpStream>>,
+    node: Arc<Mutex<NodeConnection>>,
     txs_sender: glib::Sender<bool>,
     exit_recv: Receiver<bool>,
 ) -> Result<(), InterfaceError> {
+    let mut subscribed_addresses = vec![];
+    let mut last_sync: Option<Instant> = None;
     loop {
         if exit_recv.try_recv().is_ok() {
             if let Ok(mut locked_node) = node.lock() {
-                if locked_node
-                    .write_all(fill_command(EXIT_COMMAND).as_bytes())
-                    .is_err()
-                {
+                if locked_node.disconnect().is_err() {
                     println!("Exit error");
                 };
             }
             return Ok(());
         }
 
-        let mut locked_accounts = accounts.lock().map_err(|_| InterfaceError::LockAccounts)?;
+        let notified = take_events(&node)?;
+        let fallback = last_sync
+            .is_none_or(|instant| instant.elapsed() >= Duration::from_secs(FALLBACK_SYNC_INTERVAL));
 
-        if let Some(user_info) = locked_accounts.get_actual_account() {
-            let mut locked_node = node.lock().map_err(|_| InterfaceError::LockNode)?;
-            let pk_script = pk_script_from_public_key(&user_info.get_public_key());
-            let get_transactions = GetTransactions::new(
-                pk_script,
-                user_info.get_public_key(),
-                user_info.get_last_update(),
-            );
-            locked_node
-                .write_all(&get_transactions.as_bytes())
-                .map_err(|_| InterfaceError::Write)?;
-            let command_name =
-                read_string_from_bytes(&mut *locked_node, 12).map_err(|_| InterfaceError::Read)?;
+        if notified || fallback {
+            let index = sync_actual_account(&accounts, &node)?;
+            last_sync = Some(Instant::now());
 
-            if command_name != TRANSACTIONS_COMMAND {
-                return Err(InterfaceError::InvalidResponse);
+            if index > 0 {
+                let mut locked_accounts =
+                    accounts.lock().map_err(|_| InterfaceError::LockAccounts)?;
+                if let Err(err) = locked_accounts.save_history() {
+                    println!("{}", err);
+                }
+                drop(locked_accounts);
+                txs_sender.send(true).map_err(|_| InterfaceError::Send)?;
             }
-
-            let transactions =
-                Transactions::from_bytes(&mut *locked_node).map_err(|_| InterfaceError::Read)?;
-            drop(locked_node);
-
-            locked_accounts.update(&transactions);
-
-            txs_sender.send(true).map_err(|_| InterfaceError::Send)?;
         }
+
+        let locked_accounts = accounts.lock().map_err(|_| InterfaceError::LockAccounts)?;
+        let addresses = get_subscribed_addresses(&locked_accounts);
         drop(locked_accounts);
-        thread::sleep(Duration::from_secs(5));
+        if addresses != subscribed_addresses {
+            node.lock()
+                .map_err(|_| InterfaceError::LockNode)?
+                .subscribe(addresses.clone())?;
+            subscribed_addresses = addresses;
+        }
+        thread::sleep(Duration::from_secs(EVENT_POLL_INTERVAL));
     }
 }
@@ -1,124 +1,1104 @@
+use std::io::Read;
+
 use node::{
-    block_mod::tx_out::TxOut,
+    block_mod::{transaction::Transaction, tx_out::TxOut},
+    messages::read_from_bytes::read_u32_from_bytes,
+    network::bitcoin_network::BitcoinNetwork,
     wallet_utils::{transactions::Transactions, wallet_tx::WalletTx},
 };
 
-use crate::transactions::create_transactions::pk_script_from_public_key;
+use secp256k1::SecretKey;
+use zeroize::Zeroizing;
+
+use crate::{
+    hd_wallet::{
+        bip32::{ExtendedPrivateKey, ExtendedPublicKey},
+        derivation_path::{AddressPurpose, DerivationPath},
+        hd_wallet_constants::{CHANGE_CHAIN, GAP_LIMIT, RECEIVE_CHAIN},
+        hd_wallet_error::HdWalletError,
+    },
+    keystore::keystore_entry::{AccountSecret, WatchedKey},
+    multisig::multisig_script::{MultisigKind, MultisigScript},
+    transactions::{
+        address::Address,
+        coin_selection::coin_selector::CoinSelector,
+        create_transaction_error::TransactionCreateError,
+        create_transactions::{
+            create_unsigned_transaction, sign_multisig_transaction, sign_transaction,
+            witness_pk_script_from_public_key,
+        },
+    },
+    tx_cache::tx_cache_error::TxCacheError,
+    wallet_address::WalletAddress,
+};
 
 #[derive(Debug)]
 /// Represents the information related to a user's wallet.
+///
+/// An account is either a single imported key, a BIP32 account derived from a seed that tracks a chain of receive
+/// addresses and a chain of change addresses, always keeping `GAP_LIMIT` unused addresses at the end of each one, or an
+/// m-of-n multisig account whose only address is locked to its script. A watch-only account is any of them built from
+/// public data only, so it shows the balance and history but can't sign.
+///
+/// The secret of the account is only kept while the wallet is unlocked. The addresses, and the public key of the account
+/// from which new ones are derived, remain so the wallet keeps syncing while locked.
 pub struct UserInfo {
-    public_key: Vec<u8>,
-    private_key: Vec<u8>,
-    confirmed_txs_send: Vec<WalletTx>,
-    confirmed_txs_recv: Vec<WalletTx>,
-    unconfirmed_txs_send: Vec<WalletTx>,
-    unconfirmed_txs_recv: Vec<WalletTx>,
-    utxo: Vec<(Vec<u8>, u32, TxOut)>,
-    last_update_time: u32,
+    secret: Option<AccountSecret>,
+    account_public_key: Option<ExtendedPublicKey>,
+    purpose: AddressPurpose,
+    multisig: Option<(MultisigScript, MultisigKind)>,
+    receive_addresses: Vec<WalletAddress>,
+    change_addresses: Vec<WalletAddress>,
 }
 
 impl UserInfo {
+    /// Creates an account for a single imported key pair.
     pub fn new(public_key: Vec<u8>, private_key: Vec<u8>) -> UserInfo {
         UserInfo {
-            public_key,
-            private_key,
-            utxo: vec![],
-            confirmed_txs_send: vec![],
-            confirmed_txs_recv: vec![],
-            unconfirmed_txs_send: vec![],
-            unconfirmed_txs_recv: vec![],
-            last_update_time: 0,
+            secret: Some(AccountSecret::Key {
+                public_key: public_key.clone(),
+                private_key: Zeroizing::new(private_key),
+            }),
+            account_public_key: None,
+            purpose: AddressPurpose::Bip44,
+            multisig: None,
+            receive_addresses: vec![WalletAddress::new(public_key)],
+            change_addresses: vec![],
         }
     }
 
-    pub fn get_public_key(&self) -> Vec<u8> {
-        self.public_key.clone()
+    /// Creates an m-of-n multisig account.
+    ///
+    /// # Arguments
+    ///
+    /// * `multisig` - The script with the public keys of every cosigner.
+    /// * `kind` - Whether the account receives to the P2SH or the P2WSH address of the script.
+    /// * `private_keys` - The keys of the script held by this wallet, which may be none of them.
+    ///
+    /// # Returns
+    ///
+    /// The account, whose single address is used both to receive and for the change.
+    pub fn from_multisig(
+        multisig: MultisigScript,
+        kind: MultisigKind,
+        private_keys: Vec<Vec<u8>>,
+    ) -> UserInfo {
+        let pk_script = kind.get_pk_script(&multisig.as_bytes());
+        UserInfo {
+            secret: Some(AccountSecret::Multisig {
+                multisig: multisig.clone(),
+                kind,
+                private_keys: Zeroizing::new(private_keys),
+            }),
+            account_public_key: None,
+            purpose: AddressPurpose::Bip44,
+            multisig: Some((multisig, kind)),
+            receive_addresses: vec![WalletAddress::from_pk_script(pk_script)],
+            change_addresses: vec![],
+        }
+    }
+
+    /// Creates an HD account from a BIP39 seed.
+    ///
+    /// # Arguments
+    ///
+    /// * `seed` - The seed of the wallet.
+    /// * `purpose` - Whether the account follows BIP44 (P2PKH addresses) or BIP84 (P2WPKH addresses).
+    /// * `network` - The network, which defines the coin type of the derivation path.
+    ///
+    /// # Returns
+    ///
+    /// A Result containing the account `m/purpose'/coin_type'/0'` with the first `GAP_LIMIT` receive and change addresses
+    /// derived, or an `HdWalletError` if a key can't be derived.
+    pub fn from_seed(
+        seed: &[u8],
+        purpose: AddressPurpose,
+        network: BitcoinNetwork,
+    ) -> Result<UserInfo, HdWalletError> {
+        let account_key = ExtendedPrivateKey::new_master(seed, network)?
+            .derive_path(&DerivationPath::account(purpose, network, 0))?;
+
+        UserInfo::from_secret(AccountSecret::Hd {
+            account_key,
+            purpose,
+        })
+    }
+
+    /// Creates an account from the secret saved in the keystore.
+    ///
+    /// # Arguments
+    ///
+    /// * `secret` - The key pair of a single key account, the account key of an HD account, the script and keys of a
+    ///   multisig account, or the address, public key or account public key a watch-only account follows.
+    ///
+    /// # Returns
+    ///
+    /// A Result containing the unlocked account, or an `HdWalletError` if its addresses can't be derived.
+    pub fn from_secret(secret: AccountSecret) -> Result<UserInfo, HdWalletError> {
+        let mut user_info = match &secret {
+            AccountSecret::Key { public_key, .. } => UserInfo {
+                account_public_key: None,
+                purpose: AddressPurpose::Bip44,
+                multisig: None,
+                receive_addresses: vec![WalletAddress::new(public_key.clone())],
+                change_addresses: vec![],
+                secret: None,
+            },
+            AccountSecret::Hd {
+                account_key,
+                purpose,
+            } => UserInfo {
+                account_public_key: Some(account_key.get_extended_public_key()),
+                purpose: *purpose,
+                multisig: None,
+                receive_addresses: vec![],
+                change_addresses: vec![],
+                secret: None,
+            },
+            AccountSecret::Multisig {
+                multisig,
+                kind,
+                private_keys,
+            } => UserInfo::from_multisig(multisig.clone(), *kind, private_keys.to_vec()),
+            AccountSecret::WatchOnly(watched) => {
+                let (account_public_key, purpose, receive_addresses) = match watched {
+                    WatchedKey::Address(pk_script) => (
+                        None,
+                        AddressPurpose::Bip44,
+                        vec![WalletAddress::from_pk_script(pk_script.clone())],
+                    ),
+                    WatchedKey::PublicKey(public_key) => (
+                        None,
+                        AddressPurpose::Bip44,
+                        vec![WalletAddress::new(public_key.clone())],
+                    ),
+                    WatchedKey::Extended {
+                        account_public_key,
+                        purpose,
+                    } => (Some(account_public_key.clone()), *purpose, vec![]),
+                };
+                UserInfo {
+                    account_public_key,
+                    purpose,
+                    multisig: None,
+                    receive_addresses,
+                    change_addresses: vec![],
+                    secret: None,
+                }
+            }
+        };
+        user_info.secret = Some(secret);
+        user_info.fill_gap()?;
+        Ok(user_info)
     }
 
-    pub fn get_private_key(&self) -> &Vec<u8> {
-        &self.private_key
+    /// Derives new addresses until the last `GAP_LIMIT` addresses of both chains are unused.
+    ///
+    /// # Description
+    ///
+    /// Wallets restored from a seed don't know how many addresses were used, so, as BIP44 describes, addresses are scanned in
+    /// order and the discovery stops after `GAP_LIMIT` consecutive unused ones. Accounts of a single key have nothing to derive.
+    /// Only public keys are derived, so it works while the wallet is locked.
+    fn fill_gap(&mut self) -> Result<(), HdWalletError> {
+        let account_public_key = match &self.account_public_key {
+            Some(account_public_key) => account_public_key,
+            None => return Ok(()),
+        };
+
+        for (chain, addresses) in [
+            (RECEIVE_CHAIN, &mut self.receive_addresses),
+            (CHANGE_CHAIN, &mut self.change_addresses),
+        ] {
+            let chain_key = account_public_key.derive_child(chain)?;
+            let unused = addresses
+                .iter()
+                .rev()
+                .take_while(|address| !address.is_used())
+                .count();
+
+            for _ in unused..GAP_LIMIT {
+                let key = chain_key.derive_child(addresses.len() as u32)?;
+                addresses.push(WalletAddress::new(key.get_public_key()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the secret of the account, or `None` if the wallet is locked.
+    pub fn get_secret(&self) -> Option<&AccountSecret> {
+        self.secret.as_ref()
     }
 
-    pub fn get_pk_script(&self) -> Vec<u8> {
-        pk_script_from_public_key(&self.public_key)
+    /// Forgets the secret of the account, which is overwritten in memory. Watch-only accounts have nothing to forget, so
+    /// they stay unlocked.
+    pub fn lock(&mut self) {
+        if !self.is_watch_only() {
+            self.secret = None;
+        }
     }
 
-    pub fn get_confirmed_txs_send(&self) -> &Vec<WalletTx> {
-        &self.confirmed_txs_send
+    /// Restores the secret of the account after the keystore is unlocked.
+    pub fn unlock(&mut self, secret: AccountSecret) {
+        self.secret = Some(secret);
     }
 
-    pub fn get_confirmed_txs_recv(&self) -> &Vec<WalletTx> {
-        &self.confirmed_txs_recv
+    pub fn is_locked(&self) -> bool {
+        self.secret.is_none()
     }
 
-    pub fn get_unconfirmed_txs_send(&self) -> &Vec<WalletTx> {
-        &self.unconfirmed_txs_send
+    /// Returns all the addresses of the account, the receive ones first.
+    pub fn get_addresses(&self) -> Vec<&WalletAddress> {
+        self.receive_addresses
+            .iter()
+            .chain(self.change_addresses.iter())
+            .collect()
     }
 
-    pub fn get_unconfirmed_txs_recv(&self) -> &Vec<WalletTx> {
-        &self.unconfirmed_txs_recv
+    /// Returns whether the account was derived from a seed.
+    pub fn is_hd(&self) -> bool {
+        self.account_public_key.is_some()
     }
 
-    pub fn get_last_update(&self) -> u32 {
-        self.last_update_time
+    /// Returns whether the account was created without keys, so it can't sign transactions.
+    pub fn is_watch_only(&self) -> bool {
+        matches!(self.secret, Some(AccountSecret::WatchOnly(_)))
     }
 
-    /// Returns the total available balance based on the UTXO (Unspent Transaction Output) of the `UserInfo` struct.
+    /// Returns the redeem or witness script of a multisig account, which the wallet needs to spend its outputs, or none.
+    pub fn get_scripts(&self) -> Vec<Vec<u8>> {
+        self.multisig
+            .iter()
+            .map(|(multisig, _)| multisig.as_bytes())
+            .collect()
+    }
+
+    /// Returns the public key of the first address, the one that receives the change of the transactions. Multisig
+    /// accounts have none.
+    pub fn get_public_key(&self) -> Vec<u8> {
+        self.receive_addresses
+            .first()
+            .map(|address| address.get_public_key())
+            .unwrap_or_default()
+    }
+
+    /// Returns the private keys of all the addresses, the one of `get_public_key` first.
     ///
-    /// This function calculates the sum of all the values of the UTXOs in the `UserInfo` struct, representing the total available balance.
+    /// # Description
+    ///
+    /// The private keys of an HD account are derived from the account key when needed, instead of being kept with each
+    /// address. While the wallet is locked, and in watch-only accounts, there are no private keys.
+    pub fn get_private_keys(&self) -> Zeroizing<Vec<Vec<u8>>> {
+        let mut private_keys = Zeroizing::new(vec![]);
+        match &self.secret {
+            Some(AccountSecret::Key { private_key, .. }) => private_keys.push(private_key.to_vec()),
+            Some(AccountSecret::Multisig {
+                private_keys: keys, ..
+            }) => private_keys.extend(keys.iter().cloned()),
+            Some(AccountSecret::Hd { account_key, .. }) => {
+                for (chain, addresses) in [
+                    (RECEIVE_CHAIN, &self.receive_addresses),
+                    (CHANGE_CHAIN, &self.change_addresses),
+                ] {
+                    let chain_key = match account_key.derive_child(chain) {
+                        Ok(chain_key) => chain_key,
+                        Err(_) => continue,
+                    };
+                    for index in 0..addresses.len() as u32 {
+                        if let Ok(key) = chain_key.derive_child(index) {
+                            private_keys.push(key.get_private_key());
+                        }
+                    }
+                }
+            }
+            Some(AccountSecret::WatchOnly(_)) | None => {}
+        }
+        private_keys
+    }
+
+    /// Returns the script the change of a new transaction should be sent to.
+    ///
+    /// # Returns
+    ///
+    /// For an HD account, the script of the first unused address of the change chain, of the kind of the purpose of the
+    /// account, so each transaction gets a fresh change address. A single key account has no other address, so its change
+    /// goes back to the P2WPKH script of its key, and the change of an account locked to a script, such as a multisig one
+    /// or a watched address, goes back to that script.
+    pub fn get_change_pk_script(&self) -> Vec<u8> {
+        let change_address = self
+            .change_addresses
+            .iter()
+            .find(|address| !address.is_used());
+        match (change_address, self.receive_addresses.first()) {
+            (Some(address), _) => self.purpose.get_pk_script(&address.get_public_key()),
+            (None, Some(address)) if address.get_public_key().is_empty() => address.get_pk_script(),
+            (None, _) => witness_pk_script_from_public_key(&self.get_public_key()),
+        }
+    }
+
+    /// Reserves the change address with the given script after a transaction is sent to it, so the next transaction gets
+    /// another one even if the node hasn't reported this one yet.
+    pub fn reserve_change(&mut self, change_pk_script: &[u8]) {
+        let purpose = self.purpose;
+        if let Some(address) = self
+            .change_addresses
+            .iter_mut()
+            .find(|address| purpose.get_pk_script(&address.get_public_key()) == change_pk_script)
+        {
+            address.reserve();
+        }
+        if let Err(err) = self.fill_gap() {
+            println!("{}", err);
+        }
+    }
+
+    /// Returns the P2PKH and P2WPKH scripts of all the addresses of the account.
+    pub fn get_pk_scripts(&self) -> Vec<Vec<u8>> {
+        self.get_addresses()
+            .iter()
+            .flat_map(|address| address.get_pk_scripts())
+            .collect()
+    }
+
+    /// Returns the address that should be shared to receive funds.
+    ///
+    /// # Arguments
+    ///
+    /// * `network` - The network of the address.
+    ///
+    /// # Returns
+    ///
+    /// The first unused receive address, encoded as P2PKH or P2WPKH depending on the purpose of the account. For a single key
+    /// account, its P2PKH address, and for an account locked to a script, such as a multisig one or a watched address, the
+    /// address of that script.
+    pub fn get_receive_address(&self, network: BitcoinNetwork) -> String {
+        if let Some(address) = self
+            .receive_addresses
+            .first()
+            .filter(|address| address.get_public_key().is_empty())
+        {
+            return Address::from_pk_script(&address.get_pk_script(), network)
+                .map(|address| address.to_string())
+                .unwrap_or_default();
+        }
+        self.receive_addresses
+            .iter()
+            .find(|address| !address.is_used())
+            .or(self.receive_addresses.first())
+            .map(|address| {
+                self.purpose
+                    .get_address(&address.get_public_key(), network)
+                    .to_string()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Joins the transactions of every address, removing the ones found for more than one address.
+    fn join_txs(&self, get_txs: fn(&WalletAddress) -> &Vec<WalletTx>) -> Vec<WalletTx> {
+        let mut txs: Vec<WalletTx> = vec![];
+        for tx in self.get_addresses().into_iter().flat_map(get_txs) {
+            if !txs.contains(tx) {
+                txs.push(tx.clone());
+            }
+        }
+        txs
+    }
+
+    pub fn get_confirmed_txs_send(&self) -> Vec<WalletTx> {
+        self.join_txs(WalletAddress::get_confirmed_txs_send)
+    }
+
+    pub fn get_confirmed_txs_recv(&self) -> Vec<WalletTx> {
+        self.join_txs(WalletAddress::get_confirmed_txs_recv)
+    }
+
+    pub fn get_unconfirmed_txs_send(&self) -> Vec<WalletTx> {
+        self.join_txs(WalletAddress::get_unconfirmed_txs_send)
+    }
+
+    pub fn get_unconfirmed_txs_recv(&self) -> Vec<WalletTx> {
+        self.join_txs(WalletAddress::get_unconfirmed_txs_recv)
+    }
+
+    /// Returns a transaction that sends to or spends from an address of the account, found by its id.
+    pub fn get_transaction(&self, tx_id: &[u8]) -> Option<Transaction> {
+        self.get_addresses()
+            .into_iter()
+            .flat_map(|address| {
+                address
+                    .get_confirmed_txs_recv()
+                    .iter()
+                    .chain(address.get_unconfirmed_txs_recv())
+                    .chain(address.get_confirmed_txs_send())
+                    .chain(address.get_unconfirmed_txs_send())
+            })
+            .map(WalletTx::get_tx)
+            .find(|transaction| transaction.get_id() == tx_id)
+            .cloned()
+    }
+
+    /// Returns the total available balance based on the UTXO (Unspent Transaction Output) of every address.
     ///
     /// # Returns
     ///
     /// The total available balance as an `i64` value.
     pub fn get_avaiable(&self) -> i64 {
-        let mut avaiable: i64 = 0;
-        for tx in self.utxo.iter() {
-            avaiable += tx.2.get_value();
-        }
-        avaiable
+        self.get_utxo().iter().map(|tx| tx.2.get_value()).sum()
     }
 
-    /// Filters the UTXO (Unspent Transaction Output) of the `UserInfo` struct based on the provided list of new outgoing transactions.
+    /// Updates an address with the transactions the node found for it.
+    ///
+    /// # Arguments
+    ///
+    /// * `pk_script` - The script of the address the transactions belong to.
+    /// * `txs` - The transactions returned by the node.
     ///
-    /// This function removes the UTXOs that are spent by the given list of new outgoing transactions from the `UserInfo` struct's UTXO collection.
+    /// # Description
+    ///
+    /// The node reports a transaction as sent only to the address of its first input, so the outputs it spends are removed
+    /// from the UTXOs of all the addresses. In the same way, when a reorganization disconnects a sent transaction, the
+    /// outputs it spent are given back to the addresses they pay to. If the address turns out to be used, new addresses are
+    /// derived to keep the gap.
+    pub fn update(&mut self, pk_script: &[u8], txs: &Transactions) {
+        let mut disconnected_send = vec![];
+        for address in self
+            .receive_addresses
+            .iter_mut()
+            .chain(self.change_addresses.iter_mut())
+        {
+            if address.get_pk_script() == pk_script {
+                disconnected_send.extend(address.update(txs));
+            }
+        }
+        self.restore_spent_outputs(&disconnected_send);
+
+        let new_tx_send = txs.get_confirmed_txs_send();
+        for address in self
+            .receive_addresses
+            .iter_mut()
+            .chain(self.change_addresses.iter_mut())
+        {
+            address.filter_utxo(&new_tx_send);
+        }
+
+        if let Err(err) = self.fill_gap() {
+            println!("{}", err);
+        }
+    }
+
+    /// Adds back to the UTXOs the outputs spent by transactions that are no longer confirmed.
     ///
     /// # Arguments
     ///
-    /// * `new_tx_send` - A reference to a vector of `WalletTx` representing the new outgoing transactions.
-    fn filter_utxo(&mut self, new_tx_send: &[WalletTx]) {
-        for tx in new_tx_send.iter() {
-            for txin in tx.get_tx().get_tx_in_list() {
-                let prev_id = txin.get_prev_output().get_tx_id();
-                let prev_index = txin.get_prev_output().get_index();
-
-                let mut aux = 0;
-                for i in 0..self.utxo.len() {
-                    if self.utxo[i - aux].0 == *prev_id && self.utxo[i - aux].1 == prev_index {
-                        self.utxo.remove(i - aux);
-                        aux += 1;
+    /// * `disconnected_send` - The outgoing transactions a reorganization disconnected.
+    ///
+    /// # Description
+    ///
+    /// An output is restored if the transaction that created it is still confirmed, it pays to an address of the account and
+    /// no other confirmed transaction of the account spends it.
+    fn restore_spent_outputs(&mut self, disconnected_send: &[WalletTx]) {
+        if disconnected_send.is_empty() {
+            return;
+        }
+        let confirmed_send = self.get_confirmed_txs_send();
+        let confirmed_txs = [confirmed_send.clone(), self.get_confirmed_txs_recv()].concat();
+
+        for txin in disconnected_send
+            .iter()
+            .flat_map(|tx| tx.get_tx().get_tx_in_list())
+        {
+            let prev_id = txin.get_prev_output().get_tx_id();
+            let prev_index = txin.get_prev_output().get_index();
+            let is_spent = confirmed_send
+                .iter()
+                .flat_map(|tx| tx.get_tx().get_tx_in_list())
+                .any(|other| {
+                    other.get_prev_output().get_tx_id() == prev_id
+                        && other.get_prev_output().get_index() == prev_index
+                });
+            let tx_out = confirmed_txs
+                .iter()
+                .find(|tx| tx.get_tx().get_id() == *prev_id)
+                .and_then(|tx| tx.get_tx().get_tx_out_list().get(prev_index as usize));
+
+            if let (false, Some(tx_out)) = (is_spent, tx_out) {
+                for address in self
+                    .receive_addresses
+                    .iter_mut()
+                    .chain(self.change_addresses.iter_mut())
+                {
+                    if address.get_pk_scripts().contains(&tx_out.get_pk_script()) {
+                        address.add_utxo(prev_id.clone(), prev_index, tx_out.clone());
                     }
                 }
             }
         }
     }
 
-    pub fn update(&mut self, txs: &Transactions) {
-        let new_tx_send = txs.get_confirmed_txs_send();
-        self.confirmed_txs_send.extend(new_tx_send.clone());
-        self.confirmed_txs_recv.extend(txs.get_confirmed_txs_recv());
-        self.unconfirmed_txs_send = txs.get_unconfirmed_txs_send();
-        self.unconfirmed_txs_recv = txs.get_unconfirmed_txs_recv();
-        self.utxo.extend(txs.get_utxo());
-        self.filter_utxo(&new_tx_send);
-        self.last_update_time = txs.get_last_update();
+    /// Serializes the addresses of the account with their history, to be saved in the transaction cache.
+    pub fn history_as_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![];
+        for addresses in [&self.receive_addresses, &self.change_addresses] {
+            bytes.extend((addresses.len() as u32).to_le_bytes());
+            for address in addresses.iter() {
+                bytes.extend(address.as_bytes());
+            }
+        }
+        bytes
     }
 
-    pub fn get_last_update_time(&self) -> u32 {
-        self.last_update_time
+    /// Restores the addresses of the account and their history from the transaction cache.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream` - The history of the account serialized with `history_as_bytes`.
+    ///
+    /// # Returns
+    ///
+    /// A Result with `TxCacheError::AccountMismatch` if the cached addresses aren't the ones of the account, or
+    /// `TxCacheError::InvalidFormat` if the history can't be parsed. In both cases the account is left unchanged.
+    ///
+    /// # Description
+    ///
+    /// The addresses of an HD account are derived again and compared to the cached ones, so a cache saved for another
+    /// account with the same name is rejected. The addresses keep the last block they were synced to, so the node only
+    /// scans the blocks after it.
+    pub fn restore_history(&mut self, stream: &mut dyn Read) -> Result<(), TxCacheError> {
+        let receive_addresses = read_addresses(stream)?;
+        let change_addresses = read_addresses(stream)?;
+
+        match &self.account_public_key {
+            Some(account_public_key) => {
+                for (chain, addresses) in [
+                    (RECEIVE_CHAIN, &receive_addresses),
+                    (CHANGE_CHAIN, &change_addresses),
+                ] {
+                    let chain_key = account_public_key
+                        .derive_child(chain)
+                        .map_err(|_| TxCacheError::AccountMismatch)?;
+                    for (index, address) in addresses.iter().enumerate() {
+                        let key = chain_key
+                            .derive_child(index as u32)
+                            .map_err(|_| TxCacheError::AccountMismatch)?;
+                        if key.get_public_key() != address.get_public_key() {
+                            return Err(TxCacheError::AccountMismatch);
+                        }
+                    }
+                }
+            }
+            None => {
+                if receive_addresses.len() != 1
+                    || Some(receive_addresses[0].get_pk_script())
+                        != self
+                            .receive_addresses
+                            .first()
+                            .map(WalletAddress::get_pk_script)
+                    || !change_addresses.is_empty()
+                {
+                    return Err(TxCacheError::AccountMismatch);
+                }
+            }
+        }
+
+        self.receive_addresses = receive_addresses;
+        self.change_addresses = change_addresses;
+        self.fill_gap().map_err(|_| TxCacheError::AccountMismatch)
     }
 
     pub fn get_utxo(&self) -> Vec<(Vec<u8>, u32, TxOut)> {
-        self.utxo.clone()
+        self.get_addresses()
+            .iter()
+            .flat_map(|address| address.get_utxo().clone())
+            .collect()
+    }
+
+    /// Creates a signed transaction spending the outputs of the account.
+    ///
+    /// # Arguments
+    ///
+    /// * `targets` - The addresses to pay to, with the satoshis sent to each one.
+    /// * `fee_rate` - The fee rate in satoshis per virtual byte.
+    /// * `sighash_type` - The sighash type every input is signed with, `SIGHASH_ALL` unless the transaction is meant to
+    ///   be completed by someone else.
+    /// * `network` - The network the addresses must belong to.
+    /// * `coin_selector` - The strategy that chooses which outputs are spent.
+    ///
+    /// # Returns
+    ///
+    /// The transaction, its fee and the script its change is sent to, which should be reserved with `reserve_change` once
+    /// it is broadcast. Watch-only accounts can't sign, so a `WatchOnly` error is returned for them, and a multisig account
+    /// returns `MissingSignatures` if its keys alone don't reach the required signatures.
+    pub fn create_transaction(
+        &self,
+        targets: Vec<(Vec<u8>, i64)>,
+        fee_rate: i64,
+        sighash_type: u32,
+        network: BitcoinNetwork,
+        coin_selector: &dyn CoinSelector,
+    ) -> Result<(Transaction, i64, Vec<u8>), TransactionCreateError> {
+        if self.is_watch_only() {
+            return Err(TransactionCreateError::WatchOnly);
+        }
+        let change_pk_script = self.get_change_pk_script();
+        let scripts = self.get_scripts();
+        let (mut transaction, prev_outputs, fee) = create_unsigned_transaction(
+            targets,
+            &self.get_utxo(),
+            &change_pk_script,
+            &scripts,
+            fee_rate,
+            network,
+            coin_selector,
+        )?;
+        let private_keys: Vec<SecretKey> = self
+            .get_private_keys()
+            .iter()
+            .filter_map(|private_key| SecretKey::from_slice(private_key).ok())
+            .collect();
+        if scripts.is_empty() {
+            sign_transaction(&mut transaction, &private_keys, &prev_outputs, sighash_type)?;
+        } else {
+            sign_multisig_transaction(
+                &mut transaction,
+                &private_keys,
+                &prev_outputs,
+                &scripts,
+                sighash_type,
+            )?;
+        }
+        Ok((transaction, fee, change_pk_script))
+    }
+}
+
+/// Reads a chain of addresses preceded by their count.
+fn read_addresses(stream: &mut dyn Read) -> Result<Vec<WalletAddress>, TxCacheError> {
+    let count = read_u32_from_bytes(stream, true)?;
+    (0..count)
+        .map(|_| WalletAddress::from_bytes(stream).map_err(TxCacheError::from))
+        .collect()
+}
+
+#[cfg(test)]
+mod user_info_test {
+    use node::{
+        block_mod::{
+            transaction::{Transaction, SIGHASH_ANYONECANPAY, SIGHASH_SINGLE},
+            tx_in::TxIn,
+        },
+        network::bitcoin_network::BitcoinNetwork,
+        wallet_utils::chain_sync::ChainSync,
+    };
+    use secp256k1::{PublicKey, Secp256k1, SecretKey};
+
+    use super::*;
+    use crate::{
+        hd_wallet::bip39::seed_from_mnemonic,
+        multisig::multisig_error::MultisigError,
+        transactions::{
+            coin_selection::largest_first::LargestFirst,
+            create_transactions::verify_input_signature,
+        },
+    };
+
+    #[test]
+    fn test_hd_account_addresses() -> Result<(), HdWalletError> {
+        // BIP84 test vector, m/84'/0'/0'
+        let seed = seed_from_mnemonic(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+            "",
+        )?;
+        let user_info = UserInfo::from_seed(&seed, AddressPurpose::Bip84, BitcoinNetwork::Mainnet)?;
+
+        assert!(user_info.is_hd());
+        assert_eq!(user_info.get_addresses().len(), 2 * GAP_LIMIT);
+        assert_eq!(
+            user_info.get_receive_address(BitcoinNetwork::Mainnet),
+            "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu"
+        );
+        assert_eq!(
+            user_info.change_addresses[0].get_public_key(),
+            ExtendedPrivateKey::new_master(&seed, BitcoinNetwork::Mainnet)?
+                .derive_path(&"m/84'/0'/0'/1/0".parse()?)?
+                .get_public_key()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_used_address_extends_gap() -> Result<(), HdWalletError> {
+        let seed = seed_from_mnemonic(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+            "",
+        )?;
+        let mut user_info =
+            UserInfo::from_seed(&seed, AddressPurpose::Bip44, BitcoinNetwork::Testnet)?;
+        let pk_script = user_info.receive_addresses[5].get_pk_script();
+        let received = WalletTx::new(
+            Transaction::new(1, vec![], vec![], 0),
+            vec![1; 32],
+            String::new(),
+        );
+        let txs = Transactions::new(
+            vec![],
+            vec![received],
+            vec![],
+            vec![],
+            vec![],
+            ChainSync::new(vec![1; 32], vec![], false),
+        );
+
+        user_info.update(&pk_script, &txs);
+
+        assert_eq!(user_info.receive_addresses.len(), 6 + GAP_LIMIT);
+        assert_eq!(user_info.change_addresses.len(), GAP_LIMIT);
+        assert_eq!(user_info.get_confirmed_txs_recv().len(), 1);
+        assert_eq!(
+            user_info.get_receive_address(BitcoinNetwork::Testnet),
+            user_info
+                .purpose
+                .get_address(
+                    &user_info.receive_addresses[0].get_public_key(),
+                    BitcoinNetwork::Testnet
+                )
+                .to_string()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_single_key_account() {
+        let user_info = UserInfo::new(vec![2; 33], vec![1; 32]);
+
+        assert!(!user_info.is_hd());
+        assert_eq!(user_info.get_addresses().len(), 1);
+        assert_eq!(*user_info.get_private_keys(), vec![vec![1; 32]]);
+    }
+
+    #[test]
+    fn test_locked_account_keeps_deriving_addresses() -> Result<(), HdWalletError> {
+        let seed = seed_from_mnemonic(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+            "",
+        )?;
+        let mut user_info =
+            UserInfo::from_seed(&seed, AddressPurpose::Bip84, BitcoinNetwork::Testnet)?;
+        let unlocked_keys = user_info.get_private_keys();
+        assert_eq!(unlocked_keys.len(), 2 * GAP_LIMIT);
+
+        let secret = user_info.get_secret().cloned();
+        user_info.lock();
+        assert!(user_info.is_locked());
+        assert!(user_info.get_private_keys().is_empty());
+
+        let pk_script = user_info.receive_addresses[GAP_LIMIT - 1].get_pk_script();
+        let received = WalletTx::new(
+            Transaction::new(1, vec![], vec![], 0),
+            vec![1; 32],
+            String::new(),
+        );
+        let txs = Transactions::new(
+            vec![],
+            vec![received],
+            vec![],
+            vec![],
+            vec![],
+            ChainSync::new(vec![1; 32], vec![], false),
+        );
+        user_info.update(&pk_script, &txs);
+        assert_eq!(user_info.receive_addresses.len(), 2 * GAP_LIMIT);
+
+        if let Some(secret) = secret {
+            user_info.unlock(secret);
+        }
+        let private_keys = user_info.get_private_keys();
+        assert_eq!(private_keys.len(), 3 * GAP_LIMIT);
+        assert_eq!(private_keys[..GAP_LIMIT], unlocked_keys[..GAP_LIMIT]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_history_is_restored_for_the_same_keys() -> Result<(), TxCacheError> {
+        let seed = seed_from_mnemonic(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+            "",
+        )
+        .map_err(|_| TxCacheError::AccountMismatch)?;
+        let from_seed = |seed: &[u8]| {
+            UserInfo::from_seed(seed, AddressPurpose::Bip84, BitcoinNetwork::Testnet)
+                .map_err(|_| TxCacheError::AccountMismatch)
+        };
+        let mut user_info = from_seed(&seed)?;
+        let pk_script = user_info.receive_addresses[GAP_LIMIT - 1].get_pk_script();
+        let received = WalletTx::new(
+            Transaction::new(
+                1,
+                vec![TxIn::new(vec![0; 32], 0, vec![], 0)],
+                vec![TxOut::new(1000, vec![])],
+                0,
+            ),
+            vec![1; 32],
+            "2023-06-01  10:00".to_string(),
+        );
+        let txs = Transactions::new(
+            vec![],
+            vec![received],
+            vec![],
+            vec![],
+            vec![],
+            ChainSync::new(vec![1; 32], vec![], false),
+        );
+        user_info.update(&pk_script, &txs);
+        let history = user_info.history_as_bytes();
+
+        let mut restored = from_seed(&seed)?;
+        restored.restore_history(&mut &history[..])?;
+        assert_eq!(restored.get_addresses().len(), 3 * GAP_LIMIT);
+        assert_eq!(restored.get_confirmed_txs_recv().len(), 1);
+        assert_eq!(
+            restored.receive_addresses[GAP_LIMIT - 1].get_last_block_hash(),
+            vec![1; 32]
+        );
+
+        let mut other = from_seed(&[7; 64])?;
+        assert_eq!(
+            other.restore_history(&mut &history[..]),
+            Err(TxCacheError::AccountMismatch)
+        );
+        assert_eq!(other.get_confirmed_txs_recv().len(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_reorg_restores_spent_outputs() {
+        let public_key = vec![2; 33];
+        let mut user_info = UserInfo::new(public_key.clone(), vec![1; 32]);
+        let pk_script = user_info.receive_addresses[0].get_pk_script();
+        let tx_out = TxOut::new(1000, pk_script.clone());
+        let received_tx = Transaction::new(1, vec![], vec![tx_out.clone()], 0);
+        let received_id = received_tx.get_id();
+        let spend_tx = Transaction::new(
+            1,
+            vec![TxIn::new(received_id.clone(), 0, public_key.clone(), 0)],
+            vec![],
+            0,
+        );
+
+        user_info.update(
+            &pk_script,
+            &Transactions::new(
+                vec![],
+                vec![WalletTx::new(received_tx, vec![1; 32], String::new())],
+                vec![],
+                vec![],
+                vec![(received_id.clone(), 0, tx_out.clone())],
+                ChainSync::new(vec![1; 32], vec![], false),
+            ),
+        );
+        user_info.update(
+            &pk_script,
+            &Transactions::new(
+                vec![WalletTx::new(spend_tx, vec![2; 32], String::new())],
+                vec![],
+                vec![],
+                vec![],
+                vec![],
+                ChainSync::new(vec![2; 32], vec![], false),
+            ),
+        );
+        assert_eq!(user_info.get_avaiable(), 0);
+
+        user_info.update(
+            &pk_script,
+            &Transactions::new(
+                vec![],
+                vec![],
+                vec![],
+                vec![],
+                vec![],
+                ChainSync::new(vec![3; 32], vec![vec![2; 32]], false),
+            ),
+        );
+        assert!(user_info.get_confirmed_txs_send().is_empty());
+        assert_eq!(user_info.get_confirmed_txs_recv().len(), 1);
+        assert_eq!(user_info.get_utxo(), vec![(received_id, 0, tx_out)]);
+    }
+
+    #[test]
+    fn test_transaction_is_signed_with_the_given_sighash_type() -> Result<(), TransactionCreateError>
+    {
+        let private_key = SecretKey::from_slice(&[1; 32])?;
+        let public_key = PublicKey::from_secret_key(&Secp256k1::new(), &private_key)
+            .serialize()
+            .to_vec();
+        let mut user_info = UserInfo::new(public_key, private_key.secret_bytes().to_vec());
+        let pk_script = user_info.receive_addresses[0].get_pk_script();
+        let tx_out = TxOut::new(1000000, pk_script.clone());
+        let received_tx = Transaction::new(1, vec![], vec![tx_out.clone()], 0);
+        user_info.update(
+            &pk_script,
+            &Transactions::new(
+                vec![],
+                vec![WalletTx::new(
+                    received_tx.clone(),
+                    vec![1; 32],
+                    String::new(),
+                )],
+                vec![],
+                vec![],
+                vec![(received_tx.get_id(), 0, tx_out.clone())],
+                ChainSync::new(vec![1; 32], vec![], false),
+            ),
+        );
+
+        let sighash_type = SIGHASH_SINGLE | SIGHASH_ANYONECANPAY;
+        let (transaction, _, _) = user_info.create_transaction(
+            vec![(b"mp3PDnKDtxPYrPKcYLGX1pXMe6KwAsfquD".to_vec(), 500000)],
+            10,
+            sighash_type,
+            BitcoinNetwork::Testnet,
+            &LargestFirst,
+        )?;
+
+        // The signature script starts with the push of the signature, whose last byte is the sighash type.
+        let signature_script = transaction.get_tx_in_list()[0].get_signature_script();
+        assert_eq!(
+            signature_script[signature_script[0] as usize],
+            sighash_type as u8
+        );
+        assert!(verify_input_signature(&transaction, 0, &tx_out));
+        Ok(())
+    }
+
+    #[test]
+    fn test_each_transaction_gets_a_fresh_change_address() -> Result<(), HdWalletError> {
+        let seed = seed_from_mnemonic(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+            "",
+        )?;
+        let mut user_info =
+            UserInfo::from_seed(&seed, AddressPurpose::Bip84, BitcoinNetwork::Testnet)?;
+        let first_change = user_info.get_change_pk_script();
+        assert_eq!(
+            first_change,
+            witness_pk_script_from_public_key(&user_info.change_addresses[0].get_public_key())
+        );
+
+        user_info.reserve_change(&first_change);
+        let second_change = user_info.get_change_pk_script();
+        assert_ne!(first_change, second_change);
+        assert_eq!(
+            second_change,
+            witness_pk_script_from_public_key(&user_info.change_addresses[1].get_public_key())
+        );
+        assert_eq!(user_info.change_addresses.len(), GAP_LIMIT + 1);
+
+        let single_key = UserInfo::new(vec![2; 33], vec![1; 32]);
+        assert_eq!(
+            single_key.get_change_pk_script(),
+            witness_pk_script_from_public_key(&[2; 33])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_multisig_account() -> Result<(), MultisigError> {
+        let secp = Secp256k1::new();
+        let public_keys = [[1; 32], [2; 32]]
+            .iter()
+            .filter_map(|private_key| SecretKey::from_slice(private_key).ok())
+            .map(|private_key| {
+                PublicKey::from_secret_key(&secp, &private_key)
+                    .serialize()
+                    .to_vec()
+            })
+            .collect();
+        let multisig = MultisigScript::new(2, public_keys)?;
+        let script = multisig.as_bytes();
+        let mut user_info =
+            UserInfo::from_multisig(multisig.clone(), MultisigKind::P2wsh, vec![vec![1; 32]]);
+        let pk_script = MultisigKind::P2wsh.get_pk_script(&script);
+
+        assert_eq!(user_info.get_scripts(), vec![script.clone()]);
+        assert_eq!(user_info.get_change_pk_script(), pk_script);
+        assert_eq!(
+            user_info.get_receive_address(BitcoinNetwork::Testnet),
+            MultisigKind::P2wsh
+                .get_address(&script, BitcoinNetwork::Testnet)
+                .to_string()
+        );
+        assert_eq!(*user_info.get_private_keys(), vec![vec![1; 32]]);
+
+        let tx_out = TxOut::new(1000, pk_script.clone());
+        let received_tx = Transaction::new(
+            1,
+            vec![TxIn::new(vec![0; 32], 0, vec![], 0)],
+            vec![tx_out.clone()],
+            0,
+        );
+        let received_id = received_tx.get_id();
+        user_info.update(
+            &pk_script,
+            &Transactions::new(
+                vec![],
+                vec![WalletTx::new(
+                    received_tx,
+                    vec![1; 32],
+                    "2023-06-01  10:00".to_string(),
+                )],
+                vec![],
+                vec![],
+                vec![(received_id.clone(), 0, tx_out.clone())],
+                ChainSync::new(vec![1; 32], vec![], false),
+            ),
+        );
+        assert_eq!(user_info.get_utxo(), vec![(received_id, 0, tx_out)]);
+
+        let mut restored = UserInfo::from_multisig(multisig, MultisigKind::P2wsh, vec![]);
+        assert_eq!(
+            restored.restore_history(&mut &user_info.history_as_bytes()[..]),
+            Ok(())
+        );
+        assert_eq!(restored.get_avaiable(), 1000);
+        Ok(())
+    }
+
+    #[test]
+    fn test_watch_only_accounts() -> Result<(), HdWalletError> {
+        let seed = seed_from_mnemonic(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+            "",
+        )?;
+        let hd = UserInfo::from_seed(&seed, AddressPurpose::Bip84, BitcoinNetwork::Mainnet)?;
+        let account_public_key = ExtendedPrivateKey::new_master(&seed, BitcoinNetwork::Mainnet)?
+            .derive_path(&"m/84'/0'/0'".parse()?)?
+            .get_extended_public_key();
+        let mut watched = UserInfo::from_secret(AccountSecret::WatchOnly(WatchedKey::Extended {
+            account_public_key,
+            purpose: AddressPurpose::Bip84,
+        }))?;
+
+        assert!(watched.is_watch_only());
+        assert_eq!(watched.get_pk_scripts(), hd.get_pk_scripts());
+        assert_eq!(
+            watched.get_receive_address(BitcoinNetwork::Mainnet),
+            "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu"
+        );
+        watched.lock();
+        assert!(!watched.is_locked());
+        assert!(watched.get_private_keys().is_empty());
+
+        let address = UserInfo::from_secret(AccountSecret::WatchOnly(WatchedKey::Address(
+            hd.get_change_pk_script(),
+        )))?;
+        assert_eq!(
+            address.get_receive_address(BitcoinNetwork::Mainnet),
+            hd.purpose
+                .get_address(
+                    &hd.change_addresses[0].get_public_key(),
+                    BitcoinNetwork::Mainnet
+                )
+                .to_string()
+        );
+        assert_eq!(address.get_change_pk_script(), hd.get_change_pk_script());
+        Ok(())
     }
 }
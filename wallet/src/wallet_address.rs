@@ -0,0 +1,316 @@
+use std::io::Read;
+
+use node::{
+    block_mod::{script_type::get_pubkey_hash_scripts, tx_out::TxOut},
+    messages::{
+        message_error::MessageError,
+        read_from_bytes::{read_u32_from_bytes, read_u8_from_bytes, read_vec_from_bytes},
+    },
+    wallet_utils::{
+        transactions::Transactions, wallet_tx::WalletTx, wallet_utils_constants::NULL_BLOCK_HASH,
+    },
+};
+
+use crate::transactions::create_transactions::pk_script_from_public_key;
+
+#[derive(Debug)]
+/// Represents a public key of the wallet, or a script such as a multisig one, and the transactions the node found for it.
+pub struct WalletAddress {
+    public_key: Vec<u8>,
+    pk_script: Option<Vec<u8>>,
+    confirmed_txs_send: Vec<WalletTx>,
+    confirmed_txs_recv: Vec<WalletTx>,
+    unconfirmed_txs_send: Vec<WalletTx>,
+    unconfirmed_txs_recv: Vec<WalletTx>,
+    utxo: Vec<(Vec<u8>, u32, TxOut)>,
+    last_block_hash: Vec<u8>,
+    reserved: bool,
+}
+
+impl WalletAddress {
+    pub fn new(public_key: Vec<u8>) -> WalletAddress {
+        WalletAddress {
+            public_key,
+            pk_script: None,
+            utxo: vec![],
+            confirmed_txs_send: vec![],
+            confirmed_txs_recv: vec![],
+            unconfirmed_txs_send: vec![],
+            unconfirmed_txs_recv: vec![],
+            last_block_hash: NULL_BLOCK_HASH.to_vec(),
+            reserved: false,
+        }
+    }
+
+    /// Creates an address locked to a script instead of a single key, such as the P2SH or P2WSH script of a multisig
+    /// account. It has no public key.
+    pub fn from_pk_script(pk_script: Vec<u8>) -> WalletAddress {
+        WalletAddress {
+            pk_script: Some(pk_script),
+            ..WalletAddress::new(vec![])
+        }
+    }
+
+    pub fn get_public_key(&self) -> Vec<u8> {
+        self.public_key.clone()
+    }
+
+    /// Returns the script of the address, the P2PKH script of the key if it isn't locked to another one.
+    pub fn get_pk_script(&self) -> Vec<u8> {
+        match &self.pk_script {
+            Some(pk_script) => pk_script.clone(),
+            None => pk_script_from_public_key(&self.public_key),
+        }
+    }
+
+    /// Returns the P2PKH and P2WPKH scripts of the key, both of which the node reports, or the script the address is
+    /// locked to.
+    pub fn get_pk_scripts(&self) -> Vec<Vec<u8>> {
+        get_pubkey_hash_scripts(&self.get_pk_script())
+    }
+
+    pub fn get_confirmed_txs_send(&self) -> &Vec<WalletTx> {
+        &self.confirmed_txs_send
+    }
+
+    pub fn get_confirmed_txs_recv(&self) -> &Vec<WalletTx> {
+        &self.confirmed_txs_recv
+    }
+
+    pub fn get_unconfirmed_txs_send(&self) -> &Vec<WalletTx> {
+        &self.unconfirmed_txs_send
+    }
+
+    pub fn get_unconfirmed_txs_recv(&self) -> &Vec<WalletTx> {
+        &self.unconfirmed_txs_recv
+    }
+
+    pub fn get_utxo(&self) -> &Vec<(Vec<u8>, u32, TxOut)> {
+        &self.utxo
+    }
+
+    /// Returns the hash of the last block the address was synced to, all zeros if it never was.
+    pub fn get_last_block_hash(&self) -> Vec<u8> {
+        self.last_block_hash.clone()
+    }
+
+    /// Returns whether the address was already scanned by the node at least once.
+    pub fn is_synced(&self) -> bool {
+        self.last_block_hash != NULL_BLOCK_HASH
+    }
+
+    /// Marks the address as used before the node finds a transaction for it, so it isn't handed out twice.
+    pub fn reserve(&mut self) {
+        self.reserved = true;
+    }
+
+    /// Returns whether the address was reserved, or any confirmed or unconfirmed transaction sends from or pays to it.
+    pub fn is_used(&self) -> bool {
+        self.reserved
+            || !self.confirmed_txs_send.is_empty()
+            || !self.confirmed_txs_recv.is_empty()
+            || !self.unconfirmed_txs_send.is_empty()
+            || !self.unconfirmed_txs_recv.is_empty()
+    }
+
+    /// Filters the UTXO (Unspent Transaction Output) of the address based on the provided list of new outgoing transactions.
+    ///
+    /// This function removes the UTXOs that are spent by the given list of new outgoing transactions from the address' UTXO collection.
+    ///
+    /// # Arguments
+    ///
+    /// * `new_tx_send` - A reference to a vector of `WalletTx` representing the new outgoing transactions.
+    pub fn filter_utxo(&mut self, new_tx_send: &[WalletTx]) {
+        for tx in new_tx_send.iter() {
+            for txin in tx.get_tx().get_tx_in_list() {
+                let prev_id = txin.get_prev_output().get_tx_id();
+                let prev_index = txin.get_prev_output().get_index();
+
+                self.utxo
+                    .retain(|(tx_id, index, _)| !(tx_id == prev_id && *index == prev_index));
+            }
+        }
+    }
+
+    /// Adds an output to the UTXOs of the address, unless it is already there.
+    pub fn add_utxo(&mut self, tx_id: Vec<u8>, index: u32, tx_out: TxOut) {
+        if !self
+            .utxo
+            .iter()
+            .any(|(utxo_id, utxo_index, _)| *utxo_id == tx_id && *utxo_index == index)
+        {
+            self.utxo.push((tx_id, index, tx_out));
+        }
+    }
+
+    /// Removes the confirmed transactions of the given blocks, along with the outputs they created.
+    ///
+    /// # Arguments
+    ///
+    /// * `block_hashes` - The hashes of the blocks that are no longer part of the chain.
+    ///
+    /// # Returns
+    ///
+    /// The removed outgoing transactions, whose spent outputs are unspent again.
+    fn disconnect_blocks(&mut self, block_hashes: &[Vec<u8>]) -> Vec<WalletTx> {
+        let (disconnected_send, confirmed_txs_send): (Vec<WalletTx>, Vec<WalletTx>) = self
+            .confirmed_txs_send
+            .drain(..)
+            .partition(|tx| block_hashes.contains(tx.get_block_hash()));
+        let (disconnected_recv, confirmed_txs_recv): (Vec<WalletTx>, Vec<WalletTx>) = self
+            .confirmed_txs_recv
+            .drain(..)
+            .partition(|tx| block_hashes.contains(tx.get_block_hash()));
+        self.confirmed_txs_send = confirmed_txs_send;
+        self.confirmed_txs_recv = confirmed_txs_recv;
+
+        let disconnected_ids: Vec<Vec<u8>> = disconnected_send
+            .iter()
+            .chain(disconnected_recv.iter())
+            .map(|tx| tx.get_tx().get_id())
+            .collect();
+        self.utxo
+            .retain(|(tx_id, _, _)| !disconnected_ids.contains(tx_id));
+
+        disconnected_send
+    }
+
+    /// Updates the address with the transactions the node found for it.
+    ///
+    /// # Arguments
+    ///
+    /// * `txs` - The transactions returned by the node.
+    ///
+    /// # Returns
+    ///
+    /// The outgoing transactions that were removed because a reorganization left their blocks out of the chain, or
+    /// because the node scanned the whole chain again.
+    pub fn update(&mut self, txs: &Transactions) -> Vec<WalletTx> {
+        let chain_sync = txs.get_chain_sync();
+        let disconnected_send = if chain_sync.is_full_rescan() {
+            self.utxo.clear();
+            self.confirmed_txs_recv.clear();
+            self.confirmed_txs_send.drain(..).collect()
+        } else {
+            self.disconnect_blocks(chain_sync.get_disconnected_blocks())
+        };
+
+        let new_tx_send = txs.get_confirmed_txs_send();
+        self.confirmed_txs_send.extend(new_tx_send.clone());
+        self.confirmed_txs_recv.extend(txs.get_confirmed_txs_recv());
+        self.unconfirmed_txs_send = txs.get_unconfirmed_txs_send();
+        self.unconfirmed_txs_recv = txs.get_unconfirmed_txs_recv();
+        self.utxo.extend(txs.get_utxo());
+        self.filter_utxo(&new_tx_send);
+        self.last_block_hash = chain_sync.get_last_block_hash().clone();
+
+        disconnected_send
+    }
+
+    /// Serializes the address and its history to be saved in the transaction cache.
+    pub fn as_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![self.public_key.len() as u8];
+        bytes.extend(&self.public_key);
+        let pk_script = self.pk_script.clone().unwrap_or_default();
+        bytes.push(pk_script.len() as u8);
+        bytes.extend(pk_script);
+
+        for txs in [
+            &self.confirmed_txs_send,
+            &self.confirmed_txs_recv,
+            &self.unconfirmed_txs_send,
+            &self.unconfirmed_txs_recv,
+        ] {
+            bytes.extend((txs.len() as u32).to_le_bytes());
+            for tx in txs.iter() {
+                bytes.extend(tx.as_bytes());
+            }
+        }
+
+        bytes.extend((self.utxo.len() as u32).to_le_bytes());
+        for (tx_id, index, tx_out) in self.utxo.iter() {
+            bytes.extend(tx_id);
+            bytes.extend(index.to_le_bytes());
+            bytes.extend(tx_out.as_bytes());
+        }
+
+        bytes.extend(&self.last_block_hash);
+        bytes.push(self.reserved as u8);
+        bytes
+    }
+
+    /// Parses an address serialized with `as_bytes`.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream` - The content of the transaction cache, positioned at the start of the address.
+    ///
+    /// # Returns
+    ///
+    /// A Result containing the address, or a `MessageError` if it can't be parsed.
+    pub fn from_bytes(stream: &mut dyn Read) -> Result<WalletAddress, MessageError> {
+        let public_key_len = read_u8_from_bytes(stream)?;
+        let public_key = read_vec_from_bytes(stream, public_key_len as usize)?;
+        let pk_script_len = read_u8_from_bytes(stream)?;
+        let pk_script = match pk_script_len {
+            0 => None,
+            len => Some(read_vec_from_bytes(stream, len as usize)?),
+        };
+
+        let confirmed_txs_send = read_wallet_txs(stream)?;
+        let confirmed_txs_recv = read_wallet_txs(stream)?;
+        let unconfirmed_txs_send = read_wallet_txs(stream)?;
+        let unconfirmed_txs_recv = read_wallet_txs(stream)?;
+
+        let utxo_count = read_u32_from_bytes(stream, true)?;
+        let mut utxo = vec![];
+        for _ in 0..utxo_count {
+            let tx_id = read_vec_from_bytes(stream, 32)?;
+            let index = read_u32_from_bytes(stream, true)?;
+            utxo.push((tx_id, index, TxOut::from_bytes(stream)?));
+        }
+        let last_block_hash = read_vec_from_bytes(stream, 32)?;
+        let reserved = read_u8_from_bytes(stream)? != 0;
+
+        Ok(WalletAddress {
+            public_key,
+            pk_script,
+            confirmed_txs_send,
+            confirmed_txs_recv,
+            unconfirmed_txs_send,
+            unconfirmed_txs_recv,
+            utxo,
+            last_block_hash,
+            reserved,
+        })
+    }
+}
+
+/// Reads a list of transactions preceded by their count.
+fn read_wallet_txs(stream: &mut dyn Read) -> Result<Vec<WalletTx>, MessageError> {
+    let count = read_u32_from_bytes(stream, true)?;
+    (0..count).map(|_| WalletTx::from_bytes(stream)).collect()
+}
+
+#[cfg(test)]
+mod wallet_address_test {
+    use super::*;
+
+    #[test]
+    fn test_reserved_address_from_bytes() -> Result<(), MessageError> {
+        let mut address = WalletAddress::new(vec![2; 33]);
+        address.reserve();
+        address.add_utxo(vec![1; 32], 1, TxOut::new(1000, vec![0x51]));
+
+        let bytes = address.as_bytes();
+        let parsed = WalletAddress::from_bytes(&mut bytes.as_slice())?;
+        assert!(parsed.is_used());
+        assert_eq!(parsed.as_bytes(), bytes);
+
+        let unused = WalletAddress::from_pk_script(vec![0x51]);
+        let parsed = WalletAddress::from_bytes(&mut unused.as_bytes().as_slice())?;
+        assert!(!parsed.is_used());
+        assert_eq!(parsed.get_pk_script(), vec![0x51]);
+        Ok(())
+    }
+}
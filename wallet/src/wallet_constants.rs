@@ -1,2 +1,4 @@
 pub const NODE_IP: [u8; 4] = [127, 0, 0, 1];
 pub const NODE_PORT: u16 = 8002;
+pub const EVENT_POLL_INTERVAL: u64 = 1;
+pub const FALLBACK_SYNC_INTERVAL: u64 = 60;